@@ -48,7 +48,7 @@ impl fmt::Display for CliError {
       CliError::CompilationError(error) => {
         match error {
           CompilerError::UnsupportedStrategy => {
-            write!(f, "No built-in support for requested compiler.
+            write!(f, "No built-in support for requested compiler (supported are solc, solcjs and vyper).
 To use this compiler, please specify necessary OPTIONS in compile command. E.g:
 
   vibranium compile --compiler solcjs -- <OPTIONS>...
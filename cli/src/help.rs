@@ -0,0 +1,631 @@
+use std::collections::HashMap;
+
+pub struct Example {
+  pub description: &'static str,
+  pub command: &'static str,
+}
+
+pub struct CommandHelp {
+  pub name: &'static str,
+  pub examples: &'static [Example],
+  pub related: &'static [&'static str],
+}
+
+pub struct HelpTopic {
+  pub name: &'static str,
+  pub about: &'static str,
+  pub content: &'static str,
+}
+
+pub const COMMANDS: &[CommandHelp] = &[
+  CommandHelp {
+    name: "node",
+    examples: &[
+      Example { description: "Start a node using the client configured in vibranium.toml", command: "vibranium node" },
+      Example { description: "Start a ganache node", command: "vibranium node --client ganache-cli" },
+      Example { description: "Pass custom options down to the client", command: "vibranium node --client geth -- --dev --rpc" },
+      Example { description: "Start a node with its own chain next to the development one", command: "vibranium node --client ganache-cli --network ganache" },
+      Example { description: "Start a node in the background, picking free ports if needed", command: "vibranium node start --client ganache-cli" },
+      Example { description: "Show the last 100 lines of output of the node running in the background", command: "vibranium node logs --lines 100" },
+      Example { description: "Shut down the node running in the background", command: "vibranium node stop" },
+      Example { description: "Check whether a node is running in the background, as JSON", command: "vibranium node status --format json" },
+    ],
+    related: &["accounts", "chain", "deploy"],
+  },
+  CommandHelp {
+    name: "daemon",
+    examples: &[
+      Example { description: "Serve compile and deploy requests of the project in the foreground", command: "vibranium daemon" },
+      Example { description: "Start the daemon in the background", command: "vibranium daemon start" },
+      Example { description: "Check whether the daemon is running, as JSON", command: "vibranium daemon status --format json" },
+      Example { description: "Shut down the daemon running in the background", command: "vibranium daemon stop" },
+    ],
+    related: &["compile", "deploy", "node"],
+  },
+  CommandHelp {
+    name: "lsp",
+    examples: &[
+      Example { description: "Serve the project in the current directory to an editor", command: "vibranium lsp" },
+      Example { description: "Serve a given project, logging to stderr", command: "vibranium lsp --path ./my-dapp --verbose" },
+    ],
+    related: &["compile", "daemon"],
+  },
+  CommandHelp {
+    name: "chain",
+    examples: &[
+      Example { description: "Start over with a fresh development chain", command: "vibranium chain reset development" },
+      Example { description: "Take a snapshot of the development chain", command: "vibranium chain snapshot" },
+      Example { description: "Go back to the state of a snapshot", command: "vibranium chain revert 0x1" },
+    ],
+    related: &["node", "reset"],
+  },
+  CommandHelp {
+    name: "init",
+    examples: &[
+      Example { description: "Initialize a project in the current directory", command: "vibranium init" },
+      Example { description: "Initialize a project in a given directory", command: "vibranium init --path ./my-dapp" },
+      Example { description: "Initialize a project along with a minimal frontend", command: "vibranium init --with-frontend" },
+      Example { description: "Initialize a project and install its JavaScript dependencies with yarn", command: "vibranium init --install --package-manager yarn" },
+    ],
+    related: &["reset", "config"],
+  },
+  CommandHelp {
+    name: "reset",
+    examples: &[
+      Example { description: "Reset the project and keep its vibranium.toml", command: "vibranium reset" },
+      Example { description: "Reset the project including its vibranium.toml", command: "vibranium reset --restore-config" },
+      Example { description: "Only remove tracked deployment data", command: "vibranium reset --tracking-data" },
+    ],
+    related: &["init", "list"],
+  },
+  CommandHelp {
+    name: "config",
+    examples: &[
+      Example { description: "Use solcjs as compiler", command: "vibranium config compiler.cmd solcjs" },
+      Example { description: "Set a multi value option", command: "vibranium config sources.smart_contracts \"[contracts/*.sol, lib/*.sol]\"" },
+      Example { description: "Remove a configuration option", command: "vibranium config --unset blockchain.options" },
+      Example { description: "Show how vibranium.toml would change without writing it", command: "vibranium config compiler.cmd solcjs --dry-run" },
+      Example { description: "Save the current vibranium.toml to .vibranium/backups before changing it", command: "vibranium config --unset blockchain.options --backup" },
+    ],
+    related: &["init", "reset"],
+  },
+  CommandHelp {
+    name: "compile",
+    examples: &[
+      Example { description: "Compile using the compiler configured in vibranium.toml", command: "vibranium compile" },
+      Example { description: "Compile with solcjs", command: "vibranium compile --compiler solcjs" },
+      Example { description: "Compile with solc 0.8.24, downloading it if needed", command: "vibranium config compiler.version 0.8.24" },
+      Example { description: "Pick the solc version of each source from its pragma", command: "vibranium config compiler.version auto" },
+      Example { description: "Compile Vyper sources next to Solidity ones", command: "vibranium config sources.smart_contracts \"[contracts/*.sol, contracts/*.vy]\"" },
+      Example { description: "Pass custom options down to the compiler", command: "vibranium compile -- --optimize" },
+      Example { description: "Use only the given compiler options, ignoring configured ones", command: "vibranium compile --replace-options -- --abi --bin" },
+      Example { description: "Recompile all Smart Contracts, ignoring the build cache", command: "vibranium compile --force" },
+      Example { description: "Recompile whenever Smart Contract sources change", command: "vibranium compile --watch" },
+      Example { description: "Print the compiler output and warnings as JSON, e.g. for CI", command: "vibranium compile --format json" },
+      Example { description: "Only compile Smart Contracts tagged with `tags = [\"core\"]`", command: "vibranium compile --tag core" },
+      Example { description: "Fail if compiling twice, once from a temporary copy of the project, yields different artifacts", command: "vibranium compile --assert-deterministic --isolated" },
+      Example { description: "Compile in this process even though a daemon is running", command: "vibranium compile --no-daemon" },
+      Example { description: "List the functions that contribute most to Smart Contracts exceeding their size budget", command: "vibranium compile -- --combined-json abi,bin,bin-runtime,srcmap-runtime" },
+    ],
+    related: &["deploy", "config"],
+  },
+  CommandHelp {
+    name: "accounts",
+    examples: &[
+      Example { description: "List the accounts of the connected node", command: "vibranium accounts" },
+    ],
+    related: &["node"],
+  },
+  CommandHelp {
+    name: "deploy",
+    examples: &[
+      Example { description: "Deploy all configured Smart Contracts", command: "vibranium deploy" },
+      Example { description: "Deploy without tracking the deployed Smart Contracts", command: "vibranium deploy --no-tracking" },
+      Example { description: "Make sure nothing gets deployed, e.g. when using a production config", command: "vibranium deploy --read-only" },
+      Example { description: "Recompile and redeploy to the development chain whenever sources change", command: "vibranium deploy --watch" },
+      Example { description: "Run the project's tests first and only deploy to a public network if they pass", command: "vibranium deploy --network mainnet --require-tests" },
+      Example { description: "Print gas estimates of the deployed Smart Contracts and their functions", command: "vibranium deploy --report-gas" },
+      Example { description: "Deploy as many Smart Contracts as possible and report the ones that failed", command: "vibranium deploy --continue-on-error" },
+      Example { description: "Deploy the mocks of a dev chain along with what they depend on", command: "vibranium deploy --tag mocks" },
+      Example { description: "Deploy in this process even though a daemon is running", command: "vibranium deploy --no-daemon" },
+      Example { description: "Redeploy to the development chain as it was before the previous reset", command: "vibranium deploy --reset" },
+    ],
+    related: &["compile", "gas", "list"],
+  },
+  CommandHelp {
+    name: "upgrade",
+    examples: &[
+      Example { description: "Deploy a new implementation of `Token` and point its proxy at it", command: "vibranium upgrade Token" },
+      Example { description: "Upgrade without comparing storage layouts", command: "vibranium upgrade Token --unsafe-skip-storage-check" },
+    ],
+    related: &["deploy", "deployments"],
+  },
+  CommandHelp {
+    name: "gas",
+    examples: &[
+      Example { description: "Estimate gas of deploying all configured Smart Contracts", command: "vibranium gas" },
+      Example { description: "Compare estimates with the gas limits of a configured network", command: "vibranium gas --network staging" },
+    ],
+    related: &["deploy", "compile"],
+  },
+  CommandHelp {
+    name: "test",
+    examples: &[
+      Example { description: "Run all Solidity tests of the project", command: "vibranium test" },
+      Example { description: "Only run the tests of a single test contract", command: "vibranium test --filter CounterTest." },
+      Example { description: "Run the tests and report their results as JSON", command: "vibranium test --format json" },
+    ],
+    related: &["compile", "node", "deploy"],
+  },
+  CommandHelp {
+    name: "list",
+    examples: &[
+      Example { description: "List tracked Smart Contracts of the connected chain", command: "vibranium list" },
+    ],
+    related: &["deploy", "reset"],
+  },
+  CommandHelp {
+    name: "deployments",
+    examples: &[
+      Example { description: "Tag all tracked deployments of a Smart Contract", command: "vibranium deployments tag Token release=v2.1 audited-by=firmX" },
+      Example { description: "List tracked Smart Contracts with the given tag", command: "vibranium deployments list --tag release=v2.1" },
+      Example { description: "List tracked Smart Contracts of all chains, grouped by network", command: "vibranium deployments list --all" },
+      Example { description: "Show the transaction, deployer and compiler version of a deployment", command: "vibranium deployments show Token" },
+      Example { description: "Stop tracking the deployments of a Smart Contract on the connected chain", command: "vibranium deployments remove Token" },
+      Example { description: "Remove all tracked deployments made to a network, e.g. after a testnet reset", command: "vibranium deployments clear --network staging" },
+      Example { description: "Print the constructor arguments a Smart Contract was deployed with", command: "vibranium deployments args Token" },
+      Example { description: "Rekey tracking data after changing `deployment.tracking_key`", command: "vibranium deployments rekey" },
+      Example { description: "Write Markdown documentation of all tracked deployments", command: "vibranium deployments docs --output DEPLOYMENTS.md" },
+      Example { description: "Export tracked deployments as a Safe address book", command: "vibranium deployments export --address-book safe-addressbook --output addressbook.csv" },
+      Example { description: "Show what still needs to be promoted from staging to production", command: "vibranium deployments diff --from staging --to production" },
+    ],
+    related: &["list", "deploy"],
+  },
+  CommandHelp {
+    name: "access-report",
+    examples: &[
+      Example { description: "List access restrictions of all compiled Smart Contracts", command: "vibranium access-report" },
+      Example { description: "List functions of a Smart Contract anyone can call", command: "vibranium access-report Token --unrestricted" },
+    ],
+    related: &["compile", "interface"],
+  },
+  CommandHelp {
+    name: "doctor",
+    examples: &[
+      Example { description: "Check whether the configured compiler and blockchain client are available", command: "vibranium doctor" },
+      Example { description: "Write a troubleshooting report to attach to bug reports", command: "vibranium doctor --report --output report.json" },
+    ],
+    related: &["compile", "node"],
+  },
+  CommandHelp {
+    name: "reproduce",
+    examples: &[
+      Example { description: "Check that the deployed code of a Smart Contract matches its sources", command: "vibranium reproduce Token" },
+    ],
+    related: &["compile", "deploy", "list", "verify"],
+  },
+  CommandHelp {
+    name: "children",
+    examples: &[
+      Example { description: "Track all pairs a factory announced via its PairCreated event", command: "vibranium children Factory --event PairCreated --arg pair" },
+      Example { description: "Pick up new children later on, tracked under a custom collection", command: "vibranium children Factory --event PairCreated --arg pair --collection Pair" },
+    ],
+    related: &["deployments", "verify"],
+  },
+  CommandHelp {
+    name: "events",
+    examples: &[
+      Example { description: "List all events a tracked Smart Contract has emitted", command: "vibranium events Token" },
+      Example { description: "List Transfer events of a range of blocks", command: "vibranium events Token --event Transfer --from-block 100 --to-block 200" },
+      Example { description: "List events of the last 100 blocks", command: "vibranium events Token --from-block -100" },
+      Example { description: "List Transfer events of any known Smart Contract, e.g. tokens deployed by others", command: "vibranium events --event Transfer --from-block -1000" },
+      Example { description: "List events emitted since a given date, up to the finalized block", command: "vibranium events Token --from-block 2019-06-01T00:00:00Z --to-block finalized" },
+      Example { description: "Keep listing events as new blocks come in", command: "vibranium events Token --follow" },
+    ],
+    related: &["children", "list", "tx"],
+  },
+  CommandHelp {
+    name: "tx",
+    examples: &[
+      Example { description: "Decode the call, events and revert reason of a transaction", command: "vibranium tx 0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060" },
+      Example { description: "Inspect a transaction on another network", command: "vibranium tx 0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060 --network staging" },
+    ],
+    related: &["events", "deployments"],
+  },
+  CommandHelp {
+    name: "verify",
+    examples: &[
+      Example { description: "Check that all tracked deployments still hold the compiled code", command: "vibranium verify" },
+      Example { description: "Only check the tracked deployments of a Smart Contract", command: "vibranium verify Token" },
+      Example { description: "Verify all Smart Contracts of the given groups", command: "vibranium verify --tag core --tag periphery" },
+      Example { description: "Publish the sources of a deployed Smart Contract on Etherscan", command: "vibranium verify Token --etherscan --network sepolia" },
+      Example { description: "Publish the sources of a deployed Smart Contract on Sourcify", command: "vibranium verify Token --sourcify --network sepolia" },
+    ],
+    related: &["deployments", "reproduce"],
+  },
+  CommandHelp {
+    name: "interface",
+    examples: &[
+      Example { description: "Print a Solidity interface of a compiled Smart Contract", command: "vibranium interface Token" },
+      Example { description: "Write the interface and its ABI to files", command: "vibranium interface Token --name IERC20 --output contracts/IERC20.sol --abi-output IERC20.abi" },
+    ],
+    related: &["compile"],
+  },
+  CommandHelp {
+    name: "check-interface",
+    examples: &[
+      Example { description: "Check a compiled Smart Contract against ERC-20", command: "vibranium check-interface Token --erc 20" },
+      Example { description: "Check a deployed Smart Contract against ERC-721, including supportsInterface", command: "vibranium check-interface 0x5FbDB2315678afecb367f032d93F642f64180aa3 --erc 721" },
+      Example { description: "Check against a custom interface given as ABI file", command: "vibranium check-interface Registry --erc IRegistry.abi" },
+    ],
+    related: &["interface", "compile"],
+  },
+  CommandHelp {
+    name: "history",
+    examples: &[
+      Example { description: "List previously executed commands of the project", command: "vibranium history list" },
+    ],
+    related: &["rerun"],
+  },
+  CommandHelp {
+    name: "stats",
+    examples: &[
+      Example { description: "Show compile times and gas spend of the last week", command: "vibranium stats --days 7" },
+    ],
+    related: &["history", "compile", "deploy"],
+  },
+  CommandHelp {
+    name: "rerun",
+    examples: &[
+      Example { description: "Execute the most recent command again", command: "vibranium rerun" },
+      Example { description: "Execute the third most recent command again", command: "vibranium rerun 3" },
+      Example { description: "Execute a command again although the configuration it resolves to has changed", command: "vibranium rerun 3 --force" },
+    ],
+    related: &["history"],
+  },
+  CommandHelp {
+    name: "package",
+    examples: &[
+      Example { description: "Bundle release v1.0.0", command: "vibranium package --release v1.0.0" },
+      Example { description: "Bundle a release into a given file", command: "vibranium package --release v1.0.0 --output ./release.tar" },
+    ],
+    related: &["compile", "deploy"],
+  },
+  CommandHelp {
+    name: "help",
+    examples: &[
+      Example { description: "Learn how deployment tracking works", command: "vibranium help tracking" },
+      Example { description: "Show help of a subcommand", command: "vibranium help deploy" },
+    ],
+    related: &[],
+  },
+];
+
+pub const TOPICS: &[HelpTopic] = &[
+  HelpTopic {
+    name: "networks",
+    about: "How Vibranium connects to a blockchain",
+    content: "Vibranium talks to a blockchain through the connector configured in the project's
+vibranium.toml file. Both HTTP (\"rpc\") and WebSocket (\"ws\") connections are supported:
+
+  [blockchain.connector]
+    protocol = \"rpc\"
+    host = \"localhost\"
+    port = \"8545\"
+
+Fallback endpoints can be listed as URLs. When an endpoint fails or rate-limits a request,
+the next one is tried. Transactions are only sent to the next endpoint if the failing one
+refused the connection or rate-limited them, so that they're never sent twice. With `rotation = \"round-robin\"` requests are spread across all
+endpoints instead of preferring them in the given order (\"priority\", the default):
+
+  [blockchain.connector]
+    ...
+    fallbacks = [\"https://rpc.example.org\", \"wss://ws.example.org\"]
+    rotation = \"priority\"
+
+API keys shouldn't be embedded in endpoint URLs. Reference them from the environment
+instead, e.g. `\"https://mainnet.infura.io/v3/${env.INFURA_KEY}\"`. Vibranium refuses to deploy
+to non-local networks while secrets are embedded in vibranium.toml.
+
+All JSON-RPC traffic of a command can be recorded into a cassette file with
+`--record-rpc <FILE>` and served from it later with `--replay-rpc <FILE>`, e.g. to run
+deployments deterministically in CI without a node.
+
+Results of contract calls (`eth_call`) are cached in `.vibranium/call-cache.json`. Calls
+against a specific block are cached for as long as the chain keeps its genesis block, calls
+against the latest block for 15 seconds, or `call_cache_ttl` seconds as configured in
+`[blockchain.connector]` (0 disables the cache). The cache keeps the latest 1000 calls.
+Deployments, upgrades and tests never use the cache, and resetting or reverting the chain
+clears it. Pass `--no-cache` to send every call to the node.
+
+Projects talking to multiple chains (e.g. a local node, testnets and mainnet) can name them
+in `[networks.<NAME>]` sections and select one with `--network <NAME>`. The network's URL
+(http, https, ws or wss, including any path, e.g. of a hosted provider) replaces the connector,
+while its gas defaults replace `deployment.gas_price` and `deployment.gas_limit`. The URLs of
+all networks are checked whenever vibranium.toml is read, not only the selected one. With a
+`chain_id`, deployments to any other chain are refused:
+
+  [networks.sepolia]
+    url = \"https://sepolia.infura.io/v3/${env.INFURA_KEY}\"
+    chain_id = 11155111
+    accounts = [\"0x00a329c0648769a73afac7f9381e08fb43dbea72\"]
+    gas_price = 20000000000
+
+  vibranium deploy --network sepolia
+
+Constructor arguments can reference `${constants.<NAME>}`. Constants of the selected network,
+`[networks.<NAME>.constants]`, replace those of `[constants]`, e.g. for addresses that differ
+between chains. Deployments don't start unless all referenced constants are defined.
+
+A local development node can be started with `vibranium node`. The client used for that
+is configured with `blockchain.cmd` and `blockchain.options`. Every network gets its own
+chain data in .vibranium/chains/<NAME>, which `vibranium chain reset <NAME>` wipes.
+",
+  },
+  HelpTopic {
+    name: "accounts",
+    about: "Which accounts are used to deploy Smart Contracts",
+    content: "Vibranium uses the accounts that are managed and unlocked by the connected node.
+Run `vibranium accounts` to list them. Deployments are sent from the first account, unless
+the selected `[networks.<NAME>]` lists `accounts`, in which case the first of those is used.
+
+Nodes started via `vibranium node` come with pre-funded development accounts.
+",
+  },
+  HelpTopic {
+    name: "aliases",
+    about: "How addresses are shown with human readable names",
+    content: "Addresses printed by `vibranium events`, `vibranium tx` and `vibranium children` are shown
+along with their names, e.g. `Token (0x1234…abcd)`, if they're known. Names are looked up, in
+this order, in:
+
+  [networks.<NAME>.aliases]  Aliases of the selected network
+  [aliases]                  Aliases of all networks
+  tracking data              Smart Contracts deployed to the connected chain
+  address book               Tracked deployments to chains with the network's `chain_id`
+
+Aliases name accounts and Smart Contracts that haven't been deployed via Vibranium:
+
+  [aliases]
+    deployer = \"0x00a329c0648769a73afac7f9381e08fb43dbea72\"
+
+  [networks.mainnet.aliases]
+    treasury = \"0x8ba1f109551bd432803012645ac136ddd64dba72\"
+
+Pass `--no-aliases` to print bare addresses. JSON output always contains bare addresses.
+",
+  },
+  HelpTopic {
+    name: "tracking",
+    about: "How deployed Smart Contracts are tracked",
+    content: "Every Smart Contract deployed via `vibranium deploy` is recorded in .vibranium/tracking.toml,
+grouped by the hash of the chain's first block and labeled with the selected `--network`.
+A Smart Contract that has been deployed before with the same name, bytecode and constructor
+arguments is skipped on subsequent deployments.
+
+  vibranium list                   Lists tracked Smart Contracts of the connected chain
+  vibranium deployments tag        Attaches KEY=VALUE metadata to tracked Smart Contracts
+  vibranium deploy --no-tracking   Deploys without reading or writing tracking data
+  vibranium reset --tracking-data  Removes all tracking data
+
+Before a deployment transaction is sent, it's recorded in .vibranium/journal. If a deployment
+doesn't finish (e.g. it crashed), the next one checks whether the journaled transactions landed
+and tracks those Smart Contracts instead of deploying them again. Transactions that are still
+pending are waited for, and replaced with a higher gas price if `deployment.fee_bumping` is set.
+
+Which deployments count as the same Smart Contract is configured with `deployment.tracking_key`:
+
+  content-hash   Name, bytecode and constructor arguments (default)
+  name           Name only, e.g. to keep tracking a Smart Contract across code changes
+  name-and-args  Name and constructor arguments
+
+After changing it, `vibranium deployments rekey` moves existing tracking data to the new keys.
+Deployments only move if their previous key, their tracked constructor arguments or the code
+deployed at their address show that they match the current bytecode and arguments. All others,
+including older deployments ending up with the same key as a newer one, keep their key.
+
+Where tracking data is stored is configured with `deployment.store`:
+
+  toml    A single .vibranium/tracking.toml (default)
+  json    A file per network in .vibranium/tracking, e.g. to keep diffs of large projects readable
+  sqlite  An embedded database in .vibranium/tracking.sqlite, if built with `--features sqlite`
+
+Existing tracking data isn't moved when switching stores.
+
+tracking.toml records the `schema_version` it has been written with. Files written by older
+versions of vibranium are upgraded with the next deployment, keeping the original as
+tracking.toml.v<VERSION>.bak.
+
+Tracking can be turned off for a project by setting `deployment.tracking_enabled = false`.
+",
+  },
+  HelpTopic {
+    name: "upgrades",
+    about: "How upgradeable Smart Contracts are deployed behind proxies",
+    content: "A Smart Contract configured with a `proxy` is deployed as an implementation, followed by a proxy
+delegating to it. The proxy is compiled from the project's sources like any other Smart Contract:
+
+  [[deployment.smart_contracts]]
+    name = \"Token\"
+    proxy = { contract = \"TokenProxy\", kind = \"transparent\", initializer = \"initialize\", initializer_args = [{ value = \"$Owner\", kind = \"address\" }] }
+
+  erc1967      Constructed with (implementation, data), upgraded via `upgradeToAndCall` (default)
+  transparent  Constructed with (implementation, admin, data), upgraded via its ProxyAdmin
+
+The proxy is tracked as `Token` and references to `$Token` resolve to it. Implementations are
+tracked as `Token:implementation`, with the `implementation` and `proxied-by` metadata linking both.
+
+`vibranium upgrade Token` deploys the current implementation and points the proxy at it. It's
+refused if the storage layout of the new implementation moves, removes or retypes variables of the
+current one. Storage layouts are read from the artifacts directory, so `--storage-layout` has to
+be passed to solc, e.g. via `compiler.options`.
+",
+  },
+  HelpTopic {
+    name: "editors",
+    about: "How editors get diagnostics, definitions and hover info from Vibranium",
+    content: "`vibranium lsp` is a language server speaking the Language Server Protocol over stdio. Configure
+your editor to start it for Solidity files, in the project directory. It serves:
+
+  diagnostics  Static checks (see `[compiler.checks]`) while editing, compiler errors and warnings once
+               a source is saved. Saving compiles the project like `vibranium compile` does.
+  definitions  Contracts, libraries, interfaces, structs, enums, events, errors, functions and modifiers
+               declared in the source or anything it imports, and imported sources themselves
+  hover        Bytecode size of compiled Smart Contracts, their size budget (`compiler.max_size`) and
+               the gas used by their latest tracked deployment
+",
+  },
+  HelpTopic {
+    name: "testing",
+    about: "How Solidity tests are written and run",
+    content: "`vibranium test` compiles the Solidity sources in `test/` and runs every public function starting
+with `test` that doesn't take arguments. Each test runs against a fresh instance of its contract,
+deployed from the node's first account, after `setUp()` has been called if the contract has one:
+
+  import \"../contracts/Counter.sol\";
+
+  contract CounterTest {
+    Counter counter;
+
+    function setUp() public {
+      counter = new Counter();
+    }
+
+    function testIncrement() public {
+      counter.increment();
+      require(counter.count() == 1, \"count should be 1\");
+    }
+
+    function testFailDecrementBelowZero() public {
+      counter.decrement();
+    }
+  }
+
+A test passes if its transaction succeeds. Tests named `testFail...` pass if it reverts instead.
+Fixtures are deployed by the test contracts themselves, in their constructor or `setUp()`. The gas
+used by each test's transaction is reported along with its result.
+
+Tests only run against development networks. If the configured node runs on localhost but isn't
+reachable, a managed node is started for the run and stopped afterwards (see `vibranium node start`).
+On nodes that support snapshots, e.g. ganache and anvil, the chain is reverted to its state before
+the run once all tests are done, so repeated runs don't accumulate state (see `vibranium chain`).
+Test artifacts are written to `.vibranium/test-artifacts`, next to, not into, the project's artifacts.
+Rust test scripts aren't supported.
+
+With `deployment.require_tests = true` (or `--require-tests`), deploying to a non-development network
+runs the tests on the development network first and refuses to deploy if any of them fail. Set
+`deployment.require_tests_filter` to only require the tests matching it, like `vibranium test --filter`.
+Projects with JavaScript tests can set `deployment.test_cmd` and `deployment.test_options` to run
+those instead.
+",
+  },
+  HelpTopic {
+    name: "daemon",
+    about: "How compile and deploy requests are served by a long-running process",
+    content: "`vibranium daemon start` runs a daemon for the project in the background. While it's running,
+`vibranium compile` and `vibranium deploy` send their requests to it over `.vibranium/daemon.sock`
+instead of doing the work themselves. The daemon keeps a warm instance per profile and network,
+along with the parsed vibranium.toml, which is only parsed again once it changes. Its output
+goes to `.vibranium/daemon.log`.
+
+Compilers are still run per request, as neither solc nor solcjs can compile more than once per
+process. Requests are served one at a time. `${env.NAME}` references in vibranium.toml, as well as
+the tests required by `deployment.require_tests`, see the environment of the command that sent the
+request. Compilers started by the daemon keep the environment it has been started in.
+
+Commands recording or replaying RPC cassettes, or passing `--no-cache`, always run in-process, as
+does any command passed `--no-daemon`. The daemon is only supported on Unix platforms.
+",
+  },
+  HelpTopic {
+    name: "verification",
+    about: "How sources of deployed Smart Contracts are published on block explorers",
+    content: "`vibranium verify <CONTRACT> --etherscan` (or `--sourcify`) submits the sources of the latest tracked
+deployment of a Smart Contract, along with its constructor arguments, and waits until the service
+verified them. Sources are the project's Solidity sources and everything they import, compiled with
+the configured compiler options (`--optimize`, `--optimize-runs`, `--evm-version` and `--via-ir`).
+
+  [verification]
+    etherscan_api_key = \"${env.ETHERSCAN_API_KEY}\"
+    etherscan_url = \"https://api.etherscan.io/v2/api\"
+    sourcify_url = \"https://sourcify.dev/server\"
+    compiler_version = \"0.8.24+commit.e11b9ed9\"
+    poll_interval = 5
+    timeout = 300
+
+Only the API key is required. The compiler version is asked for from the compiler the deployment
+has been built with, unless configured. The chain is the one of the selected `--network`.
+",
+  },
+  HelpTopic {
+    name: "profiles",
+    about: "How to switch between configurations without editing vibranium.toml",
+    content: "Options in a `[profile.<NAME>]` section of vibranium.toml take precedence over the ones
+at the top level when the profile is selected with `--profile <NAME>` or the
+VIBRANIUM_PROFILE environment variable. Tables are merged, all other values are replaced:
+
+  [profile.release.compiler]
+    options = [\"--abi\", \"--bin\", \"--optimize\", \"--optimize-runs\", \"1000000\"]
+
+  [profile.release.deployment]
+    gas_price = 20000000000
+    tx_confirmations = 12
+
+  vibranium compile --profile release
+",
+  },
+  HelpTopic {
+    name: "policy",
+    about: "How deployments are checked against an organization's deployment policy",
+    content: "A deployment policy is a separate TOML file referenced from vibranium.toml. `vibranium deploy`
+refuses to deploy when one of its rules is violated and lists the result of every rule
+in the \"Policy compliance\" section of its summary:
+
+  [deployment]
+    policy = \"deployment-policy.toml\"
+
+  # deployment-policy.toml
+  max_gas_price = 20000000000
+  allowed_networks = [\"localhost\", \"sepolia\"]
+  required_confirmations = 12
+  forbidden_opcodes = [\"SELFDESTRUCT\", \"DELEGATECALL\"]
+  require_verified = true
+
+Networks are matched against the name of the selected `--network`, or the host of the
+blockchain connector when deploying without one. Forbidden opcodes are looked up in the
+bytecode of every Smart Contract that is about to be deployed. With `require_verified`, the
+latest tracked deployment of every Smart Contract on the chain has to have passed
+`vibranium verify`.
+",
+  },
+];
+
+pub fn find_topic(name: &str) -> Option<&'static HelpTopic> {
+  TOPICS.iter().find(|topic| topic.name == name)
+}
+
+pub fn render_topics() -> String {
+  let width = TOPICS.iter().map(|topic| topic.name.len()).max().unwrap_or(0);
+  let mut text = "HELP TOPICS:\n".to_string();
+  for topic in TOPICS {
+    text.push_str(&format!("    {:width$}    {}\n", topic.name, topic.about, width = width));
+  }
+  text.push_str("\nRun `vibranium help <TOPIC>` to read about a topic, or `vibranium help <SUBCOMMAND>` for help on a subcommand.");
+  text
+}
+
+pub fn render_after_help(command: &CommandHelp) -> String {
+  let mut text = "EXAMPLES:\n".to_string();
+  for example in command.examples {
+    text.push_str(&format!("    {}\n      $ {}\n\n", example.description, example.command));
+  }
+
+  if !command.related.is_empty() {
+    text.push_str(&format!("RELATED COMMANDS:\n    {}\n", command.related.join(", ")));
+  }
+  text.trim_end().to_string()
+}
+
+pub fn render_all() -> HashMap<&'static str, String> {
+  COMMANDS.iter().map(|command| (command.name, render_after_help(command))).collect()
+}
@@ -4,40 +4,119 @@ extern crate log;
 extern crate env_logger;
 extern crate vibranium;
 extern crate toml;
+#[macro_use]
+extern crate serde_json;
 
 use std::env;
 use log::LevelFilter;
 use std::process;
 use std::path::PathBuf;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-use clap::{App, SubCommand, Arg};
+use clap::{App, AppSettings, SubCommand, Arg};
 
 use vibranium::Vibranium;
 use vibranium::blockchain;
+use vibranium::config;
 use vibranium::deployment;
+use vibranium::doctor;
 use vibranium::deployment::DeployOptions;
+use vibranium::deployment::aliases::AddressAliases;
+use vibranium::deployment::upgrades::UpgradeOptions;
+use vibranium::compiler;
 use vibranium::compiler::CompilerConfig;
+use vibranium::daemon::{DaemonClient, DaemonState};
+use vibranium::access_report::{AccessEntry, AccessReportOptions};
+use vibranium::interface::InterfaceOptions;
+use vibranium::package::PackageOptions;
 use vibranium::project_generator::ResetOptions;
+use vibranium::reproduce::VerificationStatus;
+use vibranium::stats;
+use vibranium::test_runner::TestOptions;
+use vibranium::verification::{SourceVerificationStatus, VerificationService};
 
 mod error;
+mod help;
+mod output;
 
 type Error = Box<std::error::Error>;
 
+const MAX_PRINTED_BYTECODE_DIFFS: usize = 20;
+const CONFIG_DIFF_CONTEXT: usize = 2;
+
 fn main() {
-  if let Err(e) = run() {
-    eprintln!("Aborted due to error:\n");
-    eprintln!("{}", e);
+  let args: Vec<String> = env::args().collect();
+  let format = output::format_from_args(&args);
+  if let Err(e) = run(args) {
+    match format {
+      output::OutputFormat::Json => output::print_error(e.as_ref()),
+      output::OutputFormat::Text => {
+        eprintln!("Aborted due to error:\n");
+        eprintln!("{}", e);
+      },
+    }
     process::exit(1);
   }
 }
 
-fn run() -> Result<(), Error> {
+fn run(args: Vec<String>) -> Result<(), Error> {
+  let help_texts = help::render_all();
+  let topics = help::render_topics();
+
   let mut app = App::new("Vibranium CLI")
                   .version(crate_version!())
                   .author(crate_authors!())
                   .about("Building DApps made easy")
-                  .subcommand(SubCommand::with_name("node")
+                  .setting(AppSettings::DisableHelpSubcommand)
+                  .after_help(topics.as_str())
+                  .arg(Arg::with_name("read-only")
+                    .long("read-only")
+                    .global(true)
+                    .help("Fails on any command that would send transactions or write tracking data"))
+                  .arg(Arg::with_name("profile")
+                    .long("profile")
+                    .value_name("PROFILE")
+                    .global(true)
+                    .takes_value(true)
+                    .env(config::PROFILE_ENV_VARIABLE)
+                    .help("Applies the options of the given [profile.<PROFILE>] section of vibranium.toml"))
+                  .arg(Arg::with_name("network")
+                    .long("network")
+                    .value_name("NETWORK")
+                    .global(true)
+                    .takes_value(true)
+                    .help("Connects to the given [networks.<NETWORK>] of vibranium.toml, or selects the chain data of nodes (defaults to development)"))
+                  .arg(Arg::with_name("format")
+                    .long("format")
+                    .value_name("FORMAT")
+                    .global(true)
+                    .takes_value(true)
+                    .possible_values(&["text", "json"])
+                    .help("Prints results as human readable text (default) or as structured JSON. Not supported by commands that don't report results, e.g. init, config or rerun, and with --watch or --follow"))
+                  .arg(Arg::with_name("record-rpc")
+                    .long("record-rpc")
+                    .value_name("CASSETTE_FILE")
+                    .global(true)
+                    .takes_value(true)
+                    .conflicts_with("replay-rpc")
+                    .help("Records all JSON-RPC requests and responses into the given cassette file"))
+                  .arg(Arg::with_name("replay-rpc")
+                    .long("replay-rpc")
+                    .value_name("CASSETTE_FILE")
+                    .global(true)
+                    .takes_value(true)
+                    .help("Serves JSON-RPC responses from the given cassette file instead of connecting to a node"))
+                  .arg(Arg::with_name("no-cache")
+                    .long("no-cache")
+                    .global(true)
+                    .help("Sends all contract calls to the node instead of serving recent results from the call cache"))
+                  .arg(Arg::with_name("no-aliases")
+                    .long("no-aliases")
+                    .global(true)
+                    .help("Prints bare addresses instead of the names of tracked Smart Contracts and configured aliases"))
+                  .subcommand(subcommand("node", &help_texts)
                     .about("Controls blockchain node")
                     .arg(Arg::with_name("client")
                       .short("c")
@@ -60,8 +139,153 @@ fn run() -> Result<(), Error> {
                       .short("v")
                       .long("verbose")
                       .help("Generates verbose output"))
+                    .subcommand(SubCommand::with_name("start")
+                      .about("Starts a blockchain node in the background")
+                      .arg(Arg::with_name("client")
+                        .short("c")
+                        .long("client")
+                        .value_name("CLIENT_BINARY")
+                        .help("Specifies client used to start local Ethereum node")
+                        .takes_value(true))
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project from which to spin up a node")
+                        .takes_value(true))
+                      .arg(Arg::with_name("client-opts")
+                        .value_name("OPTIONS")
+                        .help("Specifies node specific options that will be passed down to the client")
+                        .multiple(true)
+                        .raw(true))
+                    )
+                    .subcommand(SubCommand::with_name("stop")
+                      .about("Shuts down the node started in the background")
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("status")
+                      .about("Shows whether a node is running in the background")
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("logs")
+                      .about("Prints the output of the node started in the background")
+                      .arg(Arg::with_name("lines")
+                        .short("n")
+                        .long("lines")
+                        .value_name("LINES")
+                        .help("Specifies the number of most recent lines to print (defaults to 50)")
+                        .takes_value(true))
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                  )
+                  .subcommand(subcommand("daemon", &help_texts)
+                    .about("Serves compile and deploy requests from a long-running process")
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project to serve")
+                      .takes_value(true))
+                    .arg(Arg::with_name("verbose")
+                      .short("v")
+                      .long("verbose")
+                      .help("Generates verbose output"))
+                    .subcommand(SubCommand::with_name("start")
+                      .about("Starts the daemon in the background")
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("stop")
+                      .about("Shuts down the daemon running in the background")
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("status")
+                      .about("Shows whether a daemon is running in the background")
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                  )
+                  .subcommand(subcommand("lsp", &help_texts)
+                    .about("Runs a language server for editors, speaking LSP over stdio")
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                    .arg(Arg::with_name("verbose")
+                      .short("v")
+                      .long("verbose")
+                      .help("Generates verbose output on stderr"))
                   )
-                  .subcommand(SubCommand::with_name("init")
+                  .subcommand(subcommand("chain", &help_texts)
+                    .about("Manages chain data of local nodes")
+                    .setting(AppSettings::SubcommandRequiredElseHelp)
+                    .subcommand(SubCommand::with_name("reset")
+                      .about("Wipes the chain data of a network along with its tracked deployments")
+                      .arg(Arg::with_name("name")
+                        .value_name("NETWORK")
+                        .help("Specifies the network whose chain data to wipe")
+                        .required(true))
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("snapshot")
+                      .about("Takes a snapshot of the state of the development chain")
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("revert")
+                      .about("Reverts the development chain to a snapshot, discarding the snapshot and every later one")
+                      .arg(Arg::with_name("id")
+                        .value_name("ID")
+                        .help("Specifies the snapshot to revert to, as printed by `vibranium chain snapshot`")
+                        .required(true))
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                  )
+                  .subcommand(subcommand("init", &help_texts)
                     .about("Initializes a Vibranium project inside the current directory, or a given path")
                     .arg(Arg::with_name("path")
                       .short("p")
@@ -69,12 +293,23 @@ fn run() -> Result<(), Error> {
                       .value_name("PATH")
                       .help("Specifies path to directory in which to initialize Vibranium project")
                       .takes_value(true))
+                    .arg(Arg::with_name("with-frontend")
+                      .long("with-frontend")
+                      .help("Scaffolds a minimal frontend that reads compiled and deployed Smart Contracts"))
+                    .arg(Arg::with_name("install")
+                      .long("install")
+                      .help("Installs JavaScript dependencies of the project, e.g. of solcjs, ganache-cli or the frontend"))
+                    .arg(Arg::with_name("package-manager")
+                      .long("package-manager")
+                      .value_name("PACKAGE_MANAGER")
+                      .help("Specifies package manager to install JavaScript dependencies with (npm, yarn or pnpm). Detected if omitted")
+                      .takes_value(true))
                     .arg(Arg::with_name("verbose")
                       .short("v")
                       .long("verbose")
                       .help("Generates verbose output"))
                   )
-                  .subcommand(SubCommand::with_name("reset")
+                  .subcommand(subcommand("reset", &help_texts)
                     .about("Resets Vibranium project inside the current directory, or a given path")
                     .arg(Arg::with_name("path")
                       .short("p")
@@ -95,7 +330,7 @@ fn run() -> Result<(), Error> {
                       .long("verbose")
                       .help("Generates verbose output"))
                   )
-                  .subcommand(SubCommand::with_name("config")
+                  .subcommand(subcommand("config", &help_texts)
                     .about("Reads and writes configuration options of a Vibranium project")
                     .arg(Arg::with_name("path")
                       .short("p")
@@ -114,12 +349,19 @@ fn run() -> Result<(), Error> {
                       .value_name("CONFIG_OPTION")
                       .help("Unsets a configuration value")
                       .takes_value(true))
+                    .arg(Arg::with_name("dry-run")
+                      .long("dry-run")
+                      .help("Prints a diff of the resulting vibranium.toml without writing it"))
+                    .arg(Arg::with_name("backup")
+                      .long("backup")
+                      .conflicts_with("dry-run")
+                      .help("Saves the current vibranium.toml to .vibranium/backups before writing it"))
                     .arg(Arg::with_name("verbose")
                       .short("v")
                       .long("verbose")
                       .help("Generates verbose output"))
                   )
-                  .subcommand(SubCommand::with_name("compile")
+                  .subcommand(subcommand("compile", &help_texts)
                     .about("Compiles Smart Contracts from Vibranium project")
                     .arg(Arg::with_name("compiler")
                       .short("c")
@@ -135,15 +377,45 @@ fn run() -> Result<(), Error> {
                       .takes_value(true))
                     .arg(Arg::with_name("compiler-opts")
                       .value_name("OPTIONS")
-                      .help("Specifies compiler specific options that will be passed down to the compiler")
+                      .help("Specifies compiler specific options that will be merged into the configured options")
                       .multiple(true)
                       .raw(true))
+                    .arg(Arg::with_name("replace-options")
+                      .long("replace-options")
+                      .requires("compiler-opts")
+                      .help("Uses the given compiler options instead of merging them into the configured options"))
+                    .arg(Arg::with_name("force")
+                      .short("f")
+                      .long("force")
+                      .help("Recompiles all Smart Contracts, even if their sources haven't changed since the last build"))
+                    .arg(Arg::with_name("assert-deterministic")
+                      .long("assert-deterministic")
+                      .conflicts_with("watch")
+                      .help("Compiles twice and fails if the artifacts of both builds differ"))
+                    .arg(Arg::with_name("isolated")
+                      .long("isolated")
+                      .requires("assert-deterministic")
+                      .help("Compiles the second time from a copy of the project at a temporary path, revealing absolute paths in artifacts"))
+                    .arg(Arg::with_name("watch")
+                      .short("w")
+                      .long("watch")
+                      .help("Recompiles whenever Smart Contract sources change"))
+                    .arg(Arg::with_name("no-daemon")
+                      .long("no-daemon")
+                      .help("Runs in this process even if a daemon is running for the project"))
+                    .arg(Arg::with_name("tag")
+                      .long("tag")
+                      .value_name("TAG")
+                      .help("Only compiles sources of Smart Contracts with the given tag in the deployment configuration")
+                      .multiple(true)
+                      .number_of_values(1)
+                      .takes_value(true))
                     .arg(Arg::with_name("verbose")
                       .short("v")
                       .long("verbose")
                       .help("Generates verbose output"))
                   )
-                  .subcommand(SubCommand::with_name("accounts")
+                  .subcommand(subcommand("accounts", &help_texts)
                     .about("Outputs available wallet accounts")
                     .arg(Arg::with_name("path")
                       .short("p")
@@ -152,7 +424,7 @@ fn run() -> Result<(), Error> {
                       .help("Specifies path to Vibranium project")
                       .takes_value(true))
                   )
-                  .subcommand(SubCommand::with_name("deploy")
+                  .subcommand(subcommand("deploy", &help_texts)
                     .about("Deploys compiled artifacts")
                     .arg(Arg::with_name("path")
                       .short("p")
@@ -164,12 +436,85 @@ fn run() -> Result<(), Error> {
                       .short("nt")
                       .long("no-tracking")
                       .help("Specifices whether deployment tracking should be disabled"))
+                    .arg(Arg::with_name("watch")
+                      .short("w")
+                      .long("watch")
+                      .help("Recompiles and redeploys whenever Smart Contract sources change"))
+                    .arg(Arg::with_name("no-daemon")
+                      .long("no-daemon")
+                      .help("Runs in this process even if a daemon is running for the project"))
+                    .arg(Arg::with_name("require-tests")
+                      .long("require-tests")
+                      .help("Runs the project's tests first and refuses to deploy to non-development networks if they fail"))
+                    .arg(Arg::with_name("report-gas")
+                      .long("report-gas")
+                      .help("Prints gas estimates of the deployed Smart Contracts and their functions"))
+                    .arg(Arg::with_name("reset")
+                      .long("reset")
+                      .help("Reverts the development chain to its state before the previous `deploy --reset` first"))
+                    .arg(Arg::with_name("continue-on-error")
+                      .long("continue-on-error")
+                      .help("Keeps deploying the remaining Smart Contracts when one fails, skipping the ones depending on it, and reports the outcome of each"))
+                    .arg(Arg::with_name("tag")
+                      .long("tag")
+                      .value_name("TAG")
+                      .help("Only deploys Smart Contracts with the given tag, along with the ones they depend on")
+                      .multiple(true)
+                      .number_of_values(1)
+                      .takes_value(true))
+                    .arg(Arg::with_name("verbose")
+                      .short("v")
+                      .long("verbose")
+                      .help("Generates verbose output"))
+                  )
+                  .subcommand(subcommand("upgrade", &help_texts)
+                    .about("Deploys a new implementation of an upgradeable Smart Contract and points its proxy at it")
+                    .arg(Arg::with_name("contract")
+                      .value_name("CONTRACT")
+                      .help("Specifies the name of the upgradeable Smart Contract")
+                      .required(true))
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                    .arg(Arg::with_name("unsafe-skip-storage-check")
+                      .long("unsafe-skip-storage-check")
+                      .help("Upgrades even if the storage layouts of the implementations are incompatible or unknown"))
+                    .arg(Arg::with_name("require-tests")
+                      .long("require-tests")
+                      .help("Runs the project's tests first and refuses to upgrade on non-development networks if they fail"))
+                  )
+                  .subcommand(subcommand("gas", &help_texts)
+                    .about("Estimates gas of deploying Smart Contracts and calling their functions")
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                  )
+                  .subcommand(subcommand("test", &help_texts)
+                    .about("Runs the Solidity tests in test/ against a development node")
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                    .arg(Arg::with_name("filter")
+                      .short("f")
+                      .long("filter")
+                      .value_name("PATTERN")
+                      .help("Only runs tests whose name (Contract.function) contains the pattern")
+                      .takes_value(true))
                     .arg(Arg::with_name("verbose")
                       .short("v")
                       .long("verbose")
                       .help("Generates verbose output"))
                   )
-                  .subcommand(SubCommand::with_name("list")
+                  .subcommand(subcommand("list", &help_texts)
                     .about("List deployed application data")
                     .arg(Arg::with_name("path")
                       .short("p")
@@ -177,18 +522,578 @@ fn run() -> Result<(), Error> {
                       .value_name("PATH")
                       .help("Specifies path to Vibranium project")
                       .takes_value(true))
+                  )
+                  .subcommand(subcommand("deployments", &help_texts)
+                    .about("Lists and annotates tracked deployments")
+                    .setting(AppSettings::SubcommandRequiredElseHelp)
+                    .subcommand(SubCommand::with_name("list")
+                      .about("Lists tracked Smart Contracts of the connected chain")
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                      .arg(Arg::with_name("tag")
+                        .short("t")
+                        .long("tag")
+                        .value_name("KEY=VALUE")
+                        .help("Only lists deployments tagged with the given metadata")
+                        .multiple(true)
+                        .number_of_values(1)
+                        .takes_value(true))
+                      .arg(Arg::with_name("all")
+                        .short("a")
+                        .long("all")
+                        .help("Lists tracked Smart Contracts of all chains, grouped by network"))
+                    )
+                    .subcommand(SubCommand::with_name("args")
+                      .about("Prints the constructor arguments tracked deployments of a Smart Contract were created with")
+                      .arg(Arg::with_name("contract")
+                        .value_name("CONTRACT")
+                        .help("Specifies the name of the tracked Smart Contract")
+                        .required(true))
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("show")
+                      .about("Shows the transaction and build details of tracked deployments of a Smart Contract")
+                      .arg(Arg::with_name("contract")
+                        .value_name("CONTRACT")
+                        .help("Specifies the name of the tracked Smart Contract")
+                        .required(true))
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("remove")
+                      .about("Removes tracked deployments of a Smart Contract on the connected chain")
+                      .arg(Arg::with_name("contract")
+                        .value_name("CONTRACT")
+                        .help("Specifies the name of the tracked Smart Contract")
+                        .required(true))
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("clear")
+                      .about("Removes tracked deployments made to the network given with --network")
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("tag")
+                      .about("Attaches metadata to tracked deployments of a Smart Contract")
+                      .arg(Arg::with_name("contract")
+                        .value_name("CONTRACT")
+                        .help("Specifies the name of the tracked Smart Contract")
+                        .required(true))
+                      .arg(Arg::with_name("tags")
+                        .value_name("KEY=VALUE")
+                        .help("Specifies metadata to attach")
+                        .required(true)
+                        .multiple(true))
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("rekey")
+                      .about("Rekeys tracking data after changing the project's tracking key strategy")
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("export")
+                      .about("Exports tracked deployments of all networks as an address book for wallets")
+                      .arg(Arg::with_name("address-book")
+                        .short("a")
+                        .long("address-book")
+                        .value_name("FORMAT")
+                        .help("Specifies the address book format")
+                        .possible_values(&["metamask", "safe-addressbook"])
+                        .required(true)
+                        .takes_value(true))
+                      .arg(Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Writes the address book to the given file instead of printing it")
+                        .takes_value(true))
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("diff")
+                      .about("Compares the tracked deployments of two networks")
+                      .arg(Arg::with_name("from")
+                        .long("from")
+                        .value_name("NETWORK")
+                        .help("Network whose deployments are expected on the other one, e.g. staging")
+                        .required(true)
+                        .takes_value(true))
+                      .arg(Arg::with_name("to")
+                        .long("to")
+                        .value_name("NETWORK")
+                        .help("Network to compare against, e.g. production")
+                        .required(true)
+                        .takes_value(true))
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                    .subcommand(SubCommand::with_name("docs")
+                      .about("Generates Markdown documentation of tracked deployments of all networks")
+                      .arg(Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Writes the documentation to the given file instead of printing it")
+                        .takes_value(true))
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                  )
+                  .subcommand(subcommand("children", &help_texts)
+                    .about("Discovers and tracks Smart Contracts created by a tracked factory")
+                    .arg(Arg::with_name("factory")
+                      .value_name("FACTORY")
+                      .help("Name of the tracked factory Smart Contract")
+                      .required(true)
+                      .index(1))
+                    .arg(Arg::with_name("event")
+                      .long("event")
+                      .value_name("EVENT")
+                      .help("Event the factory emits for every created Smart Contract")
+                      .required(true)
+                      .takes_value(true))
+                    .arg(Arg::with_name("arg")
+                      .long("arg")
+                      .value_name("ARG")
+                      .help("Event parameter holding the address of the created Smart Contract")
+                      .required(true)
+                      .takes_value(true))
+                    .arg(Arg::with_name("collection")
+                      .long("collection")
+                      .value_name("COLLECTION")
+                      .help("Collection to track created Smart Contracts under (defaults to <FACTORY>.children)")
+                      .takes_value(true))
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                    .arg(Arg::with_name("verbose")
+                      .short("v")
+                      .long("verbose")
+                      .help("Generates verbose output"))
+                  )
+                  .subcommand(subcommand("events", &help_texts)
+                    .about("Lists decoded events emitted by a tracked Smart Contract, or by any known Smart Contract")
+                    .arg(Arg::with_name("contract")
+                      .value_name("CONTRACT")
+                      .help("Name of the tracked Smart Contract (defaults to any Smart Contract whose ABI is known)")
+                      .index(1))
+                    .arg(Arg::with_name("event")
+                      .long("event")
+                      .value_name("EVENT")
+                      .help("Only lists events of this name (defaults to all events of the Smart Contract's ABI, or of all known ABIs)")
+                      .takes_value(true))
+                    .arg(Arg::with_name("from-block")
+                      .long("from-block")
+                      .value_name("BLOCK")
+                      .help("First block to list events of (defaults to 0, or the next block with --follow). Accepts block numbers, latest, safe, finalized, earliest, offsets like -100 and ISO 8601 timestamps")
+                      .allow_hyphen_values(true)
+                      .takes_value(true))
+                    .arg(Arg::with_name("to-block")
+                      .long("to-block")
+                      .value_name("BLOCK")
+                      .help("Last block to list events of (defaults to the latest block). Accepts the same blocks as --from-block")
+                      .conflicts_with("follow")
+                      .allow_hyphen_values(true)
+                      .takes_value(true))
+                    .arg(Arg::with_name("follow")
+                      .short("f")
+                      .long("follow")
+                      .help("Keeps listing events of new blocks until interrupted"))
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                    .arg(Arg::with_name("verbose")
+                      .short("v")
+                      .long("verbose")
+                      .help("Generates verbose output"))
+                  )
+                  .subcommand(subcommand("tx", &help_texts)
+                    .about("Decodes the call, events and revert reason of a transaction")
+                    .arg(Arg::with_name("hash")
+                      .value_name("HASH")
+                      .help("Hash of the transaction")
+                      .required(true)
+                      .index(1))
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                  )
+                  .subcommand(subcommand("access-report", &help_texts)
+                    .about("Lists state-changing functions of compiled Smart Contracts along with their access restrictions")
+                    .arg(Arg::with_name("contract")
+                      .value_name("CONTRACT")
+                      .help("Name of the compiled Smart Contract (defaults to all compiled Smart Contracts)")
+                      .index(1))
+                    .arg(Arg::with_name("unrestricted")
+                      .long("unrestricted")
+                      .help("Only lists functions without modifiers or inline access checks"))
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                    .arg(Arg::with_name("verbose")
+                      .short("v")
+                      .long("verbose")
+                      .help("Generates verbose output"))
+                  )
+                  .subcommand(subcommand("doctor", &help_texts)
+                    .about("Checks whether the tools a project depends on are available")
+                    .arg(Arg::with_name("report")
+                      .long("report")
+                      .help("Writes a troubleshooting report including recent invocations of external tools"))
+                    .arg(Arg::with_name("output")
+                      .short("o")
+                      .long("output")
+                      .value_name("FILE")
+                      .help("Specifies the file to write the report to (defaults to vibranium-report.json in the project directory)")
+                      .requires("report")
+                      .takes_value(true))
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                    .arg(Arg::with_name("verbose")
+                      .short("v")
+                      .long("verbose")
+                      .help("Generates verbose output"))
+                  )
+                  .subcommand(subcommand("verify", &help_texts)
+                    .about("Checks the code of tracked deployments against the compiled artifacts")
+                    .arg(Arg::with_name("contract")
+                      .value_name("CONTRACT")
+                      .help("Name of the deployed Smart Contract (defaults to all tracked Smart Contracts)")
+                      .index(1))
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                    .arg(Arg::with_name("tag")
+                      .long("tag")
+                      .value_name("TAG")
+                      .help("Only verifies Smart Contracts with the given tag")
+                      .multiple(true)
+                      .number_of_values(1)
+                      .takes_value(true))
+                    .arg(Arg::with_name("etherscan")
+                      .long("etherscan")
+                      .help("Submits the sources of the Smart Contract to Etherscan instead, waiting until they're verified")
+                      .requires("contract")
+                      .conflicts_with_all(&["tag", "sourcify"]))
+                    .arg(Arg::with_name("sourcify")
+                      .long("sourcify")
+                      .help("Submits the sources of the Smart Contract to Sourcify instead, waiting until they're verified")
+                      .requires("contract")
+                      .conflicts_with("tag"))
+                    .arg(Arg::with_name("verbose")
+                      .short("v")
+                      .long("verbose")
+                      .help("Generates verbose output"))
+                  )
+                  .subcommand(subcommand("reproduce", &help_texts)
+                    .about("Recompiles a Smart Contract and compares its bytecode with the code of its tracked deployments")
+                    .arg(Arg::with_name("contract")
+                      .value_name("CONTRACT")
+                      .help("Name of the deployed Smart Contract")
+                      .required(true)
+                      .index(1))
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                    .arg(Arg::with_name("verbose")
+                      .short("v")
+                      .long("verbose")
+                      .help("Generates verbose output"))
+                  )
+                  .subcommand(subcommand("interface", &help_texts)
+                    .about("Generates a Solidity interface from the ABI of a compiled Smart Contract")
+                    .arg(Arg::with_name("contract")
+                      .value_name("CONTRACT")
+                      .help("Name of the compiled Smart Contract")
+                      .required(true)
+                      .index(1))
+                    .arg(Arg::with_name("name")
+                      .short("n")
+                      .long("name")
+                      .value_name("NAME")
+                      .help("Specifies the name of the interface (defaults to I<CONTRACT>)")
+                      .takes_value(true))
+                    .arg(Arg::with_name("output")
+                      .short("o")
+                      .long("output")
+                      .value_name("FILE")
+                      .help("Writes the interface to the given file instead of printing it")
+                      .takes_value(true))
+                    .arg(Arg::with_name("abi-output")
+                      .long("abi-output")
+                      .value_name("FILE")
+                      .help("Additionally writes the ABI of the interface to the given file")
+                      .takes_value(true))
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                    .arg(Arg::with_name("verbose")
+                      .short("v")
+                      .long("verbose")
+                      .help("Generates verbose output"))
+                  )
+                  .subcommand(subcommand("check-interface", &help_texts)
+                    .about("Checks whether a compiled or deployed Smart Contract implements a standard interface")
+                    .arg(Arg::with_name("contract")
+                      .value_name("CONTRACT|ADDRESS")
+                      .help("Name of the compiled Smart Contract or address of the deployed one")
+                      .required(true)
+                      .index(1))
+                    .arg(Arg::with_name("erc")
+                      .long("erc")
+                      .value_name("STANDARD")
+                      .help("Specifies the interface to check against: 20, 721, 1155 or the path to an ABI file")
+                      .required(true)
+                      .takes_value(true))
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                    .arg(Arg::with_name("verbose")
+                      .short("v")
+                      .long("verbose")
+                      .help("Generates verbose output"))
+                  )
+                  .subcommand(subcommand("history", &help_texts)
+                    .about("Shows previously executed commands")
+                    .setting(AppSettings::SubcommandRequiredElseHelp)
+                    .subcommand(SubCommand::with_name("list")
+                      .about("Lists recorded commands, most recent first")
+                      .arg(Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Specifies path to Vibranium project")
+                        .takes_value(true))
+                    )
+                  )
+                  .subcommand(subcommand("rerun", &help_texts)
+                    .about("Executes a previously recorded command again")
+                    .arg(Arg::with_name("number")
+                      .value_name("N")
+                      .help("Number of the command as shown by `vibranium history list` (defaults to 1, the most recent one)")
+                      .index(1))
+                    .arg(Arg::with_name("force")
+                      .long("force")
+                      .help("Reruns the command even if its network or Smart Contracts resolve differently than when it ran"))
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                  )
+                  .subcommand(subcommand("stats", &help_texts)
+                    .about("Shows local usage statistics of compile and deploy runs, if enabled via `stats = true`")
+                    .arg(Arg::with_name("days")
+                      .short("d")
+                      .long("days")
+                      .value_name("DAYS")
+                      .help("Number of most recent days to show statistics for (defaults to 30)")
+                      .takes_value(true))
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                  )
+                  .subcommand(subcommand("package", &help_texts)
+                    .about("Bundles sources, artifacts, deployment data and compiler settings of a release into a tarball")
+                    .arg(Arg::with_name("path")
+                      .short("p")
+                      .long("path")
+                      .value_name("PATH")
+                      .help("Specifies path to Vibranium project")
+                      .takes_value(true))
+                    .arg(Arg::with_name("release")
+                      .short("r")
+                      .long("release")
+                      .value_name("RELEASE")
+                      .help("Specifies the name or tag of the release to package")
+                      .required(true)
+                      .takes_value(true))
+                    .arg(Arg::with_name("output")
+                      .short("o")
+                      .long("output")
+                      .value_name("FILE")
+                      .help("Specifies the path of the package file to create")
+                      .takes_value(true))
+                    .arg(Arg::with_name("verbose")
+                      .short("v")
+                      .long("verbose")
+                      .help("Generates verbose output"))
+                  )
+                  .subcommand(subcommand("help", &help_texts)
+                    .about("Prints this message, the help of the given subcommand or a help topic")
+                    .arg(Arg::with_name("topic")
+                      .value_name("TOPIC")
+                      .help("Specifies the subcommand or topic to print help for")
+                      .takes_value(true))
                   );
-                    
 
-  let matches = app.clone().get_matches();
+  let matches = app.clone().get_matches_from(args.clone());
 
   if let (_, Some(cmd)) = matches.subcommand() {
     if cmd.is_present("verbose") {
-      env_logger::Builder::from_default_env().filter(None, LevelFilter::Info).init();
+      let _ = env_logger::Builder::from_default_env().filter(None, LevelFilter::Info).try_init();
+    }
+  }
+
+  let output = output::Output::new(output::format_from_args(&args));
+  if output.is_json() {
+    let command = match matches.subcommand() {
+      (name, Some(cmd)) => cmd.subcommand_name().map(|nested| format!("{} {}", name, nested)).unwrap_or_else(|| name.to_string()),
+      (name, None) => name.to_string(),
+    };
+    if !output::JSON_COMMANDS.contains(&command.as_str()) {
+      Err(error::CliError::Other(format!("`{}` doesn't support --format json", command)))?
+    }
+    if matches.subcommand().1.is_some_and(|cmd| cmd.is_present("watch")) {
+      Err(error::CliError::Other("--watch doesn't support --format json".to_string()))?
+    }
+    if matches.subcommand().1.is_some_and(|cmd| cmd.is_present("follow")) {
+      Err(error::CliError::Other("--follow doesn't support --format json".to_string()))?
     }
   }
 
   match matches.subcommand() {
+    ("node", Some(cmd)) if cmd.subcommand_name().is_some() => {
+      match cmd.subcommand() {
+        ("start", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path);
+
+          let config = blockchain::NodeConfig {
+            client: cmd.value_of("client").map(std::string::ToString::to_string),
+            client_options: cmd.values_of("client-opts").map(|options| options.map(std::string::ToString::to_string).collect()),
+            network: network_from(cmd),
+          };
+
+          let node = vibranium.start_managed_node(config).map_err(error::CliError::BlockchainError)?;
+          match (node.rpc_port, node.ws_port) {
+            (Some(rpc_port), Some(ws_port)) if rpc_port != ws_port => output.message(format!("Started {} node (pid {}) on ports {} (HTTP) and {} (WebSocket).", node.client, node.pid, rpc_port, ws_port)),
+            (Some(port), _) => output.message(format!("Started {} node (pid {}) on port {}.", node.client, node.pid, port)),
+            _ => output.message(format!("Started {} node (pid {}).", node.client, node.pid)),
+          }
+          output.result("node start", json!({ "node": node_json(&node) }));
+        },
+        ("stop", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path);
+          let node = vibranium.stop_node().map_err(error::CliError::BlockchainError)?;
+          match &node {
+            Some(node) => output.message(format!("Stopped {} node (pid {}).", node.client, node.pid)),
+            None => output.message("No node is running in the background."),
+          }
+          output.result("node stop", json!({ "node": node.as_ref().map(node_json) }));
+        },
+        ("status", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path);
+          let node = vibranium.node_status().map_err(error::CliError::BlockchainError)?;
+          match &node {
+            Some(node) => {
+              output.message(format!("{} node is running (pid {}, network {}, up for {}s).", node.client, node.pid, node.network, node.uptime()));
+              if let Some(port) = node.rpc_port {
+                output.message(format!("HTTP port: {}", port));
+              }
+              if let Some(port) = node.ws_port {
+                output.message(format!("WebSocket port: {}", port));
+              }
+            },
+            None => output.message("No node is running in the background."),
+          }
+          output.result("node status", json!({
+            "running": node.is_some(),
+            "node": node.as_ref().map(node_json),
+          }));
+        },
+        ("logs", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path);
+          let lines = match cmd.value_of("lines") {
+            Some(lines) => lines.parse().map_err(|_err| error::CliError::Other(format!("Invalid number of lines '{}'", lines)))?,
+            None => 50,
+          };
+          for line in vibranium.node_logs(lines).map_err(error::CliError::BlockchainError)? {
+            println!("{}", line);
+          }
+        },
+        _ => unreachable!(),
+      }
+    },
+
     ("node", Some(cmd)) => {
       println!("Starting blockchain node...");
       let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
@@ -201,26 +1106,144 @@ fn run() -> Result<(), Error> {
       let config = blockchain::NodeConfig {
         client: cmd.value_of("client").map(std::string::ToString::to_string),
         client_options,
+        network: network_from(cmd),
       };
     
       vibranium.start_node(config).map_err(error::CliError::BlockchainError)?;
     },
 
+    ("daemon", Some(cmd)) if cmd.subcommand_name().is_some() => {
+      match cmd.subcommand() {
+        ("start", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path);
+          let executable = env::current_exe().map_err(|err| error::CliError::Other(err.to_string()))?;
+          let daemon = vibranium.start_daemon(&executable)?;
+          output.message(format!("Started daemon (pid {}).", daemon.pid));
+          output.result("daemon start", json!({ "daemon": daemon_json(&daemon) }));
+        },
+        ("stop", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path);
+          let daemon = vibranium.stop_daemon()?;
+          match &daemon {
+            Some(daemon) => output.message(format!("Stopped daemon (pid {}).", daemon.pid)),
+            None => output.message("No daemon is running in the background."),
+          }
+          output.result("daemon stop", json!({ "daemon": daemon.as_ref().map(daemon_json) }));
+        },
+        ("status", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path);
+          let daemon = vibranium.daemon_status()?;
+          match &daemon {
+            Some(daemon) => output.message(format!("Daemon is running (pid {}, up for {}s).", daemon.pid, daemon.uptime())),
+            None => output.message("No daemon is running in the background."),
+          }
+          output.result("daemon status", json!({
+            "running": daemon.is_some(),
+            "daemon": daemon.as_ref().map(daemon_json),
+          }));
+        },
+        _ => unreachable!(),
+      }
+    },
+
+    ("daemon", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path);
+      println!("Serving compile and deploy requests, press Ctrl+C to stop...");
+      vibranium.run_daemon()?;
+    },
+
+    ("lsp", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path);
+      vibranium.serve_language_server()?;
+    },
+
+    ("chain", Some(cmd)) => {
+      match cmd.subcommand() {
+        ("reset", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path).with_read_only(cmd.is_present("read-only"));
+          let network = cmd.value_of("name").unwrap();
+
+          let pruned = vibranium.reset_chain(network).map_err(|err| error::CliError::Other(err.to_string()))?;
+          output.message(format!("Wiped chain data of network {}.", network));
+          output.message(format!("Pruned {} tracked deployment(s).", pruned));
+          output.result("chain reset", json!({ "network": network, "pruned": pruned }));
+        },
+        ("snapshot", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path)
+            .with_profile(profile_from(cmd))
+            .with_network(network_from(cmd));
+
+          let snapshot = vibranium.snapshot_chain().map_err(|err| error::CliError::Other(err.to_string()))?;
+          output.message(format!("Took snapshot {}.", blockchain::chain_control::format_snapshot(snapshot)));
+          output.result("chain snapshot", json!({ "snapshot": blockchain::chain_control::format_snapshot(snapshot) }));
+        },
+        ("revert", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path)
+            .with_profile(profile_from(cmd))
+            .with_network(network_from(cmd))
+            .with_read_only(cmd.is_present("read-only"));
+          let id = cmd.value_of("id").unwrap();
+          let snapshot = blockchain::chain_control::parse_snapshot(id)
+            .ok_or_else(|| error::CliError::Other(format!("Invalid snapshot id {}", id)))?;
+
+          vibranium.revert_chain(snapshot).map_err(|err| error::CliError::Other(err.to_string()))?;
+          output.message(format!("Reverted chain to snapshot {}.", blockchain::chain_control::format_snapshot(snapshot)));
+          output.result("chain revert", json!({ "snapshot": blockchain::chain_control::format_snapshot(snapshot) }));
+        },
+        _ => unreachable!(),
+      }
+    },
+
     ("init", Some(cmd)) => {
       println!("Initializing empty Vibranium project...");
       let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
       let vibranium = Vibranium::new(path);
 
-      vibranium.init_project().and_then(|_| {
-        println!("Done.");
-        Ok(())
-      })?
+      vibranium.init_project()?;
+      if vibranium.generate_package_json()? {
+        println!("Generated package.json for JavaScript dependencies of the configured tools.");
+      }
+      if cmd.is_present("with-frontend") {
+        vibranium.generate_frontend()?;
+      }
+
+      let package_manager = if cmd.is_present("install") || cmd.is_present("package-manager") {
+        let package_manager = vibranium.package_manager(cmd.value_of("package-manager"))?;
+        vibranium.set_config("package_manager".to_string(), toml::Value::String(package_manager.to_string()))?;
+        Some(package_manager)
+      } else {
+        None
+      };
+
+      if cmd.is_present("install") {
+        let package_manager = package_manager.unwrap();
+        for directory in vibranium.js_dependency_directories() {
+          println!("Installing JavaScript dependencies in {} with {}...", directory.to_string_lossy(), package_manager);
+          vibranium.install_js_dependencies(&directory, package_manager)?;
+        }
+        if cmd.is_present("with-frontend") {
+          println!("Scaffolded frontend. Run `{} run dev` inside the frontend directory to start it.", package_manager);
+        }
+      } else if cmd.is_present("with-frontend") {
+        let package_manager = package_manager.map(|package_manager| package_manager.to_string()).unwrap_or_else(|| "npm".to_string());
+        println!("Scaffolded frontend. Run `{0} install && {0} run dev` inside the frontend directory to start it.", package_manager);
+      }
+      warn_about_secrets(&vibranium);
+      println!("Done.");
     },
 
     ("reset", Some(cmd)) => {
       println!("Resetting Vibranium project...");
       let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
-      let vibranium = Vibranium::new(path);
+      let vibranium = Vibranium::new(path).with_read_only(cmd.is_present("read-only"));
 
       vibranium.reset_project(ResetOptions {
         restore_config: cmd.is_present("restore-config"),
@@ -234,6 +1257,10 @@ fn run() -> Result<(), Error> {
     ("config", Some(cmd)) => {
       let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
       let vibranium = Vibranium::new(path);
+      let write_options = config::WriteOptions {
+        dry_run: cmd.is_present("dry-run"),
+        backup: cmd.is_present("backup"),
+      };
 
       if let Some(options) = cmd.values_of("set") {
         let mut args: Vec<String> = options.map(std::string::ToString::to_string).collect();
@@ -256,103 +1283,965 @@ fn run() -> Result<(), Error> {
             .map_err(error::CliError::ConfigurationSetError)?
         };
 
-        vibranium.set_config(config_option, value)?
+        let change = vibranium.set_config_with(config_option, value, write_options)?;
+        print_config_change(&change, write_options);
       }
 
       if let Some(config_option) = cmd.value_of("unset") {
-        vibranium.unset_config(config_option.to_string()).map_err(error::CliError::ConfigurationDeleteError)?
+        let change = vibranium.unset_config_with(config_option.to_string(), write_options).map_err(error::CliError::ConfigurationDeleteError)?;
+        print_config_change(&change, write_options);
       }
     },
 
     ("compile", Some(cmd)) => {
-      println!("Compiling Vibranium project...");
+      output.message("Compiling Vibranium project...");
       let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
-      let vibranium = Vibranium::new(path);
-
-      let compiler_options = cmd.values_of("compiler-opts").map(|options| {
-        options.map(std::string::ToString::to_string).collect()
-      });
+      let vibranium = Vibranium::new(path).with_profile(profile_from(cmd)).with_network(network_from(cmd));
+      let daemon = if cmd.is_present("no-daemon") { None } else { vibranium.daemon_client() };
 
-      let config = CompilerConfig {
+      let compiler_config = || CompilerConfig {
         compiler: cmd.value_of("compiler").map(std::string::ToString::to_string),
-        compiler_options,
+        compiler_options: cmd.values_of("compiler-opts").map(|options| {
+          options.map(std::string::ToString::to_string).collect()
+        }),
+        replace_options: cmd.is_present("replace-options"),
+        force: cmd.is_present("force"),
+        tags: tags_from(cmd),
       };
 
-      vibranium
-        .compile(config)
-        .map_err(error::CliError::CompilationError)
-        .and_then(|output| {
-          if !output.stderr.is_empty() {
-            io::stderr().write_all(&output.stderr).unwrap();
-          }
-          io::stdout().write_all(&output.stdout).unwrap();
-          println!("Done.");
-          Ok(())
-        })?
+      if cmd.is_present("watch") {
+        watch(&vibranium, || compile_project(&vibranium, daemon.as_ref(), compiler_config(), &output))?;
+      } else if cmd.is_present("assert-deterministic") {
+        let differences = vibranium.check_determinism(compiler_config(), cmd.is_present("isolated")).map_err(error::CliError::CompilationError)?;
+        if !differences.is_empty() {
+          Err(error::CliError::CompilationError(compiler::error::CompilerError::NotDeterministic(differences)))?
+        }
+        output.message("Compiler output is deterministic.");
+        output.result("compile", json!({ "deterministic": true }));
+      } else {
+        compile_project(&vibranium, daemon.as_ref(), compiler_config(), &output)?;
+        output.message("Done.");
+      }
     },
 
     ("accounts", Some(cmd)) => {
       let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
-      let vibranium = Vibranium::new(path);
+      let vibranium = Vibranium::new(path)
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd))
+        .with_rpc_cassette(rpc_cassette_from(cmd))
+        .with_call_cache(!cmd.is_present("no-cache"));
 
       let (_eloop, connector) = vibranium.get_blockchain_connector().map_err(error::CliError::BlockchainConnectorError)?;
       let accounts = connector.accounts().map_err(error::CliError::BlockchainConnectorError)?;
 
       for (i, address) in accounts.iter().enumerate() {
-        println!("({}) {:?}", i, address);
+        output.message(format!("({}) {:?}", i, address));
       }
+      output.result("accounts", json!({ "accounts": accounts }));
     },
 
     ("deploy", Some(cmd)) => {
-      println!("Deploying...");
+      output.message("Deploying...");
       let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
-      let vibranium = Vibranium::new(path);
+      let vibranium = Vibranium::new(path)
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd))
+        .with_read_only(cmd.is_present("read-only"))
+        .with_rpc_cassette(rpc_cassette_from(cmd))
+        .with_call_cache(!cmd.is_present("no-cache"));
+      warn_about_secrets(&vibranium);
+      let daemon = if cmd.is_present("no-daemon") { None } else { vibranium.daemon_client() };
 
-      let deploy_options = DeployOptions {
+      let deploy_options = || DeployOptions {
         tracking_enabled: if cmd.is_present("no-tracking") {
           Some(false)
         } else {
           None
-        }
-      };
-
-      vibranium.deploy(deploy_options)
-        .map_err(|err| {
-          match err {
-            deployment::error::DeploymentError::Connection(connector_error) => error::CliError::BlockchainConnectorError(connector_error),
-            deployment::error::DeploymentError::MissingConfig => error::CliError::DeploymentError(err),
-            _ => error::CliError::Other(err.to_string()),
+        },
+        require_tests: if cmd.is_present("require-tests") {
+          Some(true)
+        } else {
+          None
+        },
+        continue_on_error: if cmd.is_present("continue-on-error") {
+          Some(true)
+        } else {
+          None
+        },
+        tags: tags_from(cmd),
+        smart_contracts: None,
+      };
+
+      if cmd.is_present("reset") {
+        let pruned = vibranium.reset_deployments().map_err(|err| error::CliError::Other(err.to_string()))?;
+        output.message(format!("Reset chain, pruned {} tracked deployment(s).", pruned));
+      }
+
+      if cmd.is_present("watch") {
+        watch(&vibranium, || {
+          compile_project(&vibranium, daemon.as_ref(), CompilerConfig {
+            compiler: None,
+            compiler_options: None,
+            replace_options: false,
+            force: false,
+            tags: None,
+          }, &output)?;
+          deploy_project(&vibranium, daemon.as_ref(), deploy_options(), cmd.is_present("report-gas"), &output)
+        })?;
+      } else {
+        deploy_project(&vibranium, daemon.as_ref(), deploy_options(), cmd.is_present("report-gas"), &output)?;
+      }
+    },
+
+    ("upgrade", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path)
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd))
+        .with_read_only(cmd.is_present("read-only"))
+        .with_rpc_cassette(rpc_cassette_from(cmd))
+        .with_call_cache(!cmd.is_present("no-cache"));
+      warn_about_secrets(&vibranium);
+
+      let name = cmd.value_of("contract").unwrap();
+      output.message(format!("Upgrading {}...", name));
+      let report = vibranium.upgrade(name, UpgradeOptions {
+        skip_storage_check: cmd.is_present("unsafe-skip-storage-check"),
+        require_tests: if cmd.is_present("require-tests") {
+          Some(true)
+        } else {
+          None
+        },
+      }).map_err(|err| match err {
+        deployment::error::DeploymentError::Connection(connector_error) => error::CliError::BlockchainConnectorError(connector_error),
+        _ => error::CliError::DeploymentError(err),
+      })?;
+
+      match report.tx_hash {
+        Some(tx_hash) => output.message(format!("  {:?}: {} now points at implementation {:?} [Transaction: {:?}]", report.proxy, report.name, report.implementation, tx_hash)),
+        None => output.message(format!("  {:?}: {} already points at implementation {:?}", report.proxy, report.name, report.implementation)),
+      }
+      output.result("upgrade", json!({
+        "name": report.name,
+        "proxy": report.proxy,
+        "previous_implementation": report.previous_implementation,
+        "implementation": report.implementation,
+        "tx_hash": report.tx_hash,
+      }));
+    },
+
+    ("gas", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path)
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd))
+        .with_rpc_cassette(rpc_cassette_from(cmd))
+        .with_call_cache(!cmd.is_present("no-cache"));
+
+      let report = vibranium.gas_report().map_err(error::CliError::DeploymentError)?;
+      print_gas_report(&report, &output);
+      output.result("gas", json!({ "gas": gas_report_json(&report) }));
+    },
+
+    ("test", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path)
+        .with_read_only(cmd.is_present("read-only"))
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd))
+        .with_call_cache(!cmd.is_present("no-cache"));
+
+      let report = vibranium.run_tests(TestOptions {
+        filter: cmd.value_of("filter").map(str::to_string),
+      })?;
+      let width = report.results.iter().map(|result| result.to_string().len()).max().unwrap_or_default();
+      for result in &report.results {
+        let status = if result.passed { "[ok]" } else { "[failed]" };
+        let gas_used = result.gas_used.map(|gas_used| format!("gas: {}", gas_used)).unwrap_or_default();
+        output.message(format!("{:8} {:width$}  {}", status, result.to_string(), gas_used, width = width));
+        if let Some(reason) = &result.reason {
+          output.message(format!("         {}", reason));
+        }
+      }
+      output.message(format!("\n{} passed, {} failed.", report.passed(), report.failed()));
+
+      let results: Vec<_> = report.results.iter().map(|result| json!({
+        "contract": result.contract,
+        "function": result.function,
+        "passed": result.passed,
+        "gas_used": result.gas_used.map(|gas_used| gas_used.low_u64()),
+        "reason": result.reason,
+      })).collect();
+      output.result("test", json!({ "passed": report.passed(), "failed": report.failed(), "tests": results }));
+      if !report.is_success() {
+        Err(error::CliError::Other(format!("{} of {} test(s) failed.", report.failed(), report.results.len())))?
+      }
+    },
+
+    ("list", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path)
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd))
+        .with_rpc_cassette(rpc_cassette_from(cmd))
+        .with_call_cache(!cmd.is_present("no-cache"));
+      let tracking_data = vibranium.get_tracking_data().map_err(|err| error::CliError::Other(err.to_string()))?;
+
+      match &tracking_data {
+        None => output.message("No Smart Contract data for currently connected chain has been tracked."),
+        Some(data) => {
+          output.message("Deployed Smart Contracts:");
+          for smart_contract in data.values() {
+            output.message(format!("  {:?}: {}", smart_contract.address, smart_contract.name));
           }
-        }).and_then(|contracts| {
-          if contracts.is_empty() {
-            println!("Nothing to deploy.");
+        }
+      }
+      let smart_contracts: Vec<_> = tracking_data.unwrap_or_default().into_values().collect();
+      output.result("list", json!({ "smart_contracts": smart_contracts }));
+    },
+
+    ("deployments", Some(cmd)) => {
+      match cmd.subcommand() {
+        ("list", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path)
+            .with_profile(profile_from(cmd))
+            .with_network(network_from(cmd))
+            .with_rpc_cassette(rpc_cassette_from(cmd))
+            .with_call_cache(!cmd.is_present("no-cache"));
+          let filters = parse_tags(cmd.values_of("tag"))?;
+
+          if cmd.is_present("all") {
+            let chains = vibranium.get_tracking_data_of_all_chains().map_err(|err| error::CliError::Other(err.to_string()))?;
+            let mut networks = deployment::tracker::group_by_network(&chains);
+            for smart_contracts in networks.values_mut() {
+              smart_contracts.retain(|smart_contract| filters.iter().all(|(key, value)| smart_contract.has_tag(key, value)));
+              smart_contracts.sort_by(|a, b| (&a.name, a.deployed_at).cmp(&(&b.name, b.deployed_at)));
+            }
+            networks.retain(|_network, smart_contracts| !smart_contracts.is_empty());
+
+            if networks.is_empty() {
+              output.message("No matching Smart Contract data has been tracked.");
+            }
+            for (network, smart_contracts) in &networks {
+              output.message(format!("{}:", network));
+              for smart_contract in smart_contracts {
+                output.message(format!("  {:?}: {}", smart_contract.address, smart_contract.name));
+              }
+            }
+            output.result("deployments list", json!({ "networks": networks }));
           } else {
-            println!();
-            for (_address, data) in contracts {
-              if data.3 {
-                println!("  {:?}: {} (skipped) [Source: {}]", data.1, data.0, data.2);
-              } else {
-                println!("  {:?}: {} [Source: {}]", data.1, data.0, data.2);
+            let tracking_data = vibranium.get_tracking_data().map_err(|err| error::CliError::Other(err.to_string()))?;
+
+            let smart_contracts: Vec<_> = tracking_data.unwrap_or_default()
+              .into_values()
+              .filter(|smart_contract| filters.iter().all(|(key, value)| smart_contract.has_tag(key, value)))
+              .collect();
+
+            if smart_contracts.is_empty() {
+              output.message("No matching Smart Contract data for currently connected chain has been tracked.");
+            } else {
+              output.message("Deployed Smart Contracts:");
+              for smart_contract in &smart_contracts {
+                match &smart_contract.metadata {
+                  Some(metadata) if !metadata.is_empty() => {
+                    let tags: Vec<String> = metadata.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+                    output.message(format!("  {:?}: {} [{}]", smart_contract.address, smart_contract.name, tags.join(", ")));
+                  },
+                  _ => output.message(format!("  {:?}: {}", smart_contract.address, smart_contract.name)),
+                }
               }
             }
-            println!();
-            println!("Done.");
+            output.result("deployments list", json!({ "smart_contracts": smart_contracts }));
+          }
+        },
+        ("args", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path)
+            .with_profile(profile_from(cmd))
+            .with_network(network_from(cmd))
+            .with_rpc_cassette(rpc_cassette_from(cmd))
+            .with_call_cache(!cmd.is_present("no-cache"));
+          let name = cmd.value_of("contract").unwrap();
+          let tracking_data = vibranium.get_tracking_data().map_err(|err| error::CliError::Other(err.to_string()))?;
+
+          let smart_contracts: Vec<_> = tracking_data.unwrap_or_default()
+            .into_values()
+            .filter(|smart_contract| smart_contract.name == name)
+            .collect();
+
+          if smart_contracts.is_empty() {
+            Err(error::CliError::Other(format!("Couldn't find tracked Smart Contract '{}' on the connected chain", name)))?
+          }
+
+          let mut deployments = vec![];
+          for smart_contract in smart_contracts {
+            output.message(format!("{} ({:?}):", smart_contract.name, smart_contract.address));
+            let args = smart_contract.decoded_args().map_err(|err| error::CliError::Other(err.to_string()))?;
+            match &args {
+              None => output.message("  No constructor arguments have been tracked for this deployment."),
+              Some(args) => {
+                if args.is_empty() {
+                  output.message("  No constructor arguments.");
+                }
+                for arg in args {
+                  output.message(format!("  {}: {}", arg.kind, arg.value));
+                }
+                output.message(format!("  Encoded: {}", smart_contract.encoded_args.clone().unwrap_or_default()));
+              }
+            }
+            deployments.push(json!({
+              "name": smart_contract.name,
+              "address": smart_contract.address,
+              "args": args,
+              "encoded_args": smart_contract.encoded_args,
+            }));
+          }
+          output.result("deployments args", json!({ "smart_contracts": deployments }));
+        },
+        ("show", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path)
+            .with_profile(profile_from(cmd))
+            .with_network(network_from(cmd))
+            .with_rpc_cassette(rpc_cassette_from(cmd))
+            .with_call_cache(!cmd.is_present("no-cache"));
+          let name = cmd.value_of("contract").unwrap();
+          let tracking_data = vibranium.get_tracking_data().map_err(|err| error::CliError::Other(err.to_string()))?;
+
+          let mut smart_contracts: Vec<_> = tracking_data.unwrap_or_default()
+            .into_values()
+            .filter(|smart_contract| smart_contract.name == name)
+            .collect();
+          smart_contracts.sort_by_key(|smart_contract| smart_contract.deployed_at);
+
+          if smart_contracts.is_empty() {
+            Err(error::CliError::Other(format!("Couldn't find tracked Smart Contract '{}' on the connected chain", name)))?
+          }
+
+          let unknown = || "unknown".to_string();
+          for smart_contract in &smart_contracts {
+            output.message(format!("{} ({:?}):", smart_contract.name, smart_contract.address));
+            output.message(format!("  Transaction:      {}", smart_contract.tx_hash.map(|tx_hash| format!("{:?}", tx_hash)).unwrap_or_else(unknown)));
+            output.message(format!("  Block:            {}", smart_contract.block_number.map(|block_number| block_number.to_string()).unwrap_or_else(unknown)));
+            output.message(format!("  Deployer:         {}", smart_contract.deployer.map(|deployer| format!("{:?}", deployer)).unwrap_or_else(unknown)));
+            output.message(format!("  Gas used:         {}", smart_contract.gas_used.map(|gas_used| gas_used.to_string()).unwrap_or_else(unknown)));
+            output.message(format!("  Deployed at:      {}", smart_contract.deployment_time().unwrap_or_else(unknown)));
+            output.message(format!("  Compiler version: {}", smart_contract.compiler_version.clone().unwrap_or_else(unknown)));
+            output.message(format!("  Commit:           {}", smart_contract.commit.clone().unwrap_or_else(unknown)));
+            match smart_contract.decoded_args().map_err(|err| error::CliError::Other(err.to_string()))? {
+              None => output.message(format!("  Constructor args: {}", unknown())),
+              Some(args) => output.message(format!("  Constructor args: {}", args.iter().map(|arg| format!("{}: {}", arg.kind, arg.value)).collect::<Vec<String>>().join(", "))),
+            }
+          }
+          output.result("deployments show", json!({ "smart_contracts": smart_contracts }));
+        },
+        ("tag", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path)
+            .with_profile(profile_from(cmd))
+            .with_network(network_from(cmd))
+            .with_read_only(cmd.is_present("read-only"))
+            .with_rpc_cassette(rpc_cassette_from(cmd))
+            .with_call_cache(!cmd.is_present("no-cache"));
+          let name = cmd.value_of("contract").unwrap();
+          let tags = parse_tags(cmd.values_of("tags"))?;
+
+          let updated = vibranium.tag_deployment(name, tags).map_err(|err| error::CliError::Other(err.to_string()))?;
+          output.message(format!("Tagged {} deployment(s) of {}.", updated, name));
+          output.result("deployments tag", json!({ "contract": name, "updated": updated }));
+        },
+        ("remove", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path)
+            .with_profile(profile_from(cmd))
+            .with_network(network_from(cmd))
+            .with_read_only(cmd.is_present("read-only"))
+            .with_rpc_cassette(rpc_cassette_from(cmd))
+            .with_call_cache(!cmd.is_present("no-cache"));
+          let name = cmd.value_of("contract").unwrap();
+
+          let removed = vibranium.remove_deployment(name).map_err(|err| error::CliError::Other(err.to_string()))?;
+          output.message(format!("Removed {} tracked deployment(s) of {}.", removed, name));
+          output.result("deployments remove", json!({ "contract": name, "removed": removed }));
+        },
+        ("clear", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let network = network_from(cmd).ok_or_else(|| error::CliError::Other("Couldn't clear tracked deployments. Specify the network to clear with --network.".to_string()))?;
+          let vibranium = Vibranium::new(path)
+            .with_profile(profile_from(cmd))
+            .with_read_only(cmd.is_present("read-only"));
+
+          let removed = vibranium.clear_deployments(&network).map_err(|err| error::CliError::Other(err.to_string()))?;
+          output.message(format!("Removed {} tracked deployment(s) made to {}.", removed, network));
+          output.result("deployments clear", json!({ "network": network, "removed": removed }));
+        },
+        ("rekey", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path)
+            .with_profile(profile_from(cmd))
+            .with_network(network_from(cmd))
+            .with_read_only(cmd.is_present("read-only"));
+
+          let (strategy, report) = vibranium.rekey_tracking_data().map_err(|err| error::CliError::Other(err.to_string()))?;
+          output.message(format!("Rekeyed {} tracked deployment(s) using the {} tracking key strategy.", report.updated, strategy));
+          if !report.kept.is_empty() {
+            let kept: Vec<String> = report.kept.iter().map(|(name, address)| format!("\n  {} at {:?}", name, address)).collect();
+            output.message(format!("Kept the previous key of {} tracked deployment(s), they couldn't be proven to match the current bytecode and constructor arguments:{}", report.kept.len(), kept.concat()));
+          }
+          output.result("deployments rekey", json!({
+            "strategy": strategy.to_string(),
+            "updated": report.updated,
+            "kept": report.kept.iter().map(|(name, address)| json!({ "name": name, "address": address })).collect::<Vec<serde_json::Value>>(),
+          }));
+        },
+        ("export", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path).with_profile(profile_from(cmd));
+          let format = cmd.value_of("address-book").unwrap().parse().map_err(|err: deployment::error::DeploymentTrackingError| error::CliError::Other(err.to_string()))?;
+          let address_book = vibranium.deployment_address_book().map_err(|err| error::CliError::Other(err.to_string()))?;
+          let rendered = address_book.render(format).map_err(|err| error::CliError::Other(err.to_string()))?;
+
+          for name in &address_book.skipped {
+            eprintln!("Warning: Skipped deployments of {}, as their network has no `chain_id` configured.", name);
           }
-          Ok(())
-        })?
+          match cmd.value_of("output") {
+            Some(file) => {
+              std::fs::write(file, &rendered)?;
+              output.message(format!("Wrote address book with {} entries to {}", address_book.entries.len(), file));
+            },
+            None => output.message(rendered.trim_end()),
+          }
+          let entries: Vec<_> = address_book.entries.iter().map(|entry| json!({
+            "name": entry.name,
+            "address": entry.address,
+            "chain_id": entry.chain_id,
+          })).collect();
+          output.result("deployments export", json!({
+            "entries": entries,
+            "skipped": address_book.skipped,
+            "output": cmd.value_of("output"),
+          }));
+        },
+        ("diff", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path).with_profile(profile_from(cmd));
+          let (from, to) = (cmd.value_of("from").unwrap(), cmd.value_of("to").unwrap());
+          let diff = vibranium.deployment_diff(from, to).map_err(|err| error::CliError::Other(err.to_string()))?;
+
+          for drift in &diff.drifts {
+            output.message(format!("  {}", drift));
+          }
+          if diff.has_drift() {
+            output.message(format!("Found {} difference(s) between {} and {}, {} Smart Contract(s) in sync.", diff.drifts.len(), from, to, diff.in_sync.len()));
+          } else {
+            output.message(format!("Deployments of {} and {} are in sync.", from, to));
+          }
+          let drifts: Vec<_> = diff.drifts.iter().map(|drift| json!({
+            "name": drift.name,
+            "drift": match &drift.drift {
+              deployment::diff::Drift::Missing => "missing",
+              deployment::diff::Drift::Unexpected => "unexpected",
+              deployment::diff::Drift::Bytecode => "bytecode",
+              deployment::diff::Drift::Args(_from, _to) => "args",
+            },
+            "message": drift.to_string(),
+          })).collect();
+          output.result("deployments diff", json!({
+            "from": from,
+            "to": to,
+            "drifts": drifts,
+            "in_sync": diff.in_sync,
+          }));
+        },
+        ("docs", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path).with_profile(profile_from(cmd));
+          let docs = vibranium.deployment_docs().map_err(|err| error::CliError::Other(err.to_string()))?;
+
+          match cmd.value_of("output") {
+            Some(file) => {
+              std::fs::write(file, &docs)?;
+              output.message(format!("Wrote deployment documentation to {}", file));
+            },
+            None => if !output.is_json() {
+              print!("{}", docs);
+            },
+          }
+          output.result("deployments docs", json!({ "markdown": docs, "output": cmd.value_of("output") }));
+        },
+        _ => unreachable!(),
+      }
     },
 
-    ("list", Some(cmd)) => {
+    ("access-report", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path).with_profile(profile_from(cmd)).with_network(network_from(cmd));
+
+      let entries: Vec<AccessEntry> = vibranium.access_report(AccessReportOptions {
+        contract: cmd.value_of("contract").map(str::to_string),
+      })?
+        .into_iter()
+        .filter(|entry| !cmd.is_present("unrestricted") || entry.is_unrestricted())
+        .collect();
+
+      let width = entries.iter().map(|entry| entry.function.len()).max().unwrap_or(0);
+      let mut contract = None;
+      for entry in &entries {
+        if contract != Some(&entry.contract) {
+          output.message(&entry.contract);
+          contract = Some(&entry.contract);
+        }
+        output.message(format!("  {:width$}  {:10}  {}", entry.function, entry.state_mutability, entry.access(), width = width));
+      }
+      let functions: Vec<_> = entries.iter().map(|entry| json!({
+        "contract": entry.contract,
+        "function": entry.function,
+        "state_mutability": entry.state_mutability,
+        "modifiers": entry.modifiers,
+        "inline_checks": entry.inline_checks,
+        "source_found": entry.source_found,
+        "unrestricted": entry.is_unrestricted(),
+      })).collect();
+      output.result("access-report", json!({ "functions": functions }));
+    },
+
+    ("doctor", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path.clone()).with_profile(profile_from(cmd)).with_network(network_from(cmd));
+      let report = vibranium.doctor();
+
+      for check in &report.checks {
+        output.message(check);
+      }
+
+      if cmd.is_present("report") {
+        let file = cmd.value_of("output").map(PathBuf::from).unwrap_or_else(|| path.join(doctor::REPORT_FILE));
+        std::fs::write(&file, report.to_json().map_err(|err| error::CliError::Other(err.to_string()))?)?;
+        output.message(format!("Wrote troubleshooting report to {}", file.to_string_lossy()));
+      }
+      output.result("doctor", json!({ "checks": report.checks }));
+    },
+
+    ("reproduce", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path)
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd))
+        .with_rpc_cassette(rpc_cassette_from(cmd))
+        .with_call_cache(!cmd.is_present("no-cache"));
+      let name = cmd.value_of("contract").unwrap();
+
+      let reproductions = vibranium.reproduce(name)?;
+      for reproduction in &reproductions {
+        if reproduction.is_reproducible() {
+          output.message(format!("{} at {:?}: matches deployed code ({} bytes, metadata ignored)", reproduction.name, reproduction.address, reproduction.compiled_length));
+        } else {
+          output.message(format!("{} at {:?}: differs from deployed code ({} bytes compiled, {} bytes deployed)", reproduction.name, reproduction.address, reproduction.compiled_length, reproduction.deployed_length));
+          for diff in reproduction.diffs.iter().take(MAX_PRINTED_BYTECODE_DIFFS) {
+            output.message(format!("  {}", diff));
+          }
+          if reproduction.diffs.len() > MAX_PRINTED_BYTECODE_DIFFS {
+            output.message(format!("  ... and {} more", reproduction.diffs.len() - MAX_PRINTED_BYTECODE_DIFFS));
+          }
+        }
+      }
+      let results: Vec<_> = reproductions.iter().map(|reproduction| json!({
+        "name": reproduction.name,
+        "address": reproduction.address,
+        "reproducible": reproduction.is_reproducible(),
+        "compiled_length": reproduction.compiled_length,
+        "deployed_length": reproduction.deployed_length,
+        "diffs": reproduction.diffs.iter().map(|diff| diff.to_string()).collect::<Vec<String>>(),
+      })).collect();
+      output.result("reproduce", json!({ "smart_contracts": results }));
+
+      if reproductions.iter().any(|reproduction| !reproduction.is_reproducible()) {
+        Err(error::CliError::Other(format!("Compiled bytecode of {} doesn't match its deployed code.", name)))?
+      }
+    },
+
+    ("children", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path)
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd))
+        .with_read_only(cmd.is_present("read-only"))
+        .with_rpc_cassette(rpc_cassette_from(cmd))
+        .with_call_cache(!cmd.is_present("no-cache"));
+
+      let report = vibranium.discover_children(deployment::children::ChildrenOptions {
+        factory: cmd.value_of("factory").unwrap().to_string(),
+        event: cmd.value_of("event").unwrap().to_string(),
+        arg: cmd.value_of("arg").unwrap().to_string(),
+        collection: cmd.value_of("collection").map(str::to_string),
+      }).map_err(|err| error::CliError::Other(err.to_string()))?;
+      let output = output.with_aliases(aliases_from(cmd, &vibranium, &output));
+
+      for child in &report.discovered {
+        output.message(format!("{:?}", child));
+      }
+      output.message(format!("Discovered {} new Smart Contract(s) created by {} up to block {}, {} tracked in collection {}.", report.discovered.len(), cmd.value_of("factory").unwrap(), report.scanned_to, report.total, report.collection));
+      output.result("children", json!({
+        "collection": report.collection,
+        "factory_address": report.factory_address,
+        "discovered": report.discovered,
+        "total": report.total,
+        "scanned_to": report.scanned_to,
+      }));
+    },
+
+    ("events", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path)
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd))
+        .with_rpc_cassette(rpc_cassette_from(cmd))
+        .with_call_cache(!cmd.is_present("no-cache"));
+
+      let block = |name: &str| -> Result<Option<blockchain::blocks::BlockRef>, error::CliError> {
+        cmd.value_of(name)
+          .map(|block| block.parse().map_err(error::CliError::BlockchainConnectorError))
+          .transpose()
+      };
+      let options = deployment::events::EventsOptions {
+        contract: cmd.value_of("contract").map(str::to_string),
+        event: cmd.value_of("event").map(str::to_string),
+        from_block: block("from-block")?,
+        to_block: block("to-block")?,
+      };
+
+      let output = output.with_aliases(aliases_from(cmd, &vibranium, &output));
+      if cmd.is_present("follow") {
+        vibranium.follow_events(options, |event| output.message(event))
+          .map_err(|err| error::CliError::Other(err.to_string()))?;
+      } else {
+        let events = vibranium.events(options).map_err(|err| error::CliError::Other(err.to_string()))?;
+        if events.is_empty() {
+          output.message("No events found.");
+        }
+        for event in &events {
+          output.message(event);
+        }
+        let events: Vec<_> = events.iter().map(|event| json!({
+          "contract": event.contract,
+          "name": event.name,
+          "address": event.address,
+          "block_number": event.block_number,
+          "transaction_hash": event.transaction_hash,
+          "params": event.params.iter().map(|(name, value)| json!({ "name": name, "value": value })).collect::<Vec<_>>(),
+        })).collect();
+        output.result("events", json!({ "events": events }));
+      }
+    },
+
+    ("tx", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path)
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd))
+        .with_rpc_cassette(rpc_cassette_from(cmd))
+        .with_call_cache(!cmd.is_present("no-cache"));
+      let hash = cmd.value_of("hash").unwrap();
+      let hash = hash.trim_start_matches("0x").parse().map_err(|_err| error::CliError::Other(format!("Invalid transaction hash '{}'", hash)))?;
+
+      let inspection = vibranium.inspect_transaction(hash).map_err(|err| error::CliError::Other(err.to_string()))?;
+      let aliases = aliases_from(cmd, &vibranium, &output);
+      let output = output.with_aliases(aliases.clone());
+      let unknown = || "unknown".to_string();
+      let status = match inspection.succeeded {
+        Some(true) => "succeeded".to_string(),
+        Some(false) => "failed".to_string(),
+        None => "pending".to_string(),
+      };
+      let to = match (&inspection.to, &inspection.contract) {
+        (Some(to), Some(contract)) if aliases.as_ref().and_then(|aliases| aliases.name(to)).is_none() => format!("{:?} ({})", to, contract),
+        (Some(to), Some(_contract)) => format!("{:?}", to),
+        (Some(to), None) => format!("{:?}", to),
+        (None, _) => "Smart Contract creation".to_string(),
+      };
+
+      output.message(format!("Transaction {:?}:", inspection.hash));
+      output.message(format!("  Status:   {}", status));
+      output.message(format!("  Block:    {}", inspection.block_number.map(|block_number| block_number.to_string()).unwrap_or_else(unknown)));
+      output.message(format!("  From:     {:?}", inspection.from));
+      output.message(format!("  To:       {}", to));
+      output.message(format!("  Gas used: {}", inspection.gas_used.map(|gas_used| gas_used.to_string()).unwrap_or_else(unknown)));
+      if inspection.to.is_some() {
+        output.message(format!("  Call:     {}", inspection.call.as_ref().map(|call| call.to_string()).unwrap_or_else(unknown)));
+      }
+      if inspection.succeeded == Some(false) {
+        output.message(format!("  Reverted: {}", inspection.revert.as_ref().map(|revert| revert.to_string()).unwrap_or_else(unknown)));
+      }
+      if !inspection.events.is_empty() || inspection.unknown_logs > 0 {
+        output.message("  Events:");
+        for event in &inspection.events {
+          output.message(format!("    {:?}: {}", event.address, event));
+        }
+        if inspection.unknown_logs > 0 {
+          output.message(format!("    {} log(s) of unknown events", inspection.unknown_logs));
+        }
+      }
+      output.result("tx", json!({
+        "hash": inspection.hash,
+        "from": inspection.from,
+        "to": inspection.to,
+        "contract": inspection.contract,
+        "block_number": inspection.block_number,
+        "succeeded": inspection.succeeded,
+        "gas_used": inspection.gas_used,
+        "call": inspection.call.map(|call| call.to_string()),
+        "events": inspection.events.iter().map(|event| event.to_string()).collect::<Vec<String>>(),
+        "unknown_logs": inspection.unknown_logs,
+        "revert": inspection.revert.map(|revert| revert.to_string()),
+      }));
+    },
+
+    ("verify", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path)
+        .with_read_only(cmd.is_present("read-only"))
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd))
+        .with_rpc_cassette(rpc_cassette_from(cmd))
+        .with_call_cache(!cmd.is_present("no-cache"));
+
+      let service = if cmd.is_present("etherscan") {
+        Some(VerificationService::Etherscan)
+      } else if cmd.is_present("sourcify") {
+        Some(VerificationService::Sourcify)
+      } else {
+        None
+      };
+
+      let verifications = match (service, tags_from(cmd)) {
+        (Some(service), _) => {
+          let verification = vibranium.verify_source(cmd.value_of("contract").unwrap(), service)?;
+          output.message(&verification);
+          output.result("verify", json!({
+            "service": verification.service.to_string(),
+            "smart_contracts": [{
+              "name": verification.name,
+              "address": verification.address,
+              "status": match verification.status {
+                SourceVerificationStatus::Verified => "verified",
+                SourceVerificationStatus::AlreadyVerified => "already-verified",
+              },
+            }],
+          }));
+          vec![]
+        },
+        (None, Some(tags)) => vibranium.verify_tagged(&tags)?,
+        (None, None) => vibranium.verify(cmd.value_of("contract"))?,
+      };
+      if verifications.is_empty() && service.is_none() {
+        output.message("No Smart Contract data for currently connected chain has been tracked.");
+      }
+      for verification in &verifications {
+        output.message(verification);
+        if let VerificationStatus::Drifted(diffs) = &verification.status {
+          for diff in diffs.iter().take(MAX_PRINTED_BYTECODE_DIFFS) {
+            output.message(format!("  {}", diff));
+          }
+          if diffs.len() > MAX_PRINTED_BYTECODE_DIFFS {
+            output.message(format!("  ... and {} more", diffs.len() - MAX_PRINTED_BYTECODE_DIFFS));
+          }
+        }
+      }
+      if service.is_none() {
+        let results: Vec<_> = verifications.iter().map(|verification| json!({
+          "name": verification.name,
+          "address": verification.address,
+          "status": match &verification.status {
+            VerificationStatus::Verified => "verified",
+            VerificationStatus::Drifted(_diffs) => "drifted",
+            VerificationStatus::Missing => "missing",
+            VerificationStatus::MissingArtifact(_artifact) => "missing-artifact",
+          },
+          "diffs": match &verification.status {
+            VerificationStatus::Drifted(diffs) => diffs.iter().map(|diff| diff.to_string()).collect(),
+            _ => vec![],
+          },
+        })).collect();
+        output.result("verify", json!({ "service": null, "smart_contracts": results }));
+      }
+
+      let unverified = verifications.iter().filter(|verification| !verification.is_verified()).count();
+      if unverified > 0 {
+        Err(error::CliError::Other(format!("Couldn't verify {} of {} tracked deployment(s).", unverified, verifications.len())))?
+      }
+    },
+
+    ("interface", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path).with_profile(profile_from(cmd)).with_network(network_from(cmd));
+
+      let interface = vibranium.generate_interface(InterfaceOptions {
+        contract: cmd.value_of("contract").unwrap().to_string(),
+        name: cmd.value_of("name").map(str::to_string),
+      })?;
+
+      if let Some(abi_output) = cmd.value_of("abi-output") {
+        std::fs::write(abi_output, interface.abi_json()?)?;
+      }
+
+      match cmd.value_of("output") {
+        Some(file) => {
+          std::fs::write(file, &interface.source)?;
+          output.message(format!("Generated interface {} in {}", interface.name, file));
+        },
+        None => if !output.is_json() {
+          print!("{}", interface.source);
+        },
+      }
+      output.result("interface", json!({ "name": interface.name, "source": interface.source, "abi": interface.abi }));
+    },
+
+    ("check-interface", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path)
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd))
+        .with_rpc_cassette(rpc_cassette_from(cmd))
+        .with_call_cache(!cmd.is_present("no-cache"));
+
+      let report = vibranium.check_interface(cmd.value_of("contract").unwrap(), cmd.value_of("erc").unwrap())?;
+      output.result("check-interface", json!({
+        "contract": report.contract,
+        "standard": report.standard,
+        "compliant": report.is_compliant(),
+        "findings": report.findings.iter().map(|finding| finding.to_string()).collect::<Vec<String>>(),
+      }));
+      if report.is_compliant() {
+        output.message(format!("{} implements {}.", report.contract, report.standard));
+      } else {
+        for finding in &report.findings {
+          output.message(format!("  {}", finding));
+        }
+        Err(error::CliError::Other(format!("{} doesn't implement {}, found {} issue(s).", report.contract, report.standard, report.findings.len())))?
+      }
+    },
+
+    ("history", Some(cmd)) => {
+      match cmd.subcommand() {
+        ("list", Some(cmd)) => {
+          let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+          let vibranium = Vibranium::new(path);
+          let entries = vibranium.command_history()?.entries()?;
+
+          if entries.is_empty() {
+            output.message("No commands have been recorded yet.");
+          }
+          for (i, entry) in entries.iter().enumerate() {
+            output.message(format!("({}) vibranium {}", i + 1, entry.args.join(" ")));
+            if let Some(network) = &entry.network {
+              output.message(format!("      Network: {}", network));
+            }
+            if let Some(contracts) = &entry.contracts {
+              output.message(format!("      Smart Contracts: {}", contracts.join(", ")));
+            }
+          }
+          output.result("history list", json!({ "entries": entries }));
+        },
+        _ => unreachable!(),
+      }
+    },
+
+    ("rerun", Some(cmd)) => {
       let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
       let vibranium = Vibranium::new(path);
-      let tracking_data = vibranium.get_tracking_data().map_err(|err| error::CliError::Other(err.to_string()))?;
+      let number = value_t!(cmd, "number", usize).unwrap_or(1);
+      let entry = vibranium.command_history()?.entry(number)?;
+      let mut rerun_args = vec![args[0].clone()];
+      rerun_args.extend(entry.args.clone());
 
-      match tracking_data {
-        None => println!("No Smart Contract data for currently connected chain has been tracked."),
-        Some(data) => {
-          println!("Deployed Smart Contracts:");
-          for (_hash, smart_contract) in data {
-            println!("  {:?}: {}", smart_contract.address, smart_contract.name);
+      // Arguments are replayed as they were given, so they might resolve to
+      // a different network or different Smart Contracts by now.
+      if let Ok(matches) = app.clone().get_matches_from_safe(rerun_args.clone()) {
+        if let (_name, Some(rerun_cmd)) = matches.subcommand() {
+          let changes = Vibranium::new(pathbuf_from_or_current_dir(project_path_of(rerun_cmd))?)
+            .with_profile(profile_from(rerun_cmd))
+            .with_network(network_from(rerun_cmd))
+            .resolve_command(entry.args.clone())
+            .map(|current| entry.changes(&current))
+            .unwrap_or_default();
+          if !changes.is_empty() && !cmd.is_present("force") {
+            Err(error::CliError::Other(format!("Command #{} resolves differently than when it ran:\n  {}\nPass --force to rerun it anyway.", number, changes.join("\n  "))))?
+          }
+        }
+      }
+
+      println!("Rerunning: vibranium {}", entry.args.join(" "));
+      return run(rerun_args);
+    },
+
+    ("stats", Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path);
+      let days = value_t!(cmd, "days", u64).unwrap_or(stats::DEFAULT_WINDOW_DAYS);
+      let usage_stats = vibranium.usage_stats()?;
+
+      if !usage_stats.is_enabled() {
+        output.message("Usage statistics are disabled. Enable them by setting `stats = true` in vibranium.toml.");
+      }
+
+      let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+      let summary = usage_stats.summary(now, days)?;
+      if summary.days.is_empty() {
+        output.message(format!("No runs have been recorded in the last {} day(s).", days));
+      } else {
+        output.message(format!("Usage statistics of the last {} day(s):\n", days));
+        for day in &summary.days {
+          output.message(format!("  {}", day));
+        }
+        output.message("");
+        match summary.average_compile_ms() {
+          Some(average) => output.message(format!("Compiled {} time(s), taking {}ms on average.", summary.compile_runs(), average)),
+          None => output.message("Compiled 0 times."),
+        }
+        output.message(format!("Deployed {} time(s), spending {} gas.", summary.deploy_runs(), summary.gas_used()));
+      }
+      let daily: Vec<_> = summary.days.iter().map(|day| json!({
+        "day": day.date(),
+        "compile_runs": day.compile_runs,
+        "average_compile_ms": day.average_compile_ms(),
+        "deploy_runs": day.deploy_runs,
+        "failed_runs": day.failed_runs,
+        "gas_used": day.gas_used,
+      })).collect();
+      output.result("stats", json!({ "enabled": usage_stats.is_enabled(), "window_days": days, "days": daily }));
+    },
+
+    ("package", Some(cmd)) => {
+      println!("Packaging release...");
+      let path = pathbuf_from_or_current_dir(cmd.value_of("path"))?;
+      let vibranium = Vibranium::new(path);
+
+      let package_path = vibranium.package(PackageOptions {
+        release: cmd.value_of("release").unwrap().to_string(),
+        output: cmd.value_of("output").map(PathBuf::from),
+      })?;
+
+      println!("Created {}", package_path.to_string_lossy());
+      println!("Done.");
+    },
+
+    ("help", Some(cmd)) => {
+      match cmd.value_of("topic") {
+        None => {
+          app.print_help()?;
+          println!();
+        },
+        Some(name) => {
+          if let Some(topic) = help::find_topic(name) {
+            print!("{}", topic.content);
+          } else if help::COMMANDS.iter().any(|command| command.name == name) {
+            if let Err(err) = app.clone().get_matches_from_safe(vec![crate_name!(), name, "--help"]) {
+              println!("{}", err.message);
+            }
+          } else {
+            Err(error::CliError::Other(format!("Unknown help topic '{}'.\n\n{}", name, help::render_topics())))?
           }
         }
       }
@@ -363,13 +2252,331 @@ fn run() -> Result<(), Error> {
     }
   };
 
+  match matches.subcommand() {
+    ("help", _) | ("history", _) | ("rerun", _) => {},
+    (_, Some(cmd)) => {
+      let path = pathbuf_from_or_current_dir(project_path_of(cmd))?;
+      let vibranium = Vibranium::new(path)
+        .with_profile(profile_from(cmd))
+        .with_network(network_from(cmd));
+      if let Err(err) = vibranium.record_command(absolute_path_args(&args[1..])?) {
+        eprintln!("Warning: {}", err);
+      }
+    },
+    _ => {},
+  }
+
   Ok(())
 }
 
+// Nested subcommands like `deployments tag` take the project path themselves.
+// Runs `cycle` right away and again whenever sources change, printing the
+// summary it returns. Failing cycles don't end the watch.
+fn watch<F>(vibranium: &Vibranium, cycle: F) -> Result<(), error::CliError>
+  where F: Fn() -> Result<String, error::CliError>
+{
+  let mut watcher = vibranium.watch_sources().map_err(error::CliError::CompilationError)?;
+  loop {
+    let started = Instant::now();
+    match cycle() {
+      Ok(summary) => println!("[watch] {} ({:.2}s)", summary, started.elapsed().as_secs_f64()),
+      Err(err) => eprintln!("[watch] Failed after {:.2}s: {}", started.elapsed().as_secs_f64(), err),
+    }
+    println!("[watch] Waiting for changes...");
+    let changed = watcher.wait_for_changes().map_err(error::CliError::CompilationError)?;
+    println!("[watch] Changed: {}", changed.join(", "));
+  }
+}
+
+// In JSON output, the compiler's own output is part of the result instead.
+fn compile_project(vibranium: &Vibranium, daemon: Option<&DaemonClient>, config: CompilerConfig, output: &output::Output) -> Result<String, error::CliError> {
+  let (stdout, stderr) = match daemon {
+    Some(daemon) => daemon.compile(config).map_err(|err| error::CliError::Other(err.to_string()))?,
+    None => {
+      let compiler_output = vibranium.compile(config).map_err(error::CliError::CompilationError)?;
+      (String::from_utf8_lossy(&compiler_output.stdout).to_string(), String::from_utf8_lossy(&compiler_output.stderr).to_string())
+    },
+  };
+  if !output.is_json() {
+    if !stderr.is_empty() {
+      io::stderr().write_all(stderr.as_bytes()).unwrap();
+    }
+    io::stdout().write_all(stdout.as_bytes()).unwrap();
+  }
+
+  let violations = vibranium.check_size_budgets().map_err(error::CliError::CompilationError)?;
+  for violation in &violations {
+    eprintln!("Warning: Smart Contract exceeds its size budget: {}", violation);
+  }
+  let diagnostics = vibranium.run_static_checks().map_err(error::CliError::CompilationError)?;
+  for diagnostic in &diagnostics {
+    eprintln!("{}", diagnostic);
+  }
+  output.result("compile", json!({
+    "stdout": stdout,
+    "stderr": stderr,
+    "size_budget_violations": violations.iter().map(ToString::to_string).collect::<Vec<String>>(),
+    "diagnostics": diagnostics.iter().map(ToString::to_string).collect::<Vec<String>>(),
+  }));
+  Ok(format!("Compiled with {} warning(s)", violations.len() + diagnostics.len()))
+}
+
+fn deploy_project(vibranium: &Vibranium, daemon: Option<&DaemonClient>, options: DeployOptions, report_gas: bool, output: &output::Output) -> Result<String, error::CliError> {
+  let contracts = match daemon {
+    Some(daemon) => daemon.deploy(options).map_err(|err| error::CliError::Other(err.to_string()))?,
+    None => vibranium.deploy(options).map_err(|err| {
+      match err {
+        deployment::error::DeploymentError::Connection(connector_error) => error::CliError::BlockchainConnectorError(connector_error),
+        deployment::error::DeploymentError::MissingConfig => error::CliError::DeploymentError(err),
+        deployment::error::DeploymentError::ReadOnlyMode => error::CliError::DeploymentError(err),
+        deployment::error::DeploymentError::EmbeddedSecrets(_) => error::CliError::DeploymentError(err),
+        deployment::error::DeploymentError::Policy(_) => error::CliError::DeploymentError(err),
+        deployment::error::DeploymentError::PolicyViolation(_) => error::CliError::DeploymentError(err),
+        deployment::error::DeploymentError::PartialDeployment(_) => error::CliError::DeploymentError(err),
+        _ => error::CliError::Other(err.to_string()),
+      }
+    })?,
+  };
+
+  if contracts.is_empty() {
+    output.message("Nothing to deploy.");
+    output.result("deploy", json!({ "smart_contracts": [], "policy": null }));
+    return Ok("Nothing to deploy".to_string());
+  }
+
+  output.message("");
+  let skipped = contracts.values().filter(|data| data.3).count();
+  for data in contracts.values() {
+    if data.3 {
+      output.message(format!("  {:?}: {} (skipped) [Source: {}]", data.1, data.0, data.2));
+    } else {
+      output.message(format!("  {:?}: {} [Source: {}]", data.1, data.0, data.2));
+    }
+  }
+  let report = vibranium.check_deployment_policy().map_err(error::CliError::DeploymentError)?;
+  if let Some(report) = &report {
+    output.message("");
+    output.message("Policy compliance:");
+    for check in &report.checks {
+      output.message(format!("  {}", check));
+    }
+  }
+  let gas_report = if report_gas {
+    let gas_report = vibranium.gas_report().map_err(error::CliError::DeploymentError)?;
+    output.message("");
+    print_gas_report(&gas_report, output);
+    Some(gas_report)
+  } else {
+    None
+  };
+  output.message("");
+  output.message("Done.");
+
+  let smart_contracts: Vec<_> = contracts.values().map(|data| json!({
+    "name": data.0,
+    "address": data.1,
+    "source": data.2,
+    "skipped": data.3,
+  })).collect();
+  output.result("deploy", json!({
+    "smart_contracts": smart_contracts,
+    "policy": report.map(|report| report.checks.iter().map(ToString::to_string).collect::<Vec<String>>()),
+    "gas": gas_report.as_ref().map(gas_report_json),
+  }));
+  Ok(format!("Deployed {} Smart Contract(s), skipped {}", contracts.len() - skipped, skipped))
+}
+
+// Estimates that couldn't be made, because the transaction would fail, are
+// marked as such.
+fn print_gas_report(report: &deployment::gas::GasReport, output: &output::Output) {
+  fn estimated<T: ToString>(gas: Option<T>) -> String {
+    gas.map(|gas| gas.to_string()).unwrap_or_else(|| "fails".to_string())
+  }
+  let width = report.estimates.iter()
+    .flat_map(|estimate| std::iter::once(estimate.name.len()).chain(estimate.functions.iter().map(|function| function.signature.len() + 2)))
+    .chain(std::iter::once("Smart Contract".len()))
+    .max()
+    .unwrap_or_default();
+
+  output.message(format!("  {:width$}  {:>10}  {:>10}", "Smart Contract", "Gas", "Gas limit", width = width));
+  for estimate in &report.estimates {
+    output.message(format!("  {:width$}  {:>10}  {:>10}", estimate.name, estimated(estimate.deployment), estimate.gas_limit, width = width));
+    for function in &estimate.functions {
+      output.message(format!("    {:width$}  {:>10}", function.signature, estimated(function.gas), width = width - 2));
+    }
+  }
+  if let Some(block_gas_limit) = report.block_gas_limit {
+    output.message(format!("\nBlock gas limit: {}", block_gas_limit));
+  }
+  for violation in report.violations() {
+    eprintln!("Warning: {}", violation);
+  }
+}
+
+fn gas_report_json(report: &deployment::gas::GasReport) -> serde_json::Value {
+  let estimates: Vec<_> = report.estimates.iter().map(|estimate| json!({
+    "name": estimate.name,
+    "address": estimate.address,
+    "deployment": estimate.deployment.map(|gas| gas.low_u64()),
+    "gas_limit": estimate.gas_limit.low_u64(),
+    "functions": estimate.functions.iter().map(|function| json!({
+      "signature": function.signature,
+      "gas": function.gas.map(|gas| gas.low_u64()),
+    })).collect::<Vec<_>>(),
+  })).collect();
+  json!({
+    "block_gas_limit": report.block_gas_limit.map(|gas| gas.low_u64()),
+    "estimates": estimates,
+    "violations": report.violations().iter().map(ToString::to_string).collect::<Vec<String>>(),
+  })
+}
+
+fn daemon_json(daemon: &DaemonState) -> serde_json::Value {
+  json!({
+    "pid": daemon.pid,
+    "started_at": daemon.started_at,
+    "uptime": daemon.uptime(),
+  })
+}
+
+fn node_json(node: &blockchain::supervisor::ManagedNode) -> serde_json::Value {
+  json!({
+    "pid": node.pid,
+    "client": node.client,
+    "network": node.network,
+    "started_at": node.started_at,
+    "uptime": node.uptime(),
+    "rpc_port": node.rpc_port,
+    "ws_port": node.ws_port,
+  })
+}
+
+// Dry runs print the changed lines of vibranium.toml along with a few lines
+// around them, colored unless stdout isn't a terminal or NO_COLOR is set.
+fn print_config_change(change: &config::ConfigChange, options: config::WriteOptions) {
+  if let Some(backup) = &change.backup {
+    println!("Backed up vibranium.toml to {}.", backup.to_string_lossy());
+  }
+  if !options.dry_run {
+    return;
+  }
+  if change.is_empty() {
+    println!("No changes to vibranium.toml.");
+    return;
+  }
+
+  let colored = io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+  let paint = |code: &str, line: String| if colored { format!("\x1b[{}m{}\x1b[0m", code, line) } else { line };
+  let lines = config::diff::diff_lines(&change.before, &change.after);
+  let is_near_change = |index: usize| lines[index.saturating_sub(CONFIG_DIFF_CONTEXT)..(index + CONFIG_DIFF_CONTEXT + 1).min(lines.len())]
+    .iter()
+    .any(config::diff::DiffLine::is_change);
+
+  let mut skipped = false;
+  for (index, line) in lines.iter().enumerate() {
+    if !is_near_change(index) {
+      skipped = true;
+      continue;
+    }
+    if skipped {
+      println!("{}", paint("36", "...".to_string()));
+      skipped = false;
+    }
+    match line {
+      config::diff::DiffLine::Unchanged(line) => println!("  {}", line),
+      config::diff::DiffLine::Added(line) => println!("{}", paint("32", format!("+ {}", line))),
+      config::diff::DiffLine::Removed(line) => println!("{}", paint("31", format!("- {}", line))),
+    }
+  }
+  if skipped {
+    println!("{}", paint("36", "...".to_string()));
+  }
+  println!("Dry run, vibranium.toml hasn't been written.");
+}
+
+fn project_path_of<'a>(cmd: &'a clap::ArgMatches) -> Option<&'a str> {
+  cmd.value_of("path").or_else(|| cmd.subcommand().1.and_then(project_path_of))
+}
+
+// Subcommands without a help entry don't list any examples.
+fn subcommand<'a, 'b>(name: &'a str, help_texts: &'b HashMap<&str, String>) -> App<'a, 'b> {
+  match help_texts.get(name) {
+    Some(examples) => SubCommand::with_name(name).after_help(examples.as_str()),
+    None => SubCommand::with_name(name),
+  }
+}
+
+fn parse_tags(values: Option<clap::Values>) -> Result<BTreeMap<String, String>, error::CliError> {
+  values.map(|values| values.collect::<Vec<&str>>()).unwrap_or_default()
+    .into_iter()
+    .map(|tag| deployment::tracker::parse_tag(tag).map_err(|err| error::CliError::Other(err.to_string())))
+    .collect()
+}
+
+fn tags_from(cmd: &clap::ArgMatches) -> Option<Vec<String>> {
+  cmd.values_of("tag").map(|tags| tags.map(std::string::ToString::to_string).collect())
+}
+
+fn warn_about_secrets(vibranium: &Vibranium) {
+  if let Ok(findings) = vibranium.scan_secrets() {
+    for finding in findings {
+      eprintln!("Warning: {}. Consider referencing it from the environment via `${{env.NAME}}`.", finding);
+    }
+  }
+}
+
+// Names of known addresses are only looked up for human readable output, and
+// not finding any isn't worth failing a command over.
+fn aliases_from(cmd: &clap::ArgMatches, vibranium: &Vibranium, output: &output::Output) -> Option<AddressAliases> {
+  if cmd.is_present("no-aliases") || output.is_json() {
+    return None;
+  }
+  vibranium.address_aliases().ok().filter(|aliases| !aliases.is_empty())
+}
+
+fn profile_from(cmd: &clap::ArgMatches) -> Option<String> {
+  cmd.value_of("profile").map(std::string::ToString::to_string)
+}
+
+fn network_from(cmd: &clap::ArgMatches) -> Option<String> {
+  cmd.value_of("network").map(std::string::ToString::to_string)
+}
+
+fn rpc_cassette_from(cmd: &clap::ArgMatches) -> Option<blockchain::connector::cassette::RpcCassette> {
+  if let Some(path) = cmd.value_of("record-rpc") {
+    Some(blockchain::connector::cassette::RpcCassette::Record(PathBuf::from(path)))
+  } else {
+    cmd.value_of("replay-rpc").map(|path| blockchain::connector::cassette::RpcCassette::Replay(PathBuf::from(path)))
+  }
+}
+
 fn pathbuf_from_or_current_dir(path: Option<&str>) -> Result<PathBuf, std::io::Error> {
   path.map(|p| Ok(PathBuf::from(p))).unwrap_or_else(env::current_dir)
 }
 
+// Recorded commands might be rerun from another directory, so project paths
+// are made absolute. Arguments after `--` are passed on to other programs.
+fn absolute_path_args(args: &[String]) -> Result<Vec<String>, std::io::Error> {
+  let current_dir = env::current_dir()?;
+  let absolute = |path: &str| current_dir.join(path).to_string_lossy().to_string();
+  let mut absolute_args = Vec::with_capacity(args.len());
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    if arg == "--" {
+      absolute_args.push(arg.to_owned());
+      absolute_args.extend(args.by_ref().cloned());
+    } else if arg == "--path" || arg == "-p" {
+      absolute_args.push(arg.to_owned());
+      absolute_args.extend(args.next().map(|path| absolute(path)));
+    } else if let Some(path) = arg.strip_prefix("--path=") {
+      absolute_args.push(format!("--path={}", absolute(path)));
+    } else {
+      absolute_args.push(arg.to_owned());
+    }
+  }
+  Ok(absolute_args)
+}
+
 fn is_multi_value_arg(value: &str) -> bool {
   value.starts_with('[') && value.ends_with(']')
 }
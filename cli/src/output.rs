@@ -0,0 +1,153 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::CliError;
+use vibranium::deployment::aliases::AddressAliases;
+
+// With `--format json`, commands print a single JSON document to stdout
+// instead of human readable text:
+//
+//   { "version": 1, "command": "node status", "ok": true, "data": { ... } }
+//   { "version": 1, "ok": false, "error": { "kind": "connection", "message": "..." } }
+//
+// Fields of `data` are only ever added. Renaming or removing any of them
+// requires bumping SCHEMA_VERSION. Warnings keep going to stderr.
+pub const SCHEMA_VERSION: u64 = 1;
+
+// Commands that emit structured output. Nested subcommands are separated by
+// a space.
+pub const JSON_COMMANDS: &[&str] = &[
+  "access-report",
+  "accounts",
+  "chain reset",
+  "chain revert",
+  "chain snapshot",
+  "check-interface",
+  "children",
+  "compile",
+  "daemon start",
+  "daemon status",
+  "daemon stop",
+  "deploy",
+  "deployments args",
+  "deployments clear",
+  "deployments diff",
+  "deployments docs",
+  "deployments export",
+  "deployments list",
+  "deployments rekey",
+  "deployments remove",
+  "deployments show",
+  "deployments tag",
+  "doctor",
+  "events",
+  "gas",
+  "history list",
+  "interface",
+  "list",
+  "node start",
+  "node status",
+  "node stop",
+  "reproduce",
+  "stats",
+  "test",
+  "tx",
+  "upgrade",
+  "verify",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+  Text,
+  Json,
+}
+
+impl FromStr for OutputFormat {
+  type Err = CliError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "text" => Ok(OutputFormat::Text),
+      "json" => Ok(OutputFormat::Json),
+      _ => Err(CliError::Other(format!("Unknown output format '{}'", s))),
+    }
+  }
+}
+
+pub struct Output {
+  format: OutputFormat,
+  aliases: Option<AddressAliases>,
+}
+
+impl Output {
+  pub fn new(format: OutputFormat) -> Output {
+    Output {
+      format,
+      aliases: None,
+    }
+  }
+
+  // Messages of the returned output show the names of known addresses along
+  // with them. JSON output keeps bare addresses.
+  pub fn with_aliases(&self, aliases: Option<AddressAliases>) -> Output {
+    Output {
+      format: self.format,
+      aliases,
+    }
+  }
+
+  pub fn is_json(&self) -> bool {
+    self.format == OutputFormat::Json
+  }
+
+  // Human readable messages are left out of JSON output.
+  pub fn message<T: fmt::Display>(&self, message: T) {
+    if self.is_json() {
+      return;
+    }
+    match &self.aliases {
+      Some(aliases) => println!("{}", aliases.annotate(&message.to_string())),
+      None => println!("{}", message),
+    }
+  }
+
+  pub fn result(&self, command: &str, data: serde_json::Value) {
+    if self.is_json() {
+      println!("{}", serde_json::json!({
+        "version": SCHEMA_VERSION,
+        "command": command,
+        "ok": true,
+        "data": data,
+      }));
+    }
+  }
+}
+
+pub fn print_error(err: &(dyn Error + 'static)) {
+  let kind = match err.downcast_ref::<CliError>() {
+    Some(CliError::CompilationError(_)) => "compilation",
+    Some(CliError::ConfigurationSetError(_)) | Some(CliError::ConfigurationDeleteError(_)) => "configuration",
+    Some(CliError::BlockchainError(_)) => "node",
+    Some(CliError::BlockchainConnectorError(_)) => "connection",
+    Some(CliError::DeploymentError(_)) => "deployment",
+    Some(CliError::Other(_)) | None => "other",
+  };
+  println!("{}", serde_json::json!({
+    "version": SCHEMA_VERSION,
+    "ok": false,
+    "error": {
+      "kind": kind,
+      "message": err.to_string(),
+    },
+  }));
+}
+
+// Errors can happen before the arguments have been parsed, so the format is
+// looked up directly.
+pub fn format_from_args(args: &[String]) -> OutputFormat {
+  let format = args.iter()
+    .position(|arg| arg == "--format")
+    .and_then(|position| args.get(position + 1).map(String::as_str))
+    .or_else(|| args.iter().find_map(|arg| arg.strip_prefix("--format=")));
+  format.and_then(|format| format.parse().ok()).unwrap_or(OutputFormat::Text)
+}
@@ -28,6 +28,7 @@ fn setup_vibranium_project(config: Option<ProjectConfig>) -> Result<(TempDir, Pa
 
     let mut config_file = OpenOptions::new()
       .write(true)
+      .truncate(true)
       .open(&project_path.join("vibranium.toml")).unwrap();
     config_file.write_all(config_toml.as_bytes()).unwrap();
   }
@@ -78,10 +79,13 @@ fn set_configuration(config: &str, value: &str, project_path: &PathBuf) -> Resul
 mod init_cmd {
 
   use std::process::Command;
+  use std::fs;
   use assert_cmd::prelude::*;
   use predicates::prelude::*;
+  use tempfile::tempdir;
   
   use super::setup_vibranium_project;
+  use super::set_configurations;
   use super::read_config;
 
   #[test]
@@ -135,6 +139,75 @@ mod init_cmd {
     tmp_dir.close()?;
     Ok(())
   }
+
+  #[test]
+  fn it_should_scaffold_frontend() -> Result<(), Box<std::error::Error>> {
+    let tmp_dir = tempdir()?;
+    let project_path = tmp_dir.path().join("test_dapp");
+    fs::create_dir(&project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("init")
+        .arg("--with-frontend")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert().success();
+
+    assert!(project_path.join("frontend").join("package.json").is_file());
+    assert!(project_path.join("frontend").join("index.html").is_file());
+    let main_js = fs::read_to_string(project_path.join("frontend").join("src").join("main.js"))?;
+    assert!(main_js.contains("'../../artifacts/*.abi'"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_generate_package_json_and_record_package_manager() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    set_configurations(vec![("compiler.cmd", "solcjs")], &project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("init")
+        .arg("--package-manager")
+        .arg("pnpm")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+      .success()
+      .stdout(predicate::str::contains("Generated package.json"));
+
+    let package_json = fs::read_to_string(project_path.join("package.json"))?;
+    assert!(package_json.contains("\"solc\": \"^0.5.0\""));
+    assert_eq!(read_config(&project_path)?.package_manager, Some("pnpm".to_string()));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_on_init_with_unsupported_package_manager() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("init")
+        .arg("--install")
+        .arg("--package-manager")
+        .arg("bower")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+      .failure()
+      .stderr(predicate::str::contains("Unsupported package manager: bower"));
+
+    assert!(!project_path.join("package.json").exists());
+
+    tmp_dir.close()?;
+    Ok(())
+  }
 }
 
 #[cfg(test)]
@@ -174,6 +247,31 @@ mod reset_cmd {
     Ok(())
   }
 
+  #[test]
+  fn it_should_fail_on_reset_if_project_is_read_only() -> Result<(), Box<std::error::Error>> {
+
+    let config = ProjectConfig {
+      read_only: Some(true),
+      ..ProjectConfig::default()
+    };
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("reset")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("disabled in read-only mode"));
+
+    assert!(project_path.join(".vibranium").exists());
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
   #[test]
   fn it_should_reset_project() -> Result<(), Box<std::error::Error>> {
 
@@ -266,10 +364,19 @@ mod reset_cmd {
     let contract_name = "SimpleTestContract";
 
     config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
       gas_limit: None,
       gas_price: None,
       tx_confirmations: None,
       tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
       smart_contracts: vec![
         SmartContractConfig {
           name: contract_name.to_string(),
@@ -282,6 +389,8 @@ mod reset_cmd {
           gas_price: None,
           abi_path: None,
           bytecode_path: None,
+          tags: None,
+          proxy: None,
         },
       ],
     });
@@ -418,6 +527,28 @@ mod config_cmd {
     tmp_dir.close()?;
     Ok(())
   }
+
+  #[test]
+  fn it_should_print_diff_without_writing_config_on_dry_run() -> Result<(), Box<std::error::Error>> {
+
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("config")
+        .arg("sources.artifacts")
+        .arg("foo")
+        .arg("--dry-run")
+        .arg("--path")
+        .arg(&project_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("- artifacts = \"artifacts\"\n+ artifacts = \"foo\""));
+
+    let config = read_config(&project_path)?;
+    assert_eq!(config.sources.artifacts, "artifacts");
+    tmp_dir.close()?;
+    Ok(())
+  }
 }
 
 #[cfg(test)]
@@ -427,9 +558,36 @@ mod compile_cmd {
   use assert_cmd::prelude::*;
   use predicates::prelude::*;
 
+  use std::collections::BTreeMap;
+  use std::fs::{self, OpenOptions};
+  use std::io::Write;
+  use std::path::PathBuf;
+  use vibranium::config::{CompilerWarningsConfig, ProjectConfig, ProjectCmdExecutionConfig};
+
   use super::setup_vibranium_project;
   use super::set_configuration;
   use super::set_configurations;
+  use super::create_test_artifact;
+  use super::create_test_contract;
+
+  fn config_with_size_budget(max_size: usize, enforcement: Option<&str>) -> ProjectConfig {
+    let mut budgets = BTreeMap::new();
+    budgets.insert("SimpleTestContract".to_string(), max_size);
+
+    ProjectConfig {
+      compiler: Some(ProjectCmdExecutionConfig {
+        cmd: Some("true".to_string()),
+        options: Some(vec!["--noop".to_string()]),
+        max_size_enforcement: enforcement.map(str::to_string),
+        artifact_format: None,
+        max_size: Some(budgets),
+        checks: None,
+        warnings: None,
+        version: None,
+      }),
+      ..ProjectConfig::default()
+    }
+  }
 
   #[test]
   fn it_should_fail_when_given_compiler_option_is_not_supported_and_no_compiler_options_specificed() -> Result<(), Box<std::error::Error>> {
@@ -454,6 +612,27 @@ mod compile_cmd {
     Ok(())
   }
 
+  #[test]
+  fn it_should_fail_when_no_smart_contract_has_given_tag() -> Result<(), Box<std::error::Error>> {
+
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+
+    cmd.arg("compile")
+        .arg("--tag")
+        .arg("unknown")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't find Smart Contracts tagged with any of: unknown"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
   #[test]
   fn it_should_fail_when_given_compiler_is_not_installed() -> Result<(), Box<std::error::Error>> {
 
@@ -537,655 +716,2670 @@ mod compile_cmd {
     tmp_dir.close()?;
     Ok(())
   }
-}
-
-#[cfg(test)]
-mod accounts_cmd {
-
-  use std::process::Command;
-  use assert_cmd::prelude::*;
-  use predicates::prelude::*;
-
-  use super::setup_vibranium_project;
 
   #[test]
-  fn it_should_output_local_blockchains_dev_accounts() -> Result<(), Box<std::error::Error>> {
+  fn it_should_merge_cli_options_into_config_file_compiler_options() -> Result<(), Box<std::error::Error>> {
+
     let (tmp_dir, project_path) = setup_vibranium_project(None)?;
 
+    set_configurations(vec![
+      ("compiler.cmd", "true"),
+      ("compiler.options", "[--abi, --optimize-runs, 200]"),
+    ], &project_path)?;
+
     let mut cmd = Command::main_binary()?;
-    cmd.arg("accounts")
+    cmd.arg("compile")
+        .arg("--verbose")
         .arg("--path")
-        .arg(&project_path);
+        .arg(&project_path)
+        .arg("--")
+        .arg("--optimize-runs")
+        .arg("1000");
 
     cmd.assert()
-      .success()
-        .stdout(predicate::str::contains("(0) 0x"));
+        .success()
+        .stderr(predicate::str::contains("using command: sh -c true --abi --optimize-runs 1000 "));
 
     tmp_dir.close()?;
     Ok(())
   }
-}
 
-#[cfg(test)]
-mod deploy_cmd {
+  #[test]
+  fn it_should_replace_config_file_compiler_options_with_cli_options() -> Result<(), Box<std::error::Error>> {
 
-  use std::process::Command;
-  use assert_cmd::prelude::*;
-  use predicates::prelude::*;
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
 
-  use super::setup_vibranium_project;
-  use super::set_configuration;
-  use super::create_test_contract;
-  use super::create_test_artifact;
-  use vibranium::config::{
-    ProjectConfig,
-    ProjectDeploymentConfig,
-    SmartContractConfig,
-    SmartContractArg
-  };
+    set_configurations(vec![
+      ("compiler.cmd", "true"),
+      ("compiler.options", "[--abi, --optimize-runs, 200]"),
+    ], &project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("compile")
+        .arg("--verbose")
+        .arg("--replace-options")
+        .arg("--path")
+        .arg(&project_path)
+        .arg("--")
+        .arg("--bin");
+
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("using command: sh -c true --bin "));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
 
   #[test]
-  fn it_should_fail_if_no_deployment_config_is_provided() -> Result<(), Box<std::error::Error>> {
+  fn it_should_fail_when_replacing_options_without_cli_options() -> Result<(), Box<std::error::Error>> {
+
     let (tmp_dir, project_path) = setup_vibranium_project(None)?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("compile")
+        .arg("--replace-options")
         .arg("--path")
         .arg(&project_path);
 
     cmd.assert()
         .failure()
-        .stderr(predicate::str::contains("Couldn't find deployment configuration"));
+        .stderr(predicate::str::contains("required arguments were not provided"));
 
     tmp_dir.close()?;
     Ok(())
   }
 
-  #[test]
-  fn it_should_skip_deployment_if_no_artifacts_exist() -> Result<(), Box<std::error::Error>> {
+  fn add_release_profile(project_path: &PathBuf) -> Result<(), Box<std::error::Error>> {
+    set_configurations(vec![
+      ("compiler.cmd", "true"),
+      ("compiler.options", "[--abi]"),
+    ], project_path)?;
 
-    let mut config = ProjectConfig::default();
+    let mut config_file = OpenOptions::new().append(true).open(project_path.join("vibranium.toml"))?;
+    writeln!(config_file, "\n[profile.release.compiler]\noptions = [\"--abi\", \"--optimize\"]")?;
+    Ok(())
+  }
 
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      smart_contracts: vec![],
-      tracking_enabled: None,
-    });
+  #[test]
+  fn it_should_apply_selected_profile() -> Result<(), Box<std::error::Error>> {
 
-    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    add_release_profile(&project_path)?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("compile")
+        .arg("--verbose")
+        .arg("--profile")
+        .arg("release")
         .arg("--path")
         .arg(&project_path);
 
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("Nothing to deploy"));
+        .stderr(predicate::str::contains("using command: sh -c true --abi --optimize "));
 
     tmp_dir.close()?;
     Ok(())
   }
 
   #[test]
-  fn it_should_skip_deployment_if_address_is_provided_in_configuration() -> Result<(), Box<std::error::Error>> {
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
-
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      tracking_enabled: None,
-      smart_contracts: vec![
-        SmartContractConfig {
-          name: contract_name.to_string(),
-          address: Some("0x552C51e32c70D5859E5163D319531B63e5dbBFF7".to_string()),
-          instance_of: None,
-          args: None,
-          gas_limit: None,
-          gas_price: None,
-          abi_path: None,
-          bytecode_path: None,
-        }
-      ],
-    });
+  fn it_should_select_profile_from_environment() -> Result<(), Box<std::error::Error>> {
 
-    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    add_release_profile(&project_path)?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.env("VIBRANIUM_PROFILE", "staging")
+        .arg("compile")
         .arg("--path")
         .arg(&project_path);
 
     cmd.assert()
-        .success()
-        .stdout(predicate::str::contains("skipped"));
+        .failure()
+        .stderr(predicate::str::contains("Couldn't find profile staging"));
 
     tmp_dir.close()?;
     Ok(())
   }
 
   #[test]
-  fn it_should_fail_if_provided_address_is_invalid() -> Result<(), Box<std::error::Error>> {
+  fn it_should_fail_when_smart_contract_exceeds_size_budget() -> Result<(), Box<std::error::Error>> {
 
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config_with_size_budget(100, None)))?;
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
 
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      tracking_enabled: None,
-      smart_contracts: vec![
-        SmartContractConfig {
-          name: contract_name.to_string(),
-          address: Some("0x".to_string()),
-          instance_of: None,
-          args: None,
-          gas_limit: None,
-          gas_price: None,
-          abi_path: None,
-          bytecode_path: None,
-        }
-      ],
-    });
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("compile")
+        .arg("--path")
+        .arg(&project_path);
 
-    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("SimpleTestContract: 332 bytes (max. 100 bytes, 232 bytes over budget)"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_list_top_contributors_of_exceeded_size_budget() -> Result<(), Box<std::error::Error>> {
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config_with_size_budget(4, None)))?;
+    let source = "contract SimpleTestContract {\n  function mint() public {}\n}\n";
+    fs::write(project_path.join("contracts").join("SimpleTestContract.sol"), source)?;
+    fs::write(project_path.join("artifacts").join("SimpleTestContract.bin-runtime"), "6001600200")?;
+    let combined_json = format!(r#"{{
+      "contracts": {{
+        "contracts/SimpleTestContract.sol:SimpleTestContract": {{ "bin-runtime": "6001600200", "srcmap-runtime": "{}:2:0:-;;0:0:-1:-" }}
+      }},
+      "sourceList": ["contracts/SimpleTestContract.sol"]
+    }}"#, source.find("{}").unwrap());
+    fs::write(project_path.join("artifacts").join("combined.json"), combined_json)?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("compile")
         .arg("--path")
         .arg(&project_path);
 
     cmd.assert()
         .failure()
-        .stderr(predicate::str::contains("Invalid address"));
+        .stderr(predicate::str::contains("SimpleTestContract: 5 bytes (max. 4 bytes, 1 bytes over budget)\n    mint: 4 bytes\n    (generated code): 1 bytes"));
 
     tmp_dir.close()?;
     Ok(())
   }
 
   #[test]
-  fn it_should_fail_if_parameter_args_are_not_valid() -> Result<(), Box<std::error::Error>> {
+  fn it_should_only_warn_about_exceeded_size_budget_if_configured() -> Result<(), Box<std::error::Error>> {
 
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
-
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      tracking_enabled: None,
-      smart_contracts: vec![
-        SmartContractConfig {
-          name: contract_name.to_string(),
-          address: None,
-          instance_of: None,
-          args: Some(vec![
-            SmartContractArg {
-              value: "2".to_string(),
-              kind: "invalid".to_string()
-            }
-          ]),
-          gas_limit: None,
-          gas_price: None,
-          abi_path: None,
-          bytecode_path: None,
-        }
-      ],
-    });
-
-    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
-
-    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
-    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config_with_size_budget(100, Some("warn"))))?;
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("compile")
         .arg("--path")
         .arg(&project_path);
 
     cmd.assert()
-        .failure()
-        .stderr(predicate::str::contains("Couldn't read Smart Contract constructor parameter"));
+        .success()
+        .stderr(predicate::str::contains("Warning: Smart Contract exceeds its size budget: SimpleTestContract"));
 
     tmp_dir.close()?;
     Ok(())
   }
 
+  fn config_with_static_checks(checks: Option<BTreeMap<String, String>>) -> ProjectConfig {
+    ProjectConfig {
+      compiler: Some(ProjectCmdExecutionConfig {
+        cmd: Some("true".to_string()),
+        options: Some(vec!["--noop".to_string()]),
+        max_size_enforcement: None,
+        artifact_format: None,
+        max_size: None,
+        checks,
+        warnings: None,
+        version: None,
+      }),
+      ..ProjectConfig::default()
+    }
+  }
+
   #[test]
-  fn it_should_fail_if_it_can_not_tokenize_args() -> Result<(), Box<std::error::Error>> {
+  fn it_should_warn_about_static_check_findings() -> Result<(), Box<std::error::Error>> {
 
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config_with_static_checks(None)))?;
+    create_test_contract(&project_path, "self_destructing_test_contract.sol")?;
 
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      tracking_enabled: None,
-      smart_contracts: vec![
-        SmartContractConfig {
-          name: contract_name.to_string(),
-          address: None,
-          instance_of: None,
-          args: Some(vec![
-            SmartContractArg {
-              value: "200".to_string(),
-              kind: "bool".to_string()
-            }
-          ]),
-          gas_limit: None,
-          gas_price: None,
-          abi_path: None,
-          bytecode_path: None,
-        }
-      ],
-    });
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("compile")
+        .arg("--path")
+        .arg(&project_path);
 
-    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("self_destructing_test_contract.sol:13: warning[selfdestruct]"));
 
-    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
-    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_on_static_check_errors() -> Result<(), Box<std::error::Error>> {
+
+    let mut checks = BTreeMap::new();
+    checks.insert("selfdestruct".to_string(), "error".to_string());
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config_with_static_checks(Some(checks))))?;
+    create_test_contract(&project_path, "self_destructing_test_contract.sol")?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("compile")
         .arg("--path")
         .arg(&project_path);
 
     cmd.assert()
         .failure()
-        .stderr(predicate::str::contains("Couldn't tokenize Smart Contract constructor parameter"));
+        .stderr(predicate::str::contains("Static checks failed"))
+        .stderr(predicate::str::contains("error[selfdestruct]"));
 
     tmp_dir.close()?;
     Ok(())
   }
 
-  #[test]
-  fn it_should_fail_if_artifacts_are_partially_missing() -> Result<(), Box<std::error::Error>> {
-
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
+  // The compiler is faked by a command that emits a coded warning.
+  fn config_with_warnings(warnings: CompilerWarningsConfig) -> ProjectConfig {
+    ProjectConfig {
+      compiler: Some(ProjectCmdExecutionConfig {
+        cmd: Some("printf 'Warning (2072): Unused local variable.\\n --> Simple.sol:5:5:\\n' >&2; true".to_string()),
+        options: Some(vec!["--noop".to_string()]),
+        max_size_enforcement: None,
+        artifact_format: None,
+        max_size: None,
+        checks: None,
+        warnings: Some(warnings),
+        version: None,
+      }),
+      ..ProjectConfig::default()
+    }
+  }
 
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      tracking_enabled: None,
-      smart_contracts: vec![SmartContractConfig {
-        name: contract_name.to_string(),
-        address: None,
-        instance_of: None,
-        args: None,
-        gas_limit: None,
-        gas_price: None,
-        abi_path: None,
-        bytecode_path: None,
-      }],
-    });
+  #[test]
+  fn it_should_fail_on_warnings_promoted_to_errors() -> Result<(), Box<std::error::Error>> {
 
-    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
-    // Having a `contract.bin` but no `contract.abi` will cause Vibranium
-    // to stop the deployment.
-    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+    let warnings = CompilerWarningsConfig { errors: Some(vec![2072]), ..CompilerWarningsConfig::default() };
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config_with_warnings(warnings)))?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("compile")
         .arg("--path")
         .arg(&project_path);
 
     cmd.assert()
         .failure()
-        .stderr(predicate::str::contains("Couldn't find abi file for"));
+        .stderr(predicate::str::contains("Compiler warnings are treated as errors"))
+        .stderr(predicate::str::contains("Simple.sol:5:5: Warning (2072): Unused local variable."));
 
     tmp_dir.close()?;
     Ok(())
   }
 
   #[test]
-  fn it_should_use_gas_limit_defined_in_smart_contract_config() -> Result<(), Box<std::error::Error>> {
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
-
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      tracking_enabled: None,
-      smart_contracts: vec![SmartContractConfig {
-        name: contract_name.to_string(),
-        address: None,
-        instance_of: None,
-        args: None,
-        gas_limit: Some(20000),
-        gas_price: None,
-        abi_path: None,
-        bytecode_path: None,
-      }],
-    });
-
-    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+  fn it_should_silence_warnings_despite_deny_warnings() -> Result<(), Box<std::error::Error>> {
 
-    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
-    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+    let warnings = CompilerWarningsConfig { deny_warnings: Some(true), errors: None, silence: Some(vec![2072]) };
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config_with_warnings(warnings)))?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("compile")
         .arg("--path")
         .arg(&project_path);
 
-    // The gas limit for SimpleTestContract is too low,
-    // so we expect the deployment to fail.
-    cmd.assert().failure();
-;
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("Unused local variable").not());
+
     tmp_dir.close()?;
     Ok(())
   }
 
   #[test]
-  fn it_should_deploy_smart_contracts() -> Result<(), Box<std::error::Error>> {
-
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
-
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      tracking_enabled: None,
-      smart_contracts: vec![
-        SmartContractConfig {
-          name: contract_name.to_string(),
-          address: None,
-          instance_of: None,
-          args: Some(vec![
-            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
-          ]),
-          gas_limit: None,
-          gas_price: None,
-          abi_path: None,
-          bytecode_path: None,
-        },
-      ],
-    });
-
+  fn it_should_write_json_artifacts() -> Result<(), Box<std::error::Error>> {
+
+    let config = ProjectConfig {
+      compiler: Some(ProjectCmdExecutionConfig {
+        cmd: Some("true".to_string()),
+        options: Some(vec!["--noop".to_string()]),
+        artifact_format: Some("json".to_string()),
+        ..ProjectCmdExecutionConfig::default()
+      }),
+      ..ProjectConfig::default()
+    };
     let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
-
     create_test_artifact(&project_path, "SimpleTestContract.abi")?;
     create_test_artifact(&project_path, "SimpleTestContract.bin")?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("compile")
         .arg("--path")
         .arg(&project_path);
 
     cmd.assert().success();
 
+    let artifact = std::fs::read_to_string(project_path.join("artifacts").join("SimpleTestContract.json"))?;
+    assert!(artifact.contains("\"contractName\": \"SimpleTestContract\""));
+    assert!(artifact.contains("\"bytecode\": \"0x"));
+    assert!(artifact.contains("\"name\": \"true\""));
+
     tmp_dir.close()?;
     Ok(())
   }
 
   #[test]
-  fn it_should_deploy_multiple_smart_contracts() -> Result<(), Box<std::error::Error>> {
-
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
-    let contract_name_2 = "SimpleTestContract2";
-
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      tracking_enabled: None,
-      smart_contracts: vec![
-        SmartContractConfig {
-          name: contract_name.to_string(),
-          address: None,
-          instance_of: None,
-          args: Some(vec![
-            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
-          ]),
-          gas_limit: None,
-          gas_price: None,
-          abi_path: None,
-          bytecode_path: None,
-        },
-        SmartContractConfig {
-          name: contract_name_2.to_string(),
-          address: None,
-          instance_of: None,
-          args: Some(vec![
-            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
-          ]),
-          gas_limit: None,
-          gas_price: None,
-          abi_path: None,
-          bytecode_path: None,
-        },
-      ],
-    });
-
+  fn it_should_skip_compilation_of_unchanged_sources() -> Result<(), Box<std::error::Error>> {
+
+    let config = ProjectConfig {
+      compiler: Some(ProjectCmdExecutionConfig {
+        cmd: Some("echo".to_string()),
+        options: Some(vec!["compiling".to_string()]),
+        ..ProjectCmdExecutionConfig::default()
+      }),
+      ..ProjectConfig::default()
+    };
     let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
     create_test_contract(&project_path, "simple_test_contract.sol")?;
     create_test_contract(&project_path, "simple_test_contract_2.sol")?;
+    std::fs::create_dir_all(project_path.join("artifacts"))?;
+
+    let compile = |force: bool| -> Result<assert_cmd::assert::Assert, Box<std::error::Error>> {
+      let mut cmd = Command::main_binary()?;
+      cmd.arg("compile").arg("--path").arg(&project_path);
+      if force {
+        cmd.arg("--force");
+      }
+      Ok(cmd.assert().success())
+    };
+
+    compile(false)?.stdout(predicate::str::contains("simple_test_contract.sol"));
+    compile(false)?.stdout(predicate::str::contains("Smart Contracts are up to date."));
+
+    let mut source = OpenOptions::new().append(true).open(project_path.join("contracts").join("simple_test_contract_2.sol"))?;
+    writeln!(source, "// changed")?;
+    compile(false)?
+        .stdout(predicate::str::contains("simple_test_contract_2.sol"))
+        .stdout(predicate::str::contains("simple_test_contract.sol").not());
+
+    compile(true)?.stdout(predicate::str::contains("simple_test_contract.sol"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_accept_smart_contracts_within_size_budget() -> Result<(), Box<std::error::Error>> {
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config_with_size_budget(24576, None)))?;
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
 
     let mut cmd = Command::main_binary()?;
     cmd.arg("compile")
-        .arg("--compiler")
-        .arg("solcjs")
         .arg("--path")
         .arg(&project_path);
 
-    cmd.assert().success();
-    
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("size budget").not());
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod accounts_cmd {
+
+  use std::process::Command;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+
+  #[test]
+  fn it_should_output_local_blockchains_dev_accounts() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("accounts")
         .arg("--path")
         .arg(&project_path);
 
-    cmd.assert().success();
+    cmd.assert()
+      .success()
+        .stdout(predicate::str::contains("(0) 0x"));
 
     tmp_dir.close()?;
     Ok(())
   }
 
   #[test]
-  fn it_should_track_deployed_smart_contracts() -> Result<(), Box<std::error::Error>> {
-
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
+  fn it_should_output_accounts_replayed_from_cassette() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    let cassette = std::env::current_dir()?.join("tests").join("fixtures").join("accounts_cassette.json");
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("accounts")
+        .arg("--path")
+        .arg(&project_path)
+        .arg("--replay-rpc")
+        .arg(&cassette);
+
+    cmd.assert()
+      .success()
+        .stdout(predicate::str::contains("(0) 0x627306090abab3a6e1400e9345bc60c78a8bef57"))
+        .stdout(predicate::str::contains("(1) 0xf17f52151ebef6c7334fad080c5704d77216b732"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_when_replaying_from_missing_cassette() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("accounts")
+        .arg("--path")
+        .arg(&project_path)
+        .arg("--replay-rpc")
+        .arg(project_path.join("missing.json"));
+
+    cmd.assert()
+      .failure()
+        .stderr(predicate::str::contains("Couldn't use RPC cassette"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod deploy_cmd {
+
+  use std::process::Command;
+  use std::fs;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+  use super::set_configuration;
+  use super::create_test_contract;
+  use super::create_test_artifact;
+  use vibranium::config::{
+    ProjectConfig,
+    ProjectDeploymentConfig,
+    SmartContractConfig,
+    SmartContractArg
+  };
+
+  #[test]
+  fn it_should_fail_if_no_deployment_config_is_provided() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't find deployment configuration"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_if_selected_network_is_not_configured() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--network")
+        .arg("mainnet")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't find network mainnet in vibranium config"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_skip_deployment_if_no_artifacts_exist() -> Result<(), Box<std::error::Error>> {
+
+    let mut config = ProjectConfig::default();
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      smart_contracts: vec![],
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing to deploy"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_in_read_only_mode() -> Result<(), Box<std::error::Error>> {
+
+    let config = ProjectConfig {
+      deployment: Some(ProjectDeploymentConfig {
+        contracts: None,
+        gas_limit: None,
+        gas_price: None,
+        tx_confirmations: None,
+        smart_contracts: vec![],
+        tracking_enabled: None,
+        policy: None,
+        tracking_key: None,
+        store: None,
+        fee_bumping: None,
+        require_tests: None,
+        require_tests_filter: None,
+        test_cmd: None,
+        test_options: None,
+      }),
+      ..ProjectConfig::default()
+    };
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path)
+        .arg("--read-only");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("disabled in read-only mode"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_refuse_to_deploy_to_remote_networks_with_embedded_secrets() -> Result<(), Box<std::error::Error>> {
+
+    let mut constants = std::collections::BTreeMap::new();
+    constants.insert("DEPLOYER_PRIVATE_KEY".to_string(), "0x4f3edf983ac636a65a842ce7c78d9aa706d3b113bce9c46f30d7d21715b23b1d".to_string());
+
+    let config = ProjectConfig {
+      constants: Some(constants),
+      ..ProjectConfig::default()
+    };
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+    set_configuration("blockchain.connector.host", "rpc.example.org", &project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("`constants.DEPLOYER_PRIVATE_KEY` looks like an embedded private key"))
+        .stderr(predicate::str::contains("Refusing to deploy to a non-development network"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_refuse_to_deploy_to_remote_networks_when_required_tests_fail() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    set_configuration("blockchain.connector.host", "rpc.example.org", &project_path)?;
+    set_configuration("deployment.test_cmd", "false", &project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path)
+        .arg("--require-tests");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Refusing to deploy to a non-development network, as `false test` failed."));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_run_required_tests_with_the_built_in_test_runner() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    let mut config = fs::read_to_string(project_path.join("vibranium.toml"))?;
+    config.push_str("\n[networks.mainnet]\nurl = \"https://rpc.example.org\"\n");
+    fs::write(project_path.join("vibranium.toml"), config)?;
+    set_configuration("deployment.require_tests_filter", "Critical", &project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--network")
+        .arg("mainnet")
+        .arg("--path")
+        .arg(&project_path)
+        .arg("--require-tests");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Refusing to deploy to a non-development network, as the tests couldn't be run: Couldn't find any Solidity tests in"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_refuse_to_upgrade_on_remote_networks_when_required_tests_fail() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    set_configuration("blockchain.connector.host", "rpc.example.org", &project_path)?;
+    set_configuration("deployment.test_cmd", "false", &project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("upgrade")
+        .arg("Token")
+        .arg("--path")
+        .arg(&project_path)
+        .arg("--require-tests");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Refusing to deploy to a non-development network, as `false test` failed."));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_skip_deployment_if_address_is_provided_in_configuration() -> Result<(), Box<std::error::Error>> {
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
 
     config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
       gas_limit: None,
       gas_price: None,
       tx_confirmations: None,
       tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
       smart_contracts: vec![
         SmartContractConfig {
           name: contract_name.to_string(),
-          address: None,
+          address: Some("0x552C51e32c70D5859E5163D319531B63e5dbBFF7".to_string()),
           instance_of: None,
-          args: Some(vec![
-            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
-          ]),
+          args: None,
           gas_limit: None,
           gas_price: None,
           abi_path: None,
           bytecode_path: None,
-        },
+          tags: None,
+          proxy: None,
+        }
+      ],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("skipped"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_if_provided_address_is_invalid() -> Result<(), Box<std::error::Error>> {
+
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![
+        SmartContractConfig {
+          name: contract_name.to_string(),
+          address: Some("0x".to_string()),
+          instance_of: None,
+          args: None,
+          gas_limit: None,
+          gas_price: None,
+          abi_path: None,
+          bytecode_path: None,
+          tags: None,
+          proxy: None,
+        }
       ],
     });
 
     let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
 
-    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
-    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid address"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_if_parameter_args_are_not_valid() -> Result<(), Box<std::error::Error>> {
+
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![
+        SmartContractConfig {
+          name: contract_name.to_string(),
+          address: None,
+          instance_of: None,
+          args: Some(vec![
+            SmartContractArg {
+              value: "2".to_string(),
+              kind: "invalid".to_string()
+            }
+          ]),
+          gas_limit: None,
+          gas_price: None,
+          abi_path: None,
+          bytecode_path: None,
+          tags: None,
+          proxy: None,
+        }
+      ],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't read Smart Contract constructor parameter"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_if_it_can_not_tokenize_args() -> Result<(), Box<std::error::Error>> {
+
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![
+        SmartContractConfig {
+          name: contract_name.to_string(),
+          address: None,
+          instance_of: None,
+          args: Some(vec![
+            SmartContractArg {
+              value: "two hundred".to_string(),
+              kind: "uint256".to_string()
+            }
+          ]),
+          gas_limit: None,
+          gas_price: None,
+          abi_path: None,
+          bytecode_path: None,
+          tags: None,
+          proxy: None,
+        }
+      ],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't tokenize Smart Contract constructor parameter"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_if_args_dont_match_constructor() -> Result<(), Box<std::error::Error>> {
+
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![
+        SmartContractConfig {
+          name: contract_name.to_string(),
+          address: None,
+          instance_of: None,
+          args: Some(vec![
+            SmartContractArg {
+              value: "true".to_string(),
+              kind: "bool".to_string()
+            }
+          ]),
+          gas_limit: None,
+          gas_price: None,
+          abi_path: None,
+          bytecode_path: None,
+          tags: None,
+          proxy: None,
+        }
+      ],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Constructor parameter 'initialValue' is of type uint256, but the configured argument is of kind bool"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_if_artifacts_are_partially_missing() -> Result<(), Box<std::error::Error>> {
+
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![SmartContractConfig {
+        name: contract_name.to_string(),
+        address: None,
+        instance_of: None,
+        args: None,
+        gas_limit: None,
+        gas_price: None,
+        abi_path: None,
+        bytecode_path: None,
+        tags: None,
+        proxy: None,
+      }],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+    // Having a `contract.bin` but no `contract.abi` will cause Vibranium
+    // to stop the deployment.
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't find abi file for"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_use_gas_limit_defined_in_smart_contract_config() -> Result<(), Box<std::error::Error>> {
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![SmartContractConfig {
+        name: contract_name.to_string(),
+        address: None,
+        instance_of: None,
+        args: None,
+        gas_limit: Some(20000),
+        gas_price: None,
+        abi_path: None,
+        bytecode_path: None,
+        tags: None,
+        proxy: None,
+      }],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    // The gas limit for SimpleTestContract is too low,
+    // so we expect the deployment to fail.
+    cmd.assert().failure();
+;
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_deploy_smart_contracts() -> Result<(), Box<std::error::Error>> {
+
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![
+        SmartContractConfig {
+          name: contract_name.to_string(),
+          address: None,
+          instance_of: None,
+          args: Some(vec![
+            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
+          ]),
+          gas_limit: None,
+          gas_price: None,
+          abi_path: None,
+          bytecode_path: None,
+          tags: None,
+          proxy: None,
+        },
+      ],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert().success();
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_deploy_multiple_smart_contracts() -> Result<(), Box<std::error::Error>> {
+
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+    let contract_name_2 = "SimpleTestContract2";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![
+        SmartContractConfig {
+          name: contract_name.to_string(),
+          address: None,
+          instance_of: None,
+          args: Some(vec![
+            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
+          ]),
+          gas_limit: None,
+          gas_price: None,
+          abi_path: None,
+          bytecode_path: None,
+          tags: None,
+          proxy: None,
+        },
+        SmartContractConfig {
+          name: contract_name_2.to_string(),
+          address: None,
+          instance_of: None,
+          args: Some(vec![
+            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
+          ]),
+          gas_limit: None,
+          gas_price: None,
+          abi_path: None,
+          bytecode_path: None,
+          tags: None,
+          proxy: None,
+        },
+      ],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+    create_test_contract(&project_path, "simple_test_contract.sol")?;
+    create_test_contract(&project_path, "simple_test_contract_2.sol")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("compile")
+        .arg("--compiler")
+        .arg("solcjs")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert().success();
+    
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert().success();
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_track_deployed_smart_contracts() -> Result<(), Box<std::error::Error>> {
+
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![
+        SmartContractConfig {
+          name: contract_name.to_string(),
+          address: None,
+          instance_of: None,
+          args: Some(vec![
+            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
+          ]),
+          gas_limit: None,
+          gas_price: None,
+          abi_path: None,
+          bytecode_path: None,
+          tags: None,
+          proxy: None,
+        },
+      ],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert().success();
+
+    let tracking_file = project_path.join(".vibranium").join("tracking.toml");
+    assert_eq!(tracking_file.exists(), true);
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+       .success()
+       .stdout(predicate::str::contains("(skipped)"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_not_track_deployment_when_tracking_is_turned_off() -> Result<(), Box<std::error::Error>> {
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: Some(false),
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![
+        SmartContractConfig {
+          name: contract_name.to_string(),
+          address: None,
+          instance_of: None,
+          args: Some(vec![
+            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
+          ]),
+          gas_limit: None,
+          gas_price: None,
+          abi_path: None,
+          bytecode_path: None,
+          tags: None,
+          proxy: None,
+        },
+      ],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert().success();
+
+    let tracking_file = project_path.join(".vibranium").join("tracking.toml");
+    assert_eq!(tracking_file.exists(), false);
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_not_track_deployment_when_notracking_flag_is_set() -> Result<(), Box<std::error::Error>> {
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![
+        SmartContractConfig {
+          name: contract_name.to_string(),
+          address: None,
+          instance_of: None,
+          args: Some(vec![
+            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
+          ]),
+          gas_limit: None,
+          gas_price: None,
+          abi_path: None,
+          bytecode_path: None,
+          tags: None,
+          proxy: None,
+        },
+      ],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--no-tracking")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert().success();
+
+    let tracking_file = project_path.join(".vibranium").join("tracking.toml");
+    assert_eq!(tracking_file.exists(), false);
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_use_bytecode_of_specified_instance_of_configuration() -> Result<(), Box<std::error::Error>> {
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![
+        SmartContractConfig {
+          name: "InstanceOfSimpleStorage".to_string(),
+          address: None,
+          args: Some(vec![
+            SmartContractArg { value: "500".to_string(),kind: "uint".to_string() },
+          ]),
+          gas_limit: None,
+          gas_price: None,
+          // we update this value manually down below due to toml-rs'
+          // ValueAfterTable error.
+          instance_of: None,
+          abi_path: None,
+          bytecode_path: None,
+          tags: None,
+          proxy: None,
+        }
+      ],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+    create_test_contract(&project_path, "simple_test_contract.sol")?;
+
+    set_configuration("deployment.smart_contracts[1].instance_of", &contract_name.to_string(), &project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("compile")
+        .arg("--compiler")
+        .arg("solcjs")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert().success();
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert().success();
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod list_cmd {
+
+  use std::process::Command;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+  use super::create_test_artifact;
+  use vibranium::config::{
+    ProjectConfig,
+    ProjectDeploymentConfig,
+    SmartContractConfig,
+    SmartContractArg
+  };
+
+  #[test]
+  fn it_should_show_no_tracking_data_exists_if_no_tracking_database() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("list")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No Smart Contract data"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+  
+  #[test]
+  fn it_should_show_tracked_smart_contract_data() -> Result<(), Box<std::error::Error>> {
+
+    let mut config = ProjectConfig::default();
+    let contract_name = "SimpleTestContract";
+
+    config.deployment = Some(ProjectDeploymentConfig {
+      contracts: None,
+      gas_limit: None,
+      gas_price: None,
+      tx_confirmations: None,
+      tracking_enabled: None,
+      policy: None,
+      tracking_key: None,
+      store: None,
+      fee_bumping: None,
+      require_tests: None,
+      require_tests_filter: None,
+      test_cmd: None,
+      test_options: None,
+      smart_contracts: vec![
+        SmartContractConfig {
+          name: contract_name.to_string(),
+          address: None,
+          instance_of: None,
+          args: Some(vec![
+            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
+          ]),
+          gas_limit: None,
+          gas_price: None,
+          abi_path: None,
+          bytecode_path: None,
+          tags: None,
+          proxy: None,
+        },
+      ],
+    });
+
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deploy")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert().success();
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("list")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Deployed Smart Contracts:\n  0x"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod deployments_cmd {
+
+  use std::process::Command;
+  use std::fs;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+
+  #[test]
+  fn it_should_fail_on_invalid_tags() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deployments")
+        .arg("tag")
+        .arg("SimpleTestContract")
+        .arg("release")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Tags need to be specified as KEY=VALUE"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_not_tag_deployments_in_read_only_mode() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("--read-only")
+        .arg("deployments")
+        .arg("tag")
+        .arg("SimpleTestContract")
+        .arg("release=v2.1")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("disabled in read-only mode"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_generate_deployment_docs_from_tracking_data() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    fs::write(project_path.join(".vibranium").join("tracking.toml"), r#"
+["0xabcdef0123456789"."0x01"]
+name = "Token"
+address = "0x00000000000000000000000000000000000000c0"
+network = "mainnet"
+deployed_at = 1641600000
+commit = "1a2b3c4"
+"#)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deployments")
+        .arg("docs")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("## mainnet"))
+        .stdout(predicate::str::contains("| Token | `0x00000000000000000000000000000000000000c0` | n/a | `1a2b3c4` | 2022-01-08 | not verified yet | n/a |"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_list_tracked_deployments_of_all_chains_by_network() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    fs::write(project_path.join(".vibranium").join("tracking.toml"), r#"
+["0xabcdef0123456789"."0x01"]
+name = "Token"
+address = "0x00000000000000000000000000000000000000c0"
+network = "mainnet"
+
+["0x0123456789abcdef"."0x02"]
+name = "Faucet"
+address = "0x00000000000000000000000000000000000000c1"
+"#)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deployments")
+        .arg("list")
+        .arg("--all")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Chain 0x01234567:\n  0x00000000000000000000000000000000000000c1: Faucet"))
+        .stdout(predicate::str::contains("mainnet:\n  0x00000000000000000000000000000000000000c0: Token"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_clear_tracked_deployments_of_a_network() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    let tracking_file = project_path.join(".vibranium").join("tracking.toml");
+    fs::write(&tracking_file, r#"
+["0xabcdef0123456789"."0x01"]
+name = "Token"
+address = "0x00000000000000000000000000000000000000c0"
+network = "staging"
+
+["0xabcdef0123456789"."0x02"]
+name = "Faucet"
+address = "0x00000000000000000000000000000000000000c1"
+"#)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deployments")
+        .arg("clear")
+        .arg("--network")
+        .arg("staging")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 1 tracked deployment(s) made to staging."));
+
+    let tracking_data = fs::read_to_string(&tracking_file)?;
+    assert!(!tracking_data.contains("Token"));
+    assert!(tracking_data.contains("Faucet"));
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deployments")
+        .arg("clear")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Specify the network to clear with --network"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_export_safe_address_books() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    let mut config = fs::read_to_string(project_path.join("vibranium.toml"))?;
+    config.push_str("\n[networks.mainnet]\nurl = \"http://localhost:8545\"\nchain_id = 1\n");
+    fs::write(project_path.join("vibranium.toml"), config)?;
+    fs::write(project_path.join(".vibranium").join("tracking.toml"), r#"
+["0xabcdef0123456789"."0x01"]
+name = "Token"
+address = "0x00000000000000000000000000000000000000c0"
+network = "mainnet"
+
+["0xabcdef0123456789"."0x02"]
+name = "Faucet"
+address = "0x00000000000000000000000000000000000000c1"
+"#)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deployments")
+        .arg("export")
+        .arg("--address-book")
+        .arg("safe-addressbook")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("address,name,chainId\n0x00000000000000000000000000000000000000C0,Token,1"))
+        .stderr(predicate::str::contains("Skipped deployments of Faucet"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_report_deployments_missing_on_target_network() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    let mut config = fs::read_to_string(project_path.join("vibranium.toml"))?;
+    config.push_str("\n[networks.staging]\nurl = \"http://localhost:8545\"\n\n[networks.production]\nurl = \"http://localhost:8546\"\n");
+    fs::write(project_path.join("vibranium.toml"), config)?;
+    fs::write(project_path.join(".vibranium").join("tracking.toml"), r#"
+["0xabcdef0123456789"."0x01"]
+name = "Token"
+address = "0x00000000000000000000000000000000000000c0"
+network = "staging"
+"#)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deployments")
+        .arg("diff")
+        .arg("--from")
+        .arg("staging")
+        .arg("--to")
+        .arg("production")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Token: deployed on staging, missing on production"));
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("deployments")
+        .arg("diff")
+        .arg("--from")
+        .arg("staging")
+        .arg("--to")
+        .arg("prod")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("prod"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod access_report_cmd {
+
+  use std::process::Command;
+  use std::fs;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+  use super::create_test_artifact;
+  use super::create_test_contract;
+
+  #[test]
+  fn it_should_list_access_restrictions_of_state_changing_functions() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    create_test_contract(&project_path, "self_destructing_test_contract.sol")?;
+    fs::write(project_path.join("artifacts").join("SelfDestructingTestContract.abi"), r#"[
+      {"constant":false,"inputs":[],"name":"kill","outputs":[],"payable":false,"stateMutability":"nonpayable","type":"function"},
+      {"inputs":[],"payable":false,"stateMutability":"nonpayable","type":"constructor"}
+    ]"#)?;
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("access-report")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("SelfDestructingTestContract\n  kill()        nonpayable  (inline check)"))
+        .stdout(predicate::str::contains("SimpleTestContract\n  set(uint256)  nonpayable  (source not found)"))
+        .stdout(predicate::str::contains("get()").not());
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_print_access_restrictions_as_json() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("access-report")
+        .arg("--path")
+        .arg(&project_path)
+        .arg("--format")
+        .arg("json");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("{\"command\":\"access-report\",\"data\":{\"functions\":[{\"contract\":\"SimpleTestContract\",\"function\":\"set(uint256)\""))
+        .stdout(predicate::str::contains("\"source_found\":false"))
+        .stdout(predicate::str::contains("SimpleTestContract\n").not());
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_when_smart_contract_has_not_been_compiled() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("access-report")
+        .arg("Unknown")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't find ABI of Smart Contract Unknown"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod doctor_cmd {
+
+  use std::process::Command;
+  use std::fs;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+  use super::set_configurations;
+
+  #[test]
+  fn it_should_check_configured_tools() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    set_configurations(vec![
+      ("compiler.cmd", "true"),
+      ("blockchain.cmd", "vibranium-non-existent-client"),
+    ], &project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("doctor")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("[ok] compiler: true"))
+        .stdout(predicate::str::contains("[failed] blockchain client: couldn't run vibranium-non-existent-client"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_write_a_report_with_recorded_process_invocations() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    set_configurations(vec![
+      ("compiler.cmd", "true"),
+    ], &project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("compile")
+        .arg("--path")
+        .arg(&project_path);
+    cmd.assert().success();
+
+    let report_path = project_path.join("report.json");
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("doctor")
+        .arg("--report")
+        .arg("--output")
+        .arg(&report_path)
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote troubleshooting report to"));
+
+    let report = fs::read_to_string(&report_path)?;
+    assert!(report.contains("\"program\": \"sh\""));
+    assert!(report.contains("\"exit_code\": 0"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod reproduce_cmd {
+
+  use std::process::Command;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+  use super::set_configurations;
+
+  #[test]
+  fn it_should_fail_when_runtime_bytecode_is_missing() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    set_configurations(vec![
+      ("compiler.cmd", "true"),
+    ], &project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("reproduce")
+        .arg("SimpleTestContract")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't find runtime bytecode of Smart Contract SimpleTestContract"))
+        .stderr(predicate::str::contains("--bin-runtime"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod verify_cmd {
+
+  use std::process::Command;
+  use std::fs;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+
+  #[test]
+  fn it_should_fail_if_project_is_not_a_vibranium_project() -> Result<(), Box<std::error::Error>> {
+    let tmp_dir = tempfile::tempdir()?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("verify")
+        .arg("--path")
+        .arg(tmp_dir.path());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Not a Vibranium project"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_not_record_verifications_in_read_only_mode() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    let block_hash = format!("0x{}", "ab".repeat(32));
+    let tracking_file = project_path.join(".vibranium").join("tracking.toml");
+    // Chains are keyed by the SHA3-256 of their genesis block hash
+    fs::write(&tracking_file, r#"
+["0xc0dce27cacfb72e862f5e7980a17c6e98bccb6d8abfbde30ef37553d57f9b14f"."0x01"]
+name = "SimpleTestContract"
+address = "0x00000000000000000000000000000000000000c0"
+"#)?;
+    let cassette = project_path.join("cassette.json");
+    let zero_hash = format!("0x{:064x}", 0);
+    fs::write(&cassette, serde_json::json!({
+      "interactions": [
+        {
+          "method": "eth_getBlockByNumber",
+          "params": ["0x0", false],
+          "result": {
+            "hash": block_hash, "parentHash": zero_hash, "sha3Uncles": zero_hash, "stateRoot": zero_hash,
+            "transactionsRoot": zero_hash, "receiptsRoot": zero_hash, "number": "0x0",
+            "miner": "0x0000000000000000000000000000000000000000", "gasUsed": "0x0", "gasLimit": "0x0",
+            "extraData": "0x", "logsBloom": format!("0x{}", "0".repeat(512)), "timestamp": "0x0",
+            "difficulty": "0x0", "totalDifficulty": "0x0", "sealFields": [], "uncles": [], "transactions": [], "size": "0x0",
+          },
+        },
+        { "method": "eth_getCode", "params": ["0x00000000000000000000000000000000000000c0", "latest"], "result": "0x6080" },
+      ],
+    }).to_string())?;
+    fs::write(project_path.join("artifacts").join("SimpleTestContract.bin-runtime"), "6080")?;
+    let tracking_data = fs::read(&tracking_file)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("--read-only")
+        .arg("verify")
+        .arg("--path")
+        .arg(&project_path)
+        .arg("--replay-rpc")
+        .arg(&cassette);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("SimpleTestContract at 0x00000000000000000000000000000000000000c0"));
+
+    assert_eq!(fs::read(&tracking_file)?, tracking_data);
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_for_unknown_networks() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("verify")
+        .arg("--network")
+        .arg("mainnet")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't find network mainnet"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod node_cmd {
+
+  use std::process::Command;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+
+  fn node_cmd(project_path: &std::path::PathBuf, args: &[&str]) -> Result<Command, Box<std::error::Error>> {
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("node").arg(args[0]).arg("--path").arg(project_path).args(&args[1..]);
+    Ok(cmd)
+  }
+
+  #[test]
+  fn it_should_manage_a_node_in_the_background() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    node_cmd(&project_path, &["start", "--client", "sh", "--", "-c", "echo started; sleep 30"])?
+      .assert()
+      .success()
+      .stdout(predicate::str::contains("Started sh node (pid "));
+
+    node_cmd(&project_path, &["start", "--client", "sh", "--", "-c", "sleep 30"])?
+      .assert()
+      .failure()
+      .stderr(predicate::str::contains("A node is already running for this project"));
+
+    node_cmd(&project_path, &["status"])?
+      .assert()
+      .success()
+      .stdout(predicate::str::contains("sh node is running (pid "));
+
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    node_cmd(&project_path, &["logs"])?
+      .assert()
+      .success()
+      .stdout(predicate::str::contains("started"));
+
+    node_cmd(&project_path, &["stop"])?
+      .assert()
+      .success()
+      .stdout(predicate::str::contains("Stopped sh node (pid "));
+
+    node_cmd(&project_path, &["status"])?
+      .assert()
+      .success()
+      .stdout(predicate::str::contains("No node is running in the background."));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_print_structured_output_with_format_json() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    node_cmd(&project_path, &["status", "--format", "json"])?
+      .assert()
+      .success()
+      .stdout("{\"command\":\"node status\",\"data\":{\"node\":null,\"running\":false},\"ok\":true,\"version\":1}\n");
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("init").arg("--path").arg(&project_path).arg("--format").arg("json");
+    cmd.assert()
+      .failure()
+      .stdout("{\"error\":{\"kind\":\"other\",\"message\":\"`init` doesn't support --format json\"},\"ok\":false,\"version\":1}\n");
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod chain_cmd {
+
+  use std::process::Command;
+  use std::fs;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+  use vibranium::config::ProjectConfig;
+
+  use super::setup_vibranium_project;
+
+  #[test]
+  fn it_should_only_wipe_chain_data_of_the_given_network() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    let chains_dir = project_path.join(".vibranium").join("chains");
+    fs::create_dir_all(chains_dir.join("ganache"))?;
+    fs::write(chains_dir.join("development").join("chain.db"), "")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("chain")
+        .arg("reset")
+        .arg("development")
+        .arg("--path")
+        .arg(&project_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Wiped chain data of network development."))
+        .stdout(predicate::str::contains("Pruned 0 tracked deployment(s)."));
+
+    assert!(!chains_dir.join("development").exists());
+    assert!(chains_dir.join("ganache").exists());
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_on_chain_reset_if_project_is_read_only() -> Result<(), Box<std::error::Error>> {
+    let config = ProjectConfig {
+      read_only: Some(true),
+      ..ProjectConfig::default()
+    };
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("chain")
+        .arg("reset")
+        .arg("development")
+        .arg("--path")
+        .arg(&project_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("read-only mode"));
+
+    assert!(project_path.join(".vibranium").join("chains").join("development").exists());
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod interface_cmd {
+
+  use std::process::Command;
+  use std::fs;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+  use super::create_test_artifact;
+
+  #[test]
+  fn it_should_print_interface_of_compiled_smart_contract() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("interface")
+        .arg("SimpleTestContract")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("interface ISimpleTestContract {"))
+        .stdout(predicate::str::contains("function set(uint256 x) external;"))
+        .stdout(predicate::str::contains("function get() external view returns (uint256 retVal);"))
+        .stdout(predicate::str::contains("constructor").not());
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_write_interface_and_abi_to_files() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("interface")
+        .arg("SimpleTestContract")
+        .arg("--name")
+        .arg("IStorage")
+        .arg("--output")
+        .arg(project_path.join("IStorage.sol"))
+        .arg("--abi-output")
+        .arg(project_path.join("IStorage.abi"))
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert().success();
+
+    assert!(fs::read_to_string(project_path.join("IStorage.sol"))?.contains("interface IStorage {"));
+    assert!(!fs::read_to_string(project_path.join("IStorage.abi"))?.contains("constructor"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_if_smart_contract_has_not_been_compiled() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("interface")
+        .arg("SimpleTestContract")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't find ABI of Smart Contract SimpleTestContract"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod check_interface_cmd {
+
+  use std::process::Command;
+  use std::fs;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+  use super::create_test_artifact;
+
+  #[test]
+  fn it_should_report_missing_functions_of_standard_interfaces() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("check-interface")
+        .arg("SimpleTestContract")
+        .arg("--erc")
+        .arg("20")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("missing function totalSupply() returns (uint256)"))
+        .stdout(predicate::str::contains("missing event Transfer(address indexed,address indexed,uint256)"))
+        .stderr(predicate::str::contains("SimpleTestContract doesn't implement ERC-20, found 8 issue(s)."));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_check_against_custom_interfaces() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
+    fs::write(project_path.join("IStorage.abi"), r#"[{"type":"function","name":"get","inputs":[],"outputs":[{"name":"","type":"uint256"}]}]"#)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("check-interface")
+        .arg("SimpleTestContract")
+        .arg("--erc")
+        .arg("IStorage.abi")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("SimpleTestContract implements IStorage.abi."));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod children_cmd {
+
+  use std::process::Command;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+
+  #[test]
+  fn it_should_not_track_children_in_read_only_mode() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("--read-only")
+        .arg("children")
+        .arg("Factory")
+        .arg("--event")
+        .arg("PairCreated")
+        .arg("--arg")
+        .arg("pair")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("disabled in read-only mode"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod events_cmd {
+
+  use std::process::Command;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+
+  #[test]
+  fn it_should_reject_invalid_blocks() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("events")
+        .arg("Token")
+        .arg("--from-block")
+        .arg("-yesterday")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid block '-yesterday'"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tx_cmd {
+
+  use std::process::Command;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+
+  #[test]
+  fn it_should_reject_invalid_transaction_hashes() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("tx")
+        .arg("0x1234")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid transaction hash '0x1234'"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test_cmd {
+
+  use std::process::Command;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+
+  #[test]
+  fn it_should_not_run_tests_in_read_only_mode() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("--read-only")
+        .arg("test")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't run tests. Sending transactions is disabled in read-only mode."));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod history_cmd {
+
+  use std::process::Command;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  use super::setup_vibranium_project;
+  use super::read_config;
+
+  fn set_compiler(compiler: &str, project_path: &std::path::PathBuf) -> Result<(), Box<std::error::Error>> {
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("config")
+        .arg("compiler.cmd")
+        .arg(compiler)
+        .arg("--path")
+        .arg(project_path);
+    cmd.assert().success();
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_list_executed_commands_most_recent_first() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    set_compiler("solcjs", &project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("history")
+        .arg("list")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("(1) vibranium config compiler.cmd solcjs --path {}", project_path.to_string_lossy())))
+        .stdout(predicate::str::contains("(2) vibranium init"))
+        .stdout(predicate::str::contains("Network: rpc://localhost:8545"));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_rerun_recorded_command() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    set_compiler("solcjs", &project_path)?;
+    set_compiler("solc", &project_path)?;
+
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("rerun")
+        .arg("2")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Rerunning: vibranium config compiler.cmd solcjs"));
+
+    let config = read_config(&project_path)?;
+    assert_eq!(config.compiler.unwrap().cmd, Some("solcjs".to_string()));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_refuse_to_rerun_commands_that_resolve_differently() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    set_compiler("solcjs", &project_path)?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("config")
+        .arg("blockchain.connector.port")
+        .arg("9545")
         .arg("--path")
         .arg(&project_path);
-
     cmd.assert().success();
 
-    let tracking_file = project_path.join(".vibranium").join("tracking.toml");
-    assert_eq!(tracking_file.exists(), true);
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("rerun")
+        .arg("2")
+        .arg("--path")
+        .arg(&project_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Command #2 resolves differently than when it ran:\n  Network: rpc://localhost:8545 (now rpc://localhost:9545)"));
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("rerun")
+        .arg("2")
+        .arg("--force")
         .arg("--path")
         .arg(&project_path);
 
     cmd.assert()
-       .success()
-       .stdout(predicate::str::contains("(skipped)"));
+        .success()
+        .stdout(predicate::str::contains("Rerunning: vibranium config compiler.cmd solcjs"));
 
     tmp_dir.close()?;
     Ok(())
   }
 
   #[test]
-  fn it_should_not_track_deployment_when_tracking_is_turned_off() -> Result<(), Box<std::error::Error>> {
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
+  fn it_should_record_absolute_project_paths() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
 
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      tracking_enabled: Some(false),
-      smart_contracts: vec![
-        SmartContractConfig {
-          name: contract_name.to_string(),
-          address: None,
-          instance_of: None,
-          args: Some(vec![
-            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
-          ]),
-          gas_limit: None,
-          gas_price: None,
-          abi_path: None,
-          bytecode_path: None,
-        },
-      ],
-    });
+    let mut cmd = Command::main_binary()?;
+    cmd.current_dir(project_path.parent().unwrap())
+        .arg("config")
+        .arg("compiler.cmd")
+        .arg("solcjs")
+        .arg("--path")
+        .arg(project_path.file_name().unwrap());
+    cmd.assert().success();
 
-    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("history")
+        .arg("list")
+        .arg("--path")
+        .arg(&project_path);
 
-    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
-    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("(1) vibranium config compiler.cmd solcjs --path {}", project_path.to_string_lossy())));
+
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_to_rerun_unknown_command() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("rerun")
+        .arg("5")
         .arg("--path")
         .arg(&project_path);
 
-    cmd.assert().success();
-
-    let tracking_file = project_path.join(".vibranium").join("tracking.toml");
-    assert_eq!(tracking_file.exists(), false);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't find command #5 in history"));
 
     tmp_dir.close()?;
     Ok(())
   }
+}
 
-  #[test]
-  fn it_should_not_track_deployment_when_notracking_flag_is_set() -> Result<(), Box<std::error::Error>> {
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
+#[cfg(test)]
+mod stats_cmd {
 
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      tracking_enabled: None,
-      smart_contracts: vec![
-        SmartContractConfig {
-          name: contract_name.to_string(),
-          address: None,
-          instance_of: None,
-          args: Some(vec![
-            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
-          ]),
-          gas_limit: None,
-          gas_price: None,
-          abi_path: None,
-          bytecode_path: None,
-        },
-      ],
-    });
+  use std::process::Command;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+  use vibranium::config::ProjectConfig;
 
-    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+  use super::setup_vibranium_project;
 
-    create_test_artifact(&project_path, "SimpleTestContract.abi")?;
-    create_test_artifact(&project_path, "SimpleTestContract.bin")?;
+  #[test]
+  fn it_should_point_out_disabled_usage_stats() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
-        .arg("--no-tracking")
+    cmd.arg("stats")
         .arg("--path")
         .arg(&project_path);
 
-    cmd.assert().success();
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Usage statistics are disabled"))
+        .stdout(predicate::str::contains("No runs have been recorded in the last 30 day(s)."));
 
-    let tracking_file = project_path.join(".vibranium").join("tracking.toml");
-    assert_eq!(tracking_file.exists(), false);
+    assert!(!project_path.join(".vibranium").join("stats.json").exists());
 
     tmp_dir.close()?;
     Ok(())
   }
 
   #[test]
-  fn it_should_use_bytecode_of_specified_instance_of_configuration() -> Result<(), Box<std::error::Error>> {
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
+  fn it_should_print_usage_stats_as_json() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
 
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      tracking_enabled: None,
-      smart_contracts: vec![
-        SmartContractConfig {
-          name: "InstanceOfSimpleStorage".to_string(),
-          address: None,
-          args: Some(vec![
-            SmartContractArg { value: "500".to_string(),kind: "uint".to_string() },
-          ]),
-          gas_limit: None,
-          gas_price: None,
-          // we update this value manually down below due to toml-rs'
-          // ValueAfterTable error.
-          instance_of: None,
-          abi_path: None,
-          bytecode_path: None,
-        }
-      ],
-    });
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("stats")
+        .arg("--path")
+        .arg(&project_path)
+        .arg("--format")
+        .arg("json");
 
-    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
-    create_test_contract(&project_path, "simple_test_contract.sol")?;
+    cmd.assert()
+        .success()
+        .stdout("{\"command\":\"stats\",\"data\":{\"days\":[],\"enabled\":false,\"window_days\":30},\"ok\":true,\"version\":1}\n");
 
-    set_configuration("deployment.smart_contracts[1].instance_of", &contract_name.to_string(), &project_path)?;
+    tmp_dir.close()?;
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_record_compile_runs_when_enabled() -> Result<(), Box<std::error::Error>> {
+    let config = ProjectConfig { stats: Some(true), ..ProjectConfig::default() };
+    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
 
+    // Failing runs are recorded as well, so this doesn't depend on a
+    // compiler being installed.
     let mut cmd = Command::main_binary()?;
     cmd.arg("compile")
-        .arg("--compiler")
-        .arg("solcjs")
         .arg("--path")
         .arg(&project_path);
-
-    cmd.assert().success();
+    let _ = cmd.output()?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("stats")
+        .arg("--days")
+        .arg("7")
         .arg("--path")
         .arg(&project_path);
 
-    cmd.assert().success();
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Usage statistics of the last 7 day(s):"))
+        .stdout(predicate::str::contains("Deployed 0 time(s), spending 0 gas."));
+
+    assert!(project_path.join(".vibranium").join("stats.json").is_file());
 
     tmp_dir.close()?;
     Ok(())
@@ -1193,87 +3387,152 @@ mod deploy_cmd {
 }
 
 #[cfg(test)]
-mod list_cmd {
+mod package_cmd {
 
   use std::process::Command;
+  use std::fs;
   use assert_cmd::prelude::*;
   use predicates::prelude::*;
+  use tempfile::tempdir;
 
   use super::setup_vibranium_project;
+  use super::create_test_contract;
   use super::create_test_artifact;
-  use vibranium::config::{
-    ProjectConfig,
-    ProjectDeploymentConfig,
-    SmartContractConfig,
-    SmartContractArg
-  };
 
   #[test]
-  fn it_should_show_no_tracking_data_exists_if_no_tracking_database() -> Result<(), Box<std::error::Error>> {
+  fn it_should_fail_if_there_is_nothing_to_package() -> Result<(), Box<std::error::Error>> {
     let (tmp_dir, project_path) = setup_vibranium_project(None)?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("list")
+    cmd.arg("package")
+        .arg("--release")
+        .arg("v1.0.0")
         .arg("--path")
         .arg(&project_path);
 
     cmd.assert()
-        .success()
-        .stdout(predicate::str::contains("No Smart Contract data"));
+        .failure()
+        .stderr(predicate::str::contains("Couldn't find any sources or artifacts to package"));
 
     tmp_dir.close()?;
     Ok(())
   }
-  
-  #[test]
-  fn it_should_show_tracked_smart_contract_data() -> Result<(), Box<std::error::Error>> {
-
-    let mut config = ProjectConfig::default();
-    let contract_name = "SimpleTestContract";
-
-    config.deployment = Some(ProjectDeploymentConfig {
-      gas_limit: None,
-      gas_price: None,
-      tx_confirmations: None,
-      tracking_enabled: None,
-      smart_contracts: vec![
-        SmartContractConfig {
-          name: contract_name.to_string(),
-          address: None,
-          instance_of: None,
-          args: Some(vec![
-            SmartContractArg { value: "200".to_string(),kind: "uint".to_string() },
-          ]),
-          gas_limit: None,
-          gas_price: None,
-          abi_path: None,
-          bytecode_path: None,
-        },
-      ],
-    });
 
-    let (tmp_dir, project_path) = setup_vibranium_project(Some(config))?;
+  #[test]
+  fn it_should_create_reproducible_packages() -> Result<(), Box<std::error::Error>> {
+    let (tmp_dir, project_path) = setup_vibranium_project(None)?;
+    let output_dir = tempdir()?;
 
+    create_test_contract(&project_path, "simple_test_contract.sol")?;
     create_test_artifact(&project_path, "SimpleTestContract.abi")?;
     create_test_artifact(&project_path, "SimpleTestContract.bin")?;
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("deploy")
+    cmd.arg("package")
+        .arg("--release")
+        .arg("v1.0.0")
         .arg("--path")
         .arg(&project_path);
 
-    cmd.assert().success();
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("test_dapp-v1.0.0.tar"));
 
     let mut cmd = Command::main_binary()?;
-    cmd.arg("list")
+    cmd.arg("package")
+        .arg("--release")
+        .arg("v1.0.0")
+        .arg("--output")
+        .arg(output_dir.path().join("release.tar"))
         .arg("--path")
         .arg(&project_path);
 
+    cmd.assert().success();
+
+    let package = fs::read(project_path.join("test_dapp-v1.0.0.tar"))?;
+    let other_package = fs::read(output_dir.path().join("release.tar"))?;
+
+    assert_eq!(package, other_package);
+    assert!(String::from_utf8_lossy(&package).contains("test_dapp-v1.0.0/manifest.json"));
+    assert!(String::from_utf8_lossy(&package).contains("test_dapp-v1.0.0/artifacts/SimpleTestContract.bin"));
+
+    output_dir.close()?;
+    tmp_dir.close()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod help_cmd {
+
+  use std::process::Command;
+  use assert_cmd::prelude::*;
+  use predicates::prelude::*;
+
+  #[test]
+  fn it_should_list_help_topics() -> Result<(), Box<std::error::Error>> {
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("--help");
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("Deployed Smart Contracts:\n  0x"));
+        .stdout(predicate::str::contains("HELP TOPICS:"))
+        .stdout(predicate::str::contains("tracking"));
+    Ok(())
+  }
 
-    tmp_dir.close()?;
+  #[test]
+  fn it_should_print_help_topic() -> Result<(), Box<std::error::Error>> {
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("help").arg("tracking");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(".vibranium/tracking.toml"));
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_print_subcommand_help_with_examples() -> Result<(), Box<std::error::Error>> {
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("help").arg("deploy");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("EXAMPLES:"))
+        .stdout(predicate::str::contains("$ vibranium deploy --no-tracking"))
+        .stdout(predicate::str::contains("RELATED COMMANDS:"));
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_list_examples_for_every_subcommand() -> Result<(), Box<std::error::Error>> {
+    let output = Command::main_binary()?.arg("--help").output()?;
+    let help = String::from_utf8(output.stdout)?;
+    let subcommands: Vec<&str> = help.lines()
+      .skip_while(|line| *line != "SUBCOMMANDS:")
+      .skip(1)
+      .take_while(|line| !line.is_empty())
+      .filter(|line| line.starts_with("    ") && !line.starts_with("     "))
+      .filter_map(|line| line.split_whitespace().next())
+      .filter(|subcommand| *subcommand != "help")
+      .collect();
+    assert!(subcommands.len() > 1);
+
+    for subcommand in subcommands {
+      let mut cmd = Command::main_binary()?;
+      cmd.arg(subcommand).arg("--help");
+      cmd.assert()
+          .success()
+          .stdout(predicate::str::contains("EXAMPLES:"));
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn it_should_fail_on_unknown_help_topic() -> Result<(), Box<std::error::Error>> {
+    let mut cmd = Command::main_binary()?;
+    cmd.arg("help").arg("unknown");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown help topic 'unknown'"));
     Ok(())
   }
 }
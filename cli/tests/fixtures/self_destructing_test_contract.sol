@@ -0,0 +1,15 @@
+pragma solidity ^0.5.0;
+
+contract SelfDestructingTestContract {
+
+  address payable owner;
+
+  constructor() public {
+    owner = msg.sender;
+  }
+
+  function kill() public {
+    require(msg.sender == owner);
+    selfdestruct(owner);
+  }
+}
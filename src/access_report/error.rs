@@ -0,0 +1,52 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+
+use crate::config;
+use crate::project_generator;
+
+#[derive(Debug)]
+pub enum AccessReportError {
+  VibraniumDirectoryNotFound(project_generator::error::ProjectGenerationError),
+  InvalidConfig(config::error::ConfigError),
+  MissingArtifact(String, String),
+  InvalidAbi(String, serde_json::Error),
+  Io(io::Error),
+}
+
+impl Error for AccessReportError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      AccessReportError::VibraniumDirectoryNotFound(error) => Some(error),
+      AccessReportError::InvalidConfig(error) => Some(error),
+      AccessReportError::MissingArtifact(_name, _path) => None,
+      AccessReportError::InvalidAbi(_name, error) => Some(error),
+      AccessReportError::Io(error) => Some(error),
+    }
+  }
+}
+
+impl fmt::Display for AccessReportError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      AccessReportError::VibraniumDirectoryNotFound(error) => write!(f, "{}", error),
+      AccessReportError::InvalidConfig(error) => write!(f, "{}", error),
+      AccessReportError::MissingArtifact(name, path) => write!(f, "Couldn't find ABI of Smart Contract {} at {}. Please compile first.", name, path),
+      AccessReportError::InvalidAbi(name, error) => write!(f, "Couldn't read ABI of Smart Contract {}: {}", name, error),
+      AccessReportError::Io(error) => write!(f, "Couldn't generate access report: {}", error),
+    }
+  }
+}
+
+impl From<config::error::ConfigError> for AccessReportError {
+  fn from(error: config::error::ConfigError) -> Self {
+    AccessReportError::InvalidConfig(error)
+  }
+}
+
+impl From<io::Error> for AccessReportError {
+  fn from(error: io::Error) -> Self {
+    AccessReportError::Io(error)
+  }
+}
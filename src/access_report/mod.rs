@@ -0,0 +1,395 @@
+pub mod error;
+
+use crate::compiler::{checks, Compiler};
+use crate::compiler::checks::{is_identifier_char, is_word_at};
+use crate::config::Config;
+use crate::interface::{AbiEntry, AbiParam};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+const ARTIFACT_EXTENSION_ABI: &str = "abi";
+
+// Inline checks that restrict who can call a function, in addition to
+// modifiers.
+const INLINE_ACCESS_CHECKS: &[&str] = &["msg.sender ==", "== msg.sender", "msg.sender !=", "!= msg.sender", "hasRole(", "_checkOwner(", "_checkRole("];
+
+// Everything that can follow a function's parameter list, apart from
+// modifiers.
+const FUNCTION_KEYWORDS: &[&str] = &["public", "external", "internal", "private", "pure", "view", "payable", "nonpayable", "constant", "virtual", "override", "returns"];
+
+pub struct AccessReportOptions {
+  pub contract: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessEntry {
+  pub contract: String,
+  pub function: String,
+  pub state_mutability: String,
+  pub modifiers: Vec<String>,
+  pub inline_checks: bool,
+  pub source_found: bool,
+}
+
+impl AccessEntry {
+  // Functions without any modifiers or inline checks can be called by anyone.
+  // That's fine for many functions, but a reviewer should have a look at each
+  // of them.
+  pub fn is_unrestricted(&self) -> bool {
+    self.source_found && self.modifiers.is_empty() && !self.inline_checks
+  }
+
+  pub fn access(&self) -> String {
+    if !self.source_found {
+      return "(source not found)".to_string();
+    }
+    if self.is_unrestricted() {
+      return "UNRESTRICTED".to_string();
+    }
+    let mut access = self.modifiers.clone();
+    if self.inline_checks {
+      access.push("(inline check)".to_string());
+    }
+    access.join(" ")
+  }
+}
+
+impl fmt::Display for AccessEntry {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} {} {}", self.function, self.state_mutability, self.access())
+  }
+}
+
+pub struct AccessReporter<'a> {
+  config: &'a Config,
+}
+
+impl<'a> AccessReporter<'a> {
+  pub fn new(config: &'a Config) -> AccessReporter<'a> {
+    AccessReporter {
+      config
+    }
+  }
+
+  // Lists every state-changing function of the compiled Smart Contracts, as
+  // found in their ABIs, along with the modifiers found in the sources.
+  pub fn report(&self, options: AccessReportOptions) -> Result<Vec<AccessEntry>, error::AccessReportError> {
+    let project_config = self.config.read()?;
+    let artifacts_dir = self.config.project_path.join(&project_config.sources.artifacts);
+
+    let abi_paths: Vec<PathBuf> = match options.contract {
+      Some(contract) => {
+        let abi_path = artifacts_dir.join(format!("{}.{}", &contract, ARTIFACT_EXTENSION_ABI));
+        if !abi_path.exists() {
+          return Err(error::AccessReportError::MissingArtifact(contract, abi_path.to_string_lossy().to_string()));
+        }
+        vec![abi_path]
+      },
+      None => {
+        let mut abi_paths: Vec<PathBuf> = fs::read_dir(&artifacts_dir)?
+          .filter_map(Result::ok)
+          .map(|entry| entry.path())
+          .filter(|path| path.extension().map(|extension| extension == ARTIFACT_EXTENSION_ABI).unwrap_or(false))
+          .collect();
+        abi_paths.sort();
+        abi_paths
+      }
+    };
+
+    let mut sources = String::new();
+    for path in Compiler::new(self.config).source_files(&project_config.sources.smart_contracts) {
+      sources.push_str(&fs::read_to_string(path)?);
+      sources.push('\n');
+    }
+    let contracts = parse_contracts(&checks::strip_comments_and_strings(&sources));
+
+    let mut entries = vec![];
+    for abi_path in abi_paths {
+      let name = abi_path.file_stem().unwrap().to_string_lossy().to_string();
+      let abi: Vec<AbiEntry> = serde_json::from_str(&fs::read_to_string(&abi_path)?)
+        .map_err(|err| error::AccessReportError::InvalidAbi(name.clone(), err))?;
+      entries.extend(access_entries(&name, &abi, &contracts));
+    }
+    Ok(entries)
+  }
+}
+
+#[derive(Debug, Default)]
+struct ContractSource {
+  parents: Vec<String>,
+  functions: Vec<FunctionSource>,
+}
+
+#[derive(Debug)]
+struct FunctionSource {
+  name: String,
+  parameter_count: usize,
+  modifiers: Vec<String>,
+  inline_checks: bool,
+}
+
+fn access_entries(contract: &str, abi: &[AbiEntry], contracts: &HashMap<String, ContractSource>) -> Vec<AccessEntry> {
+  abi.iter()
+    .filter(|entry| is_state_changing(entry))
+    .map(|entry| {
+      let name = entry.name.clone().unwrap_or_default();
+      let inputs: &[AbiParam] = entry.inputs.as_deref().unwrap_or(&[]);
+      let function = match entry.kind.as_str() {
+        "function" => format!("{}({})", &name, inputs.iter().map(|input| input.kind.as_str()).collect::<Vec<&str>>().join(",")),
+        kind => format!("{}()", kind),
+      };
+      let lookup_name = if entry.kind == "function" { name.as_str() } else { entry.kind.as_str() };
+      let source = find_function(contract, lookup_name, inputs.len(), contracts);
+
+      AccessEntry {
+        contract: contract.to_owned(),
+        function,
+        state_mutability: entry.state_mutability.clone().unwrap_or_else(|| {
+          if entry.payable.unwrap_or(false) { "payable".to_string() } else { "nonpayable".to_string() }
+        }),
+        modifiers: source.map(|source| source.modifiers.clone()).unwrap_or_default(),
+        inline_checks: source.map(|source| source.inline_checks).unwrap_or(false),
+        source_found: source.is_some(),
+      }
+    })
+    .collect()
+}
+
+// ABIs generated by older compilers only flag functions as `constant`.
+fn is_state_changing(entry: &AbiEntry) -> bool {
+  let is_callable = entry.kind == "function" || entry.kind == "fallback" || entry.kind == "receive";
+  let is_read_only = match entry.state_mutability.as_deref() {
+    Some(mutability) => mutability == "view" || mutability == "pure",
+    None => entry.constant.unwrap_or(false),
+  };
+  is_callable && !is_read_only
+}
+
+// Inherited functions are looked up in the parent contracts, closest first.
+fn find_function<'c>(contract: &str, name: &str, parameter_count: usize, contracts: &'c HashMap<String, ContractSource>) -> Option<&'c FunctionSource> {
+  let mut queue = vec![contract.to_string()];
+  let mut visited = vec![];
+
+  while !queue.is_empty() {
+    let current = queue.remove(0);
+    if visited.contains(&current) {
+      continue;
+    }
+    if let Some(source) = contracts.get(&current) {
+      let function = source.functions.iter().find(|function| function.name == name && function.parameter_count == parameter_count);
+      if function.is_some() {
+        return function;
+      }
+      queue.extend(source.parents.iter().rev().cloned());
+    }
+    visited.push(current);
+  }
+  None
+}
+
+fn parse_contracts(code: &str) -> HashMap<String, ContractSource> {
+  let mut contracts = HashMap::new();
+
+  for (offset, _) in code.match_indices("contract") {
+    if !is_word_at(code, offset, "contract") {
+      continue;
+    }
+    let header_end = match code[offset..].find('{') {
+      Some(end) => offset + end,
+      None => continue,
+    };
+    let header = &code[offset + "contract".len()..header_end];
+    let mut words = header.split(|c: char| c.is_whitespace() || c == ',').filter(|word| !word.is_empty());
+    let name = match words.next() {
+      Some(name) => name.to_string(),
+      None => continue,
+    };
+    let parents = if words.next() == Some("is") {
+      words.map(|parent| parent.split('(').next().unwrap_or(parent).to_string()).collect()
+    } else {
+      vec![]
+    };
+
+    let body_end = matching_brace(code, header_end).unwrap_or(code.len());
+    contracts.insert(name, ContractSource {
+      parents,
+      functions: parse_functions(&code[header_end + 1..body_end]),
+    });
+  }
+
+  contracts
+}
+
+fn parse_functions(code: &str) -> Vec<FunctionSource> {
+  let mut functions = vec![];
+  let keywords: Vec<(usize, &str)> = ["function", "fallback", "receive"].iter()
+    .flat_map(|keyword| code.match_indices(*keyword).map(move |(offset, _)| (offset, *keyword)))
+    .collect();
+
+  for (offset, keyword) in keywords {
+    if !is_word_at(code, offset, keyword) {
+      continue;
+    }
+    let rest = &code[offset + keyword.len()..];
+    let open = match rest.find('(') {
+      Some(open) => open,
+      None => continue,
+    };
+    let name = if keyword == "function" { rest[..open].trim() } else { keyword };
+    // `function()` declares an unnamed fallback function in Solidity < 0.6
+    let name = if name.is_empty() { "fallback" } else { name };
+    if name.chars().any(|c| !is_identifier_char(c)) {
+      continue;
+    }
+    let close = match matching_paren(rest, open) {
+      Some(close) => close,
+      None => continue,
+    };
+
+    let parameters = rest[open + 1..close].trim();
+    let parameter_count = if parameters.is_empty() { 0 } else { parameters.split(',').count() };
+
+    let header_end = rest[close..].find(['{', ';']).map(|end| close + end).unwrap_or(rest.len());
+    let body = if rest[header_end..].starts_with('{') {
+      let body_end = matching_brace(rest, header_end).unwrap_or(rest.len());
+      &rest[header_end..body_end]
+    } else {
+      ""
+    };
+
+    functions.push(FunctionSource {
+      name: name.to_string(),
+      parameter_count,
+      modifiers: parse_modifiers(&rest[close + 1..header_end]),
+      inline_checks: INLINE_ACCESS_CHECKS.iter().any(|check| body.contains(check)),
+    });
+  }
+
+  functions
+}
+
+// Splits what follows a function's parameter list into words, keeping
+// arguments of modifiers (e.g. `onlyRole(ADMIN_ROLE)`) attached to them.
+fn parse_modifiers(header: &str) -> Vec<String> {
+  let mut words: Vec<String> = vec![];
+  let mut depth = 0;
+  let mut current = String::new();
+
+  for c in header.chars() {
+    if c.is_whitespace() && depth == 0 {
+      if !current.is_empty() {
+        words.push(std::mem::take(&mut current));
+      }
+      continue;
+    }
+    if c == '(' {
+      depth += 1;
+    } else if c == ')' {
+      depth -= 1;
+    }
+    current.push(c);
+  }
+  if !current.is_empty() {
+    words.push(current);
+  }
+
+  words.into_iter()
+    .filter(|word| {
+      let keyword = word.split('(').next().unwrap_or(word);
+      !keyword.is_empty() && !FUNCTION_KEYWORDS.contains(&keyword)
+    })
+    .collect()
+}
+
+fn matching_brace(code: &str, open: usize) -> Option<usize> {
+  matching(code, open, '{', '}')
+}
+
+fn matching_paren(code: &str, open: usize) -> Option<usize> {
+  matching(code, open, '(', ')')
+}
+
+fn matching(code: &str, open: usize, open_char: char, close_char: char) -> Option<usize> {
+  let mut depth = 0;
+  for (i, c) in code[open..].char_indices() {
+    if c == open_char {
+      depth += 1;
+    } else if c == close_char {
+      depth -= 1;
+      if depth == 0 {
+        return Some(open + i);
+      }
+    }
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{access_entries, parse_contracts};
+  use crate::compiler::checks::strip_comments_and_strings;
+  use crate::interface::AbiEntry;
+
+  const SOURCE: &str = "
+    contract Ownable {
+      address owner;
+      modifier onlyOwner() { require(msg.sender == owner); _; }
+      function transferOwnership(address newOwner) public onlyOwner { owner = newOwner; }
+    }
+
+    contract Token is Ownable {
+      function mint(address to, uint256 amount) external onlyOwner returns (bool) { return true; }
+      function grant(address to) external override(Ownable) onlyRole(ADMIN_ROLE) {}
+      function transfer(address to, uint256 amount) public virtual returns (bool success) { return true; }
+      // function burn(uint256 amount) public onlyOwner {}
+      function burn(uint256 amount) public {
+        require(msg.sender == owner, \"not owner\");
+      }
+      function balanceOf(address account) public view returns (uint256) { return 0; }
+    }
+  ";
+
+  fn abi() -> Vec<AbiEntry> {
+    serde_json::from_str(r#"[
+      {"inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}],"name":"mint","outputs":[{"name":"","type":"bool"}],"stateMutability":"nonpayable","type":"function"},
+      {"inputs":[{"name":"to","type":"address"}],"name":"grant","outputs":[],"stateMutability":"nonpayable","type":"function"},
+      {"inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}],"name":"transfer","outputs":[{"name":"success","type":"bool"}],"stateMutability":"nonpayable","type":"function"},
+      {"inputs":[{"name":"amount","type":"uint256"}],"name":"burn","outputs":[],"stateMutability":"nonpayable","type":"function"},
+      {"inputs":[{"name":"newOwner","type":"address"}],"name":"transferOwnership","outputs":[],"stateMutability":"nonpayable","type":"function"},
+      {"inputs":[{"name":"account","type":"address"}],"name":"balanceOf","outputs":[{"name":"","type":"uint256"}],"stateMutability":"view","type":"function"},
+      {"inputs":[],"name":"unknown","outputs":[],"stateMutability":"payable","type":"function"}
+    ]"#).unwrap()
+  }
+
+  #[test]
+  fn it_should_list_modifiers_of_state_changing_functions() {
+    let contracts = parse_contracts(&strip_comments_and_strings(SOURCE));
+    let entries = access_entries("Token", &abi(), &contracts);
+    let lines: Vec<String> = entries.iter().map(|entry| entry.to_string()).collect();
+
+    assert_eq!(lines, vec![
+      "mint(address,uint256) nonpayable onlyOwner",
+      "grant(address) nonpayable onlyRole(ADMIN_ROLE)",
+      "transfer(address,uint256) nonpayable UNRESTRICTED",
+      "burn(uint256) nonpayable (inline check)",
+      "transferOwnership(address) nonpayable onlyOwner",
+      "unknown() payable (source not found)",
+    ]);
+  }
+
+  #[test]
+  fn it_should_flag_unrestricted_functions() {
+    let contracts = parse_contracts(&strip_comments_and_strings(SOURCE));
+    let unrestricted: Vec<String> = access_entries("Token", &abi(), &contracts)
+      .into_iter()
+      .filter(|entry| entry.is_unrestricted())
+      .map(|entry| entry.function)
+      .collect();
+
+    assert_eq!(unrestricted, vec!["transfer(address,uint256)"]);
+  }
+}
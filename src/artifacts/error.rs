@@ -0,0 +1,57 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+
+use crate::config;
+
+#[derive(Debug)]
+pub enum ArtifactsError {
+  Io(io::Error),
+  InvalidConfig(config::error::ConfigError),
+  Serialization(serde_json::Error),
+  UnsupportedFormat(String),
+  InvalidAbi(String, serde_json::Error),
+}
+
+impl Error for ArtifactsError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      ArtifactsError::Io(error) => Some(error),
+      ArtifactsError::InvalidConfig(error) => Some(error),
+      ArtifactsError::Serialization(error) => Some(error),
+      ArtifactsError::UnsupportedFormat(_format) => None,
+      ArtifactsError::InvalidAbi(_name, error) => Some(error),
+    }
+  }
+}
+
+impl fmt::Display for ArtifactsError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ArtifactsError::Io(error) => write!(f, "Couldn't access compiler artifacts: {}", error),
+      ArtifactsError::InvalidConfig(error) => write!(f, "{}", error),
+      ArtifactsError::Serialization(error) => write!(f, "Couldn't read compiler artifacts: {}", error),
+      ArtifactsError::UnsupportedFormat(format) => write!(f, "Unsupported artifact format '{}' in `compiler.artifact_format`, expected 'solc' or 'json'", format),
+      ArtifactsError::InvalidAbi(name, error) => write!(f, "Couldn't read ABI of Smart Contract '{}': {}", name, error),
+    }
+  }
+}
+
+impl From<io::Error> for ArtifactsError {
+  fn from(error: io::Error) -> Self {
+    ArtifactsError::Io(error)
+  }
+}
+
+impl From<config::error::ConfigError> for ArtifactsError {
+  fn from(error: config::error::ConfigError) -> Self {
+    ArtifactsError::InvalidConfig(error)
+  }
+}
+
+impl From<serde_json::Error> for ArtifactsError {
+  fn from(error: serde_json::Error) -> Self {
+    ArtifactsError::Serialization(error)
+  }
+}
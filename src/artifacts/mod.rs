@@ -0,0 +1,359 @@
+//! Normalized compiler output. With `artifact_format = "json"` in the
+//! `[compiler]` section of vibranium.toml, a `<Name>.json` artifact is written
+//! per Smart Contract next to the compiler's own output. Artifacts follow the
+//! shape of Truffle and Hardhat artifacts, so that other tools can consume
+//! them.
+
+pub mod error;
+
+use crate::config::Config;
+use crate::process::ProcessCommand;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const ARTIFACT_EXTENSION_JSON: &str = "json";
+pub const COMBINED_JSON_FILE: &str = "combined.json";
+const ARTIFACT_EXTENSION_ABI: &str = "abi";
+const ARTIFACT_EXTENSION_BINARY: &str = "bin";
+const ARTIFACT_EXTENSION_RUNTIME_BINARY: &str = "bin-runtime";
+const ARTIFACT_FORMAT_SOLC: &str = "solc";
+const ARTIFACT_FORMAT_JSON: &str = "json";
+const HARDHAT_ARTIFACT_FORMAT: &str = "hh-sol-artifact-1";
+const DEFAULT_OPTIMIZER_RUNS: u64 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArtifactFormat {
+  Solc,
+  Json,
+}
+
+impl FromStr for ArtifactFormat {
+  type Err = error::ArtifactsError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      ARTIFACT_FORMAT_SOLC => Ok(ArtifactFormat::Solc),
+      ARTIFACT_FORMAT_JSON => Ok(ArtifactFormat::Json),
+      _ => Err(error::ArtifactsError::UnsupportedFormat(s.to_string())),
+    }
+  }
+}
+
+impl fmt::Display for ArtifactFormat {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ArtifactFormat::Solc => write!(f, "{}", ARTIFACT_FORMAT_SOLC),
+      ArtifactFormat::Json => write!(f, "{}", ARTIFACT_FORMAT_JSON),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CompilerInfo {
+  pub name: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub version: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OptimizerSettings {
+  pub enabled: bool,
+  pub runs: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CompilerSettings {
+  pub optimizer: OptimizerSettings,
+  pub options: Vec<String>,
+}
+
+impl CompilerSettings {
+  pub fn from_options(options: &[String]) -> CompilerSettings {
+    let runs = options.iter()
+      .position(|option| option == "--optimize-runs")
+      .and_then(|index| options.get(index + 1))
+      .and_then(|runs| runs.parse().ok());
+
+    CompilerSettings {
+      optimizer: OptimizerSettings {
+        enabled: options.iter().any(|option| option == "--optimize"),
+        runs: runs.unwrap_or(DEFAULT_OPTIMIZER_RUNS),
+      },
+      options: options.to_vec(),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Artifact {
+  #[serde(rename = "_format")]
+  pub format: String,
+  pub contract_name: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub source_name: Option<String>,
+  pub abi: serde_json::Value,
+  pub bytecode: String,
+  pub deployed_bytecode: String,
+  pub source_map: String,
+  pub deployed_source_map: String,
+  pub compiler: CompilerInfo,
+  pub settings: CompilerSettings,
+}
+
+// Contracts as emitted by `solc --combined-json abi,bin,bin-runtime,srcmap,srcmap-runtime`.
+#[derive(Deserialize, Debug)]
+struct CombinedJson {
+  contracts: BTreeMap<String, CombinedJsonContract>,
+  version: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CombinedJsonContract {
+  abi: Option<serde_json::Value>,
+  bin: Option<String>,
+  #[serde(rename = "bin-runtime")]
+  bin_runtime: Option<String>,
+  srcmap: Option<String>,
+  #[serde(rename = "srcmap-runtime")]
+  srcmap_runtime: Option<String>,
+}
+
+pub struct Artifacts<'a> {
+  config: &'a Config,
+}
+
+impl<'a> Artifacts<'a> {
+  pub fn new(config: &'a Config) -> Artifacts<'a> {
+    Artifacts {
+      config
+    }
+  }
+
+  pub fn format(&self) -> Result<ArtifactFormat, error::ArtifactsError> {
+    let project_config = self.config.read()?;
+    project_config.compiler
+      .and_then(|config| config.artifact_format)
+      .map(|format| format.parse())
+      .unwrap_or(Ok(ArtifactFormat::Solc))
+  }
+
+  // Writes artifacts if the configured format asks for it, returning the
+  // paths of the written files.
+  pub fn normalize(&self, compiler: &str, options: &[String]) -> Result<Vec<PathBuf>, error::ArtifactsError> {
+    match self.format()? {
+      ArtifactFormat::Solc => Ok(vec![]),
+      ArtifactFormat::Json => {
+        let artifacts = self.collect(compiler, options)?;
+        self.write(&artifacts)
+      },
+    }
+  }
+
+  // Output of `--combined-json` takes precedence over separate `.abi`, `.bin`
+  // and `.bin-runtime` files of the same Smart Contract.
+  pub fn collect(&self, compiler: &str, options: &[String]) -> Result<Vec<Artifact>, error::ArtifactsError> {
+    let artifacts_dir = self.get_artifacts_dir()?;
+    let settings = CompilerSettings::from_options(options);
+    let mut artifacts: BTreeMap<String, Artifact> = BTreeMap::new();
+
+    let combined_json_path = artifacts_dir.join(COMBINED_JSON_FILE);
+    if combined_json_path.exists() {
+      let combined: CombinedJson = serde_json::from_str(&fs::read_to_string(combined_json_path)?)?;
+      for (key, contract) in combined.contracts {
+        let artifact = from_combined_json(&key, contract, compiler, &combined.version, &settings)?;
+        artifacts.insert(artifact.contract_name.clone(), artifact);
+      }
+    }
+
+    let mut abi_paths: Vec<PathBuf> = fs::read_dir(&artifacts_dir)?
+      .filter_map(Result::ok)
+      .map(|entry| entry.path())
+      .filter(|path| path.extension().is_some_and(|extension| extension == ARTIFACT_EXTENSION_ABI))
+      .filter(|path| path.file_stem().is_some_and(|name| !artifacts.contains_key(name.to_string_lossy().as_ref())))
+      .collect();
+    abi_paths.sort();
+
+    let version = if abi_paths.is_empty() { None } else { compiler_version(compiler, &self.config.vibranium_dir_path) };
+    for abi_path in abi_paths {
+      let artifact = from_separate_files(&abi_path, compiler, &version, &settings)?;
+      artifacts.insert(artifact.contract_name.clone(), artifact);
+    }
+
+    Ok(artifacts.into_values().collect())
+  }
+
+  pub fn write(&self, artifacts: &[Artifact]) -> Result<Vec<PathBuf>, error::ArtifactsError> {
+    let artifacts_dir = self.get_artifacts_dir()?;
+    artifacts.iter().map(|artifact| {
+      let path = artifacts_dir.join(format!("{}.{}", &artifact.contract_name, ARTIFACT_EXTENSION_JSON));
+      fs::write(&path, serde_json::to_string_pretty(artifact)?)?;
+      Ok(path)
+    }).collect()
+  }
+
+  fn get_artifacts_dir(&self) -> Result<PathBuf, error::ArtifactsError> {
+    let project_config = self.config.read()?;
+    Ok(self.config.project_path.join(&project_config.sources.artifacts))
+  }
+}
+
+// Keys of `--combined-json` output are qualified with the source file, as in
+// `contracts/Token.sol:Token`.
+fn from_combined_json(key: &str, contract: CombinedJsonContract, compiler: &str, version: &Option<String>, settings: &CompilerSettings) -> Result<Artifact, error::ArtifactsError> {
+  let (source_name, contract_name) = match key.rfind(':') {
+    Some(index) => (Some(key[..index].to_string()), key[index + 1..].to_string()),
+    None => (None, key.to_string()),
+  };
+
+  // Older compilers emit the ABI as a JSON encoded string.
+  let abi = match contract.abi {
+    Some(serde_json::Value::String(abi)) => serde_json::from_str(&abi).map_err(|err| error::ArtifactsError::InvalidAbi(contract_name.clone(), err))?,
+    Some(abi) => abi,
+    None => serde_json::Value::Array(vec![]),
+  };
+
+  Ok(Artifact {
+    format: HARDHAT_ARTIFACT_FORMAT.to_string(),
+    contract_name,
+    source_name,
+    abi,
+    bytecode: hex_prefixed(&contract.bin.unwrap_or_default()),
+    deployed_bytecode: hex_prefixed(&contract.bin_runtime.unwrap_or_default()),
+    source_map: contract.srcmap.unwrap_or_default(),
+    deployed_source_map: contract.srcmap_runtime.unwrap_or_default(),
+    compiler: CompilerInfo { name: compiler.to_string(), version: version.clone() },
+    settings: settings.clone(),
+  })
+}
+
+// Separate files don't tell which source file they're compiled from, nor do
+// they come with source maps.
+fn from_separate_files(abi_path: &Path, compiler: &str, version: &Option<String>, settings: &CompilerSettings) -> Result<Artifact, error::ArtifactsError> {
+  let contract_name = abi_path.file_stem().unwrap().to_string_lossy().to_string();
+  let abi = serde_json::from_str(&fs::read_to_string(abi_path)?).map_err(|err| error::ArtifactsError::InvalidAbi(contract_name.clone(), err))?;
+  let read_bytecode = |extension: &str| -> Result<String, error::ArtifactsError> {
+    let path = abi_path.with_extension(extension);
+    Ok(if path.exists() { hex_prefixed(&fs::read_to_string(path)?) } else { hex_prefixed("") })
+  };
+
+  Ok(Artifact {
+    format: HARDHAT_ARTIFACT_FORMAT.to_string(),
+    bytecode: read_bytecode(ARTIFACT_EXTENSION_BINARY)?,
+    deployed_bytecode: read_bytecode(ARTIFACT_EXTENSION_RUNTIME_BINARY)?,
+    contract_name,
+    source_name: None,
+    abi,
+    source_map: String::new(),
+    deployed_source_map: String::new(),
+    compiler: CompilerInfo { name: compiler.to_string(), version: version.clone() },
+    settings: settings.clone(),
+  })
+}
+
+fn hex_prefixed(bytecode: &str) -> String {
+  let bytecode = bytecode.trim();
+  format!("0x{}", bytecode.strip_prefix("0x").unwrap_or(bytecode))
+}
+
+// solc prints `Version: 0.5.0+commit.1d4f565a...` as its last line, solcjs
+// only the version itself.
+pub(crate) fn compiler_version(compiler: &str, vibranium_dir_path: &Path) -> Option<String> {
+  let output = ProcessCommand::new(compiler)
+    .arg("--version")
+    .log_to(vibranium_dir_path)
+    .output()
+    .ok()
+    .filter(|output| output.status.success())?;
+
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  let version = stdout.lines().rfind(|line| !line.trim().is_empty())?.trim();
+  Some(version.strip_prefix("Version:").unwrap_or(version).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{Artifacts, ArtifactFormat, CompilerSettings};
+  use crate::config::Config;
+  use std::fs;
+
+  const COMBINED_JSON: &str = r#"{
+    "contracts": {
+      "contracts/Token.sol:Token": {
+        "abi": "[{\"type\":\"constructor\",\"inputs\":[]}]",
+        "bin": "6080",
+        "bin-runtime": "6001",
+        "srcmap": "25:50:0:-;;",
+        "srcmap-runtime": "25:50:0:-;;;"
+      }
+    },
+    "version": "0.5.8+commit.23d335f2.Linux.g++"
+  }"#;
+
+  fn setup_project() -> tempfile::TempDir {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let vibranium = crate::Vibranium::new(tmp_dir.path().to_path_buf());
+    vibranium.init_project().unwrap();
+    tmp_dir
+  }
+
+  #[test]
+  fn it_should_parse_artifact_formats_and_settings() {
+    assert_eq!("json".parse::<ArtifactFormat>().unwrap(), ArtifactFormat::Json);
+    assert!("truffle".parse::<ArtifactFormat>().is_err());
+
+    let settings = CompilerSettings::from_options(&["--optimize".to_string(), "--optimize-runs".to_string(), "1000".to_string()]);
+    assert!(settings.optimizer.enabled);
+    assert_eq!(settings.optimizer.runs, 1000);
+    assert_eq!(CompilerSettings::from_options(&[]).optimizer.runs, 200);
+  }
+
+  #[test]
+  fn it_should_normalize_combined_json_and_separate_files() {
+    let tmp_dir = setup_project();
+    let artifacts_dir = tmp_dir.path().join("artifacts");
+    fs::write(artifacts_dir.join("combined.json"), COMBINED_JSON).unwrap();
+    fs::write(artifacts_dir.join("Token.abi"), "[]").unwrap();
+    fs::write(artifacts_dir.join("SafeMath.abi"), "[]").unwrap();
+    fs::write(artifacts_dir.join("SafeMath.bin"), "0x6080\n").unwrap();
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let artifacts = Artifacts::new(&config).collect("true", &[]).unwrap();
+    assert_eq!(artifacts.len(), 2);
+
+    assert_eq!(artifacts[0].contract_name, "SafeMath");
+    assert_eq!(artifacts[0].source_name, None);
+    assert_eq!(artifacts[0].bytecode, "0x6080");
+    assert_eq!(artifacts[0].deployed_bytecode, "0x");
+
+    assert_eq!(artifacts[1].contract_name, "Token");
+    assert_eq!(artifacts[1].source_name, Some("contracts/Token.sol".to_string()));
+    assert_eq!(artifacts[1].abi[0]["type"], "constructor");
+    assert_eq!(artifacts[1].deployed_source_map, "25:50:0:-;;;");
+    assert_eq!(artifacts[1].compiler.version, Some("0.5.8+commit.23d335f2.Linux.g++".to_string()));
+  }
+
+  #[test]
+  fn it_should_write_artifacts_in_hardhat_shape() {
+    let tmp_dir = setup_project();
+    let artifacts_dir = tmp_dir.path().join("artifacts");
+    fs::write(artifacts_dir.join("combined.json"), COMBINED_JSON).unwrap();
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let artifacts = Artifacts::new(&config);
+    assert!(artifacts.normalize("solc", &[]).unwrap().is_empty());
+
+    let paths = artifacts.write(&artifacts.collect("solc", &[]).unwrap()).unwrap();
+    assert_eq!(paths, vec![artifacts_dir.join("Token.json")]);
+
+    let artifact: serde_json::Value = serde_json::from_str(&fs::read_to_string(&paths[0]).unwrap()).unwrap();
+    assert_eq!(artifact["_format"], "hh-sol-artifact-1");
+    assert_eq!(artifact["contractName"], "Token");
+    assert_eq!(artifact["deployedBytecode"], "0x6001");
+    assert_eq!(artifact["settings"]["optimizer"]["enabled"], false);
+  }
+}
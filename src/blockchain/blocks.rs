@@ -0,0 +1,168 @@
+use super::connector::BlockchainConnector;
+use super::error::ConnectionError;
+use crate::utils;
+
+use std::fmt;
+use std::str::FromStr;
+use web3::types::{BlockId, BlockNumber};
+
+// Blocks as they're accepted by command line options: numbers (decimal or
+// `0x` prefixed), the tags `latest`, `safe`, `finalized` and `earliest`,
+// offsets from the latest block (`-100`) and ISO 8601 timestamps
+// (`2019-06-01` or `2019-06-01T12:00:00Z`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockRef {
+  Number(u64),
+  Latest,
+  Safe,
+  Finalized,
+  Earliest,
+  BeforeLatest(u64),
+  Timestamp(u64),
+}
+
+impl FromStr for BlockRef {
+  type Err = ConnectionError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let block = s.trim();
+    let invalid = || ConnectionError::InvalidBlock(s.to_string());
+    match block {
+      "latest" => Ok(BlockRef::Latest),
+      "safe" => Ok(BlockRef::Safe),
+      "finalized" => Ok(BlockRef::Finalized),
+      "earliest" => Ok(BlockRef::Earliest),
+      _ if block.starts_with('-') => block[1..].parse().map(BlockRef::BeforeLatest).map_err(|_err| invalid()),
+      _ if block.starts_with("0x") => u64::from_str_radix(&block[2..], 16).map(BlockRef::Number).map_err(|_err| invalid()),
+      _ if block.contains('-') => utils::parse_timestamp(block).map(BlockRef::Timestamp).ok_or_else(invalid),
+      _ => block.parse().map(BlockRef::Number).map_err(|_err| invalid()),
+    }
+  }
+}
+
+impl fmt::Display for BlockRef {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      BlockRef::Number(number) => write!(f, "{}", number),
+      BlockRef::Latest => write!(f, "latest"),
+      BlockRef::Safe => write!(f, "safe"),
+      BlockRef::Finalized => write!(f, "finalized"),
+      BlockRef::Earliest => write!(f, "earliest"),
+      BlockRef::BeforeLatest(offset) => write!(f, "-{}", offset),
+      BlockRef::Timestamp(timestamp) => write!(f, "@{}", timestamp),
+    }
+  }
+}
+
+impl BlockRef {
+  // Offsets reaching past the genesis block resolve to it. Timestamps resolve
+  // to the last block mined at or before them, found by a binary search over
+  // block timestamps.
+  pub fn resolve(&self, connector: &BlockchainConnector) -> Result<u64, ConnectionError> {
+    match self {
+      BlockRef::Number(number) => Ok(*number),
+      BlockRef::Earliest => Ok(0),
+      BlockRef::Latest => latest_block(connector),
+      BlockRef::BeforeLatest(offset) => Ok(latest_block(connector)?.saturating_sub(*offset)),
+      BlockRef::Safe | BlockRef::Finalized => {
+        let block = connector.get_block_by_tag(&self.to_string())?
+          .ok_or_else(|| ConnectionError::Other(format!("The connected node doesn't know a {} block", self)))?;
+        block.number.map(|number| number.low_u64()).ok_or_else(|| ConnectionError::Other(format!("The {} block of the connected node has no number", self)))
+      },
+      BlockRef::Timestamp(timestamp) => {
+        let (mut low, mut high) = (0, latest_block(connector)?);
+        if block_timestamp(connector, high)? <= *timestamp {
+          return Ok(high);
+        }
+        while low < high {
+          let middle = low + (high - low).div_ceil(2);
+          if block_timestamp(connector, middle)? <= *timestamp {
+            low = middle;
+          } else {
+            high = middle - 1;
+          }
+        }
+        Ok(low)
+      },
+    }
+  }
+}
+
+fn latest_block(connector: &BlockchainConnector) -> Result<u64, ConnectionError> {
+  connector.block_number().map(|block_number| block_number.low_u64())
+}
+
+fn block_timestamp(connector: &BlockchainConnector, number: u64) -> Result<u64, ConnectionError> {
+  connector.get_block(BlockId::Number(BlockNumber::Number(number)))?
+    .map(|block| block.timestamp.low_u64())
+    .ok_or_else(|| ConnectionError::Other(format!("Couldn't find block {}", number)))
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::BlockRef;
+  use crate::testing::MockTransport;
+
+  fn block(number: u64, timestamp: u64) -> serde_json::Value {
+    serde_json::json!({
+      "hash": format!("0x{:064x}", number),
+      "parentHash": format!("0x{:064x}", 0),
+      "sha3Uncles": format!("0x{:064x}", 0),
+      "miner": "0x0000000000000000000000000000000000000000",
+      "stateRoot": format!("0x{:064x}", 0),
+      "transactionsRoot": format!("0x{:064x}", 0),
+      "receiptsRoot": format!("0x{:064x}", 0),
+      "number": format!("0x{:x}", number),
+      "gasUsed": "0x0",
+      "gasLimit": "0x0",
+      "extraData": "0x",
+      "logsBloom": format!("0x{}", "0".repeat(512)),
+      "timestamp": format!("0x{:x}", timestamp),
+      "difficulty": "0x0",
+      "totalDifficulty": "0x0",
+      "sealFields": [],
+      "uncles": [],
+      "transactions": [],
+      "size": "0x0",
+    })
+  }
+
+  #[test]
+  fn it_should_parse_block_references() {
+    assert_eq!("120".parse::<BlockRef>().unwrap(), BlockRef::Number(120));
+    assert_eq!("0x78".parse::<BlockRef>().unwrap(), BlockRef::Number(120));
+    assert_eq!("finalized".parse::<BlockRef>().unwrap(), BlockRef::Finalized);
+    assert_eq!("-100".parse::<BlockRef>().unwrap(), BlockRef::BeforeLatest(100));
+    assert_eq!("2019-06-01".parse::<BlockRef>().unwrap(), BlockRef::Timestamp(1_559_347_200));
+    assert_eq!("2019-06-01T12:30:00Z".parse::<BlockRef>().unwrap(), BlockRef::Timestamp(1_559_392_200));
+    assert_eq!("2019-06-01T14:30:00+02:00".parse::<BlockRef>().unwrap(), BlockRef::Timestamp(1_559_392_200));
+    assert!("yesterday".parse::<BlockRef>().is_err());
+    assert!("2019-13-01".parse::<BlockRef>().is_err());
+  }
+
+  #[test]
+  fn it_should_resolve_offsets_and_timestamps() {
+    let transport = MockTransport::new();
+    transport.set_default_response("eth_blockNumber", serde_json::json!("0x64"));
+    let connector = transport.connector();
+    assert_eq!(BlockRef::BeforeLatest(10).resolve(&connector).unwrap(), 90);
+    assert_eq!(BlockRef::BeforeLatest(1000).resolve(&connector).unwrap(), 0);
+
+    // Blocks are mined every 10 seconds, block 0 at 1000.
+    let transport = MockTransport::new();
+    transport.add_response("eth_blockNumber", serde_json::json!("0x64"));
+    for number in &[100, 50, 25, 37, 43, 40, 41, 42] {
+      transport.add_response("eth_getBlockByNumber", block(*number, 1000 + number * 10));
+    }
+    assert_eq!(BlockRef::Timestamp(1425).resolve(&transport.connector()).unwrap(), 42);
+    assert!(transport.pending_responses().is_empty());
+  }
+
+  #[test]
+  fn it_should_resolve_block_tags() {
+    let transport = MockTransport::new();
+    transport.add_response("eth_getBlockByNumber", block(80, 1800));
+    assert_eq!(BlockRef::Safe.resolve(&transport.connector()).unwrap(), 80);
+    assert_eq!(transport.requests_for("eth_getBlockByNumber")[0], vec![serde_json::json!("safe"), serde_json::json!(false)]);
+  }
+}
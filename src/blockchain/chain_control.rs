@@ -0,0 +1,138 @@
+//! Snapshots of the state of development nodes like ganache and anvil, taken
+//! with `evm_snapshot` and restored with `evm_revert`. Nodes discard a
+//! snapshot once it has been reverted to, along with every later one.
+//!
+//! `vibranium deploy --reset` reverts to the snapshot it took on its previous
+//! run, so that repeated deployments start from the same state. These
+//! snapshots are recorded per chain, i.e. by the hash of its genesis block.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use jsonrpc_core as rpc;
+use web3::types::{H256, U256};
+
+use super::connector::BlockchainConnector;
+use super::error::{ChainControlError, ConnectionError};
+
+pub const SNAPSHOTS_FILE: &str = "snapshots.toml";
+
+pub struct ChainControl<'a> {
+  connector: &'a BlockchainConnector,
+}
+
+impl<'a> ChainControl<'a> {
+  pub fn new(connector: &'a BlockchainConnector) -> ChainControl<'a> {
+    ChainControl {
+      connector,
+    }
+  }
+
+  pub fn snapshot(&self) -> Result<U256, ChainControlError> {
+    self.connector.snapshot().map_err(|err| unsupported("evm_snapshot", err))
+  }
+
+  pub fn revert(&self, snapshot: U256) -> Result<(), ChainControlError> {
+    if self.connector.revert(snapshot).map_err(|err| unsupported("evm_revert", err))? {
+      Ok(())
+    } else {
+      Err(ChainControlError::UnknownSnapshot(format_snapshot(snapshot)))
+    }
+  }
+}
+
+pub fn format_snapshot(snapshot: U256) -> String {
+  format!("0x{:x}", snapshot)
+}
+
+pub fn parse_snapshot(snapshot: &str) -> Option<U256> {
+  U256::from_str(snapshot.trim_start_matches("0x")).ok()
+}
+
+pub fn read_reset_snapshot(vibranium_dir_path: &Path, block_hash: &H256) -> Option<U256> {
+  read_snapshots_file(vibranium_dir_path)
+    .get(&format!("{:?}", block_hash))
+    .and_then(|snapshot| parse_snapshot(snapshot))
+}
+
+pub fn record_reset_snapshot(vibranium_dir_path: &Path, block_hash: &H256, snapshot: U256) -> Result<(), std::io::Error> {
+  let mut snapshots = read_snapshots_file(vibranium_dir_path);
+  snapshots.insert(format!("{:?}", block_hash), format_snapshot(snapshot));
+  let content = toml::to_string(&snapshots).map_err(|err| std::io::Error::other(err.to_string()))?;
+  fs::write(vibranium_dir_path.join(SNAPSHOTS_FILE), content)
+}
+
+fn read_snapshots_file(vibranium_dir_path: &Path) -> BTreeMap<String, String> {
+  fs::read_to_string(vibranium_dir_path.join(SNAPSHOTS_FILE))
+    .ok()
+    .and_then(|content| toml::from_str(&content).ok())
+    .unwrap_or_default()
+}
+
+// Nodes without the method either don't know it or refuse it, depending on
+// the client.
+fn unsupported(method: &str, error: ConnectionError) -> ChainControlError {
+  match &error {
+    ConnectionError::Transport(web3::Error::Rpc(rpc_error)) => {
+      let message = rpc_error.message.to_lowercase();
+      if rpc_error.code == rpc::ErrorCode::MethodNotFound || message.contains("not supported") || message.contains("does not exist") || message.contains("not available") {
+        ChainControlError::Unsupported(method.to_string())
+      } else {
+        ChainControlError::Connection(error)
+      }
+    },
+    _ => ChainControlError::Connection(error),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{read_reset_snapshot, record_reset_snapshot, ChainControl};
+  use crate::blockchain::error::ChainControlError;
+  use crate::testing::MockTransport;
+  use web3::types::{H256, U256};
+
+  #[test]
+  fn it_should_take_and_revert_to_snapshots() {
+    let transport = MockTransport::new();
+    transport.add_response("evm_snapshot", serde_json::json!("0x2"));
+    transport.add_response("evm_revert", serde_json::json!(true));
+    transport.add_response("evm_revert", serde_json::json!(false));
+    let connector = transport.connector();
+    let chain_control = ChainControl::new(&connector);
+
+    let snapshot = chain_control.snapshot().unwrap();
+    assert_eq!(snapshot, U256::from(2));
+    chain_control.revert(snapshot).unwrap();
+    match chain_control.revert(snapshot) {
+      Err(ChainControlError::UnknownSnapshot(snapshot)) => assert_eq!(snapshot, "0x2"),
+      other => panic!("Expected unknown snapshot, got {:?}", other),
+    }
+    assert_eq!(transport.requests_for("evm_revert")[0], vec![serde_json::json!("0x2")]);
+  }
+
+  #[test]
+  fn it_should_report_nodes_without_snapshots() {
+    let transport = MockTransport::new();
+    transport.add_error("evm_snapshot", jsonrpc_core::Error::method_not_found());
+    let connector = transport.connector();
+
+    match ChainControl::new(&connector).snapshot() {
+      Err(ChainControlError::Unsupported(method)) => assert_eq!(method, "evm_snapshot"),
+      other => panic!("Expected unsupported method, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn it_should_record_reset_snapshots_per_chain() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    record_reset_snapshot(tmp_dir.path(), &H256::from_low_u64_be(1), U256::from(5)).unwrap();
+    record_reset_snapshot(tmp_dir.path(), &H256::from_low_u64_be(2), U256::from(7)).unwrap();
+
+    assert_eq!(read_reset_snapshot(tmp_dir.path(), &H256::from_low_u64_be(1)), Some(U256::from(5)));
+    assert_eq!(read_reset_snapshot(tmp_dir.path(), &H256::from_low_u64_be(2)), Some(U256::from(7)));
+    assert_eq!(read_reset_snapshot(tmp_dir.path(), &H256::from_low_u64_be(3)), None);
+  }
+}
@@ -0,0 +1,95 @@
+//! Every network gets its own data directory for nodes spawned by Vibranium,
+//! so that multiple local chains can coexist. The genesis block of each of
+//! these chains is recorded once the node is up, which allows pruning the
+//! chain's tracking data when its data directory gets wiped.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use web3::types::H256;
+
+use crate::project_generator::{DEFAULT_CHAINS_DIR, DEFAULT_DATADIR_NAME, DEFAULT_ENVIRONMENT};
+
+pub const GENESIS_FILE: &str = "chains.toml";
+
+pub fn data_dir(vibranium_dir_path: &Path, network: &str) -> PathBuf {
+  let chain_dir = vibranium_dir_path.join(DEFAULT_CHAINS_DIR).join(network);
+  // Projects created before chains were separated by network keep using
+  // their existing chain for the default network.
+  let legacy_dir = vibranium_dir_path.join(DEFAULT_DATADIR_NAME).join(DEFAULT_ENVIRONMENT);
+  if network == DEFAULT_ENVIRONMENT && !chain_dir.exists() && legacy_dir.exists() {
+    return legacy_dir;
+  }
+  chain_dir
+}
+
+pub fn read_genesis(vibranium_dir_path: &Path, network: &str) -> Option<H256> {
+  read_genesis_file(vibranium_dir_path)
+    .get(network)
+    .and_then(|block_hash| H256::from_str(block_hash.trim_start_matches("0x")).ok())
+}
+
+pub fn record_genesis(vibranium_dir_path: &Path, network: &str, block_hash: &H256) -> Result<(), io::Error> {
+  let mut genesis = read_genesis_file(vibranium_dir_path);
+  genesis.insert(network.to_string(), format!("{:?}", block_hash));
+  write_genesis_file(vibranium_dir_path, &genesis)
+}
+
+pub fn forget_genesis(vibranium_dir_path: &Path, network: &str) -> Result<(), io::Error> {
+  let mut genesis = read_genesis_file(vibranium_dir_path);
+  if genesis.remove(network).is_some() {
+    write_genesis_file(vibranium_dir_path, &genesis)?;
+  }
+  Ok(())
+}
+
+fn read_genesis_file(vibranium_dir_path: &Path) -> BTreeMap<String, String> {
+  fs::read_to_string(vibranium_dir_path.join(GENESIS_FILE))
+    .ok()
+    .and_then(|content| toml::from_str(&content).ok())
+    .unwrap_or_default()
+}
+
+fn write_genesis_file(vibranium_dir_path: &Path, genesis: &BTreeMap<String, String>) -> Result<(), io::Error> {
+  let content = toml::to_string(genesis).map_err(|err| io::Error::other(err.to_string()))?;
+  fs::write(vibranium_dir_path.join(GENESIS_FILE), content)
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{data_dir, forget_genesis, read_genesis, record_genesis};
+  use std::fs;
+  use web3::types::H256;
+
+  #[test]
+  fn it_should_separate_data_directories_by_network() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    assert_eq!(data_dir(tmp_dir.path(), "development"), tmp_dir.path().join("chains").join("development"));
+    assert_eq!(data_dir(tmp_dir.path(), "ganache"), tmp_dir.path().join("chains").join("ganache"));
+  }
+
+  #[test]
+  fn it_should_keep_using_existing_legacy_data_directory() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(tmp_dir.path().join("datadir").join("development")).unwrap();
+    assert_eq!(data_dir(tmp_dir.path(), "development"), tmp_dir.path().join("datadir").join("development"));
+    assert_eq!(data_dir(tmp_dir.path(), "ganache"), tmp_dir.path().join("chains").join("ganache"));
+  }
+
+  #[test]
+  fn it_should_record_genesis_per_network() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    record_genesis(tmp_dir.path(), "development", &H256::from_low_u64_be(1)).unwrap();
+    record_genesis(tmp_dir.path(), "ganache", &H256::from_low_u64_be(2)).unwrap();
+
+    assert_eq!(read_genesis(tmp_dir.path(), "development"), Some(H256::from_low_u64_be(1)));
+    assert_eq!(read_genesis(tmp_dir.path(), "ganache"), Some(H256::from_low_u64_be(2)));
+
+    forget_genesis(tmp_dir.path(), "development").unwrap();
+    assert_eq!(read_genesis(tmp_dir.path(), "development"), None);
+    assert_eq!(read_genesis(tmp_dir.path(), "ganache"), Some(H256::from_low_u64_be(2)));
+  }
+}
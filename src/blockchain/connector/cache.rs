@@ -0,0 +1,346 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::super::error::ConnectionError;
+use super::web3_adapter::Transports;
+use web3::futures::{self, Future};
+use jsonrpc_core as rpc;
+
+pub const CALL_CACHE_FILE: &str = "call-cache.json";
+pub const DEFAULT_CALL_CACHE_TTL: u64 = 15;
+// Oldest calls are dropped once the cache holds more than that.
+const MAX_CACHED_CALLS: usize = 1000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CachedCall {
+  pub network: String,
+  // Hash of the genesis block, which changes whenever a development chain
+  // is started from scratch behind the same endpoint.
+  #[serde(default)]
+  pub chain: String,
+  pub address: String,
+  pub data: String,
+  pub block: String,
+  pub result: rpc::Value,
+  pub cached_at: u64,
+}
+
+impl CachedCall {
+  // Calls pinned to a block number can't change, calls against `latest` or
+  // `pending` expire after the TTL.
+  fn is_fresh(&self, ttl: u64, now: u64) -> bool {
+    self.block.starts_with("0x") || now < self.cached_at + ttl
+  }
+
+  fn is_for(&self, key: &CallKey) -> bool {
+    self.network == key.network && self.chain == key.chain && self.address == key.address && self.data == key.data && self.block == key.block
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct CallCache {
+  pub calls: Vec<CachedCall>,
+}
+
+impl CallCache {
+  // A missing or unreadable cache means nothing has been cached yet.
+  pub fn load(path: &PathBuf) -> CallCache {
+    fs::read_to_string(path).ok()
+      .and_then(|content| serde_json::from_str(&content).map_err(|err| warn!("Ignoring invalid call cache: {}", err)).ok())
+      .unwrap_or_default()
+  }
+
+  pub fn save(&self, path: &PathBuf) -> Result<(), ConnectionError> {
+    let content = serde_json::to_string_pretty(self).map_err(|err| ConnectionError::Other(err.to_string()))?;
+    fs::write(path, content).map_err(|err| ConnectionError::Other(format!("Couldn't write call cache {}: {}", path.to_string_lossy(), err)))
+  }
+
+  pub fn clear(path: &PathBuf) -> Result<(), ConnectionError> {
+    match fs::remove_file(path) {
+      Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(ConnectionError::Other(format!("Couldn't remove call cache {}: {}", path.to_string_lossy(), err))),
+      _ => Ok(()),
+    }
+  }
+
+  fn get(&self, key: &CallKey, ttl: u64, now: u64) -> Option<&rpc::Value> {
+    self.calls.iter()
+      .find(|call| call.is_for(key))
+      .filter(|call| call.is_fresh(ttl, now))
+      .map(|call| &call.result)
+  }
+
+  // Replaces any previous result of the same call and drops expired ones,
+  // as well as the ones of chains that have been replaced.
+  fn insert(&mut self, key: CallKey, result: rpc::Value, ttl: u64, now: u64) {
+    self.calls.retain(|call| call.is_fresh(ttl, now) && !call.is_for(&key) && !(call.network == key.network && call.chain != key.chain));
+    let excess = (self.calls.len() + 1).saturating_sub(MAX_CACHED_CALLS);
+    self.calls.drain(..excess);
+    self.calls.push(CachedCall {
+      network: key.network,
+      chain: key.chain,
+      address: key.address,
+      data: key.data,
+      block: key.block,
+      result,
+      cached_at: now,
+    });
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CallKey {
+  network: String,
+  chain: String,
+  address: String,
+  data: String,
+  block: String,
+}
+
+// Serves results of `eth_call` requests from a cache file, so that commands
+// calling the same view functions over and over don't repeat requests
+// against rate limited providers. All other requests, and calls that
+// failed, are passed on to the wrapped transport untouched.
+#[derive(Debug, Clone)]
+pub struct CachingTransport {
+  transport: Box<Transports>,
+  path: PathBuf,
+  network: String,
+  ttl: u64,
+  cache: Arc<Mutex<CallCache>>,
+  chain: Arc<Mutex<Option<String>>>,
+}
+
+impl CachingTransport {
+  pub fn new(transport: Transports, path: PathBuf, network: String, ttl: u64) -> CachingTransport {
+    CachingTransport {
+      transport: Box::new(transport),
+      cache: Arc::new(Mutex::new(CallCache::load(&path))),
+      chain: Arc::new(Mutex::new(None)),
+      path,
+      network,
+      ttl,
+    }
+  }
+
+  pub fn inner(&self) -> &Transports {
+    &self.transport
+  }
+
+  // Calls sent from an account or with value might depend on who calls or
+  // how much is sent, so only plain calls are cached.
+  fn call_key(&self, method: &str, params: &[rpc::Value]) -> Option<CallKey> {
+    let call = params.first().filter(|_call| method == "eth_call")?;
+    if !call["from"].is_null() || !call["value"].is_null() {
+      return None;
+    }
+    Some(CallKey {
+      network: self.network.clone(),
+      chain: String::new(),
+      address: call["to"].as_str()?.to_lowercase(),
+      data: call["data"].as_str()?.to_lowercase(),
+      block: params.get(1).and_then(|block| block.as_str()).unwrap_or("latest").to_string(),
+    })
+  }
+
+  // The genesis block is looked up once, on the first call that could be
+  // served from the cache.
+  fn chain(&self) -> Box<dyn Future<Item = String, Error = web3::Error>> {
+    if let Some(chain) = self.chain.lock().unwrap().as_ref() {
+      return Box::new(futures::finished(chain.clone()));
+    }
+    let known_chain = self.chain.clone();
+    Box::new(web3::Transport::execute(&*self.transport, "eth_getBlockByNumber", vec![serde_json::json!("0x0"), serde_json::json!(false)]).and_then(move |block| {
+      let chain = block["hash"].as_str().map(|hash| hash.to_lowercase()).ok_or_else(|| web3::Error::InvalidResponse("Couldn't find the genesis block".to_string()))?;
+      *known_chain.lock().unwrap() = Some(chain.clone());
+      Ok(chain)
+    }))
+  }
+}
+
+impl web3::Transport for CachingTransport {
+  type Out = Box<dyn Future<Item = rpc::Value, Error = web3::Error>>;
+
+  fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (web3::RequestId, rpc::Call) {
+    self.transport.prepare(method, params)
+  }
+
+  fn send(&self, id: web3::RequestId, request: rpc::Call) -> Self::Out {
+    let key = match &request {
+      rpc::Call::MethodCall(call) => match &call.params {
+        rpc::Params::Array(params) => self.call_key(&call.method, params),
+        _ => None,
+      },
+      _ => None,
+    };
+    let mut key = match key {
+      Some(key) => key,
+      None => return self.transport.send(id, request),
+    };
+
+    let caching = self.clone();
+    Box::new(self.chain().then(move |chain| -> Self::Out {
+      // Calls are still sent when the chain can't be told apart, they just
+      // aren't cached.
+      key.chain = match chain {
+        Ok(chain) => chain,
+        Err(err) => {
+          warn!("Not caching calls: {:?}", err);
+          return caching.transport.send(id, request);
+        },
+      };
+
+      if let Some(result) = caching.cache.lock().unwrap().get(&key, caching.ttl, now()) {
+        return Box::new(futures::finished(result.clone()));
+      }
+
+      let storing = caching.clone();
+      Box::new(caching.transport.send(id, request).map(move |result| {
+        let mut cache = storing.cache.lock().unwrap();
+        cache.insert(key, result.clone(), storing.ttl, now());
+        // Not being able to cache a result doesn't keep it from being used.
+        if let Err(err) = cache.save(&storing.path) {
+          warn!("{}", err);
+        }
+        result
+      }))
+    }))
+  }
+}
+
+fn now() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{CachingTransport, CallCache, CallKey, MAX_CACHED_CALLS};
+  use super::super::web3_adapter::{Transports, Web3Adapter};
+  use super::super::BlockchainConnector;
+  use crate::testing::MockTransport;
+  use web3::types::{Address, BlockNumber, Bytes, CallRequest};
+
+  const GENESIS_HASH: &str = "0x00000000000000000000000000000000000000000000000000000000000000aa";
+
+  fn genesis_block(hash: &str) -> serde_json::Value {
+    serde_json::json!({ "hash": hash })
+  }
+
+  fn call(data: u8) -> CallRequest {
+    CallRequest {
+      from: None,
+      to: Address::from_low_u64_be(1),
+      gas: None,
+      gas_price: None,
+      value: None,
+      data: Some(Bytes(vec![data])),
+    }
+  }
+
+  #[test]
+  fn it_should_serve_repeated_calls_from_the_cache() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = tmp_dir.path().join("call-cache.json");
+    let mock = MockTransport::new();
+    mock.set_default_response("eth_getBlockByNumber", genesis_block(GENESIS_HASH));
+    mock.add_response("eth_call", serde_json::json!("0x01"));
+    mock.add_response("eth_call", serde_json::json!("0x02"));
+    mock.add_response("eth_call", serde_json::json!("0x03"));
+
+    let transport = CachingTransport::new(Transports::Mock(mock.clone()), path.clone(), "sepolia".to_string(), 60);
+    let connector = BlockchainConnector::new(Web3Adapter::with_transport(Transports::Caching(transport)));
+    assert_eq!(connector.call(call(1), None).unwrap(), Bytes(vec![1]));
+    assert_eq!(connector.call(call(1), None).unwrap(), Bytes(vec![1]));
+    assert_eq!(connector.call(call(2), None).unwrap(), Bytes(vec![2]));
+    assert_eq!(connector.call(call(1), Some(BlockNumber::Number(5))).unwrap(), Bytes(vec![3]));
+    assert_eq!(mock.requests_for("eth_call").len(), 3);
+
+    let cache = CallCache::load(&path);
+    assert_eq!(cache.calls.len(), 3);
+    assert_eq!(cache.calls[2].block, "0x5");
+    assert_eq!(cache.calls[2].chain, GENESIS_HASH);
+    assert_eq!(mock.requests_for("eth_getBlockByNumber").len(), 1);
+
+    let other_network = CachingTransport::new(Transports::Mock(mock.clone()), path, "mainnet".to_string(), 60);
+    let connector = BlockchainConnector::new(Web3Adapter::with_transport(Transports::Caching(other_network)));
+    assert!(connector.call(call(1), None).is_err());
+  }
+
+  #[test]
+  fn it_should_not_serve_calls_cached_before_a_reset() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = tmp_dir.path().join("call-cache.json");
+    let mock = MockTransport::new();
+    mock.set_default_response("eth_getBlockByNumber", genesis_block(GENESIS_HASH));
+    mock.add_response("eth_call", serde_json::json!("0x01"));
+    mock.add_response("eth_call", serde_json::json!("0x02"));
+
+    let transport = CachingTransport::new(Transports::Mock(mock.clone()), path.clone(), "development".to_string(), 60);
+    let connector = BlockchainConnector::new(Web3Adapter::with_transport(Transports::Caching(transport)));
+    assert_eq!(connector.call(call(1), Some(BlockNumber::Number(5))).unwrap(), Bytes(vec![1]));
+
+    CallCache::clear(&path).unwrap();
+    assert!(!path.exists());
+    CallCache::clear(&path).unwrap();
+
+    let transport = CachingTransport::new(Transports::Mock(mock.clone()), path, "development".to_string(), 60);
+    let connector = BlockchainConnector::new(Web3Adapter::with_transport(Transports::Caching(transport)));
+    assert_eq!(connector.call(call(1), Some(BlockNumber::Number(5))).unwrap(), Bytes(vec![2]));
+    assert_eq!(mock.requests_for("eth_call").len(), 2);
+  }
+
+  #[test]
+  fn it_should_expire_calls_against_the_latest_block() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let mock = MockTransport::new();
+    mock.set_default_response("eth_getBlockByNumber", genesis_block(GENESIS_HASH));
+    mock.set_default_response("eth_call", serde_json::json!("0x01"));
+
+    let transport = CachingTransport::new(Transports::Mock(mock.clone()), tmp_dir.path().join("call-cache.json"), "sepolia".to_string(), 0);
+    let connector = BlockchainConnector::new(Web3Adapter::with_transport(Transports::Caching(transport)));
+    connector.call(call(1), None).unwrap();
+    connector.call(call(1), None).unwrap();
+    connector.call(call(1), Some(BlockNumber::Number(5))).unwrap();
+    connector.call(call(1), Some(BlockNumber::Number(5))).unwrap();
+    assert_eq!(mock.requests_for("eth_call").len(), 3);
+  }
+
+  #[test]
+  fn it_should_not_serve_calls_cached_on_a_previous_chain() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = tmp_dir.path().join("call-cache.json");
+    let mock = MockTransport::new();
+    mock.add_response("eth_getBlockByNumber", genesis_block(GENESIS_HASH));
+    mock.add_response("eth_getBlockByNumber", genesis_block("0x00000000000000000000000000000000000000000000000000000000000000bb"));
+    mock.add_response("eth_call", serde_json::json!("0x01"));
+    mock.add_response("eth_call", serde_json::json!("0x02"));
+
+    let transport = CachingTransport::new(Transports::Mock(mock.clone()), path.clone(), "development".to_string(), 60);
+    let connector = BlockchainConnector::new(Web3Adapter::with_transport(Transports::Caching(transport)));
+    assert_eq!(connector.call(call(1), Some(BlockNumber::Number(5))).unwrap(), Bytes(vec![1]));
+
+    let restarted = CachingTransport::new(Transports::Mock(mock.clone()), path.clone(), "development".to_string(), 60);
+    let connector = BlockchainConnector::new(Web3Adapter::with_transport(Transports::Caching(restarted)));
+    assert_eq!(connector.call(call(1), Some(BlockNumber::Number(5))).unwrap(), Bytes(vec![2]));
+    assert_eq!(CallCache::load(&path).calls.len(), 1);
+  }
+
+  #[test]
+  fn it_should_drop_the_oldest_calls_once_the_cache_is_full() {
+    let mut cache = CallCache::default();
+    for index in 0..MAX_CACHED_CALLS + 1 {
+      let key = CallKey {
+        network: "sepolia".to_string(),
+        chain: GENESIS_HASH.to_string(),
+        address: "0x01".to_string(),
+        data: format!("0x{:x}", index),
+        block: "0x5".to_string(),
+      };
+      cache.insert(key, serde_json::json!("0x01"), 60, 0);
+    }
+
+    assert_eq!(cache.calls.len(), MAX_CACHED_CALLS);
+    assert_eq!(cache.calls[0].data, "0x1");
+  }
+}
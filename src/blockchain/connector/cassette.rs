@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use super::super::error::ConnectionError;
+use super::web3_adapter::Transports;
+use web3::futures::{self, Future};
+use jsonrpc_core as rpc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcCassette {
+  Record(PathBuf),
+  Replay(PathBuf),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Interaction {
+  pub method: String,
+  pub params: Vec<rpc::Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub result: Option<rpc::Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<rpc::Error>,
+}
+
+type InteractionKey = (String, String);
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Cassette {
+  pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+  pub fn load(path: &PathBuf) -> Result<Cassette, ConnectionError> {
+    let content = fs::read_to_string(path).map_err(|err| ConnectionError::InvalidCassette(path.clone(), err.to_string()))?;
+    serde_json::from_str(&content).map_err(|err| ConnectionError::InvalidCassette(path.clone(), err.to_string()))
+  }
+
+  pub fn save(&self, path: &PathBuf) -> Result<(), ConnectionError> {
+    let content = serde_json::to_string_pretty(self).map_err(|err| ConnectionError::Other(err.to_string()))?;
+    fs::write(path, content).map_err(|err| ConnectionError::InvalidCassette(path.clone(), err.to_string()))
+  }
+}
+
+// Passes every request on to the wrapped transport and writes the request along
+// with its response to the cassette file. Only responses of the node are
+// recorded, transport failures are not.
+#[derive(Debug, Clone)]
+pub struct RecordingTransport {
+  transport: Box<Transports>,
+  path: PathBuf,
+  cassette: Arc<Mutex<Cassette>>,
+}
+
+impl RecordingTransport {
+  pub fn new(transport: Transports, path: PathBuf) -> RecordingTransport {
+    RecordingTransport {
+      transport: Box::new(transport),
+      path,
+      cassette: Arc::new(Mutex::new(Cassette::default())),
+    }
+  }
+
+  fn record(&self, interaction: Interaction) -> Result<(), ConnectionError> {
+    let mut cassette = self.cassette.lock().unwrap();
+    cassette.interactions.push(interaction);
+    cassette.save(&self.path)
+  }
+}
+
+impl web3::Transport for RecordingTransport {
+  type Out = Box<dyn Future<Item = rpc::Value, Error = web3::Error>>;
+
+  fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (web3::RequestId, rpc::Call) {
+    self.transport.prepare(method, params)
+  }
+
+  fn send(&self, id: web3::RequestId, request: rpc::Call) -> Self::Out {
+    let (method, params) = method_and_params(&request);
+    let recorder = self.clone();
+
+    Box::new(self.transport.send(id, request).then(move |result| {
+      let interaction = match &result {
+        Ok(value) => Interaction { method, params, result: Some(value.clone()), error: None },
+        Err(web3::Error::Rpc(error)) => Interaction { method, params, result: None, error: Some(error.clone()) },
+        Err(_error) => return result,
+      };
+      recorder.record(interaction).map_err(|err| web3::Error::Transport(err.to_string()))?;
+      result
+    }))
+  }
+}
+
+// Serves responses from a cassette. Interactions are matched by method and
+// params, in the order they were recorded. Once all interactions for a request
+// are used up, the last one is served again, so that polling (e.g. for
+// receipts) doesn't run dry.
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+  interactions: Arc<Mutex<HashMap<InteractionKey, Vec<Interaction>>>>,
+  last_id: Arc<Mutex<usize>>,
+}
+
+impl ReplayTransport {
+  pub fn new(cassette: Cassette) -> ReplayTransport {
+    let mut interactions: HashMap<InteractionKey, Vec<Interaction>> = HashMap::new();
+    for interaction in cassette.interactions {
+      interactions
+        .entry(interaction_key(&interaction.method, &interaction.params))
+        .or_default()
+        .push(interaction);
+    }
+
+    ReplayTransport {
+      interactions: Arc::new(Mutex::new(interactions)),
+      last_id: Arc::new(Mutex::new(0)),
+    }
+  }
+
+  fn replay(&self, method: &str, params: &[rpc::Value]) -> Option<Interaction> {
+    let mut interactions = self.interactions.lock().unwrap();
+    let recorded = interactions.get_mut(&interaction_key(method, params))?;
+    if recorded.len() > 1 {
+      Some(recorded.remove(0))
+    } else {
+      recorded.first().cloned()
+    }
+  }
+}
+
+impl web3::Transport for ReplayTransport {
+  type Out = Box<dyn Future<Item = rpc::Value, Error = web3::Error>>;
+
+  fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (web3::RequestId, rpc::Call) {
+    let mut last_id = self.last_id.lock().unwrap();
+    *last_id += 1;
+    (*last_id, web3::helpers::build_request(*last_id, method, params))
+  }
+
+  fn send(&self, _id: web3::RequestId, request: rpc::Call) -> Self::Out {
+    let (method, params) = method_and_params(&request);
+
+    match self.replay(&method, &params) {
+      Some(Interaction { error: Some(error), .. }) => Box::new(futures::failed(web3::Error::Rpc(error))),
+      Some(Interaction { result, .. }) => Box::new(futures::finished(result.unwrap_or(rpc::Value::Null))),
+      None => Box::new(futures::failed(web3::Error::Transport(format!("No recorded response for JSON-RPC method {} with params {}", &method, rpc::Value::Array(params))))),
+    }
+  }
+}
+
+fn interaction_key(method: &str, params: &[rpc::Value]) -> InteractionKey {
+  (method.to_string(), rpc::Value::Array(params.to_vec()).to_string())
+}
+
+fn method_and_params(request: &rpc::Call) -> (String, Vec<rpc::Value>) {
+  let (method, params) = match request {
+    rpc::Call::MethodCall(call) => (call.method.clone(), call.params.clone()),
+    rpc::Call::Notification(notification) => (notification.method.clone(), notification.params.clone()),
+    rpc::Call::Invalid { .. } => (String::new(), rpc::Params::None),
+  };
+
+  let params = match params {
+    rpc::Params::Array(values) => values,
+    rpc::Params::Map(map) => vec![rpc::Value::Object(map)],
+    rpc::Params::None => vec![],
+  };
+  (method, params)
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{Cassette, RecordingTransport, ReplayTransport};
+  use super::super::web3_adapter::{Transports, Web3Adapter};
+  use super::super::BlockchainConnector;
+  use crate::testing::MockTransport;
+  use jsonrpc_core as rpc;
+  use web3::types::U256;
+
+  fn connector(transport: Transports) -> BlockchainConnector {
+    BlockchainConnector::new(Web3Adapter::with_transport(transport))
+  }
+
+  #[test]
+  fn it_should_replay_recorded_responses() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = tmp_dir.path().join("cassette.json");
+
+    let mock = MockTransport::new();
+    mock.add_response("eth_gasPrice", rpc::Value::String("0x1".to_string()));
+    mock.add_response("eth_gasPrice", rpc::Value::String("0x2".to_string()));
+    mock.add_error("eth_accounts", rpc::Error::internal_error());
+
+    let recording = connector(Transports::Recording(RecordingTransport::new(Transports::Mock(mock), path.clone())));
+    assert_eq!(recording.gas_price().unwrap(), U256::from(1));
+    assert_eq!(recording.gas_price().unwrap(), U256::from(2));
+    assert!(recording.accounts().is_err());
+
+    let cassette = Cassette::load(&path).unwrap();
+    assert_eq!(cassette.interactions.len(), 3);
+
+    let replay = connector(Transports::Replay(ReplayTransport::new(cassette)));
+    assert_eq!(replay.gas_price().unwrap(), U256::from(1));
+    assert_eq!(replay.gas_price().unwrap(), U256::from(2));
+    assert_eq!(replay.gas_price().unwrap(), U256::from(2));
+    assert!(replay.accounts().is_err());
+  }
+
+  #[test]
+  fn it_should_fail_on_requests_missing_in_cassette() {
+    let replay = connector(Transports::Replay(ReplayTransport::new(Cassette::default())));
+    assert!(replay.gas_price().is_err());
+  }
+
+  #[test]
+  fn it_should_not_record_transport_failures() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = tmp_dir.path().join("cassette.json");
+
+    let recording = connector(Transports::Recording(RecordingTransport::new(Transports::Mock(MockTransport::new()), path.clone())));
+    assert!(recording.gas_price().is_err());
+    assert!(!path.exists());
+  }
+}
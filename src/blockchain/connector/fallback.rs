@@ -0,0 +1,297 @@
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use super::super::error::ConnectionError;
+use super::web3_adapter::Transports;
+use web3::Transport;
+use web3::futures::{self, Future};
+use jsonrpc_core as rpc;
+
+const DEFAULT_UNHEALTHY_COOLDOWN: u64 = 30;
+// Public providers signal rate limiting either with HTTP 429 or with
+// JSON-RPC error -32005 ("limit exceeded").
+const RATE_LIMIT_ERROR_CODES: &[i64] = &[-32005, 429];
+// Methods that only read from the chain can be sent to another endpoint
+// whenever one fails.
+const READ_METHOD_PREFIXES: &[&str] = &["eth_", "net_", "web3_"];
+const WRITE_METHOD_PREFIXES: &[&str] = &["eth_send", "eth_submit"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RotationStrategy {
+  Priority,
+  RoundRobin,
+}
+
+impl FromStr for RotationStrategy {
+  type Err = ConnectionError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "priority" => Ok(RotationStrategy::Priority),
+      "round-robin" => Ok(RotationStrategy::RoundRobin),
+      _ => Err(ConnectionError::UnsupportedRotationStrategy(s.to_string())),
+    }
+  }
+}
+
+// Sends every request to one of multiple endpoints. Endpoints that fail or
+// rate-limit a request are marked unhealthy and only tried again as a last
+// resort until their cooldown has passed. Transactions and other writes are
+// only sent to the next endpoint if they've never reached the failing one, as
+// sending them twice could e.g. deploy a Smart Contract twice.
+#[derive(Debug, Clone)]
+pub struct FallbackTransport {
+  endpoints: Arc<Vec<(String, Transports)>>,
+  strategy: RotationStrategy,
+  cooldown: Duration,
+  unhealthy_since: Arc<Mutex<Vec<Option<Instant>>>>,
+  next_endpoint: Arc<AtomicUsize>,
+  next_id: Arc<AtomicUsize>,
+}
+
+impl FallbackTransport {
+  pub fn new(endpoints: Vec<(String, Transports)>, strategy: RotationStrategy) -> FallbackTransport {
+    let amount = endpoints.len();
+    FallbackTransport {
+      endpoints: Arc::new(endpoints),
+      strategy,
+      cooldown: Duration::from_secs(DEFAULT_UNHEALTHY_COOLDOWN),
+      unhealthy_since: Arc::new(Mutex::new(vec![None; amount])),
+      next_endpoint: Arc::new(AtomicUsize::new(0)),
+      next_id: Arc::new(AtomicUsize::new(0)),
+    }
+  }
+
+  pub fn with_cooldown(mut self, cooldown: Duration) -> FallbackTransport {
+    self.cooldown = cooldown;
+    self
+  }
+
+  pub fn healthy_endpoints(&self) -> Vec<String> {
+    (0..self.endpoints.len())
+      .filter(|index| self.is_healthy(*index))
+      .map(|index| self.endpoints[index].0.to_owned())
+      .collect()
+  }
+
+  fn is_healthy(&self, index: usize) -> bool {
+    match self.unhealthy_since.lock().unwrap()[index] {
+      Some(since) => since.elapsed() >= self.cooldown,
+      None => true,
+    }
+  }
+
+  fn set_healthy(&self, index: usize, healthy: bool) {
+    self.unhealthy_since.lock().unwrap()[index] = if healthy { None } else { Some(Instant::now()) };
+  }
+
+  fn endpoint_order(&self) -> Vec<usize> {
+    let amount = self.endpoints.len();
+    let start = match self.strategy {
+      RotationStrategy::Priority => 0,
+      RotationStrategy::RoundRobin => self.next_endpoint.fetch_add(1, Ordering::SeqCst) % amount,
+    };
+
+    let (healthy, unhealthy): (Vec<usize>, Vec<usize>) = (0..amount)
+      .map(|offset| (start + offset) % amount)
+      .partition(|index| self.is_healthy(*index));
+
+    healthy.into_iter().chain(unhealthy).collect()
+  }
+}
+
+impl web3::Transport for FallbackTransport {
+  type Out = Box<dyn Future<Item = rpc::Value, Error = web3::Error>>;
+
+  fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (web3::RequestId, rpc::Call) {
+    let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+    (id, web3::helpers::build_request(id, method, params))
+  }
+
+  fn send(&self, id: web3::RequestId, request: rpc::Call) -> Self::Out {
+    let is_read = match &request {
+      rpc::Call::MethodCall(call) => is_read_method(&call.method),
+      _ => false,
+    };
+    send_to_endpoints(self.clone(), self.endpoint_order(), id, request, is_read)
+  }
+}
+
+fn send_to_endpoints(transport: FallbackTransport, mut order: Vec<usize>, id: web3::RequestId, request: rpc::Call, is_read: bool) -> Box<dyn Future<Item = rpc::Value, Error = web3::Error>> {
+  let index = order.remove(0);
+  let response = transport.endpoints[index].1.send(id, request.clone());
+
+  Box::new(response.then(move |result| -> Box<dyn Future<Item = rpc::Value, Error = web3::Error>> {
+    match result {
+      Ok(value) => {
+        transport.set_healthy(index, true);
+        Box::new(futures::finished(value))
+      },
+      Err(error) => {
+        if !is_endpoint_failure(&error) {
+          return Box::new(futures::failed(error));
+        }
+
+        transport.set_healthy(index, false);
+        if order.is_empty() {
+          Box::new(futures::failed(error))
+        } else if !is_read && !is_unprocessed(&error) {
+          warn!("RPC endpoint {} failed: {}. Not trying the next endpoint, the request may have been processed already", &transport.endpoints[index].0, error);
+          Box::new(futures::failed(error))
+        } else {
+          warn!("RPC endpoint {} failed: {}. Trying next endpoint...", &transport.endpoints[index].0, error);
+          send_to_endpoints(transport, order, id, request, is_read)
+        }
+      }
+    }
+  }))
+}
+
+// Only failures of the endpoint itself lead to a failover. Errors returned by
+// the node for the request (e.g. a reverted call) would be the same on every endpoint.
+fn is_endpoint_failure(error: &web3::Error) -> bool {
+  match error {
+    web3::Error::Rpc(error) => RATE_LIMIT_ERROR_CODES.contains(&error.code.code()),
+    web3::Error::Unreachable => true,
+    web3::Error::Transport(_) => true,
+    web3::Error::Io(_) => true,
+    web3::Error::InvalidResponse(_) => true,
+    _ => false,
+  }
+}
+
+fn is_read_method(method: &str) -> bool {
+  READ_METHOD_PREFIXES.iter().any(|prefix| method.starts_with(prefix))
+    && !WRITE_METHOD_PREFIXES.iter().any(|prefix| method.starts_with(prefix))
+}
+
+// Whether the endpoint provably didn't process the request, because it
+// couldn't be connected to or rejected the request due to rate limiting.
+// Requests failing later on, e.g. because of a timeout or a reset
+// connection, may have been processed.
+fn is_unprocessed(error: &web3::Error) -> bool {
+  match error {
+    web3::Error::Rpc(error) => RATE_LIMIT_ERROR_CODES.contains(&error.code.code()),
+    web3::Error::Io(error) => error.kind() == std::io::ErrorKind::ConnectionRefused,
+    web3::Error::Transport(message) => message.contains("ConnectionRefused") || message.contains("Connection refused"),
+    _ => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{FallbackTransport, RotationStrategy, is_unprocessed};
+  use super::super::web3_adapter::Transports;
+  use crate::testing::MockTransport;
+  use jsonrpc_core as rpc;
+  use std::time::Duration;
+  use web3::futures::Future;
+  use web3::Transport;
+
+  fn mock_endpoint(name: &str, gas_price: Option<&str>) -> (MockTransport, (String, Transports)) {
+    let mock = MockTransport::new();
+    if let Some(gas_price) = gas_price {
+      mock.set_default_response("eth_gasPrice", rpc::Value::String(gas_price.to_string()));
+    }
+    (mock.clone(), (name.to_string(), Transports::Mock(mock)))
+  }
+
+  #[test]
+  fn it_should_fail_over_to_next_endpoint() {
+    let (_first, first_endpoint) = mock_endpoint("first", None);
+    let (second, second_endpoint) = mock_endpoint("second", Some("0x2"));
+    let transport = FallbackTransport::new(vec![first_endpoint, second_endpoint], RotationStrategy::Priority);
+
+    assert_eq!(transport.execute("eth_gasPrice", vec![]).wait().unwrap(), rpc::Value::String("0x2".to_string()));
+    assert_eq!(transport.healthy_endpoints(), vec!["second".to_string()]);
+    assert_eq!(second.requests_for("eth_gasPrice").len(), 1);
+  }
+
+  #[test]
+  fn it_should_not_fail_over_on_node_errors() {
+    let (first, first_endpoint) = mock_endpoint("first", None);
+    let (second, second_endpoint) = mock_endpoint("second", Some("0x2"));
+    first.add_error("eth_gasPrice", rpc::Error::invalid_params("reverted"));
+    let transport = FallbackTransport::new(vec![first_endpoint, second_endpoint], RotationStrategy::Priority);
+
+    assert!(transport.execute("eth_gasPrice", vec![]).wait().is_err());
+    assert!(second.requests_for("eth_gasPrice").is_empty());
+  }
+
+  #[test]
+  fn it_should_fail_over_when_rate_limited() {
+    let (first, first_endpoint) = mock_endpoint("first", None);
+    let (_second, second_endpoint) = mock_endpoint("second", Some("0x2"));
+    first.add_error("eth_gasPrice", rpc::Error {
+      code: rpc::ErrorCode::ServerError(-32005),
+      message: "limit exceeded".to_string(),
+      data: None,
+    });
+    let transport = FallbackTransport::new(vec![first_endpoint, second_endpoint], RotationStrategy::Priority);
+
+    assert!(transport.execute("eth_gasPrice", vec![]).wait().is_ok());
+  }
+
+  #[test]
+  fn it_should_not_resend_transactions_after_failures_during_the_request() {
+    let (first, first_endpoint) = mock_endpoint("first", None);
+    let (second, second_endpoint) = mock_endpoint("second", None);
+    second.set_default_response("eth_sendRawTransaction", rpc::Value::String(format!("0x{:064x}", 1)));
+    let transport = FallbackTransport::new(vec![first_endpoint, second_endpoint], RotationStrategy::Priority);
+
+    assert!(transport.execute("eth_sendRawTransaction", vec![rpc::Value::String("0xaa".to_string())]).wait().is_err());
+    assert_eq!(first.requests_for("eth_sendRawTransaction").len(), 1);
+    assert!(second.requests_for("eth_sendRawTransaction").is_empty());
+  }
+
+  #[test]
+  fn it_should_resend_transactions_that_have_been_rate_limited() {
+    let (first, first_endpoint) = mock_endpoint("first", None);
+    let (second, second_endpoint) = mock_endpoint("second", None);
+    first.add_error("eth_sendRawTransaction", rpc::Error {
+      code: rpc::ErrorCode::ServerError(-32005),
+      message: "limit exceeded".to_string(),
+      data: None,
+    });
+    second.set_default_response("eth_sendRawTransaction", rpc::Value::String(format!("0x{:064x}", 1)));
+    let transport = FallbackTransport::new(vec![first_endpoint, second_endpoint], RotationStrategy::Priority);
+
+    assert!(transport.execute("eth_sendRawTransaction", vec![rpc::Value::String("0xaa".to_string())]).wait().is_ok());
+    assert_eq!(second.requests_for("eth_sendRawTransaction").len(), 1);
+  }
+
+  #[test]
+  fn it_should_only_consider_refused_connections_unprocessed() {
+    assert!(is_unprocessed(&web3::Error::Io(std::io::ErrorKind::ConnectionRefused.into())));
+    assert!(is_unprocessed(&web3::Error::Transport("Error(Connect, Os { code: 111, kind: ConnectionRefused, message: \"Connection refused\" })".to_string())));
+    assert!(!is_unprocessed(&web3::Error::Io(std::io::ErrorKind::ConnectionReset.into())));
+    assert!(!is_unprocessed(&web3::Error::Transport("operation timed out".to_string())));
+    assert!(!is_unprocessed(&web3::Error::Unreachable));
+  }
+
+  #[test]
+  fn it_should_rotate_endpoints_round_robin() {
+    let (first, first_endpoint) = mock_endpoint("first", Some("0x1"));
+    let (second, second_endpoint) = mock_endpoint("second", Some("0x2"));
+    let transport = FallbackTransport::new(vec![first_endpoint, second_endpoint], RotationStrategy::RoundRobin);
+
+    for _ in 0..4 {
+      transport.execute("eth_gasPrice", vec![]).wait().unwrap();
+    }
+    assert_eq!(first.requests_for("eth_gasPrice").len(), 2);
+    assert_eq!(second.requests_for("eth_gasPrice").len(), 2);
+  }
+
+  #[test]
+  fn it_should_retry_unhealthy_endpoints_after_cooldown() {
+    let (first, first_endpoint) = mock_endpoint("first", None);
+    let (_second, second_endpoint) = mock_endpoint("second", Some("0x2"));
+    let transport = FallbackTransport::new(vec![first_endpoint, second_endpoint], RotationStrategy::Priority)
+      .with_cooldown(Duration::from_secs(0));
+
+    transport.execute("eth_gasPrice", vec![]).wait().unwrap();
+    first.set_default_response("eth_gasPrice", rpc::Value::String("0x1".to_string()));
+    assert_eq!(transport.execute("eth_gasPrice", vec![]).wait().unwrap(), rpc::Value::String("0x1".to_string()));
+  }
+}
@@ -1,14 +1,22 @@
+pub mod cache;
+pub mod cassette;
+pub mod fallback;
+pub mod polling;
 pub mod web3_adapter;
 
 use std::str::FromStr;
 use std::string::ToString;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 use super::error::ConnectionError;
+use super::super::utils;
+use polling::{ReceiptPolling, ReceiptPollingConfig};
 use web3_adapter::Web3Adapter;
-use web3::futures::Future;
-use web3::types::{Address, Block, BlockId, BlockNumber, H256, U256};
+use web3::futures::{Future, Stream};
+use web3::types::{Address, Block, BlockId, BlockNumber, Bytes, CallRequest, Filter, H256, H520, Log, Transaction, TransactionReceipt, TransactionRequest, U256};
 use jsonrpc_core as rpc;
 
-
 pub type CallFuture = web3::helpers::CallFuture<Vec<Address>, Box<dyn Future<Item = rpc::Value, Error = web3::Error>>>;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -16,6 +24,15 @@ pub struct BlockchainConnectorConfig {
   pub protocol: String,
   pub host: String,
   pub port: String,
+  // Full URL of the endpoint, e.g. of a hosted provider including its path.
+  // Takes precedence over protocol, host and port, which are derived from it.
+  pub url: Option<String>,
+  pub fallbacks: Option<Vec<String>>,
+  pub rotation: Option<String>,
+  pub receipts: Option<ReceiptPollingConfig>,
+  // Seconds results of `eth_call` requests against the latest block are
+  // cached for, 0 disables the cache.
+  pub call_cache_ttl: Option<u64>,
 }
 
 impl Default for BlockchainConnectorConfig {
@@ -24,10 +41,29 @@ impl Default for BlockchainConnectorConfig {
       protocol: SupportedProtocols::Rpc.to_string(),
       host: "localhost".to_string(),
       port: "8545".to_string(),
+      url: None,
+      fallbacks: None,
+      rotation: None,
+      receipts: None,
+      call_cache_ttl: None,
     }
   }
 }
 
+impl BlockchainConnectorConfig {
+  // The URL the connector connects to.
+  pub fn endpoint_url(&self) -> Result<String, ConnectionError> {
+    if let Some(url) = &self.url {
+      return Ok(url.to_owned());
+    }
+    let scheme = match self.protocol.parse()? {
+      SupportedProtocols::Rpc => "http",
+      SupportedProtocols::Ws => "ws",
+    };
+    Ok(format!("{}://{}:{}", scheme, utils::normalize_localhost(self.host.to_owned()), self.port))
+  }
+}
+
 pub enum SupportedProtocols {
   Rpc,
   Ws,
@@ -55,15 +91,26 @@ impl ToString for SupportedProtocols {
 
 pub struct BlockchainConnector {
   adapter: Web3Adapter,
+  receipt_polling: ReceiptPolling,
 }
 
 impl BlockchainConnector {
   pub fn new(adapter: Web3Adapter) -> BlockchainConnector {
     BlockchainConnector {
       adapter,
+      receipt_polling: ReceiptPolling::default(),
     }
   }
 
+  pub fn with_receipt_polling(mut self, receipt_polling: ReceiptPolling) -> BlockchainConnector {
+    self.receipt_polling = receipt_polling;
+    self
+  }
+
+  pub fn receipt_polling(&self) -> &ReceiptPolling {
+    &self.receipt_polling
+  }
+
   pub fn accounts(&self) -> Result<Vec<Address>, ConnectionError> {
     self.adapter.accounts().wait().map_err(ConnectionError::Transport)
   }
@@ -80,11 +127,155 @@ impl BlockchainConnector {
     self.adapter.get_block(block).wait().map_err(ConnectionError::Transport)
   }
 
+  // Tags like `safe` and `finalized` aren't known to web3's `BlockNumber`.
+  pub fn get_block_by_tag(&self, tag: &str) -> Result<Option<Block<H256>>, ConnectionError> {
+    let response = self.adapter.execute("eth_getBlockByNumber", vec![rpc::Value::String(tag.to_string()), rpc::Value::Bool(false)]).wait().map_err(ConnectionError::Transport)?;
+    serde_json::from_value(response).map_err(|err| ConnectionError::Other(err.to_string()))
+  }
+
   pub fn get_first_block(&self) -> Result<Option<Block<H256>>, ConnectionError> {
     self.get_block(BlockId::Number(BlockNumber::Number(0)))
   }
 
+  pub fn chain_id(&self) -> Result<U256, ConnectionError> {
+    let response = self.adapter.execute("eth_chainId", vec![]).wait().map_err(ConnectionError::Transport)?;
+    serde_json::from_value(response).map_err(|err| ConnectionError::Other(err.to_string()))
+  }
+
   pub fn deploy(&self, bytes: &[u8]) -> Result<web3::contract::deploy::Builder<web3_adapter::Transports>, ethabi::Error> {
     self.adapter.deploy(bytes)
   }
+
+  pub fn block_number(&self) -> Result<U256, ConnectionError> {
+    self.adapter.block_number().wait().map_err(ConnectionError::Transport)
+  }
+
+  pub fn transaction_count(&self, address: Address, block_number: Option<BlockNumber>) -> Result<U256, ConnectionError> {
+    self.adapter.transaction_count(address, block_number).wait().map_err(ConnectionError::Transport)
+  }
+
+  pub fn code(&self, address: Address, block_number: Option<BlockNumber>) -> Result<Bytes, ConnectionError> {
+    self.adapter.code(address, block_number).wait().map_err(ConnectionError::Transport)
+  }
+
+  pub fn call(&self, req: CallRequest, block_number: Option<BlockNumber>) -> Result<Bytes, ConnectionError> {
+    self.adapter.call(req, block_number).wait().map_err(ConnectionError::Transport)
+  }
+
+  pub fn transaction(&self, hash: H256) -> Result<Option<Transaction>, ConnectionError> {
+    self.adapter.transaction(hash).wait().map_err(ConnectionError::Transport)
+  }
+
+  pub fn transaction_receipt(&self, hash: H256) -> Result<Option<TransactionReceipt>, ConnectionError> {
+    self.adapter.transaction_receipt(hash).wait().map_err(ConnectionError::Transport)
+  }
+
+  pub fn logs(&self, filter: Filter) -> Result<Vec<Log>, ConnectionError> {
+    self.adapter.logs(filter).wait().map_err(ConnectionError::Transport)
+  }
+
+  // Takes a transaction instead of a call, as only those can leave out the
+  // recipient to estimate Smart Contract creations.
+  pub fn estimate_gas(&self, tx: TransactionRequest) -> Result<U256, ConnectionError> {
+    let response = self.adapter.execute("eth_estimateGas", vec![web3::helpers::serialize(&tx)]).wait().map_err(ConnectionError::Transport)?;
+    serde_json::from_value(response).map_err(|err| ConnectionError::Other(err.to_string()))
+  }
+
+  // Only supported by development nodes, e.g. ganache and anvil.
+  pub fn snapshot(&self) -> Result<U256, ConnectionError> {
+    let response = self.adapter.execute("evm_snapshot", vec![]).wait().map_err(ConnectionError::Transport)?;
+    serde_json::from_value(response).map_err(|err| ConnectionError::Other(err.to_string()))
+  }
+
+  // Whether the node knew the snapshot.
+  pub fn revert(&self, snapshot: U256) -> Result<bool, ConnectionError> {
+    let response = self.adapter.execute("evm_revert", vec![web3::helpers::serialize(&snapshot)]).wait().map_err(ConnectionError::Transport)?;
+    serde_json::from_value(response).map_err(|err| ConnectionError::Other(err.to_string()))
+  }
+
+  pub fn send_transaction(&self, tx: TransactionRequest) -> Result<H256, ConnectionError> {
+    self.adapter.send_transaction(tx).wait().map_err(ConnectionError::Transport)
+  }
+
+  pub fn send_raw_transaction(&self, tx: Bytes) -> Result<H256, ConnectionError> {
+    self.adapter.send_raw_transaction(tx).wait().map_err(ConnectionError::Transport)
+  }
+
+  pub fn sign(&self, address: Address, data: Bytes) -> Result<H520, ConnectionError> {
+    self.adapter.sign(address, data).wait().map_err(ConnectionError::Transport)
+  }
+
+  // Asks the node to sign a transaction with one of its unlocked accounts
+  // without sending it. Nodes respond with both, the raw and decoded transaction.
+  pub fn sign_transaction(&self, tx: TransactionRequest) -> Result<Bytes, ConnectionError> {
+    let response = self.adapter.execute("eth_signTransaction", vec![web3::helpers::serialize(&tx)]).wait().map_err(ConnectionError::Transport)?;
+    match response.get("raw") {
+      Some(raw) => serde_json::from_value(raw.clone()).map_err(|err| ConnectionError::Other(err.to_string())),
+      None => serde_json::from_value(response).map_err(|err| ConnectionError::Other(err.to_string())),
+    }
+  }
+
+  // On WebSocket transports, new blocks are subscribed to so that receipts
+  // are checked as soon as a block is mined. Polling continues as a fallback,
+  // in case the node doesn't support subscriptions or drops them.
+  pub fn wait_for_transaction_receipt(&self, hash: H256, confirmations: usize) -> Result<TransactionReceipt, ConnectionError> {
+    let new_heads = self.subscribe_new_heads();
+    let started = Instant::now();
+    let mut interval = self.receipt_polling.interval;
+
+    let receipt = loop {
+      match self.transaction_receipt(hash)? {
+        Some(receipt) => break receipt,
+        None => self.wait_for_next_block(&new_heads, &mut interval, started, hash)?,
+      }
+    };
+
+    if confirmations > 0 {
+      if let Some(block_number) = receipt.block_number {
+        while self.block_number()? < block_number + U256::from(confirmations) {
+          self.wait_for_next_block(&new_heads, &mut interval, started, hash)?;
+        }
+      }
+    }
+
+    Ok(receipt)
+  }
+
+  fn wait_for_next_block(&self, new_heads: &Option<mpsc::Receiver<()>>, interval: &mut Duration, started: Instant, hash: H256) -> Result<(), ConnectionError> {
+    if let Some(max_wait) = self.receipt_polling.max_wait {
+      if started.elapsed() >= max_wait {
+        return Err(ConnectionError::ReceiptTimeout(hash, max_wait));
+      }
+    }
+
+    match new_heads {
+      Some(new_heads) => {
+        if let Err(mpsc::RecvTimeoutError::Disconnected) = new_heads.recv_timeout(*interval) {
+          thread::sleep(*interval);
+        }
+      },
+      None => thread::sleep(*interval),
+    }
+    *interval = self.receipt_polling.next_interval(*interval);
+    Ok(())
+  }
+
+  // Receives a message per new block, on transports that support
+  // subscriptions.
+  pub fn subscribe_new_heads(&self) -> Option<mpsc::Receiver<()>> {
+    let subscription = self.adapter.subscribe_new_heads()?
+      .wait()
+      .map_err(|err| warn!("Couldn't subscribe to new blocks, polling for receipts instead: {}", err))
+      .ok()?;
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+      for head in subscription.wait() {
+        if head.is_err() || sender.send(()).is_err() {
+          break;
+        }
+      }
+    });
+    Some(receiver)
+  }
 }
@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+const DEFAULT_RECEIPT_POLL_INTERVAL: u64 = 1000;
+const DEFAULT_MAX_RECEIPT_POLL_INTERVAL: u64 = 30_000;
+
+// All durations are in milliseconds. Local dev chains mine instantly and can
+// be polled every few milliseconds, whereas public RPCs of slow chains are
+// better polled with a backoff.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ReceiptPollingConfig {
+  pub interval: Option<u64>,
+  pub max_interval: Option<u64>,
+  pub max_wait: Option<u64>,
+  pub backoff: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiptPolling {
+  pub interval: Duration,
+  pub max_interval: Duration,
+  pub max_wait: Option<Duration>,
+  pub backoff: f64,
+}
+
+impl Default for ReceiptPolling {
+  fn default() -> Self {
+    ReceiptPolling {
+      interval: Duration::from_millis(DEFAULT_RECEIPT_POLL_INTERVAL),
+      max_interval: Duration::from_millis(DEFAULT_MAX_RECEIPT_POLL_INTERVAL),
+      max_wait: None,
+      backoff: 1.0,
+    }
+  }
+}
+
+impl ReceiptPolling {
+  pub fn from_config(config: &Option<ReceiptPollingConfig>) -> ReceiptPolling {
+    let defaults = ReceiptPolling::default();
+    let config = match config {
+      Some(config) => config,
+      None => return defaults,
+    };
+    let interval = config.interval.map(Duration::from_millis).unwrap_or(defaults.interval);
+    ReceiptPolling {
+      interval,
+      max_interval: config.max_interval.map(Duration::from_millis).unwrap_or(defaults.max_interval).max(interval),
+      max_wait: config.max_wait.map(Duration::from_millis),
+      backoff: config.backoff.filter(|backoff| *backoff >= 1.0).unwrap_or(defaults.backoff),
+    }
+  }
+
+  pub fn next_interval(&self, interval: Duration) -> Duration {
+    interval.mul_f64(self.backoff).min(self.max_interval)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{ReceiptPolling, ReceiptPollingConfig};
+  use crate::blockchain::error::ConnectionError;
+  use crate::testing::MockTransport;
+  use std::time::Duration;
+  use web3::types::H256;
+
+  #[test]
+  fn it_should_back_off_up_to_max_interval() {
+    let polling = ReceiptPolling::from_config(&Some(ReceiptPollingConfig {
+      interval: Some(100),
+      max_interval: Some(300),
+      backoff: Some(2.0),
+      ..ReceiptPollingConfig::default()
+    }));
+
+    let intervals: Vec<Duration> = (0..3).scan(polling.interval, |interval, _| {
+      *interval = polling.next_interval(*interval);
+      Some(*interval)
+    }).collect();
+    assert_eq!(intervals, vec![Duration::from_millis(200), Duration::from_millis(300), Duration::from_millis(300)]);
+    assert_eq!(ReceiptPolling::from_config(&None), ReceiptPolling::default());
+  }
+
+  #[test]
+  fn it_should_give_up_waiting_after_max_wait() {
+    let mock = MockTransport::new();
+    mock.set_default_response("eth_getTransactionReceipt", serde_json::Value::Null);
+    let connector = mock.connector().with_receipt_polling(ReceiptPolling::from_config(&Some(ReceiptPollingConfig {
+      interval: Some(5),
+      max_wait: Some(20),
+      ..ReceiptPollingConfig::default()
+    })));
+
+    match connector.wait_for_transaction_receipt(H256::zero(), 0) {
+      Err(ConnectionError::ReceiptTimeout(hash, max_wait)) => assert_eq!((hash, max_wait), (H256::zero(), Duration::from_millis(20))),
+      other => panic!("Expected receipt timeout, got {:?}", other.map(|receipt| receipt.transaction_hash)),
+    }
+    assert!(mock.requests_for("eth_getTransactionReceipt").len() > 1);
+  }
+}
@@ -1,17 +1,29 @@
-use super::{SupportedProtocols, BlockchainConnectorConfig};
+use super::BlockchainConnectorConfig;
+use super::cache::CachingTransport;
+use super::cassette::{RecordingTransport, ReplayTransport};
+use super::fallback::{FallbackTransport, RotationStrategy};
 use super::super::error::ConnectionError;
-use super::super::super::utils;
+use crate::testing::MockTransport;
+use web3::Transport;
 use web3::futures::Future;
 use web3::helpers::CallFuture;
 use web3::contract::Contract;
-use web3::types::{Address, Block, BlockId, BlockNumber, H256, U256};
+use web3::api::SubscriptionStream;
+use web3::types::{Address, Block, BlockHeader, BlockId, BlockNumber, Bytes, CallRequest, Filter, H256, H520, Log, Transaction, TransactionId, TransactionReceipt, TransactionRequest, U256};
 use ethabi;
 use jsonrpc_core as rpc;
 
+const DEFAULT_MAX_PARALLEL_REQUESTS: usize = 64;
+
 #[derive(Debug, Clone)]
 pub enum Transports {
   Http(web3::transports::Http),
   Ws(web3::transports::WebSocket),
+  Mock(MockTransport),
+  Fallback(FallbackTransport),
+  Recording(RecordingTransport),
+  Replay(ReplayTransport),
+  Caching(CachingTransport),
 }
 
 impl web3::Transport for Transports {
@@ -19,14 +31,24 @@ impl web3::Transport for Transports {
   fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (web3::RequestId, rpc::Call) {
     match self {
       Transports::Http(transport) => transport.prepare(&method, params),
-      Transports::Ws(transport) => transport.prepare(&method, params)
+      Transports::Ws(transport) => transport.prepare(&method, params),
+      Transports::Mock(transport) => transport.prepare(method, params),
+      Transports::Fallback(transport) => transport.prepare(method, params),
+      Transports::Recording(transport) => transport.prepare(method, params),
+      Transports::Replay(transport) => transport.prepare(method, params),
+      Transports::Caching(transport) => transport.prepare(method, params),
     }
   }
 
   fn send(&self, id: web3::RequestId, request: rpc::Call) -> Self::Out {
     match self {
       Transports::Http(transport) => Box::new(transport.send(id, request)),
-      Transports::Ws(transport) => Box::new(transport.send(id, request))
+      Transports::Ws(transport) => Box::new(transport.send(id, request)),
+      Transports::Mock(transport) => transport.send(id, request),
+      Transports::Fallback(transport) => transport.send(id, request),
+      Transports::Recording(transport) => transport.send(id, request),
+      Transports::Replay(transport) => transport.send(id, request),
+      Transports::Caching(transport) => transport.send(id, request),
     }
   }
 }
@@ -37,21 +59,61 @@ pub struct Web3Adapter {
 
 impl Web3Adapter {
   pub fn new(config: BlockchainConnectorConfig) -> Result<(web3::transports::EventLoopHandle, Web3Adapter), ConnectionError> {
-    let (eloop, transport) = match config.protocol.parse() {
-      Ok(SupportedProtocols::Rpc) => {
-        let (eloop, transport) = web3::transports::Http::new(&format!("http://{}:{}", utils::normalize_localhost(config.host), config.port)).unwrap();
-        (eloop, Transports::Http(transport))
-      },
-      Ok(SupportedProtocols::Ws) => {
-        let (eloop, transport) = web3::transports::WebSocket::new(&format!("ws://{}:{}", utils::normalize_localhost(config.host), config.port)).unwrap();
-        (eloop, Transports::Ws(transport))
-      },
-      Err(err) => Err(err)?,
+    if config.fallbacks.as_ref().map(|fallbacks| !fallbacks.is_empty()).unwrap_or(false) {
+      return Web3Adapter::with_fallbacks(config);
+    }
+
+    let url = config.endpoint_url()?;
+    let (eloop, transport) = if is_ws_url(&url) {
+      let (eloop, transport) = web3::transports::WebSocket::new(&url).map_err(ConnectionError::Transport)?;
+      (eloop, Transports::Ws(transport))
+    } else {
+      let (eloop, transport) = web3::transports::Http::new(&url).map_err(ConnectionError::Transport)?;
+      (eloop, Transports::Http(transport))
     };
 
-    let web3 = web3::Web3::new(transport);
+    Ok((eloop, Web3Adapter::with_transport(transport)))
+  }
+
+  // The configured endpoint is the first one, followed by all
+  // fallback URLs. All endpoints share a single event loop.
+  fn with_fallbacks(config: BlockchainConnectorConfig) -> Result<(web3::transports::EventLoopHandle, Web3Adapter), ConnectionError> {
+    let strategy = config.rotation.as_ref().map(|rotation| rotation.parse()).unwrap_or(Ok(RotationStrategy::Priority))?;
+    let mut urls = vec![config.endpoint_url()?];
+    urls.extend(config.fallbacks.unwrap_or_default());
+
+    if let Some(url) = urls.iter().find(|url| !is_http_url(url) && !is_ws_url(url)) {
+      return Err(ConnectionError::Other(format!("Couldn't create blockchain connector. Unsupported fallback URL '{}'", url)));
+    }
+
+    let (eloop, endpoints) = web3::transports::EventLoopHandle::spawn(move |handle| {
+      urls.into_iter().map(|url| {
+        let transport = if is_http_url(&url) {
+          Transports::Http(web3::transports::Http::with_event_loop(&url, handle, DEFAULT_MAX_PARALLEL_REQUESTS)?)
+        } else {
+          Transports::Ws(web3::transports::WebSocket::with_event_loop(&url, handle)?)
+        };
+        Ok((url, transport))
+      }).collect::<Result<Vec<(String, Transports)>, web3::Error>>()
+    }).map_err(ConnectionError::Transport)?;
+
+    Ok((eloop, Web3Adapter::with_transport(Transports::Fallback(FallbackTransport::new(endpoints, strategy)))))
+  }
 
-    Ok((eloop, Web3Adapter { web3 }))
+  pub fn with_transport(transport: Transports) -> Web3Adapter {
+    Web3Adapter {
+      web3: web3::Web3::new(transport)
+    }
+  }
+
+  pub fn recording_to(self, path: std::path::PathBuf) -> Web3Adapter {
+    let transport = self.web3.transport().clone();
+    Web3Adapter::with_transport(Transports::Recording(RecordingTransport::new(transport, path)))
+  }
+
+  pub fn caching_calls(self, path: std::path::PathBuf, network: String, ttl: u64) -> Web3Adapter {
+    let transport = self.web3.transport().clone();
+    Web3Adapter::with_transport(Transports::Caching(CachingTransport::new(transport, path, network, ttl)))
   }
 
   pub fn accounts(&self) -> CallFuture<Vec<Address>, Box<dyn Future<Item = rpc::Value, Error = web3::Error>>> {
@@ -73,4 +135,72 @@ impl Web3Adapter {
   pub fn deploy(&self, bytes: &[u8]) -> Result<web3::contract::deploy::Builder<Transports>, ethabi::Error> {
     Contract::deploy(self.web3.eth(), bytes)
   }
+
+  pub fn block_number(&self) -> CallFuture<U256, Box<dyn Future<Item = rpc::Value, Error = web3::Error>>> {
+    self.web3.eth().block_number()
+  }
+
+  pub fn transaction_count(&self, address: Address, block_number: Option<BlockNumber>) -> CallFuture<U256, Box<dyn Future<Item = rpc::Value, Error = web3::Error>>> {
+    self.web3.eth().transaction_count(address, block_number)
+  }
+
+  pub fn code(&self, address: Address, block_number: Option<BlockNumber>) -> CallFuture<Bytes, Box<dyn Future<Item = rpc::Value, Error = web3::Error>>> {
+    self.web3.eth().code(address, block_number)
+  }
+
+  pub fn call(&self, req: CallRequest, block_number: Option<BlockNumber>) -> CallFuture<Bytes, Box<dyn Future<Item = rpc::Value, Error = web3::Error>>> {
+    self.web3.eth().call(req, block_number)
+  }
+
+  pub fn transaction(&self, hash: H256) -> CallFuture<Option<Transaction>, Box<dyn Future<Item = rpc::Value, Error = web3::Error>>> {
+    self.web3.eth().transaction(TransactionId::Hash(hash))
+  }
+
+  pub fn transaction_receipt(&self, hash: H256) -> CallFuture<Option<TransactionReceipt>, Box<dyn Future<Item = rpc::Value, Error = web3::Error>>> {
+    self.web3.eth().transaction_receipt(hash)
+  }
+
+  pub fn logs(&self, filter: Filter) -> CallFuture<Vec<Log>, Box<dyn Future<Item = rpc::Value, Error = web3::Error>>> {
+    self.web3.eth().logs(filter)
+  }
+
+  // Only WebSocket transports support subscriptions.
+  pub fn subscribe_new_heads(&self) -> Option<Box<dyn Future<Item = SubscriptionStream<web3::transports::WebSocket, BlockHeader>, Error = web3::Error>>> {
+    websocket(self.web3.transport())
+      .map(|transport| Box::new(web3::Web3::new(transport.clone()).eth_subscribe().subscribe_new_heads()) as Box<dyn Future<Item = _, Error = _>>)
+  }
+
+  pub fn send_transaction(&self, tx: TransactionRequest) -> CallFuture<H256, Box<dyn Future<Item = rpc::Value, Error = web3::Error>>> {
+    self.web3.eth().send_transaction(tx)
+  }
+
+  pub fn send_raw_transaction(&self, tx: Bytes) -> CallFuture<H256, Box<dyn Future<Item = rpc::Value, Error = web3::Error>>> {
+    self.web3.eth().send_raw_transaction(tx)
+  }
+
+  pub fn sign(&self, address: Address, data: Bytes) -> CallFuture<H520, Box<dyn Future<Item = rpc::Value, Error = web3::Error>>> {
+    self.web3.eth().sign(address, data)
+  }
+
+  pub fn execute(&self, method: &str, params: Vec<rpc::Value>) -> Box<dyn Future<Item = rpc::Value, Error = web3::Error>> {
+    self.web3.transport().execute(method, params)
+  }
+}
+
+fn websocket(transport: &Transports) -> Option<&web3::transports::WebSocket> {
+  match transport {
+    Transports::Ws(transport) => Some(transport),
+    Transports::Caching(transport) => websocket(transport.inner()),
+    _ => None,
+  }
+}
+
+// Configured URLs are used unchanged, others are put together from protocol,
+// host and port.
+fn is_http_url(url: &str) -> bool {
+  url.starts_with("http://") || url.starts_with("https://")
+}
+
+fn is_ws_url(url: &str) -> bool {
+  url.starts_with("ws://") || url.starts_with("wss://")
 }
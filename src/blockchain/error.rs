@@ -9,6 +9,7 @@ use crate::deployment::error::DeploymentTrackingError;
 pub enum NodeError {
   Io(io::Error),
   UnsupportedClient,
+  AlreadyRunning(u32),
   Other(String),
 }
 
@@ -17,6 +18,7 @@ impl Error for NodeError {
     match self {
       NodeError::Io(err) => Some(err),
       NodeError::UnsupportedClient => None,
+      NodeError::AlreadyRunning(_pid) => None,
       NodeError::Other(_message) => None,
     }
   }
@@ -32,6 +34,7 @@ impl fmt::Display for NodeError {
         }
       },
       NodeError::UnsupportedClient => write!(f, "No built-in support for request blockchain client. Please specify NodeConfig.client_options"),
+      NodeError::AlreadyRunning(pid) => write!(f, "A node is already running for this project (pid {}). Stop it with `vibranium node stop`", pid),
       NodeError::Other(message) => write!(f, "{}", message),
     }
   }
@@ -52,8 +55,13 @@ impl From<io::Error> for NodeError {
 #[derive(Debug)]
 pub enum ConnectionError {
   UnsupportedProtocol,
+  UnsupportedRotationStrategy(String),
   MissingConnectorConfig,
+  InvalidCassette(std::path::PathBuf, String),
+  MissingEnvVariable(String),
   Transport(web3::Error),
+  ReceiptTimeout(web3::types::H256, std::time::Duration),
+  InvalidBlock(String),
   Other(String),
 }
 
@@ -61,8 +69,13 @@ impl Error for ConnectionError {
   fn cause(&self) -> Option<&Error> {
     match self {
       ConnectionError::UnsupportedProtocol => None,
+      ConnectionError::UnsupportedRotationStrategy(_strategy) => None,
       ConnectionError::MissingConnectorConfig => None,
+      ConnectionError::InvalidCassette(_path, _message) => None,
+      ConnectionError::MissingEnvVariable(_name) => None,
       ConnectionError::Transport(error) => Some(error),
+      ConnectionError::ReceiptTimeout(_hash, _max_wait) => None,
+      ConnectionError::InvalidBlock(_block) => None,
       ConnectionError::Other(_message) => None,
     }
   }
@@ -72,8 +85,13 @@ impl fmt::Display for ConnectionError {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
       ConnectionError::UnsupportedProtocol => write!(f, "Couldn't create blockchain connector. The configured protocol is not supported"),
+      ConnectionError::UnsupportedRotationStrategy(strategy) => write!(f, "Couldn't create blockchain connector. Unsupported endpoint rotation '{}', expected 'priority' or 'round-robin'", strategy),
       ConnectionError::MissingConnectorConfig => write!(f, "Couldn't find configuration for blockchain connector in project configuration."),
+      ConnectionError::InvalidCassette(path, message) => write!(f, "Couldn't use RPC cassette {}: {}", path.to_string_lossy(), message),
+      ConnectionError::MissingEnvVariable(name) => write!(f, "Couldn't create blockchain connector. Environment variable {} referenced in connector configuration is not set", name),
       ConnectionError::Transport(error) => write!(f, "{}", error),
+      ConnectionError::ReceiptTimeout(hash, max_wait) => write!(f, "Couldn't get receipt of transaction {:?} within {}ms", hash, max_wait.as_millis()),
+      ConnectionError::InvalidBlock(block) => write!(f, "Invalid block '{}', expected a block number, latest, safe, finalized, earliest, an offset like -100 or an ISO 8601 timestamp", block),
       ConnectionError::Other(message) => write!(f, "{}", message),
     }
   }
@@ -84,3 +102,90 @@ impl From<DeploymentTrackingError> for ConnectionError {
     ConnectionError::Other(error.to_string())
   }
 }
+
+#[derive(Debug)]
+pub enum SignerError {
+  Connection(ConnectionError),
+  Other(String),
+}
+
+impl Error for SignerError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      SignerError::Connection(error) => Some(error),
+      SignerError::Other(_message) => None,
+    }
+  }
+}
+
+impl fmt::Display for SignerError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SignerError::Connection(error) => write!(f, "{}", error),
+      SignerError::Other(message) => write!(f, "{}", message),
+    }
+  }
+}
+
+impl From<ConnectionError> for SignerError {
+  fn from(error: ConnectionError) -> Self {
+    SignerError::Connection(error)
+  }
+}
+
+#[derive(Debug)]
+pub enum ChainControlError {
+  Unsupported(String),
+  UnknownSnapshot(String),
+  NotADevNetwork,
+  ReadOnlyMode,
+  Connection(ConnectionError),
+  Tracking(DeploymentTrackingError),
+  Io(io::Error),
+}
+
+impl Error for ChainControlError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      ChainControlError::Unsupported(_method) => None,
+      ChainControlError::UnknownSnapshot(_snapshot) => None,
+      ChainControlError::NotADevNetwork => None,
+      ChainControlError::ReadOnlyMode => None,
+      ChainControlError::Connection(error) => Some(error),
+      ChainControlError::Tracking(error) => Some(error),
+      ChainControlError::Io(error) => Some(error),
+    }
+  }
+}
+
+impl fmt::Display for ChainControlError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ChainControlError::Unsupported(method) => write!(f, "The connected node doesn't support {}. Snapshots require a development node like ganache or anvil", method),
+      ChainControlError::UnknownSnapshot(snapshot) => write!(f, "The connected node doesn't know snapshot {}. Snapshots can only be reverted to once", snapshot),
+      ChainControlError::NotADevNetwork => write!(f, "Snapshots are only taken of development networks"),
+      ChainControlError::ReadOnlyMode => write!(f, "Can't revert the chain in read-only mode"),
+      ChainControlError::Connection(error) => write!(f, "{}", error),
+      ChainControlError::Tracking(error) => write!(f, "{}", error),
+      ChainControlError::Io(error) => write!(f, "Couldn't record snapshot: {}", error),
+    }
+  }
+}
+
+impl From<ConnectionError> for ChainControlError {
+  fn from(error: ConnectionError) -> Self {
+    ChainControlError::Connection(error)
+  }
+}
+
+impl From<DeploymentTrackingError> for ChainControlError {
+  fn from(error: DeploymentTrackingError) -> Self {
+    ChainControlError::Tracking(error)
+  }
+}
+
+impl From<io::Error> for ChainControlError {
+  fn from(error: io::Error) -> Self {
+    ChainControlError::Io(error)
+  }
+}
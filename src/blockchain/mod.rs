@@ -1,18 +1,27 @@
-use std::process::{Command, Child};
+use std::process::ExitStatus;
 use std::path::PathBuf;
 
 use crate::config;
+use crate::process::ProcessCommand;
+use crate::project_generator::DEFAULT_ENVIRONMENT;
 use crate::utils;
 
 use support::SupportedBlockchainClients;
 
+pub mod blocks;
+pub mod chain_control;
+pub mod chains;
 pub mod error;
 pub mod support;
 pub mod connector;
+pub mod sender;
+pub mod signer;
+pub mod supervisor;
 
 pub struct NodeConfig {
   pub client: Option<String>,
   pub client_options: Option<Vec<String>>,
+  pub network: Option<String>,
 }
 
 pub struct Node<'a> {
@@ -26,29 +35,47 @@ impl<'a> Node<'a> {
     }
   }
 
-  pub fn start(&self, config: NodeConfig) -> Result<Child, error::NodeError> {
+  pub fn start(&self, config: NodeConfig) -> Result<ExitStatus, error::NodeError> {
+    let (client, client_options, _network) = self.resolve_command(&config)?;
+
+    support::init_node(&client, &client_options, &self.config.vibranium_dir_path)?;
+
+    info!("Starting node with command: {} {}", &client, client_options.join(" "));
+
+    ProcessCommand::new(client)
+      .args(client_options)
+      .log_to(&self.config.vibranium_dir_path)
+      .status()
+      .map_err(error::NodeError::Io)
+  }
+
+  // The client executable along with the options it's run with and the
+  // network whose chain data it uses.
+  pub fn resolve_command(&self, config: &NodeConfig) -> Result<(String, Vec<String>, String), error::NodeError> {
     let project_config = self.config.read()?;
 
-    let client = config.client.unwrap_or_else(|| {
+    let client = config.client.clone().unwrap_or_else(|| {
       match &project_config.blockchain {
         Some(config) => config.cmd.clone().unwrap_or_else(|| SupportedBlockchainClients::Parity.executable()),
         None => SupportedBlockchainClients::Parity.executable(),
       }
     });
 
+    let network = config.network.clone().unwrap_or_else(|| DEFAULT_ENVIRONMENT.to_string());
+
     let client_options: Vec<String> = match &config.client_options {
       Some(options) => {
         match client.parse() {
           Ok(SupportedBlockchainClients::Parity) => utils::merge_cli_options(
-            support::default_options_from(SupportedBlockchainClients::Parity, &self.config.vibranium_dir_path),
+            support::default_options_from(SupportedBlockchainClients::Parity, &self.config.vibranium_dir_path, &network),
             options.to_vec()
           ),
           Ok(SupportedBlockchainClients::Geth) => utils::merge_cli_options(
-            support::default_options_from(SupportedBlockchainClients::Geth, &self.config.vibranium_dir_path),
+            support::default_options_from(SupportedBlockchainClients::Geth, &self.config.vibranium_dir_path, &network),
             options.to_vec()
           ),
           Ok(SupportedBlockchainClients::Ganache) => utils::merge_cli_options(
-            support::default_options_from(SupportedBlockchainClients::Ganache, &self.config.vibranium_dir_path),
+            support::default_options_from(SupportedBlockchainClients::Ganache, &self.config.vibranium_dir_path, &network),
             options.to_vec()
           ),
           Err(_err) => options.to_vec(),
@@ -56,8 +83,8 @@ impl<'a> Node<'a> {
       }
       None => {
         match project_config.blockchain {
-          Some(config) => config.options.unwrap_or_else(|| try_default_options_from(&client, &self.config.vibranium_dir_path)),
-          None => try_default_options_from(&client, &self.config.vibranium_dir_path)
+          Some(config) => config.options.unwrap_or_else(|| try_default_options_from(&client, &self.config.vibranium_dir_path, &network)),
+          None => try_default_options_from(&client, &self.config.vibranium_dir_path, &network)
         }
       }
     };
@@ -68,23 +95,16 @@ impl<'a> Node<'a> {
       }
     }
 
-    support::init_node(&client, &client_options, &self.config.vibranium_dir_path)?;
-
-    info!("Starting node with command: {} {}", &client, client_options.join(" "));
-
-    Command::new(client)
-            .args(client_options)
-            .spawn()
-            .map_err(error::NodeError::Io)
+    Ok((client, client_options, network))
   }
 }
 
 
-fn try_default_options_from(client: &str, vibranium_dir_path: &PathBuf) -> Vec<String> {
+fn try_default_options_from(client: &str, vibranium_dir_path: &PathBuf, network: &str) -> Vec<String> {
   match client.parse() {
-    Ok(SupportedBlockchainClients::Parity) => support::default_options_from(SupportedBlockchainClients::Parity, vibranium_dir_path),
-    Ok(SupportedBlockchainClients::Geth) => support::default_options_from(SupportedBlockchainClients::Geth, vibranium_dir_path),
-    Ok(SupportedBlockchainClients::Ganache) => support::default_options_from(SupportedBlockchainClients::Ganache, vibranium_dir_path),
+    Ok(SupportedBlockchainClients::Parity) => support::default_options_from(SupportedBlockchainClients::Parity, vibranium_dir_path, network),
+    Ok(SupportedBlockchainClients::Geth) => support::default_options_from(SupportedBlockchainClients::Geth, vibranium_dir_path, network),
+    Ok(SupportedBlockchainClients::Ganache) => support::default_options_from(SupportedBlockchainClients::Ganache, vibranium_dir_path, network),
     Err(_err) => vec![],
   }
 }
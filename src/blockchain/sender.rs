@@ -0,0 +1,260 @@
+use super::connector::BlockchainConnector;
+use super::error::{ConnectionError, SignerError};
+use super::signer::Signer;
+
+use std::thread;
+use std::time::Instant;
+use web3::types::{BlockNumber, H256, TransactionReceipt, TransactionRequest, U256};
+
+const DEFAULT_FEE_BUMPING_BLOCKS: u64 = 3;
+// Nodes don't accept replacements that pay less than 10% more than the
+// transaction they replace.
+const MIN_FEE_BUMPING_PERCENT: u64 = 10;
+const DEFAULT_MAX_GAS_PRICE_MULTIPLIER: u64 = 2;
+
+// Transactions that haven't been included after `blocks` blocks are replaced
+// by the same transaction paying `percent` more gas, until `max_gas_price`
+// is reached. Without `max_gas_price`, gas prices are bumped up to twice the
+// initial gas price.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct FeeBumpingConfig {
+  pub blocks: Option<u64>,
+  pub percent: Option<u64>,
+  pub max_gas_price: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeBumping {
+  pub blocks: u64,
+  pub percent: u64,
+  pub max_gas_price: Option<U256>,
+}
+
+impl FeeBumping {
+  pub fn from_config(config: &FeeBumpingConfig) -> FeeBumping {
+    FeeBumping {
+      blocks: config.blocks.filter(|blocks| *blocks > 0).unwrap_or(DEFAULT_FEE_BUMPING_BLOCKS),
+      percent: config.percent.unwrap_or(MIN_FEE_BUMPING_PERCENT).max(MIN_FEE_BUMPING_PERCENT),
+      max_gas_price: config.max_gas_price.map(U256::from),
+    }
+  }
+
+  pub fn max_gas_price(&self, initial_gas_price: U256) -> U256 {
+    self.max_gas_price.unwrap_or_else(|| initial_gas_price * U256::from(DEFAULT_MAX_GAS_PRICE_MULTIPLIER))
+  }
+
+  // Bumped gas prices are rounded up, so that small gas prices still grow.
+  // Returns `None` once the cap has been reached.
+  pub fn next_gas_price(&self, gas_price: U256, max_gas_price: U256) -> Option<U256> {
+    if gas_price >= max_gas_price {
+      return None;
+    }
+    let bumped = (gas_price * U256::from(100 + self.percent) + U256::from(99)) / U256::from(100);
+    Some(bumped.max(gas_price + U256::one()).min(max_gas_price))
+  }
+}
+
+// Submits transactions through a signer and waits for their inclusion. With
+// fee bumping, transactions that are stuck get replaced, reusing their nonce,
+// and whichever of the submitted transactions gets included first wins.
+pub struct TxSender<'a> {
+  connector: &'a BlockchainConnector,
+  signer: &'a dyn Signer,
+  confirmations: usize,
+  fee_bumping: Option<FeeBumping>,
+}
+
+impl<'a> TxSender<'a> {
+  pub fn new(connector: &'a BlockchainConnector, signer: &'a dyn Signer) -> TxSender<'a> {
+    TxSender {
+      connector,
+      signer,
+      confirmations: 0,
+      fee_bumping: None,
+    }
+  }
+
+  pub fn with_confirmations(mut self, confirmations: usize) -> TxSender<'a> {
+    self.confirmations = confirmations;
+    self
+  }
+
+  pub fn with_fee_bumping(mut self, fee_bumping: Option<FeeBumping>) -> TxSender<'a> {
+    self.fee_bumping = fee_bumping;
+    self
+  }
+
+  // `on_submitted` is called with the hash of every transaction that has been
+  // submitted, including replacements.
+  pub fn send<F: FnMut(H256)>(&self, tx: TransactionRequest, mut on_submitted: F) -> Result<TransactionReceipt, SignerError> {
+    let fee_bumping = match &self.fee_bumping {
+      Some(fee_bumping) => fee_bumping,
+      None => {
+        let hash = self.signer.send_transaction(self.connector, tx)?;
+        on_submitted(hash);
+        return self.connector.wait_for_transaction_receipt(hash, self.confirmations).map_err(SignerError::Connection);
+      }
+    };
+
+    let mut tx = tx;
+    if tx.nonce.is_none() {
+      tx.nonce = Some(self.connector.transaction_count(self.signer.address(), Some(BlockNumber::Pending))?);
+    }
+    if tx.gas_price.is_none() {
+      tx.gas_price = Some(self.connector.gas_price()?);
+    }
+
+    let hash = self.signer.send_transaction(self.connector, tx.clone())?;
+    on_submitted(hash);
+    self.wait_for_inclusion(tx, hash, fee_bumping, on_submitted)
+  }
+
+  // Waits for a transaction that has been submitted before, e.g. by a
+  // deployment that didn't finish. `tx` needs to have the nonce and gas price
+  // of the submitted transaction, so that replacements outbid it.
+  pub fn resume<F: FnMut(H256)>(&self, tx: TransactionRequest, hash: H256, on_submitted: F) -> Result<TransactionReceipt, SignerError> {
+    match &self.fee_bumping {
+      Some(fee_bumping) => self.wait_for_inclusion(tx, hash, fee_bumping, on_submitted),
+      None => self.connector.wait_for_transaction_receipt(hash, self.confirmations).map_err(SignerError::Connection),
+    }
+  }
+
+  fn wait_for_inclusion<F: FnMut(H256)>(&self, tx: TransactionRequest, hash: H256, fee_bumping: &FeeBumping, mut on_submitted: F) -> Result<TransactionReceipt, SignerError> {
+    let mut gas_price = match tx.gas_price {
+      Some(gas_price) => gas_price,
+      None => self.connector.gas_price()?,
+    };
+    let max_gas_price = fee_bumping.max_gas_price(gas_price);
+
+    let mut hashes = vec![hash];
+    let mut submitted_at = self.connector.block_number()?;
+
+    let polling = self.connector.receipt_polling();
+    let started = Instant::now();
+    let mut interval = polling.interval;
+
+    loop {
+      for hash in &hashes {
+        if self.connector.transaction_receipt(*hash)?.is_some() {
+          return self.connector.wait_for_transaction_receipt(*hash, self.confirmations).map_err(SignerError::Connection);
+        }
+      }
+
+      let block_number = self.connector.block_number()?;
+      if block_number >= submitted_at + U256::from(fee_bumping.blocks) {
+        if let Some(next_gas_price) = fee_bumping.next_gas_price(gas_price, max_gas_price) {
+          let mut replacement = tx.clone();
+          replacement.gas_price = Some(next_gas_price);
+          // The original transaction might get included while the replacement
+          // is rejected, so failing to replace it isn't fatal.
+          match self.signer.send_transaction(self.connector, replacement) {
+            Ok(hash) => {
+              info!("Replaced transaction {:?} with {:?}, bumping its gas price from {} to {}", hashes[hashes.len() - 1], hash, gas_price, next_gas_price);
+              hashes.push(hash);
+              on_submitted(hash);
+            },
+            Err(err) => warn!("Couldn't replace transaction {:?}: {}", hashes[hashes.len() - 1], err),
+          }
+          gas_price = next_gas_price;
+          submitted_at = block_number;
+        }
+      }
+
+      if let Some(max_wait) = polling.max_wait {
+        if started.elapsed() >= max_wait {
+          return Err(SignerError::Connection(ConnectionError::ReceiptTimeout(hashes[hashes.len() - 1], max_wait)));
+        }
+      }
+      thread::sleep(interval);
+      interval = polling.next_interval(interval);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{FeeBumping, FeeBumpingConfig, TxSender};
+  use crate::blockchain::connector::polling::{ReceiptPolling, ReceiptPollingConfig};
+  use crate::blockchain::signer::NodeSigner;
+  use crate::testing::MockTransport;
+  use jsonrpc_core as rpc;
+  use std::str::FromStr;
+  use web3::types::{Address, H256, TransactionRequest, U256};
+
+  const FIRST_TX_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+  const SECOND_TX_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000002";
+
+  fn receipt(hash: &str) -> rpc::Value {
+    serde_json::json!({
+      "transactionHash": hash,
+      "transactionIndex": "0x0",
+      "blockHash": hash,
+      "blockNumber": "0x12",
+      "cumulativeGasUsed": "0x1",
+      "gasUsed": "0x1",
+      "contractAddress": null,
+      "logs": [],
+      "status": "0x1",
+      "logsBloom": format!("0x{}", "0".repeat(512)),
+    })
+  }
+
+  #[test]
+  fn it_should_bump_gas_prices_up_to_the_cap() {
+    let fee_bumping = FeeBumping::from_config(&FeeBumpingConfig { percent: Some(5), ..FeeBumpingConfig::default() });
+    assert_eq!((fee_bumping.blocks, fee_bumping.percent), (3, 10));
+
+    let max_gas_price = fee_bumping.max_gas_price(U256::from(100));
+    assert_eq!(max_gas_price, U256::from(200));
+    assert_eq!(fee_bumping.next_gas_price(U256::from(100), max_gas_price), Some(U256::from(110)));
+    assert_eq!(fee_bumping.next_gas_price(U256::from(1), max_gas_price), Some(U256::from(2)));
+    assert_eq!(fee_bumping.next_gas_price(U256::from(190), max_gas_price), Some(U256::from(200)));
+    assert_eq!(fee_bumping.next_gas_price(U256::from(200), max_gas_price), None);
+  }
+
+  #[test]
+  fn it_should_replace_transactions_that_arent_included_in_time() {
+    let mock = MockTransport::new();
+    mock.add_response("eth_sendTransaction", serde_json::json!(FIRST_TX_HASH));
+    mock.add_response("eth_sendTransaction", serde_json::json!(SECOND_TX_HASH));
+    for block_number in &["0x10", "0x11", "0x12"] {
+      mock.add_response("eth_blockNumber", serde_json::json!(block_number));
+    }
+    for _ in 0..3 {
+      mock.add_response("eth_getTransactionReceipt", rpc::Value::Null);
+    }
+    mock.add_response("eth_getTransactionReceipt", receipt(SECOND_TX_HASH));
+    mock.add_response("eth_getTransactionReceipt", receipt(SECOND_TX_HASH));
+
+    let connector = mock.connector().with_receipt_polling(ReceiptPolling::from_config(&Some(ReceiptPollingConfig {
+      interval: Some(1),
+      ..ReceiptPollingConfig::default()
+    })));
+    let signer = NodeSigner::new(&connector, Address::from_low_u64_be(1));
+    let sender = TxSender::new(&connector, &signer)
+      .with_fee_bumping(Some(FeeBumping::from_config(&FeeBumpingConfig { blocks: Some(2), ..FeeBumpingConfig::default() })));
+
+    let mut submitted = vec![];
+    let tx = TransactionRequest {
+      from: Address::from_low_u64_be(1),
+      to: None,
+      gas: None,
+      gas_price: Some(U256::from(100)),
+      value: None,
+      nonce: Some(U256::from(3)),
+      data: None,
+      condition: None,
+    };
+    let receipt = sender.send(tx, |hash| submitted.push(hash)).unwrap();
+
+    let second_tx_hash = H256::from_str(&SECOND_TX_HASH[2..]).unwrap();
+    assert_eq!(receipt.transaction_hash, second_tx_hash);
+    assert_eq!(submitted, vec![H256::from_str(&FIRST_TX_HASH[2..]).unwrap(), second_tx_hash]);
+
+    let transactions = mock.requests_for("eth_sendTransaction");
+    assert_eq!(transactions[1][0]["nonce"], serde_json::json!("0x3"));
+    assert_eq!(transactions[1][0]["gasPrice"], serde_json::json!("0x6e"));
+    assert!(mock.pending_responses().is_empty());
+  }
+}
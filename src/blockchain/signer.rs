@@ -0,0 +1,59 @@
+use super::connector::BlockchainConnector;
+use super::error::SignerError;
+use web3::types::{Address, BlockNumber, Bytes, H256, H520, TransactionRequest};
+
+pub trait Signer {
+  fn address(&self) -> Address;
+
+  fn sign_transaction(&self, tx: &TransactionRequest) -> Result<Bytes, SignerError>;
+
+  fn sign_message(&self, message: &[u8]) -> Result<H520, SignerError>;
+
+  // Transactions are signed by the signer and submitted as raw transactions.
+  // Nonce and gas price are filled in from the connected node if missing,
+  // since an external signer has no way of knowing them.
+  fn send_transaction(&self, connector: &BlockchainConnector, tx: TransactionRequest) -> Result<H256, SignerError> {
+    let mut tx = tx;
+    if tx.nonce.is_none() {
+      tx.nonce = Some(connector.transaction_count(self.address(), Some(BlockNumber::Pending))?);
+    }
+    if tx.gas_price.is_none() {
+      tx.gas_price = Some(connector.gas_price()?);
+    }
+    let raw_tx = self.sign_transaction(&tx)?;
+    connector.send_raw_transaction(raw_tx).map_err(SignerError::Connection)
+  }
+}
+
+// Signs with an account that is managed and unlocked by the connected node.
+pub struct NodeSigner<'a> {
+  connector: &'a BlockchainConnector,
+  address: Address,
+}
+
+impl<'a> NodeSigner<'a> {
+  pub fn new(connector: &'a BlockchainConnector, address: Address) -> NodeSigner<'a> {
+    NodeSigner {
+      connector,
+      address,
+    }
+  }
+}
+
+impl<'a> Signer for NodeSigner<'a> {
+  fn address(&self) -> Address {
+    self.address
+  }
+
+  fn sign_transaction(&self, tx: &TransactionRequest) -> Result<Bytes, SignerError> {
+    self.connector.sign_transaction(tx.clone()).map_err(SignerError::Connection)
+  }
+
+  fn sign_message(&self, message: &[u8]) -> Result<H520, SignerError> {
+    self.connector.sign(self.address, Bytes(message.to_vec())).map_err(SignerError::Connection)
+  }
+
+  fn send_transaction(&self, connector: &BlockchainConnector, tx: TransactionRequest) -> Result<H256, SignerError> {
+    connector.send_transaction(tx).map_err(SignerError::Connection)
+  }
+}
@@ -0,0 +1,259 @@
+// Blockchain nodes running in the background, as opposed to `Node::start()`,
+// which runs them in the foreground. The managed node of a project is tracked
+// in `.vibranium/node.toml`, its output goes to `.vibranium/node.log`.
+
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config;
+use crate::process::{unix_time, ProcessCommand};
+use crate::utils;
+use super::error::NodeError;
+use super::support::{self, SupportedBlockchainClients};
+use super::{Node, NodeConfig};
+
+pub const NODE_STATE_FILE: &str = "node.toml";
+pub const NODE_LOG_FILE: &str = "node.log";
+
+const DEFAULT_RPC_PORT: u16 = 8545;
+const DEFAULT_WS_PORT: u16 = 8546;
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManagedNode {
+  pub pid: u32,
+  pub client: String,
+  pub args: Vec<String>,
+  pub network: String,
+  pub started_at: u64,
+  pub rpc_port: Option<u16>,
+  pub ws_port: Option<u16>,
+}
+
+impl ManagedNode {
+  pub fn port_for(&self, protocol: &str) -> Option<u16> {
+    match protocol {
+      "ws" => self.ws_port,
+      _ => self.rpc_port,
+    }
+  }
+
+  pub fn uptime(&self) -> u64 {
+    unix_time().saturating_sub(self.started_at)
+  }
+}
+
+pub struct NodeSupervisor<'a> {
+  config: &'a config::Config,
+}
+
+impl<'a> NodeSupervisor<'a> {
+  pub fn new(config: &'a config::Config) -> NodeSupervisor<'a> {
+    NodeSupervisor {
+      config,
+    }
+  }
+
+  // Ports of built-in clients are chosen automatically, preferring the ones
+  // of the configured connector. Ports passed as client options take precedence.
+  pub fn start(&self, config: NodeConfig) -> Result<ManagedNode, NodeError> {
+    if let Some(node) = self.status()? {
+      return Err(NodeError::AlreadyRunning(node.pid));
+    }
+
+    let (client, client_options, network) = Node::new(self.config).resolve_command(&config)?;
+    let (args, rpc_port, ws_port) = match client.parse::<SupportedBlockchainClients>() {
+      Ok(supported) => {
+        let (rpc_flag, ws_flag) = support::port_flags(supported);
+        let (preferred_rpc_port, preferred_ws_port) = self.preferred_ports();
+        let rpc_port = available_port(preferred_rpc_port);
+        let mut port_options = vec![rpc_flag.to_string(), rpc_port.to_string()];
+        if let Some(ws_flag) = ws_flag {
+          port_options.push(ws_flag.to_string());
+          port_options.push(available_port(preferred_ws_port).to_string());
+        }
+
+        let args = utils::merge_cli_options(port_options, client_options);
+        let rpc_port = option_value(&args, rpc_flag);
+        let ws_port = ws_flag.map(|ws_flag| option_value(&args, ws_flag)).unwrap_or(rpc_port);
+        (args, rpc_port, ws_port)
+      },
+      Err(_err) => (client_options, None, None),
+    };
+
+    support::init_node(&client, &args, &self.config.vibranium_dir_path)?;
+    info!("Starting node in background with command: {} {}", &client, args.join(" "));
+
+    let child = ProcessCommand::new(client.as_str())
+      .args(args.clone())
+      .log_to(&self.config.vibranium_dir_path)
+      .spawn(&self.log_file())
+      .map_err(NodeError::Io)?;
+
+    let node = ManagedNode {
+      pid: child.id(),
+      client,
+      args,
+      network,
+      started_at: unix_time(),
+      rpc_port,
+      ws_port,
+    };
+    fs::write(self.state_file(), toml::to_string(&node).map_err(|err| NodeError::Other(err.to_string()))?)?;
+    Ok(node)
+  }
+
+  // Nodes are asked to shut down first and only killed if they're still
+  // running after a grace period.
+  pub fn stop(&self) -> Result<Option<ManagedNode>, NodeError> {
+    let node = match self.status()? {
+      Some(node) => node,
+      None => return Ok(None),
+    };
+
+    signal(node.pid, false)?;
+    let started = Instant::now();
+    while is_alive(node.pid) && started.elapsed() < SHUTDOWN_TIMEOUT {
+      thread::sleep(SHUTDOWN_POLL_INTERVAL);
+    }
+    if is_alive(node.pid) {
+      warn!("Node didn't shut down within {}s, killing it", SHUTDOWN_TIMEOUT.as_secs());
+      signal(node.pid, true)?;
+    }
+
+    fs::remove_file(self.state_file())?;
+    Ok(Some(node))
+  }
+
+  // State of nodes that aren't running anymore is cleaned up.
+  pub fn status(&self) -> Result<Option<ManagedNode>, NodeError> {
+    let state_file = self.state_file();
+    if !state_file.exists() {
+      return Ok(None);
+    }
+
+    let node: ManagedNode = toml::from_str(&fs::read_to_string(&state_file)?).map_err(|err| NodeError::Other(err.to_string()))?;
+    if is_alive(node.pid) {
+      Ok(Some(node))
+    } else {
+      info!("Managed node with pid {} isn't running anymore", node.pid);
+      fs::remove_file(state_file)?;
+      Ok(None)
+    }
+  }
+
+  pub fn logs(&self, lines: usize) -> Result<Vec<String>, NodeError> {
+    let log_file = self.log_file();
+    if !log_file.exists() {
+      return Ok(vec![]);
+    }
+    let content = fs::read_to_string(log_file)?;
+    let all: Vec<&str> = content.lines().collect();
+    Ok(all[all.len().saturating_sub(lines)..].iter().map(|line| line.to_string()).collect())
+  }
+
+  pub fn log_file(&self) -> PathBuf {
+    self.config.vibranium_dir_path.join(NODE_LOG_FILE)
+  }
+
+  fn state_file(&self) -> PathBuf {
+    self.config.vibranium_dir_path.join(NODE_STATE_FILE)
+  }
+
+  fn preferred_ports(&self) -> (u16, u16) {
+    let connector = self.config.read().ok()
+      .and_then(|project_config| project_config.blockchain)
+      .and_then(|blockchain_config| blockchain_config.connector);
+
+    match connector.as_ref().and_then(|connector| connector.port.parse::<u16>().ok().map(|port| (connector.protocol.as_str(), port))) {
+      Some(("ws", port)) => (DEFAULT_RPC_PORT, port),
+      Some((_protocol, port)) => (port, DEFAULT_WS_PORT),
+      None => (DEFAULT_RPC_PORT, DEFAULT_WS_PORT),
+    }
+  }
+}
+
+fn available_port(preferred: u16) -> u16 {
+  if TcpListener::bind(("127.0.0.1", preferred)).is_ok() {
+    return preferred;
+  }
+  TcpListener::bind(("127.0.0.1", 0))
+    .and_then(|listener| listener.local_addr())
+    .map(|address| address.port())
+    .unwrap_or(preferred)
+}
+
+fn option_value(args: &[String], flag: &str) -> Option<u16> {
+  args.iter()
+    .position(|arg| arg == flag)
+    .and_then(|i| args.get(i + 1))
+    .and_then(|value| value.parse().ok())
+}
+
+pub(crate) fn is_alive(pid: u32) -> bool {
+  if cfg!(target_os = "windows") {
+    Command::new("tasklist")
+      .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+      .output()
+      .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+  } else {
+    Command::new("kill")
+      .args(["-0", &pid.to_string()])
+      .stderr(Stdio::null())
+      .status()
+      .is_ok_and(|status| status.success())
+  }
+}
+
+fn signal(pid: u32, force: bool) -> Result<(), NodeError> {
+  let pid = pid.to_string();
+  let mut command = if cfg!(target_os = "windows") {
+    let mut command = Command::new("taskkill");
+    command.args(["/PID", &pid]);
+    if force {
+      command.arg("/F");
+    }
+    command
+  } else {
+    // Nodes are started in their own process group, which also contains
+    // processes spawned by wrapper scripts, e.g. of ganache-cli.
+    let mut command = Command::new("kill");
+    command.args([if force { "-KILL" } else { "-TERM" }, "--", &format!("-{}", pid)]);
+    command
+  };
+  command.stderr(Stdio::null()).status().map(|_status| ()).map_err(NodeError::Io)
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{option_value, ManagedNode};
+
+  #[test]
+  fn it_should_read_ports_from_options() {
+    let args: Vec<String> = ["--dev", "--rpcport", "9545", "--wsport", "invalid"].iter().map(|arg| arg.to_string()).collect();
+    assert_eq!(option_value(&args, "--rpcport"), Some(9545));
+    assert_eq!(option_value(&args, "--wsport"), None);
+    assert_eq!(option_value(&args, "--port"), None);
+  }
+
+  #[test]
+  fn it_should_pick_port_by_protocol() {
+    let node = ManagedNode {
+      pid: 1,
+      client: "geth".to_string(),
+      args: vec![],
+      network: "development".to_string(),
+      started_at: 0,
+      rpc_port: Some(8545),
+      ws_port: Some(18546),
+    };
+    assert_eq!(node.port_for("rpc"), Some(8545));
+    assert_eq!(node.port_for("ws"), Some(18546));
+  }
+}
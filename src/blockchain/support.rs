@@ -5,12 +5,9 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use std::string::ToString;
 
+use super::chains;
 use crate::project_generator;
-use project_generator::{
-  DEFAULT_DATADIR_NAME,
-  DEFAULT_ENVIRONMENT,
-  DEFAULT_DEV_PASSWORDS_DIR,
-};
+use project_generator::DEFAULT_DEV_PASSWORDS_DIR;
 
 const PARITY_CLIENT_BINARY_UNIX: &str = "parity";
 const PARITY_CLIENT_BINARY_WINDOWS: &str = "parity.exe";
@@ -97,7 +94,7 @@ pub fn init_node(client: &str, options: &Vec<String>, vibranium_dir_path: &PathB
   }
 }
 
-pub fn default_options_from(client: SupportedBlockchainClients, vibranium_dir_path: &PathBuf) -> Vec<String> {
+pub fn default_options_from(client: SupportedBlockchainClients, vibranium_dir_path: &PathBuf, network: &str) -> Vec<String> {
   match client {
     SupportedBlockchainClients::Parity => {
       vec![
@@ -106,9 +103,7 @@ pub fn default_options_from(client: SupportedBlockchainClients, vibranium_dir_pa
         "--ws-origins".to_string(),
         "all".to_string(),
         "--base-path".to_string(),
-        vibranium_dir_path
-          .join(DEFAULT_DATADIR_NAME)
-          .join(DEFAULT_ENVIRONMENT)
+        chains::data_dir(vibranium_dir_path, network)
           .to_string_lossy()
           .to_string(),
         "--unlock".to_string(),
@@ -129,9 +124,7 @@ pub fn default_options_from(client: SupportedBlockchainClients, vibranium_dir_pa
         "--wsorigins".to_string(),
         "*".to_string(),
         "--datadir".to_string(),
-        vibranium_dir_path
-          .join(DEFAULT_DATADIR_NAME)
-          .join(DEFAULT_ENVIRONMENT)
+        chains::data_dir(vibranium_dir_path, network)
           .to_string_lossy()
           .to_string(),
       ]
@@ -140,12 +133,19 @@ pub fn default_options_from(client: SupportedBlockchainClients, vibranium_dir_pa
       vec![
         "--deterministic".to_string(),
         "--db".to_string(),
-        vibranium_dir_path
-          .join(DEFAULT_DATADIR_NAME)
-          .join(DEFAULT_ENVIRONMENT)
+        chains::data_dir(vibranium_dir_path, network)
           .to_string_lossy()
           .to_string(),
       ]
     }
   }
 }
+
+// Flags of the HTTP and WebSocket ports. Ganache serves both on the same port.
+pub fn port_flags(client: SupportedBlockchainClients) -> (&'static str, Option<&'static str>) {
+  match client {
+    SupportedBlockchainClients::Parity => ("--jsonrpc-port", Some("--ws-port")),
+    SupportedBlockchainClients::Geth => ("--rpcport", Some("--wsport")),
+    SupportedBlockchainClients::Ganache => ("--port", None),
+  }
+}
@@ -0,0 +1,79 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+
+use crate::blockchain::error::ConnectionError;
+use crate::compiler;
+use crate::config;
+use crate::deployment;
+use web3::types::Address;
+
+#[derive(Debug)]
+pub enum BuildError {
+  Compilation(compiler::error::CompilerError),
+  InvalidConfig(config::error::ConfigError),
+  Deployment(deployment::error::DeploymentError),
+  Io(io::Error),
+  Connection(ConnectionError),
+  NoCode(Address),
+  CodeHashMismatch(Address, String, String),
+}
+
+impl Error for BuildError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      BuildError::Compilation(error) => Some(error),
+      BuildError::InvalidConfig(error) => Some(error),
+      BuildError::Deployment(error) => Some(error),
+      BuildError::Io(error) => Some(error),
+      BuildError::Connection(error) => Some(error),
+      BuildError::NoCode(_address) => None,
+      BuildError::CodeHashMismatch(_address, _expected, _actual) => None,
+    }
+  }
+}
+
+impl fmt::Display for BuildError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      BuildError::Compilation(error) => write!(f, "Couldn't compile Smart Contracts: {}", error),
+      BuildError::InvalidConfig(error) => write!(f, "{}", error),
+      BuildError::Deployment(error) => write!(f, "{}", error),
+      BuildError::Io(error) => write!(f, "Couldn't generate bindings: {}", error),
+      BuildError::Connection(error) => write!(f, "Couldn't check deployed code: {}", error),
+      BuildError::NoCode(address) => write!(f, "There's no code deployed at {:?}", address),
+      BuildError::CodeHashMismatch(address, expected, actual) => write!(f, "Code deployed at {:?} has hash {}, but bindings expect {}. Bindings are out of date or the address is wrong", address, actual, expected),
+    }
+  }
+}
+
+impl From<compiler::error::CompilerError> for BuildError {
+  fn from(error: compiler::error::CompilerError) -> Self {
+    BuildError::Compilation(error)
+  }
+}
+
+impl From<config::error::ConfigError> for BuildError {
+  fn from(error: config::error::ConfigError) -> Self {
+    BuildError::InvalidConfig(error)
+  }
+}
+
+impl From<deployment::error::DeploymentError> for BuildError {
+  fn from(error: deployment::error::DeploymentError) -> Self {
+    BuildError::Deployment(error)
+  }
+}
+
+impl From<io::Error> for BuildError {
+  fn from(error: io::Error) -> Self {
+    BuildError::Io(error)
+  }
+}
+
+impl From<ConnectionError> for BuildError {
+  fn from(error: ConnectionError) -> Self {
+    BuildError::Connection(error)
+  }
+}
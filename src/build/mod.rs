@@ -0,0 +1,307 @@
+//! Helpers to compile a project's Smart Contracts from a Cargo build script,
+//! so that crates living next to the Solidity sources stay in sync with them:
+//!
+//! ```no_run
+//! // build.rs
+//! let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+//! vibranium::build::Build::new("./contracts-project")
+//!   .bindings(out_dir.join("contracts.rs"))
+//!   .run()
+//!   .unwrap();
+//! ```
+//!
+//! The generated bindings can then be included with
+//! `include!(concat!(env!("OUT_DIR"), "/contracts.rs"));`.
+//!
+//! Every binding records the commit and compiler version it has been
+//! generated from, as well as the `CODE_HASH` of its runtime bytecode, which
+//! `check_code_hash()` compares with the code deployed at an address to catch
+//! bindings that have drifted from the chain.
+
+pub mod error;
+
+use crate::artifacts;
+use crate::blockchain::connector::BlockchainConnector;
+use crate::compiler::{self, CompilerConfig};
+use crate::deployment::planner::DeploymentPlanner;
+use crate::utils;
+use crate::Vibranium;
+
+use sha3::{Digest, Keccak256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use web3::types::Address;
+
+const ARTIFACT_EXTENSION_ABI: &str = "abi";
+const ARTIFACT_EXTENSION_BINARY: &str = "bin";
+const ARTIFACT_EXTENSION_RUNTIME_BINARY: &str = "bin-runtime";
+
+pub struct Build {
+  vibranium: Vibranium,
+  compiler_options: Option<Vec<String>>,
+  bindings: Option<PathBuf>,
+  tags: Option<Vec<String>>,
+}
+
+impl Build {
+  pub fn new<P: AsRef<Path>>(project_path: P) -> Build {
+    Build {
+      vibranium: Vibranium::new(project_path.as_ref().to_path_buf()),
+      compiler_options: None,
+      bindings: None,
+      tags: None,
+    }
+  }
+
+  pub fn compiler_options(mut self, options: Vec<String>) -> Build {
+    self.compiler_options = Some(options);
+    self
+  }
+
+  // Writes a Rust module per compiled Smart Contract with its ABI and
+  // bytecode to the given file.
+  pub fn bindings<P: AsRef<Path>>(mut self, path: P) -> Build {
+    self.bindings = Some(path.as_ref().to_path_buf());
+    self
+  }
+
+  // Only compiles and generates bindings for Smart Contracts tagged with any
+  // of the given tags in the deployment configuration.
+  pub fn tags(mut self, tags: Vec<String>) -> Build {
+    self.tags = Some(tags);
+    self
+  }
+
+  pub fn run(&self) -> Result<(), error::BuildError> {
+    for path in self.watched_files()? {
+      println!("cargo:rerun-if-changed={}", path.to_string_lossy());
+    }
+
+    let output = self.vibranium.compile(self.compiler_config())?;
+
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+      println!("cargo:warning={}", line);
+    }
+
+    if let Some(bindings) = &self.bindings {
+      fs::write(bindings, self.generate_bindings()?)?;
+    }
+    Ok(())
+  }
+
+  // Build scripts have to be rerun whenever the project configuration or one
+  // of the Smart Contract sources changes.
+  pub fn watched_files(&self) -> Result<Vec<PathBuf>, error::BuildError> {
+    let project_config = self.vibranium.config.read()?;
+    let compiler = compiler::Compiler::new(&self.vibranium.config);
+
+    let mut files = vec![self.vibranium.config.config_file.clone()];
+    files.extend(compiler.source_files(&project_config.sources.smart_contracts));
+    Ok(files)
+  }
+
+  pub fn generate_bindings(&self) -> Result<String, error::BuildError> {
+    let project_config = self.vibranium.config.read()?;
+    let artifacts_dir = self.vibranium.config.project_path.join(&project_config.sources.artifacts);
+
+    let mut abi_paths: Vec<PathBuf> = fs::read_dir(&artifacts_dir)?
+      .filter_map(Result::ok)
+      .map(|entry| entry.path())
+      .filter(|path| path.extension().map(|extension| extension == ARTIFACT_EXTENSION_ABI).unwrap_or(false))
+      .collect();
+    abi_paths.sort();
+
+    if let Some(tags) = &self.tags {
+      let names: Vec<String> = DeploymentPlanner::new(&self.vibranium.config).tagged(tags)?
+        .into_iter()
+        .map(|smart_contract_config| smart_contract_config.instance_of.unwrap_or(smart_contract_config.name))
+        .collect();
+      abi_paths.retain(|abi_path| abi_path.file_stem().is_some_and(|name| names.iter().any(|tagged| name == tagged.as_str())));
+    }
+
+    let compiler = compiler::Compiler::new(&self.vibranium.config);
+    let compiler_version = compiler.resolve_command(&self.compiler_config()).ok()
+      .and_then(|(compiler_cmd, _options)| artifacts::compiler_version(&compiler_cmd, &self.vibranium.config.vibranium_dir_path));
+    let git_commit = utils::git_commit(&self.vibranium.config.project_path, &self.vibranium.config.vibranium_dir_path);
+
+    let mut bindings = String::from("// Generated by vibranium::build. Do not edit.\n");
+
+    for abi_path in abi_paths {
+      let name = abi_path.file_stem().unwrap().to_string_lossy().to_string();
+      let abi_path = fs::canonicalize(&abi_path)?;
+      let bin_path = abi_path.with_extension(ARTIFACT_EXTENSION_BINARY);
+      let runtime_path = abi_path.with_extension(ARTIFACT_EXTENSION_RUNTIME_BINARY);
+
+      bindings.push_str(&format!("\n#[allow(dead_code)]\npub mod {} {{\n", module_name(&name)));
+      bindings.push_str(&format!("  pub const NAME: &str = {:?};\n", &name));
+      bindings.push_str(&format!("  pub const ABI: &str = include_str!({:?});\n", abi_path.to_string_lossy()));
+      if bin_path.exists() {
+        bindings.push_str(&format!("  pub const BYTECODE: &str = include_str!({:?});\n", bin_path.to_string_lossy()));
+      }
+      if runtime_path.exists() {
+        let runtime_bytecode = utils::decode_bytecode(&fs::read_to_string(&runtime_path)?);
+        bindings.push_str(&format!("  pub const CODE_HASH: &str = {:?};\n", code_hash(&runtime_bytecode)));
+      }
+      bindings.push_str(&format!("  pub const COMPILER_VERSION: Option<&str> = {:?};\n", &compiler_version.as_deref()));
+      bindings.push_str(&format!("  pub const GIT_COMMIT: Option<&str> = {:?};\n", &git_commit.as_deref()));
+      bindings.push_str("}\n");
+    }
+
+    Ok(bindings)
+  }
+
+  fn compiler_config(&self) -> CompilerConfig {
+    CompilerConfig {
+      compiler: None,
+      compiler_options: self.compiler_options.clone(),
+      replace_options: false,
+      force: false,
+      tags: self.tags.clone(),
+    }
+  }
+}
+
+// Hash of runtime bytecode without the metadata appended by the compiler,
+// which changes with e.g. source file paths.
+pub fn code_hash(code: &[u8]) -> String {
+  let mut code = code.to_vec();
+  utils::strip_metadata(&mut code);
+  format!("0x{:x}", Keccak256::digest(&code))
+}
+
+// Fails if the code deployed at `address` doesn't match the `CODE_HASH` of a
+// binding.
+pub fn check_code_hash(connector: &BlockchainConnector, address: Address, expected_hash: &str) -> Result<(), error::BuildError> {
+  let code = connector.code(address, None)?;
+  if code.0.is_empty() {
+    return Err(error::BuildError::NoCode(address));
+  }
+  let actual_hash = code_hash(&code.0);
+  if !actual_hash.eq_ignore_ascii_case(expected_hash) {
+    return Err(error::BuildError::CodeHashMismatch(address, expected_hash.to_string(), actual_hash));
+  }
+  Ok(())
+}
+
+// `SimpleTestContract` becomes `simple_test_contract`.
+fn module_name(name: &str) -> String {
+  let mut module_name = String::new();
+  let chars: Vec<char> = name.chars().collect();
+
+  for (i, c) in chars.iter().enumerate() {
+    if c.is_ascii_uppercase() {
+      let previous_is_lowercase = i > 0 && (chars[i - 1].is_ascii_lowercase() || chars[i - 1].is_ascii_digit());
+      let next_is_lowercase = chars.get(i + 1).map(|next| next.is_ascii_lowercase()).unwrap_or(false);
+      let previous_is_uppercase = i > 0 && chars[i - 1].is_ascii_uppercase();
+      if previous_is_lowercase || (previous_is_uppercase && next_is_lowercase) {
+        module_name.push('_');
+      }
+      module_name.push(c.to_ascii_lowercase());
+    } else if c.is_ascii_alphanumeric() {
+      module_name.push(*c);
+    } else {
+      module_name.push('_');
+    }
+  }
+
+  if module_name.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(true) {
+    module_name.insert(0, '_');
+  }
+  module_name
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{check_code_hash, code_hash, module_name, Build};
+  use crate::build::error::BuildError;
+  use crate::testing::MockTransport;
+  use std::fs;
+  use web3::types::Address;
+
+  fn setup_project() -> tempfile::TempDir {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let vibranium = crate::Vibranium::new(tmp_dir.path().to_path_buf());
+    vibranium.init_project().unwrap();
+    tmp_dir
+  }
+
+  #[test]
+  fn it_should_derive_module_names() {
+    assert_eq!(module_name("SimpleTestContract"), "simple_test_contract");
+    assert_eq!(module_name("ERC20Token"), "erc20_token");
+    assert_eq!(module_name("token"), "token");
+    assert_eq!(module_name("1nch"), "_1nch");
+  }
+
+  #[test]
+  fn it_should_watch_config_and_sources() {
+    let tmp_dir = setup_project();
+    fs::write(tmp_dir.path().join("contracts").join("A.sol"), "contract A {}").unwrap();
+
+    let files = Build::new(tmp_dir.path()).watched_files().unwrap();
+    assert_eq!(files, vec![tmp_dir.path().join("vibranium.toml"), tmp_dir.path().join("contracts").join("A.sol")]);
+  }
+
+  #[test]
+  fn it_should_generate_bindings_for_compiled_smart_contracts() {
+    let tmp_dir = setup_project();
+    let artifacts_dir = tmp_dir.path().join("artifacts");
+    fs::write(artifacts_dir.join("SimpleToken.abi"), "[]").unwrap();
+    fs::write(artifacts_dir.join("SimpleToken.bin"), "6080").unwrap();
+    fs::write(artifacts_dir.join("SimpleToken.bin-runtime"), "6001").unwrap();
+    fs::write(artifacts_dir.join("Library.abi"), "[]").unwrap();
+
+    let bindings = Build::new(tmp_dir.path()).generate_bindings().unwrap();
+    let artifacts_dir = fs::canonicalize(artifacts_dir).unwrap();
+
+    assert!(bindings.contains("pub mod simple_token {\n  pub const NAME: &str = \"SimpleToken\";\n"));
+    assert!(bindings.contains(&format!("  pub const BYTECODE: &str = include_str!({:?});\n", artifacts_dir.join("SimpleToken.bin").to_string_lossy())));
+    assert!(bindings.contains("pub mod library {"));
+    assert_eq!(bindings.matches("BYTECODE").count(), 1);
+    assert!(bindings.contains(&format!("  pub const CODE_HASH: &str = {:?};\n", code_hash(&[0x60, 0x01]))));
+    assert_eq!(bindings.matches("CODE_HASH").count(), 1);
+    assert_eq!(bindings.matches("pub const GIT_COMMIT: Option<&str> = None;").count(), 2);
+  }
+
+  #[test]
+  fn it_should_only_generate_bindings_for_tagged_smart_contracts() {
+    let tmp_dir = setup_project();
+    let artifacts_dir = tmp_dir.path().join("artifacts");
+    fs::write(artifacts_dir.join("Token.abi"), "[]").unwrap();
+    fs::write(artifacts_dir.join("MockToken.abi"), "[]").unwrap();
+    let mut config = fs::read_to_string(tmp_dir.path().join("vibranium.toml")).unwrap();
+    config.push_str("\n[deployment]\nsmart_contracts = [{ name = \"Token\", tags = [\"core\"] }, { name = \"MockToken\", tags = [\"mocks\"] }]\n");
+    fs::write(tmp_dir.path().join("vibranium.toml"), config).unwrap();
+
+    let bindings = Build::new(tmp_dir.path()).tags(vec!["core".to_string()]).generate_bindings().unwrap();
+    assert!(bindings.contains("pub mod token {"));
+    assert!(!bindings.contains("pub mod mock_token {"));
+
+    match Build::new(tmp_dir.path()).tags(vec!["unknown".to_string()]).generate_bindings() {
+      Err(BuildError::Deployment(error)) => assert_eq!(error.to_string(), "Couldn't find Smart Contracts tagged with any of: unknown"),
+      other => panic!("Expected missing tagged Smart Contracts, got {:?}", other.map(|_bindings| ())),
+    }
+  }
+
+  #[test]
+  fn it_should_check_code_hashes_of_deployed_code() {
+    let mock = MockTransport::new();
+    mock.add_response("eth_getCode", serde_json::json!("0x6001"));
+    mock.add_response("eth_getCode", serde_json::json!("0x6002"));
+    mock.add_response("eth_getCode", serde_json::json!("0x"));
+    let connector = mock.connector();
+    let address = Address::from_low_u64_be(1);
+    let expected_hash = code_hash(&[0x60, 0x01]);
+
+    assert!(check_code_hash(&connector, address, &expected_hash).is_ok());
+    match check_code_hash(&connector, address, &expected_hash) {
+      Err(BuildError::CodeHashMismatch(_address, expected, actual)) => assert_eq!((expected, actual), (expected_hash.clone(), code_hash(&[0x60, 0x02]))),
+      other => panic!("Expected code hash mismatch, got {:?}", other),
+    }
+    match check_code_hash(&connector, address, &expected_hash) {
+      Err(BuildError::NoCode(no_code_address)) => assert_eq!(no_code_address, address),
+      other => panic!("Expected missing code, got {:?}", other),
+    }
+  }
+}
@@ -0,0 +1,263 @@
+use sha3::{Digest, Sha3_256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+pub const BUILD_CACHE_FILE: &str = "build-cache.toml";
+const NODE_MODULES_DIRECTORY: &str = "node_modules";
+
+// Hashes of the sources and compiler settings the artifacts were last
+// compiled from, keyed by source paths relative to the project.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct BuildCache {
+  pub settings: String,
+  pub sources: BTreeMap<String, String>,
+  // Files the sources import (transitively) that aren't sources themselves,
+  // e.g. libraries installed to node_modules.
+  #[serde(default)]
+  pub imports: BTreeMap<String, String>,
+}
+
+impl BuildCache {
+  pub fn new(project_path: &Path, compiler: &str, options: &[String], sources: &[PathBuf]) -> Result<BuildCache, io::Error> {
+    let mut hashes = BTreeMap::new();
+    for source in sources {
+      hashes.insert(relative_path(project_path, source), hash(&fs::read(source)?));
+    }
+
+    let remappings = remappings(options);
+    let mut imports = BTreeMap::new();
+    let mut unvisited = sources.to_vec();
+    while let Some(file) = unvisited.pop() {
+      for import in imported_files(project_path, &file, &remappings)? {
+        let imported = relative_path(project_path, &import);
+        if !hashes.contains_key(&imported) && !imports.contains_key(&imported) {
+          imports.insert(imported, hash(&fs::read(&import)?));
+          unvisited.push(import);
+        }
+      }
+    }
+
+    Ok(BuildCache {
+      settings: hash(format!("{} {}", compiler, options.join(" ")).as_bytes()),
+      sources: hashes,
+      imports,
+    })
+  }
+
+  // A missing or unreadable cache means everything gets compiled.
+  pub fn read(path: &Path) -> BuildCache {
+    fs::read_to_string(path).ok()
+      .and_then(|content| toml::from_str(&content).map_err(|err| warn!("Ignoring invalid build cache: {}", err)).ok())
+      .unwrap_or_default()
+  }
+
+  pub fn write(&self, path: &Path) -> Result<(), io::Error> {
+    let content = toml::to_string(self).map_err(io::Error::other)?;
+    fs::write(path, content)
+  }
+
+  // Sources that changed since `previous` was written, along with the sources
+  // importing them, directly or through imported files that changed.
+  // Changed compiler settings invalidate every source.
+  pub fn stale_sources(&self, project_path: &Path, previous: &BuildCache, options: &[String]) -> Result<Vec<PathBuf>, io::Error> {
+    if self.settings != previous.settings {
+      return Ok(self.sources.keys().map(|source| project_path.join(source)).collect());
+    }
+
+    let changed = |current: &BTreeMap<String, String>, previous: &BTreeMap<String, String>| -> Vec<String> {
+      current.iter()
+        .filter(|(file, hash)| previous.get(*file) != Some(hash))
+        .map(|(file, _hash)| file.to_owned())
+        .collect()
+    };
+    let mut stale: BTreeSet<String> = changed(&self.sources, &previous.sources).into_iter()
+      .chain(changed(&self.imports, &previous.imports))
+      .collect();
+
+    let remappings = remappings(options);
+    let mut imports = BTreeMap::new();
+    for file in self.sources.keys().chain(self.imports.keys()) {
+      let imported: Vec<String> = imported_files(project_path, &project_path.join(file), &remappings)?
+        .iter()
+        .map(|import| relative_path(project_path, import))
+        .collect();
+      imports.insert(file.to_owned(), imported);
+    }
+
+    loop {
+      let dependents: Vec<String> = imports.iter()
+        .filter(|(file, imported)| !stale.contains(*file) && imported.iter().any(|import| stale.contains(import)))
+        .map(|(file, _imported)| file.to_owned())
+        .collect();
+      if dependents.is_empty() {
+        break;
+      }
+      stale.extend(dependents);
+    }
+
+    Ok(stale.into_iter().filter(|file| self.sources.contains_key(file)).map(|source| project_path.join(source)).collect())
+  }
+}
+
+fn hash(content: &[u8]) -> String {
+  format!("0x{:x}", Sha3_256::digest(content))
+}
+
+fn relative_path(project_path: &Path, path: &Path) -> String {
+  path.strip_prefix(project_path).unwrap_or(path).to_string_lossy().to_string()
+}
+
+// Remappings are passed to solc as `[context:]prefix=target`, the context is
+// ignored.
+fn remappings(options: &[String]) -> Vec<(String, String)> {
+  options.iter()
+    .filter(|option| !option.starts_with('-'))
+    .filter_map(|option| option.split_once('='))
+    .map(|(prefix, target)| (prefix.rsplit(':').next().unwrap_or(prefix).to_owned(), target.to_owned()))
+    .collect()
+}
+
+// Imported files which exist, imports that can't be resolved are left to the
+// compiler to report.
+fn imported_files(project_path: &Path, file: &Path, remappings: &[(String, String)]) -> Result<Vec<PathBuf>, io::Error> {
+  Ok(parse_imports(&fs::read_to_string(file)?)
+    .iter()
+    .map(|import| resolve_import(project_path, file, import, remappings))
+    .filter(|import| import.is_file())
+    .collect())
+}
+
+// Relative imports are resolved against the importing file, others against
+// the project, which is where the compiler is run from, after applying the
+// longest matching remapping. Imports that aren't part of the project are
+// looked up in node_modules.
+fn resolve_import(project_path: &Path, source: &Path, import: &str, remappings: &[(String, String)]) -> PathBuf {
+  if import.starts_with("./") || import.starts_with("../") {
+    return normalize_path(&source.parent().unwrap_or(project_path).join(import));
+  }
+
+  let remapped = remappings.iter()
+    .filter(|(prefix, _target)| import.starts_with(prefix.as_str()))
+    .max_by_key(|(prefix, _target)| prefix.len())
+    .map(|(prefix, target)| format!("{}{}", target, &import[prefix.len()..]));
+  let resolved = normalize_path(&project_path.join(remapped.as_deref().unwrap_or(import)));
+  if remapped.is_none() && !resolved.exists() {
+    return normalize_path(&project_path.join(NODE_MODULES_DIRECTORY).join(import));
+  }
+  resolved
+}
+
+fn normalize_path(path: &Path) -> PathBuf {
+  let mut normalized = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {},
+      Component::ParentDir => { normalized.pop(); },
+      component => normalized.push(component),
+    }
+  }
+  normalized
+}
+
+// Covers `import "A.sol";`, `import "A.sol" as A;`, `import * as A from "A.sol";`
+// and `import {A} from "A.sol";`.
+fn parse_imports(source: &str) -> Vec<String> {
+  source.split(';')
+    .filter_map(|statement| {
+      let statement = statement.trim_start();
+      if !statement.starts_with("import") {
+        return None;
+      }
+      let statement = statement.rsplit(" from ").next().unwrap_or(statement);
+      let start = statement.find(['"', '\''])?;
+      let quote = statement[start..].chars().next()?;
+      let end = statement[start + 1..].find(quote)?;
+      Some(statement[start + 1..start + 1 + end].to_string())
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{parse_imports, BuildCache};
+  use std::fs;
+  use std::path::{Path, PathBuf};
+
+  fn sources(project_path: &Path, files: &[(&str, &str)]) -> Vec<PathBuf> {
+    files.iter().map(|(name, content)| {
+      let path = project_path.join(name);
+      fs::create_dir_all(path.parent().unwrap()).unwrap();
+      fs::write(&path, content).unwrap();
+      path
+    }).collect()
+  }
+
+  #[test]
+  fn it_should_parse_imports() {
+    let source = "pragma solidity ^0.5.0;\nimport \"./A.sol\";\nimport {B, C} from '../lib/B.sol';\nimport * as D from \"D.sol\";\ncontract E {}";
+    assert_eq!(parse_imports(source), vec!["./A.sol", "../lib/B.sol", "D.sol"]);
+  }
+
+  #[test]
+  fn it_should_mark_changed_sources_and_their_dependents_stale() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let project_path = tmp_dir.path().to_path_buf();
+    let files = sources(&project_path, &[
+      ("contracts/A.sol", "contract A {}"),
+      ("contracts/B.sol", "import \"./A.sol\"; contract B is A {}"),
+      ("contracts/C.sol", "import \"contracts/B.sol\"; contract C is B {}"),
+      ("contracts/D.sol", "contract D {}"),
+    ]);
+    let options = vec!["--abi".to_string()];
+    let previous = BuildCache::new(&project_path, "solc", &options, &files).unwrap();
+
+    let unchanged = BuildCache::new(&project_path, "solc", &options, &files).unwrap();
+    assert!(unchanged.stale_sources(&project_path, &previous, &options).unwrap().is_empty());
+
+    fs::write(&files[0], "contract A { uint a; }").unwrap();
+    let changed = BuildCache::new(&project_path, "solc", &options, &files).unwrap();
+    assert_eq!(changed.stale_sources(&project_path, &previous, &options).unwrap(), files[..3].to_vec());
+
+    let reconfigured = BuildCache::new(&project_path, "solc", &["--bin".to_string()], &files).unwrap();
+    assert_eq!(reconfigured.stale_sources(&project_path, &changed, &options).unwrap(), files);
+  }
+
+  #[test]
+  fn it_should_mark_sources_stale_when_imported_libraries_change() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let project_path = tmp_dir.path().to_path_buf();
+    let files = sources(&project_path, &[
+      ("contracts/A.sol", "import \"math/Math.sol\"; contract A {}"),
+      ("contracts/B.sol", "import \"@vendor/Util.sol\"; contract B {}"),
+    ]);
+    let libraries = sources(&project_path, &[
+      ("node_modules/math/Math.sol", "import \"./Helper.sol\"; library Math {}"),
+      ("node_modules/math/Helper.sol", "library Helper {}"),
+      ("lib/vendor/Util.sol", "library Util {}"),
+    ]);
+    let options = vec!["--abi".to_string(), "@vendor/=lib/vendor/".to_string()];
+    let previous = BuildCache::new(&project_path, "solc", &options, &files).unwrap();
+    assert_eq!(previous.imports.keys().collect::<Vec<&String>>(), vec!["lib/vendor/Util.sol", "node_modules/math/Helper.sol", "node_modules/math/Math.sol"]);
+
+    fs::write(&libraries[1], "library Helper { }").unwrap();
+    let changed = BuildCache::new(&project_path, "solc", &options, &files).unwrap();
+    assert_eq!(changed.stale_sources(&project_path, &previous, &options).unwrap(), vec![files[0].clone()]);
+
+    fs::write(&libraries[2], "library Util { }").unwrap();
+    let remapped = BuildCache::new(&project_path, "solc", &options, &files).unwrap();
+    assert_eq!(remapped.stale_sources(&project_path, &changed, &options).unwrap(), vec![files[1].clone()]);
+  }
+
+  #[test]
+  fn it_should_fall_back_to_an_empty_cache() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = tmp_dir.path().join("build-cache.toml");
+    assert_eq!(BuildCache::read(&path), BuildCache::default());
+
+    fs::write(&path, "not a build cache").unwrap();
+    assert_eq!(BuildCache::read(&path), BuildCache::default());
+  }
+}
@@ -0,0 +1,314 @@
+use super::error::CompilerError;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub const RULE_SELFDESTRUCT: &str = "selfdestruct";
+pub const RULE_TX_ORIGIN: &str = "tx-origin";
+pub const RULE_DELEGATECALL: &str = "delegatecall";
+pub const RULE_UNCHECKED_CALL: &str = "unchecked-call";
+
+pub const RULES: &[&str] = &[RULE_SELFDESTRUCT, RULE_TX_ORIGIN, RULE_DELEGATECALL, RULE_UNCHECKED_CALL];
+
+const LOW_LEVEL_CALLS: &[&str] = &[".call(", ".call{", ".call.value(", ".send(", ".delegatecall("];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+  Error,
+  Warn,
+  Off,
+}
+
+impl FromStr for Severity {
+  type Err = CompilerError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "error" => Ok(Severity::Error),
+      "warn" => Ok(Severity::Warn),
+      "off" => Ok(Severity::Off),
+      _ => Err(CompilerError::UnsupportedCheckSeverity(s.to_string())),
+    }
+  }
+}
+
+impl fmt::Display for Severity {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Severity::Error => write!(f, "error"),
+      Severity::Warn => write!(f, "warning"),
+      Severity::Off => write!(f, "off"),
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct Diagnostic {
+  pub rule: &'static str,
+  pub severity: Severity,
+  pub file: PathBuf,
+  pub line: usize,
+  pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}:{}: {}[{}]: {}", self.file.to_string_lossy(), self.line, self.severity, self.rule, self.message)
+  }
+}
+
+// All rules default to warnings. Severities can be changed per rule with
+// `[compiler.checks]` in vibranium.toml, e.g. `selfdestruct = "error"`.
+pub fn severities(config: &Option<BTreeMap<String, String>>) -> Result<BTreeMap<&'static str, Severity>, CompilerError> {
+  let mut severities: BTreeMap<&'static str, Severity> = RULES.iter().map(|rule| (*rule, Severity::Warn)).collect();
+
+  if let Some(config) = config {
+    for (rule, severity) in config {
+      match RULES.iter().find(|known| *known == rule) {
+        Some(known) => { severities.insert(known, severity.parse()?); },
+        None => return Err(CompilerError::UnknownStaticCheck(rule.to_string())),
+      }
+    }
+  }
+
+  Ok(severities)
+}
+
+pub fn check(file: PathBuf, source: &str, severities: &BTreeMap<&'static str, Severity>) -> Vec<Diagnostic> {
+  let code = strip_comments_and_strings(source);
+  let mut findings = vec![];
+
+  find_selfdestruct(&code, &mut findings);
+  find_tx_origin(&code, &mut findings);
+  find_delegatecall(&code, &mut findings);
+  find_unchecked_calls(&code, &mut findings);
+
+  findings.sort_by_key(|(offset, _rule, _message)| *offset);
+  findings.into_iter()
+    .filter_map(|(offset, rule, message)| {
+      let severity = severities.get(rule).cloned().unwrap_or(Severity::Warn);
+      if severity == Severity::Off {
+        return None;
+      }
+      Some(Diagnostic {
+        rule,
+        severity,
+        file: file.clone(),
+        line: code[..offset].matches('\n').count() + 1,
+        message,
+      })
+    })
+    .collect()
+}
+
+type Finding = (usize, &'static str, String);
+
+fn find_selfdestruct(code: &str, findings: &mut Vec<Finding>) {
+  for keyword in &["selfdestruct", "suicide"] {
+    for (offset, _) in code.match_indices(keyword) {
+      if is_word_at(code, offset, keyword) && code[offset + keyword.len()..].trim_start().starts_with('(') {
+        findings.push((offset, RULE_SELFDESTRUCT, format!("Use of {} can remove the Smart Contract's code and break integrations", keyword)));
+      }
+    }
+  }
+}
+
+fn find_tx_origin(code: &str, findings: &mut Vec<Finding>) {
+  for (offset, _) in code.match_indices("tx.origin") {
+    let statement = statement_around(code, offset);
+    if statement.contains("==") || statement.contains("!=") {
+      findings.push((offset, RULE_TX_ORIGIN, "tx.origin used for authorization, use msg.sender instead".to_string()));
+    }
+  }
+}
+
+// Only delegatecalls to addresses that are passed in as function parameters
+// are flagged, as these are the ones a caller can control.
+fn find_delegatecall(code: &str, findings: &mut Vec<Finding>) {
+  for (offset, _) in code.match_indices(".delegatecall") {
+    let target = call_target(&code[..offset]);
+    let parameters = enclosing_function_parameters(&code[..offset]);
+    if let Some(target) = target {
+      if parameters.iter().any(|parameter| parameter == &target) {
+        findings.push((offset, RULE_DELEGATECALL, format!("delegatecall to user-supplied address '{}'", target)));
+      }
+    }
+  }
+}
+
+fn find_unchecked_calls(code: &str, findings: &mut Vec<Finding>) {
+  for call in LOW_LEVEL_CALLS {
+    for (offset, _) in code.match_indices(call) {
+      let start = code[..offset].rfind([';', '{', '}']).map(|i| i + 1).unwrap_or(0);
+      let prefix = code[start..offset].trim();
+      let is_checked = prefix.contains('=')
+        || prefix.contains('(')
+        || prefix.contains('!')
+        || prefix.starts_with("return")
+        || prefix.starts_with("if");
+
+      if !is_checked {
+        findings.push((offset, RULE_UNCHECKED_CALL, format!("Return value of low-level {} is not checked", call.trim_start_matches('.').trim_end_matches(['(', '{']))));
+      }
+    }
+  }
+}
+
+pub(crate) fn is_identifier_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || c == '_'
+}
+
+pub(crate) fn is_word_at(code: &str, offset: usize, word: &str) -> bool {
+  let before = code[..offset].chars().next_back();
+  let after = code[offset + word.len()..].chars().next();
+  !before.map(is_identifier_char).unwrap_or(false) && !after.map(is_identifier_char).unwrap_or(false)
+}
+
+fn statement_around(code: &str, offset: usize) -> &str {
+  let start = code[..offset].rfind([';', '{', '}']).map(|i| i + 1).unwrap_or(0);
+  let end = code[offset..].find([';', '{']).map(|i| offset + i).unwrap_or_else(|| code.len());
+  &code[start..end]
+}
+
+// Resolves `target.delegatecall` as well as `address(target).delegatecall`.
+fn call_target(before: &str) -> Option<String> {
+  let before = before.trim_end();
+  let before = if before.ends_with(')') {
+    let open = before.rfind('(')?;
+    &before[open + 1..before.len() - 1]
+  } else {
+    before
+  };
+  let target: String = before.trim().chars().rev().take_while(|c| is_identifier_char(*c)).collect::<Vec<char>>().into_iter().rev().collect();
+  if target.is_empty() { None } else { Some(target) }
+}
+
+fn enclosing_function_parameters(before: &str) -> Vec<String> {
+  let start = match before.rfind("function") {
+    Some(start) => start,
+    None => return vec![],
+  };
+  let signature = &before[start..];
+  let open = match signature.find('(') {
+    Some(open) => open,
+    None => return vec![],
+  };
+  let close = signature[open..].find(')').map(|close| open + close).unwrap_or_else(|| signature.len());
+
+  signature[open + 1..close]
+    .split(',')
+    .filter_map(|parameter| parameter.split_whitespace().last())
+    .map(str::to_string)
+    .collect()
+}
+
+// Comments and string literals are replaced by spaces, keeping newlines, so
+// that offsets and line numbers still match the original source.
+pub(crate) fn strip_comments_and_strings(source: &str) -> String {
+  let chars: Vec<char> = source.chars().collect();
+  let mut code = String::with_capacity(source.len());
+  let mut i = 0;
+
+  while i < chars.len() {
+    let c = chars[i];
+    let next = chars.get(i + 1).cloned();
+
+    if c == '/' && next == Some('/') {
+      while i < chars.len() && chars[i] != '\n' {
+        code.push(' ');
+        i += 1;
+      }
+    } else if c == '/' && next == Some('*') {
+      code.push_str("  ");
+      i += 2;
+      while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+        code.push(if chars[i] == '\n' { '\n' } else { ' ' });
+        i += 1;
+      }
+      code.push_str("  ");
+      i += 2;
+    } else if c == '"' || c == '\'' {
+      code.push(c);
+      i += 1;
+      while i < chars.len() && chars[i] != c {
+        if chars[i] == '\\' {
+          code.push(' ');
+          i += 1;
+        }
+        if i < chars.len() {
+          code.push(if chars[i] == '\n' { '\n' } else { ' ' });
+          i += 1;
+        }
+      }
+      if i < chars.len() {
+        code.push(c);
+        i += 1;
+      }
+    } else {
+      code.push(c);
+      i += 1;
+    }
+  }
+
+  code
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{check, severities, Severity, RULE_SELFDESTRUCT, RULE_TX_ORIGIN, RULE_DELEGATECALL, RULE_UNCHECKED_CALL};
+  use std::collections::BTreeMap;
+  use std::path::PathBuf;
+
+  fn rules_found(source: &str) -> Vec<(&'static str, usize)> {
+    let severities = severities(&None).unwrap();
+    check(PathBuf::from("A.sol"), source, &severities).into_iter().map(|diagnostic| (diagnostic.rule, diagnostic.line)).collect()
+  }
+
+  #[test]
+  fn it_should_flag_selfdestruct() {
+    assert_eq!(rules_found("contract A {\n  function kill() public {\n    selfdestruct(msg.sender);\n  }\n}"), vec![(RULE_SELFDESTRUCT, 3)]);
+  }
+
+  #[test]
+  fn it_should_ignore_comments_and_strings() {
+    assert!(rules_found("contract A {\n  // selfdestruct(msg.sender);\n  string s = \"tx.origin == owner\";\n  /* addr.call(data); */\n}").is_empty());
+  }
+
+  #[test]
+  fn it_should_flag_tx_origin_authorization() {
+    assert_eq!(rules_found("contract A {\n  function f() public {\n    require(tx.origin == owner);\n    emit Origin(tx.origin);\n  }\n}"), vec![(RULE_TX_ORIGIN, 3)]);
+  }
+
+  #[test]
+  fn it_should_flag_delegatecall_to_user_supplied_address() {
+    let source = "contract A {\n  function f(address target, bytes memory data) public {\n    (bool ok, ) = target.delegatecall(data);\n    require(ok);\n  }\n  function g(bytes memory data) public {\n    (bool ok, ) = implementation.delegatecall(data);\n    require(ok);\n  }\n}";
+    assert_eq!(rules_found(source), vec![(RULE_DELEGATECALL, 3)]);
+  }
+
+  #[test]
+  fn it_should_flag_unchecked_low_level_calls() {
+    let source = "contract A {\n  function f(address payable a) public {\n    a.call(\"\");\n    (bool ok, ) = a.call(\"\");\n    require(a.send(1));\n    a.send(1);\n  }\n}";
+    assert_eq!(rules_found(source), vec![(RULE_UNCHECKED_CALL, 3), (RULE_UNCHECKED_CALL, 6)]);
+  }
+
+  #[test]
+  fn it_should_apply_configured_severities() {
+    let mut config = BTreeMap::new();
+    config.insert("selfdestruct".to_string(), "error".to_string());
+    config.insert("unchecked-call".to_string(), "off".to_string());
+    let severities = severities(&Some(config)).unwrap();
+
+    let diagnostics = check(PathBuf::from("A.sol"), "contract A {\n  function f(address a) public {\n    a.call(\"\");\n    selfdestruct(a);\n  }\n}", &severities);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+  }
+
+  #[test]
+  fn it_should_fail_on_unknown_rules() {
+    let mut config = BTreeMap::new();
+    config.insert("unknown".to_string(), "error".to_string());
+    assert!(severities(&Some(config)).is_err());
+  }
+}
@@ -0,0 +1,190 @@
+// Compiling a project has to produce the same artifacts every time, otherwise
+// deployed code can't be verified against its sources. Artifacts of two builds
+// are compared, where the second build may run from a copy of the project at
+// a temporary path, which reveals absolute paths ending up in the metadata.
+
+use crate::project_generator::VIBRANIUM_PROJECT_DIRECTORY;
+use crate::utils;
+use super::error::CompilerError;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Neither needed to compile a project, nor cheap to copy.
+const COPY_EXCLUDES: &[&str] = &[VIBRANIUM_PROJECT_DIRECTORY, ".git", "node_modules"];
+const ARTIFACT_EXTENSION_BINARY: &str = "bin";
+
+// Artifacts by their path relative to the artifacts directory.
+pub type ArtifactsSnapshot = BTreeMap<String, Vec<u8>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DifferenceCause {
+  AbsolutePaths,
+  Metadata,
+  MissingArtifact,
+  Other,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtifactDifference {
+  pub artifact: String,
+  pub cause: DifferenceCause,
+}
+
+impl fmt::Display for ArtifactDifference {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.cause {
+      DifferenceCause::AbsolutePaths => write!(f, "{} contains absolute paths of the project. Compile with paths relative to the project, e.g. pass `--base-path .` to solc, and avoid absolute remappings.", self.artifact),
+      DifferenceCause::Metadata => write!(f, "{} only differs in the metadata appended to its bytecode. Pin the compiler version and settings, or pass `--metadata-hash none` to solc.", self.artifact),
+      DifferenceCause::MissingArtifact => write!(f, "{} has only been produced by one of the builds.", self.artifact),
+      DifferenceCause::Other => write!(f, "{} differs between builds. Make sure every build uses the same compiler version and settings.", self.artifact),
+    }
+  }
+}
+
+pub fn snapshot(artifacts_path: &Path) -> Result<ArtifactsSnapshot, CompilerError> {
+  let mut artifacts = ArtifactsSnapshot::new();
+  if artifacts_path.exists() {
+    read_artifacts(artifacts_path, artifacts_path, &mut artifacts).map_err(CompilerError::Io)?;
+  }
+  Ok(artifacts)
+}
+
+// Compares artifacts of two builds of the project, made at the given project
+// paths.
+pub fn compare(first: &ArtifactsSnapshot, first_project_path: &Path, second: &ArtifactsSnapshot, second_project_path: &Path) -> Vec<ArtifactDifference> {
+  let mut names: Vec<&String> = first.keys().chain(second.keys()).collect();
+  names.sort();
+  names.dedup();
+
+  names.into_iter().filter_map(|name| {
+    let cause = match (first.get(name), second.get(name)) {
+      (Some(first), Some(second)) if first == second => return None,
+      (Some(first), Some(second)) => {
+        if contains(first, first_project_path) || contains(second, second_project_path) {
+          DifferenceCause::AbsolutePaths
+        } else if name.ends_with(&format!(".{}", ARTIFACT_EXTENSION_BINARY)) && without_metadata(first) == without_metadata(second) {
+          DifferenceCause::Metadata
+        } else {
+          DifferenceCause::Other
+        }
+      },
+      _ => DifferenceCause::MissingArtifact,
+    };
+    Some(ArtifactDifference { artifact: name.to_owned(), cause })
+  }).collect()
+}
+
+// Copies everything needed to compile the project, except for its artifacts.
+pub fn copy_project(from: &Path, to: &Path, artifacts: &str) -> io::Result<()> {
+  let mut excludes: Vec<PathBuf> = COPY_EXCLUDES.iter().map(|exclude| from.join(exclude)).collect();
+  excludes.push(from.join(artifacts));
+  copy_dir(from, to, &excludes)?;
+  fs::create_dir_all(to.join(VIBRANIUM_PROJECT_DIRECTORY))
+}
+
+fn copy_dir(from: &Path, to: &Path, excludes: &[PathBuf]) -> io::Result<()> {
+  fs::create_dir_all(to)?;
+  for entry in fs::read_dir(from)? {
+    let path = entry?.path();
+    let target = to.join(path.file_name().unwrap());
+    if excludes.contains(&path) {
+      continue;
+    }
+    if path.is_dir() {
+      copy_dir(&path, &target, excludes)?;
+    } else {
+      fs::copy(&path, &target)?;
+    }
+  }
+  Ok(())
+}
+
+fn read_artifacts(root: &Path, dir: &Path, artifacts: &mut ArtifactsSnapshot) -> io::Result<()> {
+  for entry in fs::read_dir(dir)? {
+    let path = entry?.path();
+    if path.is_dir() {
+      read_artifacts(root, &path, artifacts)?;
+    } else {
+      let name = path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/");
+      artifacts.insert(name, fs::read(&path)?);
+    }
+  }
+  Ok(())
+}
+
+fn contains(artifact: &[u8], project_path: &Path) -> bool {
+  let project_path = project_path.to_string_lossy();
+  !project_path.is_empty() && String::from_utf8_lossy(artifact).contains(project_path.as_ref())
+}
+
+fn without_metadata(bytecode: &[u8]) -> Vec<u8> {
+  let mut code = utils::decode_bytecode(&String::from_utf8_lossy(bytecode));
+  utils::strip_metadata(&mut code);
+  code
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{compare, copy_project, snapshot, DifferenceCause};
+  use std::fs;
+  use std::path::Path;
+
+  // Bytecode with CBOR metadata `{"solc": 0x000818}` of 10 bytes.
+  const BYTECODE: &str = "6080a164736f6c6343000818000a";
+  const OTHER_METADATA: &str = "6080a164736f6c6343000819000a";
+
+  #[test]
+  fn it_should_tell_apart_causes_of_differences() {
+    let first_path = Path::new("/home/alice/project");
+    let second_path = Path::new("/tmp/copy");
+    let mut first = super::ArtifactsSnapshot::new();
+    let mut second = super::ArtifactsSnapshot::new();
+
+    first.insert("Same.bin".to_string(), BYTECODE.as_bytes().to_vec());
+    second.insert("Same.bin".to_string(), BYTECODE.as_bytes().to_vec());
+    first.insert("Token.bin".to_string(), BYTECODE.as_bytes().to_vec());
+    second.insert("Token.bin".to_string(), OTHER_METADATA.as_bytes().to_vec());
+    first.insert("Token.metadata.json".to_string(), b"{\"sources\":{\"/home/alice/project/Token.sol\":{}}}".to_vec());
+    second.insert("Token.metadata.json".to_string(), b"{\"sources\":{\"/tmp/copy/Token.sol\":{}}}".to_vec());
+    first.insert("Sale.bin".to_string(), b"6080".to_vec());
+    second.insert("Sale.bin".to_string(), b"6081".to_vec());
+    first.insert("Old.abi".to_string(), b"[]".to_vec());
+
+    let causes: Vec<(String, DifferenceCause)> = compare(&first, first_path, &second, second_path).into_iter()
+      .map(|difference| (difference.artifact, difference.cause))
+      .collect();
+    assert_eq!(causes, vec![
+      ("Old.abi".to_string(), DifferenceCause::MissingArtifact),
+      ("Sale.bin".to_string(), DifferenceCause::Other),
+      ("Token.bin".to_string(), DifferenceCause::Metadata),
+      ("Token.metadata.json".to_string(), DifferenceCause::AbsolutePaths),
+    ]);
+  }
+
+  #[test]
+  fn it_should_copy_projects_without_artifacts_and_tracking_data() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let (project, copy) = (tmp_dir.path().join("project"), tmp_dir.path().join("copy"));
+    fs::create_dir_all(project.join("contracts").join("lib")).unwrap();
+    fs::create_dir_all(project.join("artifacts")).unwrap();
+    fs::create_dir_all(project.join(".vibranium")).unwrap();
+    fs::write(project.join("vibranium.toml"), "").unwrap();
+    fs::write(project.join("contracts").join("lib").join("Math.sol"), "").unwrap();
+    fs::write(project.join("artifacts").join("Math.bin"), "").unwrap();
+    fs::write(project.join(".vibranium").join("tracking.toml"), "").unwrap();
+
+    copy_project(&project, &copy, "artifacts").unwrap();
+
+    assert!(copy.join("vibranium.toml").exists());
+    assert!(copy.join("contracts").join("lib").join("Math.sol").exists());
+    assert!(!copy.join("contracts").join(".vibranium").exists());
+    assert!(copy.join(".vibranium").exists());
+    assert!(!copy.join(".vibranium").join("tracking.toml").exists());
+    assert!(snapshot(&copy.join("artifacts")).unwrap().is_empty());
+  }
+}
@@ -3,6 +3,7 @@ use std::convert::From;
 use std::fmt;
 use std::io;
 
+use crate::artifacts;
 use crate::config;
 use crate::project_generator;
 
@@ -13,6 +14,18 @@ pub enum CompilerError {
   VibraniumDirectoryNotFound(project_generator::error::ProjectGenerationError),
   InvalidConfig(config::error::ConfigError),
   UnsupportedStrategy,
+  MissingReplacementOptions,
+  UnsupportedSizeEnforcement(String),
+  SizeBudgetExceeded(Vec<super::SizeBudgetViolation>),
+  UnknownStaticCheck(String),
+  UnsupportedCheckSeverity(String),
+  StaticChecksFailed(Vec<super::checks::Diagnostic>),
+  WarningsDenied(Vec<super::warnings::CompilerWarning>),
+  NotDeterministic(Vec<super::determinism::ArtifactDifference>),
+  Artifacts(artifacts::error::ArtifactsError),
+  InvalidVersion(String),
+  NoMatchingVersion(String),
+  SolcDownload(String),
   Other(String),
 }
 
@@ -24,6 +37,18 @@ impl Error for CompilerError {
       CompilerError::VibraniumDirectoryNotFound(error) => Some(error),
       CompilerError::InvalidConfig(error) => Some(error),
       CompilerError::UnsupportedStrategy => None,
+      CompilerError::MissingReplacementOptions => None,
+      CompilerError::UnsupportedSizeEnforcement(_enforcement) => None,
+      CompilerError::SizeBudgetExceeded(_violations) => None,
+      CompilerError::UnknownStaticCheck(_rule) => None,
+      CompilerError::UnsupportedCheckSeverity(_severity) => None,
+      CompilerError::StaticChecksFailed(_diagnostics) => None,
+      CompilerError::WarningsDenied(_warnings) => None,
+      CompilerError::NotDeterministic(_differences) => None,
+      CompilerError::Artifacts(error) => Some(error),
+      CompilerError::InvalidVersion(_version) => None,
+      CompilerError::NoMatchingVersion(_requirement) => None,
+      CompilerError::SolcDownload(_message) => None,
       CompilerError::Other(_message) => None,
     }
   }
@@ -36,12 +61,54 @@ impl fmt::Display for CompilerError {
       CompilerError::ExecutableNotFound(_error, exec) => write!(f, "Couldn't find executable for compiler {}", exec),
       CompilerError::VibraniumDirectoryNotFound(error) => write!(f, "{}", error.description()),
       CompilerError::InvalidConfig(error) => write!(f, "{}", error.description()),
-      CompilerError::UnsupportedStrategy => write!(f, "Couldn't compile project without `CompilerConfig::compiler_options`. No built-in support for requested compiler."),
+      CompilerError::UnsupportedStrategy => write!(f, "Couldn't compile project without `CompilerConfig::compiler_options`. No built-in support for requested compiler, only solc, solcjs and vyper are supported."),
+      CompilerError::MissingReplacementOptions => write!(f, "Couldn't replace compiler options. No compiler options have been passed."),
+      CompilerError::UnsupportedSizeEnforcement(enforcement) => write!(f, "Unsupported `compiler.max_size_enforcement` '{}', expected 'error' or 'warn'", enforcement),
+      CompilerError::SizeBudgetExceeded(violations) => {
+        writeln!(f, "Smart Contracts exceed their size budget:")?;
+        for violation in violations {
+          write!(f, "\n  {}", violation)?;
+        }
+        Ok(())
+      },
+      CompilerError::UnknownStaticCheck(rule) => write!(f, "Unknown static check '{}' in `compiler.checks`, expected one of: {}", rule, super::checks::RULES.join(", ")),
+      CompilerError::UnsupportedCheckSeverity(severity) => write!(f, "Unsupported static check severity '{}' in `compiler.checks`, expected 'error', 'warn' or 'off'", severity),
+      CompilerError::StaticChecksFailed(diagnostics) => {
+        writeln!(f, "Static checks failed:")?;
+        for diagnostic in diagnostics {
+          write!(f, "\n  {}", diagnostic)?;
+        }
+        Ok(())
+      },
+      CompilerError::WarningsDenied(warnings) => {
+        writeln!(f, "Compiler warnings are treated as errors (see `compiler.warnings`):")?;
+        for warning in warnings {
+          write!(f, "\n  {}", warning)?;
+        }
+        Ok(())
+      },
+      CompilerError::NotDeterministic(differences) => {
+        write!(f, "Compiler output isn't deterministic, artifacts differ between builds:")?;
+        for difference in differences {
+          write!(f, "\n  {}", difference)?;
+        }
+        Ok(())
+      },
+      CompilerError::Artifacts(error) => write!(f, "Couldn't normalize compiler artifacts: {}", error),
+      CompilerError::InvalidVersion(version) => write!(f, "Invalid solc version or version requirement '{}'", version),
+      CompilerError::NoMatchingVersion(requirement) => write!(f, "Couldn't find a solc release matching '{}'", requirement),
+      CompilerError::SolcDownload(message) => write!(f, "Couldn't download solc: {}", message),
       CompilerError::Other(message) => write!(f, "{}", &message),
     }
   }
 }
 
+impl From<artifacts::error::ArtifactsError> for CompilerError {
+  fn from(error: artifacts::error::ArtifactsError) -> Self {
+    CompilerError::Artifacts(error)
+  }
+}
+
 impl From<config::error::ConfigError> for CompilerError {
   fn from(error: config::error::ConfigError) -> Self {
     match error {
@@ -1,16 +1,74 @@
+pub mod cache;
+pub mod checks;
+pub mod determinism;
 pub mod error;
+pub mod sizes;
 pub mod support;
+pub mod versions;
+pub mod warnings;
+pub mod watcher;
 
-use std::process::{Child, Command, Stdio};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::str::FromStr;
+use std::process::Output;
 use crate::config;
+use crate::deployment::planner::DeploymentPlanner;
+use crate::process::ProcessCommand;
 use crate::utils;
 use support::SupportedCompilers;
 use glob::glob;
 
-#[derive(Debug)]
+const ARTIFACT_EXTENSION_ABI: &str = "abi";
+pub(crate) const ARTIFACT_EXTENSION_BINARY: &str = "bin";
+pub(crate) const ARTIFACT_EXTENSION_RUNTIME_BINARY: &str = "bin-runtime";
+const MAX_SIZE_CONTRIBUTORS: usize = 5;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CompilerConfig {
   pub compiler: Option<String>,
   pub compiler_options: Option<Vec<String>>,
+  pub replace_options: bool,
+  pub force: bool,
+  pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SizeEnforcement {
+  Error,
+  Warn,
+}
+
+impl FromStr for SizeEnforcement {
+  type Err = error::CompilerError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "error" => Ok(SizeEnforcement::Error),
+      "warn" => Ok(SizeEnforcement::Warn),
+      _ => Err(error::CompilerError::UnsupportedSizeEnforcement(s.to_string())),
+    }
+  }
+}
+
+// Contributors are only known if the compiler emitted a runtime source map.
+#[derive(Debug)]
+pub struct SizeBudgetViolation {
+  pub name: String,
+  pub size: usize,
+  pub max_size: usize,
+  pub top_contributors: Vec<sizes::SizeContribution>,
+}
+
+impl fmt::Display for SizeBudgetViolation {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}: {} bytes (max. {} bytes, {} bytes over budget)", self.name, self.size, self.max_size, self.size - self.max_size)?;
+    for contribution in &self.top_contributors {
+      write!(f, "\n    {}", contribution)?;
+    }
+    Ok(())
+  }
 }
 
 pub struct Compiler<'a> {
@@ -24,53 +82,116 @@ impl<'a> Compiler<'a> {
     }
   }
 
-  pub fn compile(&self, config: CompilerConfig) -> Result<Child, error::CompilerError> {
+  pub fn compile(&self, config: &CompilerConfig) -> Result<Output, error::CompilerError> {
     let project_config = self.config.read()?;
     let artifacts_dir = self.config.project_path.join(&project_config.sources.artifacts);
+    let (compiler, compiler_options) = self.resolve_command(config)?;
+    let sources = self.source_files(&project_config.sources.smart_contracts);
+    let solc_version = managed_version(&compiler, &project_config.compiler);
 
-    let compiler = config.compiler.unwrap_or_else(|| {
-      match &project_config.compiler {
-        Some(config) => config.cmd.clone().unwrap_or_else(|| SupportedCompilers::Solc.executable()),
-        None => SupportedCompilers::Solc.executable(),
-      }
-    });
-
-    let mut compiler_options = match &config.compiler_options {
-      Some(options) => {
-        match compiler.parse() {
-          Ok(SupportedCompilers::Solc) => utils::merge_cli_options(
-            support::default_options_from(SupportedCompilers::Solc),
-            options.to_vec()
-          ),
-          Ok(SupportedCompilers::SolcJs) => utils::merge_cli_options(
-            support::default_options_from(SupportedCompilers::SolcJs),
-            options.to_vec()
-          ),
-          Err(_err) => options.to_vec(),
-        }
+    // Compiling tagged Smart Contracts only bypasses the build cache, as it
+    // describes the build of all sources.
+    let sources = if let Some(tags) = &config.tags {
+      self.tagged_sources(tags, sources)?
+    } else if config.force || !artifacts_dir.exists() {
+      sources
+    } else {
+      let build_cache = cache::BuildCache::new(&self.config.project_path, &cache_key(&compiler, &solc_version), &compiler_options, &sources).map_err(error::CompilerError::Io)?;
+      let previous = cache::BuildCache::read(&self.config.vibranium_dir_path.join(cache::BUILD_CACHE_FILE));
+      if build_cache == previous {
+        info!("Skipping compilation, no sources changed since the last build");
+        return Ok(Output {
+          status: ExitStatus::default(),
+          stdout: b"Smart Contracts are up to date.\n".to_vec(),
+          stderr: vec![],
+        });
       }
-      None => {
-        match project_config.compiler {
-          Some(config) => config.options.unwrap_or_else(|| try_default_options_from(&compiler)),
-          None => try_default_options_from(&compiler)
-        }
+      let stale = build_cache.stale_sources(&self.config.project_path, &previous, &compiler_options).map_err(error::CompilerError::Io)?;
+      // Combined JSON output is written to a single file, which would lose
+      // the Smart Contracts of unchanged sources.
+      if stale.is_empty() || compiler_options.iter().any(|option| option == "--combined-json") {
+        sources
+      } else {
+        stale
       }
     };
 
-    if compiler_options.is_empty() {
-      if let Err(err) = compiler.parse::<SupportedCompilers>() {
-        Err(err)?
+    // Vyper sources of mixed projects are compiled with Vyper's built-in
+    // defaults, next to the sources of the configured compiler.
+    let is_vyper = compiler.parse::<SupportedCompilers>().ok() == Some(SupportedCompilers::Vyper);
+    let (vyper_sources, sources): (Vec<PathBuf>, Vec<PathBuf>) = if is_vyper {
+      (sources, vec![])
+    } else {
+      sources.into_iter().partition(|source| is_vyper_source(source))
+    };
+
+    if vyper_sources.is_empty() {
+      return self.run_solidity_compiler(&compiler, &solc_version, compiler_options, &artifacts_dir, sources);
+    }
+
+    let vyper_output = if is_vyper {
+      self.run_vyper(&compiler, &compiler_options, &artifacts_dir, &vyper_sources)?
+    } else {
+      self.run_vyper(&SupportedCompilers::Vyper.executable(), &support::default_options_from(SupportedCompilers::Vyper), &artifacts_dir, &vyper_sources)?
+    };
+    if sources.is_empty() || !vyper_output.status.success() {
+      return Ok(vyper_output);
+    }
+
+    let mut output = self.run_solidity_compiler(&compiler, &solc_version, compiler_options, &artifacts_dir, sources)?;
+    output.stderr = [vyper_output.stderr, output.stderr].concat();
+    Ok(output)
+  }
+
+  // Compiles Solidity sources other than the project's Smart Contracts, e.g.
+  // its tests, into `artifacts_dir`, bypassing the build cache. Vyper projects
+  // compile them with solc's built-in defaults.
+  pub fn compile_sources(&self, config: &CompilerConfig, sources: Vec<PathBuf>, artifacts_dir: &Path) -> Result<Output, error::CompilerError> {
+    let project_config = self.config.read()?;
+    let (compiler, compiler_options) = match self.resolve_command(config)? {
+      (compiler, _options) if compiler.parse::<SupportedCompilers>().ok() == Some(SupportedCompilers::Vyper) => {
+        (SupportedCompilers::Solc.executable(), support::default_options_from(SupportedCompilers::Solc))
+      },
+      command => command,
+    };
+    let solc_version = managed_version(&compiler, &project_config.compiler);
+    self.run_solidity_compiler(&compiler, &solc_version, compiler_options, artifacts_dir, sources)
+  }
+
+  // With `compiler.version` set, solc runs once per selected version, using
+  // the binaries of the solc cache, and stops at the first failing run.
+  fn run_solidity_compiler(&self, compiler: &str, solc_version: &Option<String>, compiler_options: Vec<String>, artifacts_dir: &Path, sources: Vec<PathBuf>) -> Result<Output, error::CompilerError> {
+    let solc_version = match solc_version {
+      Some(solc_version) => solc_version,
+      None => return self.run_compiler(compiler, compiler_options, artifacts_dir, sources),
+    };
+
+    let installer = versions::SolcInstaller::for_project(self.config);
+    let mut stdout = vec![];
+    let mut stderr = vec![];
+
+    for (version, sources) in installer.group_sources(solc_version, sources)? {
+      let executable = installer.install(version)?;
+      let output = self.run_compiler(&executable.to_string_lossy(), compiler_options.clone(), artifacts_dir, sources)?;
+      stdout.extend(output.stdout);
+      stderr.extend(output.stderr);
+      if !output.status.success() {
+        return Ok(Output { status: output.status, stdout, stderr });
       }
     }
 
+    Ok(Output {
+      status: ExitStatus::default(),
+      stdout,
+      stderr,
+    })
+  }
+
+  fn run_compiler(&self, compiler: &str, mut compiler_options: Vec<String>, artifacts_dir: &Path, sources: Vec<PathBuf>) -> Result<Output, error::CompilerError> {
     compiler_options.push(artifacts_dir.to_string_lossy().to_string());
 
-    for pattern in &project_config.sources.smart_contracts {
-      let mut full_pattern = self.config.project_path.clone();
-      full_pattern.push(&pattern);
-      for entry in glob(&full_pattern.to_str().unwrap()).unwrap().filter_map(Result::ok) {
-        compiler_options.push(entry.to_string_lossy().to_string());
-      }
+    for entry in sources {
+      compiler_options.push(entry.to_string_lossy().to_string());
     }
 
     compiler_options.insert(0, compiler.to_string());
@@ -83,12 +204,11 @@ impl<'a> Compiler<'a> {
 
     info!("Compiling project using command: {} {} {}", &shell, &shell_opt, compiler_options.join(" "));
 
-    Command::new(shell)
+    ProcessCommand::new(shell)
       .arg(shell_opt)
-      .arg(&compiler_options.join(" "))
-      .stdout(Stdio::piped())
-      .stderr(Stdio::piped())
-      .spawn()
+      .arg(compiler_options.join(" "))
+      .log_to(&self.config.vibranium_dir_path)
+      .output()
       .map_err(|err| {
         match err.kind() {
           std::io::ErrorKind::NotFound => error::CompilerError::ExecutableNotFound(err, shell.to_owned()),
@@ -96,12 +216,457 @@ impl<'a> Compiler<'a> {
         }
       })
   }
+
+  // Vyper prints its output instead of writing artifacts, so they're written
+  // here, named and encoded like the ones of solc. Stops at the first source
+  // that fails to compile.
+  fn run_vyper(&self, compiler: &str, compiler_options: &[String], artifacts_dir: &Path, sources: &[PathBuf]) -> Result<Output, error::CompilerError> {
+    fs::create_dir_all(artifacts_dir).map_err(error::CompilerError::Io)?;
+    let mut stderr = vec![];
+
+    for source in sources {
+      info!("Compiling {} using command: {} {}", source.to_string_lossy(), compiler, compiler_options.join(" "));
+      let output = ProcessCommand::new(compiler)
+        .args(compiler_options.to_vec())
+        .arg(source.to_string_lossy().to_string())
+        .log_to(&self.config.vibranium_dir_path)
+        .output()
+        .map_err(|err| {
+          match err.kind() {
+            std::io::ErrorKind::NotFound => error::CompilerError::ExecutableNotFound(err, compiler.to_owned()),
+            _ => error::CompilerError::Io(err)
+          }
+        })?;
+
+      stderr.extend(output.stderr);
+      if !output.status.success() {
+        return Ok(Output { status: output.status, stdout: output.stdout, stderr });
+      }
+      write_vyper_artifacts(artifacts_dir, source, compiler_options, &String::from_utf8_lossy(&output.stdout)).map_err(error::CompilerError::Io)?;
+    }
+
+    Ok(Output {
+      status: ExitStatus::default(),
+      stdout: vec![],
+      stderr,
+    })
+  }
+}
+
+impl<'a> Compiler<'a> {
+  // Options configured in vibranium.toml (or the compiler's built-in defaults)
+  // are the base. Options passed per invocation are merged into them, with
+  // values of options given in both taken from the invocation, unless
+  // `replace_options` is set, in which case they're used as they are.
+  // Configured options only apply to the configured compiler.
+  // The compiler executable along with the options it's run with, not
+  // including the artifacts directory and source files.
+  pub fn resolve_command(&self, config: &CompilerConfig) -> Result<(String, Vec<String>), error::CompilerError> {
+    let project_config = self.config.read()?;
+
+    let compiler = config.compiler.clone().unwrap_or_else(|| {
+      match &project_config.compiler {
+        Some(config) => config.cmd.clone().unwrap_or_else(|| SupportedCompilers::Solc.executable()),
+        None => SupportedCompilers::Solc.executable(),
+      }
+    });
+
+    let compiler_options = self.resolve_options(&compiler, &project_config.compiler, config)?;
+
+    if compiler_options.is_empty() {
+      if let Err(err) = compiler.parse::<SupportedCompilers>() {
+        Err(err)?
+      }
+    }
+    Ok((compiler, compiler_options))
+  }
+
+  pub fn resolve_options(&self, compiler: &str, project_config: &Option<config::ProjectCmdExecutionConfig>, config: &CompilerConfig) -> Result<Vec<String>, error::CompilerError> {
+    if config.replace_options && config.compiler_options.is_none() {
+      return Err(error::CompilerError::MissingReplacementOptions);
+    }
+
+    let configured_options = project_config.as_ref()
+      .filter(|project_config| project_config.cmd.as_ref().map(|cmd| cmd == compiler).unwrap_or(true))
+      .and_then(|project_config| project_config.options.clone());
+
+    let base_options = match configured_options {
+      Some(options) => {
+        info!("Using compiler options from project configuration: {}", options.join(" "));
+        options
+      },
+      None => try_default_options_from(compiler),
+    };
+
+    Ok(match &config.compiler_options {
+      Some(options) if config.replace_options => {
+        info!("Replacing compiler options with: {}", options.join(" "));
+        options.to_vec()
+      },
+      Some(options) => {
+        info!("Merging compiler options: {}", options.join(" "));
+        utils::merge_cli_options(base_options, options.to_vec())
+      },
+      None => base_options,
+    })
+  }
+
+  // Only written once a build passed, so that sources of a failed build are
+  // compiled again instead of being skipped on the next run.
+  pub fn update_build_cache(&self, config: &CompilerConfig) -> Result<(), error::CompilerError> {
+    if config.tags.is_some() {
+      return Ok(());
+    }
+    let project_config = self.config.read()?;
+    let (compiler, compiler_options) = self.resolve_command(config)?;
+    let sources = self.source_files(&project_config.sources.smart_contracts);
+    let solc_version = managed_version(&compiler, &project_config.compiler);
+    cache::BuildCache::new(&self.config.project_path, &cache_key(&compiler, &solc_version), &compiler_options, &sources)
+      .and_then(|build_cache| build_cache.write(&self.config.vibranium_dir_path.join(cache::BUILD_CACHE_FILE)))
+      .map_err(error::CompilerError::Io)
+  }
+
+  // Sources declaring Smart Contracts tagged with any of the given tags. Vyper
+  // sources declare the Smart Contract named after them.
+  fn tagged_sources(&self, tags: &[String], sources: Vec<PathBuf>) -> Result<Vec<PathBuf>, error::CompilerError> {
+    let names: Vec<String> = DeploymentPlanner::new(self.config).tagged(tags)
+      .map_err(|err| error::CompilerError::Other(err.to_string()))?
+      .into_iter()
+      .map(|smart_contract_config| smart_contract_config.instance_of.unwrap_or(smart_contract_config.name))
+      .collect();
+
+    let mut tagged_sources = vec![];
+    for source in sources {
+      let declared = if is_vyper_source(&source) {
+        source.file_stem().map(|stem| vec![stem.to_string_lossy().to_string()]).unwrap_or_default()
+      } else {
+        declared_smart_contracts(&fs::read_to_string(&source).map_err(error::CompilerError::Io)?)
+      };
+      if declared.iter().any(|name| names.contains(name)) {
+        tagged_sources.push(source);
+      }
+    }
+
+    if tagged_sources.is_empty() {
+      return Err(error::CompilerError::Other(format!("Couldn't find sources of Smart Contracts tagged with any of: {}", tags.join(", "))));
+    }
+    Ok(tagged_sources)
+  }
+
+  pub fn size_enforcement(&self) -> Result<SizeEnforcement, error::CompilerError> {
+    let project_config = self.config.read()?;
+    project_config.compiler
+      .and_then(|config| config.max_size_enforcement)
+      .map(|enforcement| enforcement.parse())
+      .unwrap_or(Ok(SizeEnforcement::Error))
+  }
+
+  // Silenced warnings are dropped from the compiler's output.
+  pub fn apply_warning_policy(&self, mut output: Output) -> Result<Output, error::CompilerError> {
+    let project_config = self.config.read()?;
+    let policy = warnings::WarningPolicy::from_config(&project_config.compiler.and_then(|config| config.warnings));
+    let (stderr, denied) = policy.apply(&String::from_utf8_lossy(&output.stderr));
+    if !denied.is_empty() {
+      return Err(error::CompilerError::WarningsDenied(denied));
+    }
+    output.stderr = stderr.into_bytes();
+    Ok(output)
+  }
+
+  // Sizes are measured on the runtime bytecode (`--bin-runtime`), which is what
+  // ends up on chain. If the compiler only emitted creation bytecode, that is
+  // measured instead, which overestimates the deployed size. The functions
+  // contributing most to a violation are listed if the compiler emitted
+  // `--combined-json` output with `srcmap-runtime`.
+  pub fn check_size_budgets(&self) -> Result<Vec<SizeBudgetViolation>, error::CompilerError> {
+    let project_config = self.config.read()?;
+    let budgets = match project_config.compiler.and_then(|config| config.max_size) {
+      Some(budgets) => budgets,
+      None => return Ok(vec![]),
+    };
+
+    let artifacts_dir = self.config.project_path.join(&project_config.sources.artifacts);
+    let mut violations = vec![];
+
+    for (name, max_size) in budgets {
+      let runtime_path = artifacts_dir.join(format!("{}.{}", &name, ARTIFACT_EXTENSION_RUNTIME_BINARY));
+      let path = if runtime_path.exists() {
+        runtime_path
+      } else {
+        artifacts_dir.join(format!("{}.{}", &name, ARTIFACT_EXTENSION_BINARY))
+      };
+
+      if !path.exists() {
+        warn!("Couldn't find artifact for size budget of Smart Contract '{}'", &name);
+        continue;
+      }
+
+      let size = bytecode_size(&fs::read_to_string(&path).map_err(error::CompilerError::Io)?);
+      info!("{} is {} bytes (max. {} bytes)", &name, size, max_size);
+
+      if size > max_size {
+        let mut top_contributors = sizes::contributions(&self.config.project_path, &artifacts_dir, &name).unwrap_or_default();
+        top_contributors.truncate(MAX_SIZE_CONTRIBUTORS);
+        violations.push(SizeBudgetViolation { name, size, max_size, top_contributors });
+      }
+    }
+
+    Ok(violations)
+  }
+}
+
+impl<'a> Compiler<'a> {
+  pub fn run_static_checks(&self) -> Result<Vec<checks::Diagnostic>, error::CompilerError> {
+    let project_config = self.config.read()?;
+    let severities = checks::severities(&project_config.compiler.and_then(|config| config.checks))?;
+    let mut diagnostics = vec![];
+
+    for path in self.source_files(&project_config.sources.smart_contracts) {
+      let source = fs::read_to_string(&path).map_err(error::CompilerError::Io)?;
+      let file = path.strip_prefix(&self.config.project_path).map(|file| file.to_path_buf()).unwrap_or_else(|_| path.clone());
+      diagnostics.append(&mut checks::check(file, &source, &severities));
+    }
+
+    Ok(diagnostics)
+  }
+
+  pub fn source_files(&self, patterns: &[String]) -> Vec<PathBuf> {
+    let mut files = vec![];
+    for pattern in patterns {
+      let mut full_pattern = self.config.project_path.clone();
+      full_pattern.push(&pattern);
+      for entry in glob(&full_pattern.to_str().unwrap()).unwrap().filter_map(Result::ok) {
+        files.push(entry);
+      }
+    }
+    files
+  }
+}
+
+pub(crate) fn bytecode_size(bytecode: &str) -> usize {
+  let bytecode = bytecode.trim().trim_matches('"');
+  let bytecode = bytecode.trim_start_matches("0x");
+  bytecode.len() / 2
+}
+
+// Names of the contracts, libraries and interfaces declared in a Solidity
+// source.
+pub(crate) fn declared_smart_contracts(source: &str) -> Vec<String> {
+  let code = checks::strip_comments_and_strings(source);
+  let mut names = vec![];
+  for keyword in &["contract", "library", "interface"] {
+    let mut offset = 0;
+    while let Some(index) = code[offset..].find(keyword) {
+      let start = offset + index;
+      offset = start + keyword.len();
+      if !checks::is_word_at(&code, start, keyword) {
+        continue;
+      }
+      let name: String = code[offset..].trim_start().chars().take_while(|c| checks::is_identifier_char(*c)).collect();
+      if !name.is_empty() {
+        names.push(name);
+      }
+    }
+  }
+  names
+}
+
+// The `compiler.version` solc is run with, only applies to solc itself.
+fn managed_version(compiler: &str, project_config: &Option<config::ProjectCmdExecutionConfig>) -> Option<String> {
+  if compiler.parse::<SupportedCompilers>().ok() != Some(SupportedCompilers::Solc) {
+    return None;
+  }
+  project_config.as_ref().and_then(|config| config.version.clone())
+}
+
+// Changing `compiler.version` invalidates the build cache.
+fn cache_key(compiler: &str, solc_version: &Option<String>) -> String {
+  match solc_version {
+    Some(version) => format!("{}@{}", compiler, version),
+    None => compiler.to_string(),
+  }
+}
+
+fn is_vyper_source(path: &Path) -> bool {
+  path.extension().is_some_and(|extension| extension == support::VYPER_SOURCE_EXTENSION)
+}
+
+// Each requested format (`-f`, only bytecode by default) is printed on a line
+// of its own, in the order they've been requested in. Formats without a solc
+// counterpart aren't written.
+fn write_vyper_artifacts(artifacts_dir: &Path, source: &Path, compiler_options: &[String], stdout: &str) -> Result<(), std::io::Error> {
+  let name = source.file_stem().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+  let formats = compiler_options.iter()
+    .position(|option| option == "-f")
+    .and_then(|index| compiler_options.get(index + 1))
+    .map(|formats| formats.split(',').map(|format| format.trim().to_string()).collect())
+    .unwrap_or_else(|| vec!["bytecode".to_string()]);
+
+  for (format, line) in formats.iter().zip(stdout.lines()) {
+    let (extension, content) = match format.as_str() {
+      "abi" => (ARTIFACT_EXTENSION_ABI, line.trim()),
+      "bytecode" => (ARTIFACT_EXTENSION_BINARY, line.trim().trim_start_matches("0x")),
+      "bytecode_runtime" => (ARTIFACT_EXTENSION_RUNTIME_BINARY, line.trim().trim_start_matches("0x")),
+      _ => continue,
+    };
+    fs::write(artifacts_dir.join(format!("{}.{}", name, extension)), content)?;
+  }
+  Ok(())
 }
 
 fn try_default_options_from(compiler: &str) -> Vec<String> {
   match compiler.parse() {
-    Ok(SupportedCompilers::Solc) => support::default_options_from(SupportedCompilers::Solc),
-    Ok(SupportedCompilers::SolcJs) => support::default_options_from(SupportedCompilers::SolcJs),
+    Ok(compiler) => support::default_options_from(compiler),
     Err(_err) => vec![],
   }
 }
+
+#[cfg(test)]
+mod tests {
+
+  mod resolve_options {
+
+    use super::super::{Compiler, CompilerConfig};
+    use crate::config::{Config, ProjectCmdExecutionConfig};
+
+    fn options(options: &[&str]) -> Option<Vec<String>> {
+      Some(options.iter().map(|option| option.to_string()).collect())
+    }
+
+    fn compiler_config(compiler: Option<&str>, compiler_options: Option<Vec<String>>, replace_options: bool) -> CompilerConfig {
+      CompilerConfig {
+        compiler: compiler.map(str::to_string),
+        compiler_options,
+        replace_options,
+        force: false,
+        tags: None,
+      }
+    }
+
+    fn project_config(cmd: &str, config_options: &[&str]) -> Option<ProjectCmdExecutionConfig> {
+      Some(ProjectCmdExecutionConfig {
+        cmd: Some(cmd.to_string()),
+        options: options(config_options),
+        ..ProjectCmdExecutionConfig::default()
+      })
+    }
+
+    #[test]
+    fn it_should_use_configured_options() {
+      let config = Config::new(std::env::temp_dir());
+      let compiler = Compiler::new(&config);
+
+      let resolved = compiler.resolve_options("solc", &project_config("solc", &["--abi", "--bin"]), &compiler_config(None, None, false)).unwrap();
+      assert_eq!(Some(resolved), options(&["--abi", "--bin"]));
+    }
+
+    #[test]
+    fn it_should_merge_invocation_options_into_configured_options() {
+      let config = Config::new(std::env::temp_dir());
+      let compiler = Compiler::new(&config);
+
+      let resolved = compiler.resolve_options(
+        "solc",
+        &project_config("solc", &["--abi", "--optimize-runs", "200"]),
+        &compiler_config(None, options(&["--optimize-runs", "1000", "--bin"]), false)
+      ).unwrap();
+      assert_eq!(Some(resolved), options(&["--abi", "--optimize-runs", "1000", "--bin"]));
+    }
+
+    #[test]
+    fn it_should_replace_configured_options_if_requested() {
+      let config = Config::new(std::env::temp_dir());
+      let compiler = Compiler::new(&config);
+
+      let resolved = compiler.resolve_options("solc", &project_config("solc", &["--abi"]), &compiler_config(None, options(&["--bin"]), true)).unwrap();
+      assert_eq!(Some(resolved), options(&["--bin"]));
+      assert!(compiler.resolve_options("solc", &None, &compiler_config(None, None, true)).is_err());
+    }
+
+    #[test]
+    fn it_should_not_apply_configured_options_to_other_compilers() {
+      let config = Config::new(std::env::temp_dir());
+      let compiler = Compiler::new(&config);
+
+      let resolved = compiler.resolve_options("custom", &project_config("solc", &["--abi"]), &compiler_config(Some("custom"), options(&["--foo"]), false)).unwrap();
+      assert_eq!(Some(resolved), options(&["--foo"]));
+    }
+  }
+
+  mod vyper {
+
+    use super::super::{is_vyper_source, support, write_vyper_artifacts, Compiler, CompilerConfig};
+    use crate::config::Config;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn it_should_use_built_in_defaults_for_vyper() {
+      let config = Config::new(std::env::temp_dir());
+      let compiler = Compiler::new(&config);
+      let compiler_config = CompilerConfig { compiler: Some("vyper".to_string()), compiler_options: None, replace_options: false, force: false, tags: None };
+
+      let resolved = compiler.resolve_options("vyper", &None, &compiler_config).unwrap();
+      assert_eq!(resolved, support::default_options_from(support::SupportedCompilers::Vyper));
+      assert!(is_vyper_source(Path::new("contracts/Token.vy")));
+      assert!(!is_vyper_source(Path::new("contracts/Token.sol")));
+    }
+
+    #[test]
+    fn it_should_write_vyper_output_as_solc_artifacts() {
+      let tmp_dir = tempfile::tempdir().unwrap();
+      let options = support::default_options_from(support::SupportedCompilers::Vyper);
+      let stdout = "[{\"name\": \"total\", \"type\": \"function\"}]\n0x6080\n0x6001\n";
+
+      write_vyper_artifacts(tmp_dir.path(), Path::new("contracts/Token.vy"), &options, stdout).unwrap();
+      assert_eq!(fs::read_to_string(tmp_dir.path().join("Token.abi")).unwrap(), "[{\"name\": \"total\", \"type\": \"function\"}]");
+      assert_eq!(fs::read_to_string(tmp_dir.path().join("Token.bin")).unwrap(), "6080");
+      assert_eq!(fs::read_to_string(tmp_dir.path().join("Token.bin-runtime")).unwrap(), "6001");
+
+      write_vyper_artifacts(tmp_dir.path(), Path::new("contracts/Coin.vy"), &["-f".to_string(), "bytecode_runtime,opcodes".to_string()], "0x6002\nPUSH1\n").unwrap();
+      assert_eq!(fs::read_to_string(tmp_dir.path().join("Coin.bin-runtime")).unwrap(), "6002");
+      assert!(!tmp_dir.path().join("Coin.bin").exists());
+    }
+  }
+
+  mod tags {
+
+    use super::super::{declared_smart_contracts, Compiler};
+    use crate::config::Config;
+    use std::fs;
+
+    #[test]
+    fn it_should_find_declared_smart_contracts() {
+      let source = "// contract Commented {}\nlibrary Math {}\ninterface IToken {}\nabstract contract Base {}\ncontract Token is Base, IToken {\n  string name = \"contract Fake\";\n}";
+      assert_eq!(declared_smart_contracts(source), vec!["Base", "Token", "Math", "IToken"]);
+    }
+
+    #[test]
+    fn it_should_only_select_sources_of_tagged_smart_contracts() {
+      let tmp_dir = tempfile::tempdir().unwrap();
+      let contracts_dir = tmp_dir.path().join("contracts");
+      fs::create_dir_all(&contracts_dir).unwrap();
+      fs::write(tmp_dir.path().join("vibranium.toml"), "
+        [sources]
+          artifacts = \"artifacts\"
+          smart_contracts = [\"contracts/*.sol\", \"contracts/*.vy\"]
+        [deployment]
+          smart_contracts = [
+            { name = \"Token\", tags = [\"core\"] },
+            { name = \"MockToken\", tags = [\"mocks\"] },
+            { name = \"Vault\", tags = [\"core\"] },
+          ]
+      ").unwrap();
+      fs::write(contracts_dir.join("Token.sol"), "contract Token {}").unwrap();
+      fs::write(contracts_dir.join("Mocks.sol"), "contract MockToken {}").unwrap();
+      fs::write(contracts_dir.join("Vault.vy"), "@external\ndef deposit(): pass").unwrap();
+
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let compiler = Compiler::new(&config);
+      let sources = vec![contracts_dir.join("Mocks.sol"), contracts_dir.join("Token.sol"), contracts_dir.join("Vault.vy")];
+
+      assert_eq!(compiler.tagged_sources(&["core".to_string()], sources.clone()).unwrap(), vec![contracts_dir.join("Token.sol"), contracts_dir.join("Vault.vy")]);
+      assert!(compiler.tagged_sources(&["periphery".to_string()], sources).is_err());
+    }
+  }
+}
@@ -0,0 +1,250 @@
+// Coarse attribution of runtime bytecode size to the functions it's been
+// compiled from. Every instruction of the runtime bytecode has an entry in the
+// runtime source map (`srcmap-runtime` of `--combined-json`), pointing at a
+// range of a source file. Instructions are attributed to the innermost
+// function, modifier or constructor whose declaration contains that range.
+// Optimized code is shared between functions, so sizes are an estimate.
+
+use super::checks::{is_identifier_char, is_word_at, strip_comments_and_strings};
+use crate::artifacts::COMBINED_JSON_FILE;
+use crate::utils;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+const DECLARATION_KEYWORDS: &[&str] = &["function", "modifier", "constructor", "fallback", "receive"];
+// Instructions without a source, e.g. the function dispatcher or internal
+// routines generated by the compiler.
+const GENERATED_CODE: &str = "(generated code)";
+// Instructions that belong to a contract but to none of its functions, e.g.
+// initializers of state variables.
+const CONTRACT_CODE: &str = "(contract)";
+// Runtime bytecode without a source map entry.
+const UNMAPPED_CODE: &str = "(unmapped)";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeContribution {
+  pub name: String,
+  pub size: usize,
+}
+
+impl fmt::Display for SizeContribution {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}: {} bytes", self.name, self.size)
+  }
+}
+
+#[derive(Deserialize, Debug)]
+struct CombinedJson {
+  contracts: BTreeMap<String, CombinedJsonContract>,
+  #[serde(rename = "sourceList")]
+  source_list: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CombinedJsonContract {
+  #[serde(rename = "bin-runtime")]
+  bin_runtime: Option<String>,
+  #[serde(rename = "srcmap-runtime")]
+  srcmap_runtime: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SourceRange {
+  start: i64,
+  file: i64,
+}
+
+// Contributions to the runtime bytecode of the given Smart Contract, if the
+// compiler wrote `--combined-json` output including `srcmap-runtime` to the
+// artifacts directory. Sources are listed relative to the project.
+pub fn contributions(project_path: &Path, artifacts_dir: &Path, name: &str) -> Option<Vec<SizeContribution>> {
+  let content = fs::read_to_string(artifacts_dir.join(COMBINED_JSON_FILE)).ok()?;
+  let combined: CombinedJson = serde_json::from_str(&content).map_err(|err| warn!("Couldn't read {}: {}", COMBINED_JSON_FILE, err)).ok()?;
+  let contract = combined.contracts.iter()
+    .find(|(key, _contract)| key.rsplit(':').next() == Some(name))
+    .map(|(_key, contract)| contract)?;
+  let bytecode = contract.bin_runtime.as_ref()?;
+  let source_map = contract.srcmap_runtime.as_ref().filter(|source_map| !source_map.is_empty())?;
+  let sources: Vec<Option<String>> = combined.source_list.unwrap_or_default().iter()
+    .map(|source| fs::read_to_string(project_path.join(source)).ok())
+    .collect();
+  Some(attribute(bytecode, source_map, &sources))
+}
+
+// Byte sizes of the bytecode's instructions, grouped by the function they've
+// been compiled from, largest first. `sources` holds the content of every
+// file of the compiler's source list, if it could be read.
+pub fn attribute(bytecode: &str, source_map: &str, sources: &[Option<String>]) -> Vec<SizeContribution> {
+  let mut code = utils::decode_bytecode(bytecode);
+  utils::strip_metadata(&mut code);
+  let ranges = parse_source_map(source_map);
+  let declarations: Vec<Vec<Declaration>> = sources.iter()
+    .map(|source| source.as_ref().map(|source| parse_declarations(source)).unwrap_or_default())
+    .collect();
+  let char_offsets: Vec<Vec<usize>> = sources.iter()
+    .map(|source| source.as_ref().map(|source| char_offsets(source)).unwrap_or_default())
+    .collect();
+
+  let mut sizes: HashMap<String, usize> = HashMap::new();
+  let mut offset = 0;
+  let mut instruction = 0;
+  while offset < code.len() {
+    let size = instruction_size(code[offset]).min(code.len() - offset);
+    let name = match ranges.get(instruction) {
+      None => UNMAPPED_CODE.to_string(),
+      Some(range) if range.file < 0 || range.file as usize >= sources.len() => GENERATED_CODE.to_string(),
+      Some(range) => {
+        let offsets = &char_offsets[range.file as usize];
+        let start = offsets.get(range.start.max(0) as usize).or_else(|| offsets.last()).cloned().unwrap_or(0);
+        declarations[range.file as usize].iter()
+          .filter(|declaration| declaration.start <= start && start < declaration.end)
+          .min_by_key(|declaration| declaration.end - declaration.start)
+          .map(|declaration| declaration.name.clone())
+          .unwrap_or_else(|| CONTRACT_CODE.to_string())
+      },
+    };
+    *sizes.entry(name).or_insert(0) += size;
+    offset += size;
+    instruction += 1;
+  }
+
+  let mut contributions: Vec<SizeContribution> = sizes.into_iter()
+    .map(|(name, size)| SizeContribution { name, size })
+    .collect();
+  contributions.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+  contributions
+}
+
+// Entries are separated by `;` and consist of `start:length:file:jump:depth`.
+// Empty or missing fields are the same as in the previous entry.
+fn parse_source_map(source_map: &str) -> Vec<SourceRange> {
+  let mut range = SourceRange { start: 0, file: -1 };
+  source_map.split(';').map(|entry| {
+    let fields: Vec<&str> = entry.split(':').collect();
+    if let Some(start) = fields.first().and_then(|start| start.parse().ok()) {
+      range.start = start;
+    }
+    if let Some(file) = fields.get(2).and_then(|file| file.parse().ok()) {
+      range.file = file;
+    }
+    range
+  }).collect()
+}
+
+// PUSH1 to PUSH32 are followed by the bytes they push.
+fn instruction_size(opcode: u8) -> usize {
+  match opcode {
+    0x60..=0x7f => 1 + (opcode - 0x5f) as usize,
+    _ => 1,
+  }
+}
+
+// Source maps count bytes, declarations are located in characters. Maps
+// every byte offset of the source to the offset of its character.
+fn char_offsets(source: &str) -> Vec<usize> {
+  let mut offsets = Vec::with_capacity(source.len() + 1);
+  for (offset, c) in source.chars().enumerate() {
+    offsets.extend(std::iter::repeat_n(offset, c.len_utf8()));
+  }
+  offsets.push(source.chars().count());
+  offsets
+}
+
+#[derive(Debug)]
+struct Declaration {
+  name: String,
+  start: usize,
+  end: usize,
+}
+
+// Declarations span from their keyword to the end of their body. Offsets are
+// in characters, as comments and strings are replaced character by character
+// and the remaining characters by a single byte each.
+fn parse_declarations(source: &str) -> Vec<Declaration> {
+  let text: String = strip_comments_and_strings(source).chars()
+    .map(|c| if c.is_ascii() { c } else { ' ' })
+    .collect();
+  let mut declarations = vec![];
+
+  for keyword in DECLARATION_KEYWORDS {
+    for (start, _) in text.match_indices(keyword) {
+      if !is_word_at(&text, start, keyword) {
+        continue;
+      }
+      let rest = &text[start + keyword.len()..];
+      let name = match *keyword {
+        "function" | "modifier" => {
+          let rest = rest.trim_start();
+          &rest[..rest.find(|c: char| !is_identifier_char(c)).unwrap_or(rest.len())]
+        },
+        keyword => keyword,
+      };
+      // `function()` declares an unnamed fallback function in Solidity < 0.6
+      let name = if name.is_empty() { "fallback" } else { name };
+      let body_start = match rest.find(['{', ';']) {
+        Some(offset) if rest[offset..].starts_with('{') => start + keyword.len() + offset,
+        _ => continue,
+      };
+      let end = matching_brace(&text, body_start).unwrap_or(text.len());
+      declarations.push(Declaration { name: name.to_string(), start, end: end + 1 });
+    }
+  }
+
+  declarations
+}
+
+fn matching_brace(code: &str, open: usize) -> Option<usize> {
+  let mut depth = 0;
+  for (i, c) in code[open..].char_indices() {
+    if c == '{' {
+      depth += 1;
+    } else if c == '}' {
+      depth -= 1;
+      if depth == 0 {
+        return Some(open + i);
+      }
+    }
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{attribute, SizeContribution};
+
+  const SOURCE: &str = "contract Token {
+  uint total = 1;
+  function mint(uint amount) public { total += amount; }
+  function burn(uint amount) public { total -= amount; }
+}
+";
+
+  #[test]
+  fn it_should_attribute_instructions_to_the_functions_they_are_compiled_from() {
+    let mint = SOURCE.find("total += amount").unwrap();
+    let burn = SOURCE.find("total -= amount").unwrap();
+    let source_map = format!("0:{}:0:-;{}:15:0:-;;{}:15:0:-;:::;0:0:-1:-", SOURCE.len(), mint, burn);
+
+    // STOP, PUSH2, ADD, PUSH1, SUB, PUSH32, followed by an unmapped JUMPDEST
+    let bytecode = format!("0061000101600203{}{}5b", "7f", "00".repeat(32));
+    let contributions = attribute(&bytecode, &source_map, &[Some(SOURCE.to_string())]);
+
+    assert_eq!(contributions, vec![
+      SizeContribution { name: "(generated code)".to_string(), size: 33 },
+      SizeContribution { name: "mint".to_string(), size: 4 },
+      SizeContribution { name: "burn".to_string(), size: 3 },
+      SizeContribution { name: "(contract)".to_string(), size: 1 },
+      SizeContribution { name: "(unmapped)".to_string(), size: 1 },
+    ]);
+  }
+
+  #[test]
+  fn it_should_attribute_instructions_of_unreadable_sources_to_their_contract() {
+    let contributions = attribute("6001", "10:5:0:-", &[None]);
+    assert_eq!(contributions, vec![SizeContribution { name: "(contract)".to_string(), size: 2 }]);
+  }
+}
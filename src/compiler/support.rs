@@ -6,10 +6,14 @@ use std::string::ToString;
 const SOLC_COMPILER_BINARY_UNIX: &str = "solc";
 const SOLC_COMPILER_BINARY_WINDOWS: &str = "solc.exe";
 const SOLC_JS_COMPILER_BINARY: &str = "solcjs";
+const VYPER_COMPILER_BINARY: &str = "vyper";
+pub const VYPER_SOURCE_EXTENSION: &str = "vy";
 
+#[derive(Debug, PartialEq)]
 pub enum SupportedCompilers {
   Solc,
   SolcJs,
+  Vyper,
 }
 
 impl SupportedCompilers {
@@ -23,6 +27,7 @@ impl SupportedCompilers {
         }
       }
       SupportedCompilers::SolcJs => SOLC_JS_COMPILER_BINARY.to_string(),
+      SupportedCompilers::Vyper => VYPER_COMPILER_BINARY.to_string(),
     }
   }
 }
@@ -33,6 +38,7 @@ impl FromStr for SupportedCompilers {
     match s {
       SOLC_COMPILER_BINARY_UNIX => Ok(SupportedCompilers::Solc),
       SOLC_JS_COMPILER_BINARY => Ok(SupportedCompilers::SolcJs),
+      VYPER_COMPILER_BINARY => Ok(SupportedCompilers::Vyper),
       _ => Err(error::CompilerError::UnsupportedStrategy),
     }
   }
@@ -43,6 +49,7 @@ impl ToString for SupportedCompilers {
     match self {
       SupportedCompilers::Solc => SOLC_COMPILER_BINARY_UNIX.to_string(),
       SupportedCompilers::SolcJs => SOLC_JS_COMPILER_BINARY.to_string(),
+      SupportedCompilers::Vyper => VYPER_COMPILER_BINARY.to_string(),
     }
   }
 }
@@ -64,5 +71,13 @@ pub fn default_options_from(compiler: SupportedCompilers) -> Vec<String> {
         "-o".to_string()
       ]
     },
+    // Vyper compiles one source file at a time and prints the requested
+    // formats to stdout, one per line.
+    SupportedCompilers::Vyper => {
+      vec![
+        "-f".to_string(),
+        "abi,bytecode,bytecode_runtime".to_string()
+      ]
+    },
   }
 }
@@ -0,0 +1,446 @@
+//! solc releases, installed into a cache shared by all projects
+//! (`~/.vibranium/solc/<version>/`, or the directory `VIBRANIUM_SOLC_DIR`
+//! points to), similar to solc-select. Releases are looked up in the list of
+//! official builds on binaries.soliditylang.org and downloaded with curl.
+//!
+//! With `compiler.version = "auto"` in vibranium.toml, every source file is
+//! compiled with the newest version satisfying its `pragma solidity`
+//! constraints, preferring versions that are installed already. Any other
+//! `compiler.version` is used for all sources.
+
+use super::error::CompilerError;
+use crate::config::Config;
+use crate::process::ProcessCommand;
+
+use sha3::{Digest, Keccak256};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const AUTO_VERSION: &str = "auto";
+pub const SOLC_DIR_ENV_VARIABLE: &str = "VIBRANIUM_SOLC_DIR";
+const RELEASES_URL: &str = "https://binaries.soliditylang.org";
+const RELEASES_LIST_FILE: &str = "list.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+  pub major: u64,
+  pub minor: u64,
+  pub patch: u64,
+}
+
+impl FromStr for Version {
+  type Err = CompilerError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let parts: Vec<u64> = s.trim().trim_start_matches('v').split('.')
+      .map(|part| part.parse().ok())
+      .collect::<Option<Vec<u64>>>()
+      .ok_or_else(|| CompilerError::InvalidVersion(s.to_string()))?;
+    match parts.as_slice() {
+      [major, minor, patch] => Ok(Version { major: *major, minor: *minor, patch: *patch }),
+      _ => Err(CompilerError::InvalidVersion(s.to_string())),
+    }
+  }
+}
+
+impl fmt::Display for Version {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+  }
+}
+
+// Versions within `[low, high)`, without an upper bound if `high` is `None`.
+#[derive(Debug, Clone, PartialEq)]
+struct Range {
+  low: Version,
+  high: Option<Version>,
+}
+
+impl Range {
+  fn contains(&self, version: &Version) -> bool {
+    *version >= self.low && self.high.as_ref().is_none_or(|high| version < high)
+  }
+}
+
+// Version requirements of `pragma solidity`, following npm's semver ranges:
+// comparators separated by spaces must all match, alternatives are separated
+// by `||`. Versions may leave out their minor and patch version, or use `x`
+// and `*` for them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionReq {
+  requirement: String,
+  alternatives: Vec<Vec<Range>>,
+}
+
+impl VersionReq {
+  pub fn matches(&self, version: &Version) -> bool {
+    self.alternatives.iter().any(|ranges| ranges.iter().all(|range| range.contains(version)))
+  }
+}
+
+impl FromStr for VersionReq {
+  type Err = CompilerError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let alternatives = s.split("||")
+      .map(|alternative| {
+        let mut comparators: Vec<String> = vec![];
+        for token in alternative.split_whitespace() {
+          match comparators.last_mut() {
+            Some(last) if last.chars().all(|c| "<>=^~".contains(c)) => last.push_str(token),
+            _ => comparators.push(token.to_string()),
+          }
+        }
+        if comparators.is_empty() {
+          return Err(CompilerError::InvalidVersion(s.to_string()));
+        }
+        comparators.iter().map(|comparator| range(comparator).ok_or_else(|| CompilerError::InvalidVersion(s.to_string()))).collect()
+      })
+      .collect::<Result<Vec<Vec<Range>>, CompilerError>>()?;
+
+    Ok(VersionReq {
+      requirement: s.trim().to_string(),
+      alternatives,
+    })
+  }
+}
+
+impl fmt::Display for VersionReq {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.requirement)
+  }
+}
+
+fn range(comparator: &str) -> Option<Range> {
+  let operator_length = comparator.find(|c: char| !"<>=^~".contains(c)).unwrap_or(comparator.len());
+  let (operator, version) = comparator.split_at(operator_length);
+
+  let parts: Vec<Option<u64>> = version.trim_start_matches('v').split('.')
+    .map(|part| match part {
+      "x" | "X" | "*" => Some(None),
+      _ => part.parse().ok().map(Some),
+    })
+    .collect::<Option<Vec<Option<u64>>>>()?;
+  if parts.is_empty() || parts.len() > 3 {
+    return None;
+  }
+  let major = parts[0]?;
+  let minor = parts.get(1).cloned().flatten();
+  let patch = minor.and(parts.get(2).cloned().flatten());
+
+  let low = Version { major, minor: minor.unwrap_or(0), patch: patch.unwrap_or(0) };
+  // The first version past all versions the comparator's version stands for.
+  let next = match (minor, patch) {
+    (Some(minor), Some(patch)) => Version { major, minor, patch: patch + 1 },
+    (Some(minor), None) => Version { major, minor: minor + 1, patch: 0 },
+    (None, _) => Version { major: major + 1, minor: 0, patch: 0 },
+  };
+  let zero = Version { major: 0, minor: 0, patch: 0 };
+
+  Some(match operator {
+    "" | "=" => Range { low, high: Some(next) },
+    ">=" => Range { low, high: None },
+    ">" => Range { low: next, high: None },
+    "<" => Range { low: zero, high: Some(low) },
+    "<=" => Range { low: zero, high: Some(next) },
+    "~" => Range { low, high: Some(match minor {
+      Some(minor) => Version { major, minor: minor + 1, patch: 0 },
+      None => Version { major: major + 1, minor: 0, patch: 0 },
+    }) },
+    "^" => Range { low, high: Some(match (major, minor, patch) {
+      (0, Some(0), Some(patch)) => Version { major: 0, minor: 0, patch: patch + 1 },
+      (0, Some(minor), _) => Version { major: 0, minor: minor + 1, patch: 0 },
+      _ => Version { major: major + 1, minor: 0, patch: 0 },
+    }) },
+    _ => return None,
+  })
+}
+
+// All `pragma solidity` statements of a source file.
+pub fn pragma_requirements(source: &str) -> Result<Vec<VersionReq>, CompilerError> {
+  let mut requirements = vec![];
+  let mut rest = source;
+  while let Some(index) = rest.find("pragma solidity") {
+    rest = &rest[index + "pragma solidity".len()..];
+    let end = rest.find(';').unwrap_or(rest.len());
+    requirements.push(rest[..end].parse()?);
+    rest = &rest[end..];
+  }
+  Ok(requirements)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+  pub version: Version,
+  pub path: String,
+  pub keccak256: String,
+}
+
+pub struct SolcInstaller<'a> {
+  cache_dir: PathBuf,
+  vibranium_dir_path: &'a Path,
+  releases: RefCell<Option<Vec<Release>>>,
+}
+
+impl<'a> SolcInstaller<'a> {
+  pub fn new(cache_dir: PathBuf, vibranium_dir_path: &'a Path) -> SolcInstaller<'a> {
+    SolcInstaller {
+      cache_dir,
+      vibranium_dir_path,
+      releases: RefCell::new(None),
+    }
+  }
+
+  pub fn for_project(config: &'a Config) -> SolcInstaller<'a> {
+    SolcInstaller::new(default_cache_dir(config), &config.vibranium_dir_path)
+  }
+
+  pub fn executable(&self, version: Version) -> PathBuf {
+    let name = if cfg!(target_os = "windows") { "solc.exe" } else { "solc" };
+    self.cache_dir.join(version.to_string()).join(name)
+  }
+
+  pub fn installed(&self) -> Vec<Version> {
+    let mut versions: Vec<Version> = fs::read_dir(&self.cache_dir).into_iter()
+      .flatten()
+      .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+      .filter(|version| self.executable(*version).exists())
+      .collect();
+    versions.sort();
+    versions
+  }
+
+  // Releases are only fetched once per installer.
+  pub fn releases(&self) -> Result<Vec<Release>, CompilerError> {
+    if let Some(releases) = self.releases.borrow().as_ref() {
+      return Ok(releases.clone());
+    }
+
+    fs::create_dir_all(&self.cache_dir).map_err(CompilerError::Io)?;
+    let list_path = self.cache_dir.join(RELEASES_LIST_FILE);
+    self.download(&format!("{}/{}/{}", RELEASES_URL, platform()?, RELEASES_LIST_FILE), &list_path)?;
+    let releases = parse_releases(&fs::read_to_string(&list_path).map_err(CompilerError::Io)?)?;
+    *self.releases.borrow_mut() = Some(releases.clone());
+    Ok(releases)
+  }
+
+  // The newest installed version satisfying all requirements, or else the
+  // newest release doing so.
+  pub fn select(&self, requirements: &[VersionReq]) -> Result<Version, CompilerError> {
+    let satisfies = |version: &Version| requirements.iter().all(|requirement| requirement.matches(version));
+    if let Some(version) = self.installed().into_iter().rev().find(satisfies) {
+      return Ok(version);
+    }
+    self.releases()?.iter()
+      .map(|release| release.version)
+      .filter(satisfies)
+      .max()
+      .ok_or_else(|| CompilerError::NoMatchingVersion(requirements.iter().map(|requirement| requirement.to_string()).collect::<Vec<String>>().join(", ")))
+  }
+
+  // Downloaded binaries are checked against the Keccak-256 hash of their
+  // release before they're used.
+  pub fn install(&self, version: Version) -> Result<PathBuf, CompilerError> {
+    let executable = self.executable(version);
+    if executable.exists() {
+      return Ok(executable);
+    }
+
+    let release = self.releases()?.into_iter()
+      .find(|release| release.version == version)
+      .ok_or_else(|| CompilerError::NoMatchingVersion(version.to_string()))?;
+    let version_dir = self.cache_dir.join(version.to_string());
+    fs::create_dir_all(&version_dir).map_err(CompilerError::Io)?;
+
+    let download_path = version_dir.join(format!("{}.download", &release.path));
+    info!("Downloading solc {}", version);
+    self.download(&format!("{}/{}/{}", RELEASES_URL, platform()?, &release.path), &download_path)?;
+
+    let hash = format!("0x{:x}", Keccak256::digest(&fs::read(&download_path).map_err(CompilerError::Io)?));
+    if hash != release.keccak256 {
+      let _ = fs::remove_file(&download_path);
+      return Err(CompilerError::SolcDownload(format!("{} doesn't match its release, expected hash {} but got {}", &release.path, &release.keccak256, hash)));
+    }
+
+    make_executable(&download_path).map_err(CompilerError::Io)?;
+    fs::rename(&download_path, &executable).map_err(CompilerError::Io)?;
+    Ok(executable)
+  }
+
+  // Sources are grouped by the version they're compiled with, so that solc
+  // runs once per version.
+  pub fn group_sources(&self, version: &str, sources: Vec<PathBuf>) -> Result<BTreeMap<Version, Vec<PathBuf>>, CompilerError> {
+    let mut groups: BTreeMap<Version, Vec<PathBuf>> = BTreeMap::new();
+    if version != AUTO_VERSION {
+      if !sources.is_empty() {
+        groups.insert(version.parse()?, sources);
+      }
+      return Ok(groups);
+    }
+
+    for source in sources {
+      let requirements = pragma_requirements(&fs::read_to_string(&source).map_err(CompilerError::Io)?)?;
+      let version = self.select(&requirements)?;
+      info!("Compiling {} with solc {}", source.to_string_lossy(), version);
+      groups.entry(version).or_default().push(source);
+    }
+    Ok(groups)
+  }
+
+  fn download(&self, url: &str, path: &Path) -> Result<(), CompilerError> {
+    let output = ProcessCommand::new("curl")
+      .args(vec!["-fsSL".to_string(), "-o".to_string(), path.to_string_lossy().to_string(), url.to_string()])
+      .log_to(self.vibranium_dir_path)
+      .output()
+      .map_err(|err| {
+        match err.kind() {
+          std::io::ErrorKind::NotFound => CompilerError::ExecutableNotFound(err, "curl".to_string()),
+          _ => CompilerError::Io(err)
+        }
+      })?;
+    if !output.status.success() {
+      return Err(CompilerError::SolcDownload(format!("{}: {}", url, String::from_utf8_lossy(&output.stderr).trim())));
+    }
+    Ok(())
+  }
+}
+
+pub fn default_cache_dir(config: &Config) -> PathBuf {
+  if let Ok(dir) = env::var(SOLC_DIR_ENV_VARIABLE) {
+    return PathBuf::from(dir);
+  }
+  match env::var("HOME").or_else(|_err| env::var("USERPROFILE")) {
+    Ok(home) => PathBuf::from(home).join(".vibranium").join("solc"),
+    Err(_err) => config.vibranium_dir_path.join("solc"),
+  }
+}
+
+// Only full releases are considered, nightly builds are left out.
+fn parse_releases(list: &str) -> Result<Vec<Release>, CompilerError> {
+  let list: serde_json::Value = serde_json::from_str(list).map_err(|err| CompilerError::SolcDownload(format!("Invalid list of releases: {}", err)))?;
+  let releases = list["releases"].as_object().ok_or_else(|| CompilerError::SolcDownload("Invalid list of releases: missing releases".to_string()))?;
+
+  let mut parsed: Vec<Release> = releases.iter()
+    .filter_map(|(version, path)| {
+      let path = path.as_str()?;
+      let build = list["builds"].as_array()?.iter().find(|build| build["path"].as_str() == Some(path))?;
+      Some(Release {
+        version: version.parse().ok()?,
+        path: path.to_string(),
+        keccak256: build["keccak256"].as_str()?.to_lowercase(),
+      })
+    })
+    .collect();
+  parsed.sort_by(|a, b| a.version.partial_cmp(&b.version).unwrap_or(Ordering::Equal));
+  Ok(parsed)
+}
+
+fn platform() -> Result<&'static str, CompilerError> {
+  if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+    Ok("linux-amd64")
+  } else if cfg!(target_os = "macos") {
+    Ok("macosx-amd64")
+  } else if cfg!(all(target_os = "windows", target_arch = "x86_64")) {
+    Ok("windows-amd64")
+  } else {
+    Err(CompilerError::SolcDownload("There are no solc releases for this platform".to_string()))
+  }
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+  use std::os::unix::fs::PermissionsExt;
+  fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{parse_releases, pragma_requirements, SolcInstaller, Version, VersionReq};
+  use std::fs;
+
+  fn version(version: &str) -> Version {
+    version.parse().unwrap()
+  }
+
+  fn matches(requirement: &str, versions: &[&str]) -> Vec<bool> {
+    let requirement: VersionReq = requirement.parse().unwrap();
+    versions.iter().map(|v| requirement.matches(&version(v))).collect()
+  }
+
+  #[test]
+  fn it_should_match_version_requirements() {
+    assert_eq!(matches("^0.8.1", &["0.8.0", "0.8.1", "0.8.24", "0.9.0"]), vec![false, true, true, false]);
+    assert_eq!(matches("~0.7.6", &["0.7.5", "0.7.6", "0.8.0"]), vec![false, true, false]);
+    assert_eq!(matches(">=0.7.0 <0.9.0", &["0.6.12", "0.7.0", "0.8.24", "0.9.0"]), vec![false, true, true, false]);
+    assert_eq!(matches(">= 0.4.22 < 0.6", &["0.4.21", "0.5.17", "0.6.0"]), vec![false, true, false]);
+    assert_eq!(matches("0.5.x || ^0.8.0", &["0.5.3", "0.6.0", "0.8.2"]), vec![true, false, true]);
+    assert_eq!(matches("0.8.24", &["0.8.23", "0.8.24", "0.8.25"]), vec![false, true, false]);
+    assert_eq!(matches(">0.7 <=0.8", &["0.7.6", "0.8.0", "0.8.24", "0.9.0"]), vec![false, true, true, false]);
+    assert!("^0.8.-1".parse::<VersionReq>().is_err());
+    assert!("0.8".parse::<Version>().is_err());
+  }
+
+  #[test]
+  fn it_should_read_pragmas_of_sources() {
+    let requirements = pragma_requirements("// SPDX-License-Identifier: MIT\npragma solidity >=0.7.0 <0.9.0;\npragma experimental ABIEncoderV2;\ncontract A {}").unwrap();
+    assert_eq!(requirements.len(), 1);
+    assert_eq!(requirements[0].to_string(), ">=0.7.0 <0.9.0");
+    assert!(pragma_requirements("contract A {}").unwrap().is_empty());
+  }
+
+  #[test]
+  fn it_should_parse_releases() {
+    let releases = parse_releases(r#"{
+      "builds": [
+        { "path": "solc-linux-amd64-v0.8.24+commit.e11b9ed9", "version": "0.8.24", "keccak256": "0xABCD" },
+        { "path": "solc-linux-amd64-v0.8.25-nightly.2024.1.1+commit.1", "version": "0.8.25", "keccak256": "0x1234" },
+        { "path": "solc-linux-amd64-v0.7.6+commit.7338295f", "version": "0.7.6", "keccak256": "0xef01" }
+      ],
+      "releases": {
+        "0.8.24": "solc-linux-amd64-v0.8.24+commit.e11b9ed9",
+        "0.7.6": "solc-linux-amd64-v0.7.6+commit.7338295f"
+      }
+    }"#).unwrap();
+
+    assert_eq!(releases.iter().map(|release| release.version).collect::<Vec<Version>>(), vec![version("0.7.6"), version("0.8.24")]);
+    assert_eq!(releases[1].keccak256, "0xabcd");
+  }
+
+  #[test]
+  fn it_should_group_sources_by_installed_versions() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tmp_dir.path().join("solc");
+    let installer = SolcInstaller::new(cache_dir, tmp_dir.path());
+    for installed in &["0.5.17", "0.8.20", "0.8.24"] {
+      let executable = installer.executable(version(installed));
+      fs::create_dir_all(executable.parent().unwrap()).unwrap();
+      fs::write(executable, "").unwrap();
+    }
+    assert_eq!(installer.installed(), vec![version("0.5.17"), version("0.8.20"), version("0.8.24")]);
+
+    let old = tmp_dir.path().join("Old.sol");
+    let new = tmp_dir.path().join("New.sol");
+    let pinned = tmp_dir.path().join("Pinned.sol");
+    fs::write(&old, "pragma solidity ^0.5.0;").unwrap();
+    fs::write(&new, "pragma solidity >=0.8.0;").unwrap();
+    fs::write(&pinned, "pragma solidity 0.8.20;").unwrap();
+
+    let groups = installer.group_sources("auto", vec![old.clone(), new.clone(), pinned.clone()]).unwrap();
+    assert_eq!(groups.get(&version("0.5.17")), Some(&vec![old.clone()]));
+    assert_eq!(groups.get(&version("0.8.20")), Some(&vec![pinned]));
+    assert_eq!(groups.get(&version("0.8.24")), Some(&vec![new.clone()]));
+
+    let groups = installer.group_sources("0.8.24", vec![old.clone(), new.clone()]).unwrap();
+    assert_eq!(groups.into_iter().collect::<Vec<_>>(), vec![(version("0.8.24"), vec![old, new])]);
+  }
+}
@@ -0,0 +1,247 @@
+// Warnings emitted by the compiler are subject to `[compiler.warnings]` in
+// vibranium.toml. Newer versions of solc identify warnings by code, e.g.
+//
+//   Warning (2072): Unused local variable.
+//    --> contracts/Simple.sol:5:5:
+//
+// while older ones only state the location. Those can only be denied as a
+// whole via `deny_warnings`.
+
+use super::checks::Severity;
+use crate::config::CompilerWarningsConfig;
+
+use std::fmt;
+
+const WARNING_PREFIX: &str = "Warning";
+const ERROR_PREFIXES: &[&str] = &["Error", "ParserError", "TypeError", "DeclarationError", "SyntaxError", "CompilerError"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompilerWarning {
+  pub code: Option<u32>,
+  pub message: String,
+  pub location: Option<String>,
+  text: String,
+}
+
+impl fmt::Display for CompilerWarning {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if let Some(location) = &self.location {
+      write!(f, "{}: ", location)?;
+    }
+    match self.code {
+      Some(code) => write!(f, "Warning ({}): {}", code, self.message),
+      None => write!(f, "Warning: {}", self.message),
+    }
+  }
+}
+
+// Any diagnostic of the compiler, where `kind` is e.g. `Warning` or `TypeError`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompilerMessage {
+  pub kind: String,
+  pub code: Option<u32>,
+  pub message: String,
+  pub location: Option<String>,
+}
+
+impl CompilerMessage {
+  pub fn is_warning(&self) -> bool {
+    self.kind == WARNING_PREFIX
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct WarningPolicy {
+  pub deny_warnings: bool,
+  pub errors: Vec<u32>,
+  pub silence: Vec<u32>,
+}
+
+impl WarningPolicy {
+  pub fn from_config(config: &Option<CompilerWarningsConfig>) -> WarningPolicy {
+    match config {
+      Some(config) => WarningPolicy {
+        deny_warnings: config.deny_warnings.unwrap_or(false),
+        errors: config.errors.clone().unwrap_or_default(),
+        silence: config.silence.clone().unwrap_or_default(),
+      },
+      None => WarningPolicy::default(),
+    }
+  }
+
+  // Silencing a code takes precedence over `deny_warnings`.
+  pub fn severity(&self, warning: &CompilerWarning) -> Severity {
+    match warning.code {
+      Some(code) if self.silence.contains(&code) => Severity::Off,
+      Some(code) if self.errors.contains(&code) => Severity::Error,
+      _ if self.deny_warnings => Severity::Error,
+      _ => Severity::Warn,
+    }
+  }
+
+  // Returns the compiler output without silenced warnings, along with the
+  // warnings that are treated as errors.
+  pub fn apply(&self, output: &str) -> (String, Vec<CompilerWarning>) {
+    let mut remaining = String::new();
+    let mut denied = vec![];
+
+    for block in blocks(output) {
+      match parse_warning(&block) {
+        Some(warning) => match self.severity(&warning) {
+          Severity::Off => info!("Silenced compiler warning: {}", warning),
+          Severity::Error => {
+            remaining.push_str(&warning.text);
+            denied.push(warning);
+          },
+          Severity::Warn => remaining.push_str(&warning.text),
+        },
+        None => remaining.push_str(&block),
+      }
+    }
+    (remaining, denied)
+  }
+}
+
+pub fn parse(output: &str) -> Vec<CompilerWarning> {
+  blocks(output).iter().filter_map(|block| parse_warning(block)).collect()
+}
+
+// Errors as well as warnings, e.g. to show them in editors.
+pub fn messages(output: &str) -> Vec<CompilerMessage> {
+  blocks(output).iter().filter_map(|block| parse_message(block)).collect()
+}
+
+// Splits compiler output into diagnostics, each starting with a header line
+// followed by the source excerpt it refers to.
+fn blocks(output: &str) -> Vec<String> {
+  let mut blocks: Vec<String> = vec![];
+  for line in output.split_inclusive('\n') {
+    match blocks.last_mut() {
+      Some(block) if !is_header(line) => block.push_str(line),
+      _ => blocks.push(line.to_string()),
+    }
+  }
+  blocks
+}
+
+fn is_header(line: &str) -> bool {
+  let kind = header_kind(line);
+  kind == WARNING_PREFIX || ERROR_PREFIXES.contains(&kind)
+}
+
+// `Warning (2072): ...` or `contracts/Simple.sol:5:5: Warning: ...`
+fn header_kind(line: &str) -> &str {
+  let line = line.trim_end();
+  let without_location = match line.find(": ") {
+    Some(index) if line[..index].contains(".sol:") => &line[index + 2..],
+    _ => line,
+  };
+  without_location.split([':', ' ']).next().unwrap_or("")
+}
+
+fn parse_warning(block: &str) -> Option<CompilerWarning> {
+  let message = parse_message(block)?;
+  if message.kind != WARNING_PREFIX {
+    return None;
+  }
+  Some(CompilerWarning {
+    code: message.code,
+    message: message.message,
+    location: message.location,
+    text: block.to_string(),
+  })
+}
+
+fn parse_message(block: &str) -> Option<CompilerMessage> {
+  let mut lines = block.lines();
+  let header = lines.next()?.trim_end();
+  if !is_header(header) {
+    return None;
+  }
+
+  let kind = header_kind(header).to_string();
+  let (location, rest) = match header.find(": ") {
+    Some(index) if header[..index].contains(".sol:") => (Some(header[..index].to_string()), &header[index + 2..]),
+    _ => (None, header),
+  };
+  let rest = &rest[kind.len()..];
+
+  let (code, message) = match rest.strip_prefix(" (").and_then(|rest| rest.find("):").map(|end| (&rest[..end], &rest[end + 2..]))) {
+    Some((code, message)) => (code.parse().ok(), message),
+    None => (None, rest.trim_start_matches(':')),
+  };
+
+  let location = location.or_else(|| {
+    lines.map(str::trim).find(|line| line.starts_with("-->")).map(|line| line[3..].trim().trim_end_matches(':').to_string())
+  });
+
+  Some(CompilerMessage {
+    kind,
+    code,
+    message: message.trim().to_string(),
+    location,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{messages, parse, WarningPolicy};
+  use crate::compiler::checks::Severity;
+
+  const OUTPUT: &str = "Warning (2072): Unused local variable.
+ --> contracts/Simple.sol:5:5:
+  |
+5 |     uint x = 1;
+  |     ^^^^^^
+/tmp/contracts/Old.sol:3:3: Warning: Function state mutability can be restricted to pure
+  function get() public view returns (uint) {
+  ^
+Warning (5667): Unused function parameter.
+ --> contracts/Simple.sol:7:18:
+";
+
+  #[test]
+  fn it_should_parse_warnings_with_and_without_codes() {
+    let warnings = parse(OUTPUT);
+    assert_eq!(warnings.len(), 3);
+    assert_eq!(warnings[0].code, Some(2072));
+    assert_eq!(warnings[0].message, "Unused local variable.");
+    assert_eq!(warnings[0].location, Some("contracts/Simple.sol:5:5".to_string()));
+    assert_eq!(warnings[1].code, None);
+    assert_eq!(warnings[1].location, Some("/tmp/contracts/Old.sol:3:3".to_string()));
+    assert_eq!(warnings[1].message, "Function state mutability can be restricted to pure");
+  }
+
+  #[test]
+  fn it_should_parse_errors_along_with_warnings() {
+    let output = format!("TypeError: Member \"foo\" not found.\n --> contracts/Simple.sol:9:5:\n{}", OUTPUT);
+    let messages = messages(&output);
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[0].kind, "TypeError");
+    assert!(!messages[0].is_warning());
+    assert_eq!(messages[0].message, "Member \"foo\" not found.");
+    assert_eq!(messages[0].location, Some("contracts/Simple.sol:9:5".to_string()));
+    assert!(messages[1].is_warning());
+  }
+
+  #[test]
+  fn it_should_promote_and_silence_warnings_by_code() {
+    let policy = WarningPolicy { deny_warnings: false, errors: vec![2072], silence: vec![5667] };
+    let warnings = parse(OUTPUT);
+    assert_eq!(policy.severity(&warnings[0]), Severity::Error);
+    assert_eq!(policy.severity(&warnings[1]), Severity::Warn);
+
+    let (remaining, denied) = policy.apply(OUTPUT);
+    assert_eq!(denied, vec![warnings[0].clone()]);
+    assert!(remaining.contains("Old.sol"));
+    assert!(!remaining.contains("5667"));
+  }
+
+  #[test]
+  fn it_should_deny_all_warnings_that_arent_silenced() {
+    let policy = WarningPolicy { deny_warnings: true, errors: vec![], silence: vec![5667] };
+    let (_remaining, denied) = policy.apply(OUTPUT);
+    assert_eq!(denied.iter().map(|warning| warning.code).collect::<Vec<Option<u32>>>(), vec![Some(2072), None]);
+  }
+}
@@ -0,0 +1,103 @@
+// Sources are polled for changes, which works the same on every platform and
+// doesn't need a native filesystem notification API. Changes are detected by
+// content, so touching a file doesn't trigger another build.
+
+use crate::config::Config;
+use super::cache::BuildCache;
+use super::error::CompilerError;
+use super::Compiler;
+
+use std::collections::BTreeMap;
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct SourceWatcher<'a> {
+  config: &'a Config,
+  interval: Duration,
+  snapshot: BTreeMap<String, String>,
+}
+
+impl<'a> SourceWatcher<'a> {
+  pub fn new(config: &'a Config) -> Result<SourceWatcher<'a>, CompilerError> {
+    let mut watcher = SourceWatcher {
+      config,
+      interval: DEFAULT_WATCH_INTERVAL,
+      snapshot: BTreeMap::new(),
+    };
+    watcher.snapshot = watcher.snapshot()?;
+    Ok(watcher)
+  }
+
+  pub fn with_interval(mut self, interval: Duration) -> SourceWatcher<'a> {
+    self.interval = interval;
+    self
+  }
+
+  // Sources that have been added, changed or removed since the last call,
+  // relative to the project.
+  pub fn changes(&mut self) -> Result<Vec<String>, CompilerError> {
+    let snapshot = self.snapshot()?;
+    let mut changed: Vec<String> = snapshot.iter()
+      .filter(|(source, hash)| self.snapshot.get(*source) != Some(hash))
+      .chain(self.snapshot.iter().filter(|(source, _hash)| !snapshot.contains_key(*source)))
+      .map(|(source, _hash)| source.to_owned())
+      .collect();
+    changed.sort();
+    self.snapshot = snapshot;
+    Ok(changed)
+  }
+
+  pub fn wait_for_changes(&mut self) -> Result<Vec<String>, CompilerError> {
+    loop {
+      let changed = self.changes()?;
+      if !changed.is_empty() {
+        return Ok(changed);
+      }
+      thread::sleep(self.interval);
+    }
+  }
+
+  // Source patterns are read on every poll, so that changes to them in
+  // vibranium.toml are picked up as well.
+  fn snapshot(&self) -> Result<BTreeMap<String, String>, CompilerError> {
+    let project_config = self.config.read()?;
+    let sources = Compiler::new(self.config).source_files(&project_config.sources.smart_contracts);
+    Ok(BuildCache::new(&self.config.project_path, "", &[], &sources).map_err(CompilerError::Io)?.sources)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::SourceWatcher;
+  use crate::config::Config;
+  use std::fs;
+
+  #[test]
+  fn it_should_report_changed_sources_by_content() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(tmp_dir.path().join("contracts")).unwrap();
+    fs::write(tmp_dir.path().join("vibranium.toml"), "
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+    ").unwrap();
+    fs::write(tmp_dir.path().join("contracts").join("A.sol"), "contract A {}").unwrap();
+    fs::write(tmp_dir.path().join("contracts").join("B.sol"), "contract B {}").unwrap();
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let mut watcher = SourceWatcher::new(&config).unwrap();
+    assert!(watcher.changes().unwrap().is_empty());
+
+    fs::write(tmp_dir.path().join("contracts").join("A.sol"), "contract A {}").unwrap();
+    assert!(watcher.changes().unwrap().is_empty());
+
+    fs::write(tmp_dir.path().join("contracts").join("A.sol"), "contract A { uint a; }").unwrap();
+    fs::remove_file(tmp_dir.path().join("contracts").join("B.sol")).unwrap();
+    fs::write(tmp_dir.path().join("contracts").join("C.sol"), "contract C {}").unwrap();
+    assert_eq!(watcher.changes().unwrap(), vec!["contracts/A.sol", "contracts/B.sol", "contracts/C.sol"]);
+    assert!(watcher.changes().unwrap().is_empty());
+  }
+}
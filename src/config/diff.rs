@@ -0,0 +1,79 @@
+// Line based differences between two versions of a configuration file, based
+// on their longest common subsequence of lines. Configuration files are small
+// enough for the quadratic table this takes.
+
+#[derive(Debug, PartialEq)]
+pub enum DiffLine<'a> {
+  Unchanged(&'a str),
+  Added(&'a str),
+  Removed(&'a str),
+}
+
+impl<'a> DiffLine<'a> {
+  pub fn is_change(&self) -> bool {
+    match self {
+      DiffLine::Unchanged(_line) => false,
+      DiffLine::Added(_line) | DiffLine::Removed(_line) => true,
+    }
+  }
+}
+
+pub fn diff_lines<'a>(before: &'a str, after: &'a str) -> Vec<DiffLine<'a>> {
+  let before: Vec<&str> = before.lines().collect();
+  let after: Vec<&str> = after.lines().collect();
+
+  // common[i][j] is the length of the longest common subsequence of
+  // before[i..] and after[j..].
+  let mut common = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+  for i in (0..before.len()).rev() {
+    for j in (0..after.len()).rev() {
+      common[i][j] = if before[i] == after[j] {
+        common[i + 1][j + 1] + 1
+      } else {
+        common[i + 1][j].max(common[i][j + 1])
+      };
+    }
+  }
+
+  let mut lines = Vec::with_capacity(before.len().max(after.len()));
+  let (mut i, mut j) = (0, 0);
+  while i < before.len() && j < after.len() {
+    if before[i] == after[j] {
+      lines.push(DiffLine::Unchanged(before[i]));
+      i += 1;
+      j += 1;
+    } else if common[i + 1][j] >= common[i][j + 1] {
+      lines.push(DiffLine::Removed(before[i]));
+      i += 1;
+    } else {
+      lines.push(DiffLine::Added(after[j]));
+      j += 1;
+    }
+  }
+  lines.extend(before[i..].iter().map(|line| DiffLine::Removed(line)));
+  lines.extend(after[j..].iter().map(|line| DiffLine::Added(line)));
+  lines
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{diff_lines, DiffLine};
+
+  #[test]
+  fn it_should_diff_changed_added_and_removed_lines() {
+    let before = "[compiler]\ncmd = \"solc\"\n\n[deployment]\ngas_price = 1\n";
+    let after = "[compiler]\n\n[deployment]\ngas_price = 2\ntx_confirmations = 3\n";
+
+    assert_eq!(diff_lines(before, after), vec![
+      DiffLine::Unchanged("[compiler]"),
+      DiffLine::Removed("cmd = \"solc\""),
+      DiffLine::Unchanged(""),
+      DiffLine::Unchanged("[deployment]"),
+      DiffLine::Removed("gas_price = 1"),
+      DiffLine::Added("gas_price = 2"),
+      DiffLine::Added("tx_confirmations = 3"),
+    ]);
+    assert!(diff_lines(before, before).iter().all(|line| !line.is_change()));
+  }
+}
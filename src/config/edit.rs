@@ -0,0 +1,290 @@
+// Edits of single options in the text of a TOML file, so that comments,
+// ordering and formatting of everything else stay the way they've been
+// written. Only options of plain tables are supported, edits that would
+// require changing arrays of tables, inline tables or dotted keys yield
+// `None`, and callers are expected to fall back to serializing the whole
+// document.
+
+#[derive(Debug)]
+struct Entry {
+  path: Vec<String>,
+  first_line: usize,
+  last_line: usize,
+  // Byte range of the value within the first line, if it doesn't span
+  // multiple lines.
+  value: Option<(usize, usize)>,
+}
+
+#[derive(Debug)]
+struct Table {
+  path: Option<Vec<String>>,
+  header_line: Option<usize>,
+  last_line: usize,
+  indentation: Option<String>,
+}
+
+#[derive(Debug)]
+struct Document<'a> {
+  lines: Vec<&'a str>,
+  entries: Vec<Entry>,
+  // The root table comes first, followed by the tables in the order their
+  // headers appear in.
+  tables: Vec<Table>,
+}
+
+pub fn set(content: &str, option: &str, value: &toml::Value) -> Option<String> {
+  let path = split_option(option)?;
+  let formatted = format_value(value)?;
+  let document = Document::parse(content)?;
+  let mut lines: Vec<String> = document.lines.iter().map(|line| line.to_string()).collect();
+
+  if let Some(entry) = document.entries.iter().find(|entry| entry.path == path) {
+    let line = document.lines[entry.first_line];
+    match entry.value {
+      Some((start, end)) => lines[entry.first_line] = format!("{}{}{}", &line[..start], formatted, &line[end..]),
+      None => {
+        let key = &line[..line.find('=')? + 1];
+        lines.splice(entry.first_line..=entry.last_line, vec![format!("{} {}", key, formatted)]);
+      }
+    }
+    return Some(join(lines, content));
+  }
+
+  let (parent, key) = path.split_at(path.len() - 1);
+  if !is_bare_key(&key[0]) {
+    return None;
+  }
+
+  match document.tables.iter().find(|table| table.path.as_deref() == Some(parent)) {
+    Some(table) => {
+      let indentation = table.indentation.clone().unwrap_or_default();
+      let line = format!("{}{} = {}", indentation, key[0], formatted);
+      match (table.header_line, table.last_line) {
+        (None, 0) if document.entries.iter().all(|entry| entry.first_line != 0) => lines.insert(0, line),
+        (_, last_line) => lines.insert(last_line + 1, line),
+      }
+    },
+    None if parent.iter().all(|key| is_bare_key(key)) => {
+      if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+        lines.push(String::new());
+      }
+      lines.push(format!("[{}]", parent.join(".")));
+      lines.push(format!("{} = {}", key[0], formatted));
+    },
+    None => return None,
+  }
+  Some(join(lines, content))
+}
+
+pub fn remove(content: &str, option: &str) -> Option<String> {
+  let path = split_option(option)?;
+  let document = Document::parse(content)?;
+  let mut lines: Vec<String> = document.lines.iter().map(|line| line.to_string()).collect();
+
+  if let Some(entry) = document.entries.iter().find(|entry| entry.path == path) {
+    lines.drain(entry.first_line..=entry.last_line);
+  }
+  Some(join(lines, content))
+}
+
+impl<'a> Document<'a> {
+  fn parse(content: &'a str) -> Option<Document<'a>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut entries = vec![];
+    let mut tables = vec![Table { path: Some(vec![]), header_line: None, last_line: 0, indentation: None }];
+    let mut index = 0;
+
+    while index < lines.len() {
+      let line = lines[index];
+      let trimmed = line.trim();
+
+      if trimmed.is_empty() || trimmed.starts_with('#') {
+        index += 1;
+        continue;
+      }
+
+      if trimmed.starts_with("[[") {
+        tables.push(Table { path: None, header_line: Some(index), last_line: index, indentation: None });
+      } else if trimmed.starts_with('[') {
+        let end = trimmed.find(']')?;
+        tables.push(Table { path: Some(split_key(&trimmed[1..end])?), header_line: Some(index), last_line: index, indentation: None });
+      } else {
+        let equals = find_outside_strings(line, '=')?;
+        let key = split_key(&line[..equals])?;
+        let value_start = equals + 1 + (line[equals + 1..].len() - line[equals + 1..].trim_start().len());
+        let last_line = value_end_line(&lines, index, value_start)?;
+        let value = if last_line == index {
+          let rest = &line[value_start..];
+          let comment = find_outside_strings(rest, '#').unwrap_or(rest.len());
+          Some((value_start, value_start + rest[..comment].trim_end().len()))
+        } else {
+          None
+        };
+
+        let table = tables.last_mut()?;
+        if table.indentation.is_none() {
+          table.indentation = Some(line[..line.len() - line.trim_start().len()].to_string());
+        }
+        if let Some(table_path) = &table.path {
+          entries.push(Entry {
+            path: table_path.iter().cloned().chain(key).collect(),
+            first_line: index,
+            last_line,
+            value,
+          });
+        }
+        table.last_line = last_line;
+        index = last_line;
+      }
+      index += 1;
+    }
+
+    Some(Document {
+      lines,
+      entries,
+      tables,
+    })
+  }
+}
+
+// Values of single line options end on the line they start on. Arrays and
+// multiline strings continue until their closing bracket or quotes.
+fn value_end_line(lines: &[&str], first_line: usize, value_start: usize) -> Option<usize> {
+  let value = &lines[first_line][value_start..];
+  for quotes in &["\"\"\"", "'''"] {
+    if let Some(rest) = value.strip_prefix(quotes) {
+      if rest.contains(quotes) {
+        return Some(first_line);
+      }
+      return (first_line + 1..lines.len()).find(|index| lines[*index].contains(quotes));
+    }
+  }
+
+  let mut depth = 0i32;
+  for (index, line) in lines.iter().enumerate().skip(first_line) {
+    let text = if index == first_line { value } else { line };
+    let mut quote = None;
+    for c in text.chars() {
+      match (quote, c) {
+        (None, '#') => break,
+        (None, '"') | (None, '\'') => quote = Some(c),
+        (Some(open), c) if c == open => quote = None,
+        (None, '[') | (None, '{') => depth += 1,
+        (None, ']') | (None, '}') => depth -= 1,
+        _ => {},
+      }
+    }
+    if depth <= 0 {
+      return Some(index);
+    }
+  }
+  None
+}
+
+fn find_outside_strings(text: &str, needle: char) -> Option<usize> {
+  let mut quote = None;
+  for (index, c) in text.char_indices() {
+    match (quote, c) {
+      (None, c) if c == needle => return Some(index),
+      (None, '"') | (None, '\'') => quote = Some(c),
+      (Some(open), c) if c == open => quote = None,
+      _ => {},
+    }
+  }
+  None
+}
+
+// Quoted keys containing dots aren't supported.
+fn split_key(key: &str) -> Option<Vec<String>> {
+  key.split('.')
+    .map(|part| {
+      let part = part.trim();
+      let unquoted = part.trim_matches('"').trim_matches('\'');
+      if unquoted.is_empty() { None } else { Some(unquoted.to_string()) }
+    })
+    .collect()
+}
+
+// Options are given as paths like `blockchain.connector.host`. Paths into
+// arrays, e.g. `deployment.smart_contracts.[0].name`, aren't supported.
+fn split_option(option: &str) -> Option<Vec<String>> {
+  option.split('.')
+    .map(|part| if part.is_empty() || part.starts_with('[') { None } else { Some(part.to_string()) })
+    .collect()
+}
+
+fn is_bare_key(key: &str) -> bool {
+  !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn format_value(value: &toml::Value) -> Option<String> {
+  match value {
+    toml::Value::Table(_) => None,
+    toml::Value::Array(values) if values.iter().any(|value| value.is_table()) => None,
+    value => Some(value.to_string()),
+  }
+}
+
+fn join(lines: Vec<String>, content: &str) -> String {
+  let mut joined = lines.join("\n");
+  if content.ends_with('\n') || content.is_empty() {
+    joined.push('\n');
+  }
+  joined
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{remove, set};
+
+  const CONTENT: &str = "# Project configuration
+[sources]
+  artifacts = \"artifacts\" # relative to the project
+  smart_contracts = [
+    \"contracts/*.sol\",
+  ]
+
+[blockchain.connector]
+  host = \"localhost\"
+  port = \"8545\"
+
+[[deployment.smart_contracts]]
+  name = \"Token\"
+";
+
+  #[test]
+  fn it_should_replace_values_in_place() {
+    let edited = set(CONTENT, "sources.artifacts", &toml::Value::String("build".to_string())).unwrap();
+    assert_eq!(edited, CONTENT.replace("artifacts = \"artifacts\" #", "artifacts = \"build\" #"));
+
+    let edited = set(CONTENT, "sources.smart_contracts", &toml::Value::Array(vec![toml::Value::String("src/*.sol".to_string())])).unwrap();
+    assert_eq!(edited, CONTENT.replace("smart_contracts = [\n    \"contracts/*.sol\",\n  ]", "smart_contracts = [\"src/*.sol\"]"));
+  }
+
+  #[test]
+  fn it_should_add_options_to_existing_and_new_tables() {
+    let edited = set(CONTENT, "blockchain.connector.protocol", &toml::Value::String("ws".to_string())).unwrap();
+    assert_eq!(edited, CONTENT.replace("  port = \"8545\"\n", "  port = \"8545\"\n  protocol = \"ws\"\n"));
+
+    let edited = set(CONTENT, "compiler.cmd", &toml::Value::String("solcjs".to_string())).unwrap();
+    assert_eq!(edited, format!("{}\n[compiler]\ncmd = \"solcjs\"\n", CONTENT));
+
+    let edited = set(CONTENT, "stats", &toml::Value::Boolean(true)).unwrap();
+    assert_eq!(edited, format!("stats = true\n{}", CONTENT));
+  }
+
+  #[test]
+  fn it_should_remove_options() {
+    let edited = remove(CONTENT, "sources.smart_contracts").unwrap();
+    assert_eq!(edited, CONTENT.replace("  smart_contracts = [\n    \"contracts/*.sol\",\n  ]\n", ""));
+    assert_eq!(remove(CONTENT, "compiler.cmd").unwrap(), CONTENT);
+  }
+
+  #[test]
+  fn it_should_leave_arrays_of_tables_alone() {
+    assert!(set(CONTENT, "deployment.smart_contracts.[0].name", &toml::Value::String("Coin".to_string())).is_none());
+    assert!(set(CONTENT, "deployment.smart_contracts", &toml::Value::Array(vec![])).is_some());
+    assert!(toml::from_str::<toml::Value>(&set(CONTENT, "blockchain.connector", &toml::Value::String("x".to_string())).unwrap()).is_err());
+  }
+}
@@ -12,6 +12,9 @@ pub enum ConfigError {
   Query(toml_query::error::Error),
   Deletion(toml_query::error::Error),
   Io(io::Error),
+  UnknownProfile(String),
+  UnknownNetwork(String),
+  InvalidNetworkUrl(String),
   Other(String),
 }
 
@@ -23,6 +26,9 @@ impl Error for ConfigError {
       ConfigError::Query(_error) => None,
       ConfigError::Deletion(_error) => None,
       ConfigError::Io(error) => Some(error),
+      ConfigError::UnknownProfile(_profile) => None,
+      ConfigError::UnknownNetwork(_network) => None,
+      ConfigError::InvalidNetworkUrl(_url) => None,
       ConfigError::Other(_message) => None,
     }
   }
@@ -36,6 +42,9 @@ impl fmt::Display for ConfigError {
       ConfigError::Query(error) => write!(f, "Couldn't query configuration: {}", error),
       ConfigError::Deletion(error) => write!(f, "{}", error),
       ConfigError::Io(error) => write!(f, "Couldn't access configuration file: {}", error),
+      ConfigError::UnknownProfile(profile) => write!(f, "Couldn't find profile {} in vibranium config", profile),
+      ConfigError::UnknownNetwork(network) => write!(f, "Couldn't find network {} in vibranium config", network),
+      ConfigError::InvalidNetworkUrl(url) => write!(f, "Invalid network url '{}'. Expected an http(s):// or ws(s):// URL", url),
       ConfigError::Other(message) => write!(f, "{}", message),
     }
   }
@@ -1,4 +1,6 @@
+pub mod diff;
 pub mod error;
+mod edit;
 
 use crate::blockchain;
 use crate::compiler;
@@ -7,11 +9,15 @@ use crate::project_generator;
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::default::Default;
+use std::collections::BTreeMap;
 use toml;
 use toml_query::set::TomlValueSetExt;
 use toml_query::delete::TomlValueDeleteExt;
 use toml_query::insert::TomlValueInsertExt;
+use toml_query::read::TomlValueReadExt;
 use toml_query::error::Error::IdentifierNotFoundInDocument;
 use blockchain::connector::BlockchainConnectorConfig;
 use project_generator::VIBRANIUM_PROJECT_DIRECTORY;
@@ -19,22 +25,42 @@ use project_generator::VIBRANIUM_PROJECT_DIRECTORY;
 pub const VIBRANIUM_CONFIG_FILE: &str = "vibranium.toml";
 pub const DEFAULT_ARTIFACTS_DIRECTORY: &str = "artifacts";
 pub const DEFAULT_CONTRACTS_DIRECTORY: &str = "contracts";
+pub const PROFILE_ENV_VARIABLE: &str = "VIBRANIUM_PROFILE";
+pub const CONFIG_BACKUPS_DIRECTORY: &str = "backups";
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ProjectConfig {
+  pub read_only: Option<bool>,
+  pub package_manager: Option<String>,
+  pub stats: Option<bool>,
   pub sources: ProjectSourcesConfig,
   pub compiler: Option<ProjectCmdExecutionConfig>,
   pub blockchain: Option<ProjectBlockchainConfig>,
   pub deployment: Option<ProjectDeploymentConfig>,
+  pub constants: Option<BTreeMap<String, String>>,
+  pub profile: Option<BTreeMap<String, toml::Value>>,
+  pub networks: Option<BTreeMap<String, NetworkConfig>>,
+  pub verification: Option<ProjectVerificationConfig>,
+  // Names of addresses, e.g. of accounts, shown in outputs instead of bare
+  // addresses.
+  pub aliases: Option<BTreeMap<String, String>>,
 }
 
 impl Default for ProjectConfig {
   fn default() -> Self {
     ProjectConfig {
+      read_only: None,
+      package_manager: None,
+      stats: None,
       sources: ProjectSourcesConfig::default(),
       compiler: Some(ProjectCmdExecutionConfig::default()),
       blockchain: Some(ProjectBlockchainConfig::default()),
-      deployment: None
+      deployment: None,
+      constants: None,
+      profile: None,
+      networks: None,
+      verification: None,
+      aliases: None,
     }
   }
 }
@@ -42,18 +68,37 @@ impl Default for ProjectConfig {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ProjectCmdExecutionConfig {
   pub cmd: Option<String>,
-  pub options: Option<Vec<String>>
+  pub options: Option<Vec<String>>,
+  pub max_size_enforcement: Option<String>,
+  pub artifact_format: Option<String>,
+  pub max_size: Option<BTreeMap<String, usize>>,
+  pub checks: Option<BTreeMap<String, String>>,
+  pub warnings: Option<CompilerWarningsConfig>,
+  pub version: Option<String>,
 }
 
 impl Default for ProjectCmdExecutionConfig {
   fn default() -> Self {
     ProjectCmdExecutionConfig {
       cmd: Some(compiler::support::SupportedCompilers::Solc.to_string()),
-      options: Some(compiler::support::default_options_from(compiler::support::SupportedCompilers::Solc))
+      options: Some(compiler::support::default_options_from(compiler::support::SupportedCompilers::Solc)),
+      max_size_enforcement: None,
+      artifact_format: None,
+      max_size: None,
+      checks: None,
+      warnings: None,
+      version: None,
     }
   }
 }
 
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct CompilerWarningsConfig {
+  pub deny_warnings: Option<bool>,
+  pub errors: Option<Vec<u32>>,
+  pub silence: Option<Vec<u32>>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ProjectBlockchainConfig {
   pub cmd: Option<String>,
@@ -86,16 +131,41 @@ impl Default for ProjectSourcesConfig {
   }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ProjectDeploymentConfig {
   pub tx_confirmations: Option<usize>,
   pub gas_price: Option<usize>,
   pub gas_limit: Option<usize>,
   pub tracking_enabled: Option<bool>,
+  pub policy: Option<String>,
+  pub tracking_key: Option<String>,
+  pub store: Option<String>,
+  pub fee_bumping: Option<blockchain::sender::FeeBumpingConfig>,
+  pub require_tests: Option<bool>,
+  pub require_tests_filter: Option<String>,
+  pub test_cmd: Option<String>,
+  pub test_options: Option<Vec<String>>,
+  #[serde(default)]
   pub smart_contracts: Vec<SmartContractConfig>,
+  pub contracts: Option<Vec<PlannedSmartContractConfig>>,
 }
 
+// Smart Contracts of a deployment plan, which are deployed in the order they're
+// listed in, either in `deployment.toml` or as `[[deployment.contracts]]`.
 #[derive(Serialize, Deserialize, Debug)]
+pub struct DeploymentPlanConfig {
+  pub contracts: Vec<PlannedSmartContractConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlannedSmartContractConfig {
+  #[serde(flatten)]
+  pub smart_contract: SmartContractConfig,
+  pub depends_on: Option<Vec<String>>,
+  pub redeploy: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct SmartContractConfig {
   pub name: String,
   pub address: Option<String>,
@@ -105,19 +175,94 @@ pub struct SmartContractConfig {
   pub instance_of: Option<String>,
   pub abi_path: Option<String>,
   pub bytecode_path: Option<String>,
+  pub tags: Option<Vec<String>>,
+  pub proxy: Option<ProxyConfig>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+// Deploys the Smart Contract as the implementation behind a proxy, built from
+// the given artifact.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProxyConfig {
+  pub contract: String,
+  pub kind: Option<String>,
+  pub initializer: Option<String>,
+  pub initializer_args: Option<Vec<SmartContractArg>>,
+}
+
+impl SmartContractConfig {
+  // Whether the Smart Contract is tagged with any of the given tags.
+  pub fn has_any_tag(&self, tags: &[String]) -> bool {
+    self.tags.iter().flatten().any(|tag| tags.contains(tag))
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SmartContractArg {
   pub value: String,
   pub kind: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NetworkConfig {
+  pub url: String,
+  pub chain_id: Option<u64>,
+  pub accounts: Option<Vec<String>>,
+  pub gas_price: Option<usize>,
+  pub gas_limit: Option<usize>,
+  pub explorer: Option<String>,
+  pub receipts: Option<blockchain::connector::polling::ReceiptPollingConfig>,
+  pub aliases: Option<BTreeMap<String, String>>,
+  // Replace the `[constants]` of the same name on this network, e.g. for
+  // addresses that differ between chains.
+  pub constants: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ProjectVerificationConfig {
+  pub etherscan_api_key: Option<String>,
+  pub etherscan_url: Option<String>,
+  pub sourcify_url: Option<String>,
+  pub compiler_version: Option<String>,
+  pub poll_interval: Option<u64>,
+  pub timeout: Option<u64>,
+}
+
+#[derive(Default, Debug, Clone, Copy)]
+pub struct WriteOptions {
+  // Computes the resulting configuration file without writing it.
+  pub dry_run: bool,
+  // Copies the configuration file to `.vibranium/backups` before writing it.
+  pub backup: bool,
+}
+
+// The content of the configuration file before and after a change.
+#[derive(Debug)]
+pub struct ConfigChange {
+  pub before: String,
+  pub after: String,
+  pub backup: Option<PathBuf>,
+}
+
+impl ConfigChange {
+  pub fn is_empty(&self) -> bool {
+    self.before == self.after
+  }
+}
+
 #[derive(Default, Debug)]
 pub struct Config {
   pub project_path: PathBuf,
   pub vibranium_dir_path: PathBuf,
   pub config_file: PathBuf,
+  pub profile: Option<String>,
+  pub network: Option<String>,
+  cache: Option<ConfigCache>,
+}
+
+// The parsed vibranium.toml, reused for as long as its contents don't change.
+#[derive(Default, Debug)]
+struct ConfigCache {
+  parsed: Mutex<Option<(String, toml::Value)>>,
 }
 
 impl Config {
@@ -125,19 +270,85 @@ impl Config {
     Config {
       project_path: path.clone(),
       vibranium_dir_path: path.clone().join(VIBRANIUM_PROJECT_DIRECTORY),
-      config_file: path.join(VIBRANIUM_CONFIG_FILE)
+      config_file: path.join(VIBRANIUM_CONFIG_FILE),
+      profile: None,
+      network: None,
+      cache: None,
     }
   }
 
+  // Long-running processes, like the daemon, keep the parsed configuration
+  // in memory instead of parsing it on every read.
+  pub fn with_cache(mut self, enabled: bool) -> Config {
+    self.cache = if enabled { Some(ConfigCache::default()) } else { None };
+    self
+  }
+
   pub fn exists(&self) -> bool {
     self.config_file.exists()
   }
 
+  // Options of the selected profile (`[profile.<name>]`) take precedence over
+  // the ones at the top level of vibranium.toml. The selected network
+  // (`[networks.<name>]`) then determines the connector and gas defaults.
   pub fn read(&self) -> Result<ProjectConfig, error::ConfigError> {
-    toml::from_str(&fs::read_to_string(&self.config_file)?).map_err(error::ConfigError::Deserialization)
+    if self.profile.is_none() && self.network.is_none() {
+      return self.read_without_profile();
+    }
+
+    let mut config = self.parse()?;
+
+    if let Some(profile) = &self.profile {
+      let overrides = config.get("profile")
+        .and_then(|profiles| profiles.get(profile))
+        .cloned()
+        .ok_or_else(|| error::ConfigError::UnknownProfile(profile.to_owned()))?;
+      merge_values(&mut config, overrides);
+    }
+
+    if let Some(network) = &self.network {
+      apply_network(&mut config, network)?;
+    }
+
+    validate_networks(config.try_into().map_err(error::ConfigError::Deserialization)?)
+  }
+
+  pub fn read_network(&self) -> Result<Option<(String, NetworkConfig)>, error::ConfigError> {
+    let network = match &self.network {
+      Some(network) => network,
+      None => return Ok(None),
+    };
+    self.read()?.networks
+      .and_then(|mut networks| networks.remove(network))
+      .map(|network_config| Some((network.to_owned(), network_config)))
+      .ok_or_else(|| error::ConfigError::UnknownNetwork(network.to_owned()))
+  }
+
+  fn read_without_profile(&self) -> Result<ProjectConfig, error::ConfigError> {
+    validate_networks(self.parse()?.try_into().map_err(error::ConfigError::Deserialization)?)
+  }
+
+  fn parse(&self) -> Result<toml::Value, error::ConfigError> {
+    let contents = fs::read_to_string(&self.config_file)?;
+    let mut parsed = match self.cache.as_ref().and_then(|cache| cache.parsed.lock().ok()) {
+      Some(parsed) => parsed,
+      None => return Ok(toml::from_str(&contents)?),
+    };
+    if let Some((cached_contents, value)) = parsed.as_ref() {
+      if *cached_contents == contents {
+        return Ok(value.clone());
+      }
+    }
+    let value: toml::Value = toml::from_str(&contents)?;
+    *parsed = Some((contents, value.clone()));
+    Ok(value)
   }
 
   pub fn write(&self, option: String, value: toml::Value) -> Result<(), error::ConfigError> {
+    self.write_with(option, value, WriteOptions::default()).map(|_change| ())
+  }
+
+  pub fn write_with(&self, option: String, value: toml::Value, options: WriteOptions) -> Result<ConfigChange, error::ConfigError> {
     let mut config = self.try_from_config_file()?;
 
     if let Err(err) = config.set(&option, value.clone()) {
@@ -149,18 +360,19 @@ impl Config {
       }
     }
 
-    config.try_into::<ProjectConfig>()
-      .map_err(error::ConfigError::Deserialization)
-      .and_then(|cfg| {
-        let config_toml = toml::to_string(&cfg)?;
-        let mut config_file = fs::File::create(&self.config_file)?;
-        config_file.write_all(config_toml.as_bytes()).map_err(error::ConfigError::Io)
-      })?;
-
-    Ok(())
+    // Unsupported options are dropped when the configuration is written, so
+    // they aren't added to the file either.
+    self.try_into_config_file(config, options, |written, content| match written.read(&option) {
+      Ok(Some(_value)) => edit::set(content, &option, &value),
+      _ => Some(content.to_string()),
+    })
   }
 
   pub fn remove(&self, option: String) -> Result<(), error::ConfigError> {
+    self.remove_with(option, WriteOptions::default()).map(|_change| ())
+  }
+
+  pub fn remove_with(&self, option: String, options: WriteOptions) -> Result<ConfigChange, error::ConfigError> {
     let mut config = self.try_from_config_file()?;
 
     if let Err(err) = config.delete(&option) {
@@ -172,22 +384,374 @@ impl Config {
       }
     }
 
-    self.try_into_config_file(config.clone())?;
-    Ok(())
+    self.try_into_config_file(config, options, |_written, content| edit::remove(content, &option))
+  }
+
+  // Copies the configuration file to `.vibranium/backups`, named after the
+  // time it's been backed up at.
+  pub fn backup(&self) -> Result<PathBuf, error::ConfigError> {
+    let backups_dir = self.vibranium_dir_path.join(CONFIG_BACKUPS_DIRECTORY);
+    fs::create_dir_all(&backups_dir)?;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+    let mut backup = backups_dir.join(format!("vibranium-{}.toml", timestamp));
+    let mut count = 1;
+    while backup.exists() {
+      backup = backups_dir.join(format!("vibranium-{}-{}.toml", timestamp, count));
+      count += 1;
+    }
+    fs::copy(&self.config_file, &backup)?;
+    Ok(backup)
   }
 
   fn try_from_config_file(&self) -> Result<toml::Value, error::ConfigError> {
-    toml::Value::try_from(self.read()?).map_err(error::ConfigError::Serialization)
+    toml::Value::try_from(self.read_without_profile()?).map_err(error::ConfigError::Serialization)
+  }
+
+  // Edits are applied to the text of the configuration file, keeping comments
+  // and formatting, as long as the edited file reads as `config`. Otherwise
+  // the whole file is serialized again.
+  fn try_into_config_file<F>(&self, config: toml::Value, options: WriteOptions, edit: F) -> Result<ConfigChange, error::ConfigError>
+    where F: FnOnce(&toml::Value, &str) -> Option<String> {
+    let cfg = config.try_into::<ProjectConfig>().map_err(error::ConfigError::Deserialization)?;
+    let written = toml::Value::try_from(&cfg).map_err(error::ConfigError::Serialization)?;
+    let before = fs::read_to_string(&self.config_file)?;
+    let edited = edit(&written, &before).filter(|edited| {
+      toml::from_str::<ProjectConfig>(edited).ok()
+        .and_then(|edited_config| toml::Value::try_from(edited_config).ok())
+        .is_some_and(|edited_config| edited_config == written)
+    });
+
+    let after = match edited {
+      Some(edited) => edited,
+      None => toml::to_string(&cfg)?,
+    };
+    let mut change = ConfigChange {
+      before,
+      after,
+      backup: None,
+    };
+    if options.dry_run {
+      return Ok(change);
+    }
+    if options.backup {
+      change.backup = Some(self.backup()?);
+    }
+    let mut config_file = fs::File::create(&self.config_file)?;
+    config_file.write_all(change.after.as_bytes()).map_err(error::ConfigError::Io)?;
+    Ok(change)
+  }
+}
+
+// The connector is replaced as a whole, as e.g. fallbacks of the default
+// connector don't belong to any other network.
+fn apply_network(config: &mut toml::Value, network: &str) -> Result<(), error::ConfigError> {
+  let network_config: NetworkConfig = config.get("networks")
+    .and_then(|networks| networks.get(network))
+    .cloned()
+    .ok_or_else(|| error::ConfigError::UnknownNetwork(network.to_owned()))?
+    .try_into()?;
+
+  let (protocol, host, port) = parse_network_url(&network_config.url)?;
+  let mut connector = toml::value::Table::new();
+  connector.insert("protocol".to_string(), toml::Value::String(protocol));
+  connector.insert("host".to_string(), toml::Value::String(host));
+  connector.insert("port".to_string(), toml::Value::String(port));
+  connector.insert("url".to_string(), toml::Value::String(network_config.url.to_owned()));
+  if let Some(receipts) = &network_config.receipts {
+    connector.insert("receipts".to_string(), toml::Value::try_from(receipts)?);
+  }
+
+  if let toml::Value::Table(config) = config {
+    let blockchain = config.entry("blockchain".to_string()).or_insert_with(|| toml::Value::Table(toml::value::Table::new()));
+    if let toml::Value::Table(blockchain) = blockchain {
+      blockchain.insert("connector".to_string(), toml::Value::Table(connector));
+    }
+
+    if let Some(network_constants) = &network_config.constants {
+      let constants = config.entry("constants".to_string()).or_insert_with(|| toml::Value::Table(toml::value::Table::new()));
+      if let toml::Value::Table(constants) = constants {
+        for (name, value) in network_constants {
+          constants.insert(name.to_owned(), toml::Value::String(value.to_owned()));
+        }
+      }
+    }
+
+    if let Some(toml::Value::Table(deployment)) = config.get_mut("deployment") {
+      if let Some(gas_price) = network_config.gas_price {
+        deployment.insert("gas_price".to_string(), toml::Value::Integer(gas_price as i64));
+      }
+      if let Some(gas_limit) = network_config.gas_limit {
+        deployment.insert("gas_limit".to_string(), toml::Value::Integer(gas_limit as i64));
+      }
+    }
+  }
+  Ok(())
+}
+
+// All networks are checked, not only the selected one, so that mistakes
+// surface before switching to them, e.g. right before deploying to mainnet.
+fn validate_networks(config: ProjectConfig) -> Result<ProjectConfig, error::ConfigError> {
+  for network_config in config.networks.iter().flat_map(|networks| networks.values()) {
+    parse_network_url(&network_config.url)?;
   }
+  Ok(config)
+}
 
-  fn try_into_config_file(&self, config: toml::Value) -> Result<(), error::ConfigError> {
-    config.try_into::<ProjectConfig>()
-      .map_err(error::ConfigError::Deserialization)
-      .and_then(|cfg| {
-        let config_toml = toml::to_string(&cfg)?;
-        let mut config_file = fs::File::create(&self.config_file)?;
-        config_file.write_all(config_toml.as_bytes()).map_err(error::ConfigError::Io)
-      })
+// The URL is passed to the connector as it is. Protocol, host and port are
+// derived from it, e.g. to tell whether the network is local, with the
+// default port of the scheme if it doesn't have one.
+pub fn parse_network_url(url: &str) -> Result<(String, String, String), error::ConfigError> {
+  let invalid = || error::ConfigError::InvalidNetworkUrl(url.to_owned());
+  let (scheme, rest) = url.split_once("://").ok_or_else(invalid)?;
+  let (protocol, default_port) = match scheme {
+    "http" => (blockchain::connector::SupportedProtocols::Rpc, "80"),
+    "https" => (blockchain::connector::SupportedProtocols::Rpc, "443"),
+    "ws" => (blockchain::connector::SupportedProtocols::Ws, "80"),
+    "wss" => (blockchain::connector::SupportedProtocols::Ws, "443"),
+    _ => return Err(invalid()),
+  };
+  let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+  let authority = authority.rsplit_once('@').map(|(_credentials, authority)| authority).unwrap_or(authority);
+  // IPv6 addresses contain colons themselves, so they are enclosed in
+  // brackets which are kept as part of the host.
+  let (host, port) = match authority.strip_prefix('[') {
+    Some(address) => {
+      let (address, port) = address.split_once(']').ok_or_else(invalid)?;
+      let port = if port.is_empty() { default_port } else { port.strip_prefix(':').ok_or_else(invalid)? };
+      (&authority[..address.len() + 2], port)
+    },
+    None => authority.rsplit_once(':').unwrap_or((authority, default_port)),
+  };
+  if host.is_empty() || port.parse::<u16>().is_err() {
+    return Err(invalid());
   }
+  Ok((protocol.to_string(), host.to_owned(), port.to_owned()))
 }
 
+// Tables are merged recursively, all other values (including arrays) are
+// replaced.
+fn merge_values(base: &mut toml::Value, overrides: toml::Value) {
+  match (base, overrides) {
+    (toml::Value::Table(base), toml::Value::Table(overrides)) => {
+      for (key, value) in overrides {
+        match base.get_mut(&key) {
+          Some(existing) => merge_values(existing, value),
+          None => { base.insert(key, value); },
+        }
+      }
+    },
+    (base, overrides) => *base = overrides,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{Config, WriteOptions};
+  use std::fs;
+
+  const CONFIG: &str = r#"
+[sources]
+artifacts = "artifacts"
+smart_contracts = ["contracts/*.sol"]
+
+[compiler]
+cmd = "solc"
+options = ["--abi", "--bin"]
+
+[deployment]
+tx_confirmations = 0
+gas_price = 1
+smart_contracts = []
+
+[profile.release.compiler]
+options = ["--abi", "--bin", "--optimize"]
+
+[profile.release.deployment]
+tx_confirmations = 12
+"#;
+
+  fn setup_config(profile: Option<&str>) -> (tempfile::TempDir, Config) {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    fs::write(tmp_dir.path().join("vibranium.toml"), CONFIG).unwrap();
+    let mut config = Config::new(tmp_dir.path().to_path_buf());
+    config.profile = profile.map(str::to_string);
+    (tmp_dir, config)
+  }
+
+  #[test]
+  fn it_should_apply_selected_profile() {
+    let (_tmp_dir, config) = setup_config(Some("release"));
+    let project_config = config.read().unwrap();
+
+    let compiler = project_config.compiler.unwrap();
+    let deployment = project_config.deployment.unwrap();
+    assert_eq!(compiler.cmd, Some("solc".to_string()));
+    assert_eq!(compiler.options, Some(vec!["--abi".to_string(), "--bin".to_string(), "--optimize".to_string()]));
+    assert_eq!(deployment.tx_confirmations, Some(12));
+    assert_eq!(deployment.gas_price, Some(1));
+  }
+
+  #[test]
+  fn it_should_ignore_profiles_unless_selected() {
+    let (_tmp_dir, config) = setup_config(None);
+    let project_config = config.read().unwrap();
+
+    assert_eq!(project_config.deployment.unwrap().tx_confirmations, Some(0));
+  }
+
+  #[test]
+  fn it_should_fail_on_unknown_profile() {
+    let (_tmp_dir, config) = setup_config(Some("staging"));
+    assert!(config.read().is_err());
+  }
+
+  #[test]
+  fn it_should_pick_up_changes_to_cached_config() {
+    let (_tmp_dir, config) = setup_config(Some("release"));
+    let config = config.with_cache(true);
+    assert_eq!(config.read().unwrap().deployment.unwrap().tx_confirmations, Some(12));
+
+    config.write("profile.release.deployment.tx_confirmations".to_string(), toml::Value::Integer(3)).unwrap();
+    assert_eq!(config.read().unwrap().deployment.unwrap().tx_confirmations, Some(3));
+  }
+
+  #[test]
+  fn it_should_keep_profiles_when_writing_config() {
+    let (_tmp_dir, config) = setup_config(Some("release"));
+    config.write("deployment.gas_price".to_string(), toml::Value::Integer(2)).unwrap();
+
+    let project_config = config.read().unwrap();
+    let deployment = project_config.deployment.unwrap();
+    assert_eq!(deployment.gas_price, Some(2));
+    assert_eq!(deployment.tx_confirmations, Some(12));
+    assert!(project_config.profile.unwrap().contains_key("release"));
+  }
+
+  #[test]
+  fn it_should_keep_comments_when_writing_config() {
+    let (tmp_dir, config) = setup_config(None);
+    let config_file = tmp_dir.path().join("vibranium.toml");
+    fs::write(&config_file, CONFIG.replace("gas_price = 1", "gas_price = 1 # in wei")).unwrap();
+
+    config.write("deployment.gas_price".to_string(), toml::Value::Integer(2)).unwrap();
+    config.write("compiler.unsupported".to_string(), toml::Value::Integer(2)).unwrap();
+    config.remove("compiler.cmd".to_string()).unwrap();
+
+    let expected = CONFIG.replace("gas_price = 1", "gas_price = 2 # in wei").replace("cmd = \"solc\"\n", "");
+    assert_eq!(fs::read_to_string(&config_file).unwrap(), expected);
+  }
+
+  #[test]
+  fn it_should_only_write_config_changes_unless_dry_run() {
+    let (tmp_dir, config) = setup_config(None);
+    let config_file = tmp_dir.path().join("vibranium.toml");
+
+    let dry_run = WriteOptions { dry_run: true, backup: true };
+    let change = config.write_with("deployment.gas_price".to_string(), toml::Value::Integer(2), dry_run).unwrap();
+    assert_eq!(change.after, CONFIG.replace("gas_price = 1", "gas_price = 2"));
+    assert!(change.backup.is_none());
+    assert_eq!(fs::read_to_string(&config_file).unwrap(), CONFIG);
+
+    let backup = WriteOptions { dry_run: false, backup: true };
+    let change = config.remove_with("compiler.cmd".to_string(), backup).unwrap();
+    assert_eq!(fs::read_to_string(change.backup.unwrap()).unwrap(), CONFIG);
+    assert_eq!(fs::read_to_string(&config_file).unwrap(), CONFIG.replace("cmd = \"solc\"\n", ""));
+  }
+
+  const NETWORKS: &str = r#"
+[blockchain.connector]
+protocol = "rpc"
+host = "localhost"
+port = "8545"
+fallbacks = ["http://localhost:8546"]
+
+[constants]
+WETH = "0x0000000000000000000000000000000000000001"
+DAI = "0x0000000000000000000000000000000000000002"
+
+[networks.sepolia.constants]
+WETH = "0x0000000000000000000000000000000000000003"
+
+[networks.sepolia]
+url = "ws://sepolia.example.org:8546"
+chain_id = 11155111
+gas_price = 20
+
+[networks.sepolia.receipts]
+interval = 4000
+backoff = 1.5
+
+[networks.mainnet]
+url = "https://mainnet.example.org/v3/key"
+"#;
+
+  const BROKEN_NETWORK: &str = r#"
+[networks.broken]
+url = "ftp://mainnet.example.org"
+"#;
+
+  fn setup_network_config(network: &str) -> (tempfile::TempDir, Config) {
+    let (tmp_dir, mut config) = setup_config(None);
+    fs::write(tmp_dir.path().join("vibranium.toml"), format!("{}{}", CONFIG, NETWORKS)).unwrap();
+    config.network = Some(network.to_string());
+    (tmp_dir, config)
+  }
+
+  #[test]
+  fn it_should_apply_selected_network() {
+    let (_tmp_dir, config) = setup_network_config("sepolia");
+    let project_config = config.read().unwrap();
+
+    let connector = project_config.blockchain.unwrap().connector.unwrap();
+    assert_eq!((connector.protocol.as_str(), connector.host.as_str(), connector.port.as_str()), ("ws", "sepolia.example.org", "8546"));
+    assert!(connector.fallbacks.is_none());
+    let receipts = connector.receipts.unwrap();
+    assert_eq!((receipts.interval, receipts.backoff, receipts.max_wait), (Some(4000), Some(1.5), None));
+    assert_eq!(project_config.deployment.unwrap().gas_price, Some(20));
+    let constants = project_config.constants.unwrap();
+    assert_eq!(constants.get("WETH").map(String::as_str), Some("0x0000000000000000000000000000000000000003"));
+    assert_eq!(constants.get("DAI").map(String::as_str), Some("0x0000000000000000000000000000000000000002"));
+
+    let (name, network) = config.read_network().unwrap().unwrap();
+    assert_eq!(name, "sepolia");
+    assert_eq!(network.chain_id, Some(11155111));
+  }
+
+  #[test]
+  fn it_should_keep_the_full_url_of_hosted_networks() {
+    let (_tmp_dir, config) = setup_network_config("mainnet");
+    let connector = config.read().unwrap().blockchain.unwrap().connector.unwrap();
+
+    assert_eq!((connector.protocol.as_str(), connector.host.as_str(), connector.port.as_str()), ("rpc", "mainnet.example.org", "443"));
+    assert_eq!(connector.endpoint_url().unwrap(), "https://mainnet.example.org/v3/key");
+  }
+
+  #[test]
+  fn it_should_fail_on_unknown_network_or_invalid_url() {
+    let (_tmp_dir, config) = setup_network_config("goerli");
+    assert_eq!(config.read().unwrap_err().to_string(), "Couldn't find network goerli in vibranium config");
+
+    let (_tmp_dir, config) = setup_network_config("broken");
+    fs::write(&config.config_file, format!("{}{}{}", CONFIG, NETWORKS, BROKEN_NETWORK)).unwrap();
+    assert!(config.read().unwrap_err().to_string().starts_with("Invalid network url"));
+  }
+
+  #[test]
+  fn it_should_validate_networks_that_arent_selected() {
+    let (_tmp_dir, config) = setup_network_config("sepolia");
+    fs::write(&config.config_file, format!("{}{}{}", CONFIG, NETWORKS, BROKEN_NETWORK)).unwrap();
+    assert_eq!(config.read().unwrap_err().to_string(), "Invalid network url 'ftp://mainnet.example.org'. Expected an http(s):// or ws(s):// URL");
+
+    let (_tmp_dir, config) = setup_config(None);
+    fs::write(&config.config_file, format!("{}{}{}", CONFIG, NETWORKS, BROKEN_NETWORK)).unwrap();
+    assert!(config.read().is_err());
+  }
+
+  #[test]
+  fn it_should_parse_bracketed_ipv6_network_urls() {
+    assert_eq!(super::parse_network_url("http://[::1]").unwrap(), ("rpc".to_string(), "[::1]".to_string(), "80".to_string()));
+    assert_eq!(super::parse_network_url("ws://[::1]:8546/path").unwrap(), ("ws".to_string(), "[::1]".to_string(), "8546".to_string()));
+    assert!(super::parse_network_url("http://[::1]8545").is_err());
+  }
+}
@@ -0,0 +1,55 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum DaemonError {
+  AlreadyRunning(u32),
+  NotStarted(String),
+  Unresponsive(u32),
+  Unsupported,
+  InvalidMessage(String),
+  Failed(String),
+  Io(io::Error),
+}
+
+impl Error for DaemonError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      DaemonError::AlreadyRunning(_pid) => None,
+      DaemonError::NotStarted(_log_file) => None,
+      DaemonError::Unresponsive(_pid) => None,
+      DaemonError::Unsupported => None,
+      DaemonError::InvalidMessage(_message) => None,
+      DaemonError::Failed(_message) => None,
+      DaemonError::Io(error) => Some(error),
+    }
+  }
+}
+
+impl fmt::Display for DaemonError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      DaemonError::AlreadyRunning(pid) => write!(f, "A daemon is already running for this project (pid {}). Stop it with `vibranium daemon stop`", pid),
+      DaemonError::NotStarted(log_file) => write!(f, "Daemon didn't start, see {} for its output", log_file),
+      DaemonError::Unresponsive(pid) => write!(f, "Daemon (pid {}) doesn't respond", pid),
+      DaemonError::Unsupported => write!(f, "The daemon is only supported on Unix platforms"),
+      DaemonError::InvalidMessage(message) => write!(f, "Couldn't read daemon message: {}", message),
+      DaemonError::Failed(message) => write!(f, "{}", message),
+      DaemonError::Io(error) => write!(f, "Couldn't talk to daemon: {}", error),
+    }
+  }
+}
+
+impl From<io::Error> for DaemonError {
+  fn from(error: io::Error) -> Self {
+    DaemonError::Io(error)
+  }
+}
+
+impl From<serde_json::Error> for DaemonError {
+  fn from(error: serde_json::Error) -> Self {
+    DaemonError::InvalidMessage(error.to_string())
+  }
+}
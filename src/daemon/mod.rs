@@ -0,0 +1,438 @@
+// A long-running process serving compile and deploy requests of a project
+// over a Unix socket at `.vibranium/daemon.sock`. Requests are served by a
+// warm `Vibranium` per profile and network, which keeps the parsed project
+// configuration in memory. Neither solc nor solcjs can compile more than
+// once per process, so compilers are still run per request.
+//
+// The daemon of a project is tracked in `.vibranium/daemon.toml`, its output
+// goes to `.vibranium/daemon.log`.
+
+pub mod error;
+
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use crate::blockchain::supervisor::is_alive;
+use crate::compiler::CompilerConfig;
+use crate::config::Config;
+use crate::deployment::{DeployOptions, DeployedContracts};
+use crate::process::{unix_time, ProcessCommand};
+use crate::project_generator::ProjectGenerator;
+use crate::Vibranium;
+use error::DaemonError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use web3::types::Address;
+
+pub const DAEMON_SOCKET_FILE: &str = "daemon.sock";
+pub const DAEMON_STATE_FILE: &str = "daemon.toml";
+pub const DAEMON_LOG_FILE: &str = "daemon.log";
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DaemonState {
+  pub pid: u32,
+  pub started_at: u64,
+}
+
+impl DaemonState {
+  pub fn uptime(&self) -> u64 {
+    unix_time().saturating_sub(self.started_at)
+  }
+}
+
+// Everything about a request that determines the `Vibranium` serving it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Session {
+  pub profile: Option<String>,
+  pub network: Option<String>,
+  pub read_only: bool,
+}
+
+// Compilations and deployments are served in the environment of the client
+// that requested them.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "request", rename_all = "kebab-case")]
+pub enum DaemonRequest {
+  Compile { session: Session, config: CompilerConfig, #[serde(default)] environment: Option<BTreeMap<String, String>> },
+  Deploy { session: Session, options: DeployOptions, #[serde(default)] environment: Option<BTreeMap<String, String>> },
+  Status,
+  Shutdown,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "response", rename_all = "kebab-case")]
+pub enum DaemonResponse {
+  Compiled { stdout: String, stderr: String },
+  Deployed { smart_contracts: Vec<(Address, (String, Address, String, bool))> },
+  Status { state: DaemonState, requests: u64, sessions: usize },
+  ShuttingDown,
+  Failed { message: String },
+}
+
+pub struct Daemon {
+  project_path: PathBuf,
+  config: Config,
+  sessions: HashMap<Session, Vibranium>,
+  requests: u64,
+}
+
+impl Daemon {
+  pub fn new(project_path: PathBuf) -> Daemon {
+    Daemon {
+      config: Config::new(project_path.clone()),
+      project_path,
+      sessions: HashMap::new(),
+      requests: 0,
+    }
+  }
+
+  // Requests are served one at a time until the daemon is asked to shut
+  // down, so that compilations and deployments never run concurrently.
+  pub fn serve(&mut self) -> Result<(), DaemonError> {
+    ProjectGenerator::new(&self.config)
+      .check_vibranium_dir_exists()
+      .map_err(|err| DaemonError::Failed(err.to_string()))?;
+
+    let supervisor = DaemonSupervisor::new(&self.config);
+    if let Some(state) = supervisor.status()? {
+      return Err(DaemonError::AlreadyRunning(state.pid));
+    }
+    let (socket_file, state_file) = (supervisor.socket_file(), supervisor.state_file());
+
+    // Left behind by daemons that didn't shut down.
+    if socket_file.exists() {
+      fs::remove_file(&socket_file)?;
+    }
+    let result = self.listen(&socket_file, &state_file);
+    let _ = fs::remove_file(&socket_file);
+    let _ = fs::remove_file(&state_file);
+    result
+  }
+
+  #[cfg(unix)]
+  fn listen(&mut self, socket_file: &Path, state_file: &Path) -> Result<(), DaemonError> {
+    let listener = UnixListener::bind(socket_file)?;
+    let state = DaemonState {
+      pid: std::process::id(),
+      started_at: unix_time(),
+    };
+    fs::write(state_file, toml::to_string(&state).map_err(|err| DaemonError::InvalidMessage(err.to_string()))?)?;
+    info!("Daemon (pid {}) listening on {}", state.pid, socket_file.to_string_lossy());
+
+    for stream in listener.incoming() {
+      let mut stream = match stream {
+        Ok(stream) => stream,
+        Err(err) => {
+          warn!("Couldn't accept connection: {}", err);
+          continue;
+        }
+      };
+
+      let response = match read_message::<DaemonRequest>(&mut stream) {
+        Ok(request) => self.handle(&state, request),
+        Err(err) => DaemonResponse::Failed { message: err.to_string() },
+      };
+      let shutting_down = matches!(response, DaemonResponse::ShuttingDown);
+      if let Err(err) = write_message(&mut stream, &response) {
+        warn!("Couldn't respond to request: {}", err);
+      }
+      if shutting_down {
+        info!("Daemon shutting down");
+        break;
+      }
+    }
+    Ok(())
+  }
+
+  #[cfg(not(unix))]
+  fn listen(&mut self, _socket_file: &Path, _state_file: &Path) -> Result<(), DaemonError> {
+    Err(DaemonError::Unsupported)
+  }
+
+  fn handle(&mut self, state: &DaemonState, request: DaemonRequest) -> DaemonResponse {
+    self.requests += 1;
+    match request {
+      DaemonRequest::Compile { session, config, environment } => {
+        info!("Compiling ({:?})", session);
+        match self.session(session, environment).compile(config) {
+          Ok(output) => DaemonResponse::Compiled {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+          },
+          Err(err) => DaemonResponse::Failed { message: err.to_string() },
+        }
+      },
+      DaemonRequest::Deploy { session, options, environment } => {
+        info!("Deploying ({:?})", session);
+        match self.session(session, environment).deploy(options) {
+          Ok(contracts) => DaemonResponse::Deployed { smart_contracts: contracts.into_iter().collect() },
+          Err(err) => DaemonResponse::Failed { message: err.to_string() },
+        }
+      },
+      DaemonRequest::Status => DaemonResponse::Status {
+        state: state.clone(),
+        requests: self.requests,
+        sessions: self.sessions.len(),
+      },
+      DaemonRequest::Shutdown => DaemonResponse::ShuttingDown,
+    }
+  }
+
+  // Warm instances are kept per session, the environment is the one of the
+  // current request.
+  fn session(&mut self, session: Session, environment: Option<BTreeMap<String, String>>) -> &Vibranium {
+    let vibranium = match self.sessions.remove(&session) {
+      Some(vibranium) => vibranium,
+      None => Vibranium::new(self.project_path.clone())
+        .with_profile(session.profile.clone())
+        .with_network(session.network.clone())
+        .with_read_only(session.read_only)
+        .with_config_cache(true),
+    };
+    self.sessions.entry(session).or_insert(vibranium.with_environment(environment))
+  }
+}
+
+pub struct DaemonClient {
+  socket_file: PathBuf,
+  session: Session,
+}
+
+impl DaemonClient {
+  // Clients are only created for daemons that are still running.
+  pub fn connect(config: &Config, session: Session) -> Option<DaemonClient> {
+    let supervisor = DaemonSupervisor::new(config);
+    supervisor.status().ok().flatten()?;
+    Some(DaemonClient {
+      socket_file: supervisor.socket_file(),
+      session,
+    })
+  }
+
+  pub fn request(&self, request: &DaemonRequest) -> Result<DaemonResponse, DaemonError> {
+    exchange(&self.socket_file, request)
+  }
+
+  // Returns the compiler's stdout and stderr.
+  pub fn compile(&self, config: CompilerConfig) -> Result<(String, String), DaemonError> {
+    match self.request(&DaemonRequest::Compile { session: self.session.clone(), config, environment: Some(env::vars().collect()) })? {
+      DaemonResponse::Compiled { stdout, stderr } => Ok((stdout, stderr)),
+      response => Err(unexpected(response)),
+    }
+  }
+
+  pub fn deploy(&self, options: DeployOptions) -> Result<DeployedContracts, DaemonError> {
+    match self.request(&DaemonRequest::Deploy { session: self.session.clone(), options, environment: Some(env::vars().collect()) })? {
+      DaemonResponse::Deployed { smart_contracts } => Ok(smart_contracts.into_iter().collect()),
+      response => Err(unexpected(response)),
+    }
+  }
+}
+
+pub struct DaemonSupervisor<'a> {
+  config: &'a Config,
+}
+
+impl<'a> DaemonSupervisor<'a> {
+  pub fn new(config: &'a Config) -> DaemonSupervisor<'a> {
+    DaemonSupervisor {
+      config,
+    }
+  }
+
+  // The daemon runs as `<executable> daemon --path <project>` in the
+  // background, where `executable` is the Vibranium CLI. Returns once it
+  // accepts requests.
+  pub fn start(&self, executable: &Path) -> Result<DaemonState, DaemonError> {
+    if let Some(state) = self.status()? {
+      return Err(DaemonError::AlreadyRunning(state.pid));
+    }
+
+    let mut child = ProcessCommand::new(executable.to_string_lossy())
+      .args(vec!["daemon".to_string(), "--path".to_string(), self.config.project_path.to_string_lossy().to_string(), "--verbose".to_string()])
+      .log_to(&self.config.vibranium_dir_path)
+      .spawn(&self.log_file())?;
+
+    let started = Instant::now();
+    while started.elapsed() < STARTUP_TIMEOUT {
+      if let Ok(DaemonResponse::Status { state, .. }) = exchange(&self.socket_file(), &DaemonRequest::Status) {
+        return Ok(state);
+      }
+      if let Ok(Some(_status)) = child.try_wait() {
+        break;
+      }
+      thread::sleep(POLL_INTERVAL);
+    }
+    Err(DaemonError::NotStarted(self.log_file().to_string_lossy().to_string()))
+  }
+
+  pub fn stop(&self) -> Result<Option<DaemonState>, DaemonError> {
+    let state = match self.status()? {
+      Some(state) => state,
+      None => return Ok(None),
+    };
+
+    exchange(&self.socket_file(), &DaemonRequest::Shutdown).map_err(|_err| DaemonError::Unresponsive(state.pid))?;
+    let started = Instant::now();
+    while is_alive(state.pid) && started.elapsed() < SHUTDOWN_TIMEOUT {
+      thread::sleep(POLL_INTERVAL);
+    }
+    if is_alive(state.pid) {
+      return Err(DaemonError::Unresponsive(state.pid));
+    }
+    Ok(Some(state))
+  }
+
+  // State of daemons that aren't running anymore is cleaned up.
+  pub fn status(&self) -> Result<Option<DaemonState>, DaemonError> {
+    let state_file = self.state_file();
+    if !state_file.exists() {
+      return Ok(None);
+    }
+
+    let state: DaemonState = toml::from_str(&fs::read_to_string(&state_file)?).map_err(|err| DaemonError::InvalidMessage(err.to_string()))?;
+    if is_alive(state.pid) {
+      Ok(Some(state))
+    } else {
+      info!("Daemon with pid {} isn't running anymore", state.pid);
+      fs::remove_file(state_file)?;
+      let _ = fs::remove_file(self.socket_file());
+      Ok(None)
+    }
+  }
+
+  pub fn log_file(&self) -> PathBuf {
+    self.config.vibranium_dir_path.join(DAEMON_LOG_FILE)
+  }
+
+  fn state_file(&self) -> PathBuf {
+    self.config.vibranium_dir_path.join(DAEMON_STATE_FILE)
+  }
+
+  fn socket_file(&self) -> PathBuf {
+    self.config.vibranium_dir_path.join(DAEMON_SOCKET_FILE)
+  }
+}
+
+fn unexpected(response: DaemonResponse) -> DaemonError {
+  match response {
+    DaemonResponse::Failed { message } => DaemonError::Failed(message),
+    response => DaemonError::InvalidMessage(format!("Unexpected response {:?}", response)),
+  }
+}
+
+// Requests are written before closing the write half of the connection,
+// responses are read until the daemon closes it.
+#[cfg(unix)]
+fn exchange(socket_file: &Path, request: &DaemonRequest) -> Result<DaemonResponse, DaemonError> {
+  let mut stream = UnixStream::connect(socket_file)?;
+  write_message(&mut stream, request)?;
+  stream.shutdown(std::net::Shutdown::Write)?;
+  read_message(&mut stream)
+}
+
+#[cfg(not(unix))]
+fn exchange(_socket_file: &Path, _request: &DaemonRequest) -> Result<DaemonResponse, DaemonError> {
+  Err(DaemonError::Unsupported)
+}
+
+fn write_message<T: Serialize>(stream: &mut impl Write, message: &T) -> Result<(), DaemonError> {
+  stream.write_all(&serde_json::to_vec(message)?)?;
+  stream.flush()?;
+  Ok(())
+}
+
+fn read_message<T: DeserializeOwned>(stream: &mut impl Read) -> Result<T, DaemonError> {
+  let mut message = vec![];
+  stream.read_to_end(&mut message)?;
+  Ok(serde_json::from_slice(&message)?)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+
+  use super::{Daemon, DaemonClient, DaemonRequest, DaemonResponse, DaemonState, Session, DAEMON_SOCKET_FILE};
+  use crate::compiler::CompilerConfig;
+  use crate::config::Config;
+  use std::collections::BTreeMap;
+  use std::thread;
+  use std::time::Duration;
+
+  #[test]
+  fn it_should_serve_requests_until_shut_down() {
+    let tmp_dir = crate::testing::project_dir();
+    let project_path = tmp_dir.path().to_path_buf();
+    let daemon = thread::spawn(move || Daemon::new(project_path).serve());
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let mut client = None;
+    for _attempt in 0..50 {
+      client = DaemonClient::connect(&config, Session::default());
+      if client.is_some() {
+        break;
+      }
+      thread::sleep(Duration::from_millis(100));
+    }
+    let client = client.expect("Daemon didn't start");
+
+    // Without a vibranium.toml, compilations fail inside the daemon.
+    let result = client.compile(CompilerConfig {
+      compiler: None,
+      compiler_options: None,
+      replace_options: false,
+      force: false,
+      tags: None,
+    });
+    assert!(result.is_err());
+
+    match client.request(&DaemonRequest::Status).unwrap() {
+      DaemonResponse::Status { state, requests, sessions } => {
+        assert_eq!(state.pid, std::process::id());
+        assert_eq!(requests, 2);
+        assert_eq!(sessions, 1);
+      },
+      response => panic!("Unexpected response {:?}", response),
+    }
+
+    match client.request(&DaemonRequest::Shutdown).unwrap() {
+      DaemonResponse::ShuttingDown => (),
+      response => panic!("Unexpected response {:?}", response),
+    }
+    daemon.join().unwrap().unwrap();
+    assert!(!tmp_dir.path().join(".vibranium").join(DAEMON_SOCKET_FILE).exists());
+  }
+
+  #[test]
+  fn it_should_serve_requests_in_the_environment_of_the_client() {
+    let tmp_dir = crate::testing::project_dir();
+    let mut daemon = Daemon::new(tmp_dir.path().to_path_buf());
+    let state = DaemonState { pid: std::process::id(), started_at: 0 };
+    let compile = |environment: Option<BTreeMap<String, String>>| DaemonRequest::Compile {
+      session: Session::default(),
+      config: CompilerConfig { compiler: None, compiler_options: None, replace_options: false, force: false, tags: None },
+      environment,
+    };
+
+    let mut environment = BTreeMap::new();
+    environment.insert("RPC_KEY".to_string(), "first".to_string());
+    daemon.handle(&state, compile(Some(environment.clone())));
+    assert_eq!(daemon.sessions[&Session::default()].environment, Some(environment.clone()));
+
+    environment.insert("RPC_KEY".to_string(), "second".to_string());
+    daemon.handle(&state, compile(Some(environment.clone())));
+    assert_eq!(daemon.sessions.len(), 1);
+    assert_eq!(daemon.sessions[&Session::default()].environment, Some(environment));
+  }
+}
@@ -0,0 +1,202 @@
+// Address books of tracked deployments, so wallets and Safes show names
+// instead of bare addresses. Wallets tell networks apart by chain id, so only
+// deployments to networks with a `chain_id` in vibranium.toml are included.
+
+use crate::config::NetworkConfig;
+use super::error::DeploymentTrackingError;
+use super::tracker::SmartContractTrackingData;
+
+use rustc_hex::ToHex;
+use sha3::{Digest, Keccak256};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use web3::types::Address;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AddressBookFormat {
+  MetaMask,
+  SafeAddressBook,
+}
+
+impl FromStr for AddressBookFormat {
+  type Err = DeploymentTrackingError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "metamask" => Ok(AddressBookFormat::MetaMask),
+      "safe-addressbook" => Ok(AddressBookFormat::SafeAddressBook),
+      _ => Err(DeploymentTrackingError::UnsupportedAddressBookFormat(s.to_string())),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressBookEntry {
+  pub name: String,
+  pub address: String,
+  pub chain_id: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct AddressBook {
+  pub entries: Vec<AddressBookEntry>,
+  // Names of Smart Contracts whose deployments were left out, as the chain
+  // id of their network isn't known.
+  pub skipped: Vec<String>,
+}
+
+impl AddressBook {
+  pub fn new(chains: &HashMap<String, SmartContractTrackingData>, networks: &BTreeMap<String, NetworkConfig>) -> AddressBook {
+    let mut address_book = AddressBook::default();
+    for entries in chains.values() {
+      for entry in entries.values() {
+        let chain_id = entry.network.as_ref()
+          .and_then(|network| networks.get(network))
+          .and_then(|network_config| network_config.chain_id);
+        match chain_id {
+          Some(chain_id) => address_book.entries.push(AddressBookEntry {
+            name: entry.name.to_owned(),
+            address: checksummed(&entry.address),
+            chain_id,
+          }),
+          None => address_book.skipped.push(entry.name.to_owned()),
+        }
+      }
+    }
+    address_book.entries.sort_by(|a, b| (a.chain_id, &a.name, &a.address).cmp(&(b.chain_id, &b.name, &b.address)));
+    address_book.skipped.sort();
+    address_book.skipped.dedup();
+    address_book
+  }
+
+  pub fn render(&self, format: AddressBookFormat) -> Result<String, DeploymentTrackingError> {
+    match format {
+      AddressBookFormat::MetaMask => self.render_metamask(),
+      AddressBookFormat::SafeAddressBook => Ok(self.render_safe_address_book()),
+    }
+  }
+
+  // Mirrors the `addressBook` section of MetaMask's state backups, which is
+  // keyed by hex chain id and address.
+  fn render_metamask(&self) -> Result<String, DeploymentTrackingError> {
+    let mut chains = serde_json::Map::new();
+    for entry in &self.entries {
+      let chain_id = format!("0x{:x}", entry.chain_id);
+      let contacts = chains.entry(chain_id.clone()).or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+      if let serde_json::Value::Object(contacts) = contacts {
+        contacts.insert(entry.address.to_owned(), serde_json::json!({
+          "address": entry.address,
+          "chainId": chain_id,
+          "isEns": false,
+          "memo": "",
+          "name": entry.name,
+        }));
+      }
+    }
+    serde_json::to_string_pretty(&serde_json::json!({ "addressBook": chains }))
+      .map_err(|err| DeploymentTrackingError::Other(err.to_string()))
+  }
+
+  // The CSV format Safe{Wallet} imports and exports address books in.
+  fn render_safe_address_book(&self) -> String {
+    let mut csv = String::from("address,name,chainId\n");
+    for entry in &self.entries {
+      csv.push_str(&format!("{},{},{}\n", entry.address, csv_field(&entry.name), entry.chain_id));
+    }
+    csv
+  }
+}
+
+// EIP-55 mixed-case checksum encoding.
+fn checksummed(address: &Address) -> String {
+  let hex: String = address.as_bytes().to_hex();
+  let hash: String = Keccak256::digest(hex.as_bytes()).to_hex();
+  let checksummed: String = hex.chars().zip(hash.chars())
+    .map(|(c, h)| if h.to_digit(16).unwrap_or(0) >= 8 { c.to_ascii_uppercase() } else { c })
+    .collect();
+  format!("0x{}", checksummed)
+}
+
+fn csv_field(value: &str) -> String {
+  if value.contains([',', '"', '\n']) {
+    format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+    value.to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{checksummed, AddressBook, AddressBookFormat};
+  use crate::config::NetworkConfig;
+  use crate::deployment::tracker::{SmartContractTrackingData, SmartContractTrackingDataEntry};
+  use std::collections::{BTreeMap, HashMap};
+  use web3::types::Address;
+
+  fn entry(name: &str, address: &str, network: Option<&str>) -> SmartContractTrackingDataEntry {
+    SmartContractTrackingDataEntry {
+      name: name.to_string(),
+      address: address.parse().unwrap(),
+      network: network.map(str::to_string),
+      deployed_at: None,
+      commit: None,
+      verification: None,
+      metadata: None,
+      args: None,
+      encoded_args: None,
+      tx_hash: None,
+      block_number: None,
+      deployer: None,
+      gas_used: None,
+      compiler_version: None,
+      scanned_to: None,
+    }
+  }
+
+  fn address_book() -> AddressBook {
+    let mut chain: SmartContractTrackingData = HashMap::new();
+    chain.insert("0x01".to_string(), entry("Token", "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", Some("mainnet")));
+    chain.insert("0x02".to_string(), entry("Registry, v2", "fb6916095ca1df60bb79ce92ce3ea74c37c5d359", Some("mainnet")));
+    chain.insert("0x03".to_string(), entry("Faucet", "0000000000000000000000000000000000000001", None));
+    let mut chains = HashMap::new();
+    chains.insert("0xabcdef".to_string(), chain);
+
+    let mut networks = BTreeMap::new();
+    networks.insert("mainnet".to_string(), NetworkConfig {
+      url: "https://mainnet.example".to_string(),
+      chain_id: Some(1),
+      accounts: None,
+      gas_price: None,
+      gas_limit: None,
+      explorer: None,
+      receipts: None,
+      aliases: None,
+      constants: None,
+    });
+    AddressBook::new(&chains, &networks)
+  }
+
+  #[test]
+  fn it_should_checksum_addresses() {
+    let address: Address = "dbf03b407c01e7cd3cbea99509d93f8dddc8c6fb".parse().unwrap();
+    assert_eq!(checksummed(&address), "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB");
+  }
+
+  #[test]
+  fn it_should_render_safe_address_books() {
+    let address_book = address_book();
+    assert_eq!(address_book.skipped, vec!["Faucet".to_string()]);
+    assert_eq!(address_book.render(AddressBookFormat::SafeAddressBook).unwrap(), "address,name,chainId\n\
+      0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359,\"Registry, v2\",1\n\
+      0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed,Token,1\n");
+  }
+
+  #[test]
+  fn it_should_render_metamask_address_books() {
+    let rendered: serde_json::Value = serde_json::from_str(&address_book().render(AddressBookFormat::MetaMask).unwrap()).unwrap();
+    let contact = &rendered["addressBook"]["0x1"]["0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"];
+    assert_eq!(contact["name"], "Token");
+    assert_eq!(contact["chainId"], "0x1");
+    assert!("metamask-json".parse::<AddressBookFormat>().is_err());
+  }
+}
@@ -0,0 +1,179 @@
+// Human readable names of addresses, so outputs show `Token (0x1234…abcd)`
+// instead of bare addresses. Names are taken from, in order of precedence,
+// the aliases configured for the selected network (`[networks.<NAME>.aliases]`)
+// and for all networks (`[aliases]`), the Smart Contracts tracked on the
+// connected chain and the address book entries of the network's chain id.
+
+use crate::blockchain::connector::BlockchainConnector;
+use crate::config::Config;
+use super::address_book::AddressBook;
+use super::children::first_block_hash;
+use super::error::DeploymentTrackingError;
+use super::tracker::DeploymentTracker;
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use web3::types::Address;
+
+const ADDRESS_HEX_LENGTH: usize = 40;
+
+#[derive(Debug, Default, Clone)]
+pub struct AddressAliases {
+  names: HashMap<Address, String>,
+}
+
+impl AddressAliases {
+  pub fn new() -> AddressAliases {
+    AddressAliases::default()
+  }
+
+  pub fn load(config: &Config, connector: &BlockchainConnector) -> Result<AddressAliases, DeploymentTrackingError> {
+    let project_config = config.read().map_err(|err| DeploymentTrackingError::Other(err.to_string()))?;
+    let network = config.read_network().map_err(|err| DeploymentTrackingError::Other(err.to_string()))?;
+    let mut aliases = AddressAliases::new();
+
+    let configured = network.as_ref().and_then(|(_name, network_config)| network_config.aliases.as_ref()).into_iter()
+      .chain(project_config.aliases.as_ref());
+    for names in configured {
+      for (name, address) in names {
+        let parsed = Address::from_str(address.trim_start_matches("0x"))
+          .map_err(|_err| DeploymentTrackingError::Other(format!("Invalid address {} configured for alias {}", address, name)))?;
+        aliases.insert(parsed, name);
+      }
+    }
+
+    let tracker = DeploymentTracker::new(config);
+    let mut tracked: Vec<(String, Address)> = tracker.get_all_smart_contract_tracking_data(&first_block_hash(connector)?)?
+      .unwrap_or_default()
+      .into_values()
+      .map(|entry| (entry.name, entry.address))
+      .collect();
+    tracked.sort();
+    for (name, address) in &tracked {
+      aliases.insert(*address, name);
+    }
+
+    let chain_id = network.as_ref().and_then(|(_name, network_config)| network_config.chain_id);
+    if let Some(chain_id) = chain_id {
+      let address_book = AddressBook::new(&tracker.get_tracking_data_of_all_chains()?, &project_config.networks.unwrap_or_default());
+      for entry in address_book.entries.iter().filter(|entry| entry.chain_id == chain_id) {
+        if let Ok(address) = Address::from_str(entry.address.trim_start_matches("0x")) {
+          aliases.insert(address, &entry.name);
+        }
+      }
+    }
+    Ok(aliases)
+  }
+
+  // Names that have been inserted first take precedence.
+  pub fn insert(&mut self, address: Address, name: &str) {
+    self.names.entry(address).or_insert_with(|| name.to_string());
+  }
+
+  pub fn name(&self, address: &Address) -> Option<&str> {
+    self.names.get(address).map(String::as_str)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.names.is_empty()
+  }
+
+  pub fn format(&self, address: &Address) -> String {
+    match self.name(address) {
+      Some(name) => format!("{} ({})", name, short_address(address)),
+      None => format!("{:?}", address),
+    }
+  }
+
+  // Replaces every address with a name in the given text, leaving unknown
+  // addresses and longer hex strings, e.g. transaction hashes, as they are.
+  pub fn annotate(&self, text: &str) -> String {
+    if self.is_empty() {
+      return text.to_string();
+    }
+    let mut annotated = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find("0x") {
+      let preceded_by_word = rest[..index].chars().next_back().is_some_and(char::is_alphanumeric);
+      let digits = rest[index + 2..].chars().take_while(char::is_ascii_hexdigit).count();
+      let end = index + 2 + digits;
+      let followed_by_word = rest[end..].chars().next().is_some_and(char::is_alphanumeric);
+      let name = Some(&rest[index + 2..end])
+        .filter(|_hex| digits == ADDRESS_HEX_LENGTH && !preceded_by_word && !followed_by_word)
+        .and_then(|hex| Address::from_str(hex).ok())
+        .filter(|address| self.names.contains_key(address));
+
+      annotated.push_str(&rest[..index]);
+      match name {
+        Some(address) => annotated.push_str(&self.format(&address)),
+        None => annotated.push_str(&rest[index..end]),
+      }
+      rest = &rest[end..];
+    }
+    annotated.push_str(rest);
+    annotated
+  }
+}
+
+// `0x1234…abcd`
+pub fn short_address(address: &Address) -> String {
+  let hex = format!("{:?}", address);
+  format!("{}…{}", &hex[..6], &hex[hex.len() - 4..])
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{short_address, AddressAliases};
+  use crate::config::Config;
+  use crate::deployment::tracker::DeploymentTracker;
+  use crate::testing::MockTransport;
+  use web3::types::{Address, Block, H256};
+
+  #[test]
+  fn it_should_annotate_addresses_with_their_names() {
+    let mut aliases = AddressAliases::new();
+    aliases.insert(Address::from_low_u64_be(9), "Token");
+    aliases.insert(Address::from_low_u64_be(9), "Other");
+
+    assert_eq!(short_address(&Address::from_low_u64_be(9)), "0x0000…0009");
+    assert_eq!(aliases.format(&Address::from_low_u64_be(9)), "Token (0x0000…0009)");
+    assert_eq!(aliases.format(&Address::from_low_u64_be(1)), format!("{:?}", Address::from_low_u64_be(1)));
+    assert_eq!(
+      aliases.annotate("Transfer(from: 0x0000000000000000000000000000000000000001, to: 0x0000000000000000000000000000000000000009)"),
+      "Transfer(from: 0x0000000000000000000000000000000000000001, to: Token (0x0000…0009))"
+    );
+    // Transaction hashes aren't addresses, even if they end like one.
+    let hash = format!("0x{:064x}", 9);
+    assert_eq!(aliases.annotate(&hash), hash);
+  }
+
+  #[test]
+  fn it_should_prefer_configured_aliases_over_tracked_names() {
+    let tmp_dir = crate::testing::project_with_config("
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+
+      [aliases]
+        deployer = \"0x0000000000000000000000000000000000000001\"
+        Vault = \"0x0000000000000000000000000000000000000002\"
+    ");
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let tracker = DeploymentTracker::new(&config);
+    tracker.create_database().unwrap();
+    tracker.track(H256::from_low_u64_be(1), "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(9), None).unwrap();
+    tracker.track(H256::from_low_u64_be(1), "Treasury".to_string(), "6081".to_string(), &vec![], Address::from_low_u64_be(2), None).unwrap();
+
+    let transport = MockTransport::new();
+    transport.set_default_response("eth_getBlockByNumber", serde_json::to_value(Block::<H256> {
+      hash: Some(H256::from_low_u64_be(1)),
+      ..Block::default()
+    }).unwrap());
+    let aliases = AddressAliases::load(&config, &transport.connector()).unwrap();
+
+    assert_eq!(aliases.name(&Address::from_low_u64_be(1)), Some("deployer"));
+    assert_eq!(aliases.name(&Address::from_low_u64_be(2)), Some("Vault"));
+    assert_eq!(aliases.name(&Address::from_low_u64_be(9)), Some("Token"));
+  }
+}
@@ -0,0 +1,205 @@
+// Constructor arguments are configured as strings along with their kind in
+// vibranium.toml. They're checked against the constructor's inputs in the
+// Smart Contract's ABI before being ABI-encoded.
+
+use ethabi::{Error, ErrorKind, ParamType, Token};
+use ethabi::param_type::Reader;
+use ethabi::token::{StrictTokenizer, Tokenizer};
+use web3::types::U256;
+
+use crate::config::SmartContractArg;
+use super::error::DeploymentError;
+use super::{DeployedContracts, CONSTANT_REFERENCE_PREFIX};
+
+const CONTRACT_REFERENCE_PREFIX: &str = "$";
+// `$Name.address` is the same as `$Name`.
+const ADDRESS_REFERENCE_SUFFIX: &str = ".address";
+
+// Accepts `0x` prefixed addresses and bytes, as well as integers beyond
+// 32 bit in decimal or `0x` prefixed hex notation.
+pub struct ArgTokenizer;
+
+impl Tokenizer for ArgTokenizer {
+  fn tokenize_address(value: &str) -> Result<[u8; 20], Error> {
+    StrictTokenizer::tokenize_address(strip_hex_prefix(value))
+  }
+
+  fn tokenize_string(value: &str) -> Result<String, Error> {
+    StrictTokenizer::tokenize_string(value)
+  }
+
+  fn tokenize_bool(value: &str) -> Result<bool, Error> {
+    StrictTokenizer::tokenize_bool(value.trim())
+  }
+
+  fn tokenize_bytes(value: &str) -> Result<Vec<u8>, Error> {
+    StrictTokenizer::tokenize_bytes(strip_hex_prefix(value))
+  }
+
+  fn tokenize_fixed_bytes(value: &str, len: usize) -> Result<Vec<u8>, Error> {
+    StrictTokenizer::tokenize_fixed_bytes(strip_hex_prefix(value), len)
+  }
+
+  fn tokenize_uint(value: &str) -> Result<[u8; 32], Error> {
+    let mut bytes = [0u8; 32];
+    parse_uint(value.trim())?.to_big_endian(&mut bytes);
+    Ok(bytes)
+  }
+
+  fn tokenize_int(value: &str) -> Result<[u8; 32], Error> {
+    let value = value.trim();
+    let int = match value.strip_prefix('-') {
+      // Two's complement
+      Some(abs) => (!parse_uint(abs)?).overflowing_add(U256::one()).0,
+      None => parse_uint(value)?,
+    };
+    let mut bytes = [0u8; 32];
+    int.to_big_endian(&mut bytes);
+    Ok(bytes)
+  }
+}
+
+fn strip_hex_prefix(value: &str) -> &str {
+  let value = value.trim();
+  value.strip_prefix("0x").unwrap_or(value)
+}
+
+fn parse_uint(value: &str) -> Result<U256, Error> {
+  let parsed = match value.strip_prefix("0x") {
+    Some(hex) => hex.parse::<U256>().ok(),
+    None => U256::from_dec_str(value).ok(),
+  };
+  parsed.ok_or_else(|| ErrorKind::InvalidData.into())
+}
+
+// Checks amount and kinds of the configured arguments against the inputs of
+// the constructor, so that mistakes surface before anything is sent.
+pub fn check_constructor_args(name: &str, abi: &[u8], args: &[SmartContractArg]) -> Result<Vec<ParamType>, DeploymentError> {
+  let contract = ethabi::Contract::load(abi).map_err(|err| DeploymentError::InvalidConstructorArgs(err, name.to_owned()))?;
+  let inputs = contract.constructor().map(|constructor| constructor.inputs.clone()).unwrap_or_default();
+
+  if inputs.len() != args.len() {
+    return Err(DeploymentError::ConstructorArgsCount(name.to_owned(), inputs.len(), args.len()));
+  }
+
+  inputs.iter().zip(args.iter()).map(|(input, arg)| {
+    let param_type = Reader::read(&arg.kind).map_err(DeploymentError::InvalidParamType)?;
+    if param_type != input.kind {
+      return Err(DeploymentError::ConstructorArgType(name.to_owned(), input.name.to_owned(), input.kind.to_string(), arg.kind.to_owned()));
+    }
+    Ok(param_type)
+  }).collect()
+}
+
+pub fn tokenize_args(args: &[SmartContractArg], param_types: &[ParamType], deployed_contracts: &DeployedContracts) -> Result<Vec<Token>, DeploymentError> {
+  args.iter().zip(param_types.iter()).map(|(arg, param_type)| {
+    let value = resolve_contract_references(&arg.value, param_type, deployed_contracts)?;
+    ArgTokenizer::tokenize(param_type, &value).map_err(|err| DeploymentError::TokenizeParam(err, value.to_owned()))
+  }).collect()
+}
+
+// Names of Smart Contracts whose addresses are passed as `$Name`, either as
+// an address or as an element of an address array.
+pub fn contract_references(arg: &SmartContractArg) -> Vec<&str> {
+  let elements: Vec<&str> = match Reader::read(&arg.kind) {
+    Ok(ParamType::Address) => vec![arg.value.trim()],
+    Ok(ParamType::Array(ref inner)) | Ok(ParamType::FixedArray(ref inner, _)) if **inner == ParamType::Address => array_elements(&arg.value),
+    _ => vec![],
+  };
+
+  elements.into_iter()
+    .filter(|element| element.starts_with(CONTRACT_REFERENCE_PREFIX) && !element.starts_with(CONSTANT_REFERENCE_PREFIX))
+    .map(|element| referenced_name(&element[CONTRACT_REFERENCE_PREFIX.len()..]))
+    .collect()
+}
+
+fn array_elements(value: &str) -> Vec<&str> {
+  let value = value.trim();
+  match value.strip_prefix('[').and_then(|value| value.strip_suffix(']')) {
+    Some(elements) if !elements.trim().is_empty() => elements.split(',').map(str::trim).collect(),
+    _ => vec![],
+  }
+}
+
+fn referenced_name(reference: &str) -> &str {
+  reference.strip_suffix(ADDRESS_REFERENCE_SUFFIX).unwrap_or(reference)
+}
+
+fn resolve_contract_references(value: &str, param_type: &ParamType, deployed_contracts: &DeployedContracts) -> Result<String, DeploymentError> {
+  let resolve = |element: &str| -> Result<String, DeploymentError> {
+    match element.strip_prefix(CONTRACT_REFERENCE_PREFIX).map(referenced_name) {
+      Some(reference) => deployed_contracts.values()
+        .find(|contract| contract.0 == reference)
+        .map(|contract| format!("{:?}", contract.1))
+        .ok_or_else(|| DeploymentError::MissingConfigForReference(reference.to_owned())),
+      None => Ok(element.to_owned()),
+    }
+  };
+
+  match param_type {
+    ParamType::Address => resolve(value.trim()),
+    ParamType::Array(inner) | ParamType::FixedArray(inner, _) if **inner == ParamType::Address => {
+      let elements = array_elements(value).into_iter().map(resolve).collect::<Result<Vec<String>, DeploymentError>>()?;
+      Ok(format!("[{}]", elements.join(",")))
+    },
+    _ => Ok(value.to_owned()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{check_constructor_args, contract_references, tokenize_args, ArgTokenizer};
+  use crate::config::SmartContractArg;
+  use crate::deployment::error::DeploymentError;
+  use crate::deployment::DeployedContracts;
+  use ethabi::{ParamType, Token};
+  use ethabi::token::Tokenizer;
+  use web3::types::{Address, U256};
+
+  const ABI: &str = r#"[{"type":"constructor","inputs":[{"name":"owners","type":"address[]"},{"name":"supply","type":"uint256"},{"name":"data","type":"bytes"}]}]"#;
+
+  fn arg(value: &str, kind: &str) -> SmartContractArg {
+    SmartContractArg { value: value.to_string(), kind: kind.to_string() }
+  }
+
+  #[test]
+  fn it_should_tokenize_large_and_prefixed_values() {
+    assert_eq!(ArgTokenizer::tokenize(&ParamType::Uint(256), "1000000000000000000000").unwrap(), Token::Uint(U256::from_dec_str("1000000000000000000000").unwrap()));
+    assert_eq!(ArgTokenizer::tokenize(&ParamType::Uint(256), "0xff").unwrap(), Token::Uint(U256::from(255)));
+    assert_eq!(ArgTokenizer::tokenize(&ParamType::Int(256), "-1").unwrap(), Token::Int(U256::max_value()));
+    assert_eq!(ArgTokenizer::tokenize(&ParamType::Bytes, "0x0102").unwrap(), Token::Bytes(vec![1, 2]));
+    assert_eq!(
+      ArgTokenizer::tokenize(&ParamType::Array(Box::new(ParamType::Address)), "[0x0000000000000000000000000000000000000001, 0x0000000000000000000000000000000000000002]").unwrap(),
+      Token::Array(vec![Token::Address(Address::from_low_u64_be(1)), Token::Address(Address::from_low_u64_be(2))])
+    );
+  }
+
+  #[test]
+  fn it_should_check_constructor_args_against_abi() {
+    let args = vec![arg("[$Owner]", "address[]"), arg("1", "uint256"), arg("0x", "bytes")];
+    assert_eq!(check_constructor_args("Token", ABI.as_bytes(), &args).unwrap().len(), 3);
+
+    match check_constructor_args("Token", ABI.as_bytes(), &args[..2]) {
+      Err(DeploymentError::ConstructorArgsCount(name, expected, given)) => assert_eq!((name.as_str(), expected, given), ("Token", 3, 2)),
+      result => panic!("Unexpected result: {:?}", result),
+    }
+
+    let args = vec![arg("[$Owner]", "address[]"), arg("1", "uint8"), arg("0x", "bytes")];
+    let error = check_constructor_args("Token", ABI.as_bytes(), &args).unwrap_err();
+    assert_eq!(error.to_string(), "Couldn't deploy Smart Contract 'Token'. Constructor parameter 'supply' is of type uint256, but the configured argument is of kind uint8.");
+  }
+
+  #[test]
+  fn it_should_resolve_references_in_address_arrays() {
+    let owner = Address::from_low_u64_be(7);
+    let mut deployed_contracts = DeployedContracts::new();
+    deployed_contracts.insert(owner, ("Owner".to_string(), owner, "unknown".to_string(), true));
+
+    let args = vec![arg("[$Owner, 0x0000000000000000000000000000000000000001, $Owner.address]", "address[]")];
+    assert_eq!(contract_references(&args[0]), vec!["Owner", "Owner"]);
+
+    let tokens = tokenize_args(&args, &[ParamType::Array(Box::new(ParamType::Address))], &deployed_contracts).unwrap();
+    assert_eq!(tokens, vec![Token::Array(vec![Token::Address(owner), Token::Address(Address::from_low_u64_be(1)), Token::Address(owner)])]);
+  }
+}
@@ -0,0 +1,301 @@
+// Smart Contracts created by factories aren't part of the deployment
+// configuration. They're discovered by scanning the logs of a tracked factory
+// for the event announcing them and tracked as members of a collection.
+// The first scan starts at the block the factory has been deployed in, later
+// ones resume after the last scanned block.
+
+use crate::blockchain::connector::BlockchainConnector;
+use crate::config::Config;
+use super::error::DeploymentTrackingError;
+use super::tracker::{DeploymentTracker, COLLECTION_TAG};
+
+use ethabi::{RawLog, Token};
+use std::collections::BTreeMap;
+use std::fs;
+use web3::types::{Address, BlockNumber, FilterBuilder, H256};
+
+const ARTIFACT_EXTENSION_ABI: &str = "abi";
+const FACTORY_TAG: &str = "factory";
+const BLOCK_TAG: &str = "block";
+// Many nodes limit the range of blocks `eth_getLogs` can be called with.
+const BLOCK_RANGE: u64 = 10_000;
+
+pub struct ChildrenOptions {
+  pub factory: String,
+  pub event: String,
+  pub arg: String,
+  pub collection: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ChildrenReport {
+  pub collection: String,
+  pub factory_address: Address,
+  pub discovered: Vec<Address>,
+  pub total: usize,
+  pub scanned_to: u64,
+}
+
+pub struct ChildDiscovery<'a> {
+  config: &'a Config,
+  connector: &'a BlockchainConnector,
+}
+
+impl<'a> ChildDiscovery<'a> {
+  pub fn new(config: &'a Config, connector: &'a BlockchainConnector) -> ChildDiscovery<'a> {
+    ChildDiscovery {
+      config,
+      connector,
+    }
+  }
+
+  pub fn discover(&self, options: &ChildrenOptions) -> Result<ChildrenReport, DeploymentTrackingError> {
+    let collection = options.collection.to_owned().unwrap_or_else(|| format!("{}.children", options.factory));
+    let tracker = DeploymentTracker::new(self.config);
+    let block_hash = first_block_hash(self.connector)?;
+
+    let factory = tracker.get_latest_smart_contract_tracking_data(&block_hash, &options.factory)?
+      .ok_or_else(|| DeploymentTrackingError::SmartContractNotTracked(options.factory.to_owned()))?;
+
+    let contract = ethabi::Contract::load(fs::read(abi_path(self.config, &options.factory)?)?.as_slice())
+      .map_err(|err| DeploymentTrackingError::Other(format!("Couldn't read ABI of {}: {}", &options.factory, err)))?;
+    let event = contract.event(&options.event)
+      .map_err(|_err| DeploymentTrackingError::UnknownEvent(options.factory.to_owned(), options.event.to_owned()))?;
+    if !event.inputs.iter().any(|input| input.name == options.arg && input.kind == ethabi::ParamType::Address) {
+      return Err(DeploymentTrackingError::InvalidEventArg(options.event.to_owned(), options.arg.to_owned()));
+    }
+
+    let members = self.members(&tracker, &block_hash, &collection)?;
+    // Collections discovered before scans were recorded resume at the block
+    // of their most recent member.
+    let from_block = factory.scanned_to.as_ref()
+      .and_then(|scanned_to| scanned_to.get(&collection))
+      .map(|block| block + 1)
+      .or_else(|| members.iter().filter_map(|(_address, block)| *block).max())
+      .or(factory.block_number)
+      .unwrap_or(0);
+    let latest_block = self.connector.block_number()
+      .map_err(|err| DeploymentTrackingError::Other(err.to_string()))?
+      .low_u64();
+
+    let mut discovered = vec![];
+    let mut start = from_block;
+    while start <= latest_block {
+      let end = latest_block.min(start + BLOCK_RANGE - 1);
+      let filter = FilterBuilder::default()
+        .address(vec![factory.address])
+        .topics(Some(vec![event.signature()]), None, None, None)
+        .from_block(BlockNumber::Number(start))
+        .to_block(BlockNumber::Number(end))
+        .build();
+
+      for log in self.connector.logs(filter).map_err(|err| DeploymentTrackingError::Other(err.to_string()))? {
+        if log.removed == Some(true) {
+          continue;
+        }
+        let parsed = event.parse_log(RawLog { topics: log.topics, data: log.data.0 })
+          .map_err(|err| DeploymentTrackingError::Other(format!("Couldn't decode {} event: {}", &options.event, err)))?;
+        let child = parsed.params.into_iter()
+          .find(|param| param.name == options.arg)
+          .and_then(|param| match param.value {
+            Token::Address(address) => Some(address),
+            _ => None,
+          });
+
+        if let Some(child) = child {
+          let mut metadata = BTreeMap::new();
+          metadata.insert(FACTORY_TAG.to_string(), options.factory.to_owned());
+          if let Some(block_number) = log.block_number {
+            metadata.insert(BLOCK_TAG.to_string(), block_number.to_string());
+          }
+          if tracker.track_instance(&block_hash, &collection, child, metadata)? {
+            info!("Discovered {} at {:?}", &collection, &child);
+            discovered.push(child);
+          }
+        }
+      }
+      start = end + 1;
+    }
+    if from_block <= latest_block {
+      tracker.record_scan(&block_hash, &factory.address, &collection, latest_block)?;
+    }
+
+    Ok(ChildrenReport {
+      total: members.len() + discovered.len(),
+      collection,
+      factory_address: factory.address,
+      discovered,
+      scanned_to: latest_block,
+    })
+  }
+
+  // Addresses of the collection's members, along with the block they've been
+  // created in.
+  fn members(&self, tracker: &DeploymentTracker, block_hash: &H256, collection: &str) -> Result<Vec<(Address, Option<u64>)>, DeploymentTrackingError> {
+    Ok(tracker.get_all_smart_contract_tracking_data(block_hash)?.unwrap_or_default()
+      .into_values()
+      .filter(|entry| entry.has_tag(COLLECTION_TAG, collection))
+      .map(|entry| {
+        let block = entry.metadata.as_ref()
+          .and_then(|metadata| metadata.get(BLOCK_TAG))
+          .and_then(|block| block.parse().ok());
+        (entry.address, block)
+      })
+      .collect())
+  }
+}
+
+// ABIs of Smart Contracts that are instances of another one are the ABIs of
+// the latter.
+pub(super) fn abi_path(config: &Config, name: &str) -> Result<std::path::PathBuf, DeploymentTrackingError> {
+  let project_config = config.read().map_err(|err| DeploymentTrackingError::Other(err.to_string()))?;
+  let artifact = project_config.deployment.iter()
+    .flat_map(|deployment_config| deployment_config.smart_contracts.iter())
+    .find(|smart_contract_config| smart_contract_config.name == name)
+    .and_then(|smart_contract_config| smart_contract_config.instance_of.to_owned())
+    .unwrap_or_else(|| name.to_string());
+  let abi_path = config.project_path.join(&project_config.sources.artifacts).join(&artifact).with_extension(ARTIFACT_EXTENSION_ABI);
+  if abi_path.exists() {
+    Ok(abi_path)
+  } else {
+    Err(DeploymentTrackingError::Other(format!("Couldn't find ABI of {} at {}", name, abi_path.to_string_lossy())))
+  }
+}
+
+pub(super) fn first_block_hash(connector: &BlockchainConnector) -> Result<H256, DeploymentTrackingError> {
+  connector.get_first_block()
+    .map_err(|err| DeploymentTrackingError::Other(err.to_string()))?
+    .and_then(|block| block.hash)
+    .ok_or_else(|| DeploymentTrackingError::Other("Couldn't read genesis block of the connected chain".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{ChildDiscovery, ChildrenOptions};
+  use crate::config::Config;
+  use crate::deployment::tracker::{DeploymentReceipt, DeploymentTracker};
+  use crate::testing::MockTransport;
+  use std::fs;
+  use std::str::FromStr;
+  use web3::types::{Address, Block, H256};
+
+  const BLOCK_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+  const ABI: &str = "[{\"anonymous\":false,\"inputs\":[{\"indexed\":true,\"name\":\"child\",\"type\":\"address\"},{\"indexed\":false,\"name\":\"salt\",\"type\":\"uint256\"}],\"name\":\"ChildCreated\",\"type\":\"event\"}]";
+  // keccak256("ChildCreated(address,uint256)")
+  const TOPIC: &str = "0xf1b544c1944129b0fa24838a22f6c7d8500381559fa6d6ccf8845923f0922a2c";
+
+  fn log(child: u64, block: u64) -> serde_json::Value {
+    serde_json::json!({
+      "address": "0x0000000000000000000000000000000000000009",
+      "topics": [TOPIC, format!("0x{:064x}", child)],
+      "data": format!("0x{:064x}", 7),
+      "blockNumber": format!("0x{:x}", block),
+    })
+  }
+
+  #[test]
+  fn it_should_track_children_announced_by_factory_events() {
+    let tmp_dir = crate::testing::project_with_config("
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+    ");
+    fs::create_dir_all(tmp_dir.path().join("artifacts")).unwrap();
+    fs::write(tmp_dir.path().join("artifacts").join("Factory.abi"), ABI).unwrap();
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let tracker = DeploymentTracker::new(&config);
+    let block_hash = H256::from_str(&BLOCK_HASH[2..]).unwrap();
+    tracker.create_database().unwrap();
+    tracker.track(block_hash, "Factory".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(9), None).unwrap();
+
+    let transport = MockTransport::new();
+    transport.set_default_response("eth_getBlockByNumber", serde_json::to_value(Block::<H256> {
+      hash: Some(block_hash),
+      ..Block::default()
+    }).unwrap());
+    transport.add_response("eth_blockNumber", serde_json::json!("0x10"));
+    transport.add_response("eth_getLogs", serde_json::json!([log(1, 3), log(2, 5)]));
+    transport.add_response("eth_blockNumber", serde_json::json!("0x12"));
+    transport.add_response("eth_getLogs", serde_json::json!([log(2, 5), log(3, 17)]));
+
+    let connector = transport.connector();
+    let options = ChildrenOptions {
+      factory: "Factory".to_string(),
+      event: "ChildCreated".to_string(),
+      arg: "child".to_string(),
+      collection: None,
+    };
+    let discovery = ChildDiscovery::new(&config, &connector);
+
+    let report = discovery.discover(&options).unwrap();
+    assert_eq!(report.collection, "Factory.children");
+    assert_eq!(report.discovered, vec![Address::from_low_u64_be(1), Address::from_low_u64_be(2)]);
+    assert_eq!(report.total, 2);
+
+    // Scanning resumes after the last scanned block
+    let report = discovery.discover(&options).unwrap();
+    assert_eq!(report.discovered, vec![Address::from_low_u64_be(3)]);
+    assert_eq!(report.total, 3);
+    assert_eq!(report.scanned_to, 0x12);
+
+    let requests = transport.requests_for("eth_getLogs");
+    assert_eq!(requests[0][0]["fromBlock"], "0x0");
+    assert_eq!(requests[1][0]["fromBlock"], "0x11");
+    assert_eq!(requests[1][0]["topics"][0], TOPIC);
+  }
+
+  #[test]
+  fn it_should_start_at_the_factory_deployment_and_resume_after_empty_scans() {
+    let tmp_dir = crate::testing::project_with_config("
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+    ");
+    fs::create_dir_all(tmp_dir.path().join("artifacts")).unwrap();
+    fs::write(tmp_dir.path().join("artifacts").join("Factory.abi"), ABI).unwrap();
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let tracker = DeploymentTracker::new(&config);
+    let block_hash = H256::from_str(&BLOCK_HASH[2..]).unwrap();
+    tracker.create_database().unwrap();
+    let receipt = DeploymentReceipt {
+      tx_hash: H256::from_low_u64_be(1),
+      block_number: Some(4),
+      deployer: Address::from_low_u64_be(8),
+      gas_used: None,
+    };
+    tracker.track_deployment(block_hash, "Factory".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(9), None, Some(receipt)).unwrap();
+
+    let transport = MockTransport::new();
+    transport.set_default_response("eth_getBlockByNumber", serde_json::to_value(Block::<H256> {
+      hash: Some(block_hash),
+      ..Block::default()
+    }).unwrap());
+    transport.add_response("eth_blockNumber", serde_json::json!("0x8"));
+    transport.add_response("eth_getLogs", serde_json::json!([]));
+    transport.add_response("eth_blockNumber", serde_json::json!("0xa"));
+    transport.add_response("eth_getLogs", serde_json::json!([log(1, 9)]));
+
+    let connector = transport.connector();
+    let options = ChildrenOptions {
+      factory: "Factory".to_string(),
+      event: "ChildCreated".to_string(),
+      arg: "child".to_string(),
+      collection: None,
+    };
+    let discovery = ChildDiscovery::new(&config, &connector);
+
+    let report = discovery.discover(&options).unwrap();
+    assert!(report.discovered.is_empty());
+    assert_eq!(report.scanned_to, 8);
+
+    let report = discovery.discover(&options).unwrap();
+    assert_eq!(report.discovered, vec![Address::from_low_u64_be(1)]);
+
+    let requests = transport.requests_for("eth_getLogs");
+    assert_eq!(requests[0][0]["fromBlock"], "0x4");
+    assert_eq!(requests[1][0]["fromBlock"], "0x9");
+  }
+}
@@ -0,0 +1,169 @@
+// Differences between the tracked deployments of two networks, e.g. to see
+// what still needs to be promoted from staging to production. Only the most
+// recent deployment of every Smart Contract on a network is compared. With the
+// default content hash tracking key, differing keys mean differing bytecode or
+// constructor arguments.
+
+use super::tracker::{SmartContractTrackingData, SmartContractTrackingDataEntry, COLLECTION_TAG};
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Drift {
+  // Deployed on the source network only, i.e. it still needs to be promoted.
+  Missing,
+  // Deployed on the target network only.
+  Unexpected,
+  Bytecode,
+  Args(String, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeploymentDrift {
+  pub name: String,
+  pub from: String,
+  pub to: String,
+  pub drift: Drift,
+}
+
+impl fmt::Display for DeploymentDrift {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match &self.drift {
+      Drift::Missing => write!(f, "{}: deployed on {}, missing on {}", self.name, self.from, self.to),
+      Drift::Unexpected => write!(f, "{}: deployed on {}, missing on {}", self.name, self.to, self.from),
+      Drift::Bytecode => write!(f, "{}: bytecode differs", self.name),
+      Drift::Args(from_args, to_args) => write!(f, "{}: constructor arguments differ ({} on {}, {} on {})", self.name, from_args, self.from, to_args, self.to),
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct DeploymentDiff {
+  pub drifts: Vec<DeploymentDrift>,
+  pub in_sync: Vec<String>,
+}
+
+impl DeploymentDiff {
+  pub fn new(chains: &HashMap<String, SmartContractTrackingData>, from: &str, to: &str) -> DeploymentDiff {
+    let from_deployments = latest_deployments(chains, from);
+    let to_deployments = latest_deployments(chains, to);
+    let names: BTreeSet<&String> = from_deployments.keys().chain(to_deployments.keys()).collect();
+
+    let mut diff = DeploymentDiff::default();
+    for name in names {
+      let drift = match (from_deployments.get(name), to_deployments.get(name)) {
+        (Some(_from), None) => Drift::Missing,
+        (None, Some(_to)) => Drift::Unexpected,
+        (Some((from_key, from_entry)), Some((to_key, to_entry))) => {
+          let (from_args, to_args) = (constructor_args(from_entry), constructor_args(to_entry));
+          if from_args != to_args {
+            Drift::Args(from_args, to_args)
+          } else if from_key != to_key {
+            Drift::Bytecode
+          } else {
+            diff.in_sync.push(name.to_owned());
+            continue;
+          }
+        },
+        (None, None) => continue,
+      };
+      diff.drifts.push(DeploymentDrift {
+        name: name.to_owned(),
+        from: from.to_string(),
+        to: to.to_string(),
+        drift,
+      });
+    }
+    diff
+  }
+
+  pub fn has_drift(&self) -> bool {
+    !self.drifts.is_empty()
+  }
+}
+
+// Members of collections have been created by other Smart Contracts and are
+// left out.
+fn latest_deployments<'a>(chains: &'a HashMap<String, SmartContractTrackingData>, network: &str) -> BTreeMap<String, (&'a String, &'a SmartContractTrackingDataEntry)> {
+  let mut deployments: BTreeMap<String, (&String, &SmartContractTrackingDataEntry)> = BTreeMap::new();
+  let entries = chains.values()
+    .flat_map(|entries| entries.iter())
+    .filter(|(_key, entry)| entry.network.as_deref() == Some(network))
+    .filter(|(_key, entry)| !entry.metadata.as_ref().is_some_and(|metadata| metadata.contains_key(COLLECTION_TAG)));
+
+  for (key, entry) in entries {
+    let is_newer = deployments.get(&entry.name).is_none_or(|(_key, latest)| entry.deployed_at > latest.deployed_at);
+    if is_newer {
+      deployments.insert(entry.name.to_owned(), (key, entry));
+    }
+  }
+  deployments
+}
+
+fn constructor_args(entry: &SmartContractTrackingDataEntry) -> String {
+  let args = entry.decoded_args().ok().flatten().or_else(|| entry.args.clone()).unwrap_or_default();
+  format!("[{}]", args.iter().map(|arg| arg.value.to_owned()).collect::<Vec<String>>().join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{DeploymentDiff, Drift};
+  use crate::deployment::tracker::{SmartContractTrackingArg, SmartContractTrackingData, SmartContractTrackingDataEntry};
+  use std::collections::HashMap;
+
+  fn entry(name: &str, network: &str, deployed_at: u64, args: &[&str]) -> SmartContractTrackingDataEntry {
+    SmartContractTrackingDataEntry {
+      name: name.to_string(),
+      address: "0000000000000000000000000000000000000001".parse().unwrap(),
+      network: Some(network.to_string()),
+      deployed_at: Some(deployed_at),
+      commit: None,
+      verification: None,
+      metadata: None,
+      args: Some(args.iter().map(|value| SmartContractTrackingArg { kind: "uint256".to_string(), value: value.to_string() }).collect()),
+      encoded_args: None,
+      tx_hash: None,
+      block_number: None,
+      deployer: None,
+      gas_used: None,
+      compiler_version: None,
+      scanned_to: None,
+    }
+  }
+
+  #[test]
+  fn it_should_report_drift_between_networks() {
+    let mut staging: SmartContractTrackingData = HashMap::new();
+    staging.insert("0x01".to_string(), entry("Token", "staging", 1, &["100"]));
+    staging.insert("0x02".to_string(), entry("Registry", "staging", 1, &[]));
+    staging.insert("0x03".to_string(), entry("Vault", "staging", 1, &["1"]));
+    staging.insert("0x04".to_string(), entry("Vault", "staging", 2, &["2"]));
+    staging.insert("0x05".to_string(), entry("Exchange", "staging", 1, &[]));
+
+    let mut production: SmartContractTrackingData = HashMap::new();
+    production.insert("0x01".to_string(), entry("Token", "production", 1, &["100"]));
+    production.insert("0x06".to_string(), entry("Registry", "production", 1, &[]));
+    production.insert("0x03".to_string(), entry("Vault", "production", 1, &["1"]));
+    production.insert("0x07".to_string(), entry("Faucet", "production", 1, &[]));
+
+    let mut chains = HashMap::new();
+    chains.insert("0xaaaa".to_string(), staging);
+    chains.insert("0xbbbb".to_string(), production);
+
+    let diff = DeploymentDiff::new(&chains, "staging", "production");
+    assert!(diff.has_drift());
+    assert_eq!(diff.in_sync, vec!["Token".to_string()]);
+
+    let drifts: Vec<(&str, &Drift)> = diff.drifts.iter().map(|drift| (drift.name.as_str(), &drift.drift)).collect();
+    assert_eq!(drifts, vec![
+      ("Exchange", &Drift::Missing),
+      ("Faucet", &Drift::Unexpected),
+      ("Registry", &Drift::Bytecode),
+      ("Vault", &Drift::Args("[2]".to_string(), "[1]".to_string())),
+    ]);
+    assert_eq!(diff.drifts[0].to_string(), "Exchange: deployed on staging, missing on production");
+    assert_eq!(diff.drifts[3].to_string(), "Vault: constructor arguments differ ([2] on staging, [1] on production)");
+  }
+}
@@ -0,0 +1,146 @@
+// Markdown documentation of tracked deployments, one section per network,
+// meant to be pasted into release notes or a docs site. Addresses link to the
+// block explorer configured as `explorer` of the network in vibranium.toml.
+
+use crate::config::NetworkConfig;
+use crate::utils;
+use super::tracker::{self, SmartContractTrackingData, SmartContractTrackingDataEntry};
+
+use std::collections::{BTreeMap, HashMap};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+const UNKNOWN: &str = "n/a";
+const VERSION_TAGS: &[&str] = &["version", "release"];
+
+pub fn render(chains: &HashMap<String, SmartContractTrackingData>, networks: &BTreeMap<String, NetworkConfig>) -> String {
+  let mut docs = String::from("# Deployments\n");
+
+  let sections = tracker::group_by_network(chains);
+
+  if sections.is_empty() {
+    docs.push_str("\nNo deployments have been tracked yet.\n");
+    return docs;
+  }
+
+  for (title, mut entries) in sections {
+    entries.sort_by(|a, b| (a.deployed_at, &a.name).cmp(&(b.deployed_at, &b.name)));
+    let explorer = entries[0].network.as_deref().and_then(|network| networks.get(network)).and_then(|config| config.explorer.as_ref());
+
+    docs.push_str(&format!("\n## {}\n\n", title));
+    docs.push_str("| Contract | Address | Version | Commit | Deployed | Verification | Constructor arguments |\n");
+    docs.push_str("| --- | --- | --- | --- | --- | --- | --- |\n");
+    for entry in entries {
+      docs.push_str(&format!("| {} | {} | {} | {} | {} | {} | {} |\n",
+        escape(&entry.name),
+        address(entry, explorer),
+        escape(&version(entry)),
+        entry.commit.as_ref().map(|commit| format!("`{}`", commit)).unwrap_or_else(|| UNKNOWN.to_string()),
+        entry.deployed_at.map(|deployed_at| utils::format_day(deployed_at / SECONDS_PER_DAY)).unwrap_or_else(|| UNKNOWN.to_string()),
+        entry.verification.as_deref().unwrap_or("not verified yet"),
+        escape(&constructor_args(entry)),
+      ));
+    }
+  }
+  docs
+}
+
+fn address(entry: &SmartContractTrackingDataEntry, explorer: Option<&String>) -> String {
+  let address = format!("{:?}", entry.address);
+  match explorer {
+    Some(explorer) => format!("[`{}`]({}/address/{})", address, explorer.trim_end_matches('/'), address),
+    None => format!("`{}`", address),
+  }
+}
+
+fn version(entry: &SmartContractTrackingDataEntry) -> String {
+  entry.metadata.as_ref()
+    .and_then(|metadata| VERSION_TAGS.iter().find_map(|tag| metadata.get(*tag)))
+    .cloned()
+    .unwrap_or_else(|| UNKNOWN.to_string())
+}
+
+fn constructor_args(entry: &SmartContractTrackingDataEntry) -> String {
+  let args = entry.decoded_args().ok().flatten().or_else(|| entry.args.clone());
+  match args {
+    None => UNKNOWN.to_string(),
+    Some(args) if args.is_empty() => "none".to_string(),
+    Some(args) => args.iter().map(|arg| format!("{} {}", arg.kind, arg.value)).collect::<Vec<String>>().join(", "),
+  }
+}
+
+fn escape(value: &str) -> String {
+  value.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::render;
+  use crate::config::NetworkConfig;
+  use crate::deployment::tracker::{SmartContractTrackingArg, SmartContractTrackingData, SmartContractTrackingDataEntry};
+  use std::collections::{BTreeMap, HashMap};
+  use web3::types::Address;
+
+  fn entry(name: &str, network: Option<&str>, deployed_at: Option<u64>) -> SmartContractTrackingDataEntry {
+    SmartContractTrackingDataEntry {
+      name: name.to_string(),
+      address: Address::from_low_u64_be(1),
+      network: network.map(str::to_string),
+      deployed_at,
+      commit: None,
+      verification: None,
+      metadata: None,
+      args: None,
+      encoded_args: None,
+      tx_hash: None,
+      block_number: None,
+      deployer: None,
+      gas_used: None,
+      compiler_version: None,
+      scanned_to: None,
+    }
+  }
+
+  #[test]
+  fn it_should_render_a_section_per_network() {
+    let mut metadata = BTreeMap::new();
+    metadata.insert("release".to_string(), "v2.1".to_string());
+    let token = SmartContractTrackingDataEntry {
+      commit: Some("1a2b3c4".to_string()),
+      verification: Some("verified".to_string()),
+      metadata: Some(metadata),
+      args: Some(vec![SmartContractTrackingArg { kind: "string".to_string(), value: "a|b".to_string() }]),
+      ..entry("Token", Some("mainnet"), Some(19_000 * 86_400))
+    };
+
+    let mut chain: SmartContractTrackingData = HashMap::new();
+    chain.insert("0x01".to_string(), token);
+    chain.insert("0x02".to_string(), entry("Registry", None, None));
+    let mut chains = HashMap::new();
+    chains.insert("0xabcdef0123456789".to_string(), chain);
+
+    let mut networks = BTreeMap::new();
+    networks.insert("mainnet".to_string(), NetworkConfig {
+      url: "https://mainnet.example".to_string(),
+      chain_id: Some(1),
+      accounts: None,
+      gas_price: None,
+      gas_limit: None,
+      explorer: Some("https://etherscan.io/".to_string()),
+      receipts: None,
+      aliases: None,
+      constants: None,
+    });
+
+    let docs = render(&chains, &networks);
+    assert!(docs.contains("\n## Chain 0xabcdef01\n"));
+    assert!(docs.contains("| Registry | `0x0000000000000000000000000000000000000001` | n/a | n/a | n/a | not verified yet | n/a |\n"));
+    assert!(docs.contains("\n## mainnet\n"));
+    assert!(docs.contains("| Token | [`0x0000000000000000000000000000000000000001`](https://etherscan.io/address/0x0000000000000000000000000000000000000001) | v2.1 | `1a2b3c4` | 2022-01-08 | verified | string a\\|b |\n"));
+  }
+
+  #[test]
+  fn it_should_note_missing_deployments() {
+    assert!(render(&HashMap::new(), &BTreeMap::new()).contains("No deployments have been tracked yet."));
+  }
+}
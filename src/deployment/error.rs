@@ -5,26 +5,53 @@ use std::io;
 use toml;
 use toml_query;
 use ethabi;
+use web3::types::U256;
 
 use crate::blockchain;
 use crate::config;
+use crate::policy;
 
 #[derive(Debug)]
 pub enum DeploymentError {
   MissingConfig,
+  ReadOnlyMode,
   InvalidParamType(ethabi::Error),
   TokenizeParam(ethabi::Error, String),
   NothingToDeploy,
   CyclicDependency(String),
+  PlanOrder(String, String),
+  InvalidRedeployCondition(String),
   MissingArtifact(String, String),
   MissingABIPath(String),
   MissingBytecodePath(String),
   TooManyConstructorArgs(String),
+  ConstructorArgsCount(String, usize, usize),
+  ConstructorArgType(String, String, String, String),
   MissingConfigForReference(String),
+  NoTaggedSmartContracts(String),
+  UnknownLibrary(String, String),
+  InvalidProxyKind(String),
+  UnknownInitializer(String, String),
+  NotUpgradeable(String),
+  ProxyNotDeployed(String),
+  MissingStorageLayout(String),
+  IncompatibleStorageLayout(String, Vec<super::upgrades::StorageIncompatibility>),
+  UpgradeFailed(String, web3::types::H256),
+  MissingConstants(Option<String>, Vec<String>),
+  EmbeddedSecrets(Vec<crate::secrets::SecretFinding>),
+  TestsFailed(String),
+  TestsNotRun(String, io::Error),
+  TestRunner(crate::test_runner::error::TestError),
+  Policy(policy::error::PolicyError),
+  PolicyViolation(policy::PolicyReport),
+  PartialDeployment(super::DeploymentReport),
   InvalidAddress(String, String),
   Connection(blockchain::error::ConnectionError),
   DeployContract(web3::contract::deploy::Error, String),
   InvalidConstructorArgs(ethabi::Error, String),
+  MissingAccount,
+  ChainIdMismatch(String, u64, U256),
+  Signing(blockchain::error::SignerError, String),
   TrackingError(DeploymentTrackingError),
   Other(String),
 }
@@ -33,19 +60,44 @@ impl Error for DeploymentError {
   fn cause(&self) -> Option<&Error> {
     match self {
       DeploymentError::MissingConfig => None,
+      DeploymentError::ReadOnlyMode => None,
       DeploymentError::InvalidParamType(error) => Some(error),
       DeploymentError::TokenizeParam(error, _value) => Some(error),
       DeploymentError::NothingToDeploy => None,
       DeploymentError::CyclicDependency(_name) => None,
+      DeploymentError::PlanOrder(_name, _dependency) => None,
+      DeploymentError::InvalidRedeployCondition(_condition) => None,
       DeploymentError::MissingArtifact(_kind, _name) => None,
       DeploymentError::MissingABIPath(_name) => None,
       DeploymentError::MissingBytecodePath(_name) => None,
       DeploymentError::TooManyConstructorArgs(_name) => None,
+      DeploymentError::ConstructorArgsCount(_name, _expected, _given) => None,
+      DeploymentError::ConstructorArgType(_name, _param, _expected, _given) => None,
       DeploymentError::MissingConfigForReference(_reference) => None,
+      DeploymentError::NoTaggedSmartContracts(_tags) => None,
+      DeploymentError::UnknownLibrary(_name, _placeholder) => None,
+      DeploymentError::InvalidProxyKind(_kind) => None,
+      DeploymentError::UnknownInitializer(_name, _initializer) => None,
+      DeploymentError::NotUpgradeable(_name) => None,
+      DeploymentError::ProxyNotDeployed(_name) => None,
+      DeploymentError::MissingStorageLayout(_name) => None,
+      DeploymentError::IncompatibleStorageLayout(_name, _incompatibilities) => None,
+      DeploymentError::UpgradeFailed(_name, _tx_hash) => None,
+      DeploymentError::MissingConstants(_network, _names) => None,
+      DeploymentError::EmbeddedSecrets(_findings) => None,
+      DeploymentError::TestsFailed(_command) => None,
+      DeploymentError::TestsNotRun(_command, error) => Some(error),
+      DeploymentError::TestRunner(error) => Some(error),
+      DeploymentError::Policy(error) => Some(error),
+      DeploymentError::PolicyViolation(_report) => None,
+      DeploymentError::PartialDeployment(_report) => None,
       DeploymentError::InvalidAddress(_name, _message) => None,
       DeploymentError::Connection(error) => Some(error),
       DeploymentError::DeployContract(error, _name) => Some(error),
       DeploymentError::InvalidConstructorArgs(error, _name) => Some(error),
+      DeploymentError::MissingAccount => None,
+      DeploymentError::ChainIdMismatch(_network, _expected, _actual) => None,
+      DeploymentError::Signing(error, _name) => Some(error),
       DeploymentError::TrackingError(error) => Some(error),
       DeploymentError::Other(_message) => None,
     }
@@ -56,19 +108,63 @@ impl fmt::Display for DeploymentError {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
       DeploymentError::MissingConfig => write!(f, "Missing deployment configuration."),
+      DeploymentError::ReadOnlyMode => write!(f, "Couldn't deploy Smart Contracts. Sending transactions and writing tracking data is disabled in read-only mode."),
       DeploymentError::InvalidParamType(error) => write!(f, "Couldn't read Smart Contract constructor parameter: {}", error),
       DeploymentError::TokenizeParam(error, value) => write!(f, "Couldn't tokenize Smart Contract constructor parameter: {} with value {:?}", error, value),
       DeploymentError::NothingToDeploy => write!(f, "Couldn't find artifacts to deploy. Please compile first."),
       DeploymentError::CyclicDependency(name) => write!(f, "Couldn't deploy Smart Contracts due to a cyclic dependency in '{}'", name),
+      DeploymentError::PlanOrder(name, dependency) => write!(f, "Couldn't deploy Smart Contracts as planned. '{}' depends on '{}', which is planned after it.", name, dependency),
+      DeploymentError::InvalidRedeployCondition(condition) => write!(f, "Invalid redeploy condition '{}'. Supported conditions are: always, if-changed, never", condition),
       DeploymentError::MissingArtifact(kind, name) => write!(f, "Couldn't find {} file for artifact '{}'", kind, name),
       DeploymentError::MissingABIPath(name) => write!(f, "Missing `abi_path` for Smart Contract configuration '{}'", name),
       DeploymentError::MissingBytecodePath(name) => write!(f, "Missing `bytecode_path` for Smart Contract configuration '{}'", name),
       DeploymentError::TooManyConstructorArgs(name) => write!(f, "Couldn't deploy Smart Contract '{}' due to too many constructor arguments (max. 10)", name),
+      DeploymentError::ConstructorArgsCount(name, expected, given) => write!(f, "Couldn't deploy Smart Contract '{}'. Its constructor expects {} argument(s), but {} are configured.", name, expected, given),
+      DeploymentError::ConstructorArgType(name, param, expected, given) => write!(f, "Couldn't deploy Smart Contract '{}'. Constructor parameter '{}' is of type {}, but the configured argument is of kind {}.", name, param, expected, given),
       DeploymentError::MissingConfigForReference(reference) => write!(f, "Couldn't find Smart Contract configuration for reference '{}'", reference),
+      DeploymentError::NoTaggedSmartContracts(tags) => write!(f, "Couldn't find Smart Contracts tagged with any of: {}", tags),
+      DeploymentError::UnknownLibrary(name, placeholder) => write!(f, "Couldn't link Smart Contract '{}'. Its bytecode references a library via placeholder {}, which doesn't match any Smart Contract in the deployment configuration.", name, placeholder),
+      DeploymentError::InvalidProxyKind(kind) => write!(f, "Unknown proxy kind '{}'. Supported are erc1967 and transparent", kind),
+      DeploymentError::UnknownInitializer(name, initializer) => write!(f, "Couldn't find initializer '{}' in the ABI of Smart Contract '{}'", initializer, name),
+      DeploymentError::NotUpgradeable(name) => write!(f, "Couldn't upgrade Smart Contract '{}', it isn't configured to be deployed behind a proxy.", name),
+      DeploymentError::ProxyNotDeployed(name) => write!(f, "Couldn't find a tracked proxy of Smart Contract '{}' on the connected chain. Please deploy first.", name),
+      DeploymentError::MissingStorageLayout(name) => write!(f, "Couldn't check the storage layout of Smart Contract '{}'. Compile with `--storage-layout` before deploying and upgrading, or skip the check with --unsafe-skip-storage-check.", name),
+      DeploymentError::IncompatibleStorageLayout(name, incompatibilities) => {
+        write!(f, "Refusing to upgrade Smart Contract '{}', its storage layout is incompatible with the current implementation:", name)?;
+        for incompatibility in incompatibilities {
+          write!(f, "\n  {}", incompatibility)?;
+        }
+        Ok(())
+      },
+      DeploymentError::UpgradeFailed(name, tx_hash) => write!(f, "Couldn't upgrade Smart Contract '{}', transaction {:?} didn't upgrade its proxy.", name, tx_hash),
+      DeploymentError::MissingConstants(None, names) => write!(f, "Couldn't find constants referenced in deployment configuration: {}", names.join(", ")),
+      DeploymentError::MissingConstants(Some(network), names) => write!(f, "Couldn't find constants referenced in deployment configuration for network {}: {}. Define them in [networks.{}.constants] or [constants]", network, names.join(", "), network),
+      DeploymentError::EmbeddedSecrets(findings) => {
+        writeln!(f, "Refusing to deploy to a non-development network with secrets embedded in vibranium.toml:")?;
+        for finding in findings {
+          writeln!(f, "  {}", finding)?;
+        }
+        write!(f, "\nPlease move them to the environment and reference them via `${{env.NAME}}` instead.")
+      },
+      DeploymentError::TestsFailed(command) => write!(f, "Refusing to deploy to a non-development network, as `{}` failed.", command),
+      DeploymentError::TestsNotRun(command, error) => write!(f, "Refusing to deploy to a non-development network, as `{}` couldn't be run: {}", command, error),
+      DeploymentError::TestRunner(error) => write!(f, "Refusing to deploy to a non-development network, as the tests couldn't be run: {}", error),
+      DeploymentError::Policy(error) => write!(f, "{}", error),
+      DeploymentError::PolicyViolation(report) => {
+        write!(f, "Refusing to deploy Smart Contracts violating the deployment policy:")?;
+        for check in report.violations() {
+          write!(f, "\n  {}", check)?;
+        }
+        Ok(())
+      },
+      DeploymentError::PartialDeployment(report) => write!(f, "Couldn't deploy all Smart Contracts, {}", report),
       DeploymentError::InvalidAddress(name, message) => write!(f, "Invalid address in Smart Contract configuration for '{}': {}", name, message),
       DeploymentError::Connection(error) => write!(f, "{}", error),
       DeploymentError::DeployContract(error, name) => write!(f, "Couldn't deploy Smart Contract '{}' due to {}", name, error),
       DeploymentError::InvalidConstructorArgs(_error, name) => write!(f, "Couldn't deploy Smart Contract '{}' due to mismatching types in constructor arguments.", name),
+      DeploymentError::MissingAccount => write!(f, "Couldn't find an account to deploy Smart Contracts from."),
+      DeploymentError::ChainIdMismatch(network, expected, actual) => write!(f, "Refusing to deploy to network {}. Expected chain id {}, but the connected chain has id {}.", network, expected, actual),
+      DeploymentError::Signing(error, name) => write!(f, "Couldn't sign deployment transaction of Smart Contract '{}': {}", name, error),
       DeploymentError::TrackingError(error) => write!(f, "Couldn't track deployed Smart Contracts: {}", error),
       DeploymentError::Other(message) => write!(f, "{}", message),
     }
@@ -109,6 +205,19 @@ impl From<ethabi::Error> for DeploymentError {
 pub enum DeploymentTrackingError {
   Other(String),
   DatabaseNotFound,
+  SmartContractNotTracked(String),
+  InvalidTag(String),
+  InvalidKeyStrategy(String),
+  InvalidSchemaVersion(String),
+  UnsupportedSchemaVersion(i64, i64),
+  InvalidStore(String),
+  UnsupportedStore(String),
+  RekeyConflict(String),
+  ReadOnlyMode,
+  Locked(std::path::PathBuf),
+  UnsupportedAddressBookFormat(String),
+  UnknownEvent(String, String),
+  InvalidEventArg(String, String),
   Deserialization(toml::de::Error),
   Serialization(toml::ser::Error),
   Insertion(toml_query::error::Error),
@@ -122,6 +231,19 @@ impl Error for DeploymentTrackingError {
     match self {
       DeploymentTrackingError::Other(_) => None,
       DeploymentTrackingError::DatabaseNotFound => None,
+      DeploymentTrackingError::SmartContractNotTracked(_name) => None,
+      DeploymentTrackingError::InvalidTag(_tag) => None,
+      DeploymentTrackingError::InvalidKeyStrategy(_strategy) => None,
+      DeploymentTrackingError::InvalidSchemaVersion(_version) => None,
+      DeploymentTrackingError::UnsupportedSchemaVersion(_version, _supported) => None,
+      DeploymentTrackingError::InvalidStore(_store) => None,
+      DeploymentTrackingError::UnsupportedStore(_store) => None,
+      DeploymentTrackingError::RekeyConflict(_name) => None,
+      DeploymentTrackingError::ReadOnlyMode => None,
+      DeploymentTrackingError::Locked(_path) => None,
+      DeploymentTrackingError::UnsupportedAddressBookFormat(_format) => None,
+      DeploymentTrackingError::UnknownEvent(_name, _event) => None,
+      DeploymentTrackingError::InvalidEventArg(_event, _arg) => None,
       DeploymentTrackingError::Deserialization(error) => Some(error),
       DeploymentTrackingError::Serialization(error) => Some(error),
       DeploymentTrackingError::Insertion(_error) => None,
@@ -137,6 +259,19 @@ impl fmt::Display for DeploymentTrackingError {
     match self {
       DeploymentTrackingError::Other(message) => write!(f, "{}", message),
       DeploymentTrackingError::DatabaseNotFound => write!(f, "Couldn't find tracking database"),
+      DeploymentTrackingError::SmartContractNotTracked(name) => write!(f, "Couldn't find tracked Smart Contract '{}' on the connected chain", name),
+      DeploymentTrackingError::InvalidTag(tag) => write!(f, "Invalid tag '{}'. Tags need to be specified as KEY=VALUE", tag),
+      DeploymentTrackingError::InvalidKeyStrategy(strategy) => write!(f, "Unknown tracking key strategy '{}'. Supported are content-hash, name and name-and-args", strategy),
+      DeploymentTrackingError::InvalidSchemaVersion(version) => write!(f, "Invalid tracking data schema version {}, expected a number", version),
+      DeploymentTrackingError::UnsupportedSchemaVersion(version, supported) => write!(f, "Tracking data has been written with schema version {}, but this version of vibranium only supports up to {}. Please upgrade vibranium.", version, supported),
+      DeploymentTrackingError::InvalidStore(store) => write!(f, "Unknown tracking store '{}'. Supported are toml, json and sqlite", store),
+      DeploymentTrackingError::UnsupportedStore(store) => write!(f, "Tracking store '{}' isn't available, vibranium has been built without the `{}` feature", store, store),
+      DeploymentTrackingError::RekeyConflict(name) => write!(f, "Couldn't rekey tracking data, multiple deployments of Smart Contract '{}' would end up with the same key", name),
+      DeploymentTrackingError::ReadOnlyMode => write!(f, "Couldn't update tracking data. Writing tracking data is disabled in read-only mode."),
+      DeploymentTrackingError::Locked(path) => write!(f, "Couldn't update tracking data, it's locked by another vibranium process ({})", path.display()),
+      DeploymentTrackingError::UnsupportedAddressBookFormat(format) => write!(f, "Unsupported address book format '{}', expected 'metamask' or 'safe-addressbook'", format),
+      DeploymentTrackingError::UnknownEvent(name, event) => write!(f, "Couldn't find event '{}' in the ABI of Smart Contract '{}'", event, name),
+      DeploymentTrackingError::InvalidEventArg(event, arg) => write!(f, "Event '{}' doesn't have an address parameter named '{}'", event, arg),
       DeploymentTrackingError::Deserialization(error) => write!(f, "Couldn't deserialize tracking data: {}", error),
       DeploymentTrackingError::Serialization(error) => write!(f, "Couldn't serialize tracking data: {}", error),
       DeploymentTrackingError::Insertion(error) => write!(f, "Couldn't insert tracking data before writing to disc: {}", error),
@@ -0,0 +1,361 @@
+// Events emitted by tracked Smart Contracts, decoded with their ABI. Without
+// a Smart Contract, events of any Smart Contract known to the ABI registry are
+// listed. Logs are fetched in chunks of blocks, as many nodes limit the range
+// `eth_getLogs` can be called with.
+
+use crate::blockchain::blocks::BlockRef;
+use crate::blockchain::connector::BlockchainConnector;
+use crate::config::Config;
+use crate::interface::compliance::topic;
+use super::children::{abi_path, first_block_hash};
+use super::error::DeploymentTrackingError;
+use super::registry::AbiRegistry;
+use super::tracker::{format_token, DeploymentTracker};
+
+use ethabi::RawLog;
+use std::fmt;
+use std::fs;
+use std::thread;
+use std::time::Duration;
+use web3::types::{Address, BlockNumber, FilterBuilder, Log, H256};
+
+const BLOCK_RANGE: u64 = 10_000;
+const FOLLOW_POLL_INTERVAL: u64 = 1000;
+
+pub struct EventsOptions {
+  // Any Smart Contract known to the ABI registry, if not set.
+  pub contract: Option<String>,
+  // All events of the Smart Contract's ABI, if not set.
+  pub event: Option<String>,
+  pub from_block: Option<BlockRef>,
+  pub to_block: Option<BlockRef>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedEvent {
+  // The Smart Contract whose ABI declares the event, if it has been decoded
+  // with the ABI registry.
+  pub contract: Option<String>,
+  pub name: String,
+  pub address: Address,
+  pub block_number: Option<u64>,
+  pub transaction_hash: Option<H256>,
+  pub params: Vec<(String, String)>,
+}
+
+impl fmt::Display for DecodedEvent {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let params: Vec<String> = self.params.iter().map(|(name, value)| format!("{}: {}", name, value)).collect();
+    if let Some(contract) = &self.contract {
+      write!(f, "{}.", contract)?;
+    }
+    write!(f, "{}({})", self.name, params.join(", "))?;
+    if let Some(block_number) = self.block_number {
+      write!(f, " in block {}", block_number)?;
+    }
+    if let Some(transaction_hash) = self.transaction_hash {
+      write!(f, " by transaction {:?}", transaction_hash)?;
+    }
+    Ok(())
+  }
+}
+
+pub struct EventLog<'a> {
+  config: &'a Config,
+  connector: &'a BlockchainConnector,
+}
+
+impl<'a> EventLog<'a> {
+  pub fn new(config: &'a Config, connector: &'a BlockchainConnector) -> EventLog<'a> {
+    EventLog {
+      config,
+      connector,
+    }
+  }
+
+  // Events up to `to_block`, or the latest block, in the order they've been
+  // emitted in.
+  pub fn events(&self, options: &EventsOptions) -> Result<Vec<DecodedEvent>, DeploymentTrackingError> {
+    let (address, decoder) = self.resolve(options)?;
+    let from_block = match &options.from_block {
+      Some(from_block) => self.resolve_block(from_block)?,
+      None => 0,
+    };
+    let to_block = match &options.to_block {
+      Some(to_block) => self.resolve_block(to_block)?,
+      None => self.latest_block()?,
+    };
+    self.fetch(address, &decoder, from_block, to_block)
+  }
+
+  // Keeps fetching events of new blocks, starting after the latest one unless
+  // `from_block` says otherwise. New blocks are subscribed to on WebSocket
+  // transports and polled for otherwise. Only returns on errors.
+  pub fn follow<F: FnMut(&DecodedEvent)>(&self, options: &EventsOptions, mut on_event: F) -> Result<(), DeploymentTrackingError> {
+    let (address, decoder) = self.resolve(options)?;
+    let new_heads = self.connector.subscribe_new_heads();
+    let interval = Duration::from_millis(FOLLOW_POLL_INTERVAL);
+    let mut next_block = match &options.from_block {
+      Some(from_block) => self.resolve_block(from_block)?,
+      None => self.latest_block()? + 1,
+    };
+
+    loop {
+      let latest_block = self.latest_block()?;
+      if latest_block >= next_block {
+        for event in self.fetch(address, &decoder, next_block, latest_block)? {
+          on_event(&event);
+        }
+        next_block = latest_block + 1;
+      }
+
+      match &new_heads {
+        Some(new_heads) => {
+          let _ = new_heads.recv_timeout(interval);
+        },
+        None => thread::sleep(interval),
+      }
+    }
+  }
+
+  // Address of the latest tracked deployment and the events to decode.
+  fn resolve(&self, options: &EventsOptions) -> Result<(Option<Address>, Decoder), DeploymentTrackingError> {
+    let name = match &options.contract {
+      Some(name) => name,
+      None => {
+        let registry = AbiRegistry::load(self.config)?;
+        if let Some(event) = &options.event {
+          if registry.events(Some(event)).is_empty() {
+            return Err(DeploymentTrackingError::Other(format!("Couldn't find event '{}' in the ABI of any known Smart Contract", event)));
+          }
+        }
+        return Ok((None, Decoder::Registry(registry, options.event.to_owned())));
+      }
+    };
+
+    let tracker = DeploymentTracker::new(self.config);
+    let block_hash = first_block_hash(self.connector)?;
+    let tracked_contract = tracker.get_latest_smart_contract_tracking_data(&block_hash, name)?
+      .ok_or_else(|| DeploymentTrackingError::SmartContractNotTracked(name.to_owned()))?;
+
+    let contract = ethabi::Contract::load(fs::read(abi_path(self.config, name)?)?.as_slice())
+      .map_err(|err| DeploymentTrackingError::Other(format!("Couldn't read ABI of {}: {}", name, err)))?;
+
+    let events = match &options.event {
+      Some(event) => vec![contract.event(event)
+        .map_err(|_err| DeploymentTrackingError::UnknownEvent(name.to_owned(), event.to_owned()))?
+        .clone()],
+      None => contract.events().cloned().collect(),
+    };
+    Ok((Some(tracked_contract.address), Decoder::Abi(events)))
+  }
+
+  fn fetch(&self, address: Option<Address>, decoder: &Decoder, from_block: u64, to_block: u64) -> Result<Vec<DecodedEvent>, DeploymentTrackingError> {
+    let signatures = decoder.signatures();
+    let mut decoded = vec![];
+    let mut start = from_block;
+
+    while start <= to_block {
+      let end = to_block.min(start + BLOCK_RANGE - 1);
+      let mut filter = FilterBuilder::default()
+        .topics(Some(signatures.clone()), None, None, None)
+        .from_block(BlockNumber::Number(start))
+        .to_block(BlockNumber::Number(end));
+      if let Some(address) = address {
+        filter = filter.address(vec![address]);
+      }
+
+      for log in self.connector.logs(filter.build()).map_err(|err| DeploymentTrackingError::Other(err.to_string()))? {
+        if log.removed == Some(true) {
+          continue;
+        }
+        if let Some(event) = decoder.decode(log)? {
+          decoded.push(event);
+        }
+      }
+      start = end + 1;
+    }
+    Ok(decoded)
+  }
+
+  fn resolve_block(&self, block: &BlockRef) -> Result<u64, DeploymentTrackingError> {
+    block.resolve(self.connector).map_err(|err| DeploymentTrackingError::Other(err.to_string()))
+  }
+
+  fn latest_block(&self) -> Result<u64, DeploymentTrackingError> {
+    self.connector.block_number()
+      .map(|block_number| block_number.low_u64())
+      .map_err(|err| DeploymentTrackingError::Other(err.to_string()))
+  }
+}
+
+// Events are decoded with the ABI of the requested Smart Contract, or with the
+// ABI registry, optionally limited to events of the given name.
+enum Decoder {
+  Abi(Vec<ethabi::Event>),
+  Registry(AbiRegistry, Option<String>),
+}
+
+impl Decoder {
+  // Anonymous events can't be told apart by their signature.
+  fn signatures(&self) -> Vec<H256> {
+    match self {
+      Decoder::Abi(events) => events.iter().filter(|event| !event.anonymous).map(|event| event.signature()).collect(),
+      Decoder::Registry(registry, name) => {
+        let mut signatures: Vec<H256> = registry.events(name.as_deref()).iter().map(|registered| H256::from(topic(&registered.entry))).collect();
+        signatures.dedup();
+        signatures
+      },
+    }
+  }
+
+  fn decode(&self, log: Log) -> Result<Option<DecodedEvent>, DeploymentTrackingError> {
+    match self {
+      Decoder::Abi(events) => decode(events, log),
+      Decoder::Registry(registry, _name) => Ok(registry.decode_log(&log)),
+    }
+  }
+}
+
+fn decode(events: &[ethabi::Event], log: Log) -> Result<Option<DecodedEvent>, DeploymentTrackingError> {
+  let event = match log.topics.first().and_then(|topic| events.iter().find(|event| !event.anonymous && event.signature() == *topic)) {
+    Some(event) => event,
+    None => return Ok(None),
+  };
+  let parsed = event.parse_log(RawLog { topics: log.topics, data: log.data.0 })
+    .map_err(|err| DeploymentTrackingError::Other(format!("Couldn't decode {} event: {}", &event.name, err)))?;
+
+  Ok(Some(DecodedEvent {
+    contract: None,
+    name: event.name.to_owned(),
+    address: log.address,
+    block_number: log.block_number.map(|block_number| block_number.low_u64()),
+    transaction_hash: log.transaction_hash,
+    params: parsed.params.iter().map(|param| (param.name.to_owned(), format_token(&param.value))).collect(),
+  }))
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{EventLog, EventsOptions};
+  use crate::blockchain::blocks::BlockRef;
+  use crate::config::Config;
+  use crate::deployment::error::DeploymentTrackingError;
+  use crate::deployment::tracker::DeploymentTracker;
+  use crate::testing::MockTransport;
+  use std::fs;
+  use std::str::FromStr;
+  use web3::types::{Address, Block, H256};
+
+  const BLOCK_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+  const ABI: &str = r#"[
+    {"anonymous":false,"inputs":[{"indexed":true,"name":"from","type":"address"},{"indexed":false,"name":"value","type":"uint256"}],"name":"Deposit","type":"event"},
+    {"anonymous":false,"inputs":[{"indexed":false,"name":"paused","type":"bool"}],"name":"Paused","type":"event"}
+  ]"#;
+  // keccak256("Deposit(address,uint256)")
+  const DEPOSIT_TOPIC: &str = "0xe1fffcc4923d04b559f4d29a8bfc6cda04eb5b0d3c460751c2402c5c5cc9109c";
+
+  fn setup_project() -> (tempfile::TempDir, Config) {
+    let tmp_dir = crate::testing::project_with_config("
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+    ");
+    fs::create_dir_all(tmp_dir.path().join("artifacts")).unwrap();
+    fs::write(tmp_dir.path().join("artifacts").join("Vault.abi"), ABI).unwrap();
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let tracker = DeploymentTracker::new(&config);
+    tracker.create_database().unwrap();
+    tracker.track(H256::from_str(&BLOCK_HASH[2..]).unwrap(), "Vault".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(9), None).unwrap();
+    (tmp_dir, config)
+  }
+
+  fn mock_transport() -> MockTransport {
+    let transport = MockTransport::new();
+    transport.set_default_response("eth_getBlockByNumber", serde_json::to_value(Block::<H256> {
+      hash: Some(H256::from_str(&BLOCK_HASH[2..]).unwrap()),
+      ..Block::default()
+    }).unwrap());
+    transport
+  }
+
+  #[test]
+  fn it_should_decode_events_of_tracked_smart_contracts() {
+    let (_tmp_dir, config) = setup_project();
+    let transport = mock_transport();
+    transport.add_response("eth_blockNumber", serde_json::json!("0x10"));
+    transport.add_response("eth_getLogs", serde_json::json!([{
+      "address": "0x0000000000000000000000000000000000000009",
+      "topics": [DEPOSIT_TOPIC, format!("0x{:064x}", 1)],
+      "data": format!("0x{:064x}", 100),
+      "blockNumber": "0x3",
+      "transactionHash": BLOCK_HASH,
+    }]));
+
+    let connector = transport.connector();
+    let events = EventLog::new(&config, &connector).events(&EventsOptions {
+      contract: Some("Vault".to_string()),
+      event: None,
+      from_block: Some(BlockRef::Number(2)),
+      to_block: None,
+    }).unwrap();
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].params, vec![("from".to_string(), "0x0000000000000000000000000000000000000001".to_string()), ("value".to_string(), "100".to_string())]);
+    assert_eq!(events[0].to_string(), format!("Deposit(from: 0x0000000000000000000000000000000000000001, value: 100) in block 3 by transaction {}", BLOCK_HASH));
+
+    let request = &transport.requests_for("eth_getLogs")[0][0];
+    assert_eq!(request["address"], "0x0000000000000000000000000000000000000009");
+    assert_eq!(request["fromBlock"], "0x2");
+    assert_eq!(request["toBlock"], "0x10");
+    assert_eq!(request["topics"][0].as_array().unwrap().len(), 2);
+  }
+
+  #[test]
+  fn it_should_decode_events_of_any_known_smart_contract() {
+    let (_tmp_dir, config) = setup_project();
+    let transport = mock_transport();
+    transport.add_response("eth_blockNumber", serde_json::json!("0x10"));
+    transport.add_response("eth_getLogs", serde_json::json!([{
+      "address": "0x00000000000000000000000000000000000000d0",
+      "topics": [DEPOSIT_TOPIC, format!("0x{:064x}", 1)],
+      "data": format!("0x{:064x}", 100),
+      "blockNumber": "0x3",
+    }]));
+
+    let connector = transport.connector();
+    let events = EventLog::new(&config, &connector).events(&EventsOptions {
+      contract: None,
+      event: Some("Deposit".to_string()),
+      from_block: None,
+      to_block: None,
+    }).unwrap();
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].address, Address::from_low_u64_be(0xd0));
+    assert_eq!(events[0].to_string(), "Vault.Deposit(from: 0x0000000000000000000000000000000000000001, value: 100) in block 3");
+
+    let request = &transport.requests_for("eth_getLogs")[0][0];
+    assert!(request.get("address").is_none());
+    assert_eq!(request["topics"][0], DEPOSIT_TOPIC);
+  }
+
+  #[test]
+  fn it_should_fail_on_events_missing_in_the_abi() {
+    let (_tmp_dir, config) = setup_project();
+    let connector = mock_transport().connector();
+    let result = EventLog::new(&config, &connector).events(&EventsOptions {
+      contract: Some("Vault".to_string()),
+      event: Some("Withdrawal".to_string()),
+      from_block: None,
+      to_block: None,
+    });
+
+    match result {
+      Err(DeploymentTrackingError::UnknownEvent(contract, event)) => assert_eq!((contract, event), ("Vault".to_string(), "Withdrawal".to_string())),
+      other => panic!("Expected unknown event, got {:?}", other),
+    }
+  }
+}
@@ -0,0 +1,172 @@
+// Gas estimates of deploying the configured Smart Contracts and, for the ones
+// that have been deployed already, of calling their state changing functions.
+// Functions are called with default arguments, i.e. zero values, empty
+// strings and arrays and the sender for addresses, so estimates of functions
+// whose costs depend on their input are only a rough guide.
+
+use crate::interface::AbiEntry;
+
+use ethabi::param_type::Reader;
+use ethabi::{ParamType, Token};
+use std::fmt;
+use web3::types::{Address, U256};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionGasEstimate {
+  pub signature: String,
+  // Calls with default arguments that revert can't be estimated.
+  pub gas: Option<U256>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasEstimate {
+  pub name: String,
+  pub deployment: Option<U256>,
+  // The gas limit the Smart Contract is deployed with.
+  pub gas_limit: U256,
+  pub address: Option<Address>,
+  pub functions: Vec<FunctionGasEstimate>,
+}
+
+impl GasEstimate {
+  pub fn exceeds_gas_limit(&self) -> bool {
+    self.deployment.is_some_and(|gas| gas > self.gas_limit)
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct GasReport {
+  pub block_gas_limit: Option<U256>,
+  pub estimates: Vec<GasEstimate>,
+}
+
+impl GasReport {
+  pub fn exceeds_block_gas_limit(&self, estimate: &GasEstimate) -> bool {
+    match (estimate.deployment, self.block_gas_limit) {
+      (Some(gas), Some(block_gas_limit)) => gas > block_gas_limit,
+      _ => false,
+    }
+  }
+
+  // Smart Contracts that can't be deployed with the current configuration.
+  pub fn violations(&self) -> Vec<GasLimitViolation> {
+    self.estimates.iter().flat_map(|estimate| {
+      let mut violations = vec![];
+      if estimate.exceeds_gas_limit() {
+        violations.push(GasLimitViolation::GasLimit(estimate.name.to_owned(), estimate.deployment.unwrap_or_default(), estimate.gas_limit));
+      }
+      if self.exceeds_block_gas_limit(estimate) {
+        violations.push(GasLimitViolation::BlockGasLimit(estimate.name.to_owned(), estimate.deployment.unwrap_or_default(), self.block_gas_limit.unwrap_or_default()));
+      }
+      violations
+    }).collect()
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GasLimitViolation {
+  GasLimit(String, U256, U256),
+  BlockGasLimit(String, U256, U256),
+}
+
+impl fmt::Display for GasLimitViolation {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      GasLimitViolation::GasLimit(name, gas, limit) => write!(f, "Deploying {} needs {} gas, exceeding its configured gas limit of {}", name, gas, limit),
+      GasLimitViolation::BlockGasLimit(name, gas, limit) => write!(f, "Deploying {} needs {} gas, exceeding the block gas limit of {}", name, gas, limit),
+    }
+  }
+}
+
+// Calls of state changing functions along with their signature, in the order
+// of the ABI. Functions taking tuples aren't supported by the ABI encoder and
+// are left out.
+pub fn function_calls(abi: &[AbiEntry], sender: Address) -> Vec<(String, Vec<u8>)> {
+  abi.iter()
+    .filter(|entry| entry.kind == "function" && is_state_changing(entry))
+    .filter_map(|entry| {
+      let name = entry.name.as_deref()?;
+      let param_types = entry.inputs.iter().flatten()
+        .map(|param| Reader::read(&param.kind).ok())
+        .collect::<Option<Vec<ParamType>>>()?;
+      let tokens: Vec<Token> = param_types.iter().map(|kind| default_token(kind, sender)).collect();
+      let signature = format!("{}({})", name, param_types.iter().map(ToString::to_string).collect::<Vec<String>>().join(","));
+      let function = ethabi::Function {
+        name: name.to_string(),
+        inputs: param_types.into_iter().map(|kind| ethabi::Param { name: String::new(), kind }).collect(),
+        outputs: vec![],
+        constant: false,
+      };
+      function.encode_input(&tokens).ok().map(|data| (signature, data))
+    })
+    .collect()
+}
+
+fn is_state_changing(entry: &AbiEntry) -> bool {
+  match entry.state_mutability.as_deref() {
+    Some(mutability) => mutability != "view" && mutability != "pure",
+    None => !entry.constant.unwrap_or(false),
+  }
+}
+
+fn default_token(kind: &ParamType, sender: Address) -> Token {
+  match kind {
+    ParamType::Address => Token::Address(sender),
+    ParamType::Bytes => Token::Bytes(vec![]),
+    ParamType::Int(_) => Token::Int(U256::zero()),
+    ParamType::Uint(_) => Token::Uint(U256::zero()),
+    ParamType::Bool => Token::Bool(false),
+    ParamType::String => Token::String(String::new()),
+    ParamType::Array(_) => Token::Array(vec![]),
+    ParamType::FixedBytes(size) => Token::FixedBytes(vec![0; *size]),
+    ParamType::FixedArray(kind, size) => Token::FixedArray(vec![default_token(kind, sender); *size]),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{function_calls, GasEstimate, GasLimitViolation, GasReport};
+  use crate::interface::AbiEntry;
+  use web3::types::{Address, U256};
+
+  #[test]
+  fn it_should_encode_calls_of_state_changing_functions_with_default_arguments() {
+    let abi: Vec<AbiEntry> = serde_json::from_str(r#"[
+      {"inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}],"name":"transfer","outputs":[],"stateMutability":"nonpayable","type":"function"},
+      {"inputs":[{"name":"account","type":"address"}],"name":"balanceOf","outputs":[{"name":"","type":"uint256"}],"stateMutability":"view","type":"function"},
+      {"constant":true,"inputs":[],"name":"owner","outputs":[{"name":"","type":"address"}],"type":"function"},
+      {"inputs":[{"name":"order","type":"tuple","components":[{"name":"amount","type":"uint256"}]}],"name":"fill","outputs":[],"stateMutability":"payable","type":"function"},
+      {"inputs":[],"stateMutability":"nonpayable","type":"constructor"}
+    ]"#).unwrap();
+
+    let calls = function_calls(&abi, Address::from_low_u64_be(1));
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].0, "transfer(address,uint256)");
+    assert_eq!(&calls[0].1[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+    assert_eq!(calls[0].1.len(), 68);
+    assert_eq!(calls[0].1[35], 1);
+  }
+
+  #[test]
+  fn it_should_report_estimates_exceeding_gas_limits() {
+    let estimate = |name: &str, gas: u64| GasEstimate {
+      name: name.to_string(),
+      deployment: Some(U256::from(gas)),
+      gas_limit: U256::from(2_000_000),
+      address: None,
+      functions: vec![],
+    };
+    let report = GasReport {
+      block_gas_limit: Some(U256::from(3_000_000)),
+      estimates: vec![estimate("Token", 1_000_000), estimate("Exchange", 2_500_000), estimate("Registry", 4_000_000)],
+    };
+
+    assert_eq!(report.violations(), vec![
+      GasLimitViolation::GasLimit("Exchange".to_string(), U256::from(2_500_000), U256::from(2_000_000)),
+      GasLimitViolation::GasLimit("Registry".to_string(), U256::from(4_000_000), U256::from(2_000_000)),
+      GasLimitViolation::BlockGasLimit("Registry".to_string(), U256::from(4_000_000), U256::from(3_000_000)),
+    ]);
+    assert_eq!(report.violations()[0].to_string(), "Deploying Exchange needs 2500000 gas, exceeding its configured gas limit of 2000000");
+  }
+}
@@ -0,0 +1,206 @@
+// Transactions decoded with the ABI registry: the function that has been
+// called, the events that have been emitted and, for failed transactions, the
+// error they reverted with. The error is recovered by replaying the
+// transaction as a call on top of the block before its own.
+
+use crate::blockchain::connector::BlockchainConnector;
+use crate::config::Config;
+use super::children::first_block_hash;
+use super::error::DeploymentTrackingError;
+use super::events::DecodedEvent;
+use super::registry::{revert_data, AbiRegistry, DecodedCall, DecodedError};
+use super::tracker::DeploymentTracker;
+
+use web3::types::{Address, BlockNumber, CallRequest, Transaction, H256};
+
+#[derive(Debug)]
+pub struct TransactionInspection {
+  pub hash: H256,
+  pub from: Address,
+  pub to: Option<Address>,
+  // Name of the tracked Smart Contract the transaction has been sent to.
+  pub contract: Option<String>,
+  pub block_number: Option<u64>,
+  // Not known for pending transactions.
+  pub succeeded: Option<bool>,
+  pub gas_used: Option<u64>,
+  pub call: Option<DecodedCall>,
+  pub events: Vec<DecodedEvent>,
+  // Logs whose event isn't declared by any known ABI.
+  pub unknown_logs: usize,
+  pub revert: Option<DecodedError>,
+}
+
+pub struct TransactionInspector<'a> {
+  config: &'a Config,
+  connector: &'a BlockchainConnector,
+}
+
+impl<'a> TransactionInspector<'a> {
+  pub fn new(config: &'a Config, connector: &'a BlockchainConnector) -> TransactionInspector<'a> {
+    TransactionInspector {
+      config,
+      connector,
+    }
+  }
+
+  pub fn inspect(&self, hash: H256) -> Result<TransactionInspection, DeploymentTrackingError> {
+    let transaction = self.connector.transaction(hash)
+      .map_err(|err| DeploymentTrackingError::Other(err.to_string()))?
+      .ok_or_else(|| DeploymentTrackingError::Other(format!("Couldn't find transaction {:?}", hash)))?;
+    let receipt = self.connector.transaction_receipt(hash).map_err(|err| DeploymentTrackingError::Other(err.to_string()))?;
+    let registry = AbiRegistry::load(self.config)?;
+
+    let logs = receipt.as_ref().map(|receipt| receipt.logs.clone()).unwrap_or_default();
+    let events: Vec<DecodedEvent> = logs.iter().filter_map(|log| registry.decode_log(log)).collect();
+    let succeeded = receipt.as_ref().and_then(|receipt| receipt.status).map(|status| status.low_u64() == 1);
+
+    Ok(TransactionInspection {
+      hash,
+      from: transaction.from,
+      to: transaction.to,
+      contract: transaction.to.and_then(|to| self.tracked_contract(to)),
+      block_number: transaction.block_number.map(|block_number| block_number.low_u64()),
+      succeeded,
+      gas_used: receipt.as_ref().and_then(|receipt| receipt.gas_used).map(|gas_used| gas_used.low_u64()),
+      call: transaction.to.and_then(|_to| registry.decode_call(&transaction.input.0)),
+      unknown_logs: logs.len() - events.len(),
+      events,
+      revert: if succeeded == Some(false) { self.revert(&transaction, &registry) } else { None },
+    })
+  }
+
+  // Creations of Smart Contracts can't be replayed as calls.
+  fn revert(&self, transaction: &Transaction, registry: &AbiRegistry) -> Option<DecodedError> {
+    let request = CallRequest {
+      from: Some(transaction.from),
+      to: transaction.to?,
+      gas: Some(transaction.gas),
+      gas_price: Some(transaction.gas_price),
+      value: Some(transaction.value),
+      data: Some(transaction.input.clone()),
+    };
+    let block = transaction.block_number.map(|block_number| BlockNumber::Number(block_number.low_u64().saturating_sub(1)));
+    match self.connector.call(request, block) {
+      Ok(data) => registry.decode_error(&data.0),
+      Err(err) => revert_data(&err).and_then(|data| registry.decode_error(&data)),
+    }
+  }
+
+  fn tracked_contract(&self, address: Address) -> Option<String> {
+    let tracker = DeploymentTracker::new(self.config);
+    let block_hash = first_block_hash(self.connector).ok()?;
+    tracker.get_all_smart_contract_tracking_data(&block_hash).ok()??
+      .into_values()
+      .find(|entry| entry.address == address)
+      .map(|entry| entry.name)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::TransactionInspector;
+  use crate::config::Config;
+  use crate::deployment::tracker::DeploymentTracker;
+  use crate::testing::MockTransport;
+  use std::fs;
+  use web3::types::{Address, Block, H256};
+
+  const ABI: &str = r#"[
+    {"inputs":[{"name":"amount","type":"uint256"}],"name":"withdraw","outputs":[],"stateMutability":"nonpayable","type":"function"},
+    {"anonymous":false,"inputs":[{"indexed":false,"name":"amount","type":"uint256"}],"name":"Withdrawal","type":"event"}
+  ]"#;
+  // keccak256("withdraw(uint256)")[..4] and keccak256("Withdrawal(uint256)")
+  const WITHDRAW_SELECTOR: &str = "2e1a7d4d";
+  const WITHDRAWAL_TOPIC: &str = "0x4e70a604b23a8edee2b1d0a656e9b9c00b73ad8bb1afc2c59381ee9f69197de7";
+  const TX_HASH: &str = "0x00000000000000000000000000000000000000000000000000000000000000aa";
+
+  fn setup_project() -> (tempfile::TempDir, Config, MockTransport) {
+    let tmp_dir = crate::testing::project_with_config("
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+    ");
+    fs::create_dir_all(tmp_dir.path().join("artifacts")).unwrap();
+    fs::write(tmp_dir.path().join("artifacts").join("Vault.abi"), ABI).unwrap();
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let tracker = DeploymentTracker::new(&config);
+    tracker.create_database().unwrap();
+    tracker.track(H256::from_low_u64_be(1), "Vault".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(9), None).unwrap();
+
+    let transport = MockTransport::new();
+    transport.set_default_response("eth_getBlockByNumber", serde_json::to_value(Block::<H256> {
+      hash: Some(H256::from_low_u64_be(1)),
+      ..Block::default()
+    }).unwrap());
+    transport.add_response("eth_getTransactionByHash", serde_json::json!({
+      "hash": TX_HASH,
+      "nonce": "0x0",
+      "blockHash": format!("0x{:064x}", 2),
+      "blockNumber": "0x5",
+      "transactionIndex": "0x0",
+      "from": "0x0000000000000000000000000000000000000001",
+      "to": "0x0000000000000000000000000000000000000009",
+      "value": "0x0",
+      "gasPrice": "0x1",
+      "gas": "0x5208",
+      "input": format!("0x{}{:064x}", WITHDRAW_SELECTOR, 7),
+    }));
+    (tmp_dir, config, transport)
+  }
+
+  fn receipt(status: &str, logs: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+      "transactionHash": TX_HASH,
+      "transactionIndex": "0x0",
+      "blockHash": format!("0x{:064x}", 2),
+      "blockNumber": "0x5",
+      "cumulativeGasUsed": "0x5208",
+      "gasUsed": "0x5208",
+      "contractAddress": null,
+      "logs": logs,
+      "status": status,
+      "logsBloom": format!("0x{}", "0".repeat(512)),
+    })
+  }
+
+  #[test]
+  fn it_should_decode_calls_and_events_of_transactions() {
+    let (_tmp_dir, config, transport) = setup_project();
+    transport.add_response("eth_getTransactionReceipt", receipt("0x1", serde_json::json!([
+      { "address": "0x0000000000000000000000000000000000000009", "topics": [WITHDRAWAL_TOPIC], "data": format!("0x{:064x}", 7) },
+      { "address": "0x0000000000000000000000000000000000000009", "topics": [format!("0x{:064x}", 3)], "data": "0x" },
+    ])));
+
+    let connector = transport.connector();
+    let inspection = TransactionInspector::new(&config, &connector).inspect(H256::from_low_u64_be(0xaa)).unwrap();
+
+    assert_eq!(inspection.contract, Some("Vault".to_string()));
+    assert_eq!(inspection.succeeded, Some(true));
+    assert_eq!(inspection.gas_used, Some(21000));
+    assert_eq!(inspection.call.unwrap().to_string(), "Vault.withdraw(amount: 7)");
+    assert_eq!(inspection.events.iter().map(|event| event.to_string()).collect::<Vec<String>>(), vec!["Vault.Withdrawal(amount: 7)"]);
+    assert_eq!(inspection.unknown_logs, 1);
+    assert!(inspection.revert.is_none());
+    assert!(transport.requests_for("eth_call").is_empty());
+  }
+
+  #[test]
+  fn it_should_decode_revert_reasons_of_failed_transactions() {
+    let (_tmp_dir, config, transport) = setup_project();
+    transport.add_response("eth_getTransactionReceipt", receipt("0x0", serde_json::json!([])));
+    let mut error = jsonrpc_core::Error::new(jsonrpc_core::ErrorCode::ServerError(3));
+    // Panic(0x11), an arithmetic overflow
+    error.data = Some(serde_json::json!(format!("0x4e487b71{:064x}", 0x11)));
+    transport.add_error("eth_call", error);
+
+    let connector = transport.connector();
+    let inspection = TransactionInspector::new(&config, &connector).inspect(H256::from_low_u64_be(0xaa)).unwrap();
+
+    assert_eq!(inspection.succeeded, Some(false));
+    assert_eq!(inspection.revert.unwrap().to_string(), "Panic(17)");
+    assert_eq!(transport.requests_for("eth_call")[0][1], serde_json::json!("0x4"));
+  }
+}
@@ -0,0 +1,171 @@
+use crate::config::Config;
+use crate::project_generator::VIBRANIUM_PROJECT_DIRECTORY;
+
+use super::error::DeploymentTrackingError;
+use super::tracker::{create_block_hash, create_smart_contract_hash};
+
+use sha3::{Digest, Keccak256};
+use std::fs;
+use std::path::PathBuf;
+use web3::types::{Address, H256, U256};
+
+pub const JOURNAL_DIRECTORY: &str = "journal";
+
+// Written before a deployment transaction is broadcast and removed once the
+// deployed Smart Contract has been tracked. An entry that is still around on
+// the next deployment means the previous run didn't finish.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+  pub name: String,
+  pub chain: String,
+  pub smart_contract: String,
+  pub from: Address,
+  pub nonce: U256,
+  pub tx_hash: Option<H256>,
+}
+
+impl JournalEntry {
+  pub fn new(block_hash: &H256, name: &str, byte_code: &str, args: &Vec<String>, from: Address, nonce: U256) -> JournalEntry {
+    JournalEntry {
+      name: name.to_owned(),
+      chain: create_block_hash(block_hash),
+      smart_contract: create_smart_contract_hash(name, byte_code, args),
+      from,
+      nonce,
+      tx_hash: None,
+    }
+  }
+
+  // Entries only apply to the same Smart Contract (bytecode and arguments
+  // included) on the same chain.
+  pub fn is_for(&self, block_hash: &H256, name: &str, byte_code: &str, args: &Vec<String>) -> bool {
+    self.chain == create_block_hash(block_hash) && self.smart_contract == create_smart_contract_hash(name, byte_code, args)
+  }
+
+  // Smart Contracts created by a transaction end up at an address derived
+  // from the sender and the transaction's nonce.
+  pub fn contract_address(&self) -> Address {
+    contract_address(self.from, self.nonce)
+  }
+}
+
+pub struct DeploymentJournal<'a> {
+  config: &'a Config,
+}
+
+impl<'a> DeploymentJournal<'a> {
+  pub fn new(config: &'a Config) -> DeploymentJournal<'a> {
+    DeploymentJournal {
+      config,
+    }
+  }
+
+  pub fn read(&self, name: &str) -> Result<Option<JournalEntry>, DeploymentTrackingError> {
+    let path = self.get_entry_file(name);
+    if !path.exists() {
+      return Ok(None);
+    }
+    Ok(Some(toml::from_str(&fs::read_to_string(path)?)?))
+  }
+
+  pub fn write(&self, entry: &JournalEntry) -> Result<(), DeploymentTrackingError> {
+    fs::create_dir_all(self.get_journal_dir())?;
+    fs::write(self.get_entry_file(&entry.name), toml::to_string(entry)?)?;
+    Ok(())
+  }
+
+  pub fn remove(&self, name: &str) -> Result<(), DeploymentTrackingError> {
+    let path = self.get_entry_file(name);
+    if path.exists() {
+      fs::remove_file(path)?;
+    }
+    Ok(())
+  }
+
+  pub fn remove_chain(&self, block_hash: &H256) -> Result<(), DeploymentTrackingError> {
+    let journal_dir = self.get_journal_dir();
+    if !journal_dir.exists() {
+      return Ok(());
+    }
+    let chain = create_block_hash(block_hash);
+    for file in fs::read_dir(journal_dir)? {
+      let path = file?.path();
+      let entry: Option<JournalEntry> = fs::read_to_string(&path).ok().and_then(|content| toml::from_str(&content).ok());
+      if entry.is_some_and(|entry| entry.chain == chain) {
+        fs::remove_file(path)?;
+      }
+    }
+    Ok(())
+  }
+
+  fn get_journal_dir(&self) -> PathBuf {
+    self.config.project_path.join(VIBRANIUM_PROJECT_DIRECTORY).join(JOURNAL_DIRECTORY)
+  }
+
+  fn get_entry_file(&self, name: &str) -> PathBuf {
+    self.get_journal_dir().join(format!("{}.toml", name))
+  }
+}
+
+// keccak256(rlp([sender, nonce]))[12..]
+pub fn contract_address(from: Address, nonce: U256) -> Address {
+  let nonce_bytes: Vec<u8> = {
+    let mut bytes = [0u8; 32];
+    nonce.to_big_endian(&mut bytes);
+    bytes.iter().skip_while(|byte| **byte == 0).cloned().collect()
+  };
+
+  let mut encoded_nonce = vec![];
+  match nonce_bytes.as_slice() {
+    [] => encoded_nonce.push(0x80),
+    [byte] if *byte < 0x80 => encoded_nonce.push(*byte),
+    bytes => {
+      encoded_nonce.push(0x80 + bytes.len() as u8);
+      encoded_nonce.extend_from_slice(bytes);
+    }
+  }
+
+  let mut rlp = vec![0xc0 + (21 + encoded_nonce.len()) as u8, 0x94];
+  rlp.extend_from_slice(from.as_bytes());
+  rlp.extend_from_slice(&encoded_nonce);
+
+  Address::from_slice(&Keccak256::digest(&rlp)[12..])
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{contract_address, DeploymentJournal, JournalEntry};
+  use crate::config::Config;
+  use std::str::FromStr;
+  use web3::types::{Address, H256, U256};
+
+  #[test]
+  fn it_should_derive_contract_addresses() {
+    let from = Address::from_str("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0").unwrap();
+
+    assert_eq!(contract_address(from, U256::from(0)), Address::from_str("cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d").unwrap());
+    assert_eq!(contract_address(from, U256::from(1)), Address::from_str("343c43a37d37dff08ae8c4a11544c718abb4fcf8").unwrap());
+  }
+
+  #[test]
+  fn it_should_read_write_and_remove_entries() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let journal = DeploymentJournal::new(&config);
+    let block_hash = H256::from_low_u64_be(1);
+    let args = vec!["1".to_string()];
+
+    let mut entry = JournalEntry::new(&block_hash, "Simple", "6080", &args, Address::from_low_u64_be(1), U256::from(7));
+    entry.tx_hash = Some(H256::from_low_u64_be(2));
+    journal.write(&entry).unwrap();
+
+    let read = journal.read("Simple").unwrap().unwrap();
+    assert_eq!(read, entry);
+    assert!(read.is_for(&block_hash, "Simple", "6080", &args));
+    assert!(!read.is_for(&block_hash, "Simple", "6081", &args));
+
+    journal.remove("Simple").unwrap();
+    assert!(journal.read("Simple").unwrap().is_none());
+  }
+}
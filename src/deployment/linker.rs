@@ -0,0 +1,80 @@
+// Bytecode of Smart Contracts using external libraries contains placeholders
+// where the libraries' addresses go. Depending on the compiler version these
+// are either `__$<keccak256 of the library's fully qualified name>$__` or the
+// fully qualified name itself, padded or truncated to fit. Either way they're
+// 40 characters long, just like the addresses they're replaced with.
+
+use rustc_hex::ToHex;
+use sha3::{Digest, Keccak256};
+use web3::types::Address;
+
+const PLACEHOLDER_LENGTH: usize = 40;
+const PLACEHOLDER_DELIMITER: &str = "__";
+
+// All distinct placeholders in order of their first occurrence.
+pub fn placeholders(bytecode: &str) -> Vec<String> {
+  let mut placeholders: Vec<String> = vec![];
+  let mut rest = bytecode;
+
+  while let Some(start) = rest.find(PLACEHOLDER_DELIMITER) {
+    let candidate = rest.get(start..start + PLACEHOLDER_LENGTH);
+    match candidate {
+      Some(placeholder) if placeholder.ends_with(PLACEHOLDER_DELIMITER) => {
+        if !placeholders.iter().any(|existing| existing == placeholder) {
+          placeholders.push(placeholder.to_string());
+        }
+        rest = &rest[start + PLACEHOLDER_LENGTH..];
+      },
+      _ => rest = &rest[start + PLACEHOLDER_DELIMITER.len()..],
+    }
+  }
+  placeholders
+}
+
+// Source files are needed as the compiler qualifies library names with the
+// path of the source file they're defined in.
+pub fn is_placeholder_of(placeholder: &str, library: &str, source_files: &[String]) -> bool {
+  let mut qualified_names = vec![library.to_string()];
+  qualified_names.extend(source_files.iter().map(|source_file| format!("{}:{}", source_file, library)));
+
+  qualified_names.iter().any(|qualified_name| {
+    let hashed = format!("__${}$__", Keccak256::digest(qualified_name.as_bytes())[..17].to_hex::<String>());
+    let padded = format!("__{:_<36.36}__", qualified_name);
+    placeholder == hashed || placeholder == padded
+  })
+}
+
+pub fn link(bytecode: &str, placeholder: &str, address: &Address) -> String {
+  bytecode.replace(placeholder, &address.as_bytes().to_hex::<String>())
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{is_placeholder_of, link, placeholders};
+  use web3::types::Address;
+
+  const HASHED: &str = "__$7e1ef7d4e0e7d59c5ae3a5b6ec8b0d1c5d$__";
+
+  #[test]
+  fn it_should_find_distinct_placeholders() {
+    let bytecode = format!("6080__SafeMath______________________________73{}6001__SafeMath______________________________", HASHED);
+    assert_eq!(placeholders(&bytecode), vec!["__SafeMath______________________________".to_string(), HASHED.to_string()]);
+    assert!(placeholders("608060405234801561001057600080fd5b50").is_empty());
+  }
+
+  #[test]
+  fn it_should_match_placeholders_of_qualified_library_names() {
+    let sources = vec!["contracts/SafeMath.sol".to_string()];
+    assert!(is_placeholder_of("__SafeMath______________________________", "SafeMath", &[]));
+    assert!(is_placeholder_of("__contracts/SafeMath.sol:SafeMath_______", "SafeMath", &sources));
+    assert!(is_placeholder_of("__$438773560da8ebd76b582f48732bda290a$__", "SafeMath", &sources));
+    assert!(!is_placeholder_of("__SafeMath______________________________", "Math", &sources));
+  }
+
+  #[test]
+  fn it_should_replace_placeholders_with_addresses() {
+    let linked = link("73__SafeMath______________________________6001", "__SafeMath______________________________", &Address::from_low_u64_be(255));
+    assert_eq!(linked, "7300000000000000000000000000000000000000ff6001");
+  }
+}
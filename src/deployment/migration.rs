@@ -0,0 +1,93 @@
+// Tracking files carry the version of the schema they've been written with.
+// Older files are upgraded by running the migrations between their version
+// and the current one when they're read, and persisted with the next write,
+// after the original has been backed up. Files written before versioning was
+// introduced don't have a version and count as version 1.
+
+use super::error::DeploymentTrackingError;
+
+pub const SCHEMA_VERSION: i64 = 2;
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+const INITIAL_SCHEMA_VERSION: i64 = 1;
+
+type Migration = fn(&mut toml::Value) -> Result<(), DeploymentTrackingError>;
+
+// Migrations by the version they upgrade from, each to the next version.
+const MIGRATIONS: &[(i64, Migration)] = &[
+  (1, migrate_v1),
+];
+
+// Removes the version from the given tracking data and returns it.
+pub fn take_version(tracking_data: &mut toml::Value) -> Result<i64, DeploymentTrackingError> {
+  let version = match tracking_data.as_table_mut().and_then(|table| table.remove(SCHEMA_VERSION_KEY)) {
+    None => INITIAL_SCHEMA_VERSION,
+    Some(toml::Value::Integer(version)) => version,
+    Some(version) => return Err(DeploymentTrackingError::InvalidSchemaVersion(version.to_string())),
+  };
+  if version > SCHEMA_VERSION {
+    return Err(DeploymentTrackingError::UnsupportedSchemaVersion(version, SCHEMA_VERSION));
+  }
+  Ok(version)
+}
+
+pub fn set_version(tracking_data: &mut toml::Value) {
+  if let Some(table) = tracking_data.as_table_mut() {
+    table.insert(SCHEMA_VERSION_KEY.to_string(), toml::Value::Integer(SCHEMA_VERSION));
+  }
+}
+
+// Upgrades tracking data of the given version to the current one.
+pub fn migrate(version: i64, tracking_data: &mut toml::Value) -> Result<(), DeploymentTrackingError> {
+  for (from, migration) in MIGRATIONS {
+    if *from >= version {
+      debug!("Migrating tracking data from schema version {} to {}", from, from + 1);
+      migration(tracking_data)?;
+    }
+  }
+  Ok(())
+}
+
+// Version 1 only tracked names and addresses. Fields introduced since are
+// optional, but chains that ended up without any entry are dropped, as they
+// can't be told apart from chains that have never been deployed to.
+fn migrate_v1(tracking_data: &mut toml::Value) -> Result<(), DeploymentTrackingError> {
+  let chains = tracking_data.as_table_mut()
+    .ok_or_else(|| DeploymentTrackingError::Other("Couldn't migrate tracking data, expected a table of chains".to_string()))?;
+  chains.retain(|_block_hash, chain_tracking_data| chain_tracking_data.as_table().is_none_or(|entries| !entries.is_empty()));
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{migrate, set_version, take_version, SCHEMA_VERSION};
+  use crate::deployment::error::DeploymentTrackingError;
+
+  #[test]
+  fn it_should_treat_unversioned_tracking_data_as_version_1() {
+    let mut tracking_data: toml::Value = toml::from_str("
+      [0xabc.0x1]
+        name = \"Token\"
+        address = \"0x0000000000000000000000000000000000000001\"
+      [0xdef]
+    ").unwrap();
+
+    let version = take_version(&mut tracking_data).unwrap();
+    assert_eq!(version, 1);
+    migrate(version, &mut tracking_data).unwrap();
+    assert!(tracking_data.get("0xabc").is_some());
+    assert!(tracking_data.get("0xdef").is_none());
+
+    set_version(&mut tracking_data);
+    assert_eq!(take_version(&mut tracking_data).unwrap(), SCHEMA_VERSION);
+  }
+
+  #[test]
+  fn it_should_reject_tracking_data_of_newer_versions() {
+    let mut tracking_data: toml::Value = toml::from_str("schema_version = 99").unwrap();
+    match take_version(&mut tracking_data) {
+      Err(DeploymentTrackingError::UnsupportedSchemaVersion(99, SCHEMA_VERSION)) => (),
+      other => panic!("Expected unsupported schema version, got {:?}", other),
+    }
+  }
+}
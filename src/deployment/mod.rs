@@ -1,39 +1,138 @@
+mod args;
+pub mod address_book;
+pub mod aliases;
+pub mod children;
+pub mod diff;
+pub mod docs;
+pub mod events;
+pub mod linker;
+pub mod migration;
 pub mod error;
+pub mod gas;
+pub mod inspect;
+pub mod journal;
+pub mod planner;
+pub mod registry;
+pub mod store;
 pub mod tracker;
+pub mod upgrades;
 
 use blockchain::connector::{BlockchainConnector};
-use config::{Config, SmartContractConfig, SmartContractArg};
+use blockchain::error::{ConnectionError, SignerError};
+use blockchain::sender::{FeeBumping, TxSender};
+use blockchain::signer::{NodeSigner, Signer};
+use config::{Config, ProjectConfig, ProjectDeploymentConfig, ProjectSourcesConfig, ProxyConfig, SmartContractConfig, SmartContractArg};
+use crate::compiler::Compiler;
 use crate::blockchain;
 use crate::config;
+use crate::policy::{self, Policy, PolicyReport};
+use crate::utils;
 use error::DeploymentError;
-use ethabi::{Token, ParamType};
-use ethabi::param_type::Reader;
-use ethabi::token::{LenientTokenizer, Tokenizer};
+use crate::interface::AbiEntry;
+use ethabi::Token;
+use gas::{FunctionGasEstimate, GasEstimate, GasReport};
 use petgraph::graphmap::DiGraphMap;
 use petgraph::algo::toposort;
+use planner::{DeploymentPlan, DeploymentPlanner, RedeployCondition};
+use rustc_hex::{FromHex, ToHex};
+use std::cell::Cell;
+use std::fmt;
 use std::fs;
 use std::str::FromStr;
 use std::path::PathBuf;
-use std::collections::HashMap;
-use tracker::DeploymentTracker;
-use web3::contract::Options;
-use web3::futures::Future;
-use web3::types::{U256, H256, Address};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracker::{DeploymentReceipt, DeploymentTracker, RekeyCandidate, RekeyReport, SmartContractTrackingArg, TrackingKeyStrategy};
+use upgrades::{ProxyKind, UpgradeOptions, UpgradeReport};
+use web3::contract::deploy;
+use web3::types::{U256, H256, Address, BlockId, BlockNumber, Bytes, TransactionRequest};
 
 const ARTIFACT_EXTENSION_BINARY: &str = "bin";
+const ARTIFACT_EXTENSION_RUNTIME_BINARY: &str = "bin-runtime";
 const ARTIFACT_EXTENSION_ABI: &str = "abi";
 const DEFAULT_GAS_PRICE: usize = 5;
 const DEFAULT_GAS_LIMIT: usize = 2_000_000;
 const DEFAULT_DEV_TX_CONFIRMATION_AMOUNT: usize = 0;
+const CONSTANT_REFERENCE_PREFIX: &str = "${constants.";
+const CONSTANT_REFERENCE_SUFFIX: &str = "}";
 
+pub type DeployedContracts = HashMap<Address, (String, Address, String, bool)>;
+
+enum Reconciliation {
+  Deployed(Address),
+  // Hash and gas price of the transaction that is still waiting to be
+  // included.
+  Pending(H256, U256),
+  Dropped,
+}
+
+// What deploying the proxy of an upgradeable Smart Contract shares with the
+// deployment of its implementation.
+struct ProxyDeployment<'b> {
+  artifacts_dir: &'b str,
+  constants: &'b BTreeMap<String, String>,
+  sender: &'b TxSender<'b>,
+  deployer: Address,
+  gas_price: U256,
+  gas_limit: U256,
+  tracking_enabled: bool,
+}
+
+struct TrackedProxy {
+  address: Address,
+  implementation: Option<Address>,
+  admin: Option<Address>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct DeployOptions {
   pub tracking_enabled: Option<bool>,
+  pub require_tests: Option<bool>,
+  pub continue_on_error: Option<bool>,
+  pub tags: Option<Vec<String>>,
+  // Only deploys the given Smart Contracts, along with their dependencies.
+  pub smart_contracts: Option<Vec<String>>,
+}
+
+// Outcome of a deployment that continued after Smart Contracts failed to
+// deploy. Skipped Smart Contracts are listed along with the dependency that
+// couldn't be deployed.
+#[derive(Debug, Default)]
+pub struct DeploymentReport {
+  pub deployed: DeployedContracts,
+  pub failed: Vec<(String, String)>,
+  pub skipped: Vec<(String, String)>,
+}
+
+impl DeploymentReport {
+  pub fn is_unsuccessful(&self, name: &str) -> bool {
+    self.failed.iter().chain(self.skipped.iter()).any(|(failed, _reason)| failed == name)
+  }
+}
+
+impl fmt::Display for DeploymentReport {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut deployed: Vec<&(String, Address, String, bool)> = self.deployed.values().collect();
+    deployed.sort_by(|a, b| a.0.cmp(&b.0));
+    write!(f, "{} succeeded, {} failed, {} skipped", deployed.len(), self.failed.len(), self.skipped.len())?;
+    for (name, address, _source, _skipped) in deployed {
+      write!(f, "\n  [succeeded] {} ({:?})", name, address)?;
+    }
+    for (name, reason) in &self.failed {
+      write!(f, "\n  [failed] {}: {}", name, reason)?;
+    }
+    for (name, dependency) in &self.skipped {
+      write!(f, "\n  [skipped] {}: depends on {}", name, dependency)?;
+    }
+    Ok(())
+  }
 }
 
 pub struct Deployer<'a> {
   config: &'a Config,
   connector: &'a BlockchainConnector,
   tracker: &'a DeploymentTracker<'a>,
+  signer: Option<&'a dyn Signer>,
+  gas_used: Cell<U256>,
 }
 
 impl<'a> Deployer<'a> {
@@ -42,21 +141,71 @@ impl<'a> Deployer<'a> {
       config,
       connector,
       tracker,
+      signer: None,
+      gas_used: Cell::new(U256::zero()),
     }
   }
 
-  pub fn deploy(&self, options: DeployOptions) -> Result<HashMap<Address, (String, Address, String, bool)>, DeploymentError>  {
+  // Gas used by transactions sent by `deploy()` so far.
+  pub fn gas_used(&self) -> U256 {
+    self.gas_used.get()
+  }
+
+  pub fn with_signer(mut self, signer: &'a dyn Signer) -> Deployer<'a> {
+    self.signer = Some(signer);
+    self
+  }
 
-    let project_config = self.config.read()?;
+  pub fn deploy(&self, options: DeployOptions) -> Result<DeployedContracts, DeploymentError>  {
+
+    let (project_config, plan) = self.read_config()?;
 
     if project_config.deployment.is_none() {
       return Err(DeploymentError::MissingConfig);
     }
 
+    if let Some(report) = self.evaluate_policy(&project_config)? {
+      if !report.is_compliant() {
+        return Err(DeploymentError::PolicyViolation(report));
+      }
+    }
+
     let deployment_config = &project_config.deployment.unwrap();
-    let accounts = self.connector.accounts()?;
+    let constants = project_config.constants.unwrap_or_default();
+
+    validate_constant_references(&deployment_config.smart_contracts, &constants, self.config.network.as_deref())?;
+    self.validate_constructor_args(&project_config.sources.artifacts, &deployment_config.smart_contracts, &constants)?;
+    let library_references = self.library_references(&project_config.sources, &deployment_config.smart_contracts)?;
+    let sorted_smart_contract_configs = plan.sort(&deployment_config.smart_contracts, &library_references)?;
+    let sorted_smart_contract_configs = match &options.tags {
+      Some(tags) => select_tagged(sorted_smart_contract_configs, tags, &plan, &library_references)?,
+      None => sorted_smart_contract_configs,
+    };
+    let sorted_smart_contract_configs = match &options.smart_contracts {
+      Some(names) => select_with_dependencies(sorted_smart_contract_configs, |smart_contract_config| names.contains(&smart_contract_config.name), &plan, &library_references),
+      None => sorted_smart_contract_configs,
+    };
+
+    let network = self.config.read_network()?;
+    if let Some((name, network_config)) = &network {
+      if let Some(chain_id) = network_config.chain_id {
+        let connected_chain_id = self.connector.chain_id()?;
+        if connected_chain_id != U256::from(chain_id) {
+          return Err(DeploymentError::ChainIdMismatch(name.to_owned(), chain_id, connected_chain_id));
+        }
+      }
+    }
+
+    let node_signer;
+    let signer = match self.signer {
+      Some(signer) => signer,
+      None => {
+        node_signer = NodeSigner::new(self.connector, self.default_account()?);
+        &node_signer
+      }
+    };
 
-    let general_gas_price = deployment_config.gas_price.map(U256::from).unwrap_or_else(|| self.connector.gas_price().ok().unwrap_or_else(|| U256::from(DEFAULT_GAS_PRICE)));
+    let general_gas_price = self.general_gas_price(deployment_config);
     let general_gas_limit = deployment_config.gas_limit.map(U256::from).unwrap_or_else(|| U256::from(DEFAULT_GAS_LIMIT));
 
     let confirmations = deployment_config.tx_confirmations.unwrap_or(DEFAULT_DEV_TX_CONFIRMATION_AMOUNT);
@@ -64,76 +213,652 @@ impl<'a> Deployer<'a> {
 
     let tracking_enabled = options.tracking_enabled
       .unwrap_or(deployment_config.tracking_enabled.unwrap_or(true));
+    let continue_on_error = options.continue_on_error.unwrap_or(false);
 
     if tracking_enabled && !self.tracker.database_exists() {
       self.tracker.create_database()?;
     }
 
-    let sorted_smart_contract_configs = sort_by_dependencies(&deployment_config.smart_contracts)?;
+    let journal = journal::DeploymentJournal::new(self.config);
+    let sender = TxSender::new(self.connector, signer)
+      .with_confirmations(confirmations)
+      .with_fee_bumping(deployment_config.fee_bumping.as_ref().map(FeeBumping::from_config));
 
-    for smart_contract_config in sorted_smart_contract_configs {
+    let artifacts_dir = &project_config.sources.artifacts;
+    let mut report = DeploymentReport::default();
 
-      if let Some(address) = &smart_contract_config.address {
-        let address = Address::from_str(&address[2..]).map_err(|err| DeploymentError::InvalidAddress(smart_contract_config.name.to_owned(), err.to_string()))?;
-        info!("{} is already deployed at {:?}", &smart_contract_config.name, &address);
-        deployed_contracts.insert(address, (smart_contract_config.name.clone(), address, "unknown".to_string(), true));
+    for smart_contract_config in sorted_smart_contract_configs {
+      // Smart Contracts depending on ones that couldn't be deployed are
+      // skipped, instead of failing on their references.
+      if let Some(dependency) = plan.dependencies(smart_contract_config, &library_references).into_iter().find(|dependency| report.is_unsuccessful(dependency)) {
+        warn!("Skipping {}, it depends on {}, which couldn't be deployed", &smart_contract_config.name, dependency);
+        report.skipped.push((smart_contract_config.name.to_owned(), dependency.to_owned()));
         continue;
       }
 
+      // Implementations of upgradeable Smart Contracts are tracked under their
+      // own name, the proxy is deployed once they are.
+      let tracked_name = match &smart_contract_config.proxy {
+        Some(_proxy_config) => upgrades::implementation_name(&smart_contract_config.name),
+        None => smart_contract_config.name.to_owned(),
+      };
+
+      let deploy_smart_contract = |deployed_contracts: &mut DeployedContracts| -> Result<(), DeploymentError> {
+
+        if let Some(address) = &smart_contract_config.address {
+          let address = Address::from_str(&address[2..]).map_err(|err| DeploymentError::InvalidAddress(smart_contract_config.name.to_owned(), err.to_string()))?;
+          info!("{} is already deployed at {:?}", &smart_contract_config.name, &address);
+          deployed_contracts.insert(address, (smart_contract_config.name.clone(), address, "unknown".to_string(), true));
+          return Ok(());
+        }
+
+        if let Some((bin_path, abi_path)) = self.get_artifacts(artifacts_dir, smart_contract_config)? {
+
+          let bytecode = fs::read_to_string(&bin_path).unwrap();
+          let bytecode = link_libraries(&bytecode, library_references.get(&smart_contract_config.name), deployed_contracts)?;
+          let abi = fs::read(abi_path).unwrap();
+
+          let resolved_args = resolve_args(smart_contract_config, &constants);
+          let args = resolved_args.iter().map(|arg| arg.value.clone()).collect();
+          let param_types = args::check_constructor_args(&smart_contract_config.name, &abi, &resolved_args)?;
+          let tokenized_args = args::tokenize_args(&resolved_args, &param_types, deployed_contracts)?;
+
+          let redeploy = plan.redeploy_condition(&smart_contract_config.name);
+          if tracking_enabled && redeploy != RedeployCondition::Always {
+            let block_hash = self.get_first_block_hash().unwrap();
+            let tracked_contract = match redeploy {
+              RedeployCondition::Never => self.tracker.get_latest_smart_contract_tracking_data(&block_hash, &tracked_name)?,
+              _ => self.tracker.get_smart_contract_tracking_data(&block_hash, &tracked_name, &bytecode, &args)?,
+            };
+
+            if let Some(tracked_contract) = tracked_contract {
+              info!("{} is already deployed at {:?}", &tracked_contract.name, &tracked_contract.address);
+              deployed_contracts.insert(tracked_contract.address, (tracked_contract.name, tracked_contract.address, bin_path.to_string_lossy().to_string(), true));
+              return Ok(());
+            }
+          }
+
+          let mut journal_entry = None;
+          let mut pending_tx = None;
+
+          if tracking_enabled {
+            let block_hash = self.get_first_block_hash()?;
+            let previous_entry = journal.read(&tracked_name)?
+              .filter(|entry| entry.is_for(&block_hash, &tracked_name, &bytecode, &args));
+
+            let nonce = match previous_entry.map(|entry| self.reconcile(&entry).map(|outcome| (entry, outcome))).transpose()? {
+              Some((_entry, Reconciliation::Deployed(address))) => {
+                info!("{} has been deployed at {:?} by a previous deployment that didn't finish", &tracked_name, &address);
+                self.tracker.track(block_hash, tracked_name.to_owned(), bytecode, &args, address, Some((tracked_args(&resolved_args, &tokenized_args), tokenized_args)))?;
+                journal.remove(&tracked_name)?;
+                deployed_contracts.insert(address, (tracked_name.to_owned(), address, bin_path.to_string_lossy().to_string(), true));
+                return Ok(());
+              },
+              // Nodes reject transactions that reuse the nonce of a pending one
+              // without outbidding it, so the previous transaction is waited
+              // for instead of deploying the Smart Contract twice.
+              Some((entry, Reconciliation::Pending(tx_hash, gas_price))) => {
+                info!("{} is still being deployed by a previous deployment that didn't finish, waiting for {:?}", &tracked_name, &tx_hash);
+                pending_tx = Some((tx_hash, gas_price));
+                entry.nonce
+              },
+              Some((_, Reconciliation::Dropped)) | None => self.connector.transaction_count(signer.address(), Some(BlockNumber::Pending))?,
+            };
+
+            let mut entry = journal::JournalEntry::new(&block_hash, &tracked_name, &bytecode, &args, signer.address(), nonce);
+            entry.tx_hash = pending_tx.map(|(tx_hash, _gas_price)| tx_hash);
+            journal.write(&entry)?;
+            journal_entry = Some(entry);
+          }
+
+          info!("Deploying {}...", &tracked_name);
+
+          let data = encode_deployment_data(&abi, &bytecode, &tokenized_args)
+            .map_err(|err| DeploymentError::InvalidConstructorArgs(err, smart_contract_config.name.to_owned()))?;
+
+          let tx = TransactionRequest {
+            from: signer.address(),
+            to: None,
+            gas: smart_contract_config.gas_limit.map(U256::from).or_else(|| Some(general_gas_limit)),
+            gas_price: pending_tx.map(|(_tx_hash, gas_price)| gas_price).or_else(|| smart_contract_config.gas_price.map(U256::from)).or_else(|| Some(general_gas_price)),
+            value: None,
+            nonce: journal_entry.as_ref().map(|entry| entry.nonce),
+            data: Some(Bytes(data)),
+            condition: None,
+          };
+
+          // Replacements share the nonce of the journaled transaction, so the
+          // journal only needs to know about the latest one of them.
+          let on_submitted = |tx_hash| {
+            if let Some(entry) = &mut journal_entry {
+              entry.tx_hash = Some(tx_hash);
+              if let Err(err) = journal.write(entry) {
+                warn!("Couldn't journal transaction {:?} of {}: {}", tx_hash, &smart_contract_config.name, err);
+              }
+            }
+          };
+          let receipt = match pending_tx {
+            Some((tx_hash, _gas_price)) => sender.resume(tx, tx_hash, on_submitted),
+            None => sender.send(tx, on_submitted),
+          }.map_err(|err| to_deployment_error(err, &smart_contract_config.name))?;
+          let tx_hash = receipt.transaction_hash;
+          self.gas_used.set(self.gas_used.get() + receipt.gas_used.unwrap_or_default());
+          let contract_address = receipt.contract_address
+            .ok_or_else(|| DeploymentError::DeployContract(deploy::Error::ContractDeploymentFailure(tx_hash), smart_contract_config.name.to_owned()))?;
+
+          if tracking_enabled {
+            self.tracker.track_deployment(
+              self.get_first_block_hash().unwrap(), 
+              tracked_name.to_owned(),
+              bytecode,
+              &args,
+              contract_address,
+              Some((tracked_args(&resolved_args, &tokenized_args), tokenized_args)),
+              Some(DeploymentReceipt {
+                tx_hash,
+                block_number: receipt.block_number.map(|block_number| block_number.low_u64()),
+                deployer: signer.address(),
+                gas_used: receipt.gas_used.map(|gas_used| gas_used.low_u64()),
+              }),
+            )?;
+            journal.remove(&tracked_name)?;
+          }
+
+          info!("Deployed {} at {:?}", &tracked_name, &contract_address);
+          deployed_contracts.insert(contract_address, (tracked_name.to_owned(), contract_address, bin_path.to_string_lossy().to_string(), false));
+        } else {
+          warn!("No bytecode or ABI found for Smart Contract '{}'", &smart_contract_config.name);
+        }
+        Ok(())
+      };
+
+      let mut deploy = || -> Result<(), DeploymentError> {
+        deploy_smart_contract(&mut deployed_contracts)?;
+        if let Some(proxy_config) = &smart_contract_config.proxy {
+          self.deploy_proxy(smart_contract_config, proxy_config, &mut deployed_contracts, ProxyDeployment {
+            artifacts_dir,
+            constants: &constants,
+            sender: &sender,
+            gas_price: smart_contract_config.gas_price.map(U256::from).unwrap_or(general_gas_price),
+            gas_limit: smart_contract_config.gas_limit.map(U256::from).unwrap_or(general_gas_limit),
+            deployer: signer.address(),
+            tracking_enabled,
+          })?;
+        }
+        Ok(())
+      };
+
+      match deploy() {
+        Err(err) if continue_on_error => {
+          warn!("Couldn't deploy {}: {}", &smart_contract_config.name, err);
+          report.failed.push((smart_contract_config.name.to_owned(), err.to_string()));
+        },
+        result => result?,
+      }
+    }
+
+    if !report.failed.is_empty() {
+      report.deployed = deployed_contracts;
+      return Err(DeploymentError::PartialDeployment(report));
+    }
+    Ok(deployed_contracts)
+  }
+
+  // Rekeys existing tracking data using the configured tracking key strategy.
+  // Entries are compared against the bytecode and arguments the Smart
+  // Contracts would be deployed with right now, linked against the libraries
+  // and referencing the deployments tracked on the entry's chain. On the
+  // connected chain, the code deployed at an entry's address is compared
+  // against the runtime bytecode as well.
+  pub fn rekey_tracking_data(&self) -> Result<(TrackingKeyStrategy, RekeyReport), DeploymentError> {
+    let (project_config, _plan) = self.read_config()?;
+    let deployment_config = project_config.deployment.as_ref().ok_or(DeploymentError::MissingConfig)?;
+    let constants = project_config.constants.clone().unwrap_or_default();
+    let strategy = self.tracker.key_strategy()?;
+    let library_references = self.library_references(&project_config.sources, &deployment_config.smart_contracts)?;
+    let connected_chain = self.connector.get_first_block().ok().flatten()
+      .and_then(|block| block.hash)
+      .map(|block_hash| tracker::create_block_hash(&block_hash));
+    let mut smart_contracts = HashMap::new();
+
+    for smart_contract_config in deployment_config.smart_contracts.iter().filter(|config| config.address.is_none()) {
       if let Some((bin_path, abi_path)) = self.get_artifacts(&project_config.sources.artifacts, smart_contract_config)? {
+        let resolved_args = resolve_args(smart_contract_config, &constants);
+        let param_types = args::check_constructor_args(&smart_contract_config.name, &fs::read(&abi_path)?, &resolved_args).ok();
+        let runtime_bytecode = fs::read_to_string(bin_path.with_extension(ARTIFACT_EXTENSION_RUNTIME_BINARY)).ok();
+        let name = match &smart_contract_config.proxy {
+          Some(_proxy_config) => upgrades::implementation_name(&smart_contract_config.name),
+          None => smart_contract_config.name.to_owned(),
+        };
+        smart_contracts.insert(name, (smart_contract_config, fs::read_to_string(&bin_path)?, runtime_bytecode, resolved_args, param_types));
+      }
+    }
+
+    let report = self.tracker.rekey(strategy, |block_hash, chain_tracking_data, entry| {
+      let (smart_contract_config, bytecode, runtime_bytecode, resolved_args, param_types) = smart_contracts.get(&entry.name)?;
+      let deployed_contracts = tracked_contracts(chain_tracking_data);
+      let libraries = library_references.get(&smart_contract_config.name);
+      let encoded_args = param_types.as_ref()
+        .and_then(|param_types| args::tokenize_args(resolved_args, param_types, &deployed_contracts).ok())
+        .map(|tokens| format!("0x{}", ethabi::encode(&tokens).to_hex::<String>()));
+      let code_matches = connected_chain.as_deref() == Some(block_hash) && runtime_bytecode.as_ref()
+        .and_then(|runtime_bytecode| link_libraries(runtime_bytecode, libraries, &deployed_contracts).ok())
+        .is_some_and(|runtime_bytecode| self.connector.code(entry.address, None).is_ok_and(|code| code.0 == utils::decode_bytecode(&runtime_bytecode)));
+
+      Some(RekeyCandidate {
+        byte_code: link_libraries(bytecode, libraries, &deployed_contracts).ok()?,
+        args: resolved_args.iter().map(|arg| arg.value.clone()).collect(),
+        encoded_args,
+        code_matches,
+      })
+    })?;
+    Ok((strategy, report))
+  }
+
+  // Evaluates the deployment policy referenced in the project configuration,
+  // if there is one, against what a deployment would do right now.
+  pub fn check_policy(&self) -> Result<Option<PolicyReport>, DeploymentError> {
+    let (project_config, _plan) = self.read_config()?;
+    if project_config.deployment.is_none() {
+      return Err(DeploymentError::MissingConfig);
+    }
+    self.evaluate_policy(&project_config)
+  }
 
-        let bytecode = fs::read_to_string(&bin_path).unwrap();
-        let abi = fs::read(abi_path).unwrap();
+  // Estimates use the bytecode and constructor arguments a deployment would
+  // use right now, with placeholders standing in for the addresses of other
+  // Smart Contracts. Functions are estimated against the most recent tracked
+  // deployment of each Smart Contract.
+  pub fn estimate_gas(&self) -> Result<GasReport, DeploymentError> {
+    let (project_config, plan) = self.read_config()?;
+    let deployment_config = project_config.deployment.as_ref().ok_or(DeploymentError::MissingConfig)?;
+    let constants = project_config.constants.clone().unwrap_or_default();
+    let library_references = self.library_references(&project_config.sources, &deployment_config.smart_contracts)?;
+    let placeholders = placeholder_contracts(&deployment_config.smart_contracts);
+    let general_gas_limit = deployment_config.gas_limit.map(U256::from).unwrap_or_else(|| U256::from(DEFAULT_GAS_LIMIT));
+    let sender = match self.signer {
+      Some(signer) => signer.address(),
+      None => self.default_account()?,
+    };
+    let block_hash = if self.tracker.database_exists() {
+      Some(self.get_first_block_hash()?)
+    } else {
+      None
+    };
+
+    let mut report = GasReport {
+      block_gas_limit: self.connector.get_block(BlockId::Number(BlockNumber::Latest))?.map(|block| block.gas_limit),
+      estimates: vec![],
+    };
+
+    for smart_contract_config in plan.sort(&deployment_config.smart_contracts, &library_references)? {
+      if smart_contract_config.address.is_some() {
+        continue;
+      }
+      if let Some((bin_path, abi_path)) = self.get_artifacts(&project_config.sources.artifacts, smart_contract_config)? {
+        let name = &smart_contract_config.name;
+        let bytecode = link_libraries(&fs::read_to_string(&bin_path)?, library_references.get(name), &placeholders)?;
+        let abi = fs::read(&abi_path)?;
+        let resolved_args = resolve_args(smart_contract_config, &constants);
+        let param_types = args::check_constructor_args(name, &abi, &resolved_args)?;
+        let tokenized_args = args::tokenize_args(&resolved_args, &param_types, &placeholders)?;
+        let data = encode_deployment_data(&abi, &bytecode, &tokenized_args)
+          .map_err(|err| DeploymentError::InvalidConstructorArgs(err, name.to_owned()))?;
 
-        let args = smart_contract_config.args.as_ref().unwrap_or(&vec![]).iter().map(|arg| arg.value.clone()).collect();
+        // Transactions that would fail can't be estimated.
+        let deployment = self.connector.estimate_gas(estimation_request(sender, None, data)).ok();
 
-        let tokenized_args = match &smart_contract_config.args {
-          Some(args) => tokenize_args(args, &deployed_contracts)?,
-          None => vec![]
+        let address = match &block_hash {
+          Some(block_hash) => self.tracker.get_latest_smart_contract_tracking_data(block_hash, name)?.map(|entry| entry.address),
+          None => None,
+        };
+        let functions = match address {
+          Some(address) => {
+            let abi: Vec<AbiEntry> = serde_json::from_slice(&abi)
+              .map_err(|err| DeploymentError::Other(format!("Couldn't read ABI of {}: {}", name, err)))?;
+            gas::function_calls(&abi, sender).into_iter().map(|(signature, data)| FunctionGasEstimate {
+              signature,
+              gas: self.connector.estimate_gas(estimation_request(sender, Some(address), data)).ok(),
+            }).collect()
+          },
+          None => vec![],
         };
 
-        if tracking_enabled {
-          let block_hash = self.get_first_block_hash().unwrap();
-          let tracked_contract = self.tracker.get_smart_contract_tracking_data(&block_hash, &smart_contract_config.name, &bytecode, &args)?;
+        report.estimates.push(GasEstimate {
+          name: name.to_owned(),
+          deployment,
+          gas_limit: smart_contract_config.gas_limit.map(U256::from).unwrap_or(general_gas_limit),
+          address,
+          functions,
+        });
+      }
+    }
+    Ok(report)
+  }
 
-          if let Some(tracked_contract) = tracked_contract {
-            info!("{} is already deployed at {:?}", &tracked_contract.name, &tracked_contract.address);
-            deployed_contracts.insert(tracked_contract.address, (tracked_contract.name, tracked_contract.address, bin_path.to_string_lossy().to_string(), true));
-            continue;
+  // Deploys the current implementation of an upgradeable Smart Contract, along
+  // with the Smart Contracts it depends on, and points its proxy at it.
+  pub fn upgrade(&self, name: &str, options: UpgradeOptions) -> Result<UpgradeReport, DeploymentError> {
+    let (project_config, _plan) = self.read_config()?;
+    let deployment_config = project_config.deployment.as_ref().ok_or(DeploymentError::MissingConfig)?;
+    let smart_contract_config = deployment_config.smart_contracts.iter()
+      .find(|smart_contract_config| smart_contract_config.name == name)
+      .ok_or_else(|| DeploymentError::MissingConfigForReference(name.to_owned()))?;
+    let proxy_config = smart_contract_config.proxy.as_ref().ok_or_else(|| DeploymentError::NotUpgradeable(name.to_owned()))?;
+    let kind: ProxyKind = proxy_config.kind.as_deref().map(str::parse).transpose()?.unwrap_or_default();
+    let proxy = self.tracked_proxy(name)?.ok_or_else(|| DeploymentError::ProxyNotDeployed(name.to_owned()))?;
+
+    let artifact_name = smart_contract_config.instance_of.as_ref().unwrap_or(&smart_contract_config.name);
+    let storage_layout = upgrades::compiled_storage_layout(&self.config.project_path.join(&project_config.sources.artifacts), artifact_name)?;
+    if !options.skip_storage_check {
+      match (upgrades::recorded_storage_layout(&self.config.project_path, name)?, &storage_layout) {
+        (Some(current), Some(new)) => {
+          let incompatibilities = upgrades::check_storage_layout(&current, new);
+          if !incompatibilities.is_empty() {
+            return Err(DeploymentError::IncompatibleStorageLayout(name.to_owned(), incompatibilities));
           }
-        }
+        },
+        _ => return Err(DeploymentError::MissingStorageLayout(name.to_owned())),
+      }
+    }
 
-        info!("Deploying {}...", &smart_contract_config.name);
+    let deployed_contracts = self.deploy(DeployOptions {
+      tracking_enabled: Some(true),
+      require_tests: None,
+      continue_on_error: None,
+      tags: None,
+      smart_contracts: Some(vec![name.to_owned()]),
+    })?;
+    let implementation = find_deployed(&deployed_contracts, &upgrades::implementation_name(name))
+      .ok_or_else(|| DeploymentError::MissingArtifact(ARTIFACT_EXTENSION_BINARY.to_string(), name.to_owned()))?;
 
-        let builder = self.connector.deploy(&abi)?;
+    let mut report = UpgradeReport {
+      name: name.to_owned(),
+      proxy: proxy.address,
+      previous_implementation: proxy.implementation,
+      implementation,
+      tx_hash: None,
+    };
+    if proxy.implementation == Some(implementation) {
+      return Ok(report);
+    }
 
-        let pending_contract = builder.confirmations(confirmations)
-                              .options(Options::with(|opts| {
-                                opts.gas_price = smart_contract_config.gas_price.map(U256::from).or_else(|| Some(general_gas_price));
-                                opts.gas = smart_contract_config.gas_limit.map(U256::from).or_else(|| Some(general_gas_limit));
-                              }))
-                              .execute(&bytecode, &*tokenized_args, accounts[0])
-                              .map_err(|err| DeploymentError::InvalidConstructorArgs(err, smart_contract_config.name.to_owned()))?;
+    let node_signer;
+    let signer = match self.signer {
+      Some(signer) => signer,
+      None => {
+        node_signer = NodeSigner::new(self.connector, self.default_account()?);
+        &node_signer
+      }
+    };
+    let sender = TxSender::new(self.connector, signer)
+      .with_confirmations(deployment_config.tx_confirmations.unwrap_or(DEFAULT_DEV_TX_CONFIRMATION_AMOUNT))
+      .with_fee_bumping(deployment_config.fee_bumping.as_ref().map(FeeBumping::from_config));
+
+    let (to, data) = upgrades::upgrade_call(kind, proxy.address, proxy.admin, signer.address(), implementation);
+    info!("Upgrading {} at {:?} to {:?}...", name, proxy.address, implementation);
+    let receipt = sender.send(TransactionRequest {
+      from: signer.address(),
+      to: Some(to),
+      gas: Some(smart_contract_config.gas_limit.or(deployment_config.gas_limit).map(U256::from).unwrap_or_else(|| U256::from(DEFAULT_GAS_LIMIT))),
+      gas_price: Some(smart_contract_config.gas_price.map(U256::from).unwrap_or_else(|| self.general_gas_price(deployment_config))),
+      value: None,
+      nonce: None,
+      data: Some(Bytes(data)),
+      condition: None,
+    }, |_tx_hash| {}).map_err(|err| to_deployment_error(err, name))?;
+    self.gas_used.set(self.gas_used.get() + receipt.gas_used.unwrap_or_default());
+
+    if receipt.status.is_some_and(|status| status.is_zero()) || upgrades::upgraded_implementation(&receipt.logs) != Some(implementation) {
+      return Err(DeploymentError::UpgradeFailed(name.to_owned(), receipt.transaction_hash));
+    }
 
-        let contract = pending_contract.wait().map_err(|err| DeploymentError::DeployContract(err, smart_contract_config.name.to_owned()))?;
+    self.link_proxy(name, kind, proxy.address, implementation, proxy.admin)?;
+    if let Some(storage_layout) = storage_layout {
+      upgrades::record_storage_layout(&self.config.project_path, name, &storage_layout)?;
+    }
+    info!("Upgraded {} at {:?} to {:?}", name, proxy.address, implementation);
+    report.tx_hash = Some(receipt.transaction_hash);
+    Ok(report)
+  }
 
-        if tracking_enabled {
-          self.tracker.track(
-            self.get_first_block_hash().unwrap(), 
-            smart_contract_config.name.to_owned(),
-            bytecode,
-            &args,
-            contract.address(),
-          )?;
+  // Deploys the proxy of an upgradeable Smart Contract once its implementation
+  // has been deployed. A tracked proxy is reused, even if it points at an older
+  // implementation, as only `upgrade()` changes the implementation. Unlike
+  // implementations, proxies aren't journaled.
+  fn deploy_proxy(&self, smart_contract_config: &SmartContractConfig, proxy_config: &ProxyConfig, deployed_contracts: &mut DeployedContracts, deployment: ProxyDeployment) -> Result<(), DeploymentError> {
+    let name = &smart_contract_config.name;
+    let implementation = match find_deployed(deployed_contracts, &upgrades::implementation_name(name)) {
+      Some(implementation) => implementation,
+      None => return Ok(()),
+    };
+    let kind: ProxyKind = proxy_config.kind.as_deref().map(str::parse).transpose()?.unwrap_or_default();
+
+    let proxy_artifact_config = SmartContractConfig { name: proxy_config.contract.to_owned(), ..SmartContractConfig::default() };
+    let (proxy_bin_path, proxy_abi_path) = self.get_artifacts(deployment.artifacts_dir, &proxy_artifact_config)?
+      .ok_or_else(|| DeploymentError::MissingArtifact(ARTIFACT_EXTENSION_BINARY.to_string(), proxy_config.contract.to_owned()))?;
+
+    if deployment.tracking_enabled {
+      if let Some(proxy) = self.tracked_proxy(name)? {
+        if proxy.implementation == Some(implementation) {
+          info!("{} is already deployed at {:?}", name, &proxy.address);
+        } else {
+          info!("{} is already deployed at {:?}, `vibranium upgrade {}` points it at implementation {:?}", name, &proxy.address, name, &implementation);
         }
+        deployed_contracts.insert(proxy.address, (name.to_owned(), proxy.address, proxy_bin_path.to_string_lossy().to_string(), true));
+        return Ok(());
+      }
+    }
+
+    let (_bin_path, abi_path) = self.get_artifacts(deployment.artifacts_dir, smart_contract_config)?
+      .ok_or_else(|| DeploymentError::MissingArtifact(ARTIFACT_EXTENSION_ABI.to_string(), name.to_owned()))?;
+    let initializer_args: Vec<SmartContractArg> = proxy_config.initializer_args.iter().flatten().map(|arg| SmartContractArg {
+      value: resolve_constant_references(&arg.value, deployment.constants),
+      kind: arg.kind.clone(),
+    }).collect();
+    let init_data = upgrades::initializer_data(name, &fs::read(abi_path)?, proxy_config.initializer.as_deref(), &initializer_args, deployed_contracts)?;
+
+    let bytecode = fs::read_to_string(&proxy_bin_path)?;
+    let tokens = upgrades::proxy_constructor_args(kind, implementation, deployment.deployer, init_data);
+    let data = encode_deployment_data(&fs::read(&proxy_abi_path)?, &bytecode, &tokens)
+      .map_err(|err| DeploymentError::InvalidConstructorArgs(err, proxy_config.contract.to_owned()))?;
 
-        info!("Deployed {} at {:?}", &smart_contract_config.name, &contract.address());
-        deployed_contracts.insert(contract.address(), (smart_contract_config.name.to_owned(), contract.address(), bin_path.to_string_lossy().to_string(), false));
-      } else {
-        warn!("No bytecode or ABI found for Smart Contract '{}'", &smart_contract_config.name);
+    info!("Deploying {} proxy of {}...", kind, name);
+    let receipt = deployment.sender.send(TransactionRequest {
+      from: deployment.deployer,
+      to: None,
+      gas: Some(deployment.gas_limit),
+      gas_price: Some(deployment.gas_price),
+      value: None,
+      nonce: None,
+      data: Some(Bytes(data)),
+      condition: None,
+    }, |_tx_hash| {}).map_err(|err| to_deployment_error(err, name))?;
+    self.gas_used.set(self.gas_used.get() + receipt.gas_used.unwrap_or_default());
+    let proxy = receipt.contract_address
+      .ok_or_else(|| DeploymentError::DeployContract(deploy::Error::ContractDeploymentFailure(receipt.transaction_hash), name.to_owned()))?;
+
+    if deployment.tracking_enabled {
+      let admin = match kind {
+        ProxyKind::Transparent => upgrades::admin_from_logs(&receipt.logs).or(Some(deployment.deployer)),
+        ProxyKind::Erc1967 => None,
+      };
+      self.tracker.track_deployment(self.get_first_block_hash()?, name.to_owned(), bytecode, &vec![], proxy, None, Some(DeploymentReceipt {
+        tx_hash: receipt.transaction_hash,
+        block_number: receipt.block_number.map(|block_number| block_number.low_u64()),
+        deployer: deployment.deployer,
+        gas_used: receipt.gas_used.map(|gas_used| gas_used.low_u64()),
+      }))?;
+      self.link_proxy(name, kind, proxy, implementation, admin)?;
+
+      let artifact_name = smart_contract_config.instance_of.as_ref().unwrap_or(name);
+      if let Some(storage_layout) = upgrades::compiled_storage_layout(&self.config.project_path.join(deployment.artifacts_dir), artifact_name)? {
+        upgrades::record_storage_layout(&self.config.project_path, name, &storage_layout)?;
       }
     }
-    Ok(deployed_contracts)
+
+    info!("Deployed {} at {:?}, pointing at implementation {:?}", name, &proxy, &implementation);
+    deployed_contracts.insert(proxy, (name.to_owned(), proxy, proxy_bin_path.to_string_lossy().to_string(), false));
+    Ok(())
+  }
+
+  // Records which implementation the proxy of the given Smart Contract points
+  // at, and which proxy its implementations are used by.
+  fn link_proxy(&self, name: &str, kind: ProxyKind, proxy: Address, implementation: Address, admin: Option<Address>) -> Result<(), DeploymentError> {
+    let block_hash = self.get_first_block_hash()?;
+    let mut proxy_metadata = BTreeMap::new();
+    proxy_metadata.insert(upgrades::METADATA_PROXY_KIND.to_string(), kind.to_string());
+    proxy_metadata.insert(upgrades::METADATA_IMPLEMENTATION.to_string(), format!("{:?}", implementation));
+    if let Some(admin) = admin {
+      proxy_metadata.insert(upgrades::METADATA_ADMIN.to_string(), format!("{:?}", admin));
+    }
+    self.tracker.tag(&block_hash, name, proxy_metadata)?;
+
+    let mut implementation_metadata = BTreeMap::new();
+    implementation_metadata.insert(upgrades::METADATA_PROXY.to_string(), format!("{:?}", proxy));
+    self.tracker.tag(&block_hash, &upgrades::implementation_name(name), implementation_metadata)?;
+    Ok(())
+  }
+
+  fn tracked_proxy(&self, name: &str) -> Result<Option<TrackedProxy>, DeploymentError> {
+    if !self.tracker.database_exists() {
+      return Ok(None);
+    }
+    let tracked_proxy = self.tracker.get_all_smart_contract_tracking_data(&self.get_first_block_hash()?)?
+      .and_then(|tracking_data| tracking_data.into_values()
+        .filter(|entry| entry.name == name && entry.metadata.as_ref().is_some_and(|metadata| metadata.contains_key(upgrades::METADATA_PROXY_KIND)))
+        .max_by_key(|entry| entry.deployed_at.unwrap_or(0)));
+
+    Ok(tracked_proxy.map(|entry| {
+      let metadata = entry.metadata.unwrap_or_default();
+      let address_of = |key: &str| metadata.get(key).and_then(|address| Address::from_str(address.trim_start_matches("0x")).ok());
+      TrackedProxy {
+        address: entry.address,
+        implementation: address_of(upgrades::METADATA_IMPLEMENTATION),
+        admin: address_of(upgrades::METADATA_ADMIN),
+      }
+    }))
+  }
+
+  // Accounts configured for the selected network take precedence over the
+  // ones the node manages.
+  fn default_account(&self) -> Result<Address, DeploymentError> {
+    if let Some((name, network_config)) = self.config.read_network()? {
+      if let Some(account) = network_config.accounts.as_ref().and_then(|accounts| accounts.first()) {
+        return Address::from_str(account.trim_start_matches("0x"))
+          .map_err(|err| DeploymentError::Other(format!("Invalid account {} configured for network {}: {}", account, name, err)));
+      }
+    }
+    Ok(*self.connector.accounts()?.first().ok_or(DeploymentError::MissingAccount)?)
+  }
+
+  // The project configuration with the deployment plan applied, if there is one.
+  fn read_config(&self) -> Result<(ProjectConfig, DeploymentPlan), DeploymentError> {
+    let mut project_config = self.config.read()?;
+    let plan = DeploymentPlanner::new(self.config).apply(&mut project_config)?;
+    Ok((project_config, plan))
+  }
+
+  fn evaluate_policy(&self, project_config: &ProjectConfig) -> Result<Option<PolicyReport>, DeploymentError> {
+    let deployment_config = project_config.deployment.as_ref().ok_or(DeploymentError::MissingConfig)?;
+    let policy = match &deployment_config.policy {
+      Some(path) => Policy::read(&self.config.project_path.join(path)).map_err(DeploymentError::Policy)?,
+      None => return Ok(None),
+    };
+
+    let general_gas_price = self.general_gas_price(deployment_config);
+    let mut smart_contracts = vec![];
+
+    // Verifications are recorded with the tracking data of each chain, so
+    // the chain is only looked up if the policy asks for them.
+    let block_hash = match policy.require_verified {
+      Some(true) if self.tracker.database_exists() => Some(self.get_first_block_hash()?),
+      _ => None,
+    };
+
+    for smart_contract_config in deployment_config.smart_contracts.iter().filter(|config| config.address.is_none()) {
+      if let Some((bin_path, _abi_path)) = self.get_artifacts(&project_config.sources.artifacts, smart_contract_config)? {
+        let tracked_name = match &smart_contract_config.proxy {
+          Some(_proxy_config) => upgrades::implementation_name(&smart_contract_config.name),
+          None => smart_contract_config.name.to_owned(),
+        };
+        let tracked = match &block_hash {
+          Some(block_hash) => self.tracker.get_latest_smart_contract_tracking_data(block_hash, &tracked_name)?,
+          None => None,
+        };
+        smart_contracts.push(policy::PlannedSmartContract {
+          name: smart_contract_config.name.to_owned(),
+          gas_price: smart_contract_config.gas_price.map(U256::from).unwrap_or(general_gas_price),
+          bytecode: fs::read_to_string(&bin_path)?,
+          tracked: tracked.map(|entry| policy::TrackedSmartContract { address: entry.address, verification: entry.verification }),
+        });
+      }
+    }
+
+    // Named networks are matched by their name, the connector host is only
+    // used when deploying without one.
+    let network = match &self.config.network {
+      Some(network) => network.to_owned(),
+      None => project_config.blockchain.as_ref()
+        .and_then(|config| config.connector.as_ref())
+        .map(|connector_config| connector_config.host.to_owned())
+        .unwrap_or_else(|| blockchain::connector::BlockchainConnectorConfig::default().host),
+    };
+
+    Ok(Some(policy.evaluate(&policy::DeploymentPlan {
+      network,
+      tx_confirmations: deployment_config.tx_confirmations.unwrap_or(DEFAULT_DEV_TX_CONFIRMATION_AMOUNT),
+      smart_contracts,
+    })))
+  }
+
+  fn general_gas_price(&self, deployment_config: &ProjectDeploymentConfig) -> U256 {
+    deployment_config.gas_price.map(U256::from).unwrap_or_else(|| self.connector.gas_price().ok().unwrap_or_else(|| U256::from(DEFAULT_GAS_PRICE)))
+  }
+
+  // Like constants, constructor arguments of all Smart Contracts are checked
+  // upfront. Addresses of Smart Contracts that are yet to be deployed are
+  // stood in for by placeholders.
+  fn validate_constructor_args(&self, artifacts_path: &str, smart_contracts: &[SmartContractConfig], constants: &BTreeMap<String, String>) -> Result<(), DeploymentError> {
+    let placeholders = placeholder_contracts(smart_contracts);
+
+    for smart_contract_config in smart_contracts.iter().filter(|smart_contract_config| smart_contract_config.address.is_none()) {
+      if let Some((_bin_path, abi_path)) = self.get_artifacts(artifacts_path, smart_contract_config)? {
+        let resolved_args = resolve_args(smart_contract_config, constants);
+        let param_types = args::check_constructor_args(&smart_contract_config.name, &fs::read(abi_path)?, &resolved_args)?;
+        args::tokenize_args(&resolved_args, &param_types, &placeholders)?;
+      }
+    }
+    Ok(())
+  }
+
+  // Libraries that need to be linked into each Smart Contract, along with
+  // their placeholders. Libraries are deployed like any other Smart Contract
+  // and therefore need to be part of the deployment configuration.
+  fn library_references(&self, sources: &ProjectSourcesConfig, smart_contracts: &[SmartContractConfig]) -> Result<HashMap<String, Vec<(String, String)>>, DeploymentError> {
+    let source_files: Vec<String> = Compiler::new(self.config).source_files(&sources.smart_contracts).iter()
+      .flat_map(|path| {
+        let relative_path = path.strip_prefix(&self.config.project_path).unwrap_or(path).to_string_lossy().to_string();
+        vec![path.to_string_lossy().to_string(), relative_path]
+      })
+      .collect();
+
+    let mut references = HashMap::new();
+    for smart_contract_config in smart_contracts.iter().filter(|smart_contract_config| smart_contract_config.address.is_none()) {
+      if let Some((bin_path, _abi_path)) = self.get_artifacts(&sources.artifacts, smart_contract_config)? {
+        let libraries = linker::placeholders(&fs::read_to_string(bin_path)?).into_iter().map(|placeholder| {
+          smart_contracts.iter()
+            .find(|library| linker::is_placeholder_of(&placeholder, library.instance_of.as_ref().unwrap_or(&library.name), &source_files))
+            .map(|library| (placeholder.to_owned(), library.name.to_owned()))
+            .ok_or_else(|| DeploymentError::UnknownLibrary(smart_contract_config.name.to_owned(), placeholder.to_owned()))
+        }).collect::<Result<Vec<(String, String)>, DeploymentError>>()?;
+
+        if !libraries.is_empty() {
+          references.insert(smart_contract_config.name.to_owned(), libraries);
+        }
+      }
+    }
+    Ok(references)
   }
 
   fn get_artifacts(&self, artifacts_path: &str, config: &SmartContractConfig) -> Result<Option<(PathBuf, PathBuf)>, DeploymentError> {
@@ -152,7 +877,10 @@ impl<'a> Deployer<'a> {
       let artifact_names: Vec<PathBuf> = artifacts_dir.map(|res| res.unwrap().path()).collect();
       let smart_contract_name = config.instance_of.as_ref().unwrap_or(&config.name);
 
-      if let Some(artifact) = artifact_names.iter().find(|path| path.to_string_lossy().to_string().contains(smart_contract_name)) {
+      // Other artifacts, e.g. storage layouts, are named after the Smart
+      // Contract as well.
+      let is_deployable = |path: &&PathBuf| path.extension().is_some_and(|extension| extension == ARTIFACT_EXTENSION_BINARY || extension == ARTIFACT_EXTENSION_ABI);
+      if let Some(artifact) = artifact_names.iter().filter(is_deployable).find(|path| path.to_string_lossy().to_string().contains(smart_contract_name)) {
         let file_extension = &artifact.extension().unwrap().to_str().unwrap();
 
         if file_extension == &ARTIFACT_EXTENSION_BINARY || file_extension == &ARTIFACT_EXTENSION_ABI {
@@ -172,43 +900,203 @@ impl<'a> Deployer<'a> {
     }
   }
 
+  // Figures out what happened to the transaction of a journal entry left
+  // behind by a deployment that didn't finish.
+  fn reconcile(&self, entry: &journal::JournalEntry) -> Result<Reconciliation, DeploymentError> {
+    if let Some(tx_hash) = entry.tx_hash {
+      if let Some(receipt) = self.connector.transaction_receipt(tx_hash)? {
+        return Ok(match (receipt.status.map(|status| status.low_u64()), receipt.contract_address) {
+          (Some(0), _) | (_, None) => Reconciliation::Dropped,
+          (_, Some(address)) => Reconciliation::Deployed(address),
+        });
+      }
+    }
+
+    let address = entry.contract_address();
+    if !self.connector.code(address, None)?.0.is_empty() {
+      return Ok(Reconciliation::Deployed(address));
+    }
+
+    if self.connector.transaction_count(entry.from, Some(BlockNumber::Latest))? > entry.nonce {
+      return Ok(Reconciliation::Dropped);
+    }
+
+    // Transactions the node doesn't know about (anymore) left their nonce
+    // unused.
+    match entry.tx_hash.map(|tx_hash| self.connector.transaction(tx_hash)).transpose()?.flatten() {
+      Some(tx) => Ok(Reconciliation::Pending(tx.hash, tx.gas_price)),
+      None => Ok(Reconciliation::Dropped),
+    }
+  }
+
   fn get_first_block_hash(&self) -> Result<H256, DeploymentError> {
     let block = self.connector.get_first_block()?.unwrap();
     Ok(block.hash.unwrap())
   }
 }
 
-fn tokenize_args(args: &[SmartContractArg], deployed_contracts: &HashMap<Address, (String, Address, String, bool)>) -> Result<Vec<Token>, DeploymentError> {
-  let mut tokenized_args: Vec<Token> = vec![];
+// Same encoding as web3's deploy builder: artifacts may contain redundant
+// quotes and `0x` prefixes which are stripped before decoding.
+fn encode_deployment_data(abi: &[u8], bytecode: &str, args: &[Token]) -> Result<Vec<u8>, ethabi::Error> {
+  let contract = ethabi::Contract::load(abi)?;
+  let code: Vec<u8> = bytecode.trim().replace("\"", "").replace("0x", "").from_hex().map_err(ethabi::ErrorKind::Hex)?;
 
-  for arg in args {
-    let param_type = Reader::read(&arg.kind).map_err(DeploymentError::InvalidParamType)?;
+  match (contract.constructor(), args.is_empty()) {
+    (None, false) => Err(ethabi::ErrorKind::Msg("Constructor is not defined in the ABI.".to_string()).into()),
+    (None, true) => Ok(code),
+    (Some(constructor), _) => constructor.encode_input(code, args),
+  }
+}
 
-    let value = if ParamType::Address == param_type {
-      if arg.value.starts_with('$') {
-        let contract = deployed_contracts.values().find(|values| values.0 == arg.value[1..]).unwrap();
-        format!("{:?}", &contract.1)[2..].to_owned()
-      } else {
-        arg.value[2..].to_owned()
-      }
-    } else {
-      arg.value.to_owned()
-    };
+// Tagged Smart Contracts along with everything they depend on, which has to
+// be deployed for their references to resolve. Relies on dependencies being
+// sorted before their dependents.
+fn select_tagged<'a>(sorted_smart_contract_configs: Vec<&'a SmartContractConfig>, tags: &[String], plan: &DeploymentPlan, library_references: &HashMap<String, Vec<(String, String)>>) -> Result<Vec<&'a SmartContractConfig>, DeploymentError> {
+  if !sorted_smart_contract_configs.iter().any(|smart_contract_config| smart_contract_config.has_any_tag(tags)) {
+    return Err(DeploymentError::NoTaggedSmartContracts(tags.join(", ")));
+  }
+  Ok(select_with_dependencies(sorted_smart_contract_configs, |smart_contract_config| smart_contract_config.has_any_tag(tags), plan, library_references))
+}
 
-    let token = LenientTokenizer::tokenize(&param_type, &value).map_err(|err| DeploymentError::TokenizeParam(err, value.to_owned()))?;
-    tokenized_args.push(token);
+fn select_with_dependencies<'a, F>(sorted_smart_contract_configs: Vec<&'a SmartContractConfig>, is_selected: F, plan: &DeploymentPlan, library_references: &HashMap<String, Vec<(String, String)>>) -> Vec<&'a SmartContractConfig>
+  where F: Fn(&SmartContractConfig) -> bool
+{
+  let mut selected: HashSet<&str> = HashSet::new();
+  for smart_contract_config in sorted_smart_contract_configs.iter().rev() {
+    if is_selected(smart_contract_config) || selected.contains(smart_contract_config.name.as_str()) {
+      selected.insert(&smart_contract_config.name);
+      selected.extend(plan.dependencies(smart_contract_config, library_references));
+    }
   }
+  sorted_smart_contract_configs.into_iter().filter(|smart_contract_config| selected.contains(smart_contract_config.name.as_str())).collect()
+}
+
+fn find_deployed(deployed_contracts: &DeployedContracts, name: &str) -> Option<Address> {
+  deployed_contracts.values().find(|deployed_contract| deployed_contract.0 == name).map(|deployed_contract| deployed_contract.1)
+}
 
-  Ok(tokenized_args)
+fn placeholder_contracts(smart_contracts: &[SmartContractConfig]) -> DeployedContracts {
+  smart_contracts.iter().enumerate().map(|(i, smart_contract_config)| {
+    let address = Address::from_low_u64_be(i as u64 + 1);
+    (address, (smart_contract_config.name.to_owned(), address, "unknown".to_string(), true))
+  }).collect()
 }
 
-fn sort_by_dependencies(smart_contracts: &Vec<SmartContractConfig>) -> Result<Vec<&SmartContractConfig>, DeploymentError> {
+fn estimation_request(from: Address, to: Option<Address>, data: Vec<u8>) -> TransactionRequest {
+  TransactionRequest {
+    from,
+    to,
+    gas: None,
+    gas_price: None,
+    value: None,
+    nonce: None,
+    data: Some(Bytes(data)),
+    condition: None,
+  }
+}
+
+fn link_libraries(bytecode: &str, libraries: Option<&Vec<(String, String)>>, deployed_contracts: &DeployedContracts) -> Result<String, DeploymentError> {
+  libraries.into_iter().flatten().try_fold(bytecode.to_string(), |bytecode, (placeholder, library)| {
+    let (_name, address, _path, _existed) = deployed_contracts.values()
+      .find(|contract| &contract.0 == library)
+      .ok_or_else(|| DeploymentError::MissingConfigForReference(library.to_owned()))?;
+    info!("Linking library {} at {:?}", library, address);
+    Ok(linker::link(&bytecode, placeholder, address))
+  })
+}
+
+fn resolve_args(smart_contract_config: &SmartContractConfig, constants: &BTreeMap<String, String>) -> Vec<SmartContractArg> {
+  smart_contract_config.args.as_ref().unwrap_or(&vec![]).iter().map(|arg| SmartContractArg {
+    value: resolve_constant_references(&arg.value, constants),
+    kind: arg.kind.clone(),
+  }).collect()
+}
+
+// The most recent deployment of each Smart Contract tracked on a chain.
+fn tracked_contracts(chain_tracking_data: &tracker::SmartContractTrackingData) -> DeployedContracts {
+  let mut latest: HashMap<&str, &tracker::SmartContractTrackingDataEntry> = HashMap::new();
+  for entry in chain_tracking_data.values() {
+    if latest.get(entry.name.as_str()).is_none_or(|latest| latest.deployed_at < entry.deployed_at) {
+      latest.insert(&entry.name, entry);
+    }
+  }
+  latest.values()
+    .map(|entry| (entry.address, (entry.name.to_owned(), entry.address, String::new(), true)))
+    .collect()
+}
+
+fn tracked_args(args: &[SmartContractArg], tokens: &[Token]) -> Vec<SmartContractTrackingArg> {
+  args.iter().zip(tokens.iter()).map(|(arg, token)| SmartContractTrackingArg {
+    kind: arg.kind.to_owned(),
+    value: tracker::format_token(token),
+  }).collect()
+}
+
+fn to_deployment_error(error: SignerError, name: &str) -> DeploymentError {
+  match error {
+    SignerError::Connection(ConnectionError::Transport(error)) => DeploymentError::DeployContract(deploy::Error::Api(error), name.to_owned()),
+    error => DeploymentError::Signing(error, name.to_owned()),
+  }
+}
+
+fn constant_references(value: &str) -> Vec<&str> {
+  let mut references = vec![];
+  let mut rest = value;
+
+  while let Some(start) = rest.find(CONSTANT_REFERENCE_PREFIX) {
+    rest = &rest[start + CONSTANT_REFERENCE_PREFIX.len()..];
+    match rest.find(CONSTANT_REFERENCE_SUFFIX) {
+      Some(end) => {
+        references.push(&rest[..end]);
+        rest = &rest[end + CONSTANT_REFERENCE_SUFFIX.len()..];
+      },
+      None => break,
+    }
+  }
+  references
+}
+
+pub(crate) fn resolve_constant_references(value: &str, constants: &BTreeMap<String, String>) -> String {
+  constant_references(value).iter().fold(value.to_string(), |resolved, name| {
+    match constants.get(*name) {
+      Some(constant) => resolved.replace(&format!("{}{}{}", CONSTANT_REFERENCE_PREFIX, name, CONSTANT_REFERENCE_SUFFIX), constant),
+      None => resolved,
+    }
+  })
+}
+
+// All referenced constants are checked upfront, so that a missing constant
+// doesn't leave us with a partially deployed set of Smart Contracts.
+fn validate_constant_references(smart_contracts: &[SmartContractConfig], constants: &BTreeMap<String, String>, network: Option<&str>) -> Result<(), DeploymentError> {
+  let mut missing: Vec<String> = smart_contracts.iter()
+    .filter_map(|contract| contract.args.as_ref())
+    .flat_map(|args| args.iter())
+    .flat_map(|arg| constant_references(&arg.value))
+    .filter(|name| !constants.contains_key(*name))
+    .map(str::to_string)
+    .collect();
+
+  missing.sort();
+  missing.dedup();
+
+  if missing.is_empty() {
+    Ok(())
+  } else {
+    Err(DeploymentError::MissingConstants(network.map(str::to_string), missing))
+  }
+}
+
+// Smart Contracts are deployed after the ones they reference in their
+// constructor arguments and after the libraries linked into them.
+fn sort_by_dependencies<'a>(smart_contracts: &'a Vec<SmartContractConfig>, library_references: &HashMap<String, Vec<(String, String)>>) -> Result<Vec<&'a SmartContractConfig>, DeploymentError> {
   let graph = DiGraphMap::<&str, ()>::from_edges(
     smart_contracts.iter().filter(|contract| contract.args.is_some()).flat_map(|contract| {
       contract.args.as_ref().unwrap().iter()
-        .filter(|dep| dep.value.starts_with("$") && dep.kind == "address")
-        .map(move |dep| (contract.name.as_str(), &dep.value[1..]))
-    })
+        .flat_map(args::contract_references)
+        .map(move |reference| (contract.name.as_str(), reference))
+    }).chain(library_references.iter().flat_map(|(name, libraries)| {
+      libraries.iter().map(move |(_placeholder, library)| (name.as_str(), library.as_str()))
+    }))
   ).into_graph::<u32>();
 
   let sorted_names = toposort(&graph, None).map_err(|err| DeploymentError::CyclicDependency(graph[err.node_id()].to_string()))?;
@@ -241,6 +1129,7 @@ mod tests {
 
     use super::super::sort_by_dependencies;
     use crate::config::ProjectConfig;
+    use std::collections::HashMap;
 
     fn project_config_from_string(config: &str) -> Result<ProjectConfig, toml::de::Error> {
       toml::from_str(&config)
@@ -265,7 +1154,7 @@ mod tests {
       ").unwrap();
 
       let smart_contracts = project_config.deployment.unwrap().smart_contracts;
-      assert_eq!(sort_by_dependencies(&smart_contracts).is_err(), true);
+      assert_eq!(sort_by_dependencies(&smart_contracts, &HashMap::new()).is_err(), true);
     }
 
     #[test]
@@ -287,7 +1176,7 @@ mod tests {
       ").unwrap();
 
       let smart_contracts = project_config.deployment.unwrap().smart_contracts;
-      assert_eq!(sort_by_dependencies(&smart_contracts).is_err(), true);
+      assert_eq!(sort_by_dependencies(&smart_contracts, &HashMap::new()).is_err(), true);
     }
 
     #[test]
@@ -319,10 +1208,788 @@ mod tests {
 
       let smart_contracts = project_config.deployment.unwrap().smart_contracts;
 
-      let sorted = sort_by_dependencies(&smart_contracts).unwrap();
+      let sorted = sort_by_dependencies(&smart_contracts, &HashMap::new()).unwrap();
       let expected = vec!["D", "B", "C", "A"];
 
       assert_eq!(sorted.iter().map(|contract| contract.name.as_str()).collect::<Vec<&str>>(), expected);
     }
+
+    #[test]
+    fn it_should_sort_libraries_before_smart_contracts_linking_them() {
+      let project_config = project_config_from_string("
+        [sources]
+          artifacts = \"tests/artifacts\"
+          smart_contracts = [\"tests/contracts/*.sol\"]
+        [deployment]
+        [[deployment.smart_contracts]]
+          name = \"Token\"
+        [[deployment.smart_contracts]]
+          name = \"SafeMath\"
+      ").unwrap();
+
+      let smart_contracts = project_config.deployment.unwrap().smart_contracts;
+      let mut library_references = HashMap::new();
+      library_references.insert("Token".to_string(), vec![("__SafeMath______________________________".to_string(), "SafeMath".to_string())]);
+
+      let sorted = sort_by_dependencies(&smart_contracts, &library_references).unwrap();
+      assert_eq!(sorted.iter().map(|contract| contract.name.as_str()).collect::<Vec<&str>>(), vec!["SafeMath", "Token"]);
+    }
+  }
+
+  mod constants {
+
+    use super::super::{resolve_constant_references, validate_constant_references, sort_by_dependencies};
+    use crate::config::ProjectConfig;
+    use std::collections::{BTreeMap, HashMap};
+
+    fn project_config_from_string(config: &str) -> Result<ProjectConfig, toml::de::Error> {
+      toml::from_str(config)
+    }
+
+    #[test]
+    fn it_should_resolve_constant_references() {
+      let mut constants = BTreeMap::new();
+      constants.insert("WETH".to_string(), "0x00000000000000000000000000000000000000aa".to_string());
+      constants.insert("NAME".to_string(), "Token".to_string());
+
+      assert_eq!(resolve_constant_references("${constants.WETH}", &constants), "0x00000000000000000000000000000000000000aa");
+      assert_eq!(resolve_constant_references("Wrapped ${constants.NAME}!", &constants), "Wrapped Token!");
+      assert_eq!(resolve_constant_references("$Other", &constants), "$Other");
+    }
+
+    #[test]
+    fn it_should_fail_if_referenced_constants_are_missing() {
+      let project_config = project_config_from_string("
+        [sources]
+          artifacts = \"artifacts\"
+          smart_contracts = [\"contracts/*.sol\"]
+        [constants]
+          WETH = \"0x00000000000000000000000000000000000000aa\"
+        [[deployment.smart_contracts]]
+          name = \"A\"
+          args = [
+            { value = \"${constants.WETH}\", kind = \"address\" },
+            { value = \"${constants.DAI}\", kind = \"address\" },
+            { value = \"${constants.USDC}\", kind = \"address\" },
+          ]
+      ").unwrap();
+
+      let constants = project_config.constants.unwrap();
+      let smart_contracts = project_config.deployment.unwrap().smart_contracts;
+      let error = validate_constant_references(&smart_contracts, &constants, None).unwrap_err();
+      assert_eq!(error.to_string(), "Couldn't find constants referenced in deployment configuration: DAI, USDC");
+      let error = validate_constant_references(&smart_contracts, &constants, Some("optimism")).unwrap_err();
+      assert_eq!(error.to_string(), "Couldn't find constants referenced in deployment configuration for network optimism: DAI, USDC. Define them in [networks.optimism.constants] or [constants]");
+    }
+
+    #[test]
+    fn it_should_not_treat_constant_references_as_dependencies() {
+      let project_config = project_config_from_string("
+        [sources]
+          artifacts = \"artifacts\"
+          smart_contracts = [\"contracts/*.sol\"]
+        [[deployment.smart_contracts]]
+          name = \"A\"
+          args = [
+            { value = \"${constants.WETH}\", kind = \"address\" },
+          ]
+      ").unwrap();
+
+      let smart_contracts = project_config.deployment.unwrap().smart_contracts;
+      assert_eq!(sort_by_dependencies(&smart_contracts, &HashMap::new()).unwrap().len(), 1);
+    }
+  }
+
+  mod deployer {
+
+    use super::super::{DeployOptions, Deployer};
+    use super::super::upgrades::UpgradeOptions;
+    use crate::deployment::error::DeploymentError;
+    use crate::blockchain::connector::polling::{ReceiptPolling, ReceiptPollingConfig};
+    use crate::blockchain::error::SignerError;
+    use crate::blockchain::signer::Signer;
+    use crate::config::Config;
+    use crate::deployment::journal::{DeploymentJournal, JournalEntry};
+    use crate::deployment::tracker::DeploymentTracker;
+    use crate::testing::MockTransport;
+    use jsonrpc_core as rpc;
+    use std::fs;
+    use std::str::FromStr;
+    use web3::types::{Address, Block, Bytes, H256, H520, TransactionRequest, U256};
+
+    const ACCOUNT: &str = "0x0000000000000000000000000000000000000001";
+    const CONTRACT_ADDRESS: &str = "0x00000000000000000000000000000000000000c0";
+    const TX_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+
+    struct TestSigner;
+
+    impl Signer for TestSigner {
+      fn address(&self) -> Address {
+        Address::from_str(&ACCOUNT[2..]).unwrap()
+      }
+
+      fn sign_transaction(&self, _tx: &TransactionRequest) -> Result<Bytes, SignerError> {
+        Ok(Bytes(vec![0xaa]))
+      }
+
+      fn sign_message(&self, _message: &[u8]) -> Result<H520, SignerError> {
+        Err(SignerError::Other("Not supported".to_string()))
+      }
+    }
+
+    fn setup_project() -> tempfile::TempDir {
+      let tmp_dir = crate::testing::project_with_config("
+        [sources]
+          artifacts = \"artifacts\"
+          smart_contracts = [\"contracts/*.sol\"]
+        [deployment]
+          gas_price = 1
+          [[deployment.smart_contracts]]
+            name = \"Simple\"
+      ");
+      fs::create_dir(tmp_dir.path().join("artifacts")).unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Simple.bin"), "6080").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Simple.abi"), "[]").unwrap();
+      tmp_dir
+    }
+
+    fn receipt() -> rpc::Value {
+      serde_json::json!({
+        "transactionHash": TX_HASH,
+        "transactionIndex": "0x0",
+        "blockHash": TX_HASH,
+        "blockNumber": "0x1",
+        "cumulativeGasUsed": "0x1",
+        "gasUsed": "0x1",
+        "contractAddress": CONTRACT_ADDRESS,
+        "logs": [],
+        "status": "0x1",
+        "logsBloom": format!("0x{}", "0".repeat(512)),
+      })
+    }
+
+    #[test]
+    fn it_should_send_transactions_from_node_accounts_by_default() {
+      let tmp_dir = setup_project();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      transport.add_response("eth_accounts", serde_json::json!([ACCOUNT]));
+      transport.add_response("eth_sendTransaction", serde_json::json!(TX_HASH));
+      transport.add_response("eth_getTransactionReceipt", receipt());
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      let deployed = deployer.deploy(DeployOptions { tracking_enabled: Some(false), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap();
+
+      let contract_address = Address::from_str(&CONTRACT_ADDRESS[2..]).unwrap();
+      assert_eq!(deployed.get(&contract_address).unwrap().0, "Simple");
+      assert_eq!(transport.requests_for("eth_sendTransaction")[0][0]["from"], serde_json::json!(ACCOUNT));
+      assert_eq!(transport.requests_for("eth_sendTransaction")[0][0]["data"], serde_json::json!("0x6080"));
+    }
+
+    #[test]
+    fn it_should_deploy_and_link_libraries_first() {
+      let tmp_dir = setup_project();
+      fs::write(tmp_dir.path().join("vibranium.toml"), "
+        [sources]
+          artifacts = \"artifacts\"
+          smart_contracts = [\"contracts/*.sol\"]
+        [deployment]
+          gas_price = 1
+          [[deployment.smart_contracts]]
+            name = \"Token\"
+          [[deployment.smart_contracts]]
+            name = \"SafeMath\"
+      ").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Token.bin"), "6073__SafeMath______________________________80").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Token.abi"), "[]").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("SafeMath.bin"), "6080").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("SafeMath.abi"), "[]").unwrap();
+
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      transport.add_response("eth_accounts", serde_json::json!([ACCOUNT]));
+      for _ in 0..2 {
+        transport.add_response("eth_sendTransaction", serde_json::json!(TX_HASH));
+        transport.add_response("eth_getTransactionReceipt", receipt());
+      }
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      deployer.deploy(DeployOptions { tracking_enabled: Some(false), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap();
+
+      let transactions = transport.requests_for("eth_sendTransaction");
+      assert_eq!(transactions[0][0]["data"], serde_json::json!("0x6080"));
+      assert_eq!(transactions[1][0]["data"], serde_json::json!(format!("0x6073{}80", &CONTRACT_ADDRESS[2..])));
+      assert_eq!(deployer.gas_used(), U256::from(2));
+    }
+
+    #[test]
+    fn it_should_send_raw_transactions_when_using_a_custom_signer() {
+      let tmp_dir = setup_project();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      transport.add_response("eth_getTransactionCount", serde_json::json!("0x7"));
+      transport.add_response("eth_sendRawTransaction", serde_json::json!(TX_HASH));
+      transport.add_response("eth_getTransactionReceipt", rpc::Value::Null);
+      transport.add_response("eth_getTransactionReceipt", receipt());
+
+      let connector = transport.connector();
+      let signer = TestSigner;
+      let deployer = Deployer::new(&config, &connector, &tracker).with_signer(&signer);
+      let deployed = deployer.deploy(DeployOptions { tracking_enabled: Some(false), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap();
+
+      assert_eq!(deployed.len(), 1);
+      assert!(transport.requests_for("eth_accounts").is_empty());
+      assert!(transport.requests_for("eth_sendTransaction").is_empty());
+      assert_eq!(transport.requests_for("eth_sendRawTransaction")[0][0], serde_json::json!("0xaa"));
+    }
+
+    fn setup_network(tmp_dir: &tempfile::TempDir) -> Config {
+      let config_file = tmp_dir.path().join("vibranium.toml");
+      let content = fs::read_to_string(&config_file).unwrap() + "
+        [networks.staging]
+          url = \"http://localhost:8545\"
+          chain_id = 5
+          accounts = [\"0x00000000000000000000000000000000000000c0\"]
+      ";
+      fs::write(&config_file, content).unwrap();
+      let mut config = Config::new(tmp_dir.path().to_path_buf());
+      config.network = Some("staging".to_string());
+      config
+    }
+
+    #[test]
+    fn it_should_send_transactions_from_accounts_of_the_selected_network() {
+      let tmp_dir = setup_project();
+      let config = setup_network(&tmp_dir);
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      transport.add_response("eth_chainId", serde_json::json!("0x5"));
+      transport.add_response("eth_sendTransaction", serde_json::json!(TX_HASH));
+      transport.add_response("eth_getTransactionReceipt", receipt());
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      deployer.deploy(DeployOptions { tracking_enabled: Some(false), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap();
+
+      assert!(transport.requests_for("eth_accounts").is_empty());
+      assert_eq!(transport.requests_for("eth_sendTransaction")[0][0]["from"], serde_json::json!("0x00000000000000000000000000000000000000c0"));
+    }
+
+    #[test]
+    fn it_should_refuse_to_deploy_to_a_chain_with_unexpected_chain_id() {
+      let tmp_dir = setup_project();
+      let config = setup_network(&tmp_dir);
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      transport.add_response("eth_chainId", serde_json::json!("0x1"));
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      let error = deployer.deploy(DeployOptions { tracking_enabled: Some(false), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap_err();
+
+      assert_eq!(error.to_string(), "Refusing to deploy to network staging. Expected chain id 5, but the connected chain has id 1.");
+      assert!(transport.requests_for("eth_sendTransaction").is_empty());
+    }
+
+    #[test]
+    fn it_should_refuse_to_deploy_when_violating_the_policy() {
+      let tmp_dir = setup_project();
+      let config_file = tmp_dir.path().join("vibranium.toml");
+      let content = fs::read_to_string(&config_file).unwrap().replace("gas_price = 1", "gas_price = 1\n          policy = \"policy.toml\"");
+      fs::write(&config_file, content).unwrap();
+      fs::write(tmp_dir.path().join("policy.toml"), "max_gas_price = 0").unwrap();
+
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      let error = deployer.deploy(DeployOptions { tracking_enabled: Some(false), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap_err();
+
+      assert_eq!(error.to_string(), "Refusing to deploy Smart Contracts violating the deployment policy:\n  [violated] max_gas_price: gas price exceeds 0 for Simple (1)");
+      assert!(transport.requests_for("eth_sendTransaction").is_empty());
+    }
+
+    #[test]
+    fn it_should_refuse_to_deploy_on_top_of_unverified_smart_contracts_if_required() {
+      let tmp_dir = setup_project();
+      let config_file = tmp_dir.path().join("vibranium.toml");
+      let content = fs::read_to_string(&config_file).unwrap().replace("gas_price = 1", "gas_price = 1\n          policy = \"policy.toml\"");
+      fs::write(&config_file, content).unwrap();
+      fs::write(tmp_dir.path().join("policy.toml"), "require_verified = true").unwrap();
+
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_str(&TX_HASH[2..]).unwrap();
+      let address = Address::from_str(&CONTRACT_ADDRESS[2..]).unwrap();
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Simple".to_string(), "6080".to_string(), &vec![], address, None).unwrap();
+      tracker.record_verification(&block_hash, &address, "drifted").unwrap();
+
+      let transport = MockTransport::new();
+      transport.set_default_response("eth_getBlockByNumber", first_block());
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      let error = deployer.deploy(DeployOptions { tracking_enabled: Some(true), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap_err();
+
+      assert_eq!(error.to_string(), format!("Refusing to deploy Smart Contracts violating the deployment policy:\n  [violated] require_verified: Simple at {} is drifted", CONTRACT_ADDRESS));
+    }
+
+    #[test]
+    fn it_should_match_allowed_networks_against_the_selected_network() {
+      let tmp_dir = setup_project();
+      let config_file = tmp_dir.path().join("vibranium.toml");
+      let content = fs::read_to_string(&config_file).unwrap().replace("gas_price = 1", "gas_price = 1\n          policy = \"policy.toml\"");
+      fs::write(&config_file, content).unwrap();
+      fs::write(tmp_dir.path().join("policy.toml"), "allowed_networks = [\"production\"]").unwrap();
+
+      let config = setup_network(&tmp_dir);
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      transport.add_response("eth_chainId", serde_json::json!("0x5"));
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      let error = deployer.deploy(DeployOptions { tracking_enabled: Some(false), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap_err();
+
+      assert_eq!(error.to_string(), "Refusing to deploy Smart Contracts violating the deployment policy:\n  [violated] allowed_networks: staging is not one of production");
+      assert!(transport.requests_for("eth_sendTransaction").is_empty());
+    }
+
+    fn first_block() -> rpc::Value {
+      serde_json::to_value(Block::<H256> {
+        hash: Some(H256::from_str(&TX_HASH[2..]).unwrap()),
+        ..Block::default()
+      }).unwrap()
+    }
+
+    #[test]
+    fn it_should_journal_deployments_until_they_are_tracked() {
+      let tmp_dir = setup_project();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      transport.set_default_response("eth_getBlockByNumber", first_block());
+      transport.add_response("eth_accounts", serde_json::json!([ACCOUNT]));
+      transport.add_response("eth_getTransactionCount", serde_json::json!("0x3"));
+      transport.add_response("eth_sendTransaction", serde_json::json!(TX_HASH));
+      transport.add_response("eth_getTransactionReceipt", receipt());
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      deployer.deploy(DeployOptions { tracking_enabled: Some(true), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap();
+
+      assert_eq!(transport.requests_for("eth_sendTransaction")[0][0]["nonce"], serde_json::json!("0x3"));
+      assert!(DeploymentJournal::new(&config).read("Simple").unwrap().is_none());
+    }
+
+    fn setup_project_with_broken_smart_contract() -> tempfile::TempDir {
+      let tmp_dir = setup_project();
+      let mut config = fs::read_to_string(tmp_dir.path().join("vibranium.toml")).unwrap();
+      config.push_str("
+          [[deployment.smart_contracts]]
+            name = \"Broken\"
+          [[deployment.smart_contracts]]
+            name = \"Vault\"
+            args = [{ value = \"$Broken\", kind = \"address\" }]
+      ");
+      fs::write(tmp_dir.path().join("vibranium.toml"), config).unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Broken.bin"), "60zz").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Broken.abi"), "[]").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Vault.bin"), "6080").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Vault.abi"), r#"[{"type":"constructor","inputs":[{"name":"token","type":"address"}],"payable":false,"stateMutability":"nonpayable"}]"#).unwrap();
+      tmp_dir
+    }
+
+    #[test]
+    fn it_should_stop_at_the_first_failing_deployment_by_default() {
+      let tmp_dir = setup_project_with_broken_smart_contract();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      transport.add_response("eth_accounts", serde_json::json!([ACCOUNT]));
+      transport.set_default_response("eth_sendTransaction", serde_json::json!(TX_HASH));
+      transport.set_default_response("eth_getTransactionReceipt", receipt());
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      let error = deployer.deploy(DeployOptions { tracking_enabled: Some(false), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap_err();
+
+      assert_eq!(error.to_string(), "Couldn't deploy Smart Contract 'Broken' due to mismatching types in constructor arguments.");
+    }
+
+    #[test]
+    fn it_should_skip_dependents_of_failed_deployments_when_continuing_on_errors() {
+      let tmp_dir = setup_project_with_broken_smart_contract();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      transport.add_response("eth_accounts", serde_json::json!([ACCOUNT]));
+      transport.set_default_response("eth_sendTransaction", serde_json::json!(TX_HASH));
+      transport.set_default_response("eth_getTransactionReceipt", receipt());
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      let error = deployer.deploy(DeployOptions { tracking_enabled: Some(false), require_tests: None, continue_on_error: Some(true), tags: None, smart_contracts: None }).unwrap_err();
+
+      match error {
+        DeploymentError::PartialDeployment(report) => {
+          assert_eq!(report.deployed.values().map(|data| data.0.as_str()).collect::<Vec<&str>>(), vec!["Simple"]);
+          assert_eq!(report.failed, vec![("Broken".to_string(), "Couldn't deploy Smart Contract 'Broken' due to mismatching types in constructor arguments.".to_string())]);
+          assert_eq!(report.skipped, vec![("Vault".to_string(), "Broken".to_string())]);
+          assert!(report.to_string().starts_with("1 succeeded, 1 failed, 1 skipped"));
+        },
+        error => panic!("Expected a partial deployment, got: {}", error),
+      }
+      assert_eq!(transport.requests_for("eth_sendTransaction").len(), 1);
+    }
+
+    #[test]
+    fn it_should_deploy_tagged_smart_contracts_along_with_their_dependencies() {
+      let tmp_dir = setup_project();
+      fs::write(tmp_dir.path().join("vibranium.toml"), "
+        [sources]
+          artifacts = \"artifacts\"
+          smart_contracts = [\"contracts/*.sol\"]
+        [deployment]
+          gas_price = 1
+          [[deployment.smart_contracts]]
+            name = \"Simple\"
+            tags = [\"core\"]
+          [[deployment.smart_contracts]]
+            name = \"Vault\"
+            args = [{ value = \"$Simple\", kind = \"address\" }]
+            tags = [\"mocks\"]
+          [[deployment.smart_contracts]]
+            name = \"Other\"
+      ").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Vault.bin"), "6081").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Vault.abi"), r#"[{"type":"constructor","inputs":[{"name":"token","type":"address"}],"payable":false,"stateMutability":"nonpayable"}]"#).unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Other.bin"), "6082").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Other.abi"), "[]").unwrap();
+
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      transport.add_response("eth_accounts", serde_json::json!([ACCOUNT]));
+      transport.set_default_response("eth_sendTransaction", serde_json::json!(TX_HASH));
+      transport.set_default_response("eth_getTransactionReceipt", receipt());
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      deployer.deploy(DeployOptions { tracking_enabled: Some(false), require_tests: None, continue_on_error: None, tags: Some(vec!["mocks".to_string()]), smart_contracts: None }).unwrap();
+
+      let sent: Vec<String> = transport.requests_for("eth_sendTransaction").iter().map(|params| params[0]["data"].as_str().unwrap()[..6].to_string()).collect();
+      assert_eq!(sent, vec!["0x6080", "0x6081"]);
+
+      let error = deployer.deploy(DeployOptions { tracking_enabled: Some(false), require_tests: None, continue_on_error: None, tags: Some(vec!["unknown".to_string()]), smart_contracts: None }).unwrap_err();
+      assert_eq!(error.to_string(), "Couldn't find Smart Contracts tagged with any of: unknown");
+    }
+
+    #[test]
+    fn it_should_track_the_receipt_of_deployments() {
+      let tmp_dir = setup_project();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      transport.set_default_response("eth_getBlockByNumber", first_block());
+      transport.add_response("eth_accounts", serde_json::json!([ACCOUNT]));
+      transport.add_response("eth_getTransactionCount", serde_json::json!("0x3"));
+      transport.add_response("eth_sendTransaction", serde_json::json!(TX_HASH));
+      transport.add_response("eth_getTransactionReceipt", receipt());
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      deployer.deploy(DeployOptions { tracking_enabled: Some(true), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap();
+
+      let block_hash = H256::from_str(&TX_HASH[2..]).unwrap();
+      let entry = tracker.get_smart_contract_tracking_data(&block_hash, "Simple", "6080", &vec![]).unwrap().unwrap();
+      assert_eq!(entry.tx_hash, Some(H256::from_str(&TX_HASH[2..]).unwrap()));
+      assert_eq!(entry.block_number, Some(1));
+      assert_eq!(entry.deployer, Some(Address::from_str(&ACCOUNT[2..]).unwrap()));
+      assert_eq!(entry.gas_used, Some(1));
+    }
+
+    #[test]
+    fn it_should_deploy_upgradeable_smart_contracts_behind_a_proxy() {
+      let tmp_dir = setup_project();
+      fs::write(tmp_dir.path().join("vibranium.toml"), "
+        [sources]
+          artifacts = \"artifacts\"
+          smart_contracts = [\"contracts/*.sol\"]
+        [deployment]
+          gas_price = 1
+          [[deployment.smart_contracts]]
+            name = \"Simple\"
+            proxy = { contract = \"Proxy\", initializer = \"initialize\", initializer_args = [{ value = \"7\", kind = \"uint\" }] }
+      ").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Simple.abi"), r#"[{"type":"function","name":"initialize","inputs":[{"name":"value","type":"uint256"}],"outputs":[],"payable":false,"stateMutability":"nonpayable","constant":false}]"#).unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Proxy.bin"), "6090").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Proxy.abi"), r#"[{"type":"constructor","inputs":[{"name":"implementation","type":"address"},{"name":"data","type":"bytes"}],"payable":true,"stateMutability":"payable"}]"#).unwrap();
+
+      let proxy_address = "0x00000000000000000000000000000000000000c1";
+      let mut proxy_receipt = receipt();
+      proxy_receipt["contractAddress"] = serde_json::json!(proxy_address);
+
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      transport.set_default_response("eth_getBlockByNumber", first_block());
+      transport.set_default_response("eth_accounts", serde_json::json!([ACCOUNT]));
+      transport.add_response("eth_getTransactionCount", serde_json::json!("0x3"));
+      transport.set_default_response("eth_sendTransaction", serde_json::json!(TX_HASH));
+      transport.add_response("eth_getTransactionReceipt", receipt());
+      transport.add_response("eth_getTransactionReceipt", proxy_receipt);
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      let deployed = deployer.deploy(DeployOptions { tracking_enabled: Some(true), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap();
+
+      let implementation = Address::from_str(&CONTRACT_ADDRESS[2..]).unwrap();
+      let proxy = Address::from_str(&proxy_address[2..]).unwrap();
+      assert_eq!(deployed.get(&implementation).unwrap().0, "Simple:implementation");
+      assert_eq!(deployed.get(&proxy).unwrap().0, "Simple");
+
+      let sent = transport.requests_for("eth_sendTransaction");
+      let proxy_data = sent[1][0]["data"].as_str().unwrap();
+      assert!(proxy_data.starts_with("0x6090"));
+      assert!(proxy_data.contains(&CONTRACT_ADDRESS[2..]));
+
+      let block_hash = H256::from_str(&TX_HASH[2..]).unwrap();
+      let tracked_proxy = tracker.get_latest_smart_contract_tracking_data(&block_hash, "Simple").unwrap().unwrap();
+      let metadata = tracked_proxy.metadata.unwrap();
+      assert_eq!(tracked_proxy.address, proxy);
+      assert_eq!(metadata.get("proxy").unwrap(), "erc1967");
+      assert_eq!(metadata.get("implementation").unwrap(), &format!("{:?}", implementation));
+      let tracked_implementation = tracker.get_latest_smart_contract_tracking_data(&block_hash, "Simple:implementation").unwrap().unwrap();
+      assert_eq!(tracked_implementation.metadata.unwrap().get("proxied-by").unwrap(), &format!("{:?}", proxy));
+
+      deployer.deploy(DeployOptions { tracking_enabled: Some(true), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap();
+      assert_eq!(transport.requests_for("eth_sendTransaction").len(), 2);
+    }
+
+    #[test]
+    fn it_should_refuse_to_upgrade_smart_contracts_without_a_proxy() {
+      let tmp_dir = setup_project();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let transport = MockTransport::new();
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+
+      match deployer.upgrade("Simple", UpgradeOptions { skip_storage_check: false, require_tests: None }) {
+        Err(DeploymentError::NotUpgradeable(name)) => assert_eq!(name, "Simple"),
+        other => panic!("Expected Simple not to be upgradeable, got {:?}", other.map(|report| report.name)),
+      }
+    }
+
+    #[test]
+    fn it_should_track_journaled_deployments_that_landed_instead_of_redeploying() {
+      let tmp_dir = setup_project();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let journal = DeploymentJournal::new(&config);
+      let block_hash = H256::from_str(&TX_HASH[2..]).unwrap();
+
+      let mut entry = JournalEntry::new(&block_hash, "Simple", "6080", &vec![], Address::from_str(&ACCOUNT[2..]).unwrap(), U256::from(3));
+      entry.tx_hash = Some(H256::from_str(&TX_HASH[2..]).unwrap());
+      journal.write(&entry).unwrap();
+
+      let transport = MockTransport::new();
+      transport.set_default_response("eth_getBlockByNumber", first_block());
+      transport.add_response("eth_accounts", serde_json::json!([ACCOUNT]));
+      transport.add_response("eth_getTransactionReceipt", receipt());
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      let deployed = deployer.deploy(DeployOptions { tracking_enabled: Some(true), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap();
+
+      let contract_address = Address::from_str(&CONTRACT_ADDRESS[2..]).unwrap();
+      assert!(transport.requests_for("eth_sendTransaction").is_empty());
+      assert_eq!(deployed.get(&contract_address).unwrap().0, "Simple");
+      assert_eq!(tracker.get_smart_contract_tracking_data(&block_hash, "Simple", "6080", &vec![]).unwrap().unwrap().address, contract_address);
+      assert!(journal.read("Simple").unwrap().is_none());
+    }
+
+    fn pending_transaction(gas_price: &str) -> rpc::Value {
+      serde_json::json!({
+        "hash": TX_HASH,
+        "nonce": "0x3",
+        "blockHash": null,
+        "blockNumber": null,
+        "transactionIndex": null,
+        "from": ACCOUNT,
+        "to": null,
+        "value": "0x0",
+        "gasPrice": gas_price,
+        "gas": "0x1",
+        "input": "0x6080",
+      })
+    }
+
+    fn setup_pending_journal_entry(config: &Config) -> DeploymentJournal<'_> {
+      let journal = DeploymentJournal::new(config);
+      let block_hash = H256::from_str(&TX_HASH[2..]).unwrap();
+      let mut entry = JournalEntry::new(&block_hash, "Simple", "6080", &vec![], Address::from_str(&ACCOUNT[2..]).unwrap(), U256::from(3));
+      entry.tx_hash = Some(H256::from_str(&TX_HASH[2..]).unwrap());
+      journal.write(&entry).unwrap();
+      journal
+    }
+
+    #[test]
+    fn it_should_wait_for_journaled_deployments_that_are_still_pending() {
+      let tmp_dir = setup_project();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let journal = setup_pending_journal_entry(&config);
+
+      let transport = MockTransport::new();
+      transport.set_default_response("eth_getBlockByNumber", first_block());
+      transport.add_response("eth_accounts", serde_json::json!([ACCOUNT]));
+      transport.add_response("eth_getTransactionReceipt", rpc::Value::Null);
+      transport.add_response("eth_getCode", serde_json::json!("0x"));
+      transport.add_response("eth_getTransactionCount", serde_json::json!("0x3"));
+      transport.add_response("eth_getTransactionByHash", pending_transaction("0x5"));
+      transport.add_response("eth_getTransactionReceipt", receipt());
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      let deployed = deployer.deploy(DeployOptions { tracking_enabled: Some(true), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap();
+
+      let contract_address = Address::from_str(&CONTRACT_ADDRESS[2..]).unwrap();
+      assert!(transport.requests_for("eth_sendTransaction").is_empty());
+      assert_eq!(transport.requests_for("eth_getTransactionCount").len(), 1);
+      assert_eq!(deployed.get(&contract_address).unwrap().0, "Simple");
+      assert!(journal.read("Simple").unwrap().is_none());
+    }
+
+    #[test]
+    fn it_should_outbid_journaled_deployments_that_are_stuck() {
+      let tmp_dir = setup_project();
+      let config_file = tmp_dir.path().join("vibranium.toml");
+      let content = fs::read_to_string(&config_file).unwrap().replace("gas_price = 1", "gas_price = 1\n          fee_bumping = { blocks = 1 }");
+      fs::write(&config_file, content).unwrap();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let journal = setup_pending_journal_entry(&config);
+
+      let replacement_tx_hash = "0x00000000000000000000000000000000000000000000000000000000000000bb";
+      let transport = MockTransport::new();
+      transport.set_default_response("eth_getBlockByNumber", first_block());
+      transport.add_response("eth_accounts", serde_json::json!([ACCOUNT]));
+      transport.add_response("eth_getCode", serde_json::json!("0x"));
+      transport.add_response("eth_getTransactionCount", serde_json::json!("0x3"));
+      transport.add_response("eth_getTransactionByHash", pending_transaction("0x64"));
+      transport.add_response("eth_blockNumber", serde_json::json!("0x10"));
+      transport.add_response("eth_blockNumber", serde_json::json!("0x11"));
+      transport.add_response("eth_sendTransaction", serde_json::json!(replacement_tx_hash));
+      for _ in 0..3 {
+        transport.add_response("eth_getTransactionReceipt", rpc::Value::Null);
+      }
+      transport.add_response("eth_getTransactionReceipt", receipt());
+      transport.add_response("eth_getTransactionReceipt", receipt());
+
+      let connector = transport.connector().with_receipt_polling(ReceiptPolling::from_config(&Some(ReceiptPollingConfig {
+        interval: Some(1),
+        ..ReceiptPollingConfig::default()
+      })));
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      deployer.deploy(DeployOptions { tracking_enabled: Some(true), require_tests: None, continue_on_error: None, tags: None, smart_contracts: None }).unwrap();
+
+      let transactions = transport.requests_for("eth_sendTransaction");
+      assert_eq!(transactions.len(), 1);
+      assert_eq!(transactions[0][0]["nonce"], serde_json::json!("0x3"));
+      assert_eq!(transactions[0][0]["gasPrice"], serde_json::json!("0x6e"));
+      assert!(journal.read("Simple").unwrap().is_none());
+      assert!(transport.pending_responses().is_empty());
+    }
+
+    #[test]
+    fn it_should_rekey_deployments_using_libraries_tracked_on_their_chain() {
+      let tmp_dir = setup_project();
+      fs::write(tmp_dir.path().join("vibranium.toml"), "
+        [sources]
+          artifacts = \"artifacts\"
+          smart_contracts = [\"contracts/*.sol\"]
+        [deployment]
+          tracking_key = \"content-hash\"
+          [[deployment.smart_contracts]]
+            name = \"Token\"
+          [[deployment.smart_contracts]]
+            name = \"SafeMath\"
+      ").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Token.bin"), "6073__SafeMath______________________________80").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("Token.abi"), "[]").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("SafeMath.bin"), "6080").unwrap();
+      fs::write(tmp_dir.path().join("artifacts").join("SafeMath.abi"), "[]").unwrap();
+
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_str(&TX_HASH[2..]).unwrap();
+      let library_address = Address::from_str(&CONTRACT_ADDRESS[2..]).unwrap();
+      let token_bytecode = format!("6073{}80", &CONTRACT_ADDRESS[2..]);
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "SafeMath".to_string(), "6080".to_string(), &vec![], library_address, None).unwrap();
+      tracker.track(block_hash, "Token".to_string(), token_bytecode, &vec![], Address::from_low_u64_be(1), None).unwrap();
+      fs::write(tmp_dir.path().join("vibranium.toml"), fs::read_to_string(tmp_dir.path().join("vibranium.toml")).unwrap().replace("content-hash", "name-and-args")).unwrap();
+
+      let transport = MockTransport::new();
+      transport.set_default_response("eth_getBlockByNumber", first_block());
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      let (_strategy, report) = deployer.rekey_tracking_data().unwrap();
+
+      assert_eq!(report.updated, 2);
+      assert!(report.kept.is_empty());
+      assert_eq!(tracker.get_smart_contract_tracking_data(&block_hash, "Token", "", &vec![]).unwrap().unwrap().address, Address::from_low_u64_be(1));
+    }
+
+    #[test]
+    fn it_should_estimate_gas_of_deployments_and_functions_of_tracked_smart_contracts() {
+      let tmp_dir = setup_project();
+      fs::write(tmp_dir.path().join("artifacts").join("Simple.abi"), r#"[
+        {"inputs":[{"name":"x","type":"uint256"}],"name":"set","outputs":[],"stateMutability":"nonpayable","type":"function"},
+        {"inputs":[],"name":"get","outputs":[{"name":"","type":"uint256"}],"stateMutability":"view","type":"function"}
+      ]"#).unwrap();
+
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let contract_address = Address::from_str(&CONTRACT_ADDRESS[2..]).unwrap();
+      tracker.create_database().unwrap();
+      tracker.track(H256::from_str(&TX_HASH[2..]).unwrap(), "Simple".to_string(), "6080".to_string(), &vec![], contract_address, None).unwrap();
+
+      let transport = MockTransport::new();
+      transport.set_default_response("eth_getBlockByNumber", serde_json::to_value(Block::<H256> {
+        hash: Some(H256::from_str(&TX_HASH[2..]).unwrap()),
+        gas_limit: U256::from(3_000_000),
+        ..Block::default()
+      }).unwrap());
+      transport.add_response("eth_accounts", serde_json::json!([ACCOUNT]));
+      transport.add_response("eth_estimateGas", serde_json::json!("0x1e8481"));
+      transport.add_error("eth_estimateGas", rpc::Error::internal_error());
+
+      let connector = transport.connector();
+      let deployer = Deployer::new(&config, &connector, &tracker);
+      let report = deployer.estimate_gas().unwrap();
+
+      assert_eq!(report.block_gas_limit, Some(U256::from(3_000_000)));
+      assert_eq!(report.estimates[0].deployment, Some(U256::from(2_000_001)));
+      assert_eq!(report.estimates[0].address, Some(contract_address));
+      assert_eq!(report.estimates[0].functions.len(), 1);
+      assert_eq!(report.estimates[0].functions[0].signature, "set(uint256)");
+      assert_eq!(report.estimates[0].functions[0].gas, None);
+      assert_eq!(report.violations()[0].to_string(), "Deploying Simple needs 2000001 gas, exceeding its configured gas limit of 2000000");
+
+      let requests = transport.requests_for("eth_estimateGas");
+      assert_eq!(requests[0][0]["data"], serde_json::json!("0x6080"));
+      assert_eq!(requests[0][0].get("to"), None);
+      assert_eq!(requests[1][0]["to"], serde_json::json!(CONTRACT_ADDRESS));
+    }
   }
 }
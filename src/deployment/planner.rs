@@ -0,0 +1,250 @@
+// Deployment plans list Smart Contracts in the order they're deployed in,
+// either in `deployment.toml` next to vibranium.toml or as
+// `[[deployment.contracts]]`, and replace `[[deployment.smart_contracts]]`.
+// Besides the regular Smart Contract configuration, every entry can declare
+// additional dependencies and when it should be redeployed.
+
+use crate::config::{Config, DeploymentPlanConfig, PlannedSmartContractConfig, ProjectConfig, SmartContractConfig};
+use super::error::DeploymentError;
+use super::{args, sort_by_dependencies};
+
+use std::collections::HashMap;
+use std::fs;
+use std::str::FromStr;
+
+pub const DEPLOYMENT_PLAN_FILE: &str = "deployment.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RedeployCondition {
+  // Redeploys on every deployment.
+  Always,
+  // Redeploys when the bytecode or constructor arguments changed, which is
+  // what happens without a deployment plan as well.
+  #[default]
+  IfChanged,
+  // Keeps using the most recent tracked deployment, if there is one.
+  Never,
+}
+
+impl FromStr for RedeployCondition {
+  type Err = DeploymentError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "always" => Ok(RedeployCondition::Always),
+      "if-changed" => Ok(RedeployCondition::IfChanged),
+      "never" => Ok(RedeployCondition::Never),
+      _ => Err(DeploymentError::InvalidRedeployCondition(s.to_string())),
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct DeploymentPlan {
+  // Names of the planned Smart Contracts in deployment order. Without a plan,
+  // Smart Contracts are ordered by their dependencies instead.
+  order: Option<Vec<String>>,
+  depends_on: HashMap<String, Vec<String>>,
+  redeploy: HashMap<String, RedeployCondition>,
+}
+
+impl DeploymentPlan {
+  pub fn is_explicit(&self) -> bool {
+    self.order.is_some()
+  }
+
+  pub fn redeploy_condition(&self, name: &str) -> RedeployCondition {
+    self.redeploy.get(name).cloned().unwrap_or_default()
+  }
+
+  // Smart Contracts referenced in constructor arguments, linked libraries and
+  // additionally declared dependencies.
+  pub fn dependencies<'a>(&'a self, smart_contract_config: &'a SmartContractConfig, library_references: &'a HashMap<String, Vec<(String, String)>>) -> Vec<&'a str> {
+    let name = smart_contract_config.name.as_str();
+    smart_contract_config.args.iter().flatten()
+      .flat_map(args::contract_references)
+      .chain(library_references.get(name).into_iter().flatten().map(|(_placeholder, library)| library.as_str()))
+      .chain(self.depends_on.get(name).into_iter().flatten().map(String::as_str))
+      .collect()
+  }
+
+  // Planned Smart Contracts are checked to only depend on the ones listed
+  // before them, instead of being reordered.
+  pub fn sort<'a>(&self, smart_contracts: &'a Vec<SmartContractConfig>, library_references: &HashMap<String, Vec<(String, String)>>) -> Result<Vec<&'a SmartContractConfig>, DeploymentError> {
+    let order = match &self.order {
+      Some(order) => order,
+      None => return sort_by_dependencies(smart_contracts, library_references),
+    };
+
+    for (position, smart_contract_config) in smart_contracts.iter().enumerate() {
+      let name = smart_contract_config.name.as_str();
+
+      for reference in self.dependencies(smart_contract_config, library_references) {
+        match order.iter().position(|planned| planned == reference) {
+          None => return Err(DeploymentError::MissingConfigForReference(reference.to_owned())),
+          Some(dependency_position) if dependency_position >= position => {
+            return Err(DeploymentError::PlanOrder(name.to_owned(), reference.to_owned()));
+          },
+          Some(_) => (),
+        }
+      }
+    }
+    Ok(smart_contracts.iter().collect())
+  }
+}
+
+pub struct DeploymentPlanner<'a> {
+  config: &'a Config,
+}
+
+impl<'a> DeploymentPlanner<'a> {
+  pub fn new(config: &'a Config) -> DeploymentPlanner<'a> {
+    DeploymentPlanner {
+      config,
+    }
+  }
+
+  // Smart Contracts tagged with any of the given tags, either in the
+  // deployment configuration or the deployment plan.
+  pub fn tagged(&self, tags: &[String]) -> Result<Vec<SmartContractConfig>, DeploymentError> {
+    let mut project_config = self.config.read()?;
+    self.apply(&mut project_config)?;
+    let tagged: Vec<SmartContractConfig> = project_config.deployment
+      .map(|deployment_config| deployment_config.smart_contracts)
+      .unwrap_or_default()
+      .into_iter()
+      .filter(|smart_contract_config| smart_contract_config.has_any_tag(tags))
+      .collect();
+
+    if tagged.is_empty() {
+      return Err(DeploymentError::NoTaggedSmartContracts(tags.join(", ")));
+    }
+    Ok(tagged)
+  }
+
+  // `deployment.toml` takes precedence over `[[deployment.contracts]]`. The
+  // planned Smart Contracts replace the configured ones, so that everything
+  // working with the deployment configuration picks them up.
+  pub fn apply(&self, project_config: &mut ProjectConfig) -> Result<DeploymentPlan, DeploymentError> {
+    let plan_file = self.config.project_path.join(DEPLOYMENT_PLAN_FILE);
+    let contracts = if plan_file.exists() {
+      let plan_config: DeploymentPlanConfig = toml::from_str(&fs::read_to_string(&plan_file)?)
+        .map_err(|err| DeploymentError::Other(format!("Couldn't read {}: {}", DEPLOYMENT_PLAN_FILE, err)))?;
+      Some(plan_config.contracts)
+    } else {
+      project_config.deployment.as_mut().and_then(|deployment_config| deployment_config.contracts.take())
+    };
+
+    let contracts = match contracts {
+      Some(contracts) => contracts,
+      None => return Ok(DeploymentPlan::default()),
+    };
+
+    let (plan, smart_contracts) = plan(contracts)?;
+    project_config.deployment.get_or_insert_with(Default::default).smart_contracts = smart_contracts;
+    Ok(plan)
+  }
+}
+
+fn plan(contracts: Vec<PlannedSmartContractConfig>) -> Result<(DeploymentPlan, Vec<SmartContractConfig>), DeploymentError> {
+  let mut plan = DeploymentPlan {
+    order: Some(contracts.iter().map(|contract| contract.smart_contract.name.to_owned()).collect()),
+    ..Default::default()
+  };
+
+  let mut smart_contracts = vec![];
+  for contract in contracts {
+    let name = contract.smart_contract.name.to_owned();
+    if smart_contracts.iter().any(|smart_contract_config: &SmartContractConfig| smart_contract_config.name == name) {
+      return Err(DeploymentError::Other(format!("Smart Contract '{}' is planned more than once", name)));
+    }
+    if let Some(redeploy) = &contract.redeploy {
+      plan.redeploy.insert(name.to_owned(), redeploy.parse()?);
+    }
+    if let Some(depends_on) = contract.depends_on {
+      plan.depends_on.insert(name, depends_on);
+    }
+    smart_contracts.push(contract.smart_contract);
+  }
+  Ok((plan, smart_contracts))
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{plan, DeploymentPlanner, RedeployCondition};
+  use crate::config::{Config, DeploymentPlanConfig, ProjectConfig};
+  use crate::deployment::error::DeploymentError;
+  use std::collections::HashMap;
+  use std::fs;
+
+  fn plan_config(plan: &str) -> DeploymentPlanConfig {
+    toml::from_str(plan).unwrap()
+  }
+
+  #[test]
+  fn it_should_keep_the_planned_order() {
+    let (plan, smart_contracts) = plan(plan_config("
+      [[contracts]]
+      name = \"MyToken\"
+      redeploy = \"never\"
+
+      [[contracts]]
+      name = \"Exchange\"
+      redeploy = \"always\"
+      args = [{ value = \"$MyToken.address\", kind = \"address\" }]
+
+      [[contracts]]
+      name = \"Faucet\"
+      depends_on = [\"MyToken\"]
+    ").contracts).unwrap();
+
+    let sorted: Vec<&str> = plan.sort(&smart_contracts, &HashMap::new()).unwrap().iter().map(|contract| contract.name.as_str()).collect();
+    assert_eq!(sorted, vec!["MyToken", "Exchange", "Faucet"]);
+    assert_eq!(plan.redeploy_condition("MyToken"), RedeployCondition::Never);
+    assert_eq!(plan.redeploy_condition("Exchange"), RedeployCondition::Always);
+    assert_eq!(plan.redeploy_condition("Faucet"), RedeployCondition::IfChanged);
+  }
+
+  #[test]
+  fn it_should_fail_on_dependencies_planned_later() {
+    let (plan, smart_contracts) = plan(plan_config("
+      [[contracts]]
+      name = \"Faucet\"
+      depends_on = [\"MyToken\"]
+
+      [[contracts]]
+      name = \"MyToken\"
+    ").contracts).unwrap();
+
+    match plan.sort(&smart_contracts, &HashMap::new()) {
+      Err(DeploymentError::PlanOrder(name, reference)) => assert_eq!((name.as_str(), reference.as_str()), ("Faucet", "MyToken")),
+      other => panic!("Unexpected result {:?}", other.map(|sorted| sorted.len())),
+    }
+  }
+
+  #[test]
+  fn it_should_prefer_the_plan_file() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    fs::write(tmp_dir.path().join(super::DEPLOYMENT_PLAN_FILE), "
+      [[contracts]]
+      name = \"MyToken\"
+      redeploy = \"sometimes\"
+    ").unwrap();
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    match DeploymentPlanner::new(&config).apply(&mut ProjectConfig::default()) {
+      Err(DeploymentError::InvalidRedeployCondition(condition)) => assert_eq!(condition, "sometimes"),
+      other => panic!("Unexpected result {:?}", other.map(|plan| plan.is_explicit())),
+    }
+
+    fs::write(tmp_dir.path().join(super::DEPLOYMENT_PLAN_FILE), "
+      [[contracts]]
+      name = \"MyToken\"
+    ").unwrap();
+
+    let mut project_config = ProjectConfig::default();
+    let plan = DeploymentPlanner::new(&config).apply(&mut project_config).unwrap();
+    assert!(plan.is_explicit());
+    assert_eq!(project_config.deployment.unwrap().smart_contracts[0].name, "MyToken");
+  }
+}
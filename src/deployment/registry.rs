@@ -0,0 +1,379 @@
+// Project-wide registry of the function selectors, event topics and errors of
+// every known ABI, i.e. compiled artifacts and the ABIs of Smart Contracts
+// deployed from pre-defined artifacts (`abi_path`). Calls, logs and revert
+// data of any known Smart Contract can be decoded with it, without knowing
+// which Smart Contract they belong to. It's persisted in .vibranium/registry
+// and rebuilt whenever one of the ABIs changed.
+
+use crate::blockchain::error::ConnectionError;
+use crate::config::Config;
+use crate::interface::{AbiEntry, AbiParam};
+use crate::interface::compliance::{selector, signature, topic};
+use crate::project_generator::VIBRANIUM_PROJECT_DIRECTORY;
+use super::error::DeploymentTrackingError;
+use super::events::DecodedEvent;
+use super::tracker::format_token;
+
+use ethabi::{ParamType, RawLog};
+use ethabi::param_type::Reader;
+use rustc_hex::{FromHex, ToHex};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use web3::types::Log;
+
+pub const REGISTRY_DIRECTORY: &str = "registry";
+const REGISTRY_FILE: &str = "abi.json";
+const ARTIFACT_EXTENSION_ABI: &str = "abi";
+// Error(string) and Panic(uint256), which Solidity reverts with unless a
+// custom error is used.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RegistryEntry {
+  pub contract: String,
+  pub entry: AbiEntry,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AbiRegistry {
+  // Modification times of the ABIs the registry has been built from.
+  sources: BTreeMap<String, u64>,
+  functions: BTreeMap<String, Vec<RegistryEntry>>,
+  events: BTreeMap<String, Vec<RegistryEntry>>,
+  errors: BTreeMap<String, Vec<RegistryEntry>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedCall {
+  pub contract: String,
+  pub name: String,
+  pub params: Vec<(String, String)>,
+}
+
+impl fmt::Display for DecodedCall {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}.{}({})", self.contract, self.name, format_params(&self.params))
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedError {
+  // Not set for the built-in Error(string) and Panic(uint256).
+  pub contract: Option<String>,
+  pub name: String,
+  pub params: Vec<(String, String)>,
+}
+
+impl fmt::Display for DecodedError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if let Some(contract) = &self.contract {
+      write!(f, "{}.", contract)?;
+    }
+    write!(f, "{}({})", self.name, format_params(&self.params))
+  }
+}
+
+impl AbiRegistry {
+  // The persisted registry, unless any of the ABIs changed since it has been
+  // built.
+  pub fn load(config: &Config) -> Result<AbiRegistry, DeploymentTrackingError> {
+    let abis = abi_sources(config)?;
+    let sources: BTreeMap<String, u64> = abis.iter()
+      .map(|(_contract, path)| (path.to_string_lossy().to_string(), modified_at(path)))
+      .collect();
+
+    let registry_file = config.project_path.join(VIBRANIUM_PROJECT_DIRECTORY).join(REGISTRY_DIRECTORY).join(REGISTRY_FILE);
+    let persisted = fs::read_to_string(&registry_file).ok()
+      .and_then(|registry| serde_json::from_str::<AbiRegistry>(&registry).ok())
+      .filter(|registry| registry.sources == sources);
+
+    if let Some(registry) = persisted {
+      return Ok(registry);
+    }
+
+    let mut registry = AbiRegistry { sources, ..AbiRegistry::default() };
+    for (contract, path) in &abis {
+      let abi: Vec<AbiEntry> = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|err| DeploymentTrackingError::Other(format!("Couldn't read ABI of {}: {}", contract, err)))?;
+      registry.add(contract, &abi);
+    }
+
+    // Without a Vibranium project, the registry is only kept in memory.
+    if config.project_path.join(VIBRANIUM_PROJECT_DIRECTORY).exists() {
+      fs::create_dir_all(registry_file.parent().unwrap())?;
+      fs::write(&registry_file, serde_json::to_string(&registry).map_err(|err| DeploymentTrackingError::Other(err.to_string()))?)?;
+    }
+    Ok(registry)
+  }
+
+  pub fn add(&mut self, contract: &str, abi: &[AbiEntry]) {
+    for entry in abi {
+      let (entries, key) = match entry.kind.as_str() {
+        "function" => (&mut self.functions, hex(&selector(entry))),
+        "error" => (&mut self.errors, hex(&selector(entry))),
+        "event" if !entry.anonymous.unwrap_or(false) => (&mut self.events, hex(&topic(entry))),
+        _ => continue,
+      };
+      let registered = entries.entry(key).or_default();
+      // Shared declarations, e.g. of standard interfaces, are registered once.
+      if !registered.iter().any(|registered| describe(&registered.entry) == describe(entry)) {
+        registered.push(RegistryEntry { contract: contract.to_string(), entry: entry.clone() });
+      }
+    }
+  }
+
+  // Known events, optionally only those of the given name.
+  pub fn events(&self, name: Option<&str>) -> Vec<&RegistryEntry> {
+    self.events.values()
+      .flatten()
+      .filter(|registered| name.is_none() || registered.entry.name.as_deref() == name)
+      .collect()
+  }
+
+  pub fn decode_log(&self, log: &Log) -> Option<DecodedEvent> {
+    let topic = log.topics.first()?;
+    self.events.get(&hex(topic.as_bytes()))?.iter().find_map(|registered| {
+      let event: ethabi::Event = serde_json::to_value(&registered.entry).ok().and_then(|event| serde_json::from_value(event).ok())?;
+      let parsed = event.parse_log(RawLog { topics: log.topics.clone(), data: log.data.0.clone() }).ok()?;
+      Some(DecodedEvent {
+        contract: Some(registered.contract.to_owned()),
+        name: event.name.to_owned(),
+        address: log.address,
+        block_number: log.block_number.map(|block_number| block_number.low_u64()),
+        transaction_hash: log.transaction_hash,
+        params: parsed.params.iter().map(|param| (param.name.to_owned(), format_token(&param.value))).collect(),
+      })
+    })
+  }
+
+  pub fn decode_call(&self, data: &[u8]) -> Option<DecodedCall> {
+    if data.len() < 4 {
+      return None;
+    }
+    self.functions.get(&hex(&data[..4]))?.iter().find_map(|registered| {
+      Some(DecodedCall {
+        contract: registered.contract.to_owned(),
+        name: registered.entry.name.to_owned().unwrap_or_default(),
+        params: decode_params(registered.entry.inputs.as_deref().unwrap_or_default(), &data[4..])?,
+      })
+    })
+  }
+
+  pub fn decode_error(&self, data: &[u8]) -> Option<DecodedError> {
+    if data.len() < 4 {
+      return None;
+    }
+    let builtin = |name: &str, kind: &str| {
+      let param = AbiParam { name: String::new(), kind: kind.to_string(), internal_type: None, components: None, indexed: None };
+      decode_params(&[param], &data[4..]).map(|params| DecodedError { contract: None, name: name.to_string(), params })
+    };
+    match &data[..4] {
+      selector if selector == ERROR_SELECTOR => builtin("Error", "string"),
+      selector if selector == PANIC_SELECTOR => builtin("Panic", "uint256"),
+      selector => self.errors.get(&hex(selector))?.iter().find_map(|registered| {
+        Some(DecodedError {
+          contract: Some(registered.contract.to_owned()),
+          name: registered.entry.name.to_owned().unwrap_or_default(),
+          params: decode_params(registered.entry.inputs.as_deref().unwrap_or_default(), &data[4..])?,
+        })
+      }),
+    }
+  }
+}
+
+// Revert data nodes attach to errors of calls and gas estimations, either as
+// `data` of the error or as `data` of an object in there.
+pub fn revert_data(error: &ConnectionError) -> Option<Vec<u8>> {
+  let data = match error {
+    ConnectionError::Transport(web3::Error::Rpc(error)) => error.data.as_ref()?,
+    _ => return None,
+  };
+  let data = match data {
+    serde_json::Value::Object(object) => object.get("data")?,
+    data => data,
+  };
+  data.as_str()
+    .filter(|data| data.starts_with("0x"))
+    .and_then(|data| data[2..].from_hex().ok())
+}
+
+// Compiled artifacts first, as their contract names are the ones users know.
+fn abi_sources(config: &Config) -> Result<Vec<(String, PathBuf)>, DeploymentTrackingError> {
+  let project_config = config.read().map_err(|err| DeploymentTrackingError::Other(err.to_string()))?;
+  let artifacts_path = config.project_path.join(&project_config.sources.artifacts);
+  let mut abis = vec![];
+
+  if artifacts_path.exists() {
+    let mut artifacts: Vec<PathBuf> = fs::read_dir(&artifacts_path)?
+      .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+      .filter(|path| path.extension().is_some_and(|extension| extension == ARTIFACT_EXTENSION_ABI))
+      .collect();
+    artifacts.sort();
+    for artifact in artifacts {
+      let contract = artifact.file_stem().unwrap().to_string_lossy().to_string();
+      abis.push((contract, artifact));
+    }
+  }
+
+  for smart_contract_config in project_config.deployment.iter().flat_map(|deployment_config| deployment_config.smart_contracts.iter()) {
+    if let Some(abi_path) = &smart_contract_config.abi_path {
+      let abi_path = config.project_path.join(abi_path);
+      if abi_path.exists() && !abis.iter().any(|(_contract, path)| path == &abi_path) {
+        abis.push((smart_contract_config.name.to_owned(), abi_path));
+      }
+    }
+  }
+  Ok(abis)
+}
+
+fn modified_at(path: &Path) -> u64 {
+  fs::metadata(path)
+    .and_then(|metadata| metadata.modified())
+    .ok()
+    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+    .map(|duration| duration.as_millis() as u64)
+    .unwrap_or(0)
+}
+
+// Parameters of types ethabi can't decode, e.g. tuples, fail the decoding.
+fn decode_params(params: &[AbiParam], data: &[u8]) -> Option<Vec<(String, String)>> {
+  let kinds: Vec<ParamType> = params.iter().map(|param| Reader::read(&param.kind).ok()).collect::<Option<Vec<ParamType>>>()?;
+  let tokens = ethabi::decode(&kinds, data).ok()?;
+  Some(params.iter().zip(tokens.iter()).map(|(param, token)| (param.name.to_owned(), format_token(token))).collect())
+}
+
+fn describe(entry: &AbiEntry) -> String {
+  let indexed: Vec<bool> = entry.inputs.iter().flatten().map(|param| param.indexed.unwrap_or(false)).collect();
+  format!("{} {:?}", signature(entry), indexed)
+}
+
+fn format_params(params: &[(String, String)]) -> String {
+  params.iter()
+    .map(|(name, value)| if name.is_empty() { value.to_owned() } else { format!("{}: {}", name, value) })
+    .collect::<Vec<String>>()
+    .join(", ")
+}
+
+fn hex(bytes: &[u8]) -> String {
+  format!("0x{}", bytes.to_hex::<String>())
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{revert_data, AbiRegistry};
+  use crate::blockchain::error::ConnectionError;
+  use crate::config::Config;
+  use rustc_hex::FromHex;
+  use std::fs;
+  use std::str::FromStr;
+  use web3::types::{Address, Log, H256};
+
+  const TOKEN_ABI: &str = r#"[
+    {"inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}],"name":"transfer","outputs":[{"name":"","type":"bool"}],"stateMutability":"nonpayable","type":"function"},
+    {"anonymous":false,"inputs":[{"indexed":true,"name":"from","type":"address"},{"indexed":true,"name":"to","type":"address"},{"indexed":false,"name":"value","type":"uint256"}],"name":"Transfer","type":"event"},
+    {"inputs":[{"name":"needed","type":"uint256"}],"name":"InsufficientBalance","type":"error"}
+  ]"#;
+  // keccak256("Transfer(address,address,uint256)")
+  const TRANSFER_TOPIC: &str = "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+  fn setup_project() -> (tempfile::TempDir, Config) {
+    let tmp_dir = crate::testing::project_with_config("
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+
+      [deployment]
+        [[deployment.smart_contracts]]
+          name = \"Bridge\"
+          abi_path = \"imported/Bridge.abi\"
+          bytecode_path = \"imported/Bridge.bin\"
+    ");
+    fs::create_dir_all(tmp_dir.path().join("artifacts")).unwrap();
+    fs::create_dir_all(tmp_dir.path().join("imported")).unwrap();
+    fs::write(tmp_dir.path().join("artifacts").join("Token.abi"), TOKEN_ABI).unwrap();
+    fs::write(tmp_dir.path().join("imported").join("Bridge.abi"), r#"[
+      {"anonymous":false,"inputs":[{"indexed":false,"name":"amount","type":"uint256"}],"name":"Bridged","type":"event"}
+    ]"#).unwrap();
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    (tmp_dir, config)
+  }
+
+  fn log(topics: Vec<H256>, data: Vec<u8>) -> Log {
+    serde_json::from_value(serde_json::json!({
+      "address": "0x00000000000000000000000000000000000000c0",
+      "topics": topics,
+      "data": format!("0x{}", data.iter().map(|byte| format!("{:02x}", byte)).collect::<String>()),
+    })).unwrap()
+  }
+
+  #[test]
+  fn it_should_decode_logs_of_artifacts_and_imported_abis() {
+    let (tmp_dir, config) = setup_project();
+    let registry = AbiRegistry::load(&config).unwrap();
+    assert!(tmp_dir.path().join(".vibranium").join("registry").join("abi.json").exists());
+
+    let transfer = registry.decode_log(&log(
+      vec![H256::from_str(TRANSFER_TOPIC).unwrap(), H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+      H256::from_low_u64_be(100).as_bytes().to_vec(),
+    )).unwrap();
+    assert_eq!(transfer.contract, Some("Token".to_string()));
+    assert_eq!(transfer.address, Address::from_low_u64_be(0xc0));
+    assert_eq!(transfer.to_string(), "Token.Transfer(from: 0x0000000000000000000000000000000000000001, to: 0x0000000000000000000000000000000000000002, value: 100)");
+
+    let bridged = registry.events(Some("Bridged"));
+    assert_eq!(bridged.len(), 1);
+    assert_eq!(bridged[0].contract, "Bridge");
+    assert!(registry.decode_log(&log(vec![H256::from_low_u64_be(7)], vec![])).is_none());
+  }
+
+  #[test]
+  fn it_should_rebuild_the_registry_when_abis_change() {
+    let (tmp_dir, config) = setup_project();
+    assert!(AbiRegistry::load(&config).unwrap().events(Some("Minted")).is_empty());
+
+    fs::write(tmp_dir.path().join("artifacts").join("Minter.abi"), r#"[
+      {"anonymous":false,"inputs":[{"indexed":false,"name":"amount","type":"uint256"}],"name":"Minted","type":"event"}
+    ]"#).unwrap();
+    assert_eq!(AbiRegistry::load(&config).unwrap().events(Some("Minted")).len(), 1);
+  }
+
+  #[test]
+  fn it_should_decode_calls_and_errors() {
+    let (_tmp_dir, config) = setup_project();
+    let registry = AbiRegistry::load(&config).unwrap();
+
+    let mut call = "a9059cbb".from_hex::<Vec<u8>>().unwrap();
+    call.extend_from_slice(H256::from_low_u64_be(2).as_bytes());
+    call.extend_from_slice(H256::from_low_u64_be(5).as_bytes());
+    assert_eq!(registry.decode_call(&call).unwrap().to_string(), "Token.transfer(to: 0x0000000000000000000000000000000000000002, amount: 5)");
+
+    // Error("Not allowed")
+    let reason = "08c379a0\
+      0000000000000000000000000000000000000000000000000000000000000020\
+      000000000000000000000000000000000000000000000000000000000000000b\
+      4e6f7420616c6c6f776564000000000000000000000000000000000000000000".from_hex::<Vec<u8>>().unwrap();
+    assert_eq!(registry.decode_error(&reason).unwrap().to_string(), "Error(Not allowed)");
+
+    // keccak256("InsufficientBalance(uint256)")[..4]
+    let mut custom = "92665351".from_hex::<Vec<u8>>().unwrap();
+    custom.extend_from_slice(H256::from_low_u64_be(10).as_bytes());
+    assert_eq!(registry.decode_error(&custom).unwrap().to_string(), "Token.InsufficientBalance(needed: 10)");
+    assert!(registry.decode_error(&[0, 0, 0, 1]).is_none());
+  }
+
+  #[test]
+  fn it_should_read_revert_data_of_rpc_errors() {
+    let mut error = jsonrpc_core::Error::new(jsonrpc_core::ErrorCode::ServerError(3));
+    error.data = Some(serde_json::json!("0x4e487b71"));
+    assert_eq!(revert_data(&ConnectionError::Transport(web3::Error::Rpc(error.clone()))), Some(vec![0x4e, 0x48, 0x7b, 0x71]));
+
+    error.data = Some(serde_json::json!({ "data": "0x08c379a0" }));
+    assert_eq!(revert_data(&ConnectionError::Transport(web3::Error::Rpc(error))), Some(vec![0x08, 0xc3, 0x79, 0xa0]));
+    assert_eq!(revert_data(&ConnectionError::Other("reverted".to_string())), None);
+  }
+}
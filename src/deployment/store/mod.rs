@@ -0,0 +1,326 @@
+// Tracking data is persisted by the store configured with `deployment.store`:
+// a single TOML file (default), a JSON file per network, which keeps diffs of
+// large projects readable, or an embedded SQLite database.
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+use super::error::DeploymentTrackingError;
+use super::migration;
+use super::tracker::{SmartContractTrackingData, SmartContractTrackingDataEntry, TrackingData, TRACKING_FILE};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+
+pub const TRACKING_DIRECTORY: &str = "tracking";
+pub const TRACKING_DATABASE: &str = "tracking.sqlite";
+const STORE_TOML: &str = "toml";
+const STORE_JSON: &str = "json";
+const STORE_SQLITE: &str = "sqlite";
+const JSON_EXTENSION: &str = "json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TrackingStoreKind {
+  #[default]
+  Toml,
+  Json,
+  Sqlite,
+}
+
+impl FromStr for TrackingStoreKind {
+  type Err = DeploymentTrackingError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      STORE_TOML => Ok(TrackingStoreKind::Toml),
+      STORE_JSON => Ok(TrackingStoreKind::Json),
+      STORE_SQLITE => Ok(TrackingStoreKind::Sqlite),
+      _ => Err(DeploymentTrackingError::InvalidStore(s.to_string())),
+    }
+  }
+}
+
+impl fmt::Display for TrackingStoreKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      TrackingStoreKind::Toml => write!(f, "{}", STORE_TOML),
+      TrackingStoreKind::Json => write!(f, "{}", STORE_JSON),
+      TrackingStoreKind::Sqlite => write!(f, "{}", STORE_SQLITE),
+    }
+  }
+}
+
+pub trait TrackingStore {
+  fn exists(&self) -> bool;
+  fn create(&self) -> Result<(), DeploymentTrackingError>;
+  fn read(&self) -> Result<TrackingData, DeploymentTrackingError>;
+  // Replaces all tracking data. Callers hold the tracking lock.
+  fn write(&self, tracking_data: &TrackingData) -> Result<(), DeploymentTrackingError>;
+  // Files the tracking data is persisted in, e.g. to package or remove them.
+  fn files(&self) -> Vec<PathBuf>;
+}
+
+pub fn open(kind: TrackingStoreKind, vibranium_dir: &Path) -> Result<Box<dyn TrackingStore>, DeploymentTrackingError> {
+  match kind {
+    TrackingStoreKind::Toml => Ok(Box::new(TomlStore { path: vibranium_dir.join(TRACKING_FILE) })),
+    TrackingStoreKind::Json => Ok(Box::new(JsonStore { path: vibranium_dir.join(TRACKING_DIRECTORY) })),
+    TrackingStoreKind::Sqlite => open_sqlite(vibranium_dir),
+  }
+}
+
+#[cfg(feature = "sqlite")]
+fn open_sqlite(vibranium_dir: &Path) -> Result<Box<dyn TrackingStore>, DeploymentTrackingError> {
+  Ok(Box::new(sqlite::SqliteStore::new(vibranium_dir.join(TRACKING_DATABASE))))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn open_sqlite(_vibranium_dir: &Path) -> Result<Box<dyn TrackingStore>, DeploymentTrackingError> {
+  Err(DeploymentTrackingError::UnsupportedStore(STORE_SQLITE.to_string()))
+}
+
+pub struct TomlStore {
+  path: PathBuf,
+}
+
+impl TrackingStore for TomlStore {
+  fn exists(&self) -> bool {
+    self.path.exists()
+  }
+
+  fn create(&self) -> Result<(), DeploymentTrackingError> {
+    let _ = fs::OpenOptions::new().write(true).create(true).truncate(false).open(&self.path)?;
+    Ok(())
+  }
+
+  fn read(&self) -> Result<TrackingData, DeploymentTrackingError> {
+    let mut toml: toml::Value = toml::from_str(&fs::read_to_string(&self.path)?)?;
+    let version = migration::take_version(&mut toml)?;
+    migration::migrate(version, &mut toml)?;
+    Ok(toml.try_into()?)
+  }
+
+  fn write(&self, tracking_data: &TrackingData) -> Result<(), DeploymentTrackingError> {
+    if let Some(version) = self.version_on_disk()?.filter(|version| *version < migration::SCHEMA_VERSION) {
+      let backup = self.path.with_file_name(format!("{}.v{}.bak", TRACKING_FILE, version));
+      info!("Upgrading tracking data to schema version {}, backing it up to {}", migration::SCHEMA_VERSION, backup.display());
+      fs::copy(&self.path, &backup)?;
+    }
+    // Serialized as a value, which puts tables after plain values.
+    let mut toml = toml::Value::try_from(tracking_data)?;
+    migration::set_version(&mut toml);
+    write_atomically(&self.path, toml::to_string(&toml)?.as_bytes())
+  }
+
+  fn files(&self) -> Vec<PathBuf> {
+    vec![self.path.clone()].into_iter().filter(|path| path.exists()).collect()
+  }
+}
+
+impl TomlStore {
+  // Empty files have just been created and don't need a backup.
+  fn version_on_disk(&self) -> Result<Option<i64>, DeploymentTrackingError> {
+    let content = match fs::read_to_string(&self.path) {
+      Ok(content) => content,
+      Err(_) => return Ok(None),
+    };
+    if content.trim().is_empty() {
+      return Ok(None);
+    }
+    let mut toml: toml::Value = toml::from_str(&content)?;
+    migration::take_version(&mut toml).map(Some)
+  }
+}
+
+// Chains are grouped into a file per network they've been deployed to, named
+// after the network. Chains without a network are named after their key.
+pub struct JsonStore {
+  path: PathBuf,
+}
+
+impl JsonStore {
+  fn file_name(chain: &str, chain_tracking_data: &SmartContractTrackingData) -> String {
+    let name = chain_tracking_data.values()
+      .filter_map(|entry| entry.network.as_deref())
+      .min()
+      .unwrap_or(chain);
+    let name: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect();
+    format!("{}.{}", name, JSON_EXTENSION)
+  }
+}
+
+impl TrackingStore for JsonStore {
+  fn exists(&self) -> bool {
+    self.path.is_dir()
+  }
+
+  fn create(&self) -> Result<(), DeploymentTrackingError> {
+    fs::create_dir_all(&self.path)?;
+    Ok(())
+  }
+
+  fn read(&self) -> Result<TrackingData, DeploymentTrackingError> {
+    let mut tracking_data = TrackingData::new();
+    for file in self.files() {
+      let chains: TrackingData = serde_json::from_str(&fs::read_to_string(&file)?)
+        .map_err(|err| DeploymentTrackingError::Other(format!("Couldn't read tracking data from {}: {}", file.display(), err)))?;
+      for (chain, chain_tracking_data) in chains {
+        tracking_data.entry(chain).or_default().extend(chain_tracking_data);
+      }
+    }
+    Ok(tracking_data)
+  }
+
+  fn write(&self, tracking_data: &TrackingData) -> Result<(), DeploymentTrackingError> {
+    // Sorted, so that unchanged tracking data is written the same way.
+    let mut files: BTreeMap<String, BTreeMap<&String, BTreeMap<&String, &SmartContractTrackingDataEntry>>> = BTreeMap::new();
+    for (chain, chain_tracking_data) in tracking_data {
+      files.entry(JsonStore::file_name(chain, chain_tracking_data)).or_default()
+        .insert(chain, chain_tracking_data.iter().collect());
+    }
+
+    for (file_name, chains) in &files {
+      let json = serde_json::to_string_pretty(chains).map_err(|err| DeploymentTrackingError::Other(err.to_string()))?;
+      write_atomically(&self.path.join(file_name), json.as_bytes())?;
+    }
+    for file in self.files() {
+      let file_name = file.file_name().unwrap().to_string_lossy().to_string();
+      if !files.contains_key(&file_name) {
+        fs::remove_file(file)?;
+      }
+    }
+    Ok(())
+  }
+
+  fn files(&self) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(&self.path).into_iter().flatten()
+      .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+      .filter(|path| path.extension().is_some_and(|extension| extension == JSON_EXTENSION))
+      .collect();
+    files.sort();
+    files
+  }
+}
+
+// Data is written to a temporary file first, which then replaces the target,
+// so that it's never left half written.
+fn write_atomically(path: &Path, data: &[u8]) -> Result<(), DeploymentTrackingError> {
+  let file_name = path.file_name().unwrap().to_string_lossy();
+  let tmp_file_path = path.with_file_name(format!(".{}.{}.tmp", file_name, process::id()));
+
+  let written = fs::File::create(&tmp_file_path)
+    .and_then(|mut tmp_file| tmp_file.write_all(data).and_then(|_| tmp_file.sync_all()))
+    .and_then(|_| fs::rename(&tmp_file_path, path));
+
+  if let Err(err) = written {
+    let _ = fs::remove_file(&tmp_file_path);
+    return Err(DeploymentTrackingError::Other(err.to_string()));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{open, TrackingStoreKind};
+  use crate::deployment::tracker::{SmartContractTrackingData, SmartContractTrackingDataEntry, TrackingData};
+  use std::fs;
+  use web3::types::Address;
+
+  fn entry(name: &str, network: Option<&str>) -> SmartContractTrackingDataEntry {
+    SmartContractTrackingDataEntry {
+      name: name.to_string(),
+      address: Address::from_low_u64_be(1),
+      network: network.map(|network| network.to_string()),
+      deployed_at: Some(1),
+      commit: None,
+      verification: None,
+      metadata: None,
+      args: None,
+      encoded_args: None,
+      tx_hash: None,
+      block_number: None,
+      deployer: None,
+      gas_used: None,
+      compiler_version: None,
+      scanned_to: None,
+    }
+  }
+
+  fn tracking_data() -> TrackingData {
+    let mut tracking_data = TrackingData::new();
+    let mut development = SmartContractTrackingData::new();
+    development.insert("a".to_string(), entry("Token", Some("development")));
+    development.insert("b".to_string(), entry("Sale", Some("development")));
+    let mut unnamed = SmartContractTrackingData::new();
+    unnamed.insert("c".to_string(), entry("Token", None));
+    tracking_data.insert("0xdev".to_string(), development);
+    tracking_data.insert("0xunnamed".to_string(), unnamed);
+    tracking_data
+  }
+
+  #[test]
+  fn it_should_write_a_json_file_per_network() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let store = open(TrackingStoreKind::Json, tmp_dir.path()).unwrap();
+    store.create().unwrap();
+    store.write(&tracking_data()).unwrap();
+
+    let files: Vec<String> = store.files().iter().map(|file| file.file_name().unwrap().to_string_lossy().to_string()).collect();
+    assert_eq!(files, vec!["0xunnamed.json", "development.json"]);
+
+    let read = store.read().unwrap();
+    assert_eq!(read["0xdev"].len(), 2);
+    assert_eq!(read["0xdev"]["b"].name, "Sale");
+    assert_eq!(read["0xunnamed"]["c"].network, None);
+
+    let mut tracking_data = tracking_data();
+    tracking_data.remove("0xunnamed");
+    store.write(&tracking_data).unwrap();
+    assert_eq!(store.files().len(), 1);
+  }
+
+  #[test]
+  fn it_should_back_up_tracking_files_of_older_schema_versions_before_upgrading_them() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let (tracking_file, backup) = (tmp_dir.path().join("tracking.toml"), tmp_dir.path().join("tracking.toml.v1.bak"));
+    let v1 = "[0xdev.0x1]\nname = \"Token\"\naddress = \"0x0000000000000000000000000000000000000001\"\n";
+    fs::write(&tracking_file, v1).unwrap();
+
+    let store = open(TrackingStoreKind::Toml, tmp_dir.path()).unwrap();
+    let tracking_data = store.read().unwrap();
+    assert_eq!(tracking_data["0xdev"]["0x1"].name, "Token");
+    assert!(!backup.exists());
+
+    store.write(&tracking_data).unwrap();
+    assert_eq!(fs::read_to_string(&backup).unwrap(), v1);
+    assert!(fs::read_to_string(&tracking_file).unwrap().starts_with("schema_version = 2"));
+    assert_eq!(store.read().unwrap()["0xdev"]["0x1"].address, Address::from_low_u64_be(1));
+  }
+
+  #[test]
+  fn it_should_reject_unknown_stores() {
+    assert!("yaml".parse::<TrackingStoreKind>().is_err());
+    assert_eq!("sqlite".parse::<TrackingStoreKind>().unwrap(), TrackingStoreKind::Sqlite);
+  }
+
+  #[cfg(feature = "sqlite")]
+  #[test]
+  fn it_should_persist_tracking_data_in_sqlite() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let store = open(TrackingStoreKind::Sqlite, tmp_dir.path()).unwrap();
+    assert!(!store.exists());
+    store.create().unwrap();
+    store.write(&tracking_data()).unwrap();
+
+    let read = store.read().unwrap();
+    assert_eq!(read.len(), 2);
+    assert_eq!(read["0xdev"]["a"].network, Some("development".to_string()));
+
+    store.write(&TrackingData::new()).unwrap();
+    assert!(store.read().unwrap().is_empty());
+  }
+}
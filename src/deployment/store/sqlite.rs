@@ -0,0 +1,205 @@
+// Minimal bindings to the system's SQLite library, covering what's needed to
+// keep tracked deployments in a single table. Every entry is stored as JSON,
+// keyed by its chain and tracking key.
+
+use super::TrackingStore;
+use crate::deployment::error::DeploymentTrackingError;
+use crate::deployment::tracker::TrackingData;
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+const SQLITE_OK: c_int = 0;
+const SQLITE_ROW: c_int = 100;
+const SQLITE_DONE: c_int = 101;
+const SQLITE_OPEN_READWRITE: c_int = 0x0000_0002;
+const SQLITE_OPEN_CREATE: c_int = 0x0000_0004;
+const BUSY_TIMEOUT_MS: c_int = 5000;
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS deployments (chain TEXT NOT NULL, key TEXT NOT NULL, entry TEXT NOT NULL, PRIMARY KEY (chain, key))";
+const SELECT_ENTRIES: &str = "SELECT chain, key, entry FROM deployments ORDER BY chain, key";
+const INSERT_ENTRY: &str = "INSERT INTO deployments (chain, key, entry) VALUES (?1, ?2, ?3)";
+
+enum Sqlite3 {}
+enum Sqlite3Stmt {}
+
+#[link(name = "sqlite3")]
+extern "C" {
+  fn sqlite3_open_v2(filename: *const c_char, db: *mut *mut Sqlite3, flags: c_int, vfs: *const c_char) -> c_int;
+  fn sqlite3_close(db: *mut Sqlite3) -> c_int;
+  fn sqlite3_busy_timeout(db: *mut Sqlite3, ms: c_int) -> c_int;
+  fn sqlite3_errmsg(db: *mut Sqlite3) -> *const c_char;
+  fn sqlite3_exec(db: *mut Sqlite3, sql: *const c_char, callback: *const c_void, arg: *mut c_void, errmsg: *mut *mut c_char) -> c_int;
+  fn sqlite3_prepare_v2(db: *mut Sqlite3, sql: *const c_char, bytes: c_int, stmt: *mut *mut Sqlite3Stmt, tail: *mut *const c_char) -> c_int;
+  fn sqlite3_bind_text(stmt: *mut Sqlite3Stmt, index: c_int, text: *const c_char, bytes: c_int, destructor: Option<unsafe extern "C" fn(*mut c_void)>) -> c_int;
+  fn sqlite3_step(stmt: *mut Sqlite3Stmt) -> c_int;
+  fn sqlite3_reset(stmt: *mut Sqlite3Stmt) -> c_int;
+  fn sqlite3_column_text(stmt: *mut Sqlite3Stmt, column: c_int) -> *const c_char;
+  fn sqlite3_finalize(stmt: *mut Sqlite3Stmt) -> c_int;
+}
+
+pub struct SqliteStore {
+  path: PathBuf,
+}
+
+impl SqliteStore {
+  pub fn new(path: PathBuf) -> SqliteStore {
+    SqliteStore {
+      path,
+    }
+  }
+}
+
+impl TrackingStore for SqliteStore {
+  fn exists(&self) -> bool {
+    self.path.exists()
+  }
+
+  fn create(&self) -> Result<(), DeploymentTrackingError> {
+    Connection::open(&self.path, true)?.execute(CREATE_TABLE)
+  }
+
+  fn read(&self) -> Result<TrackingData, DeploymentTrackingError> {
+    let connection = Connection::open(&self.path, false)?;
+    let statement = connection.prepare(SELECT_ENTRIES)?;
+    let mut tracking_data = TrackingData::new();
+
+    while statement.step(&connection)? {
+      let (chain, key, entry) = (statement.column(0), statement.column(1), statement.column(2));
+      let entry = serde_json::from_str(&entry).map_err(|err| DeploymentTrackingError::Other(format!("Couldn't read tracking data of {}: {}", key, err)))?;
+      tracking_data.entry(chain).or_default().insert(key, entry);
+    }
+    Ok(tracking_data)
+  }
+
+  fn write(&self, tracking_data: &TrackingData) -> Result<(), DeploymentTrackingError> {
+    let connection = Connection::open(&self.path, false)?;
+    connection.execute("BEGIN IMMEDIATE")?;
+    let written = connection.execute("DELETE FROM deployments").and_then(|_| {
+      let statement = connection.prepare(INSERT_ENTRY)?;
+      for (chain, chain_tracking_data) in tracking_data {
+        for (key, entry) in chain_tracking_data {
+          let entry = serde_json::to_string(entry).map_err(|err| DeploymentTrackingError::Other(err.to_string()))?;
+          statement.insert(&connection, &[chain, key, &entry])?;
+        }
+      }
+      Ok(())
+    });
+
+    match written {
+      Ok(_) => connection.execute("COMMIT"),
+      Err(err) => {
+        let _ = connection.execute("ROLLBACK");
+        Err(err)
+      }
+    }
+  }
+
+  fn files(&self) -> Vec<PathBuf> {
+    vec![self.path.clone()].into_iter().filter(|path| path.exists()).collect()
+  }
+}
+
+struct Connection {
+  db: *mut Sqlite3,
+}
+
+impl Connection {
+  fn open(path: &Path, create: bool) -> Result<Connection, DeploymentTrackingError> {
+    let filename = c_string(&path.to_string_lossy())?;
+    let flags = if create { SQLITE_OPEN_READWRITE | SQLITE_OPEN_CREATE } else { SQLITE_OPEN_READWRITE };
+    let mut db = ptr::null_mut();
+    // The handle needs to be closed even if opening failed.
+    let connection = unsafe {
+      let code = sqlite3_open_v2(filename.as_ptr(), &mut db, flags, ptr::null());
+      let connection = Connection { db };
+      if code != SQLITE_OK {
+        return Err(connection.error());
+      }
+      sqlite3_busy_timeout(db, BUSY_TIMEOUT_MS);
+      connection
+    };
+    Ok(connection)
+  }
+
+  fn execute(&self, sql: &str) -> Result<(), DeploymentTrackingError> {
+    let sql = c_string(sql)?;
+    match unsafe { sqlite3_exec(self.db, sql.as_ptr(), ptr::null(), ptr::null_mut(), ptr::null_mut()) } {
+      SQLITE_OK => Ok(()),
+      _ => Err(self.error()),
+    }
+  }
+
+  fn prepare(&self, sql: &str) -> Result<Statement, DeploymentTrackingError> {
+    let sql = c_string(sql)?;
+    let mut stmt = ptr::null_mut();
+    match unsafe { sqlite3_prepare_v2(self.db, sql.as_ptr(), -1, &mut stmt, ptr::null_mut()) } {
+      SQLITE_OK => Ok(Statement { stmt }),
+      _ => Err(self.error()),
+    }
+  }
+
+  fn error(&self) -> DeploymentTrackingError {
+    let message = if self.db.is_null() {
+      "out of memory".to_string()
+    } else {
+      unsafe { CStr::from_ptr(sqlite3_errmsg(self.db)) }.to_string_lossy().to_string()
+    };
+    DeploymentTrackingError::Other(format!("Couldn't access tracking database: {}", message))
+  }
+}
+
+impl Drop for Connection {
+  fn drop(&mut self) {
+    unsafe { sqlite3_close(self.db) };
+  }
+}
+
+struct Statement {
+  stmt: *mut Sqlite3Stmt,
+}
+
+impl Statement {
+  // Returns whether a row has been produced.
+  fn step(&self, connection: &Connection) -> Result<bool, DeploymentTrackingError> {
+    match unsafe { sqlite3_step(self.stmt) } {
+      SQLITE_ROW => Ok(true),
+      SQLITE_DONE => Ok(false),
+      _ => Err(connection.error()),
+    }
+  }
+
+  fn column(&self, column: c_int) -> String {
+    let text = unsafe { sqlite3_column_text(self.stmt, column) };
+    if text.is_null() {
+      return String::new();
+    }
+    unsafe { CStr::from_ptr(text) }.to_string_lossy().to_string()
+  }
+
+  // Values are bound without being copied by SQLite, so they have to outlive
+  // the step.
+  fn insert(&self, connection: &Connection, values: &[&str]) -> Result<(), DeploymentTrackingError> {
+    let values = values.iter().map(|value| c_string(value)).collect::<Result<Vec<CString>, DeploymentTrackingError>>()?;
+    for (index, value) in values.iter().enumerate() {
+      if unsafe { sqlite3_bind_text(self.stmt, index as c_int + 1, value.as_ptr(), -1, None) } != SQLITE_OK {
+        return Err(connection.error());
+      }
+    }
+    let stepped = self.step(connection);
+    unsafe { sqlite3_reset(self.stmt) };
+    stepped.map(|_| ())
+  }
+}
+
+impl Drop for Statement {
+  fn drop(&mut self) {
+    unsafe { sqlite3_finalize(self.stmt) };
+  }
+}
+
+fn c_string(value: &str) -> Result<CString, DeploymentTrackingError> {
+  CString::new(value).map_err(|err| DeploymentTrackingError::Other(err.to_string()))
+}
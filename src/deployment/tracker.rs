@@ -1,14 +1,26 @@
 use crate::config;
 use crate::project_generator;
+use crate::utils;
 
 use super::error::DeploymentTrackingError;
+use super::store::{self, TrackingStore, TrackingStoreKind};
+use super::upgrades;
 
 use config::Config;
+use ethabi::{ParamType, Token};
+use ethabi::param_type::Reader;
+use rustc_hex::{FromHex, ToHex};
 use project_generator::VIBRANIUM_PROJECT_DIRECTORY;
+use std::fmt;
+use std::cell::RefCell;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
-use std::collections::HashMap;
+use std::process;
+use std::thread;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use sha3::{Digest, Sha3_256};
 use toml;
 use toml_query::insert::TomlValueInsertExt;
@@ -17,14 +29,153 @@ use toml_query::read::TomlValueReadExt;
 use web3::types::{H256, Address};
 
 pub const TRACKING_FILE: &str = "tracking.toml";
+const TRACKING_LOCK_FILE: &str = "tracking.toml.lock";
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+const TRACKING_KEY_CONTENT_HASH: &str = "content-hash";
+const TRACKING_KEY_NAME: &str = "name";
+const TRACKING_KEY_NAME_AND_ARGS: &str = "name-and-args";
+pub const COLLECTION_TAG: &str = "collection";
 
 pub type SmartContractTrackingData = HashMap<String, SmartContractTrackingDataEntry>;
-type TrackingData = HashMap<String, SmartContractTrackingData>;
+pub type TrackingData = HashMap<String, SmartContractTrackingData>;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SmartContractTrackingDataEntry {
   pub name: String,
   pub address: Address,
+  pub network: Option<String>,
+  pub deployed_at: Option<u64>,
+  pub commit: Option<String>,
+  pub verification: Option<String>,
+  pub metadata: Option<BTreeMap<String, String>>,
+  pub args: Option<Vec<SmartContractTrackingArg>>,
+  pub encoded_args: Option<String>,
+  pub tx_hash: Option<H256>,
+  pub block_number: Option<u64>,
+  pub deployer: Option<Address>,
+  pub gas_used: Option<u64>,
+  pub compiler_version: Option<String>,
+  // Last block whose logs have been scanned for members of a collection,
+  // keyed by the collection.
+  pub scanned_to: Option<BTreeMap<String, u64>>,
+}
+
+// What's known about the transaction that deployed a Smart Contract. Entries
+// tracked before it was recorded, or without sending a transaction, don't
+// have one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeploymentReceipt {
+  pub tx_hash: H256,
+  pub block_number: Option<u64>,
+  pub deployer: Address,
+  pub gas_used: Option<u64>,
+}
+
+// Constructor arguments as they've been sent, i.e. after resolving constants
+// and references to other Smart Contracts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SmartContractTrackingArg {
+  pub kind: String,
+  pub value: String,
+}
+
+impl SmartContractTrackingDataEntry {
+  // Formatted as YYYY-MM-DD HH:MM:SS UTC.
+  pub fn deployment_time(&self) -> Option<String> {
+    self.deployed_at.map(utils::format_timestamp)
+  }
+
+  pub fn has_tag(&self, key: &str, value: &str) -> bool {
+    self.metadata.as_ref().and_then(|metadata| metadata.get(key)).map(|v| v == value).unwrap_or(false)
+  }
+
+  // Decodes `encoded_args` using the tracked argument types. This is what was
+  // actually sent to the chain, so it's preferred over the tracked `args` values.
+  pub fn decoded_args(&self) -> Result<Option<Vec<SmartContractTrackingArg>>, DeploymentTrackingError> {
+    let (args, encoded_args) = match (&self.args, &self.encoded_args) {
+      (Some(args), Some(encoded_args)) => (args, encoded_args),
+      _ => return Ok(None),
+    };
+
+    let param_types = args.iter()
+      .map(|arg| Reader::read(&arg.kind))
+      .collect::<Result<Vec<ParamType>, ethabi::Error>>()
+      .map_err(|err| DeploymentTrackingError::Other(err.to_string()))?;
+
+    let bytes: Vec<u8> = encoded_args.trim_start_matches("0x").from_hex().map_err(|err| DeploymentTrackingError::Other(err.to_string()))?;
+    let tokens = ethabi::decode(&param_types, &bytes).map_err(|err| DeploymentTrackingError::Other(err.to_string()))?;
+
+    Ok(Some(args.iter().zip(tokens.iter()).map(|(arg, token)| SmartContractTrackingArg {
+      kind: arg.kind.to_owned(),
+      value: format_token(token),
+    }).collect()))
+  }
+}
+
+// The bytecode and constructor arguments a Smart Contract would be deployed
+// with, with libraries and references resolved to the deployments tracked on
+// the chain in question.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RekeyCandidate {
+  pub byte_code: String,
+  pub args: Vec<String>,
+  // ABI encoded constructor arguments, if they could be encoded.
+  pub encoded_args: Option<String>,
+  // Whether the code of the entry's address is the runtime bytecode of the
+  // Smart Contract, only known for the connected chain.
+  pub code_matches: bool,
+}
+
+// Entries that kept their key couldn't be proven to have been deployed with
+// what their new key would be derived from.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RekeyReport {
+  pub updated: usize,
+  pub kept: Vec<(String, Address)>,
+}
+
+// Determines which deployments are considered to be the same Smart Contract,
+// configured via `deployment.tracking_key`. With the default content hash,
+// changing the bytecode or constructor arguments results in a new deployment.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TrackingKeyStrategy {
+  #[default]
+  ContentHash,
+  Name,
+  NameAndArgs,
+}
+
+impl TrackingKeyStrategy {
+  pub fn key(&self, name: &str, byte_code: &str, args: &Vec<String>) -> String {
+    match self {
+      TrackingKeyStrategy::ContentHash => create_smart_contract_hash(name, byte_code, args),
+      TrackingKeyStrategy::Name => create_smart_contract_hash(name, "", &vec![]),
+      TrackingKeyStrategy::NameAndArgs => create_smart_contract_hash(name, "", args),
+    }
+  }
+}
+
+impl FromStr for TrackingKeyStrategy {
+  type Err = DeploymentTrackingError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      TRACKING_KEY_CONTENT_HASH => Ok(TrackingKeyStrategy::ContentHash),
+      TRACKING_KEY_NAME => Ok(TrackingKeyStrategy::Name),
+      TRACKING_KEY_NAME_AND_ARGS => Ok(TrackingKeyStrategy::NameAndArgs),
+      _ => Err(DeploymentTrackingError::InvalidKeyStrategy(s.to_string())),
+    }
+  }
+}
+
+impl fmt::Display for TrackingKeyStrategy {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      TrackingKeyStrategy::ContentHash => write!(f, "{}", TRACKING_KEY_CONTENT_HASH),
+      TrackingKeyStrategy::Name => write!(f, "{}", TRACKING_KEY_NAME),
+      TrackingKeyStrategy::NameAndArgs => write!(f, "{}", TRACKING_KEY_NAME_AND_ARGS),
+    }
+  }
 }
 
 pub struct DeploymentTracker<'a> {
@@ -39,21 +190,54 @@ impl<'a> DeploymentTracker<'a> {
   }
 
   pub fn database_exists(&self) -> bool {
-    self.get_tracking_file().exists()
+    self.store().map(|store| store.exists()).unwrap_or(false)
   }
 
   pub fn create_database(&self) -> Result<(), DeploymentTrackingError> {
-    let _ = fs::File::create(&self.get_tracking_file())?;
-    Ok(())
+    self.store()?.create()
   }
 
-  pub fn track(&self, block_hash: H256, name: String, byte_code: String, args: &Vec<String>, address: Address) -> Result<(), DeploymentTrackingError> {
+  // Files holding the tracking data of the configured store.
+  pub fn tracking_files(&self) -> Result<Vec<PathBuf>, DeploymentTrackingError> {
+    Ok(self.store()?.files())
+  }
+
+  pub fn track(&self, block_hash: H256, name: String, byte_code: String, args: &Vec<String>, address: Address, constructor_args: Option<(Vec<SmartContractTrackingArg>, Vec<Token>)>) -> Result<(), DeploymentTrackingError> {
+    self.track_deployment(block_hash, name, byte_code, args, address, constructor_args, None)
+  }
+
+  // The compiler version is read from the metadata solc appends to bytecode.
+  #[allow(clippy::too_many_arguments)]
+  pub fn track_deployment(&self, block_hash: H256, name: String, byte_code: String, args: &Vec<String>, address: Address, constructor_args: Option<(Vec<SmartContractTrackingArg>, Vec<Token>)>, receipt: Option<DeploymentReceipt>) -> Result<(), DeploymentTrackingError> {
+    let _lock = self.lock()?;
 
     let block_hash = create_block_hash(&block_hash);
-    let smart_contract_hash = create_smart_contract_hash(&name, &byte_code, &args);
+    let smart_contract_hash = self.key_strategy()?.key(&name, &byte_code, &args);
     let query = format!("{}.{}", &block_hash, &smart_contract_hash);
 
-    let smart_contract_tracking_data = SmartContractTrackingDataEntry { name, address, };
+    let (tracked_args, encoded_args) = match constructor_args {
+      Some((args, tokens)) => (Some(args), Some(format!("0x{}", ethabi::encode(&tokens).to_hex::<String>()))),
+      None => (None, None),
+    };
+
+    let compiler_version = utils::solc_version(&utils::decode_bytecode(&byte_code));
+    let smart_contract_tracking_data = SmartContractTrackingDataEntry {
+      name,
+      address,
+      network: self.config.network.clone(),
+      deployed_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).ok(),
+      commit: utils::git_commit(&self.config.project_path, &self.config.vibranium_dir_path),
+      verification: None,
+      metadata: None,
+      args: tracked_args,
+      encoded_args,
+      tx_hash: receipt.as_ref().map(|receipt| receipt.tx_hash),
+      block_number: receipt.as_ref().and_then(|receipt| receipt.block_number),
+      deployer: receipt.as_ref().map(|receipt| receipt.deployer),
+      gas_used: receipt.as_ref().and_then(|receipt| receipt.gas_used),
+      compiler_version,
+      scanned_to: None,
+    };
 
     let mut tracking_data = self.try_from_tracking_file()?;
     let chain_tracking_data = tracking_data.read(&block_hash)?;
@@ -69,7 +253,7 @@ impl<'a> DeploymentTracker<'a> {
 
   pub fn get_smart_contract_tracking_data(&self, block_hash: &H256, name: &str, byte_code: &str, args: &Vec<String>) -> Result<Option<SmartContractTrackingDataEntry>, DeploymentTrackingError> {
     let block_hash = create_block_hash(&block_hash);
-    let smart_contract_hash = create_smart_contract_hash(&name, &byte_code, &args);
+    let smart_contract_hash = self.key_strategy()?.key(&name, &byte_code, &args);
     let tracking_data = self.try_from_tracking_file()?;
     let contract_data = tracking_data.read(&format!("{}.{}", &block_hash, &smart_contract_hash))?;
 
@@ -95,32 +279,478 @@ impl<'a> DeploymentTracker<'a> {
     }
   }
 
+  // The most recent tracked deployment of the given Smart Contract on the
+  // chain, regardless of the bytecode and arguments it was deployed with.
+  pub fn get_latest_smart_contract_tracking_data(&self, block_hash: &H256, name: &str) -> Result<Option<SmartContractTrackingDataEntry>, DeploymentTrackingError> {
+    Ok(self.get_all_smart_contract_tracking_data(block_hash)?
+      .and_then(|tracking_data| tracking_data.into_values()
+        .filter(|entry| entry.name == name)
+        .max_by_key(|entry| entry.deployed_at.unwrap_or(0))))
+  }
+
+  // Instances created by other Smart Contracts, e.g. factories, are tracked as
+  // members of a collection, keyed by their address. Returns whether the
+  // instance wasn't tracked before.
+  pub fn track_instance(&self, block_hash: &H256, collection: &str, address: Address, mut metadata: BTreeMap<String, String>) -> Result<bool, DeploymentTrackingError> {
+    if !self.database_exists() {
+      self.create_database()?;
+    }
+    let _lock = self.lock()?;
+    let block_hash = create_block_hash(block_hash);
+    let mut tracking_data: TrackingData = self.try_from_tracking_file()?.try_into()?;
+    let chain_tracking_data = tracking_data.entry(block_hash).or_default();
+    let key = create_smart_contract_hash(collection, &format!("{:?}", address), &vec![]);
+
+    if chain_tracking_data.contains_key(&key) {
+      return Ok(false);
+    }
+
+    metadata.insert(COLLECTION_TAG.to_string(), collection.to_string());
+    chain_tracking_data.insert(key, SmartContractTrackingDataEntry {
+      name: collection.to_string(),
+      address,
+      network: self.config.network.clone(),
+      deployed_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).ok(),
+      commit: None,
+      verification: None,
+      metadata: Some(metadata),
+      args: None,
+      encoded_args: None,
+      tx_hash: None,
+      block_number: None,
+      deployer: None,
+      gas_used: None,
+      compiler_version: None,
+      scanned_to: None,
+    });
+
+    self.write(toml::Value::try_from(tracking_data)?)?;
+    Ok(true)
+  }
+
+  // Tags are merged into the metadata of every tracked deployment of the given
+  // Smart Contract on the chain. Returns the amount of updated entries.
+  pub fn tag(&self, block_hash: &H256, name: &str, tags: BTreeMap<String, String>) -> Result<usize, DeploymentTrackingError> {
+    let _lock = self.lock()?;
+    let block_hash = create_block_hash(block_hash);
+    let mut tracking_data: TrackingData = self.try_from_tracking_file()?.try_into()?;
+    let mut updated = 0;
+
+    if let Some(chain_tracking_data) = tracking_data.get_mut(&block_hash) {
+      for entry in chain_tracking_data.values_mut().filter(|entry| entry.name == name) {
+        entry.metadata.get_or_insert_with(BTreeMap::new).extend(tags.clone());
+        updated += 1;
+      }
+    }
+
+    if updated == 0 {
+      return Err(DeploymentTrackingError::SmartContractNotTracked(name.to_string()));
+    }
+
+    self.write(toml::Value::try_from(tracking_data)?)?;
+    Ok(updated)
+  }
+
+  // Tracking data of every chain, keyed by its (hashed) genesis block hash.
+  pub fn get_tracking_data_of_all_chains(&self) -> Result<HashMap<String, SmartContractTrackingData>, DeploymentTrackingError> {
+    if !self.database_exists() {
+      return Ok(HashMap::new());
+    }
+    Ok(self.try_from_tracking_file()?.try_into()?)
+  }
+
+  // Records the outcome of the last verification of the deployment at the
+  // given address. Returns the amount of updated entries.
+  pub fn record_verification(&self, block_hash: &H256, address: &Address, status: &str) -> Result<usize, DeploymentTrackingError> {
+    let _lock = self.lock()?;
+    let block_hash = create_block_hash(block_hash);
+    let mut tracking_data: TrackingData = self.try_from_tracking_file()?.try_into()?;
+    let mut updated = 0;
+
+    if let Some(chain_tracking_data) = tracking_data.get_mut(&block_hash) {
+      for entry in chain_tracking_data.values_mut().filter(|entry| &entry.address == address) {
+        entry.verification = Some(status.to_string());
+        updated += 1;
+      }
+    }
+
+    if updated > 0 {
+      self.write(toml::Value::try_from(tracking_data)?)?;
+    }
+    Ok(updated)
+  }
+
+  // Records up to which block the logs of the deployment at the given address
+  // have been scanned for members of the collection. Returns the amount of
+  // updated entries.
+  pub fn record_scan(&self, block_hash: &H256, address: &Address, collection: &str, block: u64) -> Result<usize, DeploymentTrackingError> {
+    let _lock = self.lock()?;
+    let block_hash = create_block_hash(block_hash);
+    let mut tracking_data: TrackingData = self.try_from_tracking_file()?.try_into()?;
+    let mut updated = 0;
+
+    if let Some(chain_tracking_data) = tracking_data.get_mut(&block_hash) {
+      for entry in chain_tracking_data.values_mut().filter(|entry| &entry.address == address) {
+        entry.scanned_to.get_or_insert_with(BTreeMap::new).insert(collection.to_string(), block);
+        updated += 1;
+      }
+    }
+
+    if updated > 0 {
+      self.write(toml::Value::try_from(tracking_data)?)?;
+    }
+    Ok(updated)
+  }
+
+  // Returns the amount of removed entries.
+  pub fn remove_chain(&self, block_hash: &H256) -> Result<usize, DeploymentTrackingError> {
+    if !self.database_exists() {
+      return Ok(0);
+    }
+    let _lock = self.lock()?;
+    let mut tracking_data: TrackingData = self.try_from_tracking_file()?.try_into()?;
+    match tracking_data.remove(&create_block_hash(block_hash)) {
+      None => Ok(0),
+      Some(chain_tracking_data) => {
+        self.write(toml::Value::try_from(tracking_data)?)?;
+        Ok(chain_tracking_data.len())
+      }
+    }
+  }
+
+  // Removes every tracked deployment of the given Smart Contract on the chain.
+  // Returns the amount of removed entries.
+  pub fn remove(&self, block_hash: &H256, name: &str) -> Result<usize, DeploymentTrackingError> {
+    let _lock = self.lock()?;
+    let block_hash = create_block_hash(block_hash);
+    let mut tracking_data: TrackingData = self.try_from_tracking_file()?.try_into()?;
+    let mut removed = 0;
+
+    if let Some(chain_tracking_data) = tracking_data.get_mut(&block_hash) {
+      let size = chain_tracking_data.len();
+      chain_tracking_data.retain(|_key, entry| entry.name != name);
+      removed = size - chain_tracking_data.len();
+      if chain_tracking_data.is_empty() {
+        tracking_data.remove(&block_hash);
+      }
+    }
+
+    if removed == 0 {
+      return Err(DeploymentTrackingError::SmartContractNotTracked(name.to_string()));
+    }
+
+    self.write(toml::Value::try_from(tracking_data)?)?;
+    Ok(removed)
+  }
+
+  // Removes the tracked deployments with the given keys from the chain.
+  // Returns the amount of removed entries.
+  pub fn remove_entries(&self, block_hash: &H256, keys: &[String]) -> Result<usize, DeploymentTrackingError> {
+    if !self.database_exists() || keys.is_empty() {
+      return Ok(0);
+    }
+    let _lock = self.lock()?;
+    let block_hash = create_block_hash(block_hash);
+    let mut tracking_data: TrackingData = self.try_from_tracking_file()?.try_into()?;
+    let mut removed = 0;
+
+    if let Some(chain_tracking_data) = tracking_data.get_mut(&block_hash) {
+      let size = chain_tracking_data.len();
+      chain_tracking_data.retain(|key, _entry| !keys.contains(key));
+      removed = size - chain_tracking_data.len();
+      if chain_tracking_data.is_empty() {
+        tracking_data.remove(&block_hash);
+      }
+    }
+
+    if removed > 0 {
+      self.write(toml::Value::try_from(tracking_data)?)?;
+    }
+    Ok(removed)
+  }
+
+  // Removes the tracked deployments made to the given network on any chain,
+  // so that they can be pruned without connecting to the network. Returns the
+  // amount of removed entries.
+  pub fn clear_network(&self, network: &str) -> Result<usize, DeploymentTrackingError> {
+    if !self.database_exists() {
+      return Ok(0);
+    }
+    let _lock = self.lock()?;
+    let mut tracking_data: TrackingData = self.try_from_tracking_file()?.try_into()?;
+    let mut removed = 0;
+
+    for chain_tracking_data in tracking_data.values_mut() {
+      let size = chain_tracking_data.len();
+      chain_tracking_data.retain(|_key, entry| entry.network.as_deref() != Some(network));
+      removed += size - chain_tracking_data.len();
+    }
+    tracking_data.retain(|_block_hash, chain_tracking_data| !chain_tracking_data.is_empty());
+
+    if removed > 0 {
+      self.write(toml::Value::try_from(tracking_data)?)?;
+    }
+    Ok(removed)
+  }
+
+  pub fn store_kind(&self) -> Result<TrackingStoreKind, DeploymentTrackingError> {
+    self.config.read().ok()
+      .and_then(|project_config| project_config.deployment)
+      .and_then(|deployment_config| deployment_config.store)
+      .map(|kind| kind.parse())
+      .unwrap_or_else(|| Ok(TrackingStoreKind::default()))
+  }
+
+  pub fn key_strategy(&self) -> Result<TrackingKeyStrategy, DeploymentTrackingError> {
+    self.config.read().ok()
+      .and_then(|project_config| project_config.deployment)
+      .and_then(|deployment_config| deployment_config.tracking_key)
+      .map(|strategy| strategy.parse())
+      .unwrap_or_else(|| Ok(TrackingKeyStrategy::default()))
+  }
+
+  // Moves tracked deployments of all chains to the keys the given strategy
+  // derives for them. `resolve` provides what a Smart Contract would be
+  // deployed with on the given chain. That's only used for the key if the
+  // entry's previous key, its tracked constructor arguments or its deployed
+  // code prove that it was deployed with the same bytecode and arguments.
+  // Other entries keep their key, as do all but the most recent of multiple
+  // deployments ending up with the same key.
+  pub fn rekey<F>(&self, strategy: TrackingKeyStrategy, resolve: F) -> Result<RekeyReport, DeploymentTrackingError>
+    where F: Fn(&str, &SmartContractTrackingData, &SmartContractTrackingDataEntry) -> Option<RekeyCandidate>
+  {
+    let _lock = self.lock()?;
+    let tracking_data: TrackingData = self.try_from_tracking_file()?.try_into()?;
+    let mut rekeyed_data = TrackingData::new();
+    let mut report = RekeyReport::default();
+
+    for (block_hash, chain_tracking_data) in tracking_data {
+      let mut new_keys: HashMap<String, String> = HashMap::new();
+      for (key, entry) in &chain_tracking_data {
+        // Members of collections aren't deployed by us and keep their key, as
+        // do proxies, which are told apart by name only.
+        if entry.metadata.as_ref().is_some_and(|metadata| metadata.contains_key(COLLECTION_TAG) || metadata.contains_key(upgrades::METADATA_PROXY_KIND)) {
+          continue;
+        }
+        let candidate = resolve(&block_hash, &chain_tracking_data, entry);
+        match proven_key(strategy, key, entry, candidate.as_ref()) {
+          Some(new_key) => { new_keys.insert(key.to_owned(), new_key); },
+          None => report.kept.push((entry.name.to_owned(), entry.address)),
+        }
+      }
+
+      // The most recent deployment wins if multiple ones end up with the same key.
+      let mut winners: HashMap<&String, &String> = HashMap::new();
+      let rank = |key: &String| (chain_tracking_data[key].deployed_at.unwrap_or(0), key.to_owned());
+      for (key, new_key) in &new_keys {
+        if winners.get(new_key).is_none_or(|winner| rank(winner) < rank(key)) {
+          winners.insert(new_key, key);
+        }
+      }
+
+      let mut rekeyed_chain_data = SmartContractTrackingData::new();
+      let mut kept_keys = vec![];
+      for (key, entry) in chain_tracking_data {
+        match new_keys.get(&key) {
+          Some(new_key) if winners.get(new_key) == Some(&&key) => {
+            if new_key != &key {
+              report.updated += 1;
+            }
+            rekeyed_chain_data.insert(new_key.to_owned(), entry);
+          },
+          Some(_new_key) => {
+            report.kept.push((entry.name.to_owned(), entry.address));
+            kept_keys.push((key, entry));
+          },
+          None => kept_keys.push((key, entry)),
+        }
+      }
+      for (key, entry) in kept_keys {
+        if rekeyed_chain_data.contains_key(&key) {
+          return Err(DeploymentTrackingError::RekeyConflict(entry.name));
+        }
+        rekeyed_chain_data.insert(key, entry);
+      }
+      rekeyed_data.insert(block_hash, rekeyed_chain_data);
+    }
+
+    report.kept.sort();
+    self.write(toml::Value::try_from(rekeyed_data)?)?;
+    Ok(report)
+  }
+
   fn write(&self, toml: toml::Value) -> Result<(), DeploymentTrackingError> {
-    let tracking_data = toml::to_string(&toml)?;
-    let mut tracking_file= fs::File::create(&self.get_tracking_file())?;
-    tracking_file.write_all(tracking_data.as_bytes()).map_err(|err| DeploymentTrackingError::Other(err.to_string()))
+    let tracking_data: TrackingData = toml.try_into()?;
+    self.store()?.write(&tracking_data)
   }
 
-  fn try_from_tracking_file(&self) -> Result<toml::Value, DeploymentTrackingError> {
+  // Held while tracking data is read, modified and written, so that parallel
+  // vibranium processes don't drop each other's updates.
+  fn lock(&self) -> Result<TrackingLock, DeploymentTrackingError> {
     if !self.database_exists() {
       return Err(DeploymentTrackingError::DatabaseNotFound);
     }
-    let tracking_data = fs::read_to_string(self.get_tracking_file())?;
-    let toml: TrackingData = toml::from_str(&tracking_data)?;
-    toml::Value::try_from(toml).map_err(DeploymentTrackingError::Serialization)
+    TrackingLock::acquire(self.get_vibranium_dir().join(TRACKING_LOCK_FILE))
+  }
+
+  fn try_from_tracking_file(&self) -> Result<toml::Value, DeploymentTrackingError> {
+    let store = self.store()?;
+    if !store.exists() {
+      return Err(DeploymentTrackingError::DatabaseNotFound);
+    }
+    toml::Value::try_from(store.read()?).map_err(DeploymentTrackingError::Serialization)
+  }
+
+  fn store(&self) -> Result<Box<dyn TrackingStore>, DeploymentTrackingError> {
+    store::open(self.store_kind()?, &self.get_vibranium_dir())
+  }
+
+  fn get_vibranium_dir(&self) -> PathBuf {
+    self.config.project_path.join(VIBRANIUM_PROJECT_DIRECTORY)
+  }
+}
+
+// Advisory lock on the tracking data. The lock is held by the OS on the lock
+// file, so it's released when the owning process exits, even if it crashes.
+// The file holds the id of the owning process and is removed when unlocking.
+// Processes that have been waiting on a file that has been removed meanwhile
+// lock the current one instead.
+struct TrackingLock {
+  path: PathBuf,
+  _file: fs::File,
+}
+
+thread_local! {
+  // Locking the tracking data again on the same thread would wait for itself.
+  static HELD_LOCKS: RefCell<HashSet<PathBuf>> = RefCell::new(HashSet::new());
+}
+
+impl TrackingLock {
+  fn acquire(path: PathBuf) -> Result<TrackingLock, DeploymentTrackingError> {
+    if HELD_LOCKS.with(|held| held.borrow().contains(&path)) {
+      return Err(DeploymentTrackingError::Other(format!("Tracking data is already locked by this process ({})", path.display())));
+    }
+    let started = Instant::now();
+    loop {
+      let mut file = fs::OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path)?;
+      match file.try_lock() {
+        Ok(()) => {
+          if !is_current_file(&file, &path) {
+            continue;
+          }
+          file.set_len(0)?;
+          write!(file, "{}", process::id())?;
+          HELD_LOCKS.with(|held| held.borrow_mut().insert(path.clone()));
+          return Ok(TrackingLock { path, _file: file });
+        },
+        Err(fs::TryLockError::WouldBlock) => {
+          if started.elapsed() > LOCK_TIMEOUT {
+            return Err(DeploymentTrackingError::Locked(path));
+          }
+          thread::sleep(LOCK_RETRY_INTERVAL);
+        },
+        Err(fs::TryLockError::Error(err)) => return Err(err.into()),
+      }
+    }
+  }
+}
+
+#[cfg(unix)]
+fn is_current_file(file: &fs::File, path: &Path) -> bool {
+  use std::os::unix::fs::MetadataExt;
+  match (file.metadata(), fs::metadata(path)) {
+    (Ok(locked), Ok(current)) => locked.dev() == current.dev() && locked.ino() == current.ino(),
+    _ => false,
+  }
+}
+
+// Lock files aren't removed on other platforms, so the locked file is always
+// the current one.
+#[cfg(not(unix))]
+fn is_current_file(_file: &fs::File, _path: &Path) -> bool {
+  true
+}
+
+impl Drop for TrackingLock {
+  // The file is removed while it's still locked, it's unlocked once closed.
+  fn drop(&mut self) {
+    if cfg!(unix) {
+      let _ = fs::remove_file(&self.path);
+    }
+    HELD_LOCKS.with(|held| held.borrow_mut().remove(&self.path));
+  }
+}
+
+// Tracked deployments of all chains grouped by the network they've been made
+// to. Deployments without a network have been made to whatever chain the
+// project was connected to, so they're told apart by chain.
+pub fn group_by_network(chains: &HashMap<String, SmartContractTrackingData>) -> BTreeMap<String, Vec<&SmartContractTrackingDataEntry>> {
+  let mut groups: BTreeMap<String, Vec<&SmartContractTrackingDataEntry>> = BTreeMap::new();
+  for (chain, entries) in chains {
+    for entry in entries.values() {
+      let group = match &entry.network {
+        Some(network) => network.to_owned(),
+        None => format!("Chain {}", &chain[..chain.len().min(10)]),
+      };
+      groups.entry(group).or_default().push(entry);
+    }
+  }
+  groups
+}
+
+pub fn format_token(token: &Token) -> String {
+  match token {
+    Token::Address(address) => format!("{:?}", address),
+    Token::Bytes(bytes) | Token::FixedBytes(bytes) => format!("0x{}", bytes.to_hex::<String>()),
+    Token::Uint(value) => value.to_string(),
+    Token::Int(value) => {
+      if value.bit(255) {
+        format!("-{}", (!*value).overflowing_add(1.into()).0)
+      } else {
+        value.to_string()
+      }
+    },
+    Token::Bool(value) => value.to_string(),
+    Token::String(value) => value.to_owned(),
+    Token::Array(tokens) | Token::FixedArray(tokens) => {
+      format!("[{}]", tokens.iter().map(format_token).collect::<Vec<String>>().join(", "))
+    },
+  }
+}
+
+pub fn parse_tag(tag: &str) -> Result<(String, String), DeploymentTrackingError> {
+  let mut parts = tag.splitn(2, '=');
+  match (parts.next(), parts.next()) {
+    (Some(key), Some(value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.trim().to_string())),
+    _ => Err(DeploymentTrackingError::InvalidTag(tag.to_string())),
+  }
+}
+
+// The key of an entry under the given strategy, if what it has been deployed
+// with is known well enough to derive it.
+fn proven_key(strategy: TrackingKeyStrategy, key: &str, entry: &SmartContractTrackingDataEntry, candidate: Option<&RekeyCandidate>) -> Option<String> {
+  if strategy == TrackingKeyStrategy::Name {
+    return Some(strategy.key(&entry.name, "", &vec![]));
   }
+  let candidate = candidate?;
+  let has_key = |strategy: TrackingKeyStrategy| strategy.key(&entry.name, &candidate.byte_code, &candidate.args) == key;
+  let same_args = has_key(TrackingKeyStrategy::NameAndArgs)
+    || has_key(TrackingKeyStrategy::ContentHash)
+    || (entry.encoded_args.is_some() && entry.encoded_args == candidate.encoded_args);
+  let same_code = has_key(TrackingKeyStrategy::ContentHash) || candidate.code_matches;
 
-  fn get_tracking_file(&self) -> PathBuf {
-    let vibranium_dir = self.config.project_path.join(VIBRANIUM_PROJECT_DIRECTORY);
-    vibranium_dir.join(TRACKING_FILE)
+  match strategy {
+    TrackingKeyStrategy::NameAndArgs if same_args => Some(strategy.key(&entry.name, "", &candidate.args)),
+    TrackingKeyStrategy::ContentHash if same_args && same_code => Some(strategy.key(&entry.name, &candidate.byte_code, &candidate.args)),
+    _ => None,
   }
 }
 
-fn create_block_hash(block_hash: &H256) -> String {
+pub(crate) fn create_block_hash(block_hash: &H256) -> String {
   format!("0x{:x}", Sha3_256::digest(block_hash.as_bytes()))
 }
 
-fn create_smart_contract_hash(name: &str, byte_code: &str, args: &Vec<String>) -> String {
+pub(crate) fn create_smart_contract_hash(name: &str, byte_code: &str, args: &Vec<String>) -> String {
   let mut hasher = Sha3_256::new();
 
   hasher.input(name.as_bytes());
@@ -129,3 +759,541 @@ fn create_smart_contract_hash(name: &str, byte_code: &str, args: &Vec<String>) -
 
   format!("0x{:x}", hasher.result())
 }
+
+#[cfg(test)]
+mod tests {
+
+  mod tag {
+
+    use super::super::{DeploymentTracker, parse_tag};
+    use crate::config::Config;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+    use web3::types::{Address, H256};
+
+    fn tags(tags: &[(&str, &str)]) -> BTreeMap<String, String> {
+      tags.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn it_should_parse_tags() {
+      assert_eq!(parse_tag("release=v2.1").unwrap(), ("release".to_string(), "v2.1".to_string()));
+      assert_eq!(parse_tag("note=a=b").unwrap(), ("note".to_string(), "a=b".to_string()));
+      assert!(parse_tag("release").is_err());
+      assert!(parse_tag("=v2.1").is_err());
+    }
+
+    #[test]
+    fn it_should_merge_tags_into_tracked_smart_contracts() {
+      let tmp_dir = crate::testing::project_dir();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+      let address = Address::from_str("00000000000000000000000000000000000000c0").unwrap();
+
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6080".to_string(), &vec![], address, None).unwrap();
+      tracker.track(block_hash, "Other".to_string(), "6080".to_string(), &vec![], address, None).unwrap();
+
+      assert_eq!(tracker.tag(&block_hash, "Token", tags(&[("release", "v2.0"), ("audited-by", "firmX")])).unwrap(), 1);
+      assert_eq!(tracker.tag(&block_hash, "Token", tags(&[("release", "v2.1")])).unwrap(), 1);
+
+      let entry = tracker.get_smart_contract_tracking_data(&block_hash, "Token", "6080", &vec![]).unwrap().unwrap();
+      assert_eq!(entry.metadata.unwrap(), tags(&[("audited-by", "firmX"), ("release", "v2.1")]));
+
+      let other = tracker.get_smart_contract_tracking_data(&block_hash, "Other", "6080", &vec![]).unwrap().unwrap();
+      assert!(!other.has_tag("release", "v2.1"));
+    }
+
+    #[test]
+    fn it_should_fail_when_tagging_untracked_smart_contracts() {
+      let tmp_dir = crate::testing::project_dir();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      tracker.create_database().unwrap();
+
+      assert!(tracker.tag(&H256::from_low_u64_be(1), "Token", tags(&[("release", "v2.1")])).is_err());
+    }
+  }
+
+  mod network {
+
+    use super::super::DeploymentTracker;
+    use crate::config::Config;
+    use std::fs;
+    use web3::types::{Address, H256};
+
+    #[test]
+    fn it_should_record_the_selected_network_along_with_the_chain() {
+      let tmp_dir = crate::testing::project_dir();
+      let mut config = Config::new(tmp_dir.path().to_path_buf());
+      config.network = Some("staging".to_string());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+
+      let entry = tracker.get_smart_contract_tracking_data(&block_hash, "Token", "6080", &vec![]).unwrap().unwrap();
+      assert_eq!(entry.network, Some("staging".to_string()));
+      assert!(fs::read_to_string(tmp_dir.path().join(".vibranium").join("tracking.toml")).unwrap().contains("network = \"staging\""));
+    }
+  }
+
+  mod receipt {
+
+    use super::super::{DeploymentReceipt, DeploymentTracker};
+    use crate::config::Config;
+    use std::fs;
+    use web3::types::{Address, H256};
+
+    // solc 0.8.24 metadata: {"ipfs": <34 bytes>, "solc": 0x000818}
+    const BYTECODE: &str = "6080a264697066735822122000000000000000000000000000000000000000000000000000000000000000000064736f6c63430008180033";
+
+    #[test]
+    fn it_should_track_receipts_and_compiler_versions() {
+      let tmp_dir = crate::testing::project_dir();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+      let receipt = DeploymentReceipt {
+        tx_hash: H256::from_low_u64_be(2),
+        block_number: Some(12),
+        deployer: Address::from_low_u64_be(3),
+        gas_used: Some(21_000),
+      };
+
+      tracker.create_database().unwrap();
+      tracker.track_deployment(block_hash, "Token".to_string(), BYTECODE.to_string(), &vec![], Address::from_low_u64_be(1), None, Some(receipt.clone())).unwrap();
+
+      let entry = tracker.get_smart_contract_tracking_data(&block_hash, "Token", BYTECODE, &vec![]).unwrap().unwrap();
+      assert_eq!(entry.tx_hash, Some(receipt.tx_hash));
+      assert_eq!(entry.block_number, Some(12));
+      assert_eq!(entry.deployer, Some(receipt.deployer));
+      assert_eq!(entry.gas_used, Some(21_000));
+      assert_eq!(entry.compiler_version, Some("0.8.24".to_string()));
+    }
+
+    #[test]
+    fn it_should_read_tracking_files_without_receipts() {
+      let tmp_dir = crate::testing::project_dir();
+      fs::write(tmp_dir.path().join(".vibranium").join("tracking.toml"), "
+        [0xabcd.0x1234]
+        name = \"Token\"
+        address = \"0x00000000000000000000000000000000000000c0\"
+      ").unwrap();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+
+      let chains = tracker.get_tracking_data_of_all_chains().unwrap();
+      let entry = chains.get("0xabcd").unwrap().get("0x1234").unwrap();
+      assert_eq!(entry.name, "Token");
+      assert!(entry.tx_hash.is_none() && entry.deployer.is_none() && entry.compiler_version.is_none());
+    }
+  }
+
+  mod remove_chain {
+
+    use super::super::DeploymentTracker;
+    use crate::config::Config;
+    use web3::types::{Address, H256};
+
+    #[test]
+    fn it_should_only_remove_tracking_data_of_the_given_chain() {
+      let tmp_dir = crate::testing::project_dir();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let (first_chain, second_chain) = (H256::from_low_u64_be(1), H256::from_low_u64_be(2));
+      tracker.create_database().unwrap();
+      tracker.track(first_chain, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+      tracker.track(first_chain, "Sale".to_string(), "6081".to_string(), &vec![], Address::from_low_u64_be(2), None).unwrap();
+      tracker.track(second_chain, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(3), None).unwrap();
+
+      assert_eq!(tracker.remove_chain(&first_chain).unwrap(), 2);
+      assert_eq!(tracker.remove_chain(&first_chain).unwrap(), 0);
+      assert!(tracker.get_all_smart_contract_tracking_data(&first_chain).unwrap().is_none());
+      assert_eq!(tracker.get_all_smart_contract_tracking_data(&second_chain).unwrap().unwrap().len(), 1);
+    }
+  }
+
+  mod remove {
+
+    use super::super::{DeploymentTracker, group_by_network};
+    use crate::config::Config;
+    use web3::types::{Address, H256};
+
+    #[test]
+    fn it_should_remove_tracked_deployments_of_a_smart_contract() {
+      let tmp_dir = crate::testing::project_dir();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6081".to_string(), &vec![], Address::from_low_u64_be(2), None).unwrap();
+      tracker.track(block_hash, "Sale".to_string(), "6082".to_string(), &vec![], Address::from_low_u64_be(3), None).unwrap();
+
+      assert_eq!(tracker.remove(&block_hash, "Token").unwrap(), 2);
+      assert!(tracker.remove(&block_hash, "Token").is_err());
+      let remaining = tracker.get_all_smart_contract_tracking_data(&block_hash).unwrap().unwrap();
+      assert_eq!(remaining.values().map(|entry| entry.name.as_str()).collect::<Vec<&str>>(), vec!["Sale"]);
+    }
+
+    #[test]
+    fn it_should_remove_tracked_deployments_by_key() {
+      let tmp_dir = crate::testing::project_dir();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+      tracker.track(block_hash, "Sale".to_string(), "6082".to_string(), &vec![], Address::from_low_u64_be(3), None).unwrap();
+      let keys: Vec<String> = tracker.get_all_smart_contract_tracking_data(&block_hash).unwrap().unwrap()
+        .into_iter()
+        .filter(|(_key, entry)| entry.name == "Token")
+        .map(|(key, _entry)| key)
+        .collect();
+
+      assert_eq!(tracker.remove_entries(&block_hash, &keys).unwrap(), 1);
+      assert_eq!(tracker.remove_entries(&block_hash, &keys).unwrap(), 0);
+      let remaining = tracker.get_all_smart_contract_tracking_data(&block_hash).unwrap().unwrap();
+      assert_eq!(remaining.values().map(|entry| entry.name.as_str()).collect::<Vec<&str>>(), vec!["Sale"]);
+    }
+
+    #[test]
+    fn it_should_clear_and_group_tracked_deployments_by_network() {
+      let tmp_dir = crate::testing::project_dir();
+      let mut config = Config::new(tmp_dir.path().to_path_buf());
+      let (first_chain, second_chain) = (H256::from_low_u64_be(1), H256::from_low_u64_be(2));
+      DeploymentTracker::new(&config).create_database().unwrap();
+      DeploymentTracker::new(&config).track(first_chain, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+      config.network = Some("staging".to_string());
+      DeploymentTracker::new(&config).track(second_chain, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(2), None).unwrap();
+      DeploymentTracker::new(&config).track(second_chain, "Sale".to_string(), "6081".to_string(), &vec![], Address::from_low_u64_be(3), None).unwrap();
+
+      let tracker = DeploymentTracker::new(&config);
+      let chains = tracker.get_tracking_data_of_all_chains().unwrap();
+      let groups = group_by_network(&chains);
+      assert_eq!(groups.len(), 2);
+      assert_eq!(groups["staging"].len(), 2);
+      assert!(groups.keys().any(|group| group.starts_with("Chain 0x")));
+
+      assert_eq!(tracker.clear_network("production").unwrap(), 0);
+      assert_eq!(tracker.clear_network("staging").unwrap(), 2);
+      assert!(tracker.get_all_smart_contract_tracking_data(&second_chain).unwrap().is_none());
+      assert_eq!(tracker.get_all_smart_contract_tracking_data(&first_chain).unwrap().unwrap().len(), 1);
+    }
+  }
+
+  mod lock {
+
+    use super::super::DeploymentTracker;
+    use crate::config::Config;
+    use std::fs;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use web3::types::{Address, H256};
+
+    #[test]
+    fn it_should_not_lose_updates_of_concurrent_writers() {
+      let tmp_dir = crate::testing::project_dir();
+      let project_path = tmp_dir.path().to_path_buf();
+      DeploymentTracker::new(&Config::new(project_path.clone())).create_database().unwrap();
+
+      let writers: Vec<_> = (0..8).map(|i| {
+        let project_path = project_path.clone();
+        thread::spawn(move || {
+          let config = Config::new(project_path);
+          DeploymentTracker::new(&config).track(H256::from_low_u64_be(1), format!("Token{}", i), "6080".to_string(), &vec![], Address::from_low_u64_be(i), None).unwrap();
+        })
+      }).collect();
+      for writer in writers {
+        writer.join().unwrap();
+      }
+
+      let config = Config::new(project_path);
+      let tracker = DeploymentTracker::new(&config);
+      assert_eq!(tracker.get_all_smart_contract_tracking_data(&H256::from_low_u64_be(1)).unwrap().unwrap().len(), 8);
+      let leftovers: Vec<String> = fs::read_dir(tmp_dir.path().join(".vibranium")).unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|file_name| file_name.ends_with(".lock") || file_name.ends_with(".tmp"))
+        .collect();
+      assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn it_should_take_over_lock_files_that_are_left_behind() {
+      let tmp_dir = crate::testing::project_dir();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      tracker.create_database().unwrap();
+
+      // Left behind by a process that is gone, or that crashed before it
+      // wrote its id.
+      for content in &["4194303", ""] {
+        fs::write(tmp_dir.path().join(".vibranium").join("tracking.toml.lock"), content).unwrap();
+        tracker.track(H256::from_low_u64_be(1), "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+        assert!(!tmp_dir.path().join(".vibranium").join("tracking.toml.lock").exists());
+      }
+    }
+
+    #[test]
+    fn it_should_refuse_to_lock_tracking_data_twice_on_the_same_thread() {
+      let tmp_dir = crate::testing::project_dir();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      tracker.create_database().unwrap();
+
+      let _lock = tracker.lock().unwrap();
+      let started = Instant::now();
+      assert!(tracker.track(H256::from_low_u64_be(1), "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).is_err());
+      assert!(started.elapsed() < Duration::from_secs(1));
+    }
+  }
+
+  mod store {
+
+    use super::super::DeploymentTracker;
+    use crate::config::Config;
+    use web3::types::{Address, H256};
+
+    #[test]
+    fn it_should_track_deployments_in_the_configured_store() {
+      let tmp_dir = crate::testing::project_with_config("
+        [sources]
+          artifacts = \"artifacts\"
+          smart_contracts = [\"contracts/*.sol\"]
+        [deployment]
+          store = \"json\"
+          smart_contracts = []
+        [networks.local]
+          url = \"http://localhost:8545\"
+      ");
+      let mut config = Config::new(tmp_dir.path().to_path_buf());
+      config.network = Some("local".to_string());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+
+      assert!(!tmp_dir.path().join(".vibranium").join("tracking.toml").exists());
+      assert_eq!(tracker.tracking_files().unwrap(), vec![tmp_dir.path().join(".vibranium").join("tracking").join("local.json")]);
+      assert_eq!(tracker.get_latest_smart_contract_tracking_data(&block_hash, "Token").unwrap().unwrap().address, Address::from_low_u64_be(1));
+    }
+  }
+
+  mod constructor_args {
+
+    use super::super::{DeploymentTracker, SmartContractTrackingArg, format_token};
+    use crate::config::Config;
+    use ethabi::Token;
+    use std::str::FromStr;
+    use web3::types::{Address, H256, U256};
+
+    fn arg(kind: &str, value: &str) -> SmartContractTrackingArg {
+      SmartContractTrackingArg { kind: kind.to_string(), value: value.to_string() }
+    }
+
+    #[test]
+    fn it_should_format_tokens() {
+      let address = Address::from_str("00000000000000000000000000000000000000c0").unwrap();
+      assert_eq!(format_token(&Token::Address(address)), "0x00000000000000000000000000000000000000c0");
+      assert_eq!(format_token(&Token::Uint(U256::from(200))), "200");
+      assert_eq!(format_token(&Token::Int(!U256::from(4))), "-5");
+      assert_eq!(format_token(&Token::Bytes(vec![0xab, 0xcd])), "0xabcd");
+      assert_eq!(format_token(&Token::Array(vec![Token::Bool(true), Token::String("a".to_string())])), "[true, a]");
+    }
+
+    #[test]
+    fn it_should_track_and_decode_constructor_args() {
+      let tmp_dir = crate::testing::project_dir();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+      let address = Address::from_str("00000000000000000000000000000000000000c0").unwrap();
+
+      let args = vec![arg("uint", "200"), arg("string", "Token")];
+      let tokens = vec![Token::Uint(U256::from(200)), Token::String("Token".to_string())];
+
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6080".to_string(), &vec![], address, Some((args.clone(), tokens))).unwrap();
+
+      let entry = tracker.get_smart_contract_tracking_data(&block_hash, "Token", "6080", &vec![]).unwrap().unwrap();
+      assert!(entry.encoded_args.as_ref().unwrap().starts_with("0x00000000000000000000000000000000000000000000000000000000000000c8"));
+      assert_eq!(entry.decoded_args().unwrap().unwrap(), args);
+    }
+
+    #[test]
+    fn it_should_not_decode_entries_without_tracked_args() {
+      let tmp_dir = crate::testing::project_dir();
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6080".to_string(), &vec![], Address::zero(), None).unwrap();
+
+      let entry = tracker.get_smart_contract_tracking_data(&block_hash, "Token", "6080", &vec![]).unwrap().unwrap();
+      assert!(entry.decoded_args().unwrap().is_none());
+    }
+  }
+
+  mod key_strategy {
+
+    use super::super::{DeploymentTracker, RekeyCandidate, RekeyReport, SmartContractTrackingArg, TrackingKeyStrategy};
+    use crate::config::Config;
+    use ethabi::Token;
+    use std::fs;
+    use web3::types::{Address, H256};
+
+    fn setup_project(tracking_key: Option<&str>) -> tempfile::TempDir {
+      let tracking_key = tracking_key.map(|key| format!("tracking_key = \"{}\"", key)).unwrap_or_default();
+      crate::testing::project_with_config(&format!("
+        [sources]
+          artifacts = \"artifacts\"
+          smart_contracts = [\"contracts/*.sol\"]
+        [deployment]
+          {}
+          smart_contracts = []
+      ", tracking_key))
+    }
+
+    fn set_tracking_key(tmp_dir: &tempfile::TempDir, tracking_key: &str) {
+      let config_file = tmp_dir.path().join("vibranium.toml");
+      let content = fs::read_to_string(&config_file).unwrap().replace("[deployment]", &format!("[deployment]\n          tracking_key = \"{}\"", tracking_key));
+      fs::write(config_file, content).unwrap();
+    }
+
+    #[test]
+    fn it_should_read_the_configured_strategy() {
+      let tmp_dir = setup_project(None);
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      assert_eq!(DeploymentTracker::new(&config).key_strategy().unwrap(), TrackingKeyStrategy::ContentHash);
+
+      let tmp_dir = setup_project(Some("name-and-args"));
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      assert_eq!(DeploymentTracker::new(&config).key_strategy().unwrap(), TrackingKeyStrategy::NameAndArgs);
+
+      let tmp_dir = setup_project(Some("address"));
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      assert!(DeploymentTracker::new(&config).key_strategy().is_err());
+    }
+
+    fn candidate(byte_code: &str, args: &[&str], encoded_args: Option<&str>, code_matches: bool) -> Option<RekeyCandidate> {
+      Some(RekeyCandidate {
+        byte_code: byte_code.to_string(),
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        encoded_args: encoded_args.map(str::to_string),
+        code_matches,
+      })
+    }
+
+    #[test]
+    fn it_should_find_deployments_by_name_after_rekeying() {
+      let tmp_dir = setup_project(None);
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+
+      set_tracking_key(&tmp_dir, "name");
+      assert!(tracker.get_smart_contract_tracking_data(&block_hash, "Token", "6081", &vec![]).unwrap().is_none());
+      assert_eq!(tracker.rekey(TrackingKeyStrategy::Name, |_block_hash, _chain_tracking_data, _entry| None).unwrap().updated, 1);
+
+      let entry = tracker.get_smart_contract_tracking_data(&block_hash, "Token", "6081", &vec![]).unwrap().unwrap();
+      assert_eq!(entry.address, Address::from_low_u64_be(1));
+    }
+
+    #[test]
+    fn it_should_keep_keys_of_unresolved_entries() {
+      let tmp_dir = setup_project(None);
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6080".to_string(), &vec!["1".to_string()], Address::from_low_u64_be(1), None).unwrap();
+      tracker.track(block_hash, "Other".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(2), None).unwrap();
+
+      let report = tracker.rekey(TrackingKeyStrategy::NameAndArgs, |_block_hash, _chain_tracking_data, entry| {
+        if entry.name == "Token" { candidate("6080", &["1"], None, false) } else { None }
+      }).unwrap();
+      assert_eq!(report, RekeyReport { updated: 1, kept: vec![("Other".to_string(), Address::from_low_u64_be(2))] });
+
+      set_tracking_key(&tmp_dir, "name-and-args");
+      assert!(tracker.get_smart_contract_tracking_data(&block_hash, "Token", "6081", &vec!["1".to_string()]).unwrap().is_some());
+      assert_eq!(tracker.get_all_smart_contract_tracking_data(&block_hash).unwrap().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn it_should_keep_keys_of_deployments_of_other_bytecode() {
+      let tmp_dir = setup_project(None);
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+
+      let report = tracker.rekey(TrackingKeyStrategy::ContentHash, |_block_hash, _chain_tracking_data, _entry| candidate("6081", &[], Some("0x"), false)).unwrap();
+      assert_eq!(report, RekeyReport { updated: 0, kept: vec![("Token".to_string(), Address::from_low_u64_be(1))] });
+      assert!(tracker.get_smart_contract_tracking_data(&block_hash, "Token", "6080", &vec![]).unwrap().is_some());
+      assert!(tracker.get_smart_contract_tracking_data(&block_hash, "Token", "6081", &vec![]).unwrap().is_none());
+    }
+
+    #[test]
+    fn it_should_rekey_deployments_proven_by_their_tracked_args_and_deployed_code() {
+      let tmp_dir = setup_project(Some("name"));
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+      let arg = SmartContractTrackingArg { kind: "uint".to_string(), value: "1".to_string() };
+      let encoded_args = format!("0x{:064x}", 1);
+
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), Some((vec![arg], vec![Token::Uint(1.into())]))).unwrap();
+
+      let report = tracker.rekey(TrackingKeyStrategy::ContentHash, |_block_hash, _chain_tracking_data, _entry| candidate("6081", &["1"], Some(&encoded_args), false)).unwrap();
+      assert_eq!(report.updated, 0);
+
+      let report = tracker.rekey(TrackingKeyStrategy::NameAndArgs, |_block_hash, _chain_tracking_data, _entry| candidate("6081", &["1"], Some(&encoded_args), false)).unwrap();
+      assert_eq!(report.updated, 1);
+
+      let report = tracker.rekey(TrackingKeyStrategy::ContentHash, |_block_hash, _chain_tracking_data, _entry| candidate("6081", &["1"], Some(&encoded_args), true)).unwrap();
+      assert_eq!(report, RekeyReport { updated: 1, kept: vec![] });
+
+      set_tracking_key(&tmp_dir, "content-hash");
+      assert!(tracker.get_smart_contract_tracking_data(&block_hash, "Token", "6081", &vec!["1".to_string()]).unwrap().is_some());
+    }
+
+    #[test]
+    fn it_should_keep_keys_of_older_deployments_when_keys_collide() {
+      let tmp_dir = setup_project(None);
+      let config = Config::new(tmp_dir.path().to_path_buf());
+      let tracker = DeploymentTracker::new(&config);
+      let block_hash = H256::from_low_u64_be(1);
+
+      tracker.create_database().unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+      tracker.track(block_hash, "Token".to_string(), "6081".to_string(), &vec![], Address::from_low_u64_be(2), None).unwrap();
+      let tracking_file = tmp_dir.path().join(".vibranium").join("tracking.toml");
+      let content = fs::read_to_string(&tracking_file).unwrap();
+      let deployed_at = content.lines().find(|line| line.starts_with("deployed_at")).unwrap().to_string();
+      fs::write(&tracking_file, content.replacen(&deployed_at, "deployed_at = 1", 1)).unwrap();
+      let older = tracker.get_all_smart_contract_tracking_data(&block_hash).unwrap().unwrap()
+        .into_values()
+        .find(|entry| entry.deployed_at == Some(1))
+        .unwrap();
+
+      let report = tracker.rekey(TrackingKeyStrategy::Name, |_block_hash, _chain_tracking_data, _entry| None).unwrap();
+      assert_eq!(report, RekeyReport { updated: 1, kept: vec![("Token".to_string(), older.address)] });
+
+      set_tracking_key(&tmp_dir, "name");
+      let entry = tracker.get_smart_contract_tracking_data(&block_hash, "Token", "", &vec![]).unwrap().unwrap();
+      assert_ne!(entry.address, older.address);
+      assert_eq!(tracker.get_all_smart_contract_tracking_data(&block_hash).unwrap().unwrap().len(), 2);
+    }
+  }
+}
@@ -0,0 +1,353 @@
+// Upgradeable Smart Contracts are deployed as an implementation, followed by
+// an ERC-1967 or transparent proxy delegating to it. The proxy is tracked
+// under the Smart Contract's name, so that references resolve to it, and its
+// implementations under a suffixed name. Both record the address of the other
+// one in their metadata.
+//
+// Upgrades are refused unless the storage layout of the new implementation
+// is compatible with the one of the implementation the proxy points at. solc
+// writes storage layouts to the artifacts directory when `--storage-layout`
+// is passed to it, and the layout of the current implementation is kept in
+// `.vibranium/upgrades`.
+
+use super::args;
+use super::error::DeploymentError;
+use super::DeployedContracts;
+use crate::config::SmartContractArg;
+use crate::project_generator::VIBRANIUM_PROJECT_DIRECTORY;
+
+use ethabi::param_type::Reader;
+use ethabi::{ParamType, Token};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use web3::types::{Address, Log, H256};
+
+pub const IMPLEMENTATION_SUFFIX: &str = ":implementation";
+pub const METADATA_PROXY_KIND: &str = "proxy";
+pub const METADATA_IMPLEMENTATION: &str = "implementation";
+pub const METADATA_ADMIN: &str = "admin";
+pub const METADATA_PROXY: &str = "proxied-by";
+const PROXY_KIND_ERC1967: &str = "erc1967";
+const PROXY_KIND_TRANSPARENT: &str = "transparent";
+const UPGRADES_DIRECTORY: &str = "upgrades";
+const STORAGE_LAYOUT_SUFFIX: &str = "_storage.json";
+const UPGRADE_TO_AND_CALL: &str = "upgradeToAndCall(address,bytes)";
+const PROXY_ADMIN_UPGRADE_AND_CALL: &str = "upgradeAndCall(address,address,bytes)";
+const ADMIN_CHANGED_EVENT: &str = "AdminChanged(address,address)";
+const UPGRADED_EVENT: &str = "Upgraded(address)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ProxyKind {
+  // UUPS, upgraded by calling the proxy, which the implementation authorizes.
+  #[default]
+  Erc1967,
+  // Upgraded by its admin, a ProxyAdmin Smart Contract created by the proxy
+  // as of OpenZeppelin 5, or the deploying account before.
+  Transparent,
+}
+
+impl FromStr for ProxyKind {
+  type Err = DeploymentError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      PROXY_KIND_ERC1967 => Ok(ProxyKind::Erc1967),
+      PROXY_KIND_TRANSPARENT => Ok(ProxyKind::Transparent),
+      _ => Err(DeploymentError::InvalidProxyKind(s.to_string())),
+    }
+  }
+}
+
+impl fmt::Display for ProxyKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ProxyKind::Erc1967 => write!(f, "{}", PROXY_KIND_ERC1967),
+      ProxyKind::Transparent => write!(f, "{}", PROXY_KIND_TRANSPARENT),
+    }
+  }
+}
+
+pub struct UpgradeOptions {
+  pub skip_storage_check: bool,
+  // Overrides `deployment.require_tests`.
+  pub require_tests: Option<bool>,
+}
+
+#[derive(Debug)]
+pub struct UpgradeReport {
+  pub name: String,
+  pub proxy: Address,
+  pub previous_implementation: Option<Address>,
+  pub implementation: Address,
+  // None if the proxy already pointed at the implementation.
+  pub tx_hash: Option<H256>,
+}
+
+pub fn implementation_name(name: &str) -> String {
+  format!("{}{}", name, IMPLEMENTATION_SUFFIX)
+}
+
+// ERC1967Proxy takes the implementation and the data of the initializer
+// call, TransparentUpgradeableProxy additionally its admin.
+pub fn proxy_constructor_args(kind: ProxyKind, implementation: Address, admin: Address, init_data: Vec<u8>) -> Vec<Token> {
+  match kind {
+    ProxyKind::Erc1967 => vec![Token::Address(implementation), Token::Bytes(init_data)],
+    ProxyKind::Transparent => vec![Token::Address(implementation), Token::Address(admin), Token::Bytes(init_data)],
+  }
+}
+
+// Encodes the call of the implementation's initializer, done by the proxy's
+// constructor. Without an initializer, the proxy doesn't call anything.
+pub fn initializer_data(name: &str, abi: &[u8], initializer: Option<&str>, initializer_args: &[SmartContractArg], deployed_contracts: &DeployedContracts) -> Result<Vec<u8>, DeploymentError> {
+  let initializer = match initializer {
+    Some(initializer) => initializer,
+    None => return Ok(vec![]),
+  };
+  let contract = ethabi::Contract::load(abi).map_err(|err| DeploymentError::InvalidConstructorArgs(err, name.to_owned()))?;
+  let function = contract.function(initializer).map_err(|_err| DeploymentError::UnknownInitializer(name.to_owned(), initializer.to_owned()))?;
+
+  if function.inputs.len() != initializer_args.len() {
+    return Err(DeploymentError::ConstructorArgsCount(format!("{}.{}", name, initializer), function.inputs.len(), initializer_args.len()));
+  }
+  let param_types = initializer_args.iter().map(|arg| Reader::read(&arg.kind).map_err(DeploymentError::InvalidParamType)).collect::<Result<Vec<ParamType>, DeploymentError>>()?;
+  let tokens = args::tokenize_args(initializer_args, &param_types, deployed_contracts)?;
+  function.encode_input(&tokens).map_err(|err| DeploymentError::InvalidConstructorArgs(err, format!("{}.{}", name, initializer)))
+}
+
+// The call upgrading the proxy to the given implementation, along with the
+// address it's sent to.
+pub fn upgrade_call(kind: ProxyKind, proxy: Address, admin: Option<Address>, sender: Address, implementation: Address) -> (Address, Vec<u8>) {
+  match (kind, admin) {
+    (ProxyKind::Transparent, Some(admin)) if admin != sender => {
+      (admin, encode_call(PROXY_ADMIN_UPGRADE_AND_CALL, &[Token::Address(proxy), Token::Address(implementation), Token::Bytes(vec![])]))
+    },
+    _ => (proxy, encode_call(UPGRADE_TO_AND_CALL, &[Token::Address(implementation), Token::Bytes(vec![])])),
+  }
+}
+
+// The admin a transparent proxy announced when it's been deployed.
+pub fn admin_from_logs(logs: &[Log]) -> Option<Address> {
+  let topic = H256::from_slice(&keccak(ADMIN_CHANGED_EVENT));
+  logs.iter()
+    .find(|log| log.topics.first() == Some(&topic))
+    .and_then(|log| ethabi::decode(&[ParamType::Address, ParamType::Address], &log.data.0).ok())
+    .and_then(|tokens| tokens.into_iter().nth(1))
+    .and_then(|token| token.to_address())
+}
+
+// The implementation a proxy announced to have been upgraded to.
+pub fn upgraded_implementation(logs: &[Log]) -> Option<Address> {
+  let topic = H256::from_slice(&keccak(UPGRADED_EVENT));
+  logs.iter()
+    .filter(|log| log.topics.first() == Some(&topic))
+    .filter_map(|log| log.topics.get(1))
+    .map(|implementation| Address::from_slice(&implementation.as_bytes()[12..]))
+    .next_back()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StorageLayout {
+  pub storage: Vec<StorageVariable>,
+  pub types: Option<HashMap<String, StorageType>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StorageVariable {
+  pub contract: String,
+  pub label: String,
+  pub offset: u64,
+  pub slot: String,
+  #[serde(rename = "type")]
+  pub type_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StorageType {
+  pub label: String,
+  #[serde(rename = "numberOfBytes")]
+  pub number_of_bytes: String,
+}
+
+impl StorageLayout {
+  // Type ids of structs and enums contain AST ids, which change between
+  // compilations, so types are compared by their label and size.
+  fn type_of(&self, variable: &StorageVariable) -> (String, Option<String>) {
+    match self.types.as_ref().and_then(|types| types.get(&variable.type_id)) {
+      Some(storage_type) => (storage_type.label.to_owned(), Some(storage_type.number_of_bytes.to_owned())),
+      None => (variable.type_id.to_owned(), None),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageIncompatibility {
+  Removed(String),
+  Moved(String),
+  Renamed(String, String),
+  TypeChanged(String, String, String),
+}
+
+impl fmt::Display for StorageIncompatibility {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      StorageIncompatibility::Removed(label) => write!(f, "`{}` has been removed", label),
+      StorageIncompatibility::Moved(label) => write!(f, "`{}` has been moved to another slot, e.g. by inserting a variable before it", label),
+      StorageIncompatibility::Renamed(label, new_label) => write!(f, "`{}` has been renamed to `{}`", label, new_label),
+      StorageIncompatibility::TypeChanged(label, old_type, new_type) => write!(f, "`{}` has changed its type from {} to {}", label, old_type, new_type),
+    }
+  }
+}
+
+// Variables of the current layout have to keep their slot, offset, name and
+// type. New ones can only be appended.
+pub fn check_storage_layout(current: &StorageLayout, new: &StorageLayout) -> Vec<StorageIncompatibility> {
+  current.storage.iter().filter_map(|variable| {
+    let at_same_position = new.storage.iter().find(|candidate| candidate.slot == variable.slot && candidate.offset == variable.offset);
+    match at_same_position {
+      None if new.storage.iter().any(|candidate| candidate.label == variable.label) => Some(StorageIncompatibility::Moved(variable.label.to_owned())),
+      None => Some(StorageIncompatibility::Removed(variable.label.to_owned())),
+      Some(candidate) if current.type_of(variable) != new.type_of(candidate) => {
+        Some(StorageIncompatibility::TypeChanged(variable.label.to_owned(), current.type_of(variable).0, new.type_of(candidate).0))
+      },
+      Some(candidate) if candidate.label != variable.label => Some(StorageIncompatibility::Renamed(variable.label.to_owned(), candidate.label.to_owned())),
+      Some(_candidate) => None,
+    }
+  }).collect()
+}
+
+// The storage layout solc wrote next to the compiled Smart Contract.
+pub fn compiled_storage_layout(artifacts_path: &Path, name: &str) -> Result<Option<StorageLayout>, DeploymentError> {
+  read_storage_layout(&artifacts_path.join(format!("{}{}", name, STORAGE_LAYOUT_SUFFIX)))
+}
+
+// The storage layout of the implementation the proxy of the given Smart
+// Contract points at.
+pub fn recorded_storage_layout(project_path: &Path, name: &str) -> Result<Option<StorageLayout>, DeploymentError> {
+  read_storage_layout(&recorded_storage_layout_path(project_path, name))
+}
+
+pub fn record_storage_layout(project_path: &Path, name: &str, layout: &StorageLayout) -> Result<(), DeploymentError> {
+  let path = recorded_storage_layout_path(project_path, name);
+  fs::create_dir_all(path.parent().unwrap())?;
+  let layout = serde_json::to_string_pretty(layout).map_err(|err| DeploymentError::Other(err.to_string()))?;
+  fs::write(path, layout)?;
+  Ok(())
+}
+
+fn recorded_storage_layout_path(project_path: &Path, name: &str) -> PathBuf {
+  project_path.join(VIBRANIUM_PROJECT_DIRECTORY).join(UPGRADES_DIRECTORY).join(format!("{}{}", name, STORAGE_LAYOUT_SUFFIX))
+}
+
+fn read_storage_layout(path: &Path) -> Result<Option<StorageLayout>, DeploymentError> {
+  if !path.exists() {
+    return Ok(None);
+  }
+  serde_json::from_str(&fs::read_to_string(path)?)
+    .map(Some)
+    .map_err(|err| DeploymentError::Other(format!("Couldn't read storage layout {}: {}", path.display(), err)))
+}
+
+fn encode_call(signature: &str, tokens: &[Token]) -> Vec<u8> {
+  let mut data = keccak(signature)[..4].to_vec();
+  data.extend(ethabi::encode(tokens));
+  data
+}
+
+fn keccak(signature: &str) -> Vec<u8> {
+  Keccak256::digest(signature.as_bytes()).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{admin_from_logs, check_storage_layout, upgrade_call, upgraded_implementation, ProxyKind, StorageIncompatibility, StorageLayout};
+  use web3::types::{Address, Bytes, Log, H256};
+
+  fn layout(json: &str) -> StorageLayout {
+    serde_json::from_str(json).unwrap()
+  }
+
+  fn log(topics: Vec<H256>, data: Vec<u8>) -> Log {
+    serde_json::from_value(serde_json::json!({
+      "address": format!("{:?}", Address::from_low_u64_be(9)),
+      "topics": topics,
+      "data": Bytes(data),
+    })).unwrap()
+  }
+
+  const CURRENT: &str = r#"{
+    "storage": [
+      {"astId": 1, "contract": "Token.sol:Token", "label": "owner", "offset": 0, "slot": "0", "type": "t_address"},
+      {"astId": 2, "contract": "Token.sol:Token", "label": "supply", "offset": 0, "slot": "1", "type": "t_uint256"},
+      {"astId": 3, "contract": "Token.sol:Token", "label": "info", "offset": 0, "slot": "2", "type": "t_struct(Info)12_storage"}
+    ],
+    "types": {
+      "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+      "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"},
+      "t_struct(Info)12_storage": {"encoding": "inplace", "label": "struct Token.Info", "numberOfBytes": "64"}
+    }
+  }"#;
+
+  #[test]
+  fn it_should_accept_appended_variables() {
+    let new = layout(&CURRENT
+      .replace("t_struct(Info)12_storage", "t_struct(Info)40_storage")
+      .replace(r#""type": "t_struct(Info)40_storage"}"#, r#""type": "t_struct(Info)40_storage"}, {"astId": 4, "contract": "Token.sol:Token", "label": "paused", "offset": 0, "slot": "4", "type": "t_bool"}"#));
+    assert_eq!(check_storage_layout(&layout(CURRENT), &new), vec![]);
+  }
+
+  #[test]
+  fn it_should_report_incompatible_storage_layouts() {
+    let new = layout(r#"{
+      "storage": [
+        {"astId": 5, "contract": "Token.sol:Token", "label": "paused", "offset": 0, "slot": "0", "type": "t_bool"},
+        {"astId": 1, "contract": "Token.sol:Token", "label": "admin", "offset": 0, "slot": "1", "type": "t_uint256"},
+        {"astId": 2, "contract": "Token.sol:Token", "label": "owner", "offset": 0, "slot": "3", "type": "t_address"}
+      ],
+      "types": {
+        "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+        "t_bool": {"encoding": "inplace", "label": "bool", "numberOfBytes": "1"},
+        "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"}
+      }
+    }"#);
+
+    assert_eq!(check_storage_layout(&layout(CURRENT), &new), vec![
+      StorageIncompatibility::TypeChanged("owner".to_string(), "address".to_string(), "bool".to_string()),
+      StorageIncompatibility::Renamed("supply".to_string(), "admin".to_string()),
+      StorageIncompatibility::Removed("info".to_string()),
+    ]);
+  }
+
+  #[test]
+  fn it_should_upgrade_transparent_proxies_through_their_admin() {
+    let (proxy, admin, deployer, implementation) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2), Address::from_low_u64_be(3), Address::from_low_u64_be(4));
+
+    let (to, data) = upgrade_call(ProxyKind::Transparent, proxy, Some(admin), deployer, implementation);
+    assert_eq!(to, admin);
+    // keccak256("upgradeAndCall(address,address,bytes)")[..4]
+    assert_eq!(data[..4], [0x96, 0x23, 0x60, 0x9d]);
+
+    let (to, data) = upgrade_call(ProxyKind::Erc1967, proxy, None, deployer, implementation);
+    assert_eq!(to, proxy);
+    // keccak256("upgradeToAndCall(address,bytes)")[..4]
+    assert_eq!(data[..4], [0x4f, 0x1e, 0xf2, 0x86]);
+    assert_eq!(Address::from_slice(&data[16..36]), implementation);
+  }
+
+  #[test]
+  fn it_should_read_admins_and_implementations_from_proxy_logs() {
+    let (admin, implementation) = (Address::from_low_u64_be(2), Address::from_low_u64_be(4));
+    let upgraded_topic = H256::from_slice(&super::keccak(super::UPGRADED_EVENT));
+    let admin_changed_topic = H256::from_slice(&super::keccak(super::ADMIN_CHANGED_EVENT));
+    let logs = vec![
+      log(vec![upgraded_topic, H256::from(implementation)], vec![]),
+      log(vec![admin_changed_topic], ethabi::encode(&[ethabi::Token::Address(Address::zero()), ethabi::Token::Address(admin)])),
+    ];
+
+    assert_eq!(upgraded_implementation(&logs), Some(implementation));
+    assert_eq!(admin_from_logs(&logs), Some(admin));
+  }
+}
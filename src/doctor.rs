@@ -0,0 +1,183 @@
+//! Checks whether the tools a project depends on are available, and collects
+//! everything needed to troubleshoot a project into a single report.
+
+use crate::blockchain::support::SupportedBlockchainClients;
+use crate::compiler::support::SupportedCompilers;
+use crate::config::Config;
+use crate::process::{self, ProcessCommand};
+use crate::secrets;
+
+use std::fmt;
+use std::fs;
+use toml_query::set::TomlValueSetExt;
+
+pub const REPORT_FILE: &str = "vibranium-report.json";
+const REDACTED_VALUE: &str = "<redacted>";
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DoctorCheck {
+  pub name: String,
+  pub passed: bool,
+  pub details: String,
+}
+
+impl fmt::Display for DoctorCheck {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let status = if self.passed { "ok" } else { "failed" };
+    write!(f, "[{}] {}: {}", status, self.name, self.details)
+  }
+}
+
+#[derive(Serialize, Debug)]
+pub struct DoctorReport {
+  pub vibranium_version: String,
+  pub os: String,
+  pub arch: String,
+  pub checks: Vec<DoctorCheck>,
+  pub config: Option<toml::Value>,
+  pub processes: Vec<process::ProcessRecord>,
+}
+
+impl DoctorReport {
+  pub fn to_json(&self) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(self)
+  }
+}
+
+pub struct Doctor<'a> {
+  config: &'a Config,
+}
+
+impl<'a> Doctor<'a> {
+  pub fn new(config: &'a Config) -> Doctor<'a> {
+    Doctor {
+      config,
+    }
+  }
+
+  pub fn run_checks(&self) -> Vec<DoctorCheck> {
+    let mut checks = vec![DoctorCheck {
+      name: "project".to_string(),
+      passed: self.config.vibranium_dir_path.exists(),
+      details: if self.config.vibranium_dir_path.exists() {
+        format!("initialized at {}", self.config.project_path.to_string_lossy())
+      } else {
+        format!("{} is not a Vibranium project, run `vibranium init` first", self.config.project_path.to_string_lossy())
+      },
+    }];
+
+    let project_config = match self.config.read() {
+      Ok(project_config) => project_config,
+      Err(err) => {
+        checks.push(DoctorCheck { name: "config".to_string(), passed: false, details: err.to_string() });
+        return checks;
+      }
+    };
+    checks.push(DoctorCheck { name: "config".to_string(), passed: true, details: "vibranium.toml is valid".to_string() });
+
+    let compiler = project_config.compiler.and_then(|config| config.cmd).unwrap_or_else(|| SupportedCompilers::Solc.executable());
+    checks.push(self.check_executable("compiler", &compiler));
+
+    let client = project_config.blockchain.and_then(|config| config.cmd).unwrap_or_else(|| SupportedBlockchainClients::Parity.executable());
+    checks.push(self.check_executable("blockchain client", &client));
+
+    checks
+  }
+
+  // Contains the recorded invocations of external tools and the project
+  // configuration, with embedded secrets redacted.
+  pub fn report(&self, checks: Vec<DoctorCheck>) -> DoctorReport {
+    DoctorReport {
+      vibranium_version: env!("CARGO_PKG_VERSION").to_string(),
+      os: std::env::consts::OS.to_string(),
+      arch: std::env::consts::ARCH.to_string(),
+      checks,
+      config: self.redacted_config(),
+      processes: process::read_log(&self.config.vibranium_dir_path),
+    }
+  }
+
+  fn check_executable(&self, name: &str, executable: &str) -> DoctorCheck {
+    let output = ProcessCommand::new(executable)
+      .arg("--version")
+      .log_to(&self.config.vibranium_dir_path)
+      .output();
+
+    match output {
+      Ok(output) if output.status.success() => {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let version = stdout.lines().rfind(|line| !line.trim().is_empty()).unwrap_or("").trim();
+        DoctorCheck {
+          name: name.to_string(),
+          passed: true,
+          details: if version.is_empty() { executable.to_string() } else { format!("{} ({})", executable, version) },
+        }
+      },
+      Ok(output) => DoctorCheck {
+        name: name.to_string(),
+        passed: false,
+        details: format!("`{} --version` exited with {}", executable, output.status),
+      },
+      Err(err) => DoctorCheck {
+        name: name.to_string(),
+        passed: false,
+        details: format!("couldn't run {}: {}", executable, err),
+      },
+    }
+  }
+
+  fn redacted_config(&self) -> Option<toml::Value> {
+    let content = fs::read_to_string(&self.config.config_file).ok()?;
+    let mut config: toml::Value = toml::from_str(&content).ok()?;
+    for finding in secrets::scan(&config) {
+      let _ = config.set(&finding.option.replace("[", ".["), toml::Value::String(REDACTED_VALUE.to_string()));
+    }
+    Some(config)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::Doctor;
+  use crate::config::Config;
+
+  fn setup_project() -> tempfile::TempDir {
+    crate::testing::project_with_config("
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+      [compiler]
+        cmd = \"true\"
+      [blockchain]
+        cmd = \"vibranium-non-existent-client\"
+        accounts = { private_keys = [\"0x4f3edf983ac636a65a842ce7c78d9aa706d3b113bce9c46f30d7d21715b23b1d\"] }
+    ")
+  }
+
+  #[test]
+  fn it_should_check_external_tools() {
+    let tmp_dir = setup_project();
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let checks = Doctor::new(&config).run_checks();
+
+    let results: Vec<(&str, bool)> = checks.iter().map(|check| (check.name.as_str(), check.passed)).collect();
+    assert_eq!(results, vec![("project", true), ("config", true), ("compiler", true), ("blockchain client", false)]);
+  }
+
+  #[test]
+  fn it_should_report_processes_and_redact_secrets() {
+    let tmp_dir = setup_project();
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let doctor = Doctor::new(&config);
+    let report = doctor.report(doctor.run_checks());
+
+    assert_eq!(report.processes.len(), 2);
+    assert_eq!(report.processes[0].program, "true");
+    assert_eq!(report.processes[0].args, vec!["--version"]);
+
+    let report_json = serde_json::to_string(&report).unwrap();
+    assert!(report_json.contains("<redacted>"));
+    assert!(!report_json.contains("4f3edf983ac636a65a842ce7c78d9aa706d3b113bce9c46f30d7d21715b23b1d"));
+  }
+}
@@ -0,0 +1,52 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum HistoryError {
+  Io(io::Error),
+  Serialization(toml::ser::Error),
+  Deserialization(toml::de::Error),
+  EntryNotFound(usize),
+}
+
+impl Error for HistoryError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      HistoryError::Io(error) => Some(error),
+      HistoryError::Serialization(error) => Some(error),
+      HistoryError::Deserialization(error) => Some(error),
+      HistoryError::EntryNotFound(_number) => None,
+    }
+  }
+}
+
+impl fmt::Display for HistoryError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      HistoryError::Io(error) => write!(f, "Couldn't access command history: {}", error),
+      HistoryError::Serialization(error) => write!(f, "Couldn't write command history: {}", error),
+      HistoryError::Deserialization(error) => write!(f, "Couldn't read command history: {}", error),
+      HistoryError::EntryNotFound(number) => write!(f, "Couldn't find command #{} in history. Run `vibranium history list` to see recorded commands", number),
+    }
+  }
+}
+
+impl From<io::Error> for HistoryError {
+  fn from(error: io::Error) -> Self {
+    HistoryError::Io(error)
+  }
+}
+
+impl From<toml::ser::Error> for HistoryError {
+  fn from(error: toml::ser::Error) -> Self {
+    HistoryError::Serialization(error)
+  }
+}
+
+impl From<toml::de::Error> for HistoryError {
+  fn from(error: toml::de::Error) -> Self {
+    HistoryError::Deserialization(error)
+  }
+}
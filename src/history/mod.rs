@@ -0,0 +1,181 @@
+pub mod error;
+
+use crate::config::{Config, ProjectConfig};
+use crate::deployment;
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const HISTORY_FILE: &str = "history.toml";
+const MAX_HISTORY_ENTRIES: usize = 100;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct History {
+  entries: Vec<HistoryEntry>,
+}
+
+// `args` are the command's arguments as given, `network` and `contracts` are
+// resolved from the project configuration at the time the command ran.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+  pub args: Vec<String>,
+  pub executed_at: u64,
+  pub network: Option<String>,
+  pub contracts: Option<Vec<String>>,
+}
+
+impl HistoryEntry {
+  pub fn resolve(args: Vec<String>, project_config: &ProjectConfig) -> HistoryEntry {
+    let network = project_config.blockchain.as_ref()
+      .and_then(|config| config.connector.as_ref())
+      .map(|connector| connector.url.clone().unwrap_or_else(|| format!("{}://{}:{}", &connector.protocol, &connector.host, &connector.port)));
+
+    let constants = project_config.constants.clone().unwrap_or_default();
+    let contracts = project_config.deployment.as_ref().map(|config| {
+      config.smart_contracts.iter().map(|smart_contract| {
+        let args: Vec<String> = smart_contract.args.iter().flatten()
+          .map(|arg| deployment::resolve_constant_references(&arg.value, &constants))
+          .collect();
+        format!("{}({})", &smart_contract.name, args.join(", "))
+      }).collect()
+    });
+
+    HistoryEntry {
+      args,
+      executed_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0),
+      network,
+      contracts,
+    }
+  }
+
+  // Differences between the resolution of the command when it ran and the
+  // given, current one.
+  pub fn changes(&self, current: &HistoryEntry) -> Vec<String> {
+    let mut changes = vec![];
+    if self.network != current.network {
+      changes.push(format!("Network: {} (now {})", self.network.as_deref().unwrap_or("none"), current.network.as_deref().unwrap_or("none")));
+    }
+    if self.contracts != current.contracts {
+      let describe = |contracts: &Option<Vec<String>>| contracts.as_ref().map(|contracts| contracts.join(", ")).unwrap_or_else(|| "none".to_string());
+      changes.push(format!("Smart Contracts: {} (now {})", describe(&self.contracts), describe(&current.contracts)));
+    }
+    changes
+  }
+}
+
+pub struct CommandHistory<'a> {
+  config: &'a Config,
+}
+
+impl<'a> CommandHistory<'a> {
+  pub fn new(config: &'a Config) -> CommandHistory<'a> {
+    CommandHistory {
+      config
+    }
+  }
+
+  // Only the most recent entries are kept.
+  pub fn record(&self, entry: HistoryEntry) -> Result<(), error::HistoryError> {
+    let mut history = self.read()?;
+    history.entries.push(entry);
+    if history.entries.len() > MAX_HISTORY_ENTRIES {
+      let overflow = history.entries.len() - MAX_HISTORY_ENTRIES;
+      history.entries.drain(0..overflow);
+    }
+    fs::write(self.get_history_file(), toml::to_string(&history)?)?;
+    Ok(())
+  }
+
+  // Entries are returned most recent first, so that entry `1` is the last
+  // command that has been executed.
+  pub fn entries(&self) -> Result<Vec<HistoryEntry>, error::HistoryError> {
+    let mut entries = self.read()?.entries;
+    entries.reverse();
+    Ok(entries)
+  }
+
+  pub fn entry(&self, number: usize) -> Result<HistoryEntry, error::HistoryError> {
+    if number == 0 {
+      return Err(error::HistoryError::EntryNotFound(number));
+    }
+    self.entries()?.into_iter().nth(number - 1).ok_or(error::HistoryError::EntryNotFound(number))
+  }
+
+  fn read(&self) -> Result<History, error::HistoryError> {
+    let history_file = self.get_history_file();
+    if !history_file.exists() {
+      return Ok(History::default());
+    }
+    Ok(toml::from_str(&fs::read_to_string(history_file)?)?)
+  }
+
+  fn get_history_file(&self) -> PathBuf {
+    self.config.vibranium_dir_path.join(HISTORY_FILE)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{CommandHistory, HistoryEntry, MAX_HISTORY_ENTRIES};
+  use crate::config::Config;
+
+  fn entry(args: &[&str]) -> HistoryEntry {
+    HistoryEntry {
+      args: args.iter().map(|arg| arg.to_string()).collect(),
+      executed_at: 0,
+      network: Some("http://localhost:8545".to_string()),
+      contracts: None,
+    }
+  }
+
+  #[test]
+  fn it_should_return_most_recent_entries_first() {
+    let tmp_dir = crate::testing::project_dir();
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let history = CommandHistory::new(&config);
+
+    history.record(entry(&["compile"])).unwrap();
+    history.record(entry(&["deploy", "--no-tracking"])).unwrap();
+
+    assert_eq!(history.entries().unwrap(), vec![entry(&["deploy", "--no-tracking"]), entry(&["compile"])]);
+    assert_eq!(history.entry(2).unwrap(), entry(&["compile"]));
+    assert!(history.entry(0).is_err());
+    assert!(history.entry(3).is_err());
+  }
+
+  #[test]
+  fn it_should_only_keep_most_recent_entries() {
+    let tmp_dir = crate::testing::project_dir();
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let history = CommandHistory::new(&config);
+
+    for i in 0..MAX_HISTORY_ENTRIES + 5 {
+      history.record(entry(&["deploy", &i.to_string()])).unwrap();
+    }
+
+    let entries = history.entries().unwrap();
+    assert_eq!(entries.len(), MAX_HISTORY_ENTRIES);
+    assert_eq!(entries[0], entry(&["deploy", &(MAX_HISTORY_ENTRIES + 4).to_string()]));
+  }
+
+  #[test]
+  fn it_should_describe_changed_resolutions() {
+    let recorded = HistoryEntry {
+      contracts: Some(vec!["Token(1000)".to_string()]),
+      ..entry(&["deploy"])
+    };
+    assert!(recorded.changes(&recorded.clone()).is_empty());
+
+    let current = HistoryEntry {
+      network: Some("https://sepolia.example.org".to_string()),
+      contracts: Some(vec!["Token(2000)".to_string()]),
+      ..entry(&["deploy"])
+    };
+    assert_eq!(recorded.changes(&current), vec![
+      "Network: http://localhost:8545 (now https://sepolia.example.org)".to_string(),
+      "Smart Contracts: Token(1000) (now Token(2000))".to_string(),
+    ]);
+  }
+}
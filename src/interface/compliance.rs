@@ -0,0 +1,390 @@
+// Checks whether a Smart Contract implements a standard interface, either by
+// its compiled ABI or, for deployed Smart Contracts, by the function selectors
+// and event topics found in its code. Custom interfaces are given as ABI files.
+
+use super::error::InterfaceError;
+use super::{AbiEntry, AbiParam};
+use crate::blockchain::connector::BlockchainConnector;
+
+use sha3::{Digest, Keccak256};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use web3::types::{Address, Bytes, CallRequest};
+
+const SUPPORTS_INTERFACE: &str = "supportsInterface";
+
+const ERC20: &[&str] = &[
+  "function totalSupply() returns (uint256)",
+  "function balanceOf(address) returns (uint256)",
+  "function transfer(address,uint256) returns (bool)",
+  "function transferFrom(address,address,uint256) returns (bool)",
+  "function approve(address,uint256) returns (bool)",
+  "function allowance(address,address) returns (uint256)",
+  "event Transfer(address indexed,address indexed,uint256)",
+  "event Approval(address indexed,address indexed,uint256)",
+];
+
+const ERC721: &[&str] = &[
+  "function balanceOf(address) returns (uint256)",
+  "function ownerOf(uint256) returns (address)",
+  "function safeTransferFrom(address,address,uint256,bytes)",
+  "function safeTransferFrom(address,address,uint256)",
+  "function transferFrom(address,address,uint256)",
+  "function approve(address,uint256)",
+  "function setApprovalForAll(address,bool)",
+  "function getApproved(uint256) returns (address)",
+  "function isApprovedForAll(address,address) returns (bool)",
+  "function supportsInterface(bytes4) returns (bool)",
+  "event Transfer(address indexed,address indexed,uint256 indexed)",
+  "event Approval(address indexed,address indexed,uint256 indexed)",
+  "event ApprovalForAll(address indexed,address indexed,bool)",
+];
+
+const ERC1155: &[&str] = &[
+  "function safeTransferFrom(address,address,uint256,uint256,bytes)",
+  "function safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+  "function balanceOf(address,uint256) returns (uint256)",
+  "function balanceOfBatch(address[],uint256[]) returns (uint256[])",
+  "function setApprovalForAll(address,bool)",
+  "function isApprovedForAll(address,address) returns (bool)",
+  "function supportsInterface(bytes4) returns (bool)",
+  "event TransferSingle(address indexed,address indexed,address indexed,uint256,uint256)",
+  "event TransferBatch(address indexed,address indexed,address indexed,uint256[],uint256[])",
+  "event ApprovalForAll(address indexed,address indexed,bool)",
+  "event URI(string,uint256 indexed)",
+];
+
+#[derive(Debug)]
+pub struct InterfaceStandard {
+  pub name: String,
+  pub abi: Vec<AbiEntry>,
+}
+
+impl InterfaceStandard {
+  // `20`, `721` and `1155` refer to the respective ERCs, anything else is
+  // read as an ABI file relative to the project.
+  pub fn resolve(standard: &str, project_path: &Path) -> Result<InterfaceStandard, InterfaceError> {
+    let number = standard.trim_start_matches("ERC-").trim_start_matches("erc");
+    let declarations = match number {
+      "20" => Some(ERC20),
+      "721" => Some(ERC721),
+      "1155" => Some(ERC1155),
+      _ => None,
+    };
+
+    if let Some(declarations) = declarations {
+      return Ok(InterfaceStandard {
+        name: format!("ERC-{}", number),
+        abi: declarations.iter().map(|declaration| parse_declaration(declaration)).collect(),
+      });
+    }
+
+    let abi_path = project_path.join(standard);
+    if !abi_path.exists() {
+      return Err(InterfaceError::UnsupportedStandard(standard.to_string()));
+    }
+    let abi: Vec<AbiEntry> = serde_json::from_str(&fs::read_to_string(&abi_path)?)
+      .map_err(|err| InterfaceError::InvalidAbi(standard.to_string(), err))?;
+
+    Ok(InterfaceStandard {
+      name: standard.to_string(),
+      abi: abi.into_iter().filter(|entry| entry.kind == "function" || entry.kind == "event").collect(),
+    })
+  }
+
+  // ERC-165 interface identifiers are the XOR of all function selectors of
+  // an interface, except for `supportsInterface` itself. Only interfaces
+  // requiring `supportsInterface` have to report support of their identifier.
+  pub fn interface_id(&self) -> Option<[u8; 4]> {
+    if !self.functions().any(|entry| entry.name.as_deref() == Some(SUPPORTS_INTERFACE)) {
+      return None;
+    }
+
+    let mut interface_id = [0u8; 4];
+    for entry in self.functions().filter(|entry| entry.name.as_deref() != Some(SUPPORTS_INTERFACE)) {
+      for (byte, selector_byte) in interface_id.iter_mut().zip(selector(entry).iter()) {
+        *byte ^= selector_byte;
+      }
+    }
+    Some(interface_id)
+  }
+
+  fn functions(&self) -> impl Iterator<Item = &AbiEntry> {
+    self.abi.iter().filter(|entry| entry.kind == "function")
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ComplianceFinding {
+  Missing(String),
+  Mismatched(String, String),
+  InterfaceNotSupported(String),
+}
+
+impl fmt::Display for ComplianceFinding {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ComplianceFinding::Missing(expected) => write!(f, "missing {}", expected),
+      ComplianceFinding::Mismatched(expected, found) => write!(f, "mismatched {}, found {}", expected, found),
+      ComplianceFinding::InterfaceNotSupported(interface_id) => write!(f, "supportsInterface({}) doesn't return true", interface_id),
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct ComplianceReport {
+  pub contract: String,
+  pub standard: String,
+  pub findings: Vec<ComplianceFinding>,
+}
+
+impl ComplianceReport {
+  pub fn is_compliant(&self) -> bool {
+    self.findings.is_empty()
+  }
+}
+
+// Functions match by signature and have to return the same types. Events
+// additionally have to index the same parameters.
+pub fn check_abi(standard: &InterfaceStandard, abi: &[AbiEntry]) -> Vec<ComplianceFinding> {
+  standard.abi.iter().filter_map(|required| {
+    let candidates: Vec<&AbiEntry> = abi.iter()
+      .filter(|entry| entry.kind == required.kind && entry.name == required.name)
+      .collect();
+
+    match candidates.iter().find(|entry| signature(entry) == signature(required)) {
+      Some(entry) if describe(entry) == describe(required) => None,
+      Some(entry) => Some(ComplianceFinding::Mismatched(describe(required), describe(entry))),
+      None => match candidates.first() {
+        Some(entry) => Some(ComplianceFinding::Mismatched(describe(required), describe(entry))),
+        None => Some(ComplianceFinding::Missing(describe(required))),
+      },
+    }
+  }).collect()
+}
+
+// Deployed code only reveals whether selectors and topics are used at all, so
+// return types and indexed parameters can't be checked.
+pub fn check_code(standard: &InterfaceStandard, code: &[u8]) -> Vec<ComplianceFinding> {
+  let pushed = pushed_values(code);
+
+  standard.abi.iter().filter_map(|required| {
+    let found = match required.kind.as_str() {
+      "event" => pushed.contains(topic(required).as_slice()),
+      _ => pushed.contains(selector(required).as_slice()),
+    };
+    if found { None } else { Some(ComplianceFinding::Missing(describe(required))) }
+  }).collect()
+}
+
+// Interfaces with an ERC-165 identifier are also checked by calling
+// `supportsInterface` of the deployed Smart Contract.
+pub fn check_deployed(standard: &InterfaceStandard, connector: &BlockchainConnector, address: Address) -> Result<Vec<ComplianceFinding>, InterfaceError> {
+  let code = connector.code(address, None)?.0;
+  if code.is_empty() {
+    return Err(InterfaceError::NotDeployed(format!("{:?}", address)));
+  }
+
+  let mut findings = check_code(standard, &code);
+  if let Some(interface_id) = standard.interface_id() {
+    let request = CallRequest {
+      from: None,
+      to: address,
+      gas: None,
+      gas_price: None,
+      value: None,
+      data: Some(Bytes(supports_interface_call(interface_id))),
+    };
+    // Reverting counts as not supporting the interface.
+    let supported = connector.call(request, None)
+      .map(|result| result.0.len() == 32 && result.0[..31].iter().all(|byte| *byte == 0) && result.0[31] == 1)
+      .unwrap_or(false);
+    if !supported {
+      findings.push(ComplianceFinding::InterfaceNotSupported(format_interface_id(interface_id)));
+    }
+  }
+  Ok(findings)
+}
+
+fn supports_interface_call(interface_id: [u8; 4]) -> Vec<u8> {
+  let mut data = selector(&parse_declaration("function supportsInterface(bytes4) returns (bool)")).to_vec();
+  data.extend_from_slice(&interface_id);
+  data.extend_from_slice(&[0u8; 28]);
+  data
+}
+
+pub fn format_interface_id(interface_id: [u8; 4]) -> String {
+  format!("0x{}", interface_id.iter().map(|byte| format!("{:02x}", byte)).collect::<String>())
+}
+
+pub fn selector(entry: &AbiEntry) -> [u8; 4] {
+  let mut selector = [0u8; 4];
+  selector.copy_from_slice(&Keccak256::digest(signature(entry).as_bytes())[..4]);
+  selector
+}
+
+pub fn topic(entry: &AbiEntry) -> [u8; 32] {
+  let mut topic = [0u8; 32];
+  topic.copy_from_slice(&Keccak256::digest(signature(entry).as_bytes()));
+  topic
+}
+
+// Values of push instructions, with selectors shorter than 4 bytes padded, as
+// the optimizer drops their leading zeros.
+fn pushed_values(code: &[u8]) -> HashSet<Vec<u8>> {
+  let mut values = HashSet::new();
+  let mut offset = 0;
+
+  while offset < code.len() {
+    let opcode = code[offset];
+    if (0x60..=0x7f).contains(&opcode) {
+      let length = (opcode - 0x5f) as usize;
+      let value = &code[(offset + 1).min(code.len())..(offset + 1 + length).min(code.len())];
+      if length < 4 {
+        let mut padded = vec![0u8; 4 - value.len()];
+        padded.extend_from_slice(value);
+        values.insert(padded);
+      }
+      values.insert(value.to_vec());
+      offset += length;
+    }
+    offset += 1;
+  }
+  values
+}
+
+pub fn signature(entry: &AbiEntry) -> String {
+  let inputs: Vec<String> = entry.inputs.iter().flatten().map(canonical_type).collect();
+  format!("{}({})", entry.name.as_deref().unwrap_or_default(), inputs.join(","))
+}
+
+fn canonical_type(param: &AbiParam) -> String {
+  match &param.components {
+    Some(components) if param.kind.starts_with("tuple") => {
+      let components: Vec<String> = components.iter().map(canonical_type).collect();
+      format!("({}){}", components.join(","), param.kind.trim_start_matches("tuple"))
+    },
+    _ => param.kind.to_owned(),
+  }
+}
+
+// Same notation as the declarations of the standard interfaces above.
+fn describe(entry: &AbiEntry) -> String {
+  let params: Vec<String> = entry.inputs.iter().flatten().map(|param| {
+    if entry.kind == "event" && param.indexed.unwrap_or(false) {
+      format!("{} indexed", canonical_type(param))
+    } else {
+      canonical_type(param)
+    }
+  }).collect();
+
+  let mut description = format!("{} {}({})", entry.kind, entry.name.as_deref().unwrap_or_default(), params.join(","));
+  let outputs: Vec<String> = entry.outputs.iter().flatten().map(canonical_type).collect();
+  if !outputs.is_empty() {
+    description.push_str(&format!(" returns ({})", outputs.join(",")));
+  }
+  description
+}
+
+// Parses declarations like `function balanceOf(address) returns (uint256)` or
+// `event Transfer(address indexed,address indexed,uint256)`.
+fn parse_declaration(declaration: &str) -> AbiEntry {
+  let (kind, rest) = declaration.split_at(declaration.find(' ').unwrap_or(0));
+  let rest = rest.trim();
+  let open = rest.find('(').unwrap_or(rest.len());
+  let close = rest.find(')').unwrap_or(rest.len());
+  let returns = rest.find("returns (").map(|index| &rest[index + "returns (".len()..rest.len() - 1]);
+
+  let params = |list: &str| -> Vec<AbiParam> {
+    list.split(',').map(str::trim).filter(|param| !param.is_empty()).map(|param| AbiParam {
+      name: String::new(),
+      kind: param.trim_end_matches(" indexed").to_string(),
+      internal_type: None,
+      components: None,
+      indexed: if kind == "event" { Some(param.ends_with(" indexed")) } else { None },
+    }).collect()
+  };
+
+  AbiEntry {
+    kind: kind.to_string(),
+    name: Some(rest[..open].to_string()),
+    inputs: Some(params(&rest[open + 1..close])),
+    outputs: if kind == "function" { Some(returns.map(params).unwrap_or_default()) } else { None },
+    state_mutability: None,
+    constant: None,
+    payable: None,
+    anonymous: None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{check_abi, check_code, check_deployed, format_interface_id, ComplianceFinding, InterfaceStandard};
+  use crate::interface::AbiEntry;
+  use crate::testing::MockTransport;
+  use rustc_hex::FromHex;
+  use std::path::Path;
+  use web3::types::Address;
+
+  const TOKEN_ABI: &str = r#"[
+    {"type":"function","name":"totalSupply","inputs":[],"outputs":[{"name":"","type":"uint256"}]},
+    {"type":"function","name":"balanceOf","inputs":[{"name":"owner","type":"address"}],"outputs":[{"name":"","type":"uint128"}]},
+    {"type":"function","name":"transfer","inputs":[{"name":"to","type":"address"},{"name":"value","type":"uint256"}],"outputs":[{"name":"","type":"bool"}]},
+    {"type":"function","name":"approve","inputs":[{"name":"spender","type":"address"}],"outputs":[{"name":"","type":"bool"}]},
+    {"type":"function","name":"allowance","inputs":[{"name":"owner","type":"address"},{"name":"spender","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+    {"type":"event","name":"Transfer","inputs":[{"name":"from","type":"address","indexed":true},{"name":"to","type":"address","indexed":true},{"name":"value","type":"uint256","indexed":false}],"anonymous":false},
+    {"type":"event","name":"Approval","inputs":[{"name":"owner","type":"address","indexed":true},{"name":"spender","type":"address","indexed":false},{"name":"value","type":"uint256","indexed":false}],"anonymous":false}
+  ]"#;
+
+  #[test]
+  fn it_should_derive_erc165_interface_ids() {
+    let erc721 = InterfaceStandard::resolve("721", Path::new(".")).unwrap();
+    assert_eq!(erc721.interface_id().map(format_interface_id), Some("0x80ac58cd".to_string()));
+    let erc1155 = InterfaceStandard::resolve("1155", Path::new(".")).unwrap();
+    assert_eq!(erc1155.interface_id().map(format_interface_id), Some("0xd9b67a26".to_string()));
+    assert_eq!(InterfaceStandard::resolve("20", Path::new(".")).unwrap().interface_id(), None);
+    assert!(InterfaceStandard::resolve("42", Path::new(".")).is_err());
+  }
+
+  #[test]
+  fn it_should_report_missing_and_mismatched_abi_entries() {
+    let abi: Vec<AbiEntry> = serde_json::from_str(TOKEN_ABI).unwrap();
+    let findings = check_abi(&InterfaceStandard::resolve("20", Path::new(".")).unwrap(), &abi);
+
+    assert_eq!(findings, vec![
+      ComplianceFinding::Mismatched("function balanceOf(address) returns (uint256)".to_string(), "function balanceOf(address) returns (uint128)".to_string()),
+      ComplianceFinding::Missing("function transferFrom(address,address,uint256) returns (bool)".to_string()),
+      ComplianceFinding::Mismatched("function approve(address,uint256) returns (bool)".to_string(), "function approve(address) returns (bool)".to_string()),
+      ComplianceFinding::Mismatched("event Approval(address indexed,address indexed,uint256)".to_string(), "event Approval(address indexed,address,uint256)".to_string()),
+    ]);
+  }
+
+  #[test]
+  fn it_should_call_supports_interface_of_deployed_smart_contracts() {
+    let standard = InterfaceStandard::resolve("721", Path::new(".")).unwrap();
+    let transport = MockTransport::new();
+    transport.add_response("eth_getCode", serde_json::json!("0x6080"));
+    transport.add_response("eth_call", serde_json::json!(format!("0x{:064x}", 0)));
+    let connector = transport.connector();
+
+    let findings = check_deployed(&standard, &connector, Address::from_low_u64_be(1)).unwrap();
+    assert_eq!(findings.last(), Some(&ComplianceFinding::InterfaceNotSupported("0x80ac58cd".to_string())));
+    assert_eq!(transport.requests_for("eth_call")[0][0]["data"], format!("0x01ffc9a780ac58cd{}", "0".repeat(56)));
+  }
+
+  #[test]
+  fn it_should_find_selectors_and_topics_in_code() {
+    let standard = InterfaceStandard::resolve("20", Path::new(".")).unwrap();
+    // PUSH4 totalSupply(), EQ, PUSH32 Transfer(address,address,uint256)
+    let mut code = vec![0x63, 0x18, 0x16, 0x0d, 0xdd, 0x14, 0x7f];
+    code.extend("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".from_hex::<Vec<u8>>().unwrap());
+
+    let findings = check_code(&standard, &code);
+    assert_eq!(findings.len(), standard.abi.len() - 2);
+    assert!(!findings.contains(&ComplianceFinding::Missing("function totalSupply() returns (uint256)".to_string())));
+    assert!(!findings.contains(&ComplianceFinding::Missing("event Transfer(address indexed,address indexed,uint256)".to_string())));
+  }
+}
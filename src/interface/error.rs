@@ -0,0 +1,68 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+
+use crate::blockchain;
+use crate::config;
+use crate::project_generator;
+
+#[derive(Debug)]
+pub enum InterfaceError {
+  VibraniumDirectoryNotFound(project_generator::error::ProjectGenerationError),
+  InvalidConfig(config::error::ConfigError),
+  MissingArtifact(String, String),
+  InvalidAbi(String, serde_json::Error),
+  UnsupportedStandard(String),
+  NotDeployed(String),
+  Connection(blockchain::error::ConnectionError),
+  Io(io::Error),
+}
+
+impl Error for InterfaceError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      InterfaceError::VibraniumDirectoryNotFound(error) => Some(error),
+      InterfaceError::InvalidConfig(error) => Some(error),
+      InterfaceError::MissingArtifact(_name, _path) => None,
+      InterfaceError::InvalidAbi(_name, error) => Some(error),
+      InterfaceError::UnsupportedStandard(_standard) => None,
+      InterfaceError::NotDeployed(_address) => None,
+      InterfaceError::Connection(error) => Some(error),
+      InterfaceError::Io(error) => Some(error),
+    }
+  }
+}
+
+impl fmt::Display for InterfaceError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      InterfaceError::VibraniumDirectoryNotFound(error) => write!(f, "{}", error),
+      InterfaceError::InvalidConfig(error) => write!(f, "{}", error),
+      InterfaceError::MissingArtifact(name, path) => write!(f, "Couldn't find ABI of Smart Contract {} at {}. Please compile first.", name, path),
+      InterfaceError::InvalidAbi(name, error) => write!(f, "Couldn't read ABI of Smart Contract {}: {}", name, error),
+      InterfaceError::UnsupportedStandard(standard) => write!(f, "Unsupported interface standard '{}', expected 20, 721, 1155 or the path to an ABI file", standard),
+      InterfaceError::NotDeployed(address) => write!(f, "Couldn't check interface, there's no code at {}", address),
+      InterfaceError::Connection(error) => write!(f, "{}", error),
+      InterfaceError::Io(error) => write!(f, "Couldn't generate interface: {}", error),
+    }
+  }
+}
+
+impl From<config::error::ConfigError> for InterfaceError {
+  fn from(error: config::error::ConfigError) -> Self {
+    InterfaceError::InvalidConfig(error)
+  }
+}
+
+impl From<io::Error> for InterfaceError {
+  fn from(error: io::Error) -> Self {
+    InterfaceError::Io(error)
+  }
+}
+
+impl From<blockchain::error::ConnectionError> for InterfaceError {
+  fn from(error: blockchain::error::ConnectionError) -> Self {
+    InterfaceError::Connection(error)
+  }
+}
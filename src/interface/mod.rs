@@ -0,0 +1,304 @@
+pub mod compliance;
+pub mod error;
+
+use crate::config::Config;
+
+use std::fs;
+
+const ARTIFACT_EXTENSION_ABI: &str = "abi";
+const INTERFACE_PRAGMA: &str = "pragma solidity ^0.5.0;";
+const ABI_ENCODER_V2_PRAGMA: &str = "pragma experimental ABIEncoderV2;";
+
+pub struct InterfaceOptions {
+  pub contract: String,
+  pub name: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct GeneratedInterface {
+  pub name: String,
+  pub source: String,
+  pub abi: Vec<AbiEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AbiEntry {
+  #[serde(rename = "type", default = "default_entry_type")]
+  pub kind: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub inputs: Option<Vec<AbiParam>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub outputs: Option<Vec<AbiParam>>,
+  #[serde(rename = "stateMutability", skip_serializing_if = "Option::is_none")]
+  pub state_mutability: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub constant: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub payable: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub anonymous: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AbiParam {
+  #[serde(default)]
+  pub name: String,
+  #[serde(rename = "type")]
+  pub kind: String,
+  #[serde(rename = "internalType", skip_serializing_if = "Option::is_none")]
+  pub internal_type: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub components: Option<Vec<AbiParam>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub indexed: Option<bool>,
+}
+
+impl GeneratedInterface {
+  pub fn abi_json(&self) -> Result<String, error::InterfaceError> {
+    serde_json::to_string(&self.abi).map_err(|err| error::InterfaceError::InvalidAbi(self.name.clone(), err))
+  }
+}
+
+fn default_entry_type() -> String {
+  "function".to_string()
+}
+
+pub struct InterfaceGenerator<'a> {
+  config: &'a Config,
+}
+
+impl<'a> InterfaceGenerator<'a> {
+  pub fn new(config: &'a Config) -> InterfaceGenerator<'a> {
+    InterfaceGenerator {
+      config
+    }
+  }
+
+  pub fn generate(&self, options: InterfaceOptions) -> Result<GeneratedInterface, error::InterfaceError> {
+    let abi = self.read_abi(&options.contract)?;
+    let contract = options.contract;
+    let name = options.name.unwrap_or_else(|| format!("I{}", &contract));
+    Ok(generate_interface(name, abi))
+  }
+
+  pub fn read_abi(&self, contract: &str) -> Result<Vec<AbiEntry>, error::InterfaceError> {
+    let project_config = self.config.read()?;
+    let abi_path = self.config.project_path
+      .join(&project_config.sources.artifacts)
+      .join(format!("{}.{}", contract, ARTIFACT_EXTENSION_ABI));
+
+    if !abi_path.exists() {
+      return Err(error::InterfaceError::MissingArtifact(contract.to_string(), abi_path.to_string_lossy().to_string()));
+    }
+
+    serde_json::from_str(&fs::read_to_string(&abi_path)?)
+      .map_err(|err| error::InterfaceError::InvalidAbi(contract.to_string(), err))
+  }
+}
+
+// Constructors can't be part of an interface, so they are dropped from the
+// generated source as well as from its ABI.
+pub fn generate_interface(name: String, abi: Vec<AbiEntry>) -> GeneratedInterface {
+  let abi: Vec<AbiEntry> = abi.into_iter().filter(|entry| entry.kind != "constructor").collect();
+
+  let mut structs = vec![];
+  for entry in &abi {
+    for param in entry.inputs.iter().chain(entry.outputs.iter()).flatten() {
+      collect_structs(param, &mut structs);
+    }
+  }
+
+  let mut source = format!("{}\n", INTERFACE_PRAGMA);
+  if !structs.is_empty() {
+    source.push_str(&format!("{}\n", ABI_ENCODER_V2_PRAGMA));
+  }
+  source.push_str(&format!("\ninterface {} {{\n", &name));
+
+  for (struct_name, components) in &structs {
+    source.push_str(&format!("  struct {} {{\n", struct_name));
+    for component in components {
+      source.push_str(&format!("    {} {};\n", solidity_type(component), component.name));
+    }
+    source.push_str("  }\n\n");
+  }
+
+  for entry in abi.iter().filter(|entry| entry.kind == "event") {
+    source.push_str(&format!("  {}\n", event_declaration(entry)));
+  }
+
+  for entry in abi.iter().filter(|entry| entry.kind == "function" || entry.kind == "fallback") {
+    source.push_str(&format!("  {}\n", function_declaration(entry)));
+  }
+
+  source.push_str("}\n");
+
+  GeneratedInterface {
+    name,
+    source,
+    abi,
+  }
+}
+
+fn event_declaration(entry: &AbiEntry) -> String {
+  let params: Vec<String> = entry.inputs.iter().flatten().map(|param| {
+    let indexed = if param.indexed.unwrap_or(false) { "indexed" } else { "" };
+    join_words(&[&solidity_type(param), indexed, &param.name])
+  }).collect();
+
+  let anonymous = if entry.anonymous.unwrap_or(false) { " anonymous" } else { "" };
+  format!("event {}({}){};", entry.name.as_deref().unwrap_or_default(), params.join(", "), anonymous)
+}
+
+fn function_declaration(entry: &AbiEntry) -> String {
+  let mutability = state_mutability(entry);
+
+  if entry.kind == "fallback" {
+    return format!("function() {};", join_words(&["external", &mutability]));
+  }
+
+  let inputs: Vec<String> = entry.inputs.iter().flatten()
+    .map(|param| join_words(&[&solidity_type(param), data_location(param, "calldata"), &param.name]))
+    .collect();
+  let outputs: Vec<String> = entry.outputs.iter().flatten()
+    .map(|param| join_words(&[&solidity_type(param), data_location(param, "memory"), &param.name]))
+    .collect();
+
+  let mut declaration = format!("function {}({}) {}", entry.name.as_deref().unwrap_or_default(), inputs.join(", "), join_words(&["external", &mutability]));
+  if !outputs.is_empty() {
+    declaration.push_str(&format!(" returns ({})", outputs.join(", ")));
+  }
+  declaration.push(';');
+  declaration
+}
+
+// ABIs generated by older compilers don't have `stateMutability` yet and only
+// flag functions as `constant` or `payable`.
+fn state_mutability(entry: &AbiEntry) -> String {
+  match entry.state_mutability.as_deref() {
+    Some("nonpayable") => String::new(),
+    Some(mutability) => mutability.to_string(),
+    None if entry.constant.unwrap_or(false) => "view".to_string(),
+    None if entry.payable.unwrap_or(false) => "payable".to_string(),
+    None => String::new(),
+  }
+}
+
+fn data_location(param: &AbiParam, location: &'static str) -> &'static str {
+  if param.kind == "string" || param.kind == "bytes" || param.kind.ends_with(']') || param.kind.starts_with("tuple") {
+    location
+  } else {
+    ""
+  }
+}
+
+// Tuples are declared as structs, named after their `internalType` if the
+// compiler emitted one. Enums aren't part of the ABI and stay `uint8`.
+fn solidity_type(param: &AbiParam) -> String {
+  if !param.kind.starts_with("tuple") {
+    return param.kind.to_owned();
+  }
+  let array_suffix = param.kind.trim_start_matches("tuple");
+  format!("{}{}", struct_name(param), array_suffix)
+}
+
+fn struct_name(param: &AbiParam) -> String {
+  param.internal_type.as_ref()
+    .filter(|internal_type| internal_type.starts_with("struct "))
+    .map(|internal_type| {
+      let name = internal_type.trim_start_matches("struct ");
+      let name = name.split('[').next().unwrap_or(name);
+      name.rsplit('.').next().unwrap_or(name).to_string()
+    })
+    .unwrap_or_else(|| {
+      let mut name = param.name.trim_start_matches('_').to_string();
+      if let Some(first) = name.get_mut(0..1) {
+        first.make_ascii_uppercase();
+      }
+      format!("{}Struct", name)
+    })
+}
+
+fn collect_structs(param: &AbiParam, structs: &mut Vec<(String, Vec<AbiParam>)>) {
+  if let Some(components) = &param.components {
+    for component in components {
+      collect_structs(component, structs);
+    }
+    let name = struct_name(param);
+    if !structs.iter().any(|(existing, _components)| existing == &name) {
+      structs.push((name, components.to_vec()));
+    }
+  }
+}
+
+fn join_words(words: &[&str]) -> String {
+  words.iter().filter(|word| !word.is_empty()).cloned().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{generate_interface, AbiEntry};
+
+  fn abi(json: &str) -> Vec<AbiEntry> {
+    serde_json::from_str(json).unwrap()
+  }
+
+  #[test]
+  fn it_should_generate_functions_and_drop_constructor() {
+    let interface = generate_interface("ISimple".to_string(), abi(r#"[
+      {"constant":true,"inputs":[],"name":"get","outputs":[{"name":"retVal","type":"uint256"}],"payable":false,"stateMutability":"view","type":"function"},
+      {"constant":false,"inputs":[{"name":"x","type":"uint256"},{"name":"note","type":"string"}],"name":"set","outputs":[],"payable":false,"stateMutability":"nonpayable","type":"function"},
+      {"inputs":[{"name":"initialValue","type":"uint256"}],"payable":false,"stateMutability":"nonpayable","type":"constructor"}
+    ]"#));
+
+    assert_eq!(interface.source, "pragma solidity ^0.5.0;
+
+interface ISimple {
+  function get() external view returns (uint256 retVal);
+  function set(uint256 x, string calldata note) external;
+}
+");
+    assert_eq!(interface.abi.len(), 2);
+  }
+
+  #[test]
+  fn it_should_generate_events() {
+    let interface = generate_interface("IToken".to_string(), abi(r#"[
+      {"anonymous":false,"inputs":[{"indexed":true,"name":"from","type":"address"},{"indexed":false,"name":"value","type":"uint256"}],"name":"Transfer","type":"event"}
+    ]"#));
+
+    assert!(interface.source.contains("  event Transfer(address indexed from, uint256 value);\n"));
+  }
+
+  #[test]
+  fn it_should_declare_structs_used_in_signatures() {
+    let interface = generate_interface("IOrders".to_string(), abi(r#"[
+      {"inputs":[{"name":"orders","type":"tuple[]","internalType":"struct Orders.Order[]","components":[
+        {"name":"maker","type":"address","internalType":"address"},
+        {"name":"asset","type":"tuple","internalType":"struct Orders.Asset","components":[{"name":"token","type":"address"},{"name":"amount","type":"uint256"}]},
+        {"name":"side","type":"uint8","internalType":"enum Orders.Side"}
+      ]}],"name":"fill","outputs":[{"name":"","type":"tuple","internalType":"struct Orders.Asset","components":[{"name":"token","type":"address"},{"name":"amount","type":"uint256"}]}],"stateMutability":"payable","type":"function"}
+    ]"#));
+
+    assert_eq!(interface.source, "pragma solidity ^0.5.0;
+pragma experimental ABIEncoderV2;
+
+interface IOrders {
+  struct Asset {
+    address token;
+    uint256 amount;
+  }
+
+  struct Order {
+    address maker;
+    Asset asset;
+    uint8 side;
+  }
+
+  function fill(Order[] calldata orders) external payable returns (Asset memory);
+}
+");
+  }
+}
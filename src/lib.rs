@@ -10,22 +10,47 @@ extern crate sha3;
 extern crate toml;
 extern crate toml_query;
 
+pub mod access_report;
+pub mod artifacts;
 pub mod blockchain;
+pub mod build;
 pub mod project_generator;
 pub mod compiler;
 pub mod config;
+pub mod daemon;
 pub mod deployment;
+pub mod doctor;
+pub mod history;
+pub mod interface;
+pub mod lsp;
+pub mod package;
+pub mod policy;
+pub mod process;
+pub mod reproduce;
+pub mod secrets;
+pub mod stats;
+pub mod testing;
+pub mod test_runner;
+pub mod verification;
 mod utils;
 
 use std::process::{ExitStatus, Output};
-use std::path::PathBuf;
-use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 use blockchain::connector as connector;
-use web3::types::Address;
+use sha3::{Digest, Keccak256};
+
+const GENESIS_POLL_ATTEMPTS: usize = 60;
+const GENESIS_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Debug)]
 pub struct Vibranium {
   project_path: PathBuf,
+  read_only: bool,
+  rpc_cassette: Option<connector::cassette::RpcCassette>,
+  call_cache: bool,
+  environment: Option<std::collections::BTreeMap<String, String>>,
   pub config: config::Config,
 }
 
@@ -34,28 +59,256 @@ impl Vibranium {
     Vibranium {
       config: config::Config::new(project_path.clone()),
       project_path,
+      read_only: false,
+      rpc_cassette: None,
+      call_cache: true,
+      environment: None,
     }
   }
 
+  pub fn with_read_only(mut self, read_only: bool) -> Vibranium {
+    self.read_only = read_only;
+    self
+  }
+
+  // Records all JSON-RPC traffic to a cassette file, or serves it from one
+  // instead of talking to a node.
+  pub fn with_rpc_cassette(mut self, rpc_cassette: Option<connector::cassette::RpcCassette>) -> Vibranium {
+    self.rpc_cassette = rpc_cassette;
+    self
+  }
+
+  // Results of `eth_call` requests are cached in `.vibranium/call-cache.json`
+  // unless disabled here. RPC cassettes always see every request.
+  pub fn with_call_cache(mut self, call_cache: bool) -> Vibranium {
+    self.call_cache = call_cache;
+    self
+  }
+
+  // Environment variables `${env.NAME}` references and test commands see
+  // instead of the ones of the process, e.g. the environment of a client
+  // whose request is served by the daemon.
+  pub fn with_environment(mut self, environment: Option<std::collections::BTreeMap<String, String>>) -> Vibranium {
+    self.environment = environment;
+    self
+  }
+
+  // Keeps the parsed project configuration in memory, see `Config::with_cache()`.
+  pub fn with_config_cache(mut self, enabled: bool) -> Vibranium {
+    self.config = std::mem::take(&mut self.config).with_cache(enabled);
+    self
+  }
+
+  // Applies the options of the given `[profile.<name>]` on top of the
+  // project configuration.
+  pub fn with_profile(mut self, profile: Option<String>) -> Vibranium {
+    self.config.profile = profile;
+    self
+  }
+
+  // Connects to the given `[networks.<name>]` and records its name along
+  // with tracked deployments.
+  pub fn with_network(mut self, network: Option<String>) -> Vibranium {
+    self.config.network = network;
+    self
+  }
+
+  // Read-only mode is either turned on explicitly or via the project's
+  // `read_only` config option.
+  pub fn is_read_only(&self) -> bool {
+    self.read_only || self.config.read().ok().and_then(|project_config| project_config.read_only).unwrap_or(false)
+  }
+
   pub fn start_node(&self, config: blockchain::NodeConfig) -> Result<ExitStatus, blockchain::error::NodeError> {
     let generator = project_generator::ProjectGenerator::new(&self.config);
     generator
       .check_vibranium_dir_exists()
       .map_err(|error| blockchain::error::NodeError::Other(error.to_string()))
       .and_then(|_| {
+        let network = config.network.clone().unwrap_or_else(|| project_generator::DEFAULT_ENVIRONMENT.to_string());
+        self.record_genesis_in_background(network);
         let node = blockchain::Node::new(&self.config);
         node.start(config)
-          .map(|mut process| process.wait().map_err(blockchain::error::NodeError::Io))
-          .and_then(|status| status)
       })
   }
 
+  // Managed nodes run in the background. Their genesis block is recorded
+  // before returning, which also makes sure the node is ready to be used.
+  pub fn start_managed_node(&self, config: blockchain::NodeConfig) -> Result<blockchain::supervisor::ManagedNode, blockchain::error::NodeError> {
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    generator
+      .check_vibranium_dir_exists()
+      .map_err(|error| blockchain::error::NodeError::Other(error.to_string()))?;
+
+    let network = config.network.clone().unwrap_or_else(|| project_generator::DEFAULT_ENVIRONMENT.to_string());
+    let supervisor = blockchain::supervisor::NodeSupervisor::new(&self.config);
+    let node = supervisor.start(config)?;
+
+    if node.rpc_port.is_some() {
+      if let Some(vibranium) = self.genesis_recorder(&network) {
+        let is_running = || supervisor.status().ok().flatten().is_some();
+        if !vibranium.record_genesis(&network, is_running) {
+          return Err(blockchain::error::NodeError::Other(format!("Couldn't connect to node (pid {}), see {} for its output", node.pid, supervisor.log_file().to_string_lossy())));
+        }
+      }
+    }
+    Ok(node)
+  }
+
+  pub fn stop_node(&self) -> Result<Option<blockchain::supervisor::ManagedNode>, blockchain::error::NodeError> {
+    blockchain::supervisor::NodeSupervisor::new(&self.config).stop()
+  }
+
+  pub fn node_status(&self) -> Result<Option<blockchain::supervisor::ManagedNode>, blockchain::error::NodeError> {
+    blockchain::supervisor::NodeSupervisor::new(&self.config).status()
+  }
+
+  pub fn node_logs(&self, lines: usize) -> Result<Vec<String>, blockchain::error::NodeError> {
+    blockchain::supervisor::NodeSupervisor::new(&self.config).logs(lines)
+  }
+
+  // Serves the Language Server Protocol over stdio until the client exits.
+  pub fn serve_language_server(&self) -> Result<(), lsp::error::LspError> {
+    let stdin = std::io::stdin();
+    lsp::LanguageServer::new(self).run(stdin.lock(), std::io::stdout())
+  }
+
+  // Runs the project's daemon in the foreground.
+  pub fn run_daemon(&self) -> Result<(), daemon::error::DaemonError> {
+    daemon::Daemon::new(self.project_path.clone()).serve()
+  }
+
+  pub fn start_daemon(&self, executable: &Path) -> Result<daemon::DaemonState, daemon::error::DaemonError> {
+    project_generator::ProjectGenerator::new(&self.config)
+      .check_vibranium_dir_exists()
+      .map_err(|error| daemon::error::DaemonError::Failed(error.to_string()))?;
+    daemon::DaemonSupervisor::new(&self.config).start(executable)
+  }
+
+  pub fn stop_daemon(&self) -> Result<Option<daemon::DaemonState>, daemon::error::DaemonError> {
+    daemon::DaemonSupervisor::new(&self.config).stop()
+  }
+
+  pub fn daemon_status(&self) -> Result<Option<daemon::DaemonState>, daemon::error::DaemonError> {
+    daemon::DaemonSupervisor::new(&self.config).status()
+  }
+
+  // A client for the project's daemon, if one is running. RPC cassettes and
+  // disabled call caches only apply to this process, so requests using
+  // them are never forwarded.
+  pub fn daemon_client(&self) -> Option<daemon::DaemonClient> {
+    if self.rpc_cassette.is_some() || !self.call_cache {
+      return None;
+    }
+    daemon::DaemonClient::connect(&self.config, daemon::Session {
+      profile: self.config.profile.clone(),
+      network: self.config.network.clone(),
+      read_only: self.read_only,
+    })
+  }
+
+  fn record_genesis_in_background(&self, network: String) {
+    if let Some(vibranium) = self.genesis_recorder(&network) {
+      thread::spawn(move || {
+        vibranium.record_genesis(&network, || true);
+      });
+    }
+  }
+
+  // Nodes that aren't reachable via localhost can't be the one just spawned.
+  fn genesis_recorder(&self, network: &str) -> Option<Vibranium> {
+    let mut vibranium = Vibranium::new(self.project_path.clone()).with_profile(self.config.profile.clone());
+    let is_configured = vibranium.config.read().ok()
+      .and_then(|project_config| project_config.networks)
+      .map(|networks| networks.contains_key(network))
+      .unwrap_or(false);
+    if is_configured {
+      vibranium = vibranium.with_network(Some(network.to_string()));
+    }
+
+    let is_local = vibranium.config.read().ok()
+      .and_then(|project_config| project_config.blockchain)
+      .and_then(|blockchain_config| blockchain_config.connector)
+      .map(|connector_config| utils::is_localhost(&connector_config.host))
+      .unwrap_or(false);
+    if is_local {
+      Some(vibranium)
+    } else {
+      None
+    }
+  }
+
+  // Waits for the spawned node to come up and remembers the genesis block of
+  // its chain, so that `reset_chain()` can prune the chain's tracking data.
+  fn record_genesis(&self, network: &str, keep_waiting: impl Fn() -> bool) -> bool {
+    for _ in 0..GENESIS_POLL_ATTEMPTS {
+      thread::sleep(GENESIS_POLL_INTERVAL);
+      let block_hash = self.get_blockchain_connector().ok()
+        .and_then(|(_eloop, connector)| connector.get_first_block().ok())
+        .and_then(|block| block.and_then(|block| block.hash));
+
+      if let Some(block_hash) = block_hash {
+        if let Err(err) = blockchain::chains::record_genesis(&self.config.vibranium_dir_path, network, &block_hash) {
+          warn!("Couldn't record genesis block of network {}: {}", network, err);
+        }
+        return true;
+      }
+      if !keep_waiting() {
+        return false;
+      }
+    }
+    false
+  }
+
+  pub fn reset_chain(&self, network: &str) -> Result<usize, project_generator::error::ProjectGenerationError> {
+    if self.is_read_only() {
+      return Err(project_generator::error::ProjectGenerationError::ReadOnlyMode);
+    }
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    let removed = generator.reset_chain(network)?;
+    self.clear_call_cache();
+    Ok(removed)
+  }
+
+  pub fn doctor(&self) -> doctor::DoctorReport {
+    let doctor = doctor::Doctor::new(&self.config);
+    doctor.report(doctor.run_checks())
+  }
+
   pub fn init_project(&self) -> Result<(), project_generator::error::ProjectGenerationError> {
     let generator = project_generator::ProjectGenerator::new(&self.config);
     generator.generate_project(&self.project_path)
   }
 
+  pub fn generate_frontend(&self) -> Result<(), project_generator::error::ProjectGenerationError> {
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    generator.generate_frontend(&self.project_path)
+  }
+
+  pub fn generate_package_json(&self) -> Result<bool, project_generator::error::ProjectGenerationError> {
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    generator.generate_package_json(&self.project_path)
+  }
+
+  pub fn package_manager(&self, requested: Option<&str>) -> Result<project_generator::package_manager::PackageManager, project_generator::error::ProjectGenerationError> {
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    generator.package_manager(&self.project_path, requested)
+  }
+
+  pub fn js_dependency_directories(&self) -> Vec<PathBuf> {
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    generator.js_dependency_directories(&self.project_path)
+  }
+
+  pub fn install_js_dependencies(&self, directory: &Path, package_manager: project_generator::package_manager::PackageManager) -> Result<(), project_generator::error::ProjectGenerationError> {
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    generator.install_js_dependencies(directory, package_manager)
+  }
+
   pub fn reset_project(&self, reset_options: project_generator::ResetOptions) -> Result<(), project_generator::error::ProjectGenerationError> {
+    if self.is_read_only() {
+      return Err(project_generator::error::ProjectGenerationError::ReadOnlyMode);
+    }
     let generator = project_generator::ProjectGenerator::new(&self.config);
     generator
       .reset_project(&self.project_path, reset_options)
@@ -63,22 +316,37 @@ impl Vibranium {
   }
 
   pub fn set_config(&self, option: String, value: toml::Value) -> Result<(), config::error::ConfigError> {
+    self.set_config_with(option, value, config::WriteOptions::default()).map(|_change| ())
+  }
+
+  pub fn set_config_with(&self, option: String, value: toml::Value, options: config::WriteOptions) -> Result<config::ConfigChange, config::error::ConfigError> {
     let generator = project_generator::ProjectGenerator::new(&self.config);
     generator
       .check_vibranium_dir_exists()
       .map_err(|error| config::error::ConfigError::Other(error.to_string()))
-      .and_then(|_| self.config.write(option, value))
+      .and_then(|_| self.config.write_with(option, value, options))
   }
 
   pub fn unset_config(&self, option: String) -> Result<(), config::error::ConfigError> {
+    self.unset_config_with(option, config::WriteOptions::default()).map(|_change| ())
+  }
+
+  pub fn unset_config_with(&self, option: String, options: config::WriteOptions) -> Result<config::ConfigChange, config::error::ConfigError> {
     let generator = project_generator::ProjectGenerator::new(&self.config);
     generator
       .check_vibranium_dir_exists()
       .map_err(|error| config::error::ConfigError::Other(error.to_string()))
-      .and_then(|_| self.config.remove(option))
+      .and_then(|_| self.config.remove_with(option, options))
   }
 
   pub fn compile(&self, config: compiler::CompilerConfig) -> Result<Output, compiler::error::CompilerError> {
+    let (started_at, started) = (SystemTime::now(), Instant::now());
+    let result = self.run_compiler(config);
+    self.record_run(stats::Run::finished(stats::RunKind::Compile, started_at, started, result.is_ok(), None));
+    result
+  }
+
+  fn run_compiler(&self, config: compiler::CompilerConfig) -> Result<Output, compiler::error::CompilerError> {
     let compiler = compiler::Compiler::new(&self.config);
     let generator = project_generator::ProjectGenerator::new(&self.config);
 
@@ -86,11 +354,23 @@ impl Vibranium {
       .check_vibranium_dir_exists()
       .map_err(compiler::error::CompilerError::VibraniumDirectoryNotFound)
       .and_then(|_| {
-        compiler.compile(config).map(|process| {
-          process.wait_with_output().map_err(compiler::error::CompilerError::Io)
-        })
+        let failures: Vec<compiler::checks::Diagnostic> = compiler.run_static_checks()?
+          .into_iter()
+          .filter(|diagnostic| diagnostic.severity == compiler::checks::Severity::Error)
+          .collect();
+        if !failures.is_empty() {
+          return Err(compiler::error::CompilerError::StaticChecksFailed(failures));
+        }
+        Ok(())
+      })
+      .and_then(|_| {
+        let (compiler_cmd, compiler_options) = compiler.resolve_command(&config)?;
+        let output = compiler.compile(&config)?;
+        if output.status.success() {
+          artifacts::Artifacts::new(&self.config).normalize(&compiler_cmd, &compiler_options)?;
+        }
+        Ok(output)
       })
-      .and_then(|output| output)
       .and_then(|output| {
         if !output.status.success() {
           Err(compiler::error::CompilerError::Other(String::from_utf8_lossy(&output.stderr).to_string()))
@@ -98,29 +378,261 @@ impl Vibranium {
           Ok(output)
         }
       })
+      .and_then(|output| compiler.apply_warning_policy(output))
+      .and_then(|output| {
+        compiler.update_build_cache(&config)?;
+        Ok(output)
+      })
+      .and_then(|output| {
+        if compiler.size_enforcement()? == compiler::SizeEnforcement::Error {
+          let violations = compiler.check_size_budgets()?;
+          if !violations.is_empty() {
+            return Err(compiler::error::CompilerError::SizeBudgetExceeded(violations));
+          }
+        }
+        Ok(output)
+      })
+  }
+
+  // Compiles the project twice, bypassing the build cache, and reports the
+  // artifacts that differ between both builds. With `isolated`, the second
+  // build runs from a copy of the project at a temporary path. The artifacts
+  // of the first build are kept.
+  pub fn check_determinism(&self, config: compiler::CompilerConfig, isolated: bool) -> Result<Vec<compiler::determinism::ArtifactDifference>, compiler::error::CompilerError> {
+    let config = compiler::CompilerConfig { force: true, ..config };
+    let artifacts = self.config.read()?.sources.artifacts;
+
+    self.compile(config.clone())?;
+    let first = compiler::determinism::snapshot(&self.project_path.join(&artifacts))?;
+
+    if !isolated {
+      self.compile(config)?;
+      let second = compiler::determinism::snapshot(&self.project_path.join(&artifacts))?;
+      return Ok(compiler::determinism::compare(&first, &self.project_path, &second, &self.project_path));
+    }
+
+    let since_epoch = SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+    let copy_path = std::env::temp_dir().join(format!("vibranium-build-{}-{}", std::process::id(), since_epoch.as_nanos()));
+    let second = compiler::determinism::copy_project(&self.project_path, &copy_path, &artifacts)
+      .map_err(compiler::error::CompilerError::Io)
+      .and_then(|_| {
+        let mut copy = Vibranium::new(copy_path.clone());
+        copy.config.profile = self.config.profile.clone();
+        copy.config.network = self.config.network.clone();
+        copy.run_compiler(config)
+      })
+      .and_then(|_| compiler::determinism::snapshot(&copy_path.join(&artifacts)));
+    let _ = std::fs::remove_dir_all(&copy_path);
+
+    Ok(compiler::determinism::compare(&first, &self.project_path, &second?, &copy_path))
+  }
+
+  pub fn check_size_budgets(&self) -> Result<Vec<compiler::SizeBudgetViolation>, compiler::error::CompilerError> {
+    let compiler = compiler::Compiler::new(&self.config);
+    compiler.check_size_budgets()
+  }
+
+  pub fn run_static_checks(&self) -> Result<Vec<compiler::checks::Diagnostic>, compiler::error::CompilerError> {
+    let compiler = compiler::Compiler::new(&self.config);
+    compiler.run_static_checks()
+  }
+
+  pub fn watch_sources(&self) -> Result<compiler::watcher::SourceWatcher<'_>, compiler::error::CompilerError> {
+    compiler::watcher::SourceWatcher::new(&self.config)
   }
 
   pub fn get_blockchain_connector(&self) -> Result<(web3::transports::EventLoopHandle, connector::BlockchainConnector), blockchain::error::ConnectionError> {
+    self.blockchain_connector(self.call_cache)
+  }
+
+  // Deployments, upgrades and tests change the chain state that cached calls
+  // were made against, so they always talk to the node directly.
+  fn uncached_blockchain_connector(&self) -> Result<(web3::transports::EventLoopHandle, connector::BlockchainConnector), blockchain::error::ConnectionError> {
+    self.blockchain_connector(false)
+  }
+
+  fn blockchain_connector(&self, call_cache: bool) -> Result<(web3::transports::EventLoopHandle, connector::BlockchainConnector), blockchain::error::ConnectionError> {
     let generator = project_generator::ProjectGenerator::new(&self.config);
 
     generator
       .check_vibranium_dir_exists()
       .map_err(|err| blockchain::error::ConnectionError::Other(err.to_string()))
       .and_then(|_| {
+        if let Some(connector::cassette::RpcCassette::Replay(path)) = &self.rpc_cassette {
+          let cassette = connector::cassette::Cassette::load(path)?;
+          let (eloop, _) = web3::transports::EventLoopHandle::spawn(|_handle| Ok(())).map_err(blockchain::error::ConnectionError::Transport)?;
+          let transport = connector::web3_adapter::Transports::Replay(connector::cassette::ReplayTransport::new(cassette));
+          return Ok((eloop, connector::BlockchainConnector::new(connector::web3_adapter::Web3Adapter::with_transport(transport))));
+        }
+
         let project_config = self.config.read().map_err(|err| blockchain::error::ConnectionError::Other(err.to_string()))?;
         let blockchain_config = project_config.blockchain.ok_or(blockchain::error::ConnectionError::MissingConnectorConfig)?;
-        let connector_config = blockchain_config.connector.ok_or(blockchain::error::ConnectionError::MissingConnectorConfig)?;
-        let (eloop, adapter) = connector::web3_adapter::Web3Adapter::new(connector_config)?;
-        let blockchain_connector = connector::BlockchainConnector::new(adapter);
+        let mut connector_config = blockchain_config.connector.ok_or(blockchain::error::ConnectionError::MissingConnectorConfig)?;
+        connector_config.host = self.resolve_env_references(&connector_config.host).map_err(blockchain::error::ConnectionError::MissingEnvVariable)?;
+        if let Some(url) = connector_config.url.take() {
+          connector_config.url = Some(self.resolve_env_references(&url).map_err(blockchain::error::ConnectionError::MissingEnvVariable)?);
+        }
+        if let Some(fallbacks) = connector_config.fallbacks.take() {
+          connector_config.fallbacks = Some(fallbacks.iter()
+            .map(|url| self.resolve_env_references(url))
+            .collect::<Result<Vec<String>, String>>()
+            .map_err(blockchain::error::ConnectionError::MissingEnvVariable)?);
+        }
+        // A project has at most one managed node, which might not run on the
+        // configured port if that was taken.
+        if utils::is_localhost(&connector_config.host) {
+          let managed_port = blockchain::supervisor::NodeSupervisor::new(&self.config).status().ok().flatten()
+            .and_then(|node| node.port_for(&connector_config.protocol));
+          if let Some(port) = managed_port {
+            connector_config.port = port.to_string();
+            connector_config.url = None;
+          }
+        }
+        let receipt_polling = connector::polling::ReceiptPolling::from_config(&connector_config.receipts);
+        let call_cache_ttl = connector_config.call_cache_ttl.unwrap_or(connector::cache::DEFAULT_CALL_CACHE_TTL);
+        // Endpoints might contain API keys, so cached calls are only
+        // associated with their hash.
+        let endpoint = format!("{:x}", Keccak256::digest(connector_config.endpoint_url()?.as_bytes()));
+        let (eloop, mut adapter) = connector::web3_adapter::Web3Adapter::new(connector_config)?;
+        if let Some(connector::cassette::RpcCassette::Record(path)) = &self.rpc_cassette {
+          adapter = adapter.recording_to(path.clone());
+        } else if call_cache && call_cache_ttl > 0 {
+          adapter = adapter.caching_calls(self.config.vibranium_dir_path.join(connector::cache::CALL_CACHE_FILE), endpoint, call_cache_ttl);
+        }
+        let blockchain_connector = connector::BlockchainConnector::new(adapter).with_receipt_polling(receipt_polling);
         Ok((eloop, blockchain_connector))
       })
   }
 
-  pub fn deploy(&self, options: deployment::DeployOptions) -> Result<HashMap<Address, (String, Address, String, bool)>, deployment::error::DeploymentError> {
+  pub fn deploy(&self, options: deployment::DeployOptions) -> Result<deployment::DeployedContracts, deployment::error::DeploymentError> {
+    self.run_deployer(options, None)
+  }
+
+  pub fn deploy_with_signer(&self, options: deployment::DeployOptions, signer: &dyn blockchain::signer::Signer) -> Result<deployment::DeployedContracts, deployment::error::DeploymentError> {
+    self.run_deployer(options, Some(signer))
+  }
+
+  pub fn upgrade(&self, name: &str, options: deployment::upgrades::UpgradeOptions) -> Result<deployment::upgrades::UpgradeReport, deployment::error::DeploymentError> {
+    self.check_before_deployment(options.require_tests)?;
+    let (_eloop, connector) = self.uncached_blockchain_connector().map_err(deployment::error::DeploymentError::Connection)?;
+    let tracker = deployment::tracker::DeploymentTracker::new(&self.config);
+    let deployer = deployment::Deployer::new(&self.config, &connector, &tracker);
+    deployer.upgrade(name, options)
+  }
+
+  fn run_deployer(&self, options: deployment::DeployOptions, signer: Option<&dyn blockchain::signer::Signer>) -> Result<deployment::DeployedContracts, deployment::error::DeploymentError> {
+    self.check_before_deployment(options.require_tests)?;
+    let (started_at, started) = (SystemTime::now(), Instant::now());
+    let (_eloop, connector) = self.uncached_blockchain_connector().map_err(deployment::error::DeploymentError::Connection)?;
+    let tracker = deployment::tracker::DeploymentTracker::new(&self.config);
+    let mut deployer = deployment::Deployer::new(&self.config, &connector, &tracker);
+    if let Some(signer) = signer {
+      deployer = deployer.with_signer(signer);
+    }
+    let result = deployer.deploy(options);
+    self.record_run(stats::Run::finished(stats::RunKind::Deploy, started_at, started, result.is_ok(), Some(deployer.gas_used().low_u64())));
+    result
+  }
+
+  pub fn rekey_tracking_data(&self) -> Result<(deployment::tracker::TrackingKeyStrategy, deployment::tracker::RekeyReport), deployment::error::DeploymentError> {
+    if self.is_read_only() {
+      return Err(deployment::error::DeploymentError::ReadOnlyMode);
+    }
     let (_eloop, connector) = self.get_blockchain_connector().map_err(deployment::error::DeploymentError::Connection)?;
     let tracker = deployment::tracker::DeploymentTracker::new(&self.config);
     let deployer = deployment::Deployer::new(&self.config, &connector, &tracker);
-    deployer.deploy(options)
+    deployer.rekey_tracking_data()
+  }
+
+  pub fn check_deployment_policy(&self) -> Result<Option<policy::PolicyReport>, deployment::error::DeploymentError> {
+    let (_eloop, connector) = self.get_blockchain_connector().map_err(deployment::error::DeploymentError::Connection)?;
+    let tracker = deployment::tracker::DeploymentTracker::new(&self.config);
+    let deployer = deployment::Deployer::new(&self.config, &connector, &tracker);
+    deployer.check_policy()
+  }
+
+  pub fn gas_report(&self) -> Result<deployment::gas::GasReport, deployment::error::DeploymentError> {
+    let (_eloop, connector) = self.get_blockchain_connector().map_err(deployment::error::DeploymentError::Connection)?;
+    let tracker = deployment::tracker::DeploymentTracker::new(&self.config);
+    let deployer = deployment::Deployer::new(&self.config, &connector, &tracker);
+    deployer.estimate_gas()
+  }
+
+  // Recompiles the project with its configured compiler settings before
+  // comparing the result with the deployed code.
+  pub fn reproduce(&self, name: &str) -> Result<Vec<reproduce::Reproduction>, reproduce::error::ReproduceError> {
+    self.compile(compiler::CompilerConfig {
+      compiler: None,
+      compiler_options: None,
+      replace_options: false,
+      force: true,
+      tags: None,
+    })?;
+    let (_eloop, connector) = self.get_blockchain_connector()?;
+    let reproducer = reproduce::Reproducer::new(&self.config, &connector);
+    reproducer.reproduce(name)
+  }
+
+  // Outcomes are recorded in the tracking data, unless in read-only mode.
+  pub fn verify(&self, name: Option<&str>) -> Result<Vec<reproduce::Verification>, reproduce::error::ReproduceError> {
+    let (_eloop, connector) = self.get_blockchain_connector()?;
+    let verifications = reproduce::Reproducer::new(&self.config, &connector).verify(name)?;
+    self.record_verifications(&connector, verifications)
+  }
+
+  pub fn verify_tagged(&self, tags: &[String]) -> Result<Vec<reproduce::Verification>, reproduce::error::ReproduceError> {
+    let (_eloop, connector) = self.get_blockchain_connector()?;
+    let verifications = reproduce::Reproducer::new(&self.config, &connector).verify_tagged(tags)?;
+    self.record_verifications(&connector, verifications)
+  }
+
+  pub fn verify_source(&self, name: &str, service: verification::VerificationService) -> Result<verification::SourceVerification, verification::error::VerificationError> {
+    let (_eloop, connector) = self.get_blockchain_connector()?;
+    let client = verification::http::CurlClient::new(&self.config.vibranium_dir_path);
+    verification::SourceVerifier::new(&self.config, &connector, &client).verify(name, service)
+  }
+
+  fn record_verifications(&self, connector: &blockchain::connector::BlockchainConnector, verifications: Vec<reproduce::Verification>) -> Result<Vec<reproduce::Verification>, reproduce::error::ReproduceError> {
+    if !self.is_read_only() {
+      if let Some(block_hash) = connector.get_first_block()?.and_then(|block| block.hash) {
+        let tracker = deployment::tracker::DeploymentTracker::new(&self.config);
+        for verification in &verifications {
+          if let Err(err) = tracker.record_verification(&block_hash, &verification.address, verification.status.label()) {
+            warn!("Couldn't record verification of {}: {}", verification.name, err);
+          }
+        }
+      }
+    }
+    Ok(verifications)
+  }
+
+  pub fn deployment_docs(&self) -> Result<String, deployment::error::DeploymentTrackingError> {
+    let project_config = self.config.read().map_err(|err| deployment::error::DeploymentTrackingError::Other(err.to_string()))?;
+    let tracker = deployment::tracker::DeploymentTracker::new(&self.config);
+    Ok(deployment::docs::render(&tracker.get_tracking_data_of_all_chains()?, &project_config.networks.unwrap_or_default()))
+  }
+
+  pub fn deployment_address_book(&self) -> Result<deployment::address_book::AddressBook, deployment::error::DeploymentTrackingError> {
+    let project_config = self.config.read().map_err(|err| deployment::error::DeploymentTrackingError::Other(err.to_string()))?;
+    let tracker = deployment::tracker::DeploymentTracker::new(&self.config);
+    Ok(deployment::address_book::AddressBook::new(&tracker.get_tracking_data_of_all_chains()?, &project_config.networks.unwrap_or_default()))
+  }
+
+  // Both networks need to be configured, so that typos don't go unnoticed as
+  // a network without any deployments.
+  pub fn deployment_diff(&self, from: &str, to: &str) -> Result<deployment::diff::DeploymentDiff, deployment::error::DeploymentTrackingError> {
+    let project_config = self.config.read().map_err(|err| deployment::error::DeploymentTrackingError::Other(err.to_string()))?;
+    let networks = project_config.networks.unwrap_or_default();
+    if let Some(network) = [from, to].iter().find(|network| !networks.contains_key(**network)) {
+      return Err(deployment::error::DeploymentTrackingError::Other(config::error::ConfigError::UnknownNetwork(network.to_string()).to_string()));
+    }
+    let tracker = deployment::tracker::DeploymentTracker::new(&self.config);
+    Ok(deployment::diff::DeploymentDiff::new(&tracker.get_tracking_data_of_all_chains()?, from, to))
+  }
+
+  pub fn address_aliases(&self) -> Result<deployment::aliases::AddressAliases, deployment::error::DeploymentTrackingError> {
+    let (_eloop, connector) = self.get_blockchain_connector()?;
+    deployment::aliases::AddressAliases::load(&self.config, &connector)
   }
 
   pub fn get_tracking_data(&self) -> Result<Option<deployment::tracker::SmartContractTrackingData>, deployment::error::DeploymentTrackingError> {
@@ -130,4 +642,408 @@ impl Vibranium {
       .map_err(|err| deployment::error::DeploymentTrackingError::Other(err.to_string()))
       .and_then(|block| tracker.get_all_smart_contract_tracking_data(&block.unwrap().hash.unwrap()))
   }
+
+  pub fn tag_deployment(&self, name: &str, tags: std::collections::BTreeMap<String, String>) -> Result<usize, deployment::error::DeploymentTrackingError> {
+    if self.is_read_only() {
+      return Err(deployment::error::DeploymentTrackingError::ReadOnlyMode);
+    }
+    let (_eloop, connector) = self.get_blockchain_connector()?;
+    let tracker = deployment::tracker::DeploymentTracker::new(&self.config);
+    connector.get_first_block()
+      .map_err(|err| deployment::error::DeploymentTrackingError::Other(err.to_string()))
+      .and_then(|block| tracker.tag(&block.unwrap().hash.unwrap(), name, tags))
+  }
+
+  pub fn remove_deployment(&self, name: &str) -> Result<usize, deployment::error::DeploymentTrackingError> {
+    if self.is_read_only() {
+      return Err(deployment::error::DeploymentTrackingError::ReadOnlyMode);
+    }
+    let (_eloop, connector) = self.get_blockchain_connector()?;
+    let tracker = deployment::tracker::DeploymentTracker::new(&self.config);
+    connector.get_first_block()
+      .map_err(|err| deployment::error::DeploymentTrackingError::Other(err.to_string()))
+      .and_then(|block| tracker.remove(&block.unwrap().hash.unwrap(), name))
+  }
+
+  // Doesn't connect to the network, so deployments to networks that are gone,
+  // e.g. reset testnets, can be cleared as well.
+  pub fn clear_deployments(&self, network: &str) -> Result<usize, deployment::error::DeploymentTrackingError> {
+    if self.is_read_only() {
+      return Err(deployment::error::DeploymentTrackingError::ReadOnlyMode);
+    }
+    deployment::tracker::DeploymentTracker::new(&self.config).clear_network(network)
+  }
+
+  pub fn snapshot_chain(&self) -> Result<web3::types::U256, blockchain::error::ChainControlError> {
+    if !is_dev_network(&self.config.read().map_err(|err| blockchain::error::ConnectionError::Other(err.to_string()))?) {
+      return Err(blockchain::error::ChainControlError::NotADevNetwork);
+    }
+    let (_eloop, connector) = self.get_blockchain_connector()?;
+    blockchain::chain_control::ChainControl::new(&connector).snapshot()
+  }
+
+  pub fn revert_chain(&self, snapshot: web3::types::U256) -> Result<(), blockchain::error::ChainControlError> {
+    if self.is_read_only() {
+      return Err(blockchain::error::ChainControlError::ReadOnlyMode);
+    }
+    if !is_dev_network(&self.config.read().map_err(|err| blockchain::error::ConnectionError::Other(err.to_string()))?) {
+      return Err(blockchain::error::ChainControlError::NotADevNetwork);
+    }
+    let (_eloop, connector) = self.uncached_blockchain_connector()?;
+    blockchain::chain_control::ChainControl::new(&connector).revert(snapshot)?;
+    self.clear_call_cache();
+    Ok(())
+  }
+
+  // Reverts the chain to the snapshot taken by the previous reset, so that
+  // the following deployment starts from the same state, and takes a new one.
+  // Tracked deployments that are gone afterwards are removed. Returns the
+  // amount of removed entries.
+  pub fn reset_deployments(&self) -> Result<usize, blockchain::error::ChainControlError> {
+    if self.is_read_only() {
+      return Err(blockchain::error::ChainControlError::ReadOnlyMode);
+    }
+    if !is_dev_network(&self.config.read().map_err(|err| blockchain::error::ConnectionError::Other(err.to_string()))?) {
+      return Err(blockchain::error::ChainControlError::NotADevNetwork);
+    }
+    let (_eloop, connector) = self.uncached_blockchain_connector()?;
+    let chain_control = blockchain::chain_control::ChainControl::new(&connector);
+    let block_hash = connector.get_first_block()?
+      .and_then(|block| block.hash)
+      .ok_or_else(|| blockchain::error::ConnectionError::Other("Couldn't read the genesis block".to_string()))?;
+
+    if let Some(snapshot) = blockchain::chain_control::read_reset_snapshot(&self.config.vibranium_dir_path, &block_hash) {
+      match chain_control.revert(snapshot) {
+        Err(blockchain::error::ChainControlError::UnknownSnapshot(snapshot)) => warn!("Couldn't revert to snapshot {}, it has been reverted to already or the node has been restarted", snapshot),
+        result => result?,
+      }
+      self.clear_call_cache();
+    }
+
+    let tracker = deployment::tracker::DeploymentTracker::new(&self.config);
+    let mut gone = vec![];
+    for (key, entry) in tracker.get_all_smart_contract_tracking_data(&block_hash)?.unwrap_or_default() {
+      if connector.code(entry.address, None)?.0.is_empty() {
+        gone.push(key);
+      }
+    }
+    let removed = tracker.remove_entries(&block_hash, &gone)?;
+
+    let snapshot = chain_control.snapshot()?;
+    blockchain::chain_control::record_reset_snapshot(&self.config.vibranium_dir_path, &block_hash, snapshot)?;
+    Ok(removed)
+  }
+
+  pub fn get_tracking_data_of_all_chains(&self) -> Result<std::collections::HashMap<String, deployment::tracker::SmartContractTrackingData>, deployment::error::DeploymentTrackingError> {
+    deployment::tracker::DeploymentTracker::new(&self.config).get_tracking_data_of_all_chains()
+  }
+
+  pub fn discover_children(&self, options: deployment::children::ChildrenOptions) -> Result<deployment::children::ChildrenReport, deployment::error::DeploymentTrackingError> {
+    if self.is_read_only() {
+      return Err(deployment::error::DeploymentTrackingError::ReadOnlyMode);
+    }
+    let (_eloop, connector) = self.get_blockchain_connector()?;
+    deployment::children::ChildDiscovery::new(&self.config, &connector).discover(&options)
+  }
+
+  pub fn events(&self, options: deployment::events::EventsOptions) -> Result<Vec<deployment::events::DecodedEvent>, deployment::error::DeploymentTrackingError> {
+    let (_eloop, connector) = self.get_blockchain_connector()?;
+    deployment::events::EventLog::new(&self.config, &connector).events(&options)
+  }
+
+  pub fn inspect_transaction(&self, hash: web3::types::H256) -> Result<deployment::inspect::TransactionInspection, deployment::error::DeploymentTrackingError> {
+    let (_eloop, connector) = self.get_blockchain_connector()?;
+    deployment::inspect::TransactionInspector::new(&self.config, &connector).inspect(hash)
+  }
+
+  pub fn follow_events<F: FnMut(&deployment::events::DecodedEvent)>(&self, options: deployment::events::EventsOptions, on_event: F) -> Result<(), deployment::error::DeploymentTrackingError> {
+    let (_eloop, connector) = self.get_blockchain_connector()?;
+    deployment::events::EventLog::new(&self.config, &connector).follow(&options, on_event)
+  }
+
+  pub fn package(&self, options: package::PackageOptions) -> Result<PathBuf, package::error::PackageError> {
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    generator
+      .check_vibranium_dir_exists()
+      .map_err(package::error::PackageError::VibraniumDirectoryNotFound)
+      .and_then(|_| {
+        let packager = package::Packager::new(&self.config);
+        packager.package(options)
+      })
+  }
+
+  pub fn generate_interface(&self, options: interface::InterfaceOptions) -> Result<interface::GeneratedInterface, interface::error::InterfaceError> {
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    generator
+      .check_vibranium_dir_exists()
+      .map_err(interface::error::InterfaceError::VibraniumDirectoryNotFound)
+      .and_then(|_| {
+        let interface_generator = interface::InterfaceGenerator::new(&self.config);
+        interface_generator.generate(options)
+      })
+  }
+
+  // Targets are either the name of a compiled Smart Contract or the address
+  // of a deployed one.
+  pub fn check_interface(&self, target: &str, standard: &str) -> Result<interface::compliance::ComplianceReport, interface::error::InterfaceError> {
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    generator.check_vibranium_dir_exists().map_err(interface::error::InterfaceError::VibraniumDirectoryNotFound)?;
+    let standard = interface::compliance::InterfaceStandard::resolve(standard, &self.config.project_path)?;
+
+    let findings = match target.strip_prefix("0x").and_then(|address| address.parse::<web3::types::Address>().ok()) {
+      Some(address) => {
+        let (_eloop, connector) = self.get_blockchain_connector()?;
+        interface::compliance::check_deployed(&standard, &connector, address)?
+      },
+      None => {
+        let abi = interface::InterfaceGenerator::new(&self.config).read_abi(target)?;
+        interface::compliance::check_abi(&standard, &abi)
+      },
+    };
+
+    Ok(interface::compliance::ComplianceReport {
+      contract: target.to_string(),
+      standard: standard.name,
+      findings,
+    })
+  }
+
+  // Compiles the Solidity tests in `test/` and runs them. If the configured
+  // node runs on localhost but isn't reachable, a managed node is started for
+  // the run and stopped afterwards. Tests send transactions, so they don't run
+  // in read-only mode.
+  pub fn run_tests(&self, options: test_runner::TestOptions) -> Result<test_runner::TestReport, test_runner::error::TestError> {
+    project_generator::ProjectGenerator::new(&self.config)
+      .check_vibranium_dir_exists()
+      .map_err(test_runner::error::TestError::VibraniumDirectoryNotFound)?;
+    if self.is_read_only() {
+      return Err(test_runner::error::TestError::ReadOnlyMode);
+    }
+    let project_config = self.config.read()?;
+    let connector_config = project_config.blockchain.as_ref().and_then(|config| config.connector.as_ref());
+    if !is_dev_network(&project_config) {
+      return Err(test_runner::error::TestError::NotADevNetwork(connector_config.map(|config| config.host.to_owned()).unwrap_or_default()));
+    }
+
+    let compiler = compiler::Compiler::new(&self.config);
+    let sources = compiler.source_files(&[test_runner::TEST_SOURCE_PATTERN.to_string()]);
+    if sources.is_empty() {
+      return Err(test_runner::error::TestError::NoTests(self.config.project_path.join(test_runner::TEST_DIRECTORY).to_string_lossy().to_string()));
+    }
+    // Artifacts of removed tests would otherwise still be discovered.
+    let artifacts_dir = self.config.vibranium_dir_path.join(test_runner::TEST_ARTIFACTS_DIRECTORY);
+    if artifacts_dir.exists() {
+      std::fs::remove_dir_all(&artifacts_dir)?;
+    }
+    std::fs::create_dir_all(&artifacts_dir)?;
+    let compiler_config = compiler::CompilerConfig {
+      compiler: None,
+      compiler_options: None,
+      replace_options: false,
+      force: true,
+      tags: None,
+    };
+    let output = compiler.compile_sources(&compiler_config, sources.clone(), &artifacts_dir)?;
+    if !output.status.success() {
+      return Err(test_runner::error::TestError::Compilation(compiler::error::CompilerError::Other(String::from_utf8_lossy(&output.stderr).to_string())));
+    }
+
+    let is_local = connector_config.is_some_and(|config| utils::is_localhost(&config.host));
+    let is_reachable = || self.get_blockchain_connector().ok().is_some_and(|(_eloop, connector)| connector.block_number().is_ok());
+    let started_node = if is_local && self.node_status().ok().flatten().is_none() && !is_reachable() {
+      info!("Starting a node for the tests...");
+      Some(self.start_managed_node(blockchain::NodeConfig {
+        client: None,
+        client_options: None,
+        network: self.config.network.clone(),
+      }).map_err(test_runner::error::TestError::Node)?)
+    } else {
+      None
+    };
+
+    let result = self.uncached_blockchain_connector()
+      .map_err(test_runner::error::TestError::Connection)
+      .and_then(|(_eloop, connector)| {
+        let runner = test_runner::TestRunner::new(&self.config, &connector);
+        let contracts = runner.discover(&artifacts_dir, &sources)?;
+        runner.run(&contracts, &options)
+      });
+    // Tests revert the chain after every test, and a node started for them
+    // is gone afterwards.
+    self.clear_call_cache();
+
+    if started_node.is_some() {
+      if let Err(err) = self.stop_node() {
+        warn!("Couldn't stop the node started for the tests: {}", err);
+      }
+    }
+    result
+  }
+
+  pub fn access_report(&self, options: access_report::AccessReportOptions) -> Result<Vec<access_report::AccessEntry>, access_report::error::AccessReportError> {
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    generator
+      .check_vibranium_dir_exists()
+      .map_err(access_report::error::AccessReportError::VibraniumDirectoryNotFound)
+      .and_then(|_| {
+        let reporter = access_report::AccessReporter::new(&self.config);
+        reporter.report(options)
+      })
+  }
+
+  // Commands run outside of a Vibranium project aren't recorded.
+  pub fn record_command(&self, args: Vec<String>) -> Result<(), history::error::HistoryError> {
+    if !self.config.vibranium_dir_path.exists() {
+      return Ok(());
+    }
+    match self.resolve_command(args) {
+      Ok(entry) => history::CommandHistory::new(&self.config).record(entry),
+      Err(_err) => Ok(()),
+    }
+  }
+
+  // Resolves the network and Smart Contracts of a command against the
+  // current configuration, including the selected profile and network.
+  pub fn resolve_command(&self, args: Vec<String>) -> Result<history::HistoryEntry, config::error::ConfigError> {
+    Ok(history::HistoryEntry::resolve(args, &self.config.read()?))
+  }
+
+  pub fn command_history(&self) -> Result<history::CommandHistory<'_>, project_generator::error::ProjectGenerationError> {
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    generator
+      .check_vibranium_dir_exists()
+      .map(|_| history::CommandHistory::new(&self.config))
+  }
+
+  pub fn usage_stats(&self) -> Result<stats::UsageStats<'_>, project_generator::error::ProjectGenerationError> {
+    let generator = project_generator::ProjectGenerator::new(&self.config);
+    generator
+      .check_vibranium_dir_exists()
+      .map(|_| stats::UsageStats::new(&self.config))
+  }
+
+  fn resolve_env_references(&self, value: &str) -> Result<String, String> {
+    match &self.environment {
+      Some(environment) => secrets::resolve_env_references_with(value, |name| environment.get(name).cloned()),
+      None => secrets::resolve_env_references(value),
+    }
+  }
+
+  // Calls pinned to a block number are cached for good, which only holds
+  // as long as the chain isn't reset or reverted.
+  fn clear_call_cache(&self) {
+    if let Err(err) = connector::cache::CallCache::clear(&self.config.vibranium_dir_path.join(connector::cache::CALL_CACHE_FILE)) {
+      warn!("{}", err);
+    }
+  }
+
+  // Failing to record statistics never fails the run itself.
+  fn record_run(&self, run: stats::Run) {
+    let stats = stats::UsageStats::new(&self.config);
+    if stats.is_enabled() {
+      if let Err(err) = stats.record(run) {
+        warn!("{}", err);
+      }
+    }
+  }
+
+  pub fn scan_secrets(&self) -> Result<Vec<secrets::SecretFinding>, config::error::ConfigError> {
+    let content = std::fs::read_to_string(&self.config.config_file)?;
+    let config: toml::Value = toml::from_str(&content).map_err(config::error::ConfigError::Deserialization)?;
+    Ok(secrets::scan(&config))
+  }
+
+  // Runs before anything is deployed, by any command.
+  fn check_before_deployment(&self, require_tests: Option<bool>) -> Result<(), deployment::error::DeploymentError> {
+    if self.is_read_only() {
+      return Err(deployment::error::DeploymentError::ReadOnlyMode);
+    }
+    self.check_secrets_before_deployment()?;
+    self.check_tests_before_deployment(require_tests)
+  }
+
+  // Embedded secrets are tolerated on local development chains only.
+  fn check_secrets_before_deployment(&self) -> Result<(), deployment::error::DeploymentError> {
+    let findings = self.scan_secrets().map_err(|err| deployment::error::DeploymentError::Other(err.to_string()))?;
+    if findings.is_empty() {
+      return Ok(());
+    }
+
+    let project_config = self.config.read().map_err(|err| deployment::error::DeploymentError::Other(err.to_string()))?;
+    if is_dev_network(&project_config) {
+      Ok(())
+    } else {
+      Err(deployment::error::DeploymentError::EmbeddedSecrets(findings))
+    }
+  }
+
+  // With `require_tests`, the project's tests have to pass before deploying
+  // to a non-development network. They're run by the built-in test runner on
+  // the development network, i.e. without the selected `--network`, unless
+  // `test_cmd` and `test_options` name a command to run instead. A
+  // `require_tests_filter` only requires the matching tests to pass.
+  fn check_tests_before_deployment(&self, require_tests: Option<bool>) -> Result<(), deployment::error::DeploymentError> {
+    let project_config = self.config.read().map_err(|err| deployment::error::DeploymentError::Other(err.to_string()))?;
+    let deployment_config = match &project_config.deployment {
+      Some(deployment_config) => deployment_config,
+      None => return Ok(()),
+    };
+    if !require_tests.unwrap_or(deployment_config.require_tests.unwrap_or(false)) || is_dev_network(&project_config) {
+      return Ok(());
+    }
+
+    let test_cmd = match &deployment_config.test_cmd {
+      Some(test_cmd) => test_cmd.to_owned(),
+      None => return self.run_required_tests(deployment_config.require_tests_filter.clone()),
+    };
+    let test_options = deployment_config.test_options.clone().unwrap_or_else(|| vec!["test".to_string()]);
+    let command = std::iter::once(test_cmd.clone()).chain(test_options.iter().cloned()).collect::<Vec<String>>().join(" ");
+
+    info!("Running `{}` before deploying...", command);
+    let status = process::ProcessCommand::new(test_cmd)
+      .args(test_options)
+      .environment(self.environment.clone())
+      .current_dir(&self.config.project_path)
+      .log_to(&self.config.vibranium_dir_path)
+      .status()
+      .map_err(|err| deployment::error::DeploymentError::TestsNotRun(command.clone(), err))?;
+
+    if status.success() {
+      Ok(())
+    } else {
+      Err(deployment::error::DeploymentError::TestsFailed(command))
+    }
+  }
+
+  fn run_required_tests(&self, filter: Option<String>) -> Result<(), deployment::error::DeploymentError> {
+    let command = match &filter {
+      Some(filter) => format!("vibranium test --filter {}", filter),
+      None => "vibranium test".to_string(),
+    };
+    info!("Running `{}` before deploying...", command);
+
+    let development = Vibranium::new(self.project_path.clone())
+      .with_profile(self.config.profile.clone())
+      .with_environment(self.environment.clone())
+      .with_call_cache(false);
+    let report = development.run_tests(test_runner::TestOptions { filter: filter.clone() })
+      .map_err(deployment::error::DeploymentError::TestRunner)?;
+
+    if report.results.is_empty() {
+      let tests = self.config.project_path.join(test_runner::TEST_DIRECTORY).to_string_lossy().to_string();
+      let tests = filter.map(|filter| format!("{} matching '{}'", tests, filter)).unwrap_or(tests);
+      Err(deployment::error::DeploymentError::TestRunner(test_runner::error::TestError::NoTests(tests)))
+    } else if report.is_success() {
+      Ok(())
+    } else {
+      Err(deployment::error::DeploymentError::TestsFailed(command))
+    }
+  }
+}
+
+fn is_dev_network(project_config: &config::ProjectConfig) -> bool {
+  project_config.blockchain.as_ref()
+    .and_then(|config| config.connector.as_ref())
+    .map(secrets::is_dev_network)
+    .unwrap_or(true)
 }
@@ -0,0 +1,40 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum LspError {
+  InvalidMessage(String),
+  Io(io::Error),
+}
+
+impl Error for LspError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      LspError::InvalidMessage(_message) => None,
+      LspError::Io(error) => Some(error),
+    }
+  }
+}
+
+impl fmt::Display for LspError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      LspError::InvalidMessage(message) => write!(f, "Couldn't read message from language client: {}", message),
+      LspError::Io(error) => write!(f, "Couldn't talk to language client: {}", error),
+    }
+  }
+}
+
+impl From<io::Error> for LspError {
+  fn from(error: io::Error) -> Self {
+    LspError::Io(error)
+  }
+}
+
+impl From<serde_json::Error> for LspError {
+  fn from(error: serde_json::Error) -> Self {
+    LspError::InvalidMessage(error.to_string())
+  }
+}
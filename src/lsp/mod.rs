@@ -0,0 +1,516 @@
+// A minimal language server behind `vibranium lsp`, speaking the Language
+// Server Protocol over stdio. Editors get
+//
+//   - diagnostics of the static checks while editing, and of the compiler
+//     once a source is saved, compiled with the project's configuration,
+//   - go-to-definition of declarations across imports, as well as of the
+//     imported sources themselves,
+//   - hover info on Smart Contracts with the size of their bytecode and the
+//     gas used by their latest tracked deployment.
+//
+// Positions are taken as character offsets, which match the UTF-16 offsets
+// of the protocol for ASCII sources.
+
+pub mod error;
+mod protocol;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::compiler::{self, checks, error::CompilerError, warnings, CompilerConfig};
+use crate::deployment::tracker::DeploymentTracker;
+use crate::verification;
+use crate::Vibranium;
+use error::LspError;
+use serde_json::{json, Value};
+
+const SERVER_NAME: &str = "vibranium";
+const DECLARATION_KEYWORDS: &[&str] = &["contract", "library", "interface", "struct", "enum", "event", "error", "function", "modifier"];
+
+pub struct LanguageServer<'a> {
+  vibranium: &'a Vibranium,
+  project_path: PathBuf,
+  documents: HashMap<PathBuf, String>,
+  // Diagnostics of the latest compilation, by source.
+  compiler_diagnostics: HashMap<PathBuf, Vec<Value>>,
+  // Sources with published diagnostics, which are cleared once they're gone.
+  published: HashSet<PathBuf>,
+}
+
+impl<'a> LanguageServer<'a> {
+  pub fn new(vibranium: &'a Vibranium) -> LanguageServer<'a> {
+    LanguageServer {
+      project_path: canonical(&vibranium.config.project_path),
+      vibranium,
+      documents: HashMap::new(),
+      compiler_diagnostics: HashMap::new(),
+      published: HashSet::new(),
+    }
+  }
+
+  // Serves messages until the client sends `exit` or closes the connection.
+  pub fn run(&mut self, mut reader: impl BufRead, mut writer: impl Write) -> Result<(), LspError> {
+    while let Some(message) = protocol::read_message(&mut reader)? {
+      if message["method"] == "exit" {
+        break;
+      }
+      for outgoing in self.handle(&message) {
+        protocol::write_message(&mut writer, &outgoing)?;
+      }
+    }
+    Ok(())
+  }
+
+  // Returns the responses and notifications to send for a message.
+  pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+    let params = &message["params"];
+    match (message["method"].as_str(), message.get("id")) {
+      (None, _) => vec![],
+      (Some("initialize"), Some(id)) => vec![protocol::response(id, json!({
+        "capabilities": {
+          "textDocumentSync": { "openClose": true, "change": 1, "save": true },
+          "definitionProvider": true,
+          "hoverProvider": true,
+        },
+        "serverInfo": { "name": SERVER_NAME },
+      }))],
+      (Some("shutdown"), Some(id)) => vec![protocol::response(id, Value::Null)],
+      (Some("textDocument/definition"), Some(id)) => vec![protocol::response(id, self.definition(params).unwrap_or(Value::Null))],
+      (Some("textDocument/hover"), Some(id)) => vec![protocol::response(id, self.hover(params).unwrap_or(Value::Null))],
+      (Some(method), Some(id)) => vec![protocol::error_response(id, protocol::METHOD_NOT_FOUND, format!("Unsupported method {}", method))],
+      (Some("textDocument/didOpen"), None) => {
+        if let (Some(path), Some(text)) = (document_path(params), params["textDocument"]["text"].as_str()) {
+          self.documents.insert(path, text.to_string());
+        }
+        self.publish_diagnostics()
+      },
+      // Documents are always synced in full.
+      (Some("textDocument/didChange"), None) => {
+        let text = params["contentChanges"].as_array().and_then(|changes| changes.last()).and_then(|change| change["text"].as_str());
+        if let (Some(path), Some(text)) = (document_path(params), text) {
+          self.documents.insert(path, text.to_string());
+        }
+        self.publish_diagnostics()
+      },
+      (Some("textDocument/didSave"), None) => {
+        let mut messages = self.compile();
+        messages.append(&mut self.publish_diagnostics());
+        messages
+      },
+      (Some("textDocument/didClose"), None) => {
+        if let Some(path) = document_path(params) {
+          self.documents.remove(&path);
+        }
+        self.publish_diagnostics()
+      },
+      _ => vec![],
+    }
+  }
+
+  // Compiles the project as `vibranium compile` does. Failures that aren't
+  // about the sources are shown as a message instead.
+  fn compile(&mut self) -> Vec<Value> {
+    let config = CompilerConfig {
+      compiler: None,
+      compiler_options: None,
+      replace_options: false,
+      force: false,
+      tags: None,
+    };
+
+    let compiler_messages = match self.vibranium.compile(config) {
+      Ok(output) => warnings::messages(&String::from_utf8_lossy(&output.stderr)),
+      Err(CompilerError::Other(output)) if !warnings::messages(&output).is_empty() => warnings::messages(&output),
+      Err(CompilerError::WarningsDenied(denied)) => denied.into_iter().map(|warning| warnings::CompilerMessage {
+        kind: "Error".to_string(),
+        code: warning.code,
+        message: warning.message,
+        location: warning.location,
+      }).collect(),
+      // Already reported while editing.
+      Err(CompilerError::StaticChecksFailed(_diagnostics)) => vec![],
+      Err(err) => {
+        self.compiler_diagnostics.clear();
+        return vec![protocol::notification("window/showMessage", json!({ "type": protocol::MESSAGE_TYPE_ERROR, "message": err.to_string() }))];
+      },
+    };
+
+    self.compiler_diagnostics.clear();
+    for message in compiler_messages {
+      if let Some((path, line, character)) = message.location.as_ref().and_then(|location| self.parse_location(location)) {
+        let severity = if message.is_warning() { protocol::SEVERITY_WARNING } else { protocol::SEVERITY_ERROR };
+        let end = self.text(&path).and_then(|text| text.lines().nth(line).map(|line| line.chars().count())).unwrap_or(character);
+        self.compiler_diagnostics.entry(path).or_default().push(json!({
+          "range": range(line, character, end.max(character)),
+          "severity": severity,
+          "source": SERVER_NAME,
+          "code": message.code,
+          "message": format!("{}: {}", message.kind, message.message),
+        }));
+      }
+    }
+    vec![]
+  }
+
+  fn publish_diagnostics(&mut self) -> Vec<Value> {
+    let mut diagnostics: BTreeMap<PathBuf, Vec<Value>> = self.compiler_diagnostics.clone().into_iter().collect();
+    for (path, text) in &self.documents {
+      diagnostics.entry(path.clone()).or_default().append(&mut self.static_diagnostics(path, text));
+    }
+    for path in self.published.drain() {
+      diagnostics.entry(path).or_default();
+    }
+
+    self.published = diagnostics.iter().filter(|(_path, diagnostics)| !diagnostics.is_empty()).map(|(path, _diagnostics)| path.clone()).collect();
+    diagnostics.into_iter()
+      .map(|(path, diagnostics)| protocol::notification("textDocument/publishDiagnostics", json!({
+        "uri": protocol::path_to_uri(&path),
+        "diagnostics": diagnostics,
+      })))
+      .collect()
+  }
+
+  // Severities are the configured ones, falling back to the defaults while
+  // vibranium.toml can't be read.
+  fn static_diagnostics(&self, path: &Path, text: &str) -> Vec<Value> {
+    let configured = self.vibranium.config.read().ok().and_then(|project_config| project_config.compiler).and_then(|config| config.checks);
+    let severities = checks::severities(&configured).or_else(|_err| checks::severities(&None)).unwrap_or_default();
+
+    checks::check(path.to_path_buf(), text, &severities).into_iter()
+      .map(|diagnostic| {
+        let line = diagnostic.line - 1;
+        let end = text.lines().nth(line).map(|line| line.chars().count()).unwrap_or(0);
+        json!({
+          "range": range(line, 0, end),
+          "severity": if diagnostic.severity == checks::Severity::Error { protocol::SEVERITY_ERROR } else { protocol::SEVERITY_WARNING },
+          "source": SERVER_NAME,
+          "code": diagnostic.rule,
+          "message": diagnostic.message,
+        })
+      })
+      .collect()
+  }
+
+  fn definition(&self, params: &Value) -> Option<Value> {
+    let (path, line, character) = position(params)?;
+    let line_text = self.text(&path)?.lines().nth(line)?.to_string();
+
+    if let Some(import) = import_at(&line_text, character) {
+      let target = self.resolve_import(&path, &import)?;
+      return Some(location(&target, 0, 0, 0));
+    }
+
+    let word = word_at(&line_text, character)?;
+    self.import_closure(&path).into_iter().find_map(|source| {
+      let (line, start) = declaration(&self.text(&source)?, &word)?;
+      Some(location(&source, line, start, start + word.chars().count()))
+    })
+  }
+
+  fn hover(&self, params: &Value) -> Option<Value> {
+    let (path, line, character) = position(params)?;
+    let word = word_at(self.text(&path)?.lines().nth(line)?, character)?;
+    let project_config = self.vibranium.config.read().ok()?;
+    let artifacts_dir = self.project_path.join(&project_config.sources.artifacts);
+
+    let runtime_path = artifacts_dir.join(format!("{}.{}", word, compiler::ARTIFACT_EXTENSION_RUNTIME_BINARY));
+    let (label, bytecode) = match fs::read_to_string(&runtime_path) {
+      Ok(bytecode) => ("Runtime bytecode", bytecode),
+      Err(_err) => ("Bytecode", fs::read_to_string(artifacts_dir.join(format!("{}.{}", word, compiler::ARTIFACT_EXTENSION_BINARY))).ok()?),
+    };
+    let size = compiler::bytecode_size(&bytecode);
+
+    let mut lines = vec![format!("**{}**", word)];
+    match project_config.compiler.and_then(|config| config.max_size).and_then(|budgets| budgets.get(&word).cloned()) {
+      Some(max_size) => lines.push(format!("{}: {} bytes (budget {} bytes)", label, size, max_size)),
+      None => lines.push(format!("{}: {} bytes", label, size)),
+    }
+
+    let chains = DeploymentTracker::new(&self.vibranium.config).get_tracking_data_of_all_chains().unwrap_or_default();
+    let latest = chains.values()
+      .flat_map(|data| data.values())
+      .filter(|entry| entry.name == word)
+      .max_by_key(|entry| entry.deployed_at);
+    if let Some(entry) = latest {
+      let network = entry.network.clone().unwrap_or_else(|| "unknown network".to_string());
+      match entry.gas_used {
+        Some(gas_used) => lines.push(format!("Deployed at {:?} ({}) using {} gas", entry.address, network, gas_used)),
+        None => lines.push(format!("Deployed at {:?} ({})", entry.address, network)),
+      }
+    }
+
+    Some(json!({ "contents": { "kind": "markdown", "value": lines.join("\n\n") } }))
+  }
+
+  // Open documents take precedence over what's on disk.
+  fn text(&self, path: &Path) -> Option<String> {
+    match self.documents.get(path) {
+      Some(text) => Some(text.to_owned()),
+      None => fs::read_to_string(path).ok(),
+    }
+  }
+
+  fn resolve_import(&self, path: &Path, import: &str) -> Option<PathBuf> {
+    match path.strip_prefix(&self.project_path) {
+      Ok(relative_path) => {
+        let name = verification::resolve_import(&verification::source_name(relative_path), import);
+        verification::source_path(&self.project_path, &name).map(|path| canonical(&path))
+      },
+      Err(_err) if import.starts_with("./") || import.starts_with("../") => {
+        let path = path.parent()?.join(import);
+        if path.exists() { Some(canonical(&path)) } else { None }
+      },
+      Err(_err) => verification::source_path(&self.project_path, import).map(|path| canonical(&path)),
+    }
+  }
+
+  // The given source, followed by everything it imports.
+  fn import_closure(&self, path: &Path) -> Vec<PathBuf> {
+    let mut sources = vec![path.to_path_buf()];
+    let mut i = 0;
+    while i < sources.len() {
+      let imported: Vec<PathBuf> = self.text(&sources[i]).map(|text| verification::imports(&text)).unwrap_or_default()
+        .iter()
+        .filter_map(|import| self.resolve_import(&sources[i], import))
+        .collect();
+      for source in imported {
+        if !sources.contains(&source) {
+          sources.push(source);
+        }
+      }
+      i += 1;
+    }
+    sources
+  }
+
+  // Compiler locations are either absolute or relative to the project, e.g.
+  // `contracts/Simple.sol:5:5`.
+  fn parse_location(&self, location: &str) -> Option<(PathBuf, usize, usize)> {
+    let mut parts = location.rsplitn(3, ':');
+    let character: usize = parts.next()?.parse().ok()?;
+    let line: usize = parts.next()?.parse().ok()?;
+    let file = PathBuf::from(parts.next()?);
+    let path = if file.is_absolute() { file } else { self.project_path.join(file) };
+    Some((canonical(&path), line.saturating_sub(1), character.saturating_sub(1)))
+  }
+}
+
+// Symlinks are resolved, so that paths of the project match the ones of
+// documents opened by the editor.
+fn canonical(path: &Path) -> PathBuf {
+  fs::canonicalize(path).unwrap_or_else(|_err| path.to_path_buf())
+}
+
+fn document_path(params: &Value) -> Option<PathBuf> {
+  params["textDocument"]["uri"].as_str().and_then(protocol::uri_to_path).map(|path| canonical(&path))
+}
+
+fn position(params: &Value) -> Option<(PathBuf, usize, usize)> {
+  let line = params["position"]["line"].as_u64()? as usize;
+  let character = params["position"]["character"].as_u64()? as usize;
+  Some((document_path(params)?, line, character))
+}
+
+fn range(line: usize, start: usize, end: usize) -> Value {
+  json!({
+    "start": { "line": line, "character": start },
+    "end": { "line": line, "character": end },
+  })
+}
+
+fn location(path: &Path, line: usize, start: usize, end: usize) -> Value {
+  json!({ "uri": protocol::path_to_uri(path), "range": range(line, start, end) })
+}
+
+fn word_at(line: &str, character: usize) -> Option<String> {
+  let chars: Vec<char> = line.chars().collect();
+  let is_word = |i: usize| chars.get(i).is_some_and(|c| checks::is_identifier_char(*c));
+  let mut start = character.min(chars.len());
+  // The cursor may be right behind a word.
+  if !is_word(start) && start > 0 && is_word(start - 1) {
+    start -= 1;
+  }
+  if !is_word(start) {
+    return None;
+  }
+  while start > 0 && is_word(start - 1) {
+    start -= 1;
+  }
+  let mut end = start;
+  while is_word(end) {
+    end += 1;
+  }
+  Some(chars[start..end].iter().collect())
+}
+
+// The path of an `import` directive, if the cursor is on it.
+fn import_at(line: &str, character: usize) -> Option<String> {
+  if !line.trim_start().starts_with("import") {
+    return None;
+  }
+  let chars: Vec<char> = line.chars().collect();
+  let start = chars.iter().position(|c| *c == '"' || *c == '\'')?;
+  let end = start + 1 + chars[start + 1..].iter().position(|c| *c == chars[start])?;
+  if character > start && character <= end {
+    Some(chars[start + 1..end].iter().collect())
+  } else {
+    None
+  }
+}
+
+// Line and character of the declaration of `name` in a Solidity source.
+fn declaration(source: &str, name: &str) -> Option<(usize, usize)> {
+  let code = checks::strip_comments_and_strings(source);
+  for (line_index, line) in code.lines().enumerate() {
+    for keyword in DECLARATION_KEYWORDS {
+      for (index, _) in line.match_indices(keyword) {
+        if !checks::is_word_at(line, index, keyword) {
+          continue;
+        }
+        let rest = &line[index + keyword.len()..];
+        let declared = rest.trim_start();
+        let identifier: String = declared.chars().take_while(|c| checks::is_identifier_char(*c)).collect();
+        if identifier == name && declared.len() < rest.len() {
+          let start = index + keyword.len() + (rest.len() - declared.len());
+          return Some((line_index, line[..start].chars().count()));
+        }
+      }
+    }
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{protocol, LanguageServer};
+  use crate::Vibranium;
+  use serde_json::json;
+  use std::fs;
+  use std::path::Path;
+
+  const CONFIG: &str = r#"
+[sources]
+artifacts = "artifacts"
+smart_contracts = ["contracts/*.sol"]
+
+[compiler]
+cmd = "solc"
+options = ["--abi", "--bin"]
+max_size = { Token = 24576 }
+"#;
+
+  const TOKEN: &str = "pragma solidity ^0.8.0;
+
+import \"./Owned.sol\";
+
+contract Token is Owned {
+  function withdraw() public {
+    require(tx.origin == owner);
+  }
+}
+";
+
+  const OWNED: &str = "pragma solidity ^0.8.0;
+
+// contract Owned is declared below.
+contract Owned {
+  address owner;
+}
+";
+
+  fn setup_project() -> (tempfile::TempDir, Vibranium) {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let project_path = fs::canonicalize(tmp_dir.path()).unwrap();
+    fs::write(project_path.join("vibranium.toml"), CONFIG).unwrap();
+    fs::create_dir_all(project_path.join("contracts")).unwrap();
+    fs::create_dir_all(project_path.join("artifacts")).unwrap();
+    fs::write(project_path.join("contracts").join("Token.sol"), TOKEN).unwrap();
+    fs::write(project_path.join("contracts").join("Owned.sol"), OWNED).unwrap();
+    (tmp_dir, Vibranium::new(project_path))
+  }
+
+  fn request(method: &str, path: &Path, line: usize, character: usize) -> serde_json::Value {
+    json!({
+      "jsonrpc": "2.0",
+      "id": 1,
+      "method": method,
+      "params": {
+        "textDocument": { "uri": protocol::path_to_uri(path) },
+        "position": { "line": line, "character": character },
+      },
+    })
+  }
+
+  #[test]
+  fn it_should_read_and_write_framed_messages() {
+    let message = json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} });
+    let mut buffer = vec![];
+    protocol::write_message(&mut buffer, &message).unwrap();
+    assert!(String::from_utf8_lossy(&buffer).starts_with("Content-Length: 52\r\n\r\n"));
+
+    let mut reader = &buffer[..];
+    assert_eq!(protocol::read_message(&mut reader).unwrap(), Some(message));
+    assert_eq!(protocol::read_message(&mut reader).unwrap(), None);
+  }
+
+  #[test]
+  fn it_should_publish_static_check_diagnostics_of_open_documents() {
+    let (_tmp_dir, vibranium) = setup_project();
+    let path = vibranium.config.project_path.join("contracts").join("Token.sol");
+    let mut server = LanguageServer::new(&vibranium);
+
+    let messages = server.handle(&json!({
+      "jsonrpc": "2.0",
+      "method": "textDocument/didOpen",
+      "params": { "textDocument": { "uri": protocol::path_to_uri(&path), "languageId": "solidity", "version": 1, "text": TOKEN } },
+    }));
+    assert_eq!(messages.len(), 1);
+    let diagnostics = &messages[0]["params"]["diagnostics"];
+    assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+    assert_eq!(diagnostics[0]["code"], "tx-origin");
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], 6);
+
+    // Fixed diagnostics are cleared.
+    let messages = server.handle(&json!({
+      "jsonrpc": "2.0",
+      "method": "textDocument/didChange",
+      "params": { "textDocument": { "uri": protocol::path_to_uri(&path), "version": 2 }, "contentChanges": [{ "text": TOKEN.replace("tx.origin", "msg.sender") }] },
+    }));
+    assert_eq!(messages[0]["params"]["diagnostics"], json!([]));
+  }
+
+  #[test]
+  fn it_should_go_to_definitions_across_imports() {
+    let (_tmp_dir, vibranium) = setup_project();
+    let token_path = vibranium.config.project_path.join("contracts").join("Token.sol");
+    let owned_uri = protocol::path_to_uri(&vibranium.config.project_path.join("contracts").join("Owned.sol"));
+    let mut server = LanguageServer::new(&vibranium);
+
+    let response = server.handle(&request("textDocument/definition", &token_path, 4, 20));
+    assert_eq!(response[0]["result"]["uri"], owned_uri);
+    assert_eq!(response[0]["result"]["range"]["start"], json!({ "line": 3, "character": 9 }));
+
+    let response = server.handle(&request("textDocument/definition", &token_path, 2, 12));
+    assert_eq!(response[0]["result"]["uri"], owned_uri);
+    assert_eq!(response[0]["result"]["range"]["start"], json!({ "line": 0, "character": 0 }));
+  }
+
+  #[test]
+  fn it_should_show_sizes_of_compiled_smart_contracts_on_hover() {
+    let (_tmp_dir, vibranium) = setup_project();
+    fs::write(vibranium.config.project_path.join("artifacts").join("Token.bin-runtime"), "6080604052").unwrap();
+    let token_path = vibranium.config.project_path.join("contracts").join("Token.sol");
+    let mut server = LanguageServer::new(&vibranium);
+
+    let response = server.handle(&request("textDocument/hover", &token_path, 4, 10));
+    let contents = response[0]["result"]["contents"]["value"].as_str().unwrap().to_string();
+    assert!(contents.contains("**Token**"));
+    assert!(contents.contains("Runtime bytecode: 5 bytes (budget 24576 bytes)"));
+
+    let response = server.handle(&request("textDocument/hover", &token_path, 5, 4));
+    assert_eq!(response[0]["result"], serde_json::Value::Null);
+  }
+}
@@ -0,0 +1,104 @@
+// Messages of the Language Server Protocol are JSON-RPC messages, each
+// preceded by headers, of which only `Content-Length` is required:
+//
+//   Content-Length: 52\r\n
+//   \r\n
+//   {"jsonrpc":"2.0","method":"initialized","params":{}}
+
+use super::error::LspError;
+
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const SEVERITY_ERROR: u8 = 1;
+pub const SEVERITY_WARNING: u8 = 2;
+pub const MESSAGE_TYPE_ERROR: u8 = 1;
+
+const CONTENT_LENGTH_HEADER: &str = "Content-Length:";
+const FILE_URI_PREFIX: &str = "file://";
+
+// Returns `None` once the client closed the connection.
+pub fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>, LspError> {
+  let mut content_length = None;
+  loop {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+      return Ok(None);
+    }
+    let line = line.trim_end();
+    if line.is_empty() {
+      break;
+    }
+    if let Some(length) = line.strip_prefix(CONTENT_LENGTH_HEADER) {
+      content_length = Some(length.trim().parse::<usize>().map_err(|_err| LspError::InvalidMessage(format!("Invalid header '{}'", line)))?);
+    }
+  }
+
+  let content_length = content_length.ok_or_else(|| LspError::InvalidMessage("Missing Content-Length header".to_string()))?;
+  let mut content = vec![0; content_length];
+  reader.read_exact(&mut content)?;
+  Ok(Some(serde_json::from_slice(&content)?))
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Value) -> Result<(), LspError> {
+  let content = message.to_string();
+  write!(writer, "{} {}\r\n\r\n{}", CONTENT_LENGTH_HEADER, content.len(), content)?;
+  writer.flush()?;
+  Ok(())
+}
+
+pub fn response(id: &Value, result: Value) -> Value {
+  json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+pub fn error_response(id: &Value, code: i64, message: String) -> Value {
+  json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+pub fn notification(method: &str, params: Value) -> Value {
+  json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+  let path = percent_decode(uri.strip_prefix(FILE_URI_PREFIX)?);
+  // `file:///C:/...` on Windows.
+  match path.strip_prefix('/') {
+    Some(windows_path) if windows_path.get(1..2) == Some(":") => Some(PathBuf::from(windows_path)),
+    _ => Some(PathBuf::from(path)),
+  }
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+  let path = path.to_string_lossy().replace('\\', "/");
+  let encoded: String = path.bytes().map(|byte| match byte {
+    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' | b':' => (byte as char).to_string(),
+    _ => format!("%{:02X}", byte),
+  }).collect();
+  if encoded.starts_with('/') {
+    format!("{}{}", FILE_URI_PREFIX, encoded)
+  } else {
+    format!("{}/{}", FILE_URI_PREFIX, encoded)
+  }
+}
+
+fn percent_decode(value: &str) -> String {
+  let bytes = value.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    let byte = value.get(i + 1..i + 3).filter(|_hex| bytes[i] == b'%').and_then(|hex| u8::from_str_radix(hex, 16).ok());
+    match byte {
+      Some(byte) => {
+        decoded.push(byte);
+        i += 3;
+      },
+      None => {
+        decoded.push(bytes[i]);
+        i += 1;
+      },
+    }
+  }
+  String::from_utf8_lossy(&decoded).to_string()
+}
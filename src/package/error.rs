@@ -0,0 +1,64 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+
+use crate::config;
+use crate::project_generator;
+
+#[derive(Debug)]
+pub enum PackageError {
+  VibraniumDirectoryNotFound(project_generator::error::ProjectGenerationError),
+  InvalidConfig(config::error::ConfigError),
+  InvalidReleaseName(String),
+  NothingToPackage,
+  Serialization(serde_json::Error),
+  Io(io::Error),
+  Other(String),
+}
+
+impl Error for PackageError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      PackageError::VibraniumDirectoryNotFound(error) => Some(error),
+      PackageError::InvalidConfig(error) => Some(error),
+      PackageError::InvalidReleaseName(_name) => None,
+      PackageError::NothingToPackage => None,
+      PackageError::Serialization(error) => Some(error),
+      PackageError::Io(error) => Some(error),
+      PackageError::Other(_message) => None,
+    }
+  }
+}
+
+impl fmt::Display for PackageError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PackageError::VibraniumDirectoryNotFound(error) => write!(f, "{}", error),
+      PackageError::InvalidConfig(error) => write!(f, "{}", error),
+      PackageError::InvalidReleaseName(name) => write!(f, "Invalid release name '{}'. Release names can't be empty or contain path separators", name),
+      PackageError::NothingToPackage => write!(f, "Couldn't find any sources or artifacts to package. Please compile first."),
+      PackageError::Serialization(error) => write!(f, "Couldn't serialize package manifest: {}", error),
+      PackageError::Io(error) => write!(f, "Couldn't create package: {}", error),
+      PackageError::Other(message) => write!(f, "{}", message),
+    }
+  }
+}
+
+impl From<config::error::ConfigError> for PackageError {
+  fn from(error: config::error::ConfigError) -> Self {
+    PackageError::InvalidConfig(error)
+  }
+}
+
+impl From<io::Error> for PackageError {
+  fn from(error: io::Error) -> Self {
+    PackageError::Io(error)
+  }
+}
+
+impl From<serde_json::Error> for PackageError {
+  fn from(error: serde_json::Error) -> Self {
+    PackageError::Serialization(error)
+  }
+}
@@ -0,0 +1,184 @@
+pub mod error;
+
+use crate::config;
+use crate::deployment::tracker::DeploymentTracker;
+
+use config::{Config, ProjectCmdExecutionConfig};
+use glob::glob;
+use sha3::{Digest, Sha3_256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use project_generator::VIBRANIUM_PROJECT_DIRECTORY;
+use crate::project_generator;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+const PACKAGE_EXTENSION: &str = "tar";
+const PACKAGE_FILE_MODE: u32 = 0o644;
+
+pub struct PackageOptions {
+  pub release: String,
+  pub output: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PackageManifest {
+  pub release: String,
+  pub compiler: Option<ProjectCmdExecutionConfig>,
+  pub files: Vec<PackageManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PackageManifestEntry {
+  pub path: String,
+  pub sha3_256: String,
+}
+
+pub struct Packager<'a> {
+  config: &'a Config,
+}
+
+impl<'a> Packager<'a> {
+  pub fn new(config: &'a Config) -> Packager<'a> {
+    Packager {
+      config
+    }
+  }
+
+  pub fn package(&self, options: PackageOptions) -> Result<PathBuf, error::PackageError> {
+    if options.release.is_empty() || options.release.contains('/') || options.release.contains('\\') {
+      return Err(error::PackageError::InvalidReleaseName(options.release));
+    }
+
+    let project_config = self.config.read()?;
+    let mut files = vec![];
+
+    for pattern in &project_config.sources.smart_contracts {
+      let full_pattern = self.config.project_path.join(pattern);
+      for entry in glob(&full_pattern.to_string_lossy()).map_err(|err| error::PackageError::Other(err.to_string()))?.filter_map(Result::ok) {
+        files.push(entry);
+      }
+    }
+
+    collect_files(&self.config.project_path.join(&project_config.sources.artifacts), &mut files)?;
+
+    if files.is_empty() {
+      return Err(error::PackageError::NothingToPackage);
+    }
+
+    files.push(self.config.config_file.clone());
+
+    files.extend(DeploymentTracker::new(self.config).tracking_files().map_err(|err| error::PackageError::Other(err.to_string()))?);
+
+    let mut entries = vec![];
+    for file in files {
+      let path = relative_package_path(&self.config.project_path, &file);
+      let content = fs::read(&file)?;
+      entries.push((path, content));
+    }
+
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries.dedup_by(|a, b| a.0 == b.0);
+
+    let manifest = PackageManifest {
+      release: options.release.clone(),
+      compiler: project_config.compiler,
+      files: entries.iter().map(|(path, content)| PackageManifestEntry {
+        path: path.to_owned(),
+        sha3_256: format!("0x{:x}", Sha3_256::digest(content)),
+      }).collect(),
+    };
+
+    let package_name = format!("{}-{}", self.project_name(), &options.release);
+    let package_path = options.output.unwrap_or_else(|| {
+      self.config.project_path.join(format!("{}.{}", &package_name, PACKAGE_EXTENSION))
+    });
+
+    info!("Creating package: {}", &package_path.to_string_lossy());
+
+    let mut builder = tar::Builder::new(fs::File::create(&package_path)?);
+    append_entry(&mut builder, &format!("{}/{}", &package_name, MANIFEST_FILE), serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+
+    for (path, content) in &entries {
+      info!("Adding: {}", &path);
+      append_entry(&mut builder, &format!("{}/{}", &package_name, path), content)?;
+    }
+
+    builder.finish()?;
+    Ok(package_path)
+  }
+
+  fn project_name(&self) -> String {
+    self.config.project_path
+      .file_name()
+      .map(|name| name.to_string_lossy().to_string())
+      .unwrap_or_else(|| "vibranium".to_string())
+  }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), io::Error> {
+  if !dir.exists() {
+    return Ok(());
+  }
+
+  for entry in fs::read_dir(dir)? {
+    let path = entry?.path();
+    if path.is_dir() {
+      collect_files(&path, files)?;
+    } else {
+      files.push(path);
+    }
+  }
+  Ok(())
+}
+
+// Paths inside of a package always use forward slashes so that packages
+// created on different platforms are byte-for-byte identical.
+fn relative_package_path(project_path: &Path, file: &Path) -> String {
+  let relative = file.strip_prefix(project_path).unwrap_or(file);
+  let components: Vec<String> = relative.components()
+    .map(|component| component.as_os_str().to_string_lossy().to_string())
+    .collect();
+
+  if components.first().map(String::as_str) == Some(VIBRANIUM_PROJECT_DIRECTORY) {
+    components[1..].join("/")
+  } else {
+    components.join("/")
+  }
+}
+
+// Headers are normalized (no timestamps, owners or permissions of the local
+// file system) so that packaging the same release twice yields the same tarball.
+fn append_entry<W: io::Write>(builder: &mut tar::Builder<W>, path: &str, content: &[u8]) -> Result<(), io::Error> {
+  let mut header = tar::Header::new_gnu();
+  header.set_path(path)?;
+  header.set_size(content.len() as u64);
+  header.set_mode(PACKAGE_FILE_MODE);
+  header.set_mtime(0);
+  header.set_uid(0);
+  header.set_gid(0);
+  header.set_cksum();
+  builder.append(&header, content)
+}
+
+#[cfg(test)]
+mod tests {
+
+  mod relative_package_path {
+
+    use super::super::relative_package_path;
+    use std::path::PathBuf;
+
+    #[test]
+    fn it_should_strip_project_path() {
+      let project_path = PathBuf::from("/tmp/dapp");
+      assert_eq!(relative_package_path(&project_path, &project_path.join("contracts").join("A.sol")), "contracts/A.sol");
+    }
+
+    #[test]
+    fn it_should_flatten_vibranium_directory() {
+      let project_path = PathBuf::from("/tmp/dapp");
+      assert_eq!(relative_package_path(&project_path, &project_path.join(".vibranium").join("tracking.toml")), "tracking.toml");
+    }
+  }
+}
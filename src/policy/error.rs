@@ -0,0 +1,38 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+use toml;
+
+#[derive(Debug)]
+pub enum PolicyError {
+  Io(String, io::Error),
+  Deserialization(toml::de::Error),
+  UnknownOpcode(String),
+}
+
+impl Error for PolicyError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      PolicyError::Io(_path, error) => Some(error),
+      PolicyError::Deserialization(error) => Some(error),
+      PolicyError::UnknownOpcode(_name) => None,
+    }
+  }
+}
+
+impl fmt::Display for PolicyError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PolicyError::Io(path, error) => write!(f, "Couldn't read deployment policy {}: {}", path, error),
+      PolicyError::Deserialization(error) => write!(f, "Couldn't deserialize deployment policy: {}", error),
+      PolicyError::UnknownOpcode(name) => write!(f, "Unknown opcode '{}' in `forbidden_opcodes` of deployment policy", name),
+    }
+  }
+}
+
+impl From<toml::de::Error> for PolicyError {
+  fn from(error: toml::de::Error) -> Self {
+    PolicyError::Deserialization(error)
+  }
+}
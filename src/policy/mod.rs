@@ -0,0 +1,316 @@
+pub mod error;
+
+use crate::reproduce::VerificationStatus;
+use crate::utils;
+use error::PolicyError;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use web3::types::{Address, U256};
+
+const PUSH1: u8 = 0x60;
+const PUSH32: u8 = 0x7f;
+
+const OPCODES: [(&str, u8); 25] = [
+  ("BALANCE", 0x31),
+  ("ORIGIN", 0x32),
+  ("EXTCODESIZE", 0x3b),
+  ("EXTCODECOPY", 0x3c),
+  ("EXTCODEHASH", 0x3f),
+  ("BLOCKHASH", 0x40),
+  ("COINBASE", 0x41),
+  ("TIMESTAMP", 0x42),
+  ("NUMBER", 0x43),
+  ("DIFFICULTY", 0x44),
+  ("GASLIMIT", 0x45),
+  ("SSTORE", 0x55),
+  ("GAS", 0x5a),
+  ("LOG0", 0xa0),
+  ("LOG1", 0xa1),
+  ("LOG2", 0xa2),
+  ("LOG3", 0xa3),
+  ("LOG4", 0xa4),
+  ("CREATE", 0xf0),
+  ("CALL", 0xf1),
+  ("CALLCODE", 0xf2),
+  ("DELEGATECALL", 0xf4),
+  ("CREATE2", 0xf5),
+  ("STATICCALL", 0xfa),
+  ("SELFDESTRUCT", 0xff),
+];
+
+// Organization wide rules for deployments, read from the file referenced by
+// `deployment.policy` in vibranium.toml.
+#[derive(Deserialize, Debug, Default)]
+pub struct Policy {
+  pub max_gas_price: Option<usize>,
+  pub allowed_networks: Option<Vec<String>>,
+  pub required_confirmations: Option<usize>,
+  pub forbidden_opcodes: Option<Vec<String>>,
+  pub require_verified: Option<bool>,
+}
+
+pub struct DeploymentPlan {
+  pub network: String,
+  pub tx_confirmations: usize,
+  pub smart_contracts: Vec<PlannedSmartContract>,
+}
+
+pub struct PlannedSmartContract {
+  pub name: String,
+  pub gas_price: U256,
+  pub bytecode: String,
+  // The latest tracked deployment on the chain that is deployed to.
+  pub tracked: Option<TrackedSmartContract>,
+}
+
+pub struct TrackedSmartContract {
+  pub address: Address,
+  pub verification: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PolicyCheck {
+  pub rule: String,
+  pub passed: bool,
+  pub details: String,
+}
+
+impl fmt::Display for PolicyCheck {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let status = if self.passed { "ok" } else { "violated" };
+    write!(f, "[{}] {}: {}", status, self.rule, self.details)
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct PolicyReport {
+  pub checks: Vec<PolicyCheck>,
+}
+
+impl PolicyReport {
+  pub fn is_compliant(&self) -> bool {
+    self.checks.iter().all(|check| check.passed)
+  }
+
+  pub fn violations(&self) -> Vec<&PolicyCheck> {
+    self.checks.iter().filter(|check| !check.passed).collect()
+  }
+}
+
+impl Policy {
+  pub fn read(path: &Path) -> Result<Policy, PolicyError> {
+    let content = fs::read_to_string(path).map_err(|err| PolicyError::Io(path.to_string_lossy().to_string(), err))?;
+    let policy: Policy = toml::from_str(&content)?;
+
+    for name in policy.forbidden_opcodes.iter().flatten() {
+      opcode_from(name).ok_or_else(|| PolicyError::UnknownOpcode(name.to_owned()))?;
+    }
+    Ok(policy)
+  }
+
+  // Only rules that are set in the policy end up in the report.
+  pub fn evaluate(&self, plan: &DeploymentPlan) -> PolicyReport {
+    let mut report = PolicyReport::default();
+
+    if let Some(max_gas_price) = self.max_gas_price {
+      let max_gas_price = U256::from(max_gas_price);
+      let exceeding: Vec<String> = plan.smart_contracts.iter()
+        .filter(|smart_contract| smart_contract.gas_price > max_gas_price)
+        .map(|smart_contract| format!("{} ({})", smart_contract.name, smart_contract.gas_price))
+        .collect();
+
+      report.checks.push(PolicyCheck {
+        rule: "max_gas_price".to_string(),
+        passed: exceeding.is_empty(),
+        details: if exceeding.is_empty() {
+          format!("all gas prices are at most {}", max_gas_price)
+        } else {
+          format!("gas price exceeds {} for {}", max_gas_price, exceeding.join(", "))
+        },
+      });
+    }
+
+    if let Some(allowed_networks) = &self.allowed_networks {
+      let passed = allowed_networks.contains(&plan.network);
+      report.checks.push(PolicyCheck {
+        rule: "allowed_networks".to_string(),
+        passed,
+        details: if passed {
+          format!("{} is allowed", plan.network)
+        } else {
+          format!("{} is not one of {}", plan.network, allowed_networks.join(", "))
+        },
+      });
+    }
+
+    if let Some(required_confirmations) = self.required_confirmations {
+      let passed = plan.tx_confirmations >= required_confirmations;
+      report.checks.push(PolicyCheck {
+        rule: "required_confirmations".to_string(),
+        passed,
+        details: format!("waiting for {} of at least {} confirmations", plan.tx_confirmations, required_confirmations),
+      });
+    }
+
+    if let Some(forbidden_opcodes) = &self.forbidden_opcodes {
+      let forbidden: Vec<(&str, u8)> = forbidden_opcodes.iter().filter_map(|name| opcode_from(name)).collect();
+      let findings: Vec<String> = plan.smart_contracts.iter()
+        .filter_map(|smart_contract| {
+          let opcodes = opcodes_in(&smart_contract.bytecode);
+          let found: Vec<&str> = forbidden.iter()
+            .filter(|(_name, opcode)| opcodes.contains(opcode))
+            .map(|(name, _opcode)| *name)
+            .collect();
+          if found.is_empty() {
+            None
+          } else {
+            Some(format!("{} uses {}", smart_contract.name, found.join(", ")))
+          }
+        })
+        .collect();
+
+      report.checks.push(PolicyCheck {
+        rule: "forbidden_opcodes".to_string(),
+        passed: findings.is_empty(),
+        details: if findings.is_empty() {
+          format!("none of {} found", forbidden_opcodes.join(", "))
+        } else {
+          findings.join("; ")
+        },
+      });
+    }
+
+    // Smart Contracts that haven't been deployed to the chain yet have nothing
+    // to be verified.
+    if self.require_verified == Some(true) {
+      let verified = VerificationStatus::Verified.label();
+      let unverified: Vec<String> = plan.smart_contracts.iter()
+        .filter_map(|smart_contract| smart_contract.tracked.as_ref().map(|tracked| (&smart_contract.name, tracked)))
+        .filter(|(_name, tracked)| tracked.verification.as_deref() != Some(verified))
+        .map(|(name, tracked)| format!("{} at {:?} is {}", name, tracked.address, tracked.verification.as_deref().unwrap_or("unverified")))
+        .collect();
+
+      report.checks.push(PolicyCheck {
+        rule: "require_verified".to_string(),
+        passed: unverified.is_empty(),
+        details: if unverified.is_empty() {
+          "all tracked Smart Contracts are verified".to_string()
+        } else {
+          unverified.join(", ")
+        },
+      });
+    }
+
+    report
+  }
+}
+
+fn opcode_from(name: &str) -> Option<(&'static str, u8)> {
+  let name = match name.to_uppercase().as_str() {
+    "SUICIDE" => "SELFDESTRUCT".to_string(),
+    name => name.to_string(),
+  };
+  OPCODES.iter().find(|(opcode_name, _opcode)| *opcode_name == name).cloned()
+}
+
+// Walks the bytecode instruction by instruction, so that data pushed onto the
+// stack isn't mistaken for opcodes. The metadata solc appends to the bytecode
+// is skipped for the same reason.
+pub fn opcodes_in(bytecode: &str) -> Vec<u8> {
+  let mut code = utils::decode_bytecode(bytecode);
+  utils::strip_metadata(&mut code);
+
+  let mut opcodes = vec![];
+  let mut i = 0;
+  while i < code.len() {
+    let opcode = code[i];
+    opcodes.push(opcode);
+    i += if (PUSH1..=PUSH32).contains(&opcode) { (opcode - PUSH1) as usize + 2 } else { 1 };
+  }
+  opcodes
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{opcodes_in, DeploymentPlan, PlannedSmartContract, Policy, TrackedSmartContract};
+  use std::fs;
+  use web3::types::{Address, U256};
+
+  fn plan() -> DeploymentPlan {
+    DeploymentPlan {
+      network: "localhost".to_string(),
+      tx_confirmations: 1,
+      smart_contracts: vec![
+        PlannedSmartContract { name: "A".to_string(), gas_price: U256::from(10), bytecode: "6080604052ff".to_string(), tracked: None },
+        PlannedSmartContract { name: "B".to_string(), gas_price: U256::from(30), bytecode: "0x60ff60f4".to_string(), tracked: None },
+      ],
+    }
+  }
+
+  #[test]
+  fn it_should_skip_pushed_data_and_metadata() {
+    assert_eq!(opcodes_in("60ff5b"), vec![0x60, 0x5b]);
+    assert_eq!(opcodes_in(&format!("0x7f__$Library$__{}f4", "0".repeat(51))), vec![0x7f, 0xf4]);
+    assert_eq!(opcodes_in("6080fea165627a7a72305820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0029"), vec![0x60, 0xfe]);
+  }
+
+  #[test]
+  fn it_should_report_violations() {
+    let policy = Policy {
+      max_gas_price: Some(20),
+      allowed_networks: Some(vec!["localhost".to_string()]),
+      required_confirmations: Some(2),
+      forbidden_opcodes: Some(vec!["SELFDESTRUCT".to_string(), "DELEGATECALL".to_string()]),
+      require_verified: None,
+    };
+
+    let report = policy.evaluate(&plan());
+    assert!(!report.is_compliant());
+    assert_eq!(report.checks.len(), 4);
+
+    let violations: Vec<String> = report.violations().iter().map(|check| check.to_string()).collect();
+    assert_eq!(violations, vec![
+      "[violated] max_gas_price: gas price exceeds 20 for B (30)",
+      "[violated] required_confirmations: waiting for 1 of at least 2 confirmations",
+      "[violated] forbidden_opcodes: A uses SELFDESTRUCT",
+    ]);
+  }
+
+  #[test]
+  fn it_should_only_check_configured_rules() {
+    let policy = Policy {
+      allowed_networks: Some(vec!["localhost".to_string()]),
+      ..Policy::default()
+    };
+
+    let report = policy.evaluate(&plan());
+    assert!(report.is_compliant());
+    assert_eq!(report.checks[0].to_string(), "[ok] allowed_networks: localhost is allowed");
+  }
+
+  #[test]
+  fn it_should_require_tracked_smart_contracts_to_be_verified() {
+    let policy = Policy {
+      require_verified: Some(true),
+      ..Policy::default()
+    };
+    let mut plan = plan();
+    plan.smart_contracts[0].tracked = Some(TrackedSmartContract { address: Address::from_low_u64_be(1), verification: Some("verified".to_string()) });
+    assert!(policy.evaluate(&plan).is_compliant());
+
+    plan.smart_contracts[1].tracked = Some(TrackedSmartContract { address: Address::from_low_u64_be(2), verification: Some("drifted".to_string()) });
+    let report = policy.evaluate(&plan);
+    assert_eq!(report.checks[0].to_string(), "[violated] require_verified: B at 0x0000000000000000000000000000000000000002 is drifted");
+  }
+
+  #[test]
+  fn it_should_reject_unknown_opcodes() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = tmp_dir.path().join("policy.toml");
+    fs::write(&path, "forbidden_opcodes = [\"selfdestruct\", \"JUMPY\"]").unwrap();
+
+    assert_eq!(Policy::read(&path).unwrap_err().to_string(), "Unknown opcode 'JUMPY' in `forbidden_opcodes` of deployment policy");
+  }
+}
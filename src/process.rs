@@ -0,0 +1,269 @@
+//! Execution of external tools, e.g. compilers and blockchain clients. Every
+//! invocation is recorded, so it shows up in `--verbose` output and in the
+//! troubleshooting report generated by `vibranium doctor --report`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+pub const PROCESS_LOG_FILE: &str = "processes.log";
+const MAX_LOGGED_PROCESSES: usize = 20;
+const MAX_LOGGED_OUTPUT_LENGTH: usize = 4096;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProcessRecord {
+  pub program: String,
+  pub args: Vec<String>,
+  pub env: BTreeMap<String, String>,
+  pub started_at: u64,
+  pub duration_ms: u64,
+  pub exit_code: Option<i32>,
+  pub pid: Option<u32>,
+  pub stdout: Option<String>,
+  pub stderr: Option<String>,
+  pub error: Option<String>,
+}
+
+impl fmt::Display for ProcessRecord {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.program)?;
+    for arg in &self.args {
+      write!(f, " {}", arg)?;
+    }
+    match (&self.error, self.exit_code, self.pid) {
+      (Some(error), _, _) => write!(f, " (failed to start: {})", error),
+      (None, _, Some(pid)) => write!(f, " (running in background as pid {})", pid),
+      (None, Some(code), None) => write!(f, " (exit code {}, {}ms)", code, self.duration_ms),
+      (None, None, None) => write!(f, " (terminated by signal, {}ms)", self.duration_ms),
+    }
+  }
+}
+
+pub struct ProcessCommand {
+  program: String,
+  args: Vec<String>,
+  env: BTreeMap<String, String>,
+  // Replaces the environment inherited from Vibranium. Unlike `env`, it isn't
+  // recorded, as it usually contains secrets.
+  environment: Option<BTreeMap<String, String>>,
+  current_dir: Option<PathBuf>,
+  log_file: Option<PathBuf>,
+}
+
+impl ProcessCommand {
+  pub fn new<S: Into<String>>(program: S) -> ProcessCommand {
+    ProcessCommand {
+      program: program.into(),
+      args: vec![],
+      env: BTreeMap::new(),
+      environment: None,
+      current_dir: None,
+      log_file: None,
+    }
+  }
+
+  pub fn arg<S: Into<String>>(mut self, arg: S) -> ProcessCommand {
+    self.args.push(arg.into());
+    self
+  }
+
+  pub fn args(mut self, args: Vec<String>) -> ProcessCommand {
+    self.args.extend(args);
+    self
+  }
+
+  pub fn env<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> ProcessCommand {
+    self.env.insert(key.into(), value.into());
+    self
+  }
+
+  pub fn environment(mut self, environment: Option<BTreeMap<String, String>>) -> ProcessCommand {
+    self.environment = environment;
+    self
+  }
+
+  pub fn current_dir(mut self, current_dir: &Path) -> ProcessCommand {
+    self.current_dir = Some(current_dir.to_path_buf());
+    self
+  }
+
+  // Records are only persisted for initialized projects.
+  pub fn log_to(mut self, vibranium_dir_path: &Path) -> ProcessCommand {
+    if vibranium_dir_path.exists() {
+      self.log_file = Some(vibranium_dir_path.join(PROCESS_LOG_FILE));
+    }
+    self
+  }
+
+  // Runs the process to completion while capturing its output.
+  pub fn output(&self) -> Result<Output, io::Error> {
+    let started_at = unix_time();
+    let started = Instant::now();
+    let result = self.command().stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+
+    let mut record = self.record(started_at, started, result.as_ref().map(|output| output.status.code()));
+    if let Ok(output) = &result {
+      record.stdout = Some(truncate(&String::from_utf8_lossy(&output.stdout)));
+      record.stderr = Some(truncate(&String::from_utf8_lossy(&output.stderr)));
+    }
+    self.log(&record);
+    result
+  }
+
+  // Runs the process to completion with its output going to the terminal,
+  // e.g. for long running blockchain nodes.
+  pub fn status(&self) -> Result<ExitStatus, io::Error> {
+    let started_at = unix_time();
+    let started = Instant::now();
+    let result = self.command().status();
+    let record = self.record(started_at, started, result.as_ref().map(|status| status.code()));
+    self.log(&record);
+    result
+  }
+
+  // Starts the process without waiting for it, with its output appended to
+  // `output_file`. The process gets its own process group, so that it isn't
+  // interrupted along with the terminal it was started from.
+  pub fn spawn(&self, output_file: &Path) -> Result<Child, io::Error> {
+    let started_at = unix_time();
+    let started = Instant::now();
+    let result = fs::OpenOptions::new().create(true).append(true).open(output_file).and_then(|output| {
+      let mut command = self.command();
+      command.stdin(Stdio::null()).stderr(output.try_clone()?).stdout(output);
+      #[cfg(unix)]
+      {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+      }
+      command.spawn()
+    });
+
+    let mut record = self.record(started_at, started, result.as_ref().map(|_child| None));
+    record.pid = result.as_ref().ok().map(Child::id);
+    self.log(&record);
+    result
+  }
+
+  fn command(&self) -> Command {
+    let mut command = Command::new(&self.program);
+    if let Some(environment) = &self.environment {
+      command.env_clear().envs(environment);
+    }
+    command.args(&self.args).envs(&self.env);
+    if let Some(current_dir) = &self.current_dir {
+      command.current_dir(current_dir);
+    }
+    command
+  }
+
+  fn record(&self, started_at: u64, started: Instant, exit_code: Result<Option<i32>, &io::Error>) -> ProcessRecord {
+    ProcessRecord {
+      program: self.program.to_owned(),
+      args: self.args.clone(),
+      env: self.env.clone(),
+      started_at,
+      duration_ms: started.elapsed().as_millis() as u64,
+      exit_code: exit_code.as_ref().ok().and_then(|code| *code),
+      pid: None,
+      stdout: None,
+      stderr: None,
+      error: exit_code.err().map(|err| err.to_string()),
+    }
+  }
+
+  fn log(&self, record: &ProcessRecord) {
+    info!("Ran {}", record);
+    for (key, value) in &record.env {
+      info!("  with {}={}", key, value);
+    }
+
+    if let Some(log_file) = &self.log_file {
+      let mut records = read_log_file(log_file);
+      records.push(record.clone());
+      let skip = records.len().saturating_sub(MAX_LOGGED_PROCESSES);
+      let content: Vec<String> = records.iter().skip(skip).filter_map(|record| serde_json::to_string(record).ok()).collect();
+      if let Err(err) = fs::write(log_file, content.join("\n") + "\n") {
+        warn!("Couldn't record process invocation in {}: {}", log_file.to_string_lossy(), err);
+      }
+    }
+  }
+}
+
+// The most recent invocations, oldest first.
+pub fn read_log(vibranium_dir_path: &Path) -> Vec<ProcessRecord> {
+  read_log_file(&vibranium_dir_path.join(PROCESS_LOG_FILE))
+}
+
+fn read_log_file(log_file: &Path) -> Vec<ProcessRecord> {
+  fs::read_to_string(log_file)
+    .map(|content| content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+    .unwrap_or_default()
+}
+
+pub(crate) fn unix_time() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+}
+
+fn truncate(output: &str) -> String {
+  if output.len() <= MAX_LOGGED_OUTPUT_LENGTH {
+    return output.to_string();
+  }
+  let mut end = MAX_LOGGED_OUTPUT_LENGTH;
+  while !output.is_char_boundary(end) {
+    end -= 1;
+  }
+  format!("{}... ({} bytes truncated)", &output[..end], output.len() - end)
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{read_log, ProcessCommand, MAX_LOGGED_PROCESSES};
+
+  #[test]
+  fn it_should_record_invocations() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let output = ProcessCommand::new("sh")
+      .arg("-c")
+      .arg("echo $GREETING; exit 3")
+      .env("GREETING", "hello")
+      .log_to(tmp_dir.path())
+      .output()
+      .unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+
+    let records = read_log(tmp_dir.path());
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].args, vec!["-c", "echo $GREETING; exit 3"]);
+    assert_eq!(records[0].env.get("GREETING").unwrap(), "hello");
+    assert_eq!(records[0].exit_code, Some(3));
+    assert_eq!(records[0].stdout.as_ref().unwrap(), "hello\n");
+  }
+
+  #[test]
+  fn it_should_record_processes_that_fail_to_start() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    assert!(ProcessCommand::new("vibranium-non-existent-tool").log_to(tmp_dir.path()).status().is_err());
+
+    let records = read_log(tmp_dir.path());
+    assert!(records[0].error.is_some());
+    assert!(records[0].to_string().starts_with("vibranium-non-existent-tool (failed to start: "));
+  }
+
+  #[test]
+  fn it_should_only_keep_the_most_recent_invocations() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    for i in 0..MAX_LOGGED_PROCESSES + 2 {
+      ProcessCommand::new("true").arg(i.to_string()).log_to(tmp_dir.path()).status().unwrap();
+    }
+
+    let records = read_log(tmp_dir.path());
+    assert_eq!(records.len(), MAX_LOGGED_PROCESSES);
+    assert_eq!(records[0].args, vec!["2"]);
+  }
+}
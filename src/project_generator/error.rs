@@ -11,6 +11,10 @@ pub enum ProjectGenerationError {
   InvalidConfig(config::error::ConfigError),
   ProjectPathNotFound,
   VibraniumDirectoryNotFound,
+  ReadOnlyMode,
+  UnsupportedPackageManager(String),
+  MissingPackageManager,
+  DependencyInstallationFailed(String),
   Io(io::Error),
   Serialization(toml::ser::Error),
   Other(String),
@@ -22,6 +26,10 @@ impl Error for ProjectGenerationError {
       ProjectGenerationError::InvalidConfig(error) => Some(error),
       ProjectGenerationError::ProjectPathNotFound => None,
       ProjectGenerationError::VibraniumDirectoryNotFound => None,
+      ProjectGenerationError::ReadOnlyMode => None,
+      ProjectGenerationError::UnsupportedPackageManager(_name) => None,
+      ProjectGenerationError::MissingPackageManager => None,
+      ProjectGenerationError::DependencyInstallationFailed(_package_manager) => None,
       ProjectGenerationError::Io(error) => Some(error),
       ProjectGenerationError::Serialization(error) => Some(error),
       ProjectGenerationError::Other(_message) => None,
@@ -35,6 +43,10 @@ impl fmt::Display for ProjectGenerationError {
       ProjectGenerationError::InvalidConfig(error) => write!(f, "{}", error),
       ProjectGenerationError::ProjectPathNotFound => write!(f, "Couldn't find directory for given project path"),
       ProjectGenerationError::VibraniumDirectoryNotFound => write!(f, "Not a Vibranium project. Couldn't find .vibranium directory"),
+      ProjectGenerationError::ReadOnlyMode => write!(f, "Couldn't reset project. Removing tracking data is disabled in read-only mode."),
+      ProjectGenerationError::UnsupportedPackageManager(name) => write!(f, "Unsupported package manager: {}. Use one of npm, yarn or pnpm.", name),
+      ProjectGenerationError::MissingPackageManager => write!(f, "Couldn't find a package manager to install JavaScript dependencies. Install one of npm, yarn or pnpm."),
+      ProjectGenerationError::DependencyInstallationFailed(package_manager) => write!(f, "Couldn't install JavaScript dependencies using {}", package_manager),
       ProjectGenerationError::Io(error) => write!(f, "{}", error),
       ProjectGenerationError::Serialization(error) => write!(f, "Couldn't serialize data: {}", error),
       ProjectGenerationError::Other(message) => write!(f, "{}", message),
@@ -0,0 +1,126 @@
+// Templates of the minimal frontend scaffolded by `vibranium init --with-frontend`.
+// It loads the ABIs from the project's artifacts directory and the addresses
+// of deployed Smart Contracts from Vibranium's tracking data, so it works
+// right after `vibranium compile` and `vibranium deploy`.
+
+pub const FRONTEND_DIRECTORY: &str = "frontend";
+const ARTIFACTS_DIRECTORY_PLACEHOLDER: &str = "{{ARTIFACTS_DIRECTORY}}";
+
+const PACKAGE_JSON: &str = r#"{
+  "name": "vibranium-frontend",
+  "private": true,
+  "version": "0.0.0",
+  "type": "module",
+  "scripts": {
+    "dev": "vite",
+    "build": "vite build",
+    "preview": "vite preview"
+  },
+  "dependencies": {
+    "ethers": "^6.13.0",
+    "smol-toml": "^1.3.0"
+  },
+  "devDependencies": {
+    "vite": "^5.4.0"
+  }
+}
+"#;
+
+// The project directory has to be accessible to the dev server, because
+// artifacts and tracking data live outside of the frontend directory.
+const VITE_CONFIG: &str = r#"import { defineConfig } from 'vite';
+
+export default defineConfig({
+  server: {
+    fs: {
+      allow: ['..'],
+    },
+  },
+});
+"#;
+
+const INDEX_HTML: &str = r#"<!doctype html>
+<html lang="en">
+  <head>
+    <meta charset="UTF-8" />
+    <title>Vibranium DApp</title>
+  </head>
+  <body>
+    <h1>Deployed Smart Contracts</h1>
+    <div id="contracts">Connecting...</div>
+    <script type="module" src="/src/main.js"></script>
+  </body>
+</html>
+"#;
+
+const MAIN_JS: &str = r#"import { BrowserProvider, Contract, JsonRpcProvider } from 'ethers';
+import { parse } from 'smol-toml';
+
+// Generated by `vibranium init --with-frontend`. ABIs are read from the
+// artifacts directory, addresses from `.vibranium/tracking.toml`.
+const abis = import.meta.glob('../../{{ARTIFACTS_DIRECTORY}}/*.abi', { eager: true, query: '?raw', import: 'default' });
+const trackingData = import.meta.glob('../../.vibranium/tracking.toml', { eager: true, query: '?raw', import: 'default' });
+
+const NODE_URL = 'http://localhost:8545';
+
+function abiOf(name) {
+  const entry = Object.entries(abis).find(([path]) => path.endsWith(`/${name}.abi`));
+  return entry ? JSON.parse(entry[1]) : null;
+}
+
+// Tracking data is grouped by the hash of the chain's first block.
+async function deployedContracts(provider) {
+  const content = Object.values(trackingData)[0];
+  if (!content) {
+    return [];
+  }
+  const genesis = await provider.getBlock(0);
+  return Object.values(parse(content)[genesis.hash] || {});
+}
+
+async function render() {
+  const provider = window.ethereum ? new BrowserProvider(window.ethereum) : new JsonRpcProvider(NODE_URL);
+  const container = document.getElementById('contracts');
+  const contracts = await deployedContracts(provider);
+
+  if (contracts.length === 0) {
+    container.textContent = 'No Smart Contracts have been deployed to the connected chain. Run `vibranium deploy` first.';
+    return;
+  }
+
+  container.textContent = '';
+  for (const { name, address } of contracts) {
+    const abi = abiOf(name);
+    const section = document.createElement('section');
+    section.innerHTML = `<h2>${name}</h2><code>${address}</code>`;
+    container.appendChild(section);
+
+    if (!abi) {
+      continue;
+    }
+
+    // Read-only functions without parameters are called right away.
+    const contract = new Contract(address, abi, provider);
+    const getters = abi.filter((entry) => entry.type === 'function' && entry.inputs.length === 0 && ['view', 'pure'].includes(entry.stateMutability));
+    for (const getter of getters) {
+      const value = await contract[getter.name]();
+      const line = document.createElement('p');
+      line.textContent = `${getter.name}(): ${value}`;
+      section.appendChild(line);
+    }
+  }
+}
+
+render().catch((error) => {
+  document.getElementById('contracts').textContent = `Error: ${error.message}`;
+});
+"#;
+
+pub fn files(artifacts_directory: &str) -> Vec<(&'static str, String)> {
+  vec![
+    ("package.json", PACKAGE_JSON.to_string()),
+    ("vite.config.js", VITE_CONFIG.to_string()),
+    ("index.html", INDEX_HTML.to_string()),
+    ("src/main.js", MAIN_JS.replace(ARTIFACTS_DIRECTORY_PLACEHOLDER, artifacts_directory.trim_end_matches('/'))),
+  ]
+}
@@ -1,17 +1,25 @@
 extern crate toml;
 extern crate log;
 
-use std::path::{PathBuf};
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::io::Write;
 
+use crate::blockchain::chains;
 use crate::config;
-use crate::deployment::tracker::TRACKING_FILE;
+use crate::deployment::error::DeploymentTrackingError;
+use crate::deployment::journal::{DeploymentJournal, JOURNAL_DIRECTORY};
+use crate::deployment::tracker::DeploymentTracker;
+use crate::process::ProcessCommand;
+use package_manager::{PackageManager, PACKAGE_JSON_FILE};
 
 pub mod error;
+pub mod frontend;
+pub mod package_manager;
 
 pub const VIBRANIUM_PROJECT_DIRECTORY: &str = ".vibranium";
 pub const DEFAULT_DATADIR_NAME: &str = "datadir";
+pub const DEFAULT_CHAINS_DIR: &str = "chains";
 pub const DEFAULT_DEV_PASSWORDS_DIR: &str = "passwords";
 pub const DEFAULT_ENVIRONMENT: &str = "development";
 
@@ -38,7 +46,7 @@ impl<'a> ProjectGenerator<'a> {
 
     let mut directories_to_create: Vec<PathBuf> = vec![
       project_path.join(VIBRANIUM_PROJECT_DIRECTORY),
-      project_path.join(VIBRANIUM_PROJECT_DIRECTORY).join(DEFAULT_DATADIR_NAME).join(DEFAULT_ENVIRONMENT),
+      chains::data_dir(&project_path.join(VIBRANIUM_PROJECT_DIRECTORY), DEFAULT_ENVIRONMENT),
       project_path.join(VIBRANIUM_PROJECT_DIRECTORY).join(DEFAULT_DEV_PASSWORDS_DIR),
       project_path.join(config::DEFAULT_CONTRACTS_DIRECTORY),
     ];
@@ -60,15 +68,86 @@ impl<'a> ProjectGenerator<'a> {
     Ok(())
   }
 
+  // Files that already exist are left untouched, so that running this again
+  // doesn't overwrite changes made to the scaffolded frontend.
+  pub fn generate_frontend(&self, project_path: &Path) -> Result<(), error::ProjectGenerationError> {
+    self.check_vibranium_dir_exists()?;
+    let project_config = self.config.read()?;
+    let frontend_path = project_path.join(frontend::FRONTEND_DIRECTORY);
+
+    for (file, content) in frontend::files(&project_config.sources.artifacts) {
+      let path = frontend_path.join(file);
+      if path.exists() {
+        info!("Skipping existing file: {}", path.to_str().unwrap());
+        continue;
+      }
+      if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+      }
+      info!("Creating: {}", path.to_str().unwrap());
+      fs::write(path, content)?;
+    }
+    Ok(())
+  }
+
+  // Only generated when configured tools are distributed via npm, and never
+  // overwrites an existing package.json. Returns whether one was generated.
+  pub fn generate_package_json(&self, project_path: &Path) -> Result<bool, error::ProjectGenerationError> {
+    let path = project_path.join(PACKAGE_JSON_FILE);
+    let dependencies = package_manager::js_dependencies(&self.config.read()?);
+    if dependencies.is_empty() || path.exists() {
+      return Ok(false);
+    }
+    info!("Creating: {}", path.to_str().unwrap());
+    fs::write(path, package_manager::package_json(&dependencies))?;
+    Ok(true)
+  }
+
+  // An explicitly requested package manager wins over the one recorded in
+  // the project's config, which in turn wins over detecting one.
+  pub fn package_manager(&self, project_path: &Path, requested: Option<&str>) -> Result<PackageManager, error::ProjectGenerationError> {
+    let configured = if self.config.exists() { self.config.read()?.package_manager } else { None };
+    match requested.map(str::to_string).or(configured) {
+      Some(name) => name.parse(),
+      None => PackageManager::detect(project_path, &self.config.vibranium_dir_path).ok_or(error::ProjectGenerationError::MissingPackageManager),
+    }
+  }
+
+  pub fn js_dependency_directories(&self, project_path: &Path) -> Vec<PathBuf> {
+    vec![project_path.to_path_buf(), project_path.join(frontend::FRONTEND_DIRECTORY)]
+      .into_iter()
+      .filter(|directory| directory.join(PACKAGE_JSON_FILE).exists())
+      .collect()
+  }
+
+  // Output of the package manager goes straight to the terminal, so its
+  // progress is visible while installing.
+  pub fn install_js_dependencies(&self, directory: &Path, package_manager: PackageManager) -> Result<(), error::ProjectGenerationError> {
+    let status = ProcessCommand::new(package_manager.executable())
+      .arg("install")
+      .current_dir(directory)
+      .log_to(&self.config.vibranium_dir_path)
+      .status()
+      .map_err(|_| error::ProjectGenerationError::DependencyInstallationFailed(package_manager.to_string()))?;
+
+    if !status.success() {
+      return Err(error::ProjectGenerationError::DependencyInstallationFailed(package_manager.to_string()));
+    }
+    Ok(())
+  }
+
   pub fn reset_project(&self, project_path: &PathBuf, options: ResetOptions) -> Result<(), error::ProjectGenerationError> {
     self.check_vibranium_dir_exists()?;
     let vibranium_project_directory = self.config.vibranium_dir_path.clone();
     let default_artifacts_directory = project_path.join(config::DEFAULT_ARTIFACTS_DIRECTORY);
 
     if options.tracking_data_only {
-      let tracking_file = vibranium_project_directory.join(TRACKING_FILE);
-      info!("Removing: {}", &tracking_file.to_str().unwrap());
-      fs::remove_file(tracking_file)?;
+      let tracking_files = DeploymentTracker::new(self.config).tracking_files().map_err(|err| error::ProjectGenerationError::Other(err.to_string()))?;
+      for tracking_file in tracking_files {
+        info!("Removing: {}", &tracking_file.to_str().unwrap());
+        fs::remove_file(tracking_file)?;
+      }
+      let _ = fs::remove_dir_all(vibranium_project_directory.join(JOURNAL_DIRECTORY));
     } else {
       if options.restore_config {
         info!("Restoring project's config file");
@@ -93,6 +172,32 @@ impl<'a> ProjectGenerator<'a> {
     Ok(())
   }
 
+  // Wipes the data directory of the network's local chain. Tracking data can
+  // only be pruned if the chain's genesis block has been recorded while its
+  // node was running. Returns the amount of pruned tracked deployments.
+  pub fn reset_chain(&self, network: &str) -> Result<usize, error::ProjectGenerationError> {
+    self.check_vibranium_dir_exists()?;
+    let vibranium_project_directory = &self.config.vibranium_dir_path;
+
+    let pruned = match chains::read_genesis(vibranium_project_directory, network) {
+      Some(block_hash) => {
+        let to_tracking_error = |err: DeploymentTrackingError| error::ProjectGenerationError::Other(err.to_string());
+        DeploymentJournal::new(self.config).remove_chain(&block_hash).map_err(to_tracking_error)?;
+        let pruned = DeploymentTracker::new(self.config).remove_chain(&block_hash).map_err(to_tracking_error)?;
+        chains::forget_genesis(vibranium_project_directory, network)?;
+        pruned
+      },
+      None => 0,
+    };
+
+    let chain_directory = chains::data_dir(vibranium_project_directory, network);
+    if chain_directory.exists() {
+      info!("Removing: {}", &chain_directory.to_str().unwrap());
+      fs::remove_dir_all(&chain_directory)?;
+    }
+    Ok(pruned)
+  }
+
   pub fn check_vibranium_dir_exists(&self) -> Result<(), error::ProjectGenerationError> {
     if !self.config.vibranium_dir_path.exists() {
       return Err(error::ProjectGenerationError::VibraniumDirectoryNotFound);
@@ -109,3 +214,38 @@ impl<'a> ProjectGenerator<'a> {
     Ok(())
   }
 }
+
+#[cfg(test)]
+mod tests {
+
+  use super::ProjectGenerator;
+  use crate::blockchain::chains;
+  use crate::config::Config;
+  use crate::deployment::tracker::DeploymentTracker;
+  use std::fs;
+  use web3::types::{Address, H256};
+
+  #[test]
+  fn it_should_reset_chain_and_prune_its_tracking_data() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let vibranium_dir = tmp_dir.path().join(".vibranium");
+    fs::create_dir_all(vibranium_dir.join("chains").join("development")).unwrap();
+    fs::create_dir_all(vibranium_dir.join("chains").join("ganache")).unwrap();
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let tracker = DeploymentTracker::new(&config);
+    let (development_chain, ganache_chain) = (H256::from_low_u64_be(1), H256::from_low_u64_be(2));
+    tracker.create_database().unwrap();
+    tracker.track(development_chain, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+    tracker.track(ganache_chain, "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(2), None).unwrap();
+    chains::record_genesis(&vibranium_dir, "development", &development_chain).unwrap();
+    chains::record_genesis(&vibranium_dir, "ganache", &ganache_chain).unwrap();
+
+    assert_eq!(ProjectGenerator::new(&config).reset_chain("development").unwrap(), 1);
+    assert!(!vibranium_dir.join("chains").join("development").exists());
+    assert!(vibranium_dir.join("chains").join("ganache").exists());
+    assert!(tracker.get_all_smart_contract_tracking_data(&development_chain).unwrap().is_none());
+    assert!(tracker.get_all_smart_contract_tracking_data(&ganache_chain).unwrap().is_some());
+    assert_eq!(chains::read_genesis(&vibranium_dir, "development"), None);
+  }
+}
@@ -0,0 +1,155 @@
+// JavaScript dependencies of a project, e.g. of solcjs, ganache-cli or the
+// scaffolded frontend. They're installed with the package manager recorded as
+// `package_manager` in vibranium.toml.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::blockchain::support::SupportedBlockchainClients;
+use crate::compiler::support::SupportedCompilers;
+use crate::config::ProjectConfig;
+use crate::process::ProcessCommand;
+use super::error::ProjectGenerationError;
+
+pub const PACKAGE_JSON_FILE: &str = "package.json";
+const NPM: &str = "npm";
+const YARN: &str = "yarn";
+const PNPM: &str = "pnpm";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PackageManager {
+  Npm,
+  Yarn,
+  Pnpm,
+}
+
+impl PackageManager {
+  pub fn executable(&self) -> String {
+    let name = self.to_string();
+    if cfg!(target_os = "windows") {
+      format!("{}.cmd", name)
+    } else {
+      name
+    }
+  }
+
+  fn lock_file(&self) -> &'static str {
+    match self {
+      PackageManager::Npm => "package-lock.json",
+      PackageManager::Yarn => "yarn.lock",
+      PackageManager::Pnpm => "pnpm-lock.yaml",
+    }
+  }
+
+  // Prefers the package manager whose lock file is around, then the first
+  // one that's installed.
+  pub fn detect(project_path: &Path, vibranium_dir_path: &Path) -> Option<PackageManager> {
+    let package_managers = [PackageManager::Npm, PackageManager::Yarn, PackageManager::Pnpm];
+
+    package_managers.iter()
+      .find(|package_manager| project_path.join(package_manager.lock_file()).exists())
+      .or_else(|| package_managers.iter().find(|package_manager| package_manager.is_installed(vibranium_dir_path)))
+      .cloned()
+  }
+
+  fn is_installed(&self, vibranium_dir_path: &Path) -> bool {
+    ProcessCommand::new(self.executable())
+      .arg("--version")
+      .log_to(vibranium_dir_path)
+      .output()
+      .map(|output| output.status.success())
+      .unwrap_or(false)
+  }
+}
+
+impl FromStr for PackageManager {
+  type Err = ProjectGenerationError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      NPM => Ok(PackageManager::Npm),
+      YARN => Ok(PackageManager::Yarn),
+      PNPM => Ok(PackageManager::Pnpm),
+      _ => Err(ProjectGenerationError::UnsupportedPackageManager(s.to_string())),
+    }
+  }
+}
+
+impl fmt::Display for PackageManager {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PackageManager::Npm => write!(f, "{}", NPM),
+      PackageManager::Yarn => write!(f, "{}", YARN),
+      PackageManager::Pnpm => write!(f, "{}", PNPM),
+    }
+  }
+}
+
+// Tools configured in vibranium.toml that are distributed via npm.
+pub fn js_dependencies(project_config: &ProjectConfig) -> BTreeMap<&'static str, &'static str> {
+  let mut dependencies = BTreeMap::new();
+
+  let compiler = project_config.compiler.as_ref().and_then(|config| config.cmd.as_ref());
+  if let Some(Ok(SupportedCompilers::SolcJs)) = compiler.map(|cmd| cmd.parse()) {
+    dependencies.insert("solc", "^0.5.0");
+  }
+
+  let client = project_config.blockchain.as_ref().and_then(|config| config.cmd.as_ref());
+  if let Some(Ok(SupportedBlockchainClients::Ganache)) = client.map(|cmd| cmd.parse()) {
+    dependencies.insert("ganache-cli", "^6.4.0");
+  }
+
+  dependencies
+}
+
+pub fn package_json(dependencies: &BTreeMap<&str, &str>) -> String {
+  let dependencies: Vec<String> = dependencies.iter().map(|(name, version)| format!("    \"{}\": \"{}\"", name, version)).collect();
+  format!("{{
+  \"name\": \"vibranium-project\",
+  \"private\": true,
+  \"version\": \"0.0.0\",
+  \"devDependencies\": {{
+{}
+  }}
+}}
+", dependencies.join(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{js_dependencies, package_json, PackageManager};
+  use crate::config::{ProjectBlockchainConfig, ProjectCmdExecutionConfig, ProjectConfig};
+  use std::fs;
+
+  #[test]
+  fn it_should_parse_supported_package_managers() {
+    assert_eq!("yarn".parse::<PackageManager>().unwrap(), PackageManager::Yarn);
+    assert_eq!(PackageManager::Pnpm.to_string(), "pnpm");
+    assert!("bower".parse::<PackageManager>().is_err());
+  }
+
+  #[test]
+  fn it_should_prefer_package_manager_of_existing_lock_file() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    fs::write(tmp_dir.path().join("pnpm-lock.yaml"), "").unwrap();
+    assert_eq!(PackageManager::detect(tmp_dir.path(), tmp_dir.path()), Some(PackageManager::Pnpm));
+  }
+
+  #[test]
+  fn it_should_collect_js_dependencies_of_configured_tools() {
+    assert!(js_dependencies(&ProjectConfig::default()).is_empty());
+
+    let config = ProjectConfig {
+      compiler: Some(ProjectCmdExecutionConfig { cmd: Some("solcjs".to_string()), ..ProjectCmdExecutionConfig::default() }),
+      blockchain: Some(ProjectBlockchainConfig { cmd: Some("ganache-cli".to_string()), ..ProjectBlockchainConfig::default() }),
+      ..ProjectConfig::default()
+    };
+    let dependencies = js_dependencies(&config);
+    assert_eq!(dependencies.keys().cloned().collect::<Vec<&str>>(), vec!["ganache-cli", "solc"]);
+
+    let package: serde_json::Value = serde_json::from_str(&package_json(&dependencies)).unwrap();
+    assert_eq!(package["devDependencies"]["solc"], "^0.5.0");
+  }
+}
@@ -0,0 +1,87 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+
+use crate::blockchain;
+use crate::compiler;
+use crate::config;
+use crate::deployment;
+
+#[derive(Debug)]
+pub enum ReproduceError {
+  Compilation(compiler::error::CompilerError),
+  InvalidConfig(config::error::ConfigError),
+  Connection(blockchain::error::ConnectionError),
+  Tracking(deployment::error::DeploymentTrackingError),
+  Deployment(deployment::error::DeploymentError),
+  NotDeployed(String),
+  MissingRuntimeBytecode(String, String),
+  Io(io::Error),
+}
+
+impl Error for ReproduceError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      ReproduceError::Compilation(error) => Some(error),
+      ReproduceError::InvalidConfig(error) => Some(error),
+      ReproduceError::Connection(error) => Some(error),
+      ReproduceError::Tracking(error) => Some(error),
+      ReproduceError::Deployment(error) => Some(error),
+      ReproduceError::NotDeployed(_name) => None,
+      ReproduceError::MissingRuntimeBytecode(_name, _path) => None,
+      ReproduceError::Io(error) => Some(error),
+    }
+  }
+}
+
+impl fmt::Display for ReproduceError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ReproduceError::Compilation(error) => write!(f, "Couldn't recompile Smart Contracts: {}", error),
+      ReproduceError::InvalidConfig(error) => write!(f, "{}", error),
+      ReproduceError::Connection(error) => write!(f, "{}", error),
+      ReproduceError::Tracking(error) => write!(f, "Couldn't read tracking data: {}", error),
+      ReproduceError::Deployment(error) => write!(f, "{}", error),
+      ReproduceError::NotDeployed(name) => write!(f, "Couldn't find a tracked deployment of Smart Contract {} on the connected chain.", name),
+      ReproduceError::MissingRuntimeBytecode(name, path) => write!(f, "Couldn't find runtime bytecode of Smart Contract {} at {}. Please add `--bin-runtime` to the compiler options.", name, path),
+      ReproduceError::Io(error) => write!(f, "Couldn't reproduce Smart Contract: {}", error),
+    }
+  }
+}
+
+impl From<compiler::error::CompilerError> for ReproduceError {
+  fn from(error: compiler::error::CompilerError) -> Self {
+    ReproduceError::Compilation(error)
+  }
+}
+
+impl From<config::error::ConfigError> for ReproduceError {
+  fn from(error: config::error::ConfigError) -> Self {
+    ReproduceError::InvalidConfig(error)
+  }
+}
+
+impl From<blockchain::error::ConnectionError> for ReproduceError {
+  fn from(error: blockchain::error::ConnectionError) -> Self {
+    ReproduceError::Connection(error)
+  }
+}
+
+impl From<deployment::error::DeploymentTrackingError> for ReproduceError {
+  fn from(error: deployment::error::DeploymentTrackingError) -> Self {
+    ReproduceError::Tracking(error)
+  }
+}
+
+impl From<deployment::error::DeploymentError> for ReproduceError {
+  fn from(error: deployment::error::DeploymentError) -> Self {
+    ReproduceError::Deployment(error)
+  }
+}
+
+impl From<io::Error> for ReproduceError {
+  fn from(error: io::Error) -> Self {
+    ReproduceError::Io(error)
+  }
+}
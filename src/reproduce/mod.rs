@@ -0,0 +1,397 @@
+pub mod error;
+
+use crate::blockchain::connector::BlockchainConnector;
+use crate::config::{Config, ProjectConfig};
+use crate::deployment::planner::DeploymentPlanner;
+use crate::deployment::tracker::DeploymentTracker;
+use crate::utils;
+
+use error::ReproduceError;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use web3::types::Address;
+
+const ARTIFACT_EXTENSION_RUNTIME_BINARY: &str = "bin-runtime";
+
+#[derive(Debug, PartialEq)]
+pub struct ByteRangeDiff {
+  pub offset: usize,
+  pub expected: Vec<u8>,
+  pub actual: Vec<u8>,
+}
+
+impl fmt::Display for ByteRangeDiff {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let length = self.expected.len().max(self.actual.len());
+    write!(f, "0x{:04x}..0x{:04x}: expected {}, found {}", self.offset, self.offset + length, hex(&self.expected), hex(&self.actual))
+  }
+}
+
+#[derive(Debug)]
+pub struct Reproduction {
+  pub name: String,
+  pub address: Address,
+  pub compiled_length: usize,
+  pub deployed_length: usize,
+  pub diffs: Vec<ByteRangeDiff>,
+}
+
+impl Reproduction {
+  pub fn is_reproducible(&self) -> bool {
+    self.diffs.is_empty()
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum VerificationStatus {
+  Verified,
+  Drifted(Vec<ByteRangeDiff>),
+  // There's no code at the tracked address (anymore), e.g. because the chain
+  // has been reset or the Smart Contract self-destructed.
+  Missing,
+  MissingArtifact(String),
+}
+
+impl VerificationStatus {
+  // As recorded in the tracking data.
+  pub fn label(&self) -> &'static str {
+    match self {
+      VerificationStatus::Verified => "verified",
+      VerificationStatus::Drifted(_diffs) => "drifted",
+      VerificationStatus::Missing => "missing",
+      VerificationStatus::MissingArtifact(_path) => "unverifiable",
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct Verification {
+  pub name: String,
+  pub address: Address,
+  pub status: VerificationStatus,
+}
+
+impl Verification {
+  pub fn is_verified(&self) -> bool {
+    self.status == VerificationStatus::Verified
+  }
+}
+
+impl fmt::Display for Verification {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} at {:?}: ", self.name, self.address)?;
+    match &self.status {
+      VerificationStatus::Verified => write!(f, "verified"),
+      VerificationStatus::Drifted(diffs) => write!(f, "differs from local artifacts in {} byte range(s)", diffs.len()),
+      VerificationStatus::Missing => write!(f, "missing, there's no code at this address"),
+      VerificationStatus::MissingArtifact(path) => write!(f, "couldn't verify, there's no runtime bytecode at {}", path),
+    }
+  }
+}
+
+pub struct Reproducer<'a> {
+  config: &'a Config,
+  connector: &'a BlockchainConnector,
+}
+
+impl<'a> Reproducer<'a> {
+  pub fn new(config: &'a Config, connector: &'a BlockchainConnector) -> Reproducer<'a> {
+    Reproducer {
+      config,
+      connector,
+    }
+  }
+
+  // Compares the runtime bytecode in the artifacts directory with the code
+  // of every tracked deployment of the Smart Contract. Metadata appended by
+  // the compiler is ignored, as it changes with e.g. source file paths.
+  pub fn reproduce(&self, name: &str) -> Result<Vec<Reproduction>, ReproduceError> {
+    let project_config = self.config.read()?;
+
+    let (artifact_name, runtime_path) = self.runtime_artifact(&project_config, name);
+    if !runtime_path.exists() {
+      return Err(ReproduceError::MissingRuntimeBytecode(artifact_name, runtime_path.to_string_lossy().to_string()));
+    }
+    let compiled = read_runtime_bytecode(&runtime_path)?;
+
+    let addresses: Vec<Address> = self.tracked_deployments()?
+      .into_iter()
+      .filter(|(tracked_name, _address)| tracked_name == name)
+      .map(|(_name, address)| address)
+      .collect();
+
+    if addresses.is_empty() {
+      return Err(ReproduceError::NotDeployed(name.to_string()));
+    }
+
+    addresses.into_iter().map(|address| {
+      let deployed = self.deployed_code(address)?;
+
+      Ok(Reproduction {
+        name: name.to_string(),
+        address,
+        compiled_length: compiled.len(),
+        deployed_length: deployed.len(),
+        diffs: diff(&compiled, &deployed),
+      })
+    }).collect()
+  }
+
+  // Checks every tracked deployment on the connected chain (or the ones of
+  // the given Smart Contract) against the artifacts as they are, i.e. without
+  // recompiling.
+  pub fn verify(&self, name: Option<&str>) -> Result<Vec<Verification>, ReproduceError> {
+    let project_config = self.config.read()?;
+    let deployments: Vec<(String, Address)> = self.tracked_deployments()?
+      .into_iter()
+      .filter(|(tracked_name, _address)| name.map(|name| name == tracked_name).unwrap_or(true))
+      .collect();
+
+    if let (Some(name), true) = (name, deployments.is_empty()) {
+      return Err(ReproduceError::NotDeployed(name.to_string()));
+    }
+    self.verify_deployments(&project_config, deployments)
+  }
+
+  // Tagged Smart Contracts that haven't been deployed yet are left out.
+  pub fn verify_tagged(&self, tags: &[String]) -> Result<Vec<Verification>, ReproduceError> {
+    let project_config = self.config.read()?;
+    let names: Vec<String> = DeploymentPlanner::new(self.config).tagged(tags)?
+      .into_iter()
+      .map(|smart_contract_config| smart_contract_config.name)
+      .collect();
+    let deployments = self.tracked_deployments()?
+      .into_iter()
+      .filter(|(tracked_name, _address)| names.contains(tracked_name))
+      .collect();
+    self.verify_deployments(&project_config, deployments)
+  }
+
+  fn verify_deployments(&self, project_config: &ProjectConfig, deployments: Vec<(String, Address)>) -> Result<Vec<Verification>, ReproduceError> {
+    deployments.into_iter().map(|(name, address)| {
+      let (_artifact_name, runtime_path) = self.runtime_artifact(project_config, &name);
+      let deployed = self.deployed_code(address)?;
+
+      let status = if deployed.is_empty() {
+        VerificationStatus::Missing
+      } else if !runtime_path.exists() {
+        VerificationStatus::MissingArtifact(runtime_path.to_string_lossy().to_string())
+      } else {
+        let diffs = diff(&read_runtime_bytecode(&runtime_path)?, &deployed);
+        if diffs.is_empty() { VerificationStatus::Verified } else { VerificationStatus::Drifted(diffs) }
+      };
+
+      Ok(Verification { name, address, status })
+    }).collect()
+  }
+
+  fn runtime_artifact(&self, project_config: &ProjectConfig, name: &str) -> (String, PathBuf) {
+    let artifact_name = project_config.deployment.as_ref()
+      .and_then(|deployment_config| deployment_config.smart_contracts.iter().find(|config| config.name == name))
+      .and_then(|config| config.instance_of.clone())
+      .unwrap_or_else(|| name.to_string());
+
+    let runtime_path = self.config.project_path
+      .join(&project_config.sources.artifacts)
+      .join(format!("{}.{}", artifact_name, ARTIFACT_EXTENSION_RUNTIME_BINARY));
+
+    (artifact_name, runtime_path)
+  }
+
+  // Tracked deployments of the connected chain, ordered by name and address.
+  fn tracked_deployments(&self) -> Result<Vec<(String, Address)>, ReproduceError> {
+    let block_hash = match self.connector.get_first_block()?.and_then(|block| block.hash) {
+      Some(block_hash) => block_hash,
+      None => return Ok(vec![]),
+    };
+
+    let tracker = DeploymentTracker::new(self.config);
+    let mut deployments: Vec<(String, Address)> = tracker.get_all_smart_contract_tracking_data(&block_hash)?
+      .unwrap_or_default()
+      .into_values()
+      .map(|entry| (entry.name, entry.address))
+      .collect();
+    deployments.sort();
+    Ok(deployments)
+  }
+
+  fn deployed_code(&self, address: Address) -> Result<Vec<u8>, ReproduceError> {
+    let mut deployed = self.connector.code(address, None)?.0;
+    utils::strip_metadata(&mut deployed);
+    Ok(deployed)
+  }
+}
+
+fn read_runtime_bytecode(runtime_path: &Path) -> Result<Vec<u8>, ReproduceError> {
+  let mut compiled = utils::decode_bytecode(&fs::read_to_string(runtime_path)?);
+  utils::strip_metadata(&mut compiled);
+  Ok(compiled)
+}
+
+// Consecutive differing bytes are grouped into a single range.
+pub fn diff(expected: &[u8], actual: &[u8]) -> Vec<ByteRangeDiff> {
+  let mut diffs: Vec<ByteRangeDiff> = vec![];
+
+  for offset in 0..expected.len().max(actual.len()) {
+    let (expected_byte, actual_byte) = (expected.get(offset), actual.get(offset));
+    if expected_byte == actual_byte {
+      continue;
+    }
+
+    match diffs.last_mut() {
+      Some(last) if last.offset + last.expected.len().max(last.actual.len()) == offset => {
+        last.expected.extend(expected_byte);
+        last.actual.extend(actual_byte);
+      },
+      _ => diffs.push(ByteRangeDiff {
+        offset,
+        expected: expected_byte.into_iter().cloned().collect(),
+        actual: actual_byte.into_iter().cloned().collect(),
+      }),
+    }
+  }
+  diffs
+}
+
+fn hex(bytes: &[u8]) -> String {
+  if bytes.is_empty() {
+    return "nothing".to_string();
+  }
+  bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{diff, ByteRangeDiff, Reproducer, VerificationStatus};
+  use crate::config::Config;
+  use crate::deployment::tracker::DeploymentTracker;
+  use crate::testing::MockTransport;
+  use std::fs;
+  use std::str::FromStr;
+  use web3::types::{Address, Block, H256};
+
+  const BLOCK_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+  const METADATA: &str = "a165627a7a72305820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0029";
+
+  #[test]
+  fn it_should_group_differing_bytes_into_ranges() {
+    assert_eq!(diff(&[1, 2, 3, 4], &[1, 2, 3, 4]), vec![]);
+    assert_eq!(diff(&[1, 2, 3, 4, 5], &[1, 9, 9, 4]), vec![
+      ByteRangeDiff { offset: 1, expected: vec![2, 3], actual: vec![9, 9] },
+      ByteRangeDiff { offset: 4, expected: vec![5], actual: vec![] },
+    ]);
+    assert_eq!(diff(&[1, 2, 3], &[1, 9, 9, 4]).len(), 1);
+    assert_eq!(diff(&[1, 2, 3], &[1, 9, 9, 4])[0].to_string(), "0x0001..0x0004: expected 0203, found 090904");
+  }
+
+  #[test]
+  fn it_should_compare_runtime_bytecode_with_deployed_code_ignoring_metadata() {
+    let tmp_dir = crate::testing::project_with_config("
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+    ");
+    fs::create_dir_all(tmp_dir.path().join("artifacts")).unwrap();
+    fs::write(tmp_dir.path().join("artifacts").join("Simple.bin-runtime"), format!("6080604052{}", METADATA)).unwrap();
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let tracker = DeploymentTracker::new(&config);
+    let block_hash = H256::from_str(&BLOCK_HASH[2..]).unwrap();
+    tracker.create_database().unwrap();
+    tracker.track(block_hash, "Simple".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+    tracker.track(block_hash, "Simple".to_string(), "6081".to_string(), &vec![], Address::from_low_u64_be(2), None).unwrap();
+
+    let transport = MockTransport::new();
+    transport.set_default_response("eth_getBlockByNumber", serde_json::to_value(Block::<H256> {
+      hash: Some(block_hash),
+      ..Block::default()
+    }).unwrap());
+    // Different metadata, e.g. due to different source file paths
+    transport.add_response("eth_getCode", serde_json::json!(format!("0x6080604052{}", METADATA.replace("ff", "ee"))));
+    transport.add_response("eth_getCode", serde_json::json!("0x6080604053"));
+
+    let connector = transport.connector();
+    let reproductions = Reproducer::new(&config, &connector).reproduce("Simple").unwrap();
+
+    assert_eq!(reproductions.len(), 2);
+    assert!(reproductions[0].is_reproducible());
+    assert_eq!(reproductions[0].compiled_length, 5);
+    assert_eq!(reproductions[1].diffs, vec![ByteRangeDiff { offset: 4, expected: vec![0x52], actual: vec![0x53] }]);
+  }
+
+  #[test]
+  fn it_should_verify_all_tracked_deployments_against_artifacts() {
+    let tmp_dir = crate::testing::project_with_config("
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+    ");
+    fs::create_dir_all(tmp_dir.path().join("artifacts")).unwrap();
+    fs::write(tmp_dir.path().join("artifacts").join("Simple.bin-runtime"), format!("6080604052{}", METADATA)).unwrap();
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let tracker = DeploymentTracker::new(&config);
+    let block_hash = H256::from_str(&BLOCK_HASH[2..]).unwrap();
+    tracker.create_database().unwrap();
+    tracker.track(block_hash, "Other".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+    tracker.track(block_hash, "Simple".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(2), None).unwrap();
+    tracker.track(block_hash, "Simple".to_string(), "6081".to_string(), &vec![], Address::from_low_u64_be(3), None).unwrap();
+    tracker.track(block_hash, "Simple".to_string(), "6082".to_string(), &vec![], Address::from_low_u64_be(4), None).unwrap();
+
+    let transport = MockTransport::new();
+    transport.set_default_response("eth_getBlockByNumber", serde_json::to_value(Block::<H256> {
+      hash: Some(block_hash),
+      ..Block::default()
+    }).unwrap());
+    transport.add_response("eth_getCode", serde_json::json!("0x6080"));
+    transport.add_response("eth_getCode", serde_json::json!(format!("0x6080604052{}", METADATA)));
+    transport.add_response("eth_getCode", serde_json::json!("0x6080604053"));
+    transport.add_response("eth_getCode", serde_json::json!("0x"));
+
+    let connector = transport.connector();
+    let verifications = Reproducer::new(&config, &connector).verify(None).unwrap();
+
+    let statuses: Vec<&VerificationStatus> = verifications.iter().map(|verification| &verification.status).collect();
+    assert!(match statuses[0] { VerificationStatus::MissingArtifact(path) => path.ends_with("Other.bin-runtime"), _ => false });
+    assert_eq!(statuses[1], &VerificationStatus::Verified);
+    assert_eq!(statuses[2], &VerificationStatus::Drifted(vec![ByteRangeDiff { offset: 4, expected: vec![0x52], actual: vec![0x53] }]));
+    assert_eq!(statuses[3], &VerificationStatus::Missing);
+    assert!(Reproducer::new(&config, &connector).verify(Some("Unknown")).is_err());
+  }
+
+  #[test]
+  fn it_should_only_verify_tagged_deployments() {
+    let tmp_dir = crate::testing::project_with_config("
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+      [deployment]
+        smart_contracts = [{ name = \"Simple\", tags = [\"core\"] }, { name = \"Mock\", tags = [\"mocks\"] }, { name = \"Pending\", tags = [\"core\"] }]
+    ");
+    fs::create_dir_all(tmp_dir.path().join("artifacts")).unwrap();
+    fs::write(tmp_dir.path().join("artifacts").join("Simple.bin-runtime"), format!("6080604052{}", METADATA)).unwrap();
+
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let tracker = DeploymentTracker::new(&config);
+    let block_hash = H256::from_str(&BLOCK_HASH[2..]).unwrap();
+    tracker.create_database().unwrap();
+    tracker.track(block_hash, "Mock".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+    tracker.track(block_hash, "Simple".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(2), None).unwrap();
+
+    let transport = MockTransport::new();
+    transport.set_default_response("eth_getBlockByNumber", serde_json::to_value(Block::<H256> {
+      hash: Some(block_hash),
+      ..Block::default()
+    }).unwrap());
+    transport.add_response("eth_getCode", serde_json::json!(format!("0x6080604052{}", METADATA)));
+
+    let connector = transport.connector();
+    let verifications = Reproducer::new(&config, &connector).verify_tagged(&["core".to_string()]).unwrap();
+
+    assert_eq!(verifications.len(), 1);
+    assert_eq!(verifications[0].name, "Simple");
+    assert_eq!(verifications[0].status, VerificationStatus::Verified);
+  }
+}
@@ -0,0 +1,256 @@
+//! Detection of secrets that are embedded in vibranium.toml instead of being
+//! referenced from the environment via `${env.NAME}`.
+
+use crate::blockchain::connector::BlockchainConnectorConfig;
+use crate::config;
+
+use std::env;
+use std::fmt;
+
+pub const ENV_REFERENCE_PREFIX: &str = "${env.";
+const ENV_REFERENCE_SUFFIX: &str = "}";
+const LOCAL_HOSTS: &[&str] = &["localhost", "127.0.0.1", "0.0.0.0", "::1", "[::1]"];
+const SECRET_KEY_NAMES: &[&str] = &["private", "mnemonic", "secret", "password", "seed", "api_key", "apikey"];
+const MNEMONIC_WORD_COUNTS: &[usize] = &[12, 15, 18, 21, 24];
+const MIN_API_KEY_LENGTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecretKind {
+  PrivateKey,
+  Mnemonic,
+  ApiKey,
+  Secret,
+}
+
+impl fmt::Display for SecretKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SecretKind::PrivateKey => write!(f, "private key"),
+      SecretKind::Mnemonic => write!(f, "mnemonic"),
+      SecretKind::ApiKey => write!(f, "API key"),
+      SecretKind::Secret => write!(f, "secret"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecretFinding {
+  pub option: String,
+  pub kind: SecretKind,
+}
+
+impl fmt::Display for SecretFinding {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "`{}` looks like an embedded {}", self.option, self.kind)
+  }
+}
+
+// Scans all string values of a parsed vibranium.toml, including options
+// Vibranium itself doesn't know about.
+pub fn scan(config: &toml::Value) -> Vec<SecretFinding> {
+  let mut findings = vec![];
+  scan_value("", config, &mut findings);
+  findings
+}
+
+fn scan_value(option: &str, value: &toml::Value, findings: &mut Vec<SecretFinding>) {
+  match value {
+    toml::Value::Table(table) => {
+      for (key, value) in table {
+        let option = if option.is_empty() { key.to_owned() } else { format!("{}.{}", option, key) };
+        scan_value(&option, value, findings);
+      }
+    },
+    toml::Value::Array(values) => {
+      for (i, value) in values.iter().enumerate() {
+        scan_value(&format!("{}[{}]", option, i), value, findings);
+      }
+    },
+    toml::Value::String(value) => {
+      if let Some(kind) = detect_secret(option, value) {
+        findings.push(SecretFinding { option: option.to_owned(), kind });
+      }
+    },
+    _ => {},
+  }
+}
+
+fn detect_secret(option: &str, value: &str) -> Option<SecretKind> {
+  let value = value.trim();
+  if value.is_empty() || value.contains(ENV_REFERENCE_PREFIX) {
+    return None;
+  }
+
+  let name = option.rsplit('.').next().unwrap_or(option).to_lowercase();
+  let has_secret_name = SECRET_KEY_NAMES.iter().any(|secret_name| name.contains(secret_name));
+
+  if is_mnemonic(value) {
+    Some(SecretKind::Mnemonic)
+  } else if is_url(value) {
+    if url_contains_api_key(value) { Some(SecretKind::ApiKey) } else { None }
+  } else if (has_secret_name || name.contains("key")) && is_private_key(value) {
+    Some(SecretKind::PrivateKey)
+  } else if has_secret_name {
+    Some(SecretKind::Secret)
+  } else {
+    None
+  }
+}
+
+fn is_private_key(value: &str) -> bool {
+  let key = value.trim_start_matches("0x");
+  key.len() == 64 && key.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn is_mnemonic(value: &str) -> bool {
+  let words: Vec<&str> = value.split_whitespace().collect();
+  MNEMONIC_WORD_COUNTS.contains(&words.len())
+    && words.iter().all(|word| word.len() >= 3 && word.len() <= 8 && word.chars().all(|c| c.is_ascii_lowercase()))
+}
+
+fn is_url(value: &str) -> bool {
+  ["http://", "https://", "ws://", "wss://"].iter().any(|scheme| value.starts_with(scheme))
+}
+
+// Hosted node providers usually take the API key as a path segment
+// (e.g. `/v3/<key>`) or as a query parameter.
+fn url_contains_api_key(url: &str) -> bool {
+  let (path, query) = match url.find('?') {
+    Some(index) => (&url[..index], &url[index + 1..]),
+    None => (url, ""),
+  };
+
+  let path = path.splitn(4, '/').nth(3).unwrap_or("");
+  let path_has_key = path.split('/').any(|segment| segment.len() >= MIN_API_KEY_LENGTH && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+  let query_has_key = query.split('&').any(|param| {
+    let name = param.split('=').next().unwrap_or("").to_lowercase();
+    name.contains("key") || name.contains("token")
+  });
+
+  path_has_key || query_has_key
+}
+
+// Only chains reachable on this machine count as development networks.
+// Hosts are taken from the URL instead of the host option if one is set.
+pub fn is_dev_network(config: &BlockchainConnectorConfig) -> bool {
+  let is_local_url = |url: &str| config::parse_network_url(url).map(|(_protocol, host, _port)| LOCAL_HOSTS.contains(&host.as_str())).unwrap_or(false);
+  let is_local = match &config.url {
+    Some(url) => is_local_url(url),
+    None => LOCAL_HOSTS.contains(&config.host.as_str()),
+  };
+  is_local && config.fallbacks.iter().flatten().all(|url| is_local_url(url))
+}
+
+// Replaces all `${env.NAME}` references with the value of the environment
+// variable. Returns the name of the first variable that isn't set.
+pub fn resolve_env_references(value: &str) -> Result<String, String> {
+  resolve_env_references_with(value, |name| env::var(name).ok())
+}
+
+// Like `resolve_env_references()`, with variables looked up by `lookup`.
+pub fn resolve_env_references_with<F: Fn(&str) -> Option<String>>(value: &str, lookup: F) -> Result<String, String> {
+  let mut resolved = String::new();
+  let mut rest = value;
+  while let Some(start) = rest.find(ENV_REFERENCE_PREFIX) {
+    let name_start = start + ENV_REFERENCE_PREFIX.len();
+    let name_end = match rest[name_start..].find(ENV_REFERENCE_SUFFIX) {
+      Some(end) => name_start + end,
+      None => break,
+    };
+    let name = &rest[name_start..name_end];
+    resolved.push_str(&rest[..start]);
+    resolved.push_str(&lookup(name).ok_or_else(|| name.to_string())?);
+    rest = &rest[name_end + ENV_REFERENCE_SUFFIX.len()..];
+  }
+  resolved.push_str(rest);
+  Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{scan, is_dev_network, resolve_env_references, resolve_env_references_with, SecretKind};
+  use crate::blockchain::connector::BlockchainConnectorConfig;
+
+  fn kinds(config: &str) -> Vec<(String, SecretKind)> {
+    scan(&toml::from_str(config).unwrap()).into_iter().map(|finding| (finding.option, finding.kind)).collect()
+  }
+
+  #[test]
+  fn it_should_detect_private_keys_and_mnemonics() {
+    let findings = kinds(r#"
+      [accounts]
+      deployer_private_key = "0x4f3edf983ac636a65a842ce7c78d9aa706d3b113bce9c46f30d7d21715b23b1d"
+      mnemonic = "candy maple cake sugar pudding cream honey rich smooth crumble sweet treat"
+    "#);
+
+    assert_eq!(findings, vec![
+      ("accounts.deployer_private_key".to_string(), SecretKind::PrivateKey),
+      ("accounts.mnemonic".to_string(), SecretKind::Mnemonic),
+    ]);
+  }
+
+  #[test]
+  fn it_should_detect_api_keys_in_endpoint_urls() {
+    let findings = kinds(r#"
+      [blockchain.connector]
+      host = "localhost"
+      fallbacks = ["https://mainnet.infura.io/v3/9aa3d95b3bc440fa88ea12eaa4456161", "https://rpc.example.org?apikey=abc", "https://rpc.example.org/v1"]
+    "#);
+
+    assert_eq!(findings, vec![
+      ("blockchain.connector.fallbacks[0]".to_string(), SecretKind::ApiKey),
+      ("blockchain.connector.fallbacks[1]".to_string(), SecretKind::ApiKey),
+    ]);
+  }
+
+  #[test]
+  fn it_should_ignore_env_references_and_regular_values() {
+    let findings = kinds(r#"
+      [constants]
+      MERKLE_ROOT = "0x4f3edf983ac636a65a842ce7c78d9aa706d3b113bce9c46f30d7d21715b23b1d"
+      API_KEY = "${env.API_KEY}"
+
+      [blockchain.connector]
+      fallbacks = ["https://mainnet.infura.io/v3/${env.INFURA_KEY}"]
+    "#);
+
+    assert!(findings.is_empty());
+  }
+
+  #[test]
+  fn it_should_only_consider_local_chains_dev_networks() {
+    let mut config = BlockchainConnectorConfig::default();
+    assert!(is_dev_network(&config));
+
+    config.fallbacks = Some(vec!["http://127.0.0.1:8546".to_string()]);
+    assert!(is_dev_network(&config));
+
+    config.fallbacks = Some(vec!["https://rpc.example.org".to_string()]);
+    assert!(!is_dev_network(&config));
+  }
+
+  #[test]
+  fn it_should_take_the_host_of_dev_networks_from_their_url() {
+    let mut config = BlockchainConnectorConfig {
+      url: Some("https://mainnet.infura.io/v3/key".to_string()),
+      ..BlockchainConnectorConfig::default()
+    };
+    assert_eq!(config.host, "localhost");
+    assert!(!is_dev_network(&config));
+
+    config.url = Some("http://[::1]:8545".to_string());
+    assert!(is_dev_network(&config));
+  }
+
+  #[test]
+  fn it_should_resolve_env_references() {
+    std::env::set_var("VIBRANIUM_TEST_SECRET", "s3cr3t");
+    assert_eq!(resolve_env_references("https://rpc.example.org/${env.VIBRANIUM_TEST_SECRET}").unwrap(), "https://rpc.example.org/s3cr3t");
+    assert_eq!(resolve_env_references("${env.VIBRANIUM_TEST_MISSING}"), Err("VIBRANIUM_TEST_MISSING".to_string()));
+
+    let lookup = |name: &str| if name == "VIBRANIUM_TEST_MISSING" { Some("forwarded".to_string()) } else { None };
+    assert_eq!(resolve_env_references_with("${env.VIBRANIUM_TEST_MISSING}", lookup).unwrap(), "forwarded");
+    assert_eq!(resolve_env_references_with("${env.VIBRANIUM_TEST_SECRET}", lookup), Err("VIBRANIUM_TEST_SECRET".to_string()));
+  }
+}
@@ -0,0 +1,40 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum StatsError {
+  Io(io::Error),
+  Serialization(serde_json::Error),
+}
+
+impl Error for StatsError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      StatsError::Io(error) => Some(error),
+      StatsError::Serialization(error) => Some(error),
+    }
+  }
+}
+
+impl fmt::Display for StatsError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      StatsError::Io(error) => write!(f, "Couldn't access usage statistics: {}", error),
+      StatsError::Serialization(error) => write!(f, "Couldn't read usage statistics: {}", error),
+    }
+  }
+}
+
+impl From<io::Error> for StatsError {
+  fn from(error: io::Error) -> Self {
+    StatsError::Io(error)
+  }
+}
+
+impl From<serde_json::Error> for StatsError {
+  fn from(error: serde_json::Error) -> Self {
+    StatsError::Serialization(error)
+  }
+}
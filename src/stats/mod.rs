@@ -0,0 +1,226 @@
+//! Opt-in usage statistics, enabled via `stats = true` in vibranium.toml.
+//! Compile and deploy runs are recorded in `.vibranium/stats.json` and never
+//! leave the machine.
+
+pub mod error;
+
+use crate::config::Config;
+use crate::utils;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+pub const STATS_FILE: &str = "stats.json";
+pub const DEFAULT_WINDOW_DAYS: u64 = 30;
+const MAX_RECORDED_RUNS: usize = 1000;
+const SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RunKind {
+  Compile,
+  Deploy,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Run {
+  pub kind: RunKind,
+  pub started_at: u64,
+  pub duration_ms: u64,
+  pub success: bool,
+  pub gas_used: Option<u64>,
+}
+
+impl Run {
+  pub fn finished(kind: RunKind, started_at: SystemTime, started: Instant, success: bool, gas_used: Option<u64>) -> Run {
+    Run {
+      kind,
+      started_at: started_at.duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0),
+      duration_ms: started.elapsed().as_millis() as u64,
+      success,
+      gas_used,
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Stats {
+  runs: Vec<Run>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DailyStats {
+  pub day: u64,
+  pub compile_runs: usize,
+  pub compile_ms: u64,
+  pub deploy_runs: usize,
+  pub failed_runs: usize,
+  pub gas_used: u64,
+}
+
+impl DailyStats {
+  // The day formatted as YYYY-MM-DD.
+  pub fn date(&self) -> String {
+    utils::format_day(self.day)
+  }
+
+  pub fn average_compile_ms(&self) -> Option<u64> {
+    if self.compile_runs == 0 {
+      None
+    } else {
+      Some(self.compile_ms / self.compile_runs as u64)
+    }
+  }
+}
+
+impl fmt::Display for DailyStats {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}  compiles: {:>3}", self.date(), self.compile_runs)?;
+    match self.average_compile_ms() {
+      Some(average) => write!(f, " (avg. {:>6}ms)", average)?,
+      None => write!(f, "{:>16}", "")?,
+    }
+    write!(f, "  deploys: {:>3}  gas: {:>10}", self.deploy_runs, self.gas_used)?;
+    if self.failed_runs > 0 {
+      write!(f, "  failed: {}", self.failed_runs)?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct StatsSummary {
+  pub window_days: u64,
+  pub days: Vec<DailyStats>,
+}
+
+impl StatsSummary {
+  pub fn compile_runs(&self) -> usize {
+    self.days.iter().map(|day| day.compile_runs).sum()
+  }
+
+  pub fn deploy_runs(&self) -> usize {
+    self.days.iter().map(|day| day.deploy_runs).sum()
+  }
+
+  pub fn gas_used(&self) -> u64 {
+    self.days.iter().map(|day| day.gas_used).sum()
+  }
+
+  pub fn average_compile_ms(&self) -> Option<u64> {
+    let compile_ms: u64 = self.days.iter().map(|day| day.compile_ms).sum();
+    match self.compile_runs() {
+      0 => None,
+      runs => Some(compile_ms / runs as u64),
+    }
+  }
+}
+
+pub struct UsageStats<'a> {
+  config: &'a Config,
+}
+
+impl<'a> UsageStats<'a> {
+  pub fn new(config: &'a Config) -> UsageStats<'a> {
+    UsageStats {
+      config
+    }
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    self.config.vibranium_dir_path.exists() && self.config.read().ok().and_then(|config| config.stats).unwrap_or(false)
+  }
+
+  // Only the most recent runs are kept.
+  pub fn record(&self, run: Run) -> Result<(), error::StatsError> {
+    let mut stats = self.read()?;
+    stats.runs.push(run);
+    let overflow = stats.runs.len().saturating_sub(MAX_RECORDED_RUNS);
+    stats.runs.drain(0..overflow);
+    fs::write(self.get_stats_file(), serde_json::to_string_pretty(&stats)?)?;
+    Ok(())
+  }
+
+  pub fn runs(&self) -> Result<Vec<Run>, error::StatsError> {
+    Ok(self.read()?.runs)
+  }
+
+  // Runs of the last `window_days` days up until `now`, grouped by day.
+  pub fn summary(&self, now: u64, window_days: u64) -> Result<StatsSummary, error::StatsError> {
+    let since = now.saturating_sub(window_days * SECONDS_PER_DAY);
+    let mut days: BTreeMap<u64, DailyStats> = BTreeMap::new();
+
+    for run in self.read()?.runs.into_iter().filter(|run| run.started_at >= since && run.started_at <= now) {
+      let day = run.started_at / SECONDS_PER_DAY;
+      let stats = days.entry(day).or_insert_with(|| DailyStats { day, ..DailyStats::default() });
+      if !run.success {
+        stats.failed_runs += 1;
+        continue;
+      }
+      match run.kind {
+        RunKind::Compile => {
+          stats.compile_runs += 1;
+          stats.compile_ms += run.duration_ms;
+        },
+        RunKind::Deploy => {
+          stats.deploy_runs += 1;
+          stats.gas_used += run.gas_used.unwrap_or(0);
+        },
+      }
+    }
+
+    Ok(StatsSummary {
+      window_days,
+      days: days.into_values().collect(),
+    })
+  }
+
+  fn read(&self) -> Result<Stats, error::StatsError> {
+    let stats_file = self.get_stats_file();
+    if !stats_file.exists() {
+      return Ok(Stats::default());
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(stats_file)?)?)
+  }
+
+  fn get_stats_file(&self) -> PathBuf {
+    self.config.vibranium_dir_path.join(STATS_FILE)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{Run, RunKind, UsageStats, SECONDS_PER_DAY};
+  use crate::config::Config;
+
+  fn run(kind: RunKind, started_at: u64, duration_ms: u64, gas_used: Option<u64>) -> Run {
+    Run { kind, started_at, duration_ms, success: true, gas_used }
+  }
+
+  #[test]
+  fn it_should_summarize_runs_per_day_within_window() {
+    let tmp_dir = crate::testing::project_dir();
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    let stats = UsageStats::new(&config);
+    let now = 100 * SECONDS_PER_DAY + 3600;
+
+    stats.record(run(RunKind::Compile, now - 40 * SECONDS_PER_DAY, 5000, None)).unwrap();
+    stats.record(run(RunKind::Compile, now - SECONDS_PER_DAY, 1000, None)).unwrap();
+    stats.record(run(RunKind::Compile, now - SECONDS_PER_DAY, 3000, None)).unwrap();
+    stats.record(run(RunKind::Deploy, now - 10, 200, Some(21_000))).unwrap();
+    stats.record(Run { success: false, ..run(RunKind::Deploy, now - 5, 100, None) }).unwrap();
+
+    let summary = stats.summary(now, 30).unwrap();
+    assert_eq!(summary.days.len(), 2);
+    assert_eq!(summary.days[0].average_compile_ms(), Some(2000));
+    assert_eq!(summary.days[1].failed_runs, 1);
+    assert_eq!(summary.compile_runs(), 2);
+    assert_eq!(summary.deploy_runs(), 1);
+    assert_eq!(summary.gas_used(), 21_000);
+    assert_eq!(stats.runs().unwrap().len(), 5);
+  }
+}
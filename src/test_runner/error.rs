@@ -0,0 +1,87 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+
+use crate::blockchain;
+use crate::compiler;
+use crate::config;
+use crate::project_generator;
+
+#[derive(Debug)]
+pub enum TestError {
+  VibraniumDirectoryNotFound(project_generator::error::ProjectGenerationError),
+  InvalidConfig(config::error::ConfigError),
+  NoTests(String),
+  Compilation(compiler::error::CompilerError),
+  InvalidAbi(String, serde_json::Error),
+  InvalidBytecode(String),
+  NotADevNetwork(String),
+  MissingAccount,
+  ReadOnlyMode,
+  Connection(blockchain::error::ConnectionError),
+  Node(blockchain::error::NodeError),
+  Io(io::Error),
+}
+
+impl Error for TestError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      TestError::VibraniumDirectoryNotFound(error) => Some(error),
+      TestError::InvalidConfig(error) => Some(error),
+      TestError::NoTests(_directory) => None,
+      TestError::Compilation(error) => Some(error),
+      TestError::InvalidAbi(_name, error) => Some(error),
+      TestError::InvalidBytecode(_name) => None,
+      TestError::NotADevNetwork(_host) => None,
+      TestError::MissingAccount => None,
+      TestError::ReadOnlyMode => None,
+      TestError::Connection(error) => Some(error),
+      TestError::Node(error) => Some(error),
+      TestError::Io(error) => Some(error),
+    }
+  }
+}
+
+impl fmt::Display for TestError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      TestError::VibraniumDirectoryNotFound(error) => write!(f, "{}", error),
+      TestError::InvalidConfig(error) => write!(f, "{}", error),
+      TestError::NoTests(directory) => write!(f, "Couldn't find any Solidity tests in {}", directory),
+      TestError::Compilation(error) => write!(f, "Couldn't compile tests: {}", error),
+      TestError::InvalidAbi(name, error) => write!(f, "Couldn't read ABI of test contract {}: {}", name, error),
+      TestError::InvalidBytecode(name) => write!(f, "Couldn't read bytecode of test contract {}. Does it link libraries?", name),
+      TestError::NotADevNetwork(host) => write!(f, "Tests only run against development networks, but the connector is configured for {}", host),
+      TestError::MissingAccount => write!(f, "Couldn't find an account to run tests with"),
+      TestError::ReadOnlyMode => write!(f, "Couldn't run tests. Sending transactions is disabled in read-only mode."),
+      TestError::Connection(error) => write!(f, "{}", error),
+      TestError::Node(error) => write!(f, "Couldn't start node for tests: {}", error),
+      TestError::Io(error) => write!(f, "Couldn't run tests: {}", error),
+    }
+  }
+}
+
+impl From<config::error::ConfigError> for TestError {
+  fn from(error: config::error::ConfigError) -> Self {
+    TestError::InvalidConfig(error)
+  }
+}
+
+impl From<compiler::error::CompilerError> for TestError {
+  fn from(error: compiler::error::CompilerError) -> Self {
+    TestError::Compilation(error)
+  }
+}
+
+impl From<blockchain::error::ConnectionError> for TestError {
+  fn from(error: blockchain::error::ConnectionError) -> Self {
+    TestError::Connection(error)
+  }
+}
+
+impl From<io::Error> for TestError {
+  fn from(error: io::Error) -> Self {
+    TestError::Io(error)
+  }
+}
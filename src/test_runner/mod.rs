@@ -0,0 +1,435 @@
+// Solidity tests are contracts in `test/` with public functions whose names
+// start with `test` and that don't take any arguments. Every test runs against
+// a fresh instance of its contract, deployed from the first account of the
+// node, after `setUp()` has been called if the contract has one. A test
+// passes if its transaction succeeds, tests named `testFail...` pass if it
+// reverts. Fixtures, i.e. the project's Smart Contracts under test, are
+// deployed by the test contracts themselves, in their constructor or in
+// `setUp()`. On nodes that support snapshots, the chain is reverted to its
+// state before the run once all tests are done.
+
+pub mod error;
+
+use crate::blockchain::chain_control::ChainControl;
+use crate::blockchain::connector::BlockchainConnector;
+use crate::compiler::declared_smart_contracts;
+use crate::config::Config;
+use crate::deployment::registry::{revert_data, AbiRegistry};
+use crate::interface::AbiEntry;
+use error::TestError;
+
+use rustc_hex::FromHex;
+use sha3::{Digest, Keccak256};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use web3::types::{Address, Bytes, CallRequest, TransactionRequest, U256};
+
+pub const TEST_DIRECTORY: &str = "test";
+pub const TEST_SOURCE_PATTERN: &str = "test/**/*.sol";
+pub const TEST_ARTIFACTS_DIRECTORY: &str = "test-artifacts";
+const ARTIFACT_EXTENSION_ABI: &str = "abi";
+const ARTIFACT_EXTENSION_BINARY: &str = "bin";
+const TEST_FUNCTION_PREFIX: &str = "test";
+const FAILING_TEST_FUNCTION_PREFIX: &str = "testFail";
+const SET_UP_FUNCTION: &str = "setUp";
+// Test contracts deploy fixtures, so they need more gas than most
+// deployments.
+pub const DEFAULT_GAS_LIMIT: usize = 6_000_000;
+
+#[derive(Debug, Default)]
+pub struct TestOptions {
+  // Only tests whose `Contract.function` name contains the filter are run.
+  pub filter: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestContract {
+  pub name: String,
+  pub source: PathBuf,
+  pub bytecode: Vec<u8>,
+  pub tests: Vec<String>,
+  pub has_set_up: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+  pub contract: String,
+  pub function: String,
+  pub passed: bool,
+  // Gas used by the test's transaction, not including the deployment of the
+  // test contract and `setUp()`.
+  pub gas_used: Option<U256>,
+  pub reason: Option<String>,
+}
+
+impl fmt::Display for TestResult {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}.{}", self.contract, self.function)
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct TestReport {
+  pub results: Vec<TestResult>,
+}
+
+impl TestReport {
+  pub fn passed(&self) -> usize {
+    self.results.iter().filter(|result| result.passed).count()
+  }
+
+  pub fn failed(&self) -> usize {
+    self.results.len() - self.passed()
+  }
+
+  pub fn is_success(&self) -> bool {
+    self.failed() == 0
+  }
+}
+
+struct Execution {
+  succeeded: bool,
+  gas_used: Option<U256>,
+  contract_address: Option<Address>,
+  reason: Option<String>,
+}
+
+pub struct TestRunner<'a> {
+  config: &'a Config,
+  connector: &'a BlockchainConnector,
+  gas_limit: U256,
+}
+
+impl<'a> TestRunner<'a> {
+  pub fn new(config: &'a Config, connector: &'a BlockchainConnector) -> TestRunner<'a> {
+    let gas_limit = config.read().ok()
+      .and_then(|project_config| project_config.deployment)
+      .and_then(|deployment_config| deployment_config.gas_limit)
+      .unwrap_or(DEFAULT_GAS_LIMIT);
+    TestRunner {
+      config,
+      connector,
+      gas_limit: U256::from(gas_limit),
+    }
+  }
+
+  // Test contracts declared in the given sources, as compiled to
+  // `artifacts_dir`. Contracts without tests, e.g. helpers, and contracts
+  // that can't be deployed, e.g. interfaces, are left out.
+  pub fn discover(&self, artifacts_dir: &Path, sources: &[PathBuf]) -> Result<Vec<TestContract>, TestError> {
+    let mut contracts = vec![];
+    for source in sources {
+      for name in declared_smart_contracts(&fs::read_to_string(source)?) {
+        let abi_path = artifacts_dir.join(&name).with_extension(ARTIFACT_EXTENSION_ABI);
+        let bin_path = artifacts_dir.join(&name).with_extension(ARTIFACT_EXTENSION_BINARY);
+        if !abi_path.exists() || !bin_path.exists() {
+          continue;
+        }
+
+        let abi: Vec<AbiEntry> = serde_json::from_str(&fs::read_to_string(&abi_path)?)
+          .map_err(|err| TestError::InvalidAbi(name.to_owned(), err))?;
+        let functions: Vec<&str> = abi.iter()
+          .filter(|entry| entry.kind == "function" && entry.inputs.as_ref().is_none_or(Vec::is_empty))
+          .filter_map(|entry| entry.name.as_deref())
+          .collect();
+        let tests: Vec<String> = functions.iter()
+          .filter(|function| function.starts_with(TEST_FUNCTION_PREFIX))
+          .map(|function| function.to_string())
+          .collect();
+
+        let bytecode = fs::read_to_string(&bin_path)?;
+        let bytecode = bytecode.trim().trim_start_matches("0x");
+        if tests.is_empty() || bytecode.is_empty() {
+          continue;
+        }
+
+        contracts.push(TestContract {
+          bytecode: bytecode.from_hex().map_err(|_err| TestError::InvalidBytecode(name.to_owned()))?,
+          has_set_up: functions.contains(&SET_UP_FUNCTION),
+          name,
+          source: source.to_owned(),
+          tests,
+        });
+      }
+    }
+    Ok(contracts)
+  }
+
+  pub fn run(&self, contracts: &[TestContract], options: &TestOptions) -> Result<TestReport, TestError> {
+    let sender = *self.connector.accounts()?.first().ok_or(TestError::MissingAccount)?;
+    let registry = AbiRegistry::load(self.config).unwrap_or_default();
+    let mut report = TestReport::default();
+    // Test contracts are deployed for every test, so the chain is reverted
+    // afterwards when the node supports it.
+    let chain_control = ChainControl::new(self.connector);
+    let snapshot = chain_control.snapshot()
+      .map_err(|err| info!("Not reverting the chain after the tests: {}", err))
+      .ok();
+
+    let result = contracts.iter().try_for_each(|contract| {
+      for function in &contract.tests {
+        let name = format!("{}.{}", contract.name, function);
+        if options.filter.as_ref().is_some_and(|filter| !name.contains(filter.as_str())) {
+          continue;
+        }
+        info!("Running {}...", name);
+        report.results.push(self.run_test(contract, function, sender, &registry)?);
+      }
+      Ok(())
+    });
+
+    if let Some(snapshot) = snapshot {
+      if let Err(err) = chain_control.revert(snapshot) {
+        warn!("Couldn't revert the chain after the tests: {}", err);
+      }
+    }
+    result.map(|_| report)
+  }
+
+  fn run_test(&self, contract: &TestContract, function: &str, sender: Address, registry: &AbiRegistry) -> Result<TestResult, TestError> {
+    let mut result = TestResult {
+      contract: contract.name.to_owned(),
+      function: function.to_string(),
+      passed: false,
+      gas_used: None,
+      reason: None,
+    };
+
+    let deployment = self.execute(sender, None, contract.bytecode.clone(), registry)?;
+    let address = match deployment.contract_address {
+      Some(address) if deployment.succeeded => address,
+      _ => {
+        result.reason = Some(failure("Deploying the test contract", deployment.reason));
+        return Ok(result);
+      }
+    };
+
+    if contract.has_set_up {
+      let set_up = self.execute(sender, Some(address), function_selector(SET_UP_FUNCTION), registry)?;
+      if !set_up.succeeded {
+        result.reason = Some(failure("setUp()", set_up.reason));
+        return Ok(result);
+      }
+    }
+
+    let test = self.execute(sender, Some(address), function_selector(function), registry)?;
+    let expects_revert = function.starts_with(FAILING_TEST_FUNCTION_PREFIX);
+    result.passed = test.succeeded != expects_revert;
+    result.gas_used = test.gas_used;
+    if !result.passed {
+      result.reason = Some(if expects_revert {
+        "Expected a revert".to_string()
+      } else {
+        failure(&format!("{}()", function), test.reason)
+      });
+    }
+    Ok(result)
+  }
+
+  // Some development nodes reject transactions that revert instead of mining
+  // them, with the revert data attached to the error.
+  fn execute(&self, from: Address, to: Option<Address>, data: Vec<u8>, registry: &AbiRegistry) -> Result<Execution, TestError> {
+    let tx = TransactionRequest {
+      from,
+      to,
+      gas: Some(self.gas_limit),
+      gas_price: None,
+      value: None,
+      nonce: None,
+      data: Some(Bytes(data.clone())),
+      condition: None,
+    };
+    let hash = match self.connector.send_transaction(tx) {
+      Ok(hash) => hash,
+      Err(err) if revert_data(&err).is_some() || err.to_string().contains("revert") => {
+        return Ok(Execution {
+          succeeded: false,
+          gas_used: None,
+          contract_address: None,
+          reason: revert_data(&err).and_then(|data| registry.decode_error(&data)).map(|error| error.to_string()),
+        });
+      },
+      Err(err) => return Err(err.into()),
+    };
+
+    let receipt = self.connector.wait_for_transaction_receipt(hash, 0)?;
+    let succeeded = !receipt.status.is_some_and(|status| status.is_zero());
+    Ok(Execution {
+      succeeded,
+      gas_used: receipt.gas_used,
+      contract_address: receipt.contract_address,
+      reason: match to {
+        Some(to) if !succeeded => self.revert_reason(from, to, data, registry),
+        _ => None,
+      },
+    })
+  }
+
+  // Reverted transactions don't change any state, so replaying them as a
+  // call on top of the latest block reverts the same way.
+  fn revert_reason(&self, from: Address, to: Address, data: Vec<u8>, registry: &AbiRegistry) -> Option<String> {
+    let request = CallRequest {
+      from: Some(from),
+      to,
+      gas: Some(self.gas_limit),
+      gas_price: None,
+      value: None,
+      data: Some(Bytes(data)),
+    };
+    let error = match self.connector.call(request, None) {
+      Ok(data) => registry.decode_error(&data.0),
+      Err(err) => revert_data(&err).and_then(|data| registry.decode_error(&data)),
+    };
+    error.map(|error| error.to_string())
+  }
+}
+
+fn function_selector(name: &str) -> Vec<u8> {
+  Keccak256::digest(format!("{}()", name).as_bytes())[..4].to_vec()
+}
+
+fn failure(step: &str, reason: Option<String>) -> String {
+  match reason {
+    Some(reason) => format!("{} reverted with {}", step, reason),
+    None => format!("{} reverted", step),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{TestOptions, TestRunner};
+  use crate::config::Config;
+  use crate::testing::MockTransport;
+  use std::fs;
+
+  const ABI: &str = r#"[
+    {"inputs":[],"name":"setUp","outputs":[],"stateMutability":"nonpayable","type":"function"},
+    {"inputs":[],"name":"testIncrement","outputs":[],"stateMutability":"nonpayable","type":"function"},
+    {"inputs":[],"name":"testFailUnderflow","outputs":[],"stateMutability":"nonpayable","type":"function"},
+    {"inputs":[{"name":"amount","type":"uint256"}],"name":"testWith","outputs":[],"stateMutability":"nonpayable","type":"function"}
+  ]"#;
+  const SOURCE: &str = "
+    import \"../contracts/Counter.sol\";
+
+    interface ICounterTest { function testIncrement() external; }
+
+    contract CounterTest {
+      function setUp() public {}
+      function testIncrement() public {}
+      function testFailUnderflow() public {}
+      function testWith(uint256 amount) public {}
+    }
+  ";
+  // keccak256("testIncrement()")[..4]
+  const TEST_INCREMENT_SELECTOR: &str = "0xb913a5ca";
+
+  fn setup_project() -> (tempfile::TempDir, Config) {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let artifacts_dir = tmp_dir.path().join(".vibranium").join("test-artifacts");
+    fs::create_dir_all(&artifacts_dir).unwrap();
+    fs::create_dir_all(tmp_dir.path().join("test")).unwrap();
+    fs::write(tmp_dir.path().join("vibranium.toml"), "
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+    ").unwrap();
+    fs::write(tmp_dir.path().join("test").join("CounterTest.sol"), SOURCE).unwrap();
+    fs::write(artifacts_dir.join("CounterTest.abi"), ABI).unwrap();
+    fs::write(artifacts_dir.join("CounterTest.bin"), "6080\n").unwrap();
+    fs::write(artifacts_dir.join("ICounterTest.abi"), ABI).unwrap();
+    fs::write(artifacts_dir.join("ICounterTest.bin"), "").unwrap();
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    (tmp_dir, config)
+  }
+
+  fn receipt(status: &str, contract_address: Option<&str>, gas_used: u64) -> serde_json::Value {
+    serde_json::json!({
+      "transactionHash": format!("0x{:064x}", 0xaa),
+      "transactionIndex": "0x0",
+      "blockHash": format!("0x{:064x}", 2),
+      "blockNumber": "0x5",
+      "cumulativeGasUsed": format!("0x{:x}", gas_used),
+      "gasUsed": format!("0x{:x}", gas_used),
+      "contractAddress": contract_address,
+      "logs": [],
+      "status": status,
+      "logsBloom": format!("0x{}", "0".repeat(512)),
+    })
+  }
+
+  #[test]
+  fn it_should_discover_test_functions() {
+    let (tmp_dir, config) = setup_project();
+    let connector = MockTransport::new().connector();
+    let runner = TestRunner::new(&config, &connector);
+
+    let contracts = runner.discover(&tmp_dir.path().join(".vibranium").join("test-artifacts"), &[tmp_dir.path().join("test").join("CounterTest.sol")]).unwrap();
+
+    assert_eq!(contracts.len(), 1);
+    assert_eq!(contracts[0].name, "CounterTest");
+    assert_eq!(contracts[0].tests, vec!["testIncrement", "testFailUnderflow"]);
+    assert_eq!(contracts[0].bytecode, vec![0x60, 0x80]);
+    assert!(contracts[0].has_set_up);
+  }
+
+  #[test]
+  fn it_should_run_tests_against_fresh_test_contracts() {
+    let (tmp_dir, config) = setup_project();
+    let transport = MockTransport::new();
+    transport.add_response("eth_accounts", serde_json::json!(["0x0000000000000000000000000000000000000001"]));
+    transport.set_default_response("eth_sendTransaction", serde_json::json!(format!("0x{:064x}", 0xaa)));
+    transport.add_response("evm_snapshot", serde_json::json!("0x1"));
+    transport.add_response("evm_revert", serde_json::json!(true));
+    let deployed = Some("0x0000000000000000000000000000000000000009");
+    // testIncrement passes, testFailUnderflow doesn't revert and fails.
+    for gas_used in &[43_210, 21_000] {
+      transport.add_response("eth_getTransactionReceipt", receipt("0x1", deployed, 100_000));
+      transport.add_response("eth_getTransactionReceipt", receipt("0x1", None, 0));
+      transport.add_response("eth_getTransactionReceipt", receipt("0x1", None, *gas_used));
+    }
+
+    let connector = transport.connector();
+    let runner = TestRunner::new(&config, &connector);
+    let contracts = runner.discover(&tmp_dir.path().join(".vibranium").join("test-artifacts"), &[tmp_dir.path().join("test").join("CounterTest.sol")]).unwrap();
+    let report = runner.run(&contracts, &TestOptions::default()).unwrap();
+
+    assert_eq!(report.passed(), 1);
+    assert_eq!(report.failed(), 1);
+    assert_eq!(report.results[0].to_string(), "CounterTest.testIncrement");
+    assert_eq!(report.results[0].gas_used, Some(43_210.into()));
+    assert_eq!(report.results[1].reason, Some("Expected a revert".to_string()));
+
+    let transactions = transport.requests_for("eth_sendTransaction");
+    assert_eq!(transactions.len(), 6);
+    assert_eq!(transactions[0][0]["data"], serde_json::json!("0x6080"));
+    assert_eq!(transactions[2][0]["to"], serde_json::json!("0x0000000000000000000000000000000000000009"));
+    assert_eq!(transactions[2][0]["data"], serde_json::json!(TEST_INCREMENT_SELECTOR));
+    assert_eq!(transport.requests_for("evm_revert"), vec![vec![serde_json::json!("0x1")]]);
+  }
+
+  #[test]
+  fn it_should_report_revert_reasons_of_failing_tests() {
+    let (tmp_dir, config) = setup_project();
+    let transport = MockTransport::new();
+    transport.add_response("eth_accounts", serde_json::json!(["0x0000000000000000000000000000000000000001"]));
+    transport.set_default_response("eth_sendTransaction", serde_json::json!(format!("0x{:064x}", 0xaa)));
+    transport.add_response("eth_getTransactionReceipt", receipt("0x1", Some("0x0000000000000000000000000000000000000009"), 100_000));
+    transport.add_response("eth_getTransactionReceipt", receipt("0x1", None, 0));
+    transport.add_response("eth_getTransactionReceipt", receipt("0x0", None, 30_000));
+    let mut error = jsonrpc_core::Error::new(jsonrpc_core::ErrorCode::ServerError(3));
+    // Panic(0x11), an arithmetic overflow
+    error.data = Some(serde_json::json!(format!("0x4e487b71{:064x}", 0x11)));
+    transport.add_error("eth_call", error);
+
+    let connector = transport.connector();
+    let runner = TestRunner::new(&config, &connector);
+    let contracts = runner.discover(&tmp_dir.path().join(".vibranium").join("test-artifacts"), &[tmp_dir.path().join("test").join("CounterTest.sol")]).unwrap();
+    let report = runner.run(&contracts, &TestOptions { filter: Some("testIncrement".to_string()) }).unwrap();
+
+    assert_eq!(report.results.len(), 1);
+    assert!(!report.is_success());
+    assert_eq!(report.results[0].reason, Some("testIncrement() reverted with Panic(17)".to_string()));
+  }
+}
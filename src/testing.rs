@@ -0,0 +1,180 @@
+//! Test doubles for code that embeds the vibranium library, e.g. an in-memory
+//! JSON-RPC transport with canned responses per method that can be turned
+//! into a `BlockchainConnector`.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use jsonrpc_core as rpc;
+use web3::futures::{self, Future};
+
+use crate::blockchain::connector::BlockchainConnector;
+use crate::blockchain::connector::web3_adapter::{Transports, Web3Adapter};
+
+type Response = Result<rpc::Value, rpc::Error>;
+
+#[derive(Debug, Default)]
+struct MockState {
+  queued: HashMap<String, VecDeque<Response>>,
+  defaults: HashMap<String, Response>,
+  requests: Vec<(String, Vec<rpc::Value>)>,
+  last_id: usize,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct MockTransport {
+  state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransport {
+  pub fn new() -> MockTransport {
+    MockTransport::default()
+  }
+
+  // Queued responses are handed out once, in the order they've been added.
+  // Once drained, the default response for the method (if any) is used.
+  pub fn add_response(&self, method: &str, value: rpc::Value) {
+    self.queue(method, Ok(value));
+  }
+
+  pub fn add_error(&self, method: &str, error: rpc::Error) {
+    self.queue(method, Err(error));
+  }
+
+  pub fn set_default_response(&self, method: &str, value: rpc::Value) {
+    self.state.lock().unwrap().defaults.insert(method.to_string(), Ok(value));
+  }
+
+  pub fn requests(&self) -> Vec<(String, Vec<rpc::Value>)> {
+    self.state.lock().unwrap().requests.clone()
+  }
+
+  pub fn requests_for(&self, method: &str) -> Vec<Vec<rpc::Value>> {
+    self.requests()
+      .into_iter()
+      .filter(|(m, _params)| m == method)
+      .map(|(_m, params)| params)
+      .collect()
+  }
+
+  pub fn pending_responses(&self) -> Vec<String> {
+    self.state.lock().unwrap().queued
+      .iter()
+      .filter(|(_method, responses)| !responses.is_empty())
+      .map(|(method, _responses)| method.to_owned())
+      .collect()
+  }
+
+  pub fn connector(&self) -> BlockchainConnector {
+    BlockchainConnector::new(Web3Adapter::with_transport(Transports::Mock(self.clone())))
+  }
+
+  fn queue(&self, method: &str, response: Response) {
+    self.state.lock().unwrap().queued
+      .entry(method.to_string())
+      .or_default()
+      .push_back(response);
+  }
+
+  fn respond(&self, method: &str) -> Option<Response> {
+    let mut state = self.state.lock().unwrap();
+    let queued = state.queued.get_mut(method).and_then(VecDeque::pop_front);
+    queued.or_else(|| state.defaults.get(method).cloned())
+  }
+}
+
+impl web3::Transport for MockTransport {
+  type Out = Box<dyn Future<Item = rpc::Value, Error = web3::Error>>;
+
+  fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (web3::RequestId, rpc::Call) {
+    let mut state = self.state.lock().unwrap();
+    state.last_id += 1;
+    (state.last_id, web3::helpers::build_request(state.last_id, method, params))
+  }
+
+  // Requests are recorded when they're sent, so that requests prepared by
+  // wrapping transports are recorded as well.
+  fn send(&self, _id: web3::RequestId, request: rpc::Call) -> Self::Out {
+    let (method, params) = match request {
+      rpc::Call::MethodCall(call) => (call.method, call.params),
+      rpc::Call::Notification(notification) => (notification.method, notification.params),
+      rpc::Call::Invalid { .. } => (String::new(), rpc::Params::None),
+    };
+
+    let params = match params {
+      rpc::Params::Array(values) => values,
+      rpc::Params::Map(map) => vec![rpc::Value::Object(map)],
+      rpc::Params::None => vec![],
+    };
+    self.state.lock().unwrap().requests.push((method.clone(), params));
+
+    match self.respond(&method) {
+      Some(Ok(value)) => Box::new(futures::finished(value)),
+      Some(Err(error)) => Box::new(futures::failed(web3::Error::Rpc(error))),
+      None => {
+        warn!("No mock response registered for JSON-RPC method {}", &method);
+        Box::new(futures::failed(web3::Error::Unreachable))
+      }
+    }
+  }
+}
+
+// Temporary project directory with an initialized `.vibranium` directory,
+// removed again once the returned handle is dropped.
+#[cfg(test)]
+pub(crate) fn project_dir() -> tempfile::TempDir {
+  let tmp_dir = tempfile::tempdir().unwrap();
+  std::fs::create_dir(tmp_dir.path().join(".vibranium")).unwrap();
+  tmp_dir
+}
+
+// Like `project_dir()`, with the given vibranium.toml.
+#[cfg(test)]
+pub(crate) fn project_with_config(config: &str) -> tempfile::TempDir {
+  let tmp_dir = project_dir();
+  std::fs::write(tmp_dir.path().join("vibranium.toml"), config).unwrap();
+  tmp_dir
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::MockTransport;
+  use jsonrpc_core as rpc;
+  use web3::types::U256;
+
+  #[test]
+  fn it_should_serve_queued_responses_in_order() {
+    let transport = MockTransport::new();
+    transport.add_response("eth_gasPrice", rpc::Value::String("0x1".to_string()));
+    transport.add_response("eth_gasPrice", rpc::Value::String("0x2".to_string()));
+
+    let connector = transport.connector();
+    assert_eq!(connector.gas_price().unwrap(), U256::from(1));
+    assert_eq!(connector.gas_price().unwrap(), U256::from(2));
+    assert!(connector.gas_price().is_err());
+    assert!(transport.pending_responses().is_empty());
+  }
+
+  #[test]
+  fn it_should_fall_back_to_default_responses() {
+    let transport = MockTransport::new();
+    transport.set_default_response("eth_gasPrice", rpc::Value::String("0x5".to_string()));
+    transport.add_response("eth_gasPrice", rpc::Value::String("0x1".to_string()));
+
+    let connector = transport.connector();
+    assert_eq!(connector.gas_price().unwrap(), U256::from(1));
+    assert_eq!(connector.gas_price().unwrap(), U256::from(5));
+    assert_eq!(connector.gas_price().unwrap(), U256::from(5));
+  }
+
+  #[test]
+  fn it_should_record_requests() {
+    let transport = MockTransport::new();
+    transport.add_error("eth_accounts", rpc::Error::internal_error());
+
+    let connector = transport.connector();
+    assert!(connector.accounts().is_err());
+    assert_eq!(transport.requests_for("eth_accounts"), vec![Vec::<rpc::Value>::new()]);
+    assert_eq!(transport.requests().len(), 1);
+  }
+}
@@ -1,6 +1,68 @@
+use crate::process::ProcessCommand;
+
+use rustc_hex::FromHex;
+use std::path::Path;
+
 const LOCALHOST_ADDRESS: &str = "127.0.0.1";
 const LOCALHOST_ALIAS: &str = "localhost";
 
+// Decodes bytecode as found in artifacts. Placeholders of libraries that
+// haven't been linked yet are only ever pushed, so replacing them with zeros
+// keeps instructions and offsets intact.
+pub fn decode_bytecode(bytecode: &str) -> Vec<u8> {
+  let mut hex: String = bytecode.trim().replace("\"", "").trim_start_matches("0x")
+    .chars()
+    .map(|c| if c.is_ascii_hexdigit() { c } else { '0' })
+    .collect();
+  if hex.len() % 2 == 1 {
+    hex.pop();
+  }
+  hex.from_hex().unwrap_or_default()
+}
+
+// Removes the metadata solc appends to bytecode, a CBOR encoded map followed
+// by its length.
+pub fn strip_metadata(code: &mut Vec<u8>) {
+  if code.len() > 2 {
+    let metadata_length = ((code[code.len() - 2] as usize) << 8) + code[code.len() - 1] as usize;
+    if metadata_length + 2 < code.len() {
+      let metadata_start = code.len() - 2 - metadata_length;
+      if (0xa1..=0xa5).contains(&code[metadata_start]) {
+        code.truncate(metadata_start);
+      }
+    }
+  }
+}
+
+// The solc version recorded in the metadata solc appends to bytecode, stored
+// as the three bytes of the `solc` key (since solc 0.5.9).
+pub fn solc_version(code: &[u8]) -> Option<String> {
+  if code.len() < 2 {
+    return None;
+  }
+  let metadata_length = ((code[code.len() - 2] as usize) << 8) + code[code.len() - 1] as usize;
+  let metadata = code.get(code.len().checked_sub(2 + metadata_length)?..code.len() - 2)?;
+  let key = [0x64, b's', b'o', b'l', b'c', 0x43];
+  metadata.windows(key.len() + 3)
+    .find(|window| window.starts_with(&key))
+    .map(|window| format!("{}.{}.{}", window[6], window[7], window[8]))
+}
+
+// The commit checked out in the project, if it's a git repository.
+pub fn git_commit(project_path: &Path, log_dir: &Path) -> Option<String> {
+  let output = ProcessCommand::new("git")
+    .arg("rev-parse")
+    .arg("--short")
+    .arg("HEAD")
+    .current_dir(project_path)
+    .log_to(log_dir)
+    .output()
+    .ok()
+    .filter(|output| output.status.success())?;
+  let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+  if commit.is_empty() { None } else { Some(commit) }
+}
+
 pub fn merge_cli_options(a: Vec<String>, b: Vec<String>) -> Vec<String> {
 
   let mut merged = vec![];
@@ -42,6 +104,87 @@ pub fn normalize_localhost(host: String) -> String {
   }
 }
 
+pub fn is_localhost(host: &str) -> bool {
+  host == LOCALHOST_ADDRESS || host == LOCALHOST_ALIAS
+}
+
+// Formats days since the unix epoch as YYYY-MM-DD.
+pub fn format_day(day: u64) -> String {
+  // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+  let z = day as i64 + 719_468;
+  let era = z.div_euclid(146_097);
+  let day_of_era = z.rem_euclid(146_097);
+  let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let shifted_month = (5 * day_of_year + 2) / 153;
+  let day_of_month = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+  let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+  let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+  format!("{:04}-{:02}-{:02}", year, month, day_of_month)
+}
+
+// Formats seconds since the unix epoch as YYYY-MM-DD HH:MM:SS UTC.
+pub fn format_timestamp(timestamp: u64) -> String {
+  let seconds = timestamp % 86_400;
+  format!("{} {:02}:{:02}:{:02} UTC", format_day(timestamp / 86_400), seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
+
+// Parses ISO 8601 dates (YYYY-MM-DD) and date times (YYYY-MM-DDTHH:MM[:SS]
+// followed by `Z` or an offset like `+02:00`, UTC if omitted) as seconds
+// since the unix epoch.
+pub fn parse_timestamp(timestamp: &str) -> Option<u64> {
+  let (date, time) = match timestamp.find(['T', ' ']) {
+    Some(index) => (&timestamp[..index], Some(&timestamp[index + 1..])),
+    None => (timestamp, None),
+  };
+
+  let date: Vec<i64> = date.split('-').map(|part| part.parse().ok()).collect::<Option<Vec<i64>>>()?;
+  let (year, month, day) = match date.as_slice() {
+    [year, month, day] if (1..=12).contains(month) && (1..=31).contains(day) => (*year, *month, *day),
+    _ => return None,
+  };
+
+  let (seconds, offset) = match time {
+    Some(time) => {
+      let (time, offset) = match time.find(['Z', '+', '-']) {
+        Some(index) => (&time[..index], &time[index..]),
+        None => (time, ""),
+      };
+      let parts: Vec<i64> = time.split(':').map(|part| part.parse().ok()).collect::<Option<Vec<i64>>>()?;
+      let seconds = match parts.as_slice() {
+        [hours, minutes] if *hours < 24 && *minutes < 60 => hours * 3600 + minutes * 60,
+        [hours, minutes, seconds] if *hours < 24 && *minutes < 60 && *seconds < 61 => hours * 3600 + minutes * 60 + seconds,
+        _ => return None,
+      };
+      let offset = match offset {
+        "" | "Z" => 0,
+        _ => {
+          let sign = if offset.starts_with('-') { -1 } else { 1 };
+          let parts: Vec<i64> = offset[1..].split(':').map(|part| part.parse().ok()).collect::<Option<Vec<i64>>>()?;
+          match parts.as_slice() {
+            [hours, minutes] => sign * (hours * 3600 + minutes * 60),
+            _ => return None,
+          }
+        },
+      };
+      (seconds, offset)
+    },
+    None => (0, 0),
+  };
+
+  // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = year.div_euclid(400);
+  let year_of_era = year - era * 400;
+  let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+  let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+  let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+  let days = era * 146_097 + day_of_era - 719_468;
+
+  let timestamp = days * 86_400 + seconds - offset;
+  if timestamp < 0 { None } else { Some(timestamp as u64) }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -94,4 +237,16 @@ mod tests {
       assert_eq!(merged, vec!["--one".to_string(), "value1".to_string(), "--two".to_string(), "value2".to_string()]);
     }
   }
+
+  mod format_day {
+
+    use super::super::format_day;
+
+    #[test]
+    fn it_should_format_days_since_epoch() {
+      assert_eq!(format_day(0), "1970-01-01");
+      assert_eq!(format_day(19_000), "2022-01-08");
+      assert_eq!(format_day(11_016), "2000-02-29");
+    }
+  }
 }
@@ -0,0 +1,102 @@
+use std::error::Error;
+use std::convert::From;
+use std::fmt;
+use std::io;
+
+use crate::blockchain;
+use crate::compiler;
+use crate::config;
+use crate::deployment;
+
+#[derive(Debug)]
+pub enum VerificationError {
+  InvalidConfig(config::error::ConfigError),
+  Connection(blockchain::error::ConnectionError),
+  Tracking(deployment::error::DeploymentTrackingError),
+  Compilation(compiler::error::CompilerError),
+  UnsupportedService(String),
+  MissingApiKey(String),
+  MissingEnvVariable(String),
+  NotDeployed(String),
+  MissingSource(String),
+  UnknownCompilerVersion(String),
+  Request(String),
+  InvalidResponse(String, String),
+  Rejected(String, String, String),
+  Timeout(String, String),
+  Io(io::Error),
+}
+
+impl Error for VerificationError {
+  fn cause(&self) -> Option<&dyn Error> {
+    match self {
+      VerificationError::InvalidConfig(error) => Some(error),
+      VerificationError::Connection(error) => Some(error),
+      VerificationError::Tracking(error) => Some(error),
+      VerificationError::Compilation(error) => Some(error),
+      VerificationError::UnsupportedService(_service) => None,
+      VerificationError::MissingApiKey(_service) => None,
+      VerificationError::MissingEnvVariable(_name) => None,
+      VerificationError::NotDeployed(_name) => None,
+      VerificationError::MissingSource(_path) => None,
+      VerificationError::UnknownCompilerVersion(_name) => None,
+      VerificationError::Request(_message) => None,
+      VerificationError::InvalidResponse(_service, _response) => None,
+      VerificationError::Rejected(_name, _service, _reason) => None,
+      VerificationError::Timeout(_name, _service) => None,
+      VerificationError::Io(error) => Some(error),
+    }
+  }
+}
+
+impl fmt::Display for VerificationError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      VerificationError::InvalidConfig(error) => write!(f, "{}", error),
+      VerificationError::Connection(error) => write!(f, "{}", error),
+      VerificationError::Tracking(error) => write!(f, "Couldn't read tracking data: {}", error),
+      VerificationError::Compilation(error) => write!(f, "Couldn't determine compiler settings: {}", error),
+      VerificationError::UnsupportedService(service) => write!(f, "Unsupported verification service '{}'. Use one of: etherscan, sourcify", service),
+      VerificationError::MissingApiKey(service) => write!(f, "Couldn't find an API key for {}. Please set `verification.etherscan_api_key` in vibranium.toml, e.g. to \"${{env.ETHERSCAN_API_KEY}}\".", service),
+      VerificationError::MissingEnvVariable(name) => write!(f, "Couldn't resolve verification config, environment variable {} isn't set", name),
+      VerificationError::NotDeployed(name) => write!(f, "Couldn't find a tracked deployment of Smart Contract {} on the connected chain.", name),
+      VerificationError::MissingSource(path) => write!(f, "Couldn't find source {}", path),
+      VerificationError::UnknownCompilerVersion(name) => write!(f, "Couldn't determine the compiler version Smart Contract {} has been compiled with. Please set `verification.compiler_version` in vibranium.toml.", name),
+      VerificationError::Request(message) => write!(f, "Couldn't reach verification service: {}", message),
+      VerificationError::InvalidResponse(service, response) => write!(f, "Unexpected response from {}: {}", service, response),
+      VerificationError::Rejected(name, service, reason) => write!(f, "{} couldn't verify Smart Contract {}: {}", service, name, reason),
+      VerificationError::Timeout(name, service) => write!(f, "Gave up waiting for {} to verify Smart Contract {}", service, name),
+      VerificationError::Io(error) => write!(f, "Couldn't verify Smart Contract: {}", error),
+    }
+  }
+}
+
+impl From<config::error::ConfigError> for VerificationError {
+  fn from(error: config::error::ConfigError) -> Self {
+    VerificationError::InvalidConfig(error)
+  }
+}
+
+impl From<blockchain::error::ConnectionError> for VerificationError {
+  fn from(error: blockchain::error::ConnectionError) -> Self {
+    VerificationError::Connection(error)
+  }
+}
+
+impl From<deployment::error::DeploymentTrackingError> for VerificationError {
+  fn from(error: deployment::error::DeploymentTrackingError) -> Self {
+    VerificationError::Tracking(error)
+  }
+}
+
+impl From<compiler::error::CompilerError> for VerificationError {
+  fn from(error: compiler::error::CompilerError) -> Self {
+    VerificationError::Compilation(error)
+  }
+}
+
+impl From<io::Error> for VerificationError {
+  fn from(error: io::Error) -> Self {
+    VerificationError::Io(error)
+  }
+}
@@ -0,0 +1,77 @@
+// Etherscan's multichain API. Sources are submitted as standard JSON input,
+// which returns a GUID to poll the verification status with.
+
+use super::error::VerificationError;
+use super::http::{self, HttpClient};
+use super::{Progress, Submission, VerificationService};
+
+pub const DEFAULT_URL: &str = "https://api.etherscan.io/v2/api";
+const STATUS_OK: &str = "1";
+const CODE_FORMAT: &str = "solidity-standard-json-input";
+
+pub fn submit(client: &dyn HttpClient, url: &str, api_key: &str, submission: &Submission) -> Result<Progress, VerificationError> {
+  let chain_id = submission.chain_id.to_string();
+  let address = format!("{:?}", submission.address);
+  let source_code = submission.input.to_string();
+  let contract_name = submission.contract_identifier();
+  let compiler_version = format!("v{}", submission.compiler_version);
+
+  let body = http::form(&[
+    ("apikey", api_key),
+    ("chainid", &chain_id),
+    ("module", "contract"),
+    ("action", "verifysourcecode"),
+    ("contractaddress", &address),
+    ("sourceCode", &source_code),
+    ("codeformat", CODE_FORMAT),
+    ("contractname", &contract_name),
+    ("compilerversion", &compiler_version),
+    // Misspelled by Etherscan.
+    ("constructorArguements", &submission.constructor_args),
+  ]);
+  let (status, result) = parse(&client.post(&format!("{}?chainid={}", url, chain_id), http::CONTENT_TYPE_FORM, &body)?)?;
+
+  if status == STATUS_OK {
+    Ok(Progress::Pending(result))
+  } else if is_already_verified(&result) {
+    Ok(Progress::AlreadyVerified)
+  } else {
+    Err(VerificationError::Rejected(submission.name.to_owned(), VerificationService::Etherscan.to_string(), result))
+  }
+}
+
+pub fn check(client: &dyn HttpClient, url: &str, api_key: &str, submission: &Submission, guid: &str) -> Result<Progress, VerificationError> {
+  let chain_id = submission.chain_id.to_string();
+  let body = http::form(&[
+    ("apikey", api_key),
+    ("chainid", &chain_id),
+    ("module", "contract"),
+    ("action", "checkverifystatus"),
+    ("guid", guid),
+  ]);
+  let (status, result) = parse(&client.post(&format!("{}?chainid={}", url, chain_id), http::CONTENT_TYPE_FORM, &body)?)?;
+
+  if is_already_verified(&result) {
+    Ok(Progress::AlreadyVerified)
+  } else if status == STATUS_OK {
+    Ok(Progress::Verified)
+  } else if result.to_lowercase().starts_with("pending") {
+    Ok(Progress::Pending(guid.to_owned()))
+  } else {
+    Err(VerificationError::Rejected(submission.name.to_owned(), VerificationService::Etherscan.to_string(), result))
+  }
+}
+
+// Responses come as `{ "status": "1", "message": "OK", "result": "..." }`.
+fn parse(response: &str) -> Result<(String, String), VerificationError> {
+  let invalid = || VerificationError::InvalidResponse(VerificationService::Etherscan.to_string(), response.trim().to_string());
+  let response: serde_json::Value = serde_json::from_str(response).map_err(|_err| invalid())?;
+  match (response["status"].as_str(), response["result"].as_str()) {
+    (Some(status), Some(result)) => Ok((status.to_string(), result.to_string())),
+    _ => Err(invalid()),
+  }
+}
+
+fn is_already_verified(result: &str) -> bool {
+  result.to_lowercase().contains("already verified")
+}
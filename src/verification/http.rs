@@ -0,0 +1,84 @@
+// Requests to verification services are sent with curl. Request bodies are
+// passed in a file rather than as arguments, so that API keys don't end up
+// in the process log.
+
+use super::error::VerificationError;
+use crate::process::ProcessCommand;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CONTENT_TYPE_FORM: &str = "application/x-www-form-urlencoded";
+pub const CONTENT_TYPE_JSON: &str = "application/json";
+const REQUEST_BODY_FILE: &str = "verification-request";
+
+pub trait HttpClient {
+  fn get(&self, url: &str) -> Result<String, VerificationError>;
+  fn post(&self, url: &str, content_type: &str, body: &str) -> Result<String, VerificationError>;
+}
+
+pub struct CurlClient {
+  vibranium_dir_path: PathBuf,
+}
+
+impl CurlClient {
+  pub fn new(vibranium_dir_path: &Path) -> CurlClient {
+    CurlClient {
+      vibranium_dir_path: vibranium_dir_path.to_path_buf(),
+    }
+  }
+
+  // Responses with error status codes are returned as well, as services
+  // explain in their body why a request has been rejected.
+  fn curl(&self, args: Vec<String>) -> Result<String, VerificationError> {
+    let output = ProcessCommand::new("curl")
+      .args(vec!["-sS".to_string()])
+      .args(args)
+      .log_to(&self.vibranium_dir_path)
+      .output()
+      .map_err(|err| VerificationError::Request(format!("Couldn't run curl: {}", err)))?;
+    if !output.status.success() {
+      return Err(VerificationError::Request(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+  }
+}
+
+impl HttpClient for CurlClient {
+  fn get(&self, url: &str) -> Result<String, VerificationError> {
+    self.curl(vec![url.to_string()])
+  }
+
+  fn post(&self, url: &str, content_type: &str, body: &str) -> Result<String, VerificationError> {
+    let body_dir = if self.vibranium_dir_path.exists() { self.vibranium_dir_path.clone() } else { std::env::temp_dir() };
+    let body_path = body_dir.join(format!("{}-{}", REQUEST_BODY_FILE, std::process::id()));
+    fs::write(&body_path, body)?;
+
+    let response = self.curl(vec![
+      "-X".to_string(),
+      "POST".to_string(),
+      "-H".to_string(),
+      format!("Content-Type: {}", content_type),
+      "--data-binary".to_string(),
+      format!("@{}", body_path.to_string_lossy()),
+      url.to_string(),
+    ]);
+    let _ = fs::remove_file(&body_path);
+    response
+  }
+}
+
+// Encodes fields as `application/x-www-form-urlencoded`.
+pub fn form(fields: &[(&str, &str)]) -> String {
+  fields.iter()
+    .map(|(name, value)| format!("{}={}", percent_encode(name), percent_encode(value)))
+    .collect::<Vec<String>>()
+    .join("&")
+}
+
+fn percent_encode(value: &str) -> String {
+  value.bytes().map(|byte| match byte {
+    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+    _ => format!("%{:02X}", byte),
+  }).collect()
+}
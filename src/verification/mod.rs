@@ -0,0 +1,524 @@
+//! Source verification on block explorers. Sources of a tracked Smart
+//! Contract are submitted to Etherscan or Sourcify as standard JSON input,
+//! along with the compiler version and settings it has been compiled with and
+//! the constructor arguments it has been deployed with, and the service is
+//! polled until it verified them.
+//!
+//! Services are configured in the `[verification]` section of vibranium.toml.
+
+pub mod error;
+pub mod http;
+mod etherscan;
+mod sourcify;
+
+use crate::artifacts::{self, CompilerSettings};
+use crate::blockchain::connector::BlockchainConnector;
+use crate::compiler::{self, CompilerConfig};
+use crate::compiler::versions::{SolcInstaller, Version};
+use crate::config::{Config, ProjectConfig, ProjectVerificationConfig};
+use crate::deployment::tracker::{DeploymentTracker, SmartContractTrackingDataEntry};
+use crate::deployment::upgrades;
+use crate::secrets;
+
+use error::VerificationError;
+use http::HttpClient;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+use web3::types::{Address, H256, U256};
+
+const SERVICE_ETHERSCAN: &str = "etherscan";
+const SERVICE_SOURCIFY: &str = "sourcify";
+const SOLIDITY_SOURCE_EXTENSION: &str = "sol";
+const NODE_MODULES_DIRECTORY: &str = "node_modules";
+const COMMIT_PREFIX: &str = "+commit.";
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 5;
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VerificationService {
+  Etherscan,
+  Sourcify,
+}
+
+impl FromStr for VerificationService {
+  type Err = VerificationError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      SERVICE_ETHERSCAN => Ok(VerificationService::Etherscan),
+      SERVICE_SOURCIFY => Ok(VerificationService::Sourcify),
+      _ => Err(VerificationError::UnsupportedService(s.to_string())),
+    }
+  }
+}
+
+impl fmt::Display for VerificationService {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      VerificationService::Etherscan => write!(f, "Etherscan"),
+      VerificationService::Sourcify => write!(f, "Sourcify"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SourceVerificationStatus {
+  Verified,
+  AlreadyVerified,
+}
+
+#[derive(Debug)]
+pub struct SourceVerification {
+  pub name: String,
+  pub address: Address,
+  pub service: VerificationService,
+  pub status: SourceVerificationStatus,
+}
+
+impl fmt::Display for SourceVerification {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.status {
+      SourceVerificationStatus::Verified => write!(f, "{} at {:?}: verified on {}", self.name, self.address, self.service),
+      SourceVerificationStatus::AlreadyVerified => write!(f, "{} at {:?}: already verified on {}", self.name, self.address, self.service),
+    }
+  }
+}
+
+// What's submitted to a verification service.
+#[derive(Debug)]
+pub struct Submission {
+  pub name: String,
+  pub address: Address,
+  pub chain_id: U256,
+  pub source_name: String,
+  pub contract_name: String,
+  pub compiler_version: String,
+  pub input: serde_json::Value,
+  pub constructor_args: String,
+  pub tx_hash: Option<H256>,
+}
+
+impl Submission {
+  // As in `contracts/Token.sol:Token`.
+  pub fn contract_identifier(&self) -> String {
+    format!("{}:{}", self.source_name, self.contract_name)
+  }
+}
+
+// Submissions are pending as long as the service hasn't decided on them,
+// identified by what the service returned when submitting them.
+#[derive(Debug, PartialEq)]
+pub enum Progress {
+  Pending(String),
+  Verified,
+  AlreadyVerified,
+}
+
+pub struct SourceVerifier<'a> {
+  config: &'a Config,
+  connector: &'a BlockchainConnector,
+  client: &'a dyn HttpClient,
+}
+
+impl<'a> SourceVerifier<'a> {
+  pub fn new(config: &'a Config, connector: &'a BlockchainConnector, client: &'a dyn HttpClient) -> SourceVerifier<'a> {
+    SourceVerifier {
+      config,
+      connector,
+      client,
+    }
+  }
+
+  // Verifies the latest tracked deployment of the Smart Contract.
+  pub fn verify(&self, name: &str, service: VerificationService) -> Result<SourceVerification, VerificationError> {
+    let project_config = self.config.read()?;
+    let verification_config = project_config.verification.clone().unwrap_or_default();
+    let submission = self.submission(&project_config, &verification_config, name)?;
+    let (poll_interval, timeout) = (
+      Duration::from_secs(verification_config.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL_SECS)),
+      Duration::from_secs(verification_config.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS)),
+    );
+
+    let submit = || -> Result<Progress, VerificationError> {
+      match service {
+        VerificationService::Etherscan => etherscan::submit(self.client, &etherscan_url(&verification_config)?, &etherscan_api_key(&verification_config)?, &submission),
+        VerificationService::Sourcify => sourcify::submit(self.client, &sourcify_url(&verification_config)?, &submission),
+      }
+    };
+    let check = |id: &str| -> Result<Progress, VerificationError> {
+      match service {
+        VerificationService::Etherscan => etherscan::check(self.client, &etherscan_url(&verification_config)?, &etherscan_api_key(&verification_config)?, &submission, id),
+        VerificationService::Sourcify => sourcify::check(self.client, &sourcify_url(&verification_config)?, &submission, id),
+      }
+    };
+
+    info!("Submitting {} to {}...", submission.contract_identifier(), service);
+    let deadline = Instant::now() + timeout;
+    let mut progress = submit()?;
+    while let Progress::Pending(id) = &progress {
+      if Instant::now() >= deadline {
+        return Err(VerificationError::Timeout(name.to_owned(), service.to_string()));
+      }
+      info!("Waiting for {} to verify {}...", service, name);
+      thread::sleep(poll_interval);
+      progress = check(id)?;
+    }
+
+    Ok(SourceVerification {
+      name: name.to_owned(),
+      address: submission.address,
+      service,
+      status: if progress == Progress::AlreadyVerified { SourceVerificationStatus::AlreadyVerified } else { SourceVerificationStatus::Verified },
+    })
+  }
+
+  fn submission(&self, project_config: &ProjectConfig, verification_config: &ProjectVerificationConfig, name: &str) -> Result<Submission, VerificationError> {
+    let block_hash = self.connector.get_first_block()?.and_then(|block| block.hash)
+      .ok_or_else(|| VerificationError::NotDeployed(name.to_owned()))?;
+    let entry = DeploymentTracker::new(self.config).get_latest_smart_contract_tracking_data(&block_hash, name)?
+      .ok_or_else(|| VerificationError::NotDeployed(name.to_owned()))?;
+
+    // Upgradeable Smart Contracts are tracked as their proxy, with their
+    // implementations under a suffixed name.
+    let configured_name = name.trim_end_matches(upgrades::IMPLEMENTATION_SUFFIX);
+    let is_proxy = entry.metadata.as_ref().is_some_and(|metadata| metadata.contains_key(upgrades::METADATA_PROXY_KIND));
+    let smart_contract_config = project_config.deployment.as_ref()
+      .and_then(|deployment_config| deployment_config.smart_contracts.iter().find(|config| config.name == configured_name));
+    let contract_name = match smart_contract_config {
+      Some(config) if is_proxy => config.proxy.as_ref().map(|proxy_config| proxy_config.contract.to_owned()),
+      Some(config) => config.instance_of.clone(),
+      None => None,
+    }.unwrap_or_else(|| configured_name.to_string());
+
+    let compiler = compiler::Compiler::new(self.config);
+    let (compiler_cmd, compiler_options) = compiler.resolve_command(&CompilerConfig {
+      compiler: None,
+      compiler_options: None,
+      replace_options: false,
+      force: false,
+      tags: None,
+    })?;
+
+    let source_files: Vec<String> = compiler.source_files(&project_config.sources.smart_contracts)
+      .into_iter()
+      .filter(|path| path.extension().is_some_and(|extension| extension == SOLIDITY_SOURCE_EXTENSION))
+      .filter_map(|path| path.strip_prefix(&self.config.project_path).ok().map(source_name))
+      .collect();
+    let sources = collect_sources(&self.config.project_path, &source_files)?;
+    let source_name = sources.iter()
+      .find(|(_source_name, content)| compiler::declared_smart_contracts(content).contains(&contract_name))
+      .map(|(source_name, _content)| source_name.to_owned())
+      .ok_or_else(|| VerificationError::MissingSource(format!("declaring Smart Contract {}", contract_name)))?;
+
+    Ok(Submission {
+      name: name.to_owned(),
+      address: entry.address,
+      chain_id: self.connector.chain_id()?,
+      source_name,
+      contract_name,
+      compiler_version: self.compiler_version(verification_config, &entry, &compiler_cmd)?,
+      input: standard_json_input(&sources, &compiler_options),
+      constructor_args: entry.encoded_args.as_deref().unwrap_or("").trim_start_matches("0x").to_string(),
+      tx_hash: entry.tx_hash,
+    })
+  }
+
+  // Verification services ask for the full version, including the commit
+  // of the build. Unless configured, it's asked for from the solc release
+  // the tracked bytecode says it's been compiled with, if installed, or else
+  // from the configured compiler.
+  fn compiler_version(&self, verification_config: &ProjectVerificationConfig, entry: &SmartContractTrackingDataEntry, compiler_cmd: &str) -> Result<String, VerificationError> {
+    if let Some(version) = &verification_config.compiler_version {
+      return Ok(full_version(version));
+    }
+    let installer = SolcInstaller::for_project(self.config);
+    let compiler_cmd = entry.compiler_version.as_ref()
+      .and_then(|version| version.parse::<Version>().ok())
+      .map(|version| installer.executable(version))
+      .filter(|executable| executable.exists())
+      .map(|executable| executable.to_string_lossy().to_string())
+      .unwrap_or_else(|| compiler_cmd.to_string());
+
+    artifacts::compiler_version(&compiler_cmd, &self.config.vibranium_dir_path)
+      .map(|version| full_version(&version))
+      .ok_or_else(|| VerificationError::UnknownCompilerVersion(entry.name.to_owned()))
+  }
+}
+
+fn etherscan_url(config: &ProjectVerificationConfig) -> Result<String, VerificationError> {
+  resolve(config.etherscan_url.as_deref().unwrap_or(etherscan::DEFAULT_URL))
+}
+
+fn etherscan_api_key(config: &ProjectVerificationConfig) -> Result<String, VerificationError> {
+  let api_key = config.etherscan_api_key.as_ref().ok_or_else(|| VerificationError::MissingApiKey(VerificationService::Etherscan.to_string()))?;
+  resolve(api_key)
+}
+
+fn sourcify_url(config: &ProjectVerificationConfig) -> Result<String, VerificationError> {
+  resolve(config.sourcify_url.as_deref().unwrap_or(sourcify::DEFAULT_URL))
+}
+
+fn resolve(value: &str) -> Result<String, VerificationError> {
+  secrets::resolve_env_references(value).map_err(VerificationError::MissingEnvVariable)
+}
+
+// `0.8.24+commit.e11b9ed9.Linux.g++` as printed by solc becomes
+// `0.8.24+commit.e11b9ed9`.
+fn full_version(version: &str) -> String {
+  let version = version.trim().trim_start_matches('v');
+  match version.find(COMMIT_PREFIX) {
+    Some(index) => {
+      let commit: String = version[index + COMMIT_PREFIX.len()..].chars().take_while(char::is_ascii_hexdigit).collect();
+      format!("{}{}{}", &version[..index], COMMIT_PREFIX, commit)
+    },
+    None => version.to_string(),
+  }
+}
+
+pub(crate) fn source_name(path: &Path) -> String {
+  path.components().map(|component| component.as_os_str().to_string_lossy().to_string()).collect::<Vec<String>>().join("/")
+}
+
+// Sources are named by their path relative to the project, as solc names
+// the configured source files. Imports are followed, so that sources outside
+// of `sources.smart_contracts`, e.g. in node_modules, are included as well.
+fn collect_sources(project_path: &Path, source_files: &[String]) -> Result<BTreeMap<String, String>, VerificationError> {
+  let mut sources = BTreeMap::new();
+  let mut pending: Vec<String> = source_files.to_vec();
+
+  while let Some(name) = pending.pop() {
+    if sources.contains_key(&name) {
+      continue;
+    }
+    let path = source_path(project_path, &name).ok_or_else(|| VerificationError::MissingSource(name.to_owned()))?;
+    let content = fs::read_to_string(path)?;
+
+    for import in imports(&content) {
+      pending.push(resolve_import(&name, &import));
+    }
+    sources.insert(name, content);
+  }
+  Ok(sources)
+}
+
+// The source name an `import` of the source `name` refers to.
+pub(crate) fn resolve_import(name: &str, import: &str) -> String {
+  if import.starts_with("./") || import.starts_with("../") {
+    let directory = name.rsplit_once('/').map(|(directory, _file)| directory).unwrap_or("");
+    normalize(&format!("{}/{}", directory, import))
+  } else {
+    import.to_string()
+  }
+}
+
+// Sources that aren't part of the project are looked up in node_modules.
+pub(crate) fn source_path(project_path: &Path, name: &str) -> Option<PathBuf> {
+  [project_path.join(name), project_path.join(NODE_MODULES_DIRECTORY).join(name)]
+    .iter()
+    .find(|path| path.exists())
+    .cloned()
+}
+
+// Paths of the `import` directives of a Solidity source.
+pub(crate) fn imports(source: &str) -> Vec<String> {
+  let code = compiler::checks::strip_comments_and_strings(source);
+  // Stripping keeps the amount of characters, but not necessarily of bytes.
+  let chars: Vec<char> = source.chars().collect();
+  let mut imports = vec![];
+  let mut offset = 0;
+
+  while let Some(index) = code[offset..].find("import") {
+    let start = offset + index;
+    offset = start + "import".len();
+    if !compiler::checks::is_word_at(&code, start, "import") {
+      continue;
+    }
+    let end = code[offset..].find(';').map(|index| offset + index).unwrap_or_else(|| code.len());
+    if let Some(quote_index) = code[offset..end].find(['"', '\'']) {
+      let quote = code[offset + quote_index..].chars().next().unwrap();
+      let literal_start = code[..offset + quote_index].chars().count() + 1;
+      imports.push(chars[literal_start..].iter().take_while(|c| **c != quote).collect());
+    }
+  }
+  imports
+}
+
+pub(crate) fn normalize(path: &str) -> String {
+  let mut components: Vec<&str> = vec![];
+  for component in path.split('/') {
+    match component {
+      "" | "." => {},
+      ".." => { components.pop(); },
+      component => components.push(component),
+    }
+  }
+  components.join("/")
+}
+
+fn standard_json_input(sources: &BTreeMap<String, String>, compiler_options: &[String]) -> serde_json::Value {
+  let settings = CompilerSettings::from_options(compiler_options);
+  let sources: serde_json::Map<String, serde_json::Value> = sources.iter()
+    .map(|(name, content)| (name.to_owned(), serde_json::json!({ "content": content })))
+    .collect();
+
+  let mut input = serde_json::json!({
+    "language": "Solidity",
+    "sources": sources,
+    "settings": {
+      "optimizer": { "enabled": settings.optimizer.enabled, "runs": settings.optimizer.runs },
+      "outputSelection": { "*": { "*": ["abi", "evm.bytecode", "evm.deployedBytecode"] } },
+    },
+  });
+  if let Some(evm_version) = compiler_options.iter().position(|option| option == "--evm-version").and_then(|index| compiler_options.get(index + 1)) {
+    input["settings"]["evmVersion"] = serde_json::json!(evm_version);
+  }
+  if compiler_options.iter().any(|option| option == "--via-ir") {
+    input["settings"]["viaIR"] = serde_json::json!(true);
+  }
+  input
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::{collect_sources, full_version, standard_json_input, SourceVerificationStatus, SourceVerifier, VerificationService};
+  use super::error::VerificationError;
+  use super::http::HttpClient;
+  use crate::config::Config;
+  use crate::deployment::tracker::DeploymentTracker;
+  use crate::testing::MockTransport;
+  use std::cell::RefCell;
+  use std::collections::VecDeque;
+  use std::fs;
+  use std::str::FromStr;
+  use web3::types::{Address, Block, H256};
+
+  const BLOCK_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+
+  #[derive(Default)]
+  struct MockClient {
+    responses: RefCell<VecDeque<String>>,
+    requests: RefCell<Vec<(String, String)>>,
+  }
+
+  impl MockClient {
+    fn respond(&self, response: &str) {
+      self.responses.borrow_mut().push_back(response.to_string());
+    }
+
+    fn respond_to(&self, url: &str) -> Result<String, VerificationError> {
+      self.responses.borrow_mut().pop_front().ok_or_else(|| VerificationError::Request(format!("Unexpected request to {}", url)))
+    }
+  }
+
+  impl HttpClient for MockClient {
+    fn get(&self, url: &str) -> Result<String, VerificationError> {
+      self.requests.borrow_mut().push((url.to_string(), String::new()));
+      self.respond_to(url)
+    }
+
+    fn post(&self, url: &str, _content_type: &str, body: &str) -> Result<String, VerificationError> {
+      self.requests.borrow_mut().push((url.to_string(), body.to_string()));
+      self.respond_to(url)
+    }
+  }
+
+  fn setup_project() -> tempfile::TempDir {
+    let tmp_dir = crate::testing::project_with_config("
+      [sources]
+        artifacts = \"artifacts\"
+        smart_contracts = [\"contracts/*.sol\"]
+      [compiler]
+        cmd = \"solc\"
+        options = [\"--bin\", \"--abi\", \"--optimize\", \"--optimize-runs\", \"1000\"]
+      [verification]
+        etherscan_api_key = \"secret\"
+        compiler_version = \"v0.8.24+commit.e11b9ed9.Linux.g++\"
+        poll_interval = 0
+    ");
+    fs::create_dir_all(tmp_dir.path().join("contracts").join("lib")).unwrap();
+    fs::create_dir_all(tmp_dir.path().join("node_modules").join("@oz")).unwrap();
+    fs::write(tmp_dir.path().join("contracts").join("Token.sol"), "import \"./lib/Math.sol\";\n// import \"./Missing.sol\";\ncontract Token is Math {}").unwrap();
+    fs::write(tmp_dir.path().join("contracts").join("lib").join("Math.sol"), "import {Ownable} from '../../node_modules/@oz/Ownable.sol';\nimport \"@oz/Ownable.sol\";\nlibrary Math {}").unwrap();
+    fs::write(tmp_dir.path().join("node_modules").join("@oz").join("Ownable.sol"), "contract Ownable {}").unwrap();
+    tmp_dir
+  }
+
+  fn track_token(config: &Config) {
+    let tracker = DeploymentTracker::new(config);
+    tracker.create_database().unwrap();
+    tracker.track(H256::from_str(&BLOCK_HASH[2..]).unwrap(), "Token".to_string(), "6080".to_string(), &vec![], Address::from_low_u64_be(1), None).unwrap();
+  }
+
+  fn transport() -> MockTransport {
+    let transport = MockTransport::new();
+    transport.set_default_response("eth_getBlockByNumber", serde_json::to_value(Block::<H256> {
+      hash: Some(H256::from_str(&BLOCK_HASH[2..]).unwrap()),
+      ..Block::default()
+    }).unwrap());
+    transport.set_default_response("eth_chainId", serde_json::json!("0xaa36a7"));
+    transport
+  }
+
+  #[test]
+  fn it_should_collect_imported_sources() {
+    let tmp_dir = setup_project();
+    let sources = collect_sources(tmp_dir.path(), &["contracts/Token.sol".to_string()]).unwrap();
+    assert_eq!(sources.keys().collect::<Vec<&String>>(), vec!["@oz/Ownable.sol", "contracts/Token.sol", "contracts/lib/Math.sol", "node_modules/@oz/Ownable.sol"]);
+
+    let input = standard_json_input(&sources, &["--optimize".to_string(), "--evm-version".to_string(), "paris".to_string()]);
+    assert_eq!(input["settings"]["optimizer"], serde_json::json!({ "enabled": true, "runs": 200 }));
+    assert_eq!(input["settings"]["evmVersion"], serde_json::json!("paris"));
+    assert_eq!(full_version("0.8.24+commit.e11b9ed9.Linux.g++"), "0.8.24+commit.e11b9ed9");
+  }
+
+  #[test]
+  fn it_should_poll_etherscan_until_sources_are_verified() {
+    let tmp_dir = setup_project();
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    track_token(&config);
+    let transport = transport();
+    let client = MockClient::default();
+    client.respond(r#"{ "status": "1", "message": "OK", "result": "guid-1" }"#);
+    client.respond(r#"{ "status": "0", "message": "NOTOK", "result": "Pending in queue" }"#);
+    client.respond(r#"{ "status": "1", "message": "OK", "result": "Pass - Verified" }"#);
+
+    let connector = transport.connector();
+    let verification = SourceVerifier::new(&config, &connector, &client).verify("Token", VerificationService::Etherscan).unwrap();
+    assert_eq!(verification.status, SourceVerificationStatus::Verified);
+
+    let requests = client.requests.borrow();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].0, "https://api.etherscan.io/v2/api?chainid=11155111");
+    assert!(requests[0].1.contains("action=verifysourcecode"));
+    assert!(requests[0].1.contains("contractname=contracts%2FToken.sol%3AToken"));
+    assert!(requests[0].1.contains("compilerversion=v0.8.24%2Bcommit.e11b9ed9&"));
+    assert!(requests[2].1.contains("guid=guid-1"));
+  }
+
+  #[test]
+  fn it_should_report_sources_rejected_by_sourcify() {
+    let tmp_dir = setup_project();
+    let config = Config::new(tmp_dir.path().to_path_buf());
+    track_token(&config);
+    let transport = transport();
+    let client = MockClient::default();
+    client.respond(r#"{ "verificationId": "job-1" }"#);
+    client.respond(r#"{ "isJobCompleted": true, "error": { "customCode": "no_match", "message": "The onchain and recompiled bytecodes don't match." } }"#);
+
+    let connector = transport.connector();
+    match SourceVerifier::new(&config, &connector, &client).verify("Token", VerificationService::Sourcify) {
+      Err(VerificationError::Rejected(name, service, reason)) => {
+        assert_eq!((name.as_str(), service.as_str()), ("Token", "Sourcify"));
+        assert!(reason.contains("don't match"));
+      },
+      other => panic!("Expected verification to be rejected, got {:?}", other),
+    }
+    assert_eq!(client.requests.borrow()[0].0, "https://sourcify.dev/server/v2/verify/11155111/0x0000000000000000000000000000000000000001");
+    assert_eq!(client.requests.borrow()[1].0, "https://sourcify.dev/server/v2/verify/job-1");
+  }
+}
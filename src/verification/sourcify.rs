@@ -0,0 +1,55 @@
+// Sourcify's v2 API. Sources are submitted as standard JSON input, which
+// starts a verification job to poll.
+
+use super::error::VerificationError;
+use super::http::{self, HttpClient};
+use super::{Progress, Submission, VerificationService};
+
+pub const DEFAULT_URL: &str = "https://sourcify.dev/server";
+const ALREADY_VERIFIED: &str = "already_verified";
+
+pub fn submit(client: &dyn HttpClient, url: &str, submission: &Submission) -> Result<Progress, VerificationError> {
+  let mut body = serde_json::json!({
+    "stdJsonInput": submission.input,
+    "compilerVersion": submission.compiler_version,
+    "contractIdentifier": submission.contract_identifier(),
+  });
+  if let Some(tx_hash) = submission.tx_hash {
+    body["creationTransactionHash"] = serde_json::json!(tx_hash);
+  }
+
+  let endpoint = format!("{}/v2/verify/{}/{:?}", url.trim_end_matches('/'), submission.chain_id, submission.address);
+  let response = parse(&client.post(&endpoint, http::CONTENT_TYPE_JSON, &body.to_string())?)?;
+
+  match response["verificationId"].as_str() {
+    Some(verification_id) => Ok(Progress::Pending(verification_id.to_string())),
+    None => rejected(submission, &response),
+  }
+}
+
+pub fn check(client: &dyn HttpClient, url: &str, submission: &Submission, verification_id: &str) -> Result<Progress, VerificationError> {
+  let response = parse(&client.get(&format!("{}/v2/verify/{}", url.trim_end_matches('/'), verification_id))?)?;
+
+  if !response["isJobCompleted"].as_bool().unwrap_or(false) {
+    return Ok(Progress::Pending(verification_id.to_owned()));
+  }
+  if response["contract"]["match"].is_string() {
+    return Ok(Progress::Verified);
+  }
+  rejected(submission, &response["error"])
+}
+
+fn parse(response: &str) -> Result<serde_json::Value, VerificationError> {
+  serde_json::from_str(response).map_err(|_err| VerificationError::InvalidResponse(VerificationService::Sourcify.to_string(), response.trim().to_string()))
+}
+
+// Errors come as `{ "customCode": "...", "message": "..." }`.
+fn rejected(submission: &Submission, error: &serde_json::Value) -> Result<Progress, VerificationError> {
+  if error["customCode"].as_str() == Some(ALREADY_VERIFIED) {
+    return Ok(Progress::AlreadyVerified);
+  }
+  match error["message"].as_str() {
+    Some(message) => Err(VerificationError::Rejected(submission.name.to_owned(), VerificationService::Sourcify.to_string(), message.to_string())),
+    None => Err(VerificationError::InvalidResponse(VerificationService::Sourcify.to_string(), error.to_string())),
+  }
+}